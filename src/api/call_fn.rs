@@ -23,6 +23,19 @@ pub struct CallFnOptions<'t> {
     pub eval_ast: bool,
     /// Rewind the [`Scope`] after the function call? Default `true`.
     pub rewind_scope: bool,
+    /// The time at which this evaluation run times out (if any), overrides the time limit set via
+    /// [`Engine::set_max_execution_time`]. Default [`None`].
+    ///
+    /// Not available under `unchecked` or `no_time`.
+    #[cfg(not(feature = "unchecked"))]
+    #[cfg(not(feature = "no_time"))]
+    pub deadline: Option<crate::Instant>,
+    /// Handle to cancel this evaluation run (if any), in addition to
+    /// [`Engine::cancellation_handle`]. Default [`None`].
+    ///
+    /// Not available under `unchecked`.
+    #[cfg(not(feature = "unchecked"))]
+    pub cancellation: Option<crate::CancellationHandle>,
 }
 
 impl Default for CallFnOptions<'_> {
@@ -42,6 +55,11 @@ impl<'a> CallFnOptions<'a> {
             tag: None,
             eval_ast: true,
             rewind_scope: true,
+            #[cfg(not(feature = "unchecked"))]
+            #[cfg(not(feature = "no_time"))]
+            deadline: None,
+            #[cfg(not(feature = "unchecked"))]
+            cancellation: None,
         }
     }
     /// Bind to the `this` pointer.
@@ -72,6 +90,27 @@ impl<'a> CallFnOptions<'a> {
         self.rewind_scope = value;
         self
     }
+    /// Set the time at which this evaluation run times out.
+    ///
+    /// Not available under `unchecked` or `no_time`.
+    #[cfg(not(feature = "unchecked"))]
+    #[cfg(not(feature = "no_time"))]
+    #[inline(always)]
+    #[must_use]
+    pub const fn with_deadline(mut self, value: crate::Instant) -> Self {
+        self.deadline = Some(value);
+        self
+    }
+    /// Set a handle to cancel this evaluation run.
+    ///
+    /// Not available under `unchecked`.
+    #[cfg(not(feature = "unchecked"))]
+    #[inline(always)]
+    #[must_use]
+    pub fn with_cancellation(mut self, value: crate::CancellationHandle) -> Self {
+        self.cancellation = Some(value);
+        self
+    }
 }
 
 impl Engine {
@@ -223,6 +262,13 @@ impl Engine {
 
         let orig_tag = options.tag.map(|v| mem::replace(&mut global.tag, v));
 
+        #[cfg(not(feature = "unchecked"))]
+        #[cfg(not(feature = "no_time"))]
+        let orig_deadline = options.deadline.map(|v| global.deadline.replace(v));
+
+        #[cfg(not(feature = "unchecked"))]
+        let orig_cancellation = options.cancellation.map(|v| global.cancellation.replace(v));
+
         let mut this_ptr = options.this_ptr;
 
         #[cfg(not(feature = "no_module"))]
@@ -237,6 +283,11 @@ impl Engine {
                 g.embedded_module_resolver = orig_embedded_module_resolver;
             }
             if let Some(orig_tag) = orig_tag { g.tag = orig_tag; }
+            #[cfg(not(feature = "unchecked"))]
+            #[cfg(not(feature = "no_time"))]
+            if let Some(orig_deadline) = orig_deadline { g.deadline = orig_deadline; }
+            #[cfg(not(feature = "unchecked"))]
+            if let Some(orig_cancellation) = orig_cancellation { g.cancellation = orig_cancellation; }
             g.lib.truncate(orig_lib_len);
             g.source = orig_source;
         }}
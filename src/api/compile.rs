@@ -70,6 +70,67 @@ impl Engine {
     pub fn compile_with_scope(&self, scope: &Scope, script: impl AsRef<str>) -> ParseResult<AST> {
         self.compile_scripts_with_scope(scope, &[script])
     }
+    /// Compile a string into an [`AST`], collecting all syntax errors instead of stopping at the
+    /// first one.
+    ///
+    /// After a syntax error, parsing resumes at the next statement boundary (i.e. after a `;` or
+    /// a `}`). The erroneous statements are skipped, so the resultant [`AST`] is partial if any
+    /// error is found.
+    ///
+    /// This is useful for tools such as editors, which need to report all the errors in a script
+    /// at once.
+    ///
+    /// Returns the [`AST`] together with the list of errors, which is empty if the script
+    /// compiles successfully.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::Engine;
+    ///
+    /// let engine = Engine::new();
+    ///
+    /// let (ast, errors) = engine.compile_with_diagnostics(
+    ///     "
+    ///         let x = 40 +;
+    ///         let y = 2;
+    ///         x +* y;
+    ///         y
+    ///     ",
+    /// );
+    ///
+    /// assert_eq!(errors.len(), 2);
+    ///
+    /// // The valid statements are still parsed
+    /// assert_eq!(engine.eval_ast::<i64>(&ast)?, 2);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn compile_with_diagnostics(
+        &self,
+        script: impl AsRef<str>,
+    ) -> (AST, Vec<crate::ParseError>) {
+        let scripts = [script];
+        let (stream, tc) = self.lex(&scripts);
+
+        let input = &mut stream.peekable();
+        let lib = &mut <_>::default();
+        let state = ParseState::new(None, input, tc.clone(), lib);
+        let (mut _ast, errors) = self.parse_with_recovery(
+            state,
+            #[cfg(not(feature = "no_optimize"))]
+            self.optimization_level,
+        );
+        #[cfg(feature = "metadata")]
+        {
+            let global_comments = &tc.borrow().global_comments;
+            _ast.doc = global_comments.into();
+        }
+        (_ast, errors)
+    }
     /// Compile a string into an [`AST`] using own scope, which can be used later for evaluation,
     /// embedding all imported modules.
     ///
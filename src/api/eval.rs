@@ -2,6 +2,7 @@
 
 use crate::eval::{Caches, GlobalRuntimeState};
 use crate::parser::ParseState;
+use crate::types::continuation::ResumeFrame;
 use crate::types::dynamic::Variant;
 use crate::{Dynamic, Engine, Position, RhaiResult, RhaiResultOf, Scope, AST, ERR};
 #[cfg(feature = "no_std")]
@@ -213,13 +214,27 @@ impl Engine {
         })
     }
     /// Evaluate an [`AST`] with own scope, returning the result value or an error.
-    #[inline]
+    #[inline(always)]
     pub(crate) fn eval_ast_with_scope_raw(
         &self,
         global: &mut GlobalRuntimeState,
         caches: &mut Caches,
         scope: &mut Scope,
         ast: &AST,
+    ) -> RhaiResult {
+        self.resume_ast_with_scope_raw(global, caches, scope, ast, None)
+    }
+    /// Evaluate an [`AST`] with own scope, resuming a suspended evaluation at the frames recorded
+    /// in a [`Continuation`][crate::Continuation] with the output of the suspending function call
+    /// (if any), returning the result value or an error.
+    #[inline]
+    pub(crate) fn resume_ast_with_scope_raw(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        scope: &mut Scope,
+        ast: &AST,
+        resume: Option<(&mut [ResumeFrame], RhaiResult)>,
     ) -> RhaiResult {
         let orig_source = mem::replace(&mut global.source, ast.source_raw().cloned());
 
@@ -245,7 +260,14 @@ impl Engine {
             g.source = orig_source;
         }}
 
-        let r = self.eval_global_statements(global, caches, scope, ast.statements(), true)?;
+        let statements = ast.statements();
+
+        let r = match resume {
+            Some((frames, output)) => {
+                self.resume_global_statements(global, caches, scope, statements, frames, output)?
+            }
+            None => self.eval_global_statements(global, caches, scope, statements, true)?,
+        };
 
         #[cfg(feature = "debugging")]
         if self.is_debugger_registered() {
@@ -0,0 +1,220 @@
+//! Module that defines the asynchronous evaluation API of [`Engine`].
+
+use crate::eval::{Caches, GlobalRuntimeState};
+use crate::func::async_fn::{AsyncCall, AsyncFn, RhaiNativeAsyncFunc};
+use crate::func::SendSync;
+use crate::types::dynamic::Variant;
+use crate::{
+    Dynamic, Engine, FuncRegistration, Identifier, Position, RhaiResult, RhaiResultOf, Scope, AST,
+    ERR,
+};
+use std::any::{type_name, TypeId};
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+
+#[cfg(not(feature = "no_function"))]
+use crate::{FuncArgs, StaticVec};
+
+impl Engine {
+    /// Register a custom asynchronous function with the [`Engine`].
+    ///
+    /// The function takes all its parameters by value and returns a
+    /// [`Future`][std::future::Future], which may output either a value or a `Result`. Under the
+    /// `sync` feature, the `Future` must be `Send + Sync`.
+    ///
+    /// Asynchronous functions can only be called within an evaluation started by
+    /// [`eval_async`][Engine::eval_async] or [`call_fn_async`][Engine::call_fn_async].
+    /// Calling one anywhere else returns [`ErrorSystem`][crate::EvalAltResult::ErrorSystem].
+    ///
+    /// A call to an asynchronous function suspends the evaluation, so it must be the whole value
+    /// of a statement, e.g. `let x = fetch(42);` (see [`Continuation`][crate::Continuation] for
+    /// details). Calling one anywhere else, e.g. `fetch(42) + 1`, returns
+    /// [`ErrorSystem`][crate::EvalAltResult::ErrorSystem].
+    ///
+    /// # Assumptions
+    ///
+    /// * **Accessibility**: The function namespace is [`FnNamespace::Global`][`crate::FnNamespace::Global`].
+    ///
+    /// * **Volatility**: The function is assumed to be _volatile_ -- i.e. it does not guarantee the same result for the same input(s).
+    #[inline]
+    pub fn register_async_fn<A: 'static, const N: usize, R: Variant + Clone, const F: bool>(
+        &mut self,
+        name: impl AsRef<str> + Into<Identifier>,
+        func: impl RhaiNativeAsyncFunc<A, N, R, F> + SendSync + 'static,
+    ) -> &mut Self {
+        FuncRegistration::new(name.into())
+            .with_volatility(true)
+            .register_into_engine(self, AsyncFn::<_, F>(func));
+
+        self
+    }
+    /// Evaluate a string as a script asynchronously, returning a
+    /// [`Future`][std::future::Future] that resolves to the result value or an error.
+    ///
+    /// The script is run within the returned `Future` when it is polled. Whenever the script
+    /// calls a function registered via [`register_async_fn`][Engine::register_async_fn], the
+    /// evaluation is suspended, and the returned `Future` polls the function's `Future`
+    /// instead. When that completes, the evaluation is resumed with its output.
+    ///
+    /// No thread is started. Between calls to asynchronous functions, the script runs on the
+    /// thread polling the returned `Future` without yielding, so a long-running script blocks
+    /// the executor; use [`on_progress`][Engine::on_progress] or limits such as
+    /// [`max_operations`][Engine::max_operations] to bound it. These apply to the evaluation as a
+    /// whole, as in a normal evaluation, and so does
+    /// [`max_execution_time`][Engine::max_execution_time], including the time spent awaiting
+    /// asynchronous functions.
+    ///
+    /// Dropping the returned `Future` abandons the evaluation, together with the `Future` of
+    /// any pending asynchronous function call.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use std::{future::Future, sync::Arc, task::*, thread};
+    /// # struct Unparker(thread::Thread);
+    /// # impl Wake for Unparker { fn wake(self: Arc<Self>) { self.0.unpark(); } }
+    /// # fn block_on<F: Future>(future: F) -> F::Output {
+    /// #     let mut future = Box::pin(future);
+    /// #     let waker = Arc::new(Unparker(thread::current())).into();
+    /// #     let mut cx = Context::from_waker(&waker);
+    /// #     loop {
+    /// #         match future.as_mut().poll(&mut cx) {
+    /// #             Poll::Ready(output) => return output,
+    /// #             Poll::Pending => thread::park(),
+    /// #         }
+    /// #     }
+    /// # }
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::Engine;
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// engine.register_async_fn("fetch", |x: i64| async move { x * 2 });
+    ///
+    /// let result = block_on(engine.eval_async::<i64>("let x = fetch(20); x + 2"))?;
+    ///
+    /// assert_eq!(result, 42);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub async fn eval_async<T: Variant + Clone>(&self, script: &str) -> RhaiResultOf<T> {
+        self.eval_with_scope_async(&mut Scope::new(), script).await
+    }
+    /// Evaluate a string as a script asynchronously with own scope, returning a
+    /// [`Future`][std::future::Future] that resolves to the result value or an error.
+    ///
+    /// See [`eval_async`][Engine::eval_async] for details.
+    pub async fn eval_with_scope_async<T: Variant + Clone>(
+        &self,
+        scope: &mut Scope<'_>,
+        script: &str,
+    ) -> RhaiResultOf<T> {
+        let ast = self.compile_scripts_with_scope_raw(
+            Some(scope),
+            [script],
+            #[cfg(not(feature = "no_optimize"))]
+            self.optimization_level,
+        )?;
+
+        let result = {
+            let global = &mut self.new_async_global_runtime_state();
+            self.eval_ast_with_scope_raw(global, &mut Caches::new(), scope, &ast)
+        };
+
+        let result = self.run_async(scope, &ast, result).await?;
+        self.cast_async_result(result)
+    }
+    /// Call a script function defined in an [`AST`] asynchronously, returning a
+    /// [`Future`][std::future::Future] that resolves to the result value or an error.
+    ///
+    /// See [`eval_async`][Engine::eval_async] for details.
+    ///
+    /// Not available under `no_function`.
+    #[cfg(not(feature = "no_function"))]
+    pub async fn call_fn_async<T: Variant + Clone>(
+        &self,
+        scope: &mut Scope<'_>,
+        ast: &AST,
+        name: impl AsRef<str>,
+        args: impl FuncArgs,
+    ) -> RhaiResultOf<T> {
+        let mut arg_values = StaticVec::new_const();
+        args.parse(&mut arg_values);
+
+        let result = {
+            let global = &mut self.new_async_global_runtime_state();
+            let caches = &mut Caches::new();
+            let name = name.as_ref();
+            let args = arg_values.as_mut();
+
+            self._call_fn(<_>::default(), scope, ast, name, args, global, caches)
+        };
+
+        let result = self.run_async(scope, ast, result).await?;
+        self.cast_async_result(result)
+    }
+    /// Drive an asynchronous evaluation of an [`AST`] to completion, given the result of its first
+    /// run.
+    ///
+    /// Each time the evaluation is suspended by an asynchronous function call, the call's
+    /// [`Future`][std::future::Future] is awaited and the evaluation is resumed with its output.
+    async fn run_async(
+        &self,
+        scope: &mut Scope<'_>,
+        ast: &AST,
+        mut result: RhaiResult,
+    ) -> RhaiResult {
+        loop {
+            let continuation = match result {
+                Ok(r) => return Ok(r),
+                Err(err) => err.into_continuation()?,
+            };
+
+            let Some(future) = continuation
+                .value()
+                .read_lock::<AsyncCall>()
+                .and_then(|call| call.take())
+            else {
+                // Not suspended by an asynchronous function
+                return Err(
+                    ERR::ErrorTerminated(Dynamic::from(continuation), Position::NONE).into(),
+                );
+            };
+
+            let output = future.await;
+
+            let global = &mut self.new_async_global_runtime_state();
+            result = self.resume_raw(global, scope, ast, continuation, output);
+        }
+    }
+    /// Create a new [`GlobalRuntimeState`] for an asynchronous evaluation.
+    #[inline]
+    #[must_use]
+    fn new_async_global_runtime_state(&self) -> GlobalRuntimeState {
+        let mut global = self.new_global_runtime_state();
+        global.is_async = true;
+        global
+    }
+    /// Cast the result of an asynchronous evaluation to the output type.
+    fn cast_async_result<T: Variant + Clone>(&self, result: Dynamic) -> RhaiResultOf<T> {
+        // Bail out early if the return type needs no cast
+        if TypeId::of::<T>() == TypeId::of::<Dynamic>() {
+            return Ok(reify! { result => T });
+        }
+
+        result.try_cast_result::<T>().map_err(|v| {
+            let typename = match type_name::<T>() {
+                typ if typ.contains("::") => self.map_type_name(typ),
+                typ => typ,
+            };
+
+            ERR::ErrorMismatchOutputType(
+                typename.into(),
+                self.map_type_name(v.type_name()).into(),
+                Position::NONE,
+            )
+            .into()
+        })
+    }
+}
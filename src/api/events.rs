@@ -325,6 +325,66 @@ impl Engine {
         self.debug = Some(Box::new(callback));
         self
     }
+    /// Register a callback to receive warnings raised during parsing, such as a `switch` statement
+    /// that does not cover all the variants of an `enum`.
+    ///
+    /// Warnings do not stop compilation.
+    ///
+    /// # Callback Function Signature
+    ///
+    /// The callback function signature passed takes the following form:
+    ///
+    /// `Fn(message: &str, pos: Position)`
+    ///
+    /// where:
+    /// * `message`: the warning message
+    /// * [`pos`][`Position`]: location of the construct that triggered the warning
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// # #[cfg(not(feature = "no_function"))]
+    /// # {
+    /// # use std::sync::RwLock;
+    /// # use std::sync::Arc;
+    /// use rhai::Engine;
+    ///
+    /// let warnings = Arc::new(RwLock::new(Vec::<String>::new()));
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// let logger = warnings.clone();
+    /// engine.on_parse_warning(move |msg, _| logger.write().unwrap().push(msg.to_string()));
+    ///
+    /// engine.compile(
+    ///     "
+    ///         enum Shape { Circle(r), Square(s) }
+    ///
+    ///         fn area(shape) {
+    ///             switch shape {
+    ///                 Shape::Circle(r) => r * r * 3,
+    ///             }
+    ///         }
+    ///     ",
+    /// )?;
+    ///
+    /// assert_eq!(
+    ///     *warnings.read().unwrap(),
+    ///     ["Non-exhaustive switch: missing Shape::Square"]
+    /// );
+    /// # }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline(always)]
+    pub fn on_parse_warning(
+        &mut self,
+        callback: impl Fn(&str, Position) + SendSync + 'static,
+    ) -> &mut Self {
+        self.parse_warning = Some(Box::new(callback));
+        self
+    }
     /// _(internals)_ Register a callback for access to [`Map`][crate::Map] properties that do not exist.
     /// Exported under the `internals` feature only.
     ///
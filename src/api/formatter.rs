@@ -0,0 +1,1010 @@
+//! Module that provides a source-preserving code formatter for Rhai scripts.
+#![cfg(not(feature = "no_position"))]
+
+use crate::parser::{ParseResult, ParseState};
+use crate::tokenizer::{is_valid_identifier, Token};
+use crate::{Engine, Position};
+use std::iter::{once, repeat};
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+
+/// Options for formatting a script via [`Engine::format_script_with_options`].
+///
+/// Not available under `no_position`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub struct FormatOptions {
+    /// Maximum number of characters in a line. Default `100`.
+    ///
+    /// Lines that cannot be broken up (e.g. long strings) may still exceed this width.
+    pub max_width: usize,
+    /// Number of spaces for each level of indentation. Default `4`.
+    pub indent: usize,
+}
+
+impl Default for FormatOptions {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FormatOptions {
+    /// Create a default [`FormatOptions`].
+    #[inline(always)]
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            max_width: 100,
+            indent: 4,
+        }
+    }
+    /// Set the maximum number of characters in a line.
+    #[inline(always)]
+    #[must_use]
+    pub const fn max_width(mut self, value: usize) -> Self {
+        self.max_width = value;
+        self
+    }
+    /// Set the number of spaces for each level of indentation.
+    #[inline(always)]
+    #[must_use]
+    pub const fn indent(mut self, value: usize) -> Self {
+        self.indent = value;
+        self
+    }
+}
+
+/// A token together with its original text.
+struct Lexeme<'a> {
+    /// The token.
+    token: Token,
+    /// Original text of the token.
+    text: &'a str,
+    /// Number of line breaks between the previous token and this token.
+    newlines: usize,
+}
+
+impl Lexeme<'_> {
+    /// Is this a comment?
+    #[inline(always)]
+    #[must_use]
+    const fn is_comment(&self) -> bool {
+        matches!(self.token, Token::Comment(..))
+    }
+    /// Is this a comment that must be followed by a line break?
+    #[inline]
+    #[must_use]
+    fn needs_line_break(&self) -> bool {
+        self.is_comment() && (self.text.starts_with("//") || self.text.contains('\n'))
+    }
+}
+
+/// A token, or a group of tokens enclosed within brackets.
+enum Node<'a> {
+    /// A single token.
+    Token(Lexeme<'a>),
+    /// A group of tokens within `()`, `[]`, `?[]`, `{}` or `#{}`.
+    Group(Box<Group<'a>>),
+}
+
+/// A group of tokens enclosed within brackets.
+struct Group<'a> {
+    /// The opening bracket.
+    open: Lexeme<'a>,
+    /// Tokens within the brackets.
+    nodes: Vec<Node<'a>>,
+    /// The closing bracket, if any.
+    close: Option<Lexeme<'a>>,
+}
+
+impl<'a> Node<'a> {
+    /// Get the first token of this node.
+    #[inline]
+    #[must_use]
+    fn first(&self) -> &Lexeme<'a> {
+        match self {
+            Self::Token(lexeme) => lexeme,
+            Self::Group(group) => &group.open,
+        }
+    }
+    /// Get the last token of this node.
+    #[inline]
+    #[must_use]
+    fn last(&self) -> &Lexeme<'a> {
+        match self {
+            Self::Token(lexeme) => lexeme,
+            Self::Group(group) => group.close.as_ref().unwrap_or(&group.open),
+        }
+    }
+    /// Is this node a specific token?
+    #[inline]
+    #[must_use]
+    fn is(&self, token: &Token) -> bool {
+        matches!(self, Self::Token(lexeme) if lexeme.token == *token)
+    }
+    /// Is this node a comment?
+    #[inline]
+    #[must_use]
+    fn is_comment(&self) -> bool {
+        matches!(self, Self::Token(lexeme) if lexeme.is_comment())
+    }
+    /// Is this node a statements block?
+    #[inline]
+    #[must_use]
+    fn is_block(&self) -> bool {
+        matches!(self, Self::Group(group) if group.open.token == Token::LeftBrace)
+    }
+    /// Is this node written in a single line without any comments?
+    #[must_use]
+    fn is_single_line(&self) -> bool {
+        match self {
+            Self::Token(lexeme) => !lexeme.is_comment() && !lexeme.text.contains('\n'),
+            Self::Group(group) => {
+                group
+                    .nodes
+                    .iter()
+                    .all(|node| node.first().newlines == 0 && node.is_single_line())
+                    && group
+                        .close
+                        .as_ref()
+                        .map_or(true, |close| close.newlines == 0)
+            }
+        }
+    }
+}
+
+impl Group<'_> {
+    /// Is this group a single statement written in one line without any comments?
+    #[must_use]
+    fn is_single_statement(&self) -> bool {
+        self.nodes.iter().all(|node| {
+            node.first().newlines == 0 && node.is_single_line() && !node.is(&Token::SemiColon)
+        }) && self
+            .close
+            .as_ref()
+            .map_or(false, |close| close.newlines == 0)
+            && split_statements(&self.nodes).len() == 1
+    }
+}
+
+/// Tokenize a script, keeping comments and the original text of each token.
+///
+/// Interpolated strings are kept verbatim as a single token.
+fn lex<'a>(engine: &Engine, script: &'a str) -> Vec<Lexeme<'a>> {
+    // Offset of the beginning of each line
+    let lines: Vec<_> = once(0)
+        .chain(script.match_indices('\n').map(|(i, ..)| i + 1))
+        .collect();
+
+    let offset_of = |pos: Position| {
+        let start = *lines.get(pos.line()? - 1)?;
+        let column = pos.position()?.saturating_sub(1);
+
+        Some(
+            script[start..]
+                .char_indices()
+                .nth(column)
+                .map_or(script.len(), |(i, ..)| start + i),
+        )
+    };
+
+    let scripts = [script];
+    let (mut stream, control) = engine.lex(&scripts);
+    stream.state.include_comments = true;
+
+    let mut tokens = Vec::new();
+    // Brace levels of the interpolated strings being scanned
+    let mut strings = Vec::new();
+    let mut level = 0_usize;
+    let mut within_text = false;
+
+    loop {
+        let (token, pos) = match stream.next() {
+            Some((Token::EOF, ..)) | None => break,
+            Some(item) => item,
+        };
+
+        let continued = within_text;
+        within_text = false;
+
+        match token {
+            Token::InterpolatedString(..) if continued => continue,
+            Token::StringConstant(..) if continued => {
+                strings.pop();
+                continue;
+            }
+            Token::InterpolatedString(..) => strings.push(level),
+            Token::LeftBrace | Token::MapStart => level += 1,
+            Token::RightBrace => {
+                level = level.saturating_sub(1);
+
+                // Switch the tokenizer back to text mode at the end of an interpolation
+                if strings.last() == Some(&level) {
+                    control.borrow_mut().is_within_text = true;
+                    within_text = true;
+                }
+            }
+            _ => (),
+        }
+
+        // Keep only the first token of an interpolated string
+        let is_start = matches!(token, Token::InterpolatedString(..)) && strings.len() == 1;
+
+        if strings.is_empty() || is_start {
+            if let Some(offset) = offset_of(pos) {
+                tokens.push((token, offset));
+            }
+        }
+    }
+
+    let mut lexemes = Vec::with_capacity(tokens.len());
+    let mut last_end = 0;
+
+    for (i, (token, start)) in tokens.iter().enumerate() {
+        let end = tokens
+            .get(i + 1)
+            .map_or(script.len(), |&(.., offset)| offset);
+        let text = script[*start..end].trim_end();
+
+        lexemes.push(Lexeme {
+            token: token.clone(),
+            text,
+            newlines: script[last_end.min(*start)..*start].matches('\n').count(),
+        });
+
+        last_end = start + text.len();
+    }
+
+    lexemes
+}
+
+/// Arrange tokens into nested groups of brackets.
+fn build_tree(lexemes: Vec<Lexeme>) -> Vec<Node> {
+    let mut stack: Vec<(Lexeme, Vec<Node>)> = Vec::new();
+    let mut nodes = Vec::new();
+
+    for lexeme in lexemes {
+        match lexeme.token {
+            Token::LeftParen | Token::LeftBracket | Token::LeftBrace | Token::MapStart => {
+                stack.push((lexeme, std::mem::take(&mut nodes)));
+            }
+            #[cfg(not(feature = "no_index"))]
+            Token::QuestionBracket => stack.push((lexeme, std::mem::take(&mut nodes))),
+            Token::RightParen | Token::RightBracket | Token::RightBrace if !stack.is_empty() => {
+                let (open, parent) = stack.pop().unwrap();
+                let group = Group {
+                    open,
+                    nodes: std::mem::replace(&mut nodes, parent),
+                    close: Some(lexeme),
+                };
+                nodes.push(Node::Group(group.into()));
+            }
+            _ => nodes.push(Node::Token(lexeme)),
+        }
+    }
+
+    // Close all unterminated groups
+    while let Some((open, parent)) = stack.pop() {
+        let group = Group {
+            open,
+            nodes: std::mem::replace(&mut nodes, parent),
+            close: None,
+        };
+        nodes.push(Node::Group(group.into()));
+    }
+
+    nodes
+}
+
+/// Can this token be the end of an operand?
+#[must_use]
+fn is_operand_end(token: &Token) -> bool {
+    match token {
+        Token::IntegerConstant(..)
+        | Token::CharConstant(..)
+        | Token::StringConstant(..)
+        | Token::InterpolatedString(..)
+        | Token::Identifier(..)
+        | Token::True
+        | Token::False
+        | Token::Unit
+        | Token::RightParen
+        | Token::RightBracket
+        | Token::RightBrace => true,
+
+        // Keywords such as `this` and `print` are reserved by the tokenizer
+        Token::Reserved(s) => is_valid_identifier(s),
+
+        #[cfg(not(feature = "no_float"))]
+        Token::FloatConstant(..) => true,
+        #[cfg(feature = "decimal")]
+        Token::DecimalConstant(..) => true,
+
+        _ => false,
+    }
+}
+
+/// Is a space needed between two tokens?
+#[must_use]
+fn needs_space(prev: &Token, next: &Token) -> bool {
+    match next {
+        Token::Comma
+        | Token::SemiColon
+        | Token::Colon
+        | Token::DoubleColon
+        | Token::Period
+        | Token::RightParen
+        | Token::RightBracket
+        | Token::ExclusiveRange
+        | Token::InclusiveRange => return false,
+
+        #[cfg(not(feature = "no_object"))]
+        Token::Elvis => return false,
+        #[cfg(not(feature = "no_index"))]
+        Token::QuestionBracket => return false,
+
+        // Function call or indexing
+        Token::LeftParen | Token::LeftBracket | Token::Unit if is_operand_end(prev) => {
+            return false
+        }
+
+        _ => (),
+    }
+
+    match prev {
+        Token::LeftParen
+        | Token::LeftBracket
+        | Token::MapStart
+        | Token::DoubleColon
+        | Token::Period
+        | Token::Bang
+        | Token::ExclusiveRange
+        | Token::InclusiveRange => false,
+
+        #[cfg(not(feature = "no_object"))]
+        Token::Elvis => false,
+        #[cfg(not(feature = "no_index"))]
+        Token::QuestionBracket => false,
+
+        _ => true,
+    }
+}
+
+/// Does a statement starting with this node end with a block, without the need for a semicolon?
+#[must_use]
+fn is_self_terminated(head: &Node) -> bool {
+    match head {
+        Node::Group(..) => head.is_block(),
+        Node::Token(lexeme) => match lexeme.token {
+            Token::If | Token::Switch | Token::While | Token::Loop | Token::For | Token::Try => {
+                true
+            }
+
+            #[cfg(not(feature = "no_function"))]
+            Token::Fn | Token::Private => true,
+            #[cfg(not(feature = "no_custom_syntax"))]
+            Token::Custom(..) => true,
+
+            _ => false,
+        },
+    }
+}
+
+/// An element in a block of statements.
+enum Element<'n, 'a> {
+    /// A stand-alone comment.
+    Comment(&'n Lexeme<'a>),
+    /// A statement with an optional trailing comment.
+    Statement(&'n [Node<'a>], Option<&'n Lexeme<'a>>),
+}
+
+/// Split a block into statements and stand-alone comments.
+///
+/// Each element is paired with a flag indicating whether it is preceded by blank lines.
+fn split_statements<'n, 'a>(nodes: &'n [Node<'a>]) -> Vec<(Element<'n, 'a>, bool)> {
+    let mut elements: Vec<(Element, bool)> = Vec::new();
+    let mut start = 0;
+
+    for (i, node) in nodes.iter().enumerate() {
+        if i == start {
+            if let Node::Token(lexeme) = node {
+                // Block comment leading code on the same line stays with that code
+                let inline = !lexeme.needs_line_break()
+                    && nodes.get(i + 1).map_or(false, |n| n.first().newlines == 0);
+
+                if lexeme.is_comment() && !inline {
+                    start = i + 1;
+
+                    // Comment at the end of the previous statement
+                    if lexeme.newlines == 0 {
+                        if let Some((Element::Statement(.., trailing @ None), ..)) =
+                            elements.last_mut()
+                        {
+                            *trailing = Some(lexeme);
+                            continue;
+                        }
+                    }
+
+                    let blank = !elements.is_empty() && lexeme.newlines > 1;
+                    elements.push((Element::Comment(lexeme), blank));
+                    continue;
+                }
+            }
+        }
+
+        let head = nodes[start..=i]
+            .iter()
+            .find(|n| !n.is_comment())
+            .unwrap_or(&nodes[start]);
+
+        let ends = if node.is(&Token::SemiColon) {
+            true
+        } else if node.is_block() && is_self_terminated(head) {
+            match nodes.get(i + 1) {
+                None => true,
+                Some(next) if next.is(&Token::Else) || next.is(&Token::Catch) => false,
+                // Custom syntax may continue on the same line
+                #[cfg(not(feature = "no_custom_syntax"))]
+                Some(next) if matches!(head.first().token, Token::Custom(..)) => {
+                    next.first().newlines > 0
+                }
+                Some(..) => true,
+            }
+        } else {
+            i == nodes.len() - 1
+        };
+
+        if ends {
+            let blank = !elements.is_empty() && nodes[start].first().newlines > 1;
+            elements.push((Element::Statement(&nodes[start..=i], None), blank));
+            start = i + 1;
+        }
+    }
+
+    elements
+}
+
+/// An item in a comma-separated list.
+struct Item<'n, 'a> {
+    /// Comments on separate lines before the item.
+    leading: Vec<&'n Lexeme<'a>>,
+    /// The item.
+    nodes: &'n [Node<'a>],
+    /// Comment at the end of the line of the item.
+    trailing: Option<&'n Lexeme<'a>>,
+}
+
+/// Split a comma-separated list into items.
+///
+/// If `split_after_blocks` is `true`, a statements block also ends an item (e.g. `switch` cases).
+///
+/// Comments after the last item are returned separately.
+fn split_items<'n, 'a>(
+    nodes: &'n [Node<'a>],
+    split_after_blocks: bool,
+) -> (Vec<Item<'n, 'a>>, Vec<&'n Lexeme<'a>>) {
+    let mut slices = Vec::new();
+    let mut start = 0;
+
+    for (i, node) in nodes.iter().enumerate() {
+        if node.is(&Token::Comma) {
+            slices.push(&nodes[start..i]);
+            start = i + 1;
+        } else if split_after_blocks
+            && node.is_block()
+            && !nodes.get(i + 1).map_or(false, |n| n.is(&Token::Comma))
+        {
+            slices.push(&nodes[start..=i]);
+            start = i + 1;
+        }
+    }
+    slices.push(&nodes[start..]);
+
+    let mut items: Vec<Item> = Vec::new();
+    let mut tail = Vec::new();
+
+    for mut slice in slices {
+        let mut leading = Vec::new();
+
+        while let Some(Node::Token(lexeme)) = slice.first() {
+            if !lexeme.is_comment() {
+                break;
+            }
+
+            match items.last_mut() {
+                Some(Item { trailing, .. }) if lexeme.newlines == 0 && trailing.is_none() => {
+                    *trailing = Some(lexeme);
+                }
+                _ => leading.push(lexeme),
+            }
+            slice = &slice[1..];
+        }
+
+        if slice.is_empty() {
+            tail.extend(leading);
+            continue;
+        }
+
+        let mut trailing = None;
+
+        if let Some((Node::Token(lexeme), rest)) = slice.split_last() {
+            if lexeme.is_comment() && lexeme.newlines == 0 {
+                trailing = Some(lexeme);
+                slice = rest;
+            }
+        }
+
+        items.push(Item {
+            leading,
+            nodes: slice,
+            trailing,
+        });
+    }
+
+    (items, tail)
+}
+
+/// A document to be laid out.
+enum Doc<'a> {
+    /// Text, which may contain line breaks.
+    Text(&'a str),
+    /// A space, or a line break if the enclosing group is broken.
+    Space,
+    /// Nothing, or a line break if the enclosing group is broken.
+    SoftLine,
+    /// A line break.
+    HardLine,
+    /// Text only if the enclosing group is broken.
+    IfBreak(&'a str),
+    /// Documents indented one level if the enclosing group is broken.
+    Indent(Vec<Doc<'a>>),
+    /// A group of documents laid out on a single line if it fits, otherwise broken.
+    ///
+    /// The flag forces the group to be broken.
+    Group(bool, Vec<Doc<'a>>),
+}
+
+/// Line break followed by an extra level of indentation, for the continuation of a statement.
+#[inline]
+#[must_use]
+fn continuation<'a>() -> Doc<'a> {
+    Doc::Group(true, vec![Doc::Indent(vec![Doc::HardLine])])
+}
+
+/// Layout documents into text.
+struct Printer {
+    /// Formatting options.
+    options: FormatOptions,
+}
+
+impl Printer {
+    /// Check if a document in flat mode, plus the rest, fits into the remaining width up to the
+    /// next line break.
+    ///
+    /// A document containing a hard line break or a broken group never fits in flat mode.
+    #[must_use]
+    fn fits(&self, mut width: usize, doc: &Doc, rest: &[(usize, bool, &Doc)]) -> bool {
+        let mut stack = vec![(true, doc)];
+        let mut rest = rest.iter().rev();
+
+        loop {
+            let (flat, doc) = match stack.pop() {
+                Some(item) => item,
+                None => match rest.next() {
+                    Some(&(.., flat, doc)) => (flat, doc),
+                    None => return true,
+                },
+            };
+
+            let len = match doc {
+                Doc::Text(text) => match text.split_once('\n') {
+                    Some((line, ..)) => return line.chars().count() <= width,
+                    None => text.chars().count(),
+                },
+                Doc::Space if flat => 1,
+                Doc::SoftLine if flat => 0,
+                // A document with a line break cannot be laid out flat
+                Doc::HardLine if flat => return false,
+                Doc::Space | Doc::SoftLine | Doc::HardLine => return true,
+                Doc::IfBreak(text) if !flat => text.len(),
+                Doc::IfBreak(..) => 0,
+                Doc::Indent(docs) => {
+                    stack.extend(docs.iter().rev().map(|doc| (flat, doc)));
+                    0
+                }
+                Doc::Group(true, ..) if flat => return false,
+                Doc::Group(broken, docs) => {
+                    stack.extend(docs.iter().rev().map(|doc| (flat && !broken, doc)));
+                    0
+                }
+            };
+
+            width = match width.checked_sub(len) {
+                Some(w) => w,
+                None => return false,
+            };
+        }
+    }
+    /// Layout a document.
+    #[must_use]
+    fn print(&self, doc: &Doc) -> String {
+        let mut output = String::new();
+        let mut column = 0;
+        let mut stack = vec![(0, false, doc)];
+
+        while let Some((indent, flat, doc)) = stack.pop() {
+            match doc {
+                Doc::Text(text) => {
+                    output.push_str(text);
+                    column = match text.rfind('\n') {
+                        Some(n) => text[n + 1..].chars().count(),
+                        None => column + text.chars().count(),
+                    };
+                }
+                Doc::Space if flat => {
+                    output.push(' ');
+                    column += 1;
+                }
+                Doc::SoftLine if flat => (),
+                Doc::Space | Doc::SoftLine | Doc::HardLine => {
+                    output.truncate(output.trim_end_matches(' ').len());
+                    output.push('\n');
+                    output.extend(repeat(' ').take(indent));
+                    column = indent;
+                }
+                Doc::IfBreak(text) => {
+                    if !flat {
+                        output.push_str(text);
+                        column += text.len();
+                    }
+                }
+                Doc::Indent(docs) => {
+                    let indent = if flat {
+                        indent
+                    } else {
+                        indent + self.options.indent
+                    };
+                    stack.extend(docs.iter().rev().map(|doc| (indent, flat, doc)));
+                }
+                Doc::Group(broken, docs) => {
+                    let width = self.options.max_width.saturating_sub(column);
+                    let flat = !broken && (flat || self.fits(width, doc, &stack));
+                    stack.extend(docs.iter().rev().map(|doc| (indent, flat, doc)));
+                }
+            }
+        }
+
+        output
+    }
+}
+
+/// Build documents from a tree of tokens.
+struct Formatter;
+
+impl Formatter {
+    /// Format a sequence of tokens within a statement or expression.
+    fn sequence<'a>(&self, nodes: &[Node<'a>]) -> Vec<Doc<'a>> {
+        let mut docs = Vec::new();
+        let mut prev: Option<&Token> = None;
+        let mut after_comment = false;
+        let mut after_line_break = false;
+        let mut glued = false;
+        let mut within_params = false;
+        let mut switch = false;
+
+        // Blocks in the same statement (e.g. `if` ... `else`) are either all kept on one line or all broken
+        let expand = nodes.iter().any(|node| match node {
+            Node::Group(group) if group.open.token == Token::LeftBrace => {
+                !group.nodes.is_empty() && !group.is_single_statement()
+            }
+            _ => false,
+        });
+
+        for node in nodes {
+            let lexeme = node.first();
+            let closes_params = within_params && lexeme.token == Token::Pipe;
+
+            if after_line_break {
+                docs.push(continuation());
+            } else if after_comment {
+                docs.push(Doc::Text(" "));
+            } else if let Some(prev) = prev {
+                if node.is_comment()
+                    || (!glued && !closes_params && needs_space(prev, &lexeme.token))
+                {
+                    docs.push(Doc::Text(" "));
+                }
+            }
+
+            after_comment = false;
+            after_line_break = false;
+            glued = false;
+
+            let operand_end = prev.map_or(false, is_operand_end);
+
+            match node {
+                Node::Token(lexeme) if lexeme.is_comment() => {
+                    docs.push(Doc::Text(lexeme.text));
+                    after_comment = true;
+                    after_line_break = lexeme.needs_line_break();
+                    continue;
+                }
+                Node::Token(lexeme) => {
+                    docs.push(Doc::Text(lexeme.text));
+
+                    match lexeme.token {
+                        Token::Pipe if within_params => within_params = false,
+                        Token::Pipe if !operand_end => {
+                            within_params = true;
+                            glued = true;
+                        }
+                        Token::Plus | Token::Minus | Token::UnaryPlus | Token::UnaryMinus
+                            if !operand_end =>
+                        {
+                            glued = true
+                        }
+                        Token::Switch => switch = true,
+                        _ => (),
+                    }
+                }
+                Node::Group(group) => docs.push(match group.open.token {
+                    Token::LeftBrace if switch => {
+                        switch = false;
+                        self.switch(group)
+                    }
+                    Token::LeftBrace => self.block(group, expand),
+                    Token::MapStart => self.list(group, true),
+                    Token::LeftBracket => self.list(group, !operand_end),
+                    _ => self.list(group, false),
+                }),
+            }
+
+            prev = Some(&node.last().token);
+        }
+
+        docs
+    }
+    /// Format a block of statements.
+    fn statements<'a>(&self, nodes: &[Node<'a>]) -> Vec<Doc<'a>> {
+        let mut docs = Vec::new();
+
+        for (i, (element, blank)) in split_statements(nodes).into_iter().enumerate() {
+            if i > 0 {
+                docs.push(Doc::HardLine);
+            }
+            if blank {
+                docs.push(Doc::HardLine);
+            }
+
+            match element {
+                Element::Comment(lexeme) => docs.push(Doc::Text(lexeme.text)),
+                Element::Statement(nodes, trailing) => {
+                    docs.extend(self.sequence(nodes));
+
+                    if let Some(lexeme) = trailing {
+                        docs.push(Doc::Text(" "));
+                        docs.push(Doc::Text(lexeme.text));
+                    }
+                }
+            }
+        }
+
+        docs
+    }
+    /// Format a statements block.
+    ///
+    /// A block with a single statement written in one line is kept on one line if it fits,
+    /// unless `expand` is set.
+    fn block<'a>(&self, group: &Group<'a>, expand: bool) -> Doc<'a> {
+        let close = group.close.as_ref().map_or("", |lexeme| lexeme.text);
+
+        if group.nodes.is_empty() {
+            return Doc::Group(false, vec![Doc::Text(group.open.text), Doc::Text(close)]);
+        }
+
+        let single = !expand && group.is_single_statement();
+
+        let line = || if single { Doc::Space } else { Doc::HardLine };
+
+        let mut inner = vec![line()];
+        inner.extend(self.statements(&group.nodes));
+
+        Doc::Group(
+            !single,
+            vec![
+                Doc::Text(group.open.text),
+                Doc::Indent(inner),
+                line(),
+                Doc::Text(close),
+            ],
+        )
+    }
+    /// Format the cases of a `switch` expression, one on each line.
+    fn switch<'a>(&self, group: &Group<'a>) -> Doc<'a> {
+        let close = group.close.as_ref().map_or("", |lexeme| lexeme.text);
+        let (items, tail) = split_items(&group.nodes, true);
+
+        if items.is_empty() && tail.is_empty() {
+            return Doc::Group(false, vec![Doc::Text(group.open.text), Doc::Text(close)]);
+        }
+
+        let mut inner = Vec::new();
+
+        for (i, item) in items.into_iter().enumerate() {
+            let first = item
+                .leading
+                .first()
+                .copied()
+                .unwrap_or_else(|| item.nodes[0].first());
+
+            inner.push(Doc::HardLine);
+            if i > 0 && first.newlines > 1 {
+                inner.push(Doc::HardLine);
+            }
+            for lexeme in item.leading {
+                inner.push(Doc::Text(lexeme.text));
+                inner.push(Doc::HardLine);
+            }
+            inner.extend(self.sequence(item.nodes));
+            inner.push(Doc::Text(","));
+            if let Some(lexeme) = item.trailing {
+                inner.push(Doc::Text(" "));
+                inner.push(Doc::Text(lexeme.text));
+            }
+        }
+        for lexeme in tail {
+            inner.push(Doc::HardLine);
+            inner.push(Doc::Text(lexeme.text));
+        }
+
+        Doc::Group(
+            true,
+            vec![
+                Doc::Text(group.open.text),
+                Doc::Indent(inner),
+                Doc::HardLine,
+                Doc::Text(close),
+            ],
+        )
+    }
+    /// Format a comma-separated list within brackets.
+    ///
+    /// If the list does not fit into one line, each item is placed on a separate line, and a
+    /// trailing comma is added if `trailing_comma` is `true`.
+    fn list<'a>(&self, group: &Group<'a>, trailing_comma: bool) -> Doc<'a> {
+        let close = group.close.as_ref().map_or("", |lexeme| lexeme.text);
+        let (items, tail) = split_items(&group.nodes, false);
+
+        if items.is_empty() && tail.is_empty() {
+            return Doc::Group(false, vec![Doc::Text(group.open.text), Doc::Text(close)]);
+        }
+
+        let broken = !tail.is_empty()
+            || items
+                .iter()
+                .any(|item| !item.leading.is_empty() || item.trailing.is_some());
+
+        let mut inner = vec![Doc::SoftLine];
+
+        for (i, item) in items.iter().enumerate() {
+            let is_last = i == items.len() - 1;
+
+            for lexeme in &item.leading {
+                inner.push(Doc::Text(lexeme.text));
+                inner.push(Doc::HardLine);
+            }
+            inner.extend(self.sequence(item.nodes));
+
+            if !is_last {
+                inner.push(Doc::Text(","));
+            } else if trailing_comma {
+                inner.push(Doc::IfBreak(","));
+            }
+
+            if let Some(lexeme) = item.trailing {
+                inner.push(Doc::Text(" "));
+                inner.push(Doc::Text(lexeme.text));
+            }
+
+            if !is_last {
+                inner.push(if item.trailing.map_or(false, Lexeme::needs_line_break) {
+                    Doc::HardLine
+                } else {
+                    Doc::Space
+                });
+            }
+        }
+        for lexeme in tail {
+            inner.push(Doc::HardLine);
+            inner.push(Doc::Text(lexeme.text));
+        }
+
+        Doc::Group(
+            broken,
+            vec![
+                Doc::Text(group.open.text),
+                Doc::Indent(inner),
+                Doc::SoftLine,
+                Doc::Text(close),
+            ],
+        )
+    }
+}
+
+impl Engine {
+    /// Format a script with default [`FormatOptions`].
+    ///
+    /// Not available under `no_position`.
+    ///
+    /// See [`format_script_with_options`][Engine::format_script_with_options] for details.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), rhai::ParseError> {
+    /// use rhai::Engine;
+    ///
+    /// let engine = Engine::new();
+    ///
+    /// let script = engine.format_script("let x=[1,2,3];// numbers\nif x.len()>2{print(x)}")?;
+    ///
+    /// assert_eq!(script, "let x = [1, 2, 3]; // numbers\nif x.len() > 2 { print(x) }\n");
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline(always)]
+    pub fn format_script(&self, script: impl AsRef<str>) -> ParseResult<String> {
+        self.format_script_with_options(script, FormatOptions::new())
+    }
+    /// Format a script into a canonical style.
+    ///
+    /// Not available under `no_position`.
+    ///
+    /// The script must be valid, otherwise a syntax error is returned.
+    ///
+    /// * Comments and doc-comments are preserved.
+    /// * Each statement is placed on its own line, indented according to nesting level.
+    /// * Spacing around operators, commas and brackets is normalized.
+    /// * Opening braces are placed at the end of the line.
+    /// * Blank lines between statements are kept, but multiple blank lines are merged into one.
+    /// * Lists of items within brackets that do not fit into one line are broken up with one
+    ///   item on each line, adding trailing commas in arrays and object maps.
+    ///
+    /// Unlike [`compact_script`][Engine::compact_script], this method does not change the text
+    /// of any token (such as numbers or strings).
+    pub fn format_script_with_options(
+        &self,
+        script: impl AsRef<str>,
+        options: FormatOptions,
+    ) -> ParseResult<String> {
+        let script = script.as_ref();
+
+        // Make sure the script is valid
+        {
+            let scripts = [script];
+            let (stream, tc) = self.lex(&scripts);
+            let input = &mut stream.peekable();
+            let lib = &mut <_>::default();
+            let state = ParseState::new(None, input, tc, lib);
+
+            self.parse(
+                state,
+                #[cfg(not(feature = "no_optimize"))]
+                crate::OptimizationLevel::None,
+            )?;
+        }
+
+        let nodes = build_tree(lex(self, script));
+
+        if nodes.is_empty() {
+            return Ok(String::new());
+        }
+
+        let doc = Doc::Group(true, Formatter.statements(&nodes));
+        let mut output = Printer { options }.print(&doc);
+        output.push('\n');
+
+        Ok(output)
+    }
+}
@@ -1,9 +1,9 @@
 //! Module that provide formatting services to the [`Engine`].
-use crate::packages::iter_basic::{BitRange, CharsStream, StepRange};
+use crate::packages::iter_basic::{BitRange, CharsStream, LazyIterator, StepRange};
 use crate::parser::{ParseResult, ParseState};
 use crate::{
-    Engine, ExclusiveRange, FnPtr, ImmutableString, InclusiveRange, Position, RhaiError,
-    SmartString, ERR,
+    Dynamic, Engine, ExclusiveRange, FnPtr, ImmutableString, InclusiveRange, Position, RhaiError,
+    RhaiResultOf, SmartString, ERR,
 };
 use std::any::type_name;
 #[cfg(feature = "no_std")]
@@ -58,6 +58,9 @@ pub fn map_std_type_name(name: &str, shorthands: bool) -> &str {
     if name == type_name::<crate::Instant>() || name == "Instant" {
         return if shorthands { "timestamp" } else { "Instant" };
     }
+    if name == type_name::<crate::DateTime>() || name == "DateTime" {
+        return if shorthands { "datetime" } else { "DateTime" };
+    }
     if name == type_name::<ExclusiveRange>() || name == "ExclusiveRange" {
         return if shorthands {
             "range"
@@ -82,6 +85,13 @@ pub fn map_std_type_name(name: &str, shorthands: bool) -> &str {
     if name == type_name::<CharsStream>() {
         return if shorthands { "range" } else { "CharStream" };
     }
+    if name == type_name::<LazyIterator>() {
+        return if shorthands {
+            "iterator"
+        } else {
+            "LazyIterator"
+        };
+    }
 
     let step_range_name = type_name::<StepRange<u8>>();
     let step_range_name = &step_range_name[..step_range_name.len() - 3];
@@ -219,6 +229,40 @@ impl Engine {
             .unwrap_or_else(|| map_std_type_name(name, true))
     }
 
+    /// Pretty-print the type name of a value.
+    ///
+    /// This is the same as [`map_type_name`][Engine::map_type_name] on the type name of the value,
+    /// except that the name of a script-defined record or enum type is the name it is declared with.
+    #[inline]
+    #[must_use]
+    pub(crate) fn map_value_type_name<'a>(
+        &'a self,
+        value: &'a Dynamic,
+    ) -> std::borrow::Cow<'a, str> {
+        #[cfg(not(feature = "no_object"))]
+        if let Some(record) = value.downcast_ref::<crate::Record>() {
+            return record.name().into();
+        }
+        #[cfg(not(feature = "no_object"))]
+        #[cfg(not(feature = "no_closure"))]
+        if value.is_shared() {
+            if let Some(record) = value.read_lock::<crate::Record>() {
+                return record.name().to_string().into();
+            }
+        }
+        if let Some(value) = value.downcast_ref::<crate::EnumValue>() {
+            return value.type_name().into();
+        }
+        #[cfg(not(feature = "no_closure"))]
+        if value.is_shared() {
+            if let Some(value) = value.read_lock::<crate::EnumValue>() {
+                return value.type_name().to_string().into();
+            }
+        }
+
+        self.map_type_name(value.type_name()).into()
+    }
+
     /// Format a Rust parameter type.
     ///
     /// If a type is registered via [`register_type_with_name`][Engine::register_type_with_name],
@@ -248,6 +292,169 @@ impl Engine {
             .into()
     }
 
+    /// Make an error explaining why a value does not match a [`Pattern`][crate::ast::Pattern].
+    ///
+    /// The first part of the pattern that fails to match is reported.
+    #[cold]
+    #[inline(never)]
+    #[must_use]
+    pub(crate) fn make_pattern_mismatch_err(
+        &self,
+        pattern: &crate::ast::Pattern,
+        value: &Dynamic,
+    ) -> RhaiError {
+        use crate::ast::Pattern;
+
+        #[cfg(any(not(feature = "no_index"), not(feature = "no_object")))]
+        fn count(n: usize, item: &str, items: &str) -> String {
+            format!("{n} {}", if n == 1 { item } else { items })
+        }
+
+        #[cfg(not(feature = "no_closure"))]
+        if value.is_shared() {
+            return self.make_pattern_mismatch_err(pattern, &value.flatten_clone());
+        }
+
+        let pos = pattern.position();
+        let mismatch = |expected: String, actual: String| -> RhaiError {
+            ERR::ErrorMismatchDataType(expected, actual, pos).into()
+        };
+        let actual_type = || self.map_value_type_name(value).into_owned();
+
+        match pattern {
+            Pattern::Constant(x, ..) => mismatch(format!("{x:?}"), format!("{value:?}")),
+
+            Pattern::Variant(x, ..) => {
+                let (type_name, variant, patterns) = &**x;
+                let expected = format!("{type_name}::{variant}");
+
+                match value.downcast_ref::<crate::EnumValue>() {
+                    Some(v)
+                        if v.type_name() == type_name.as_str()
+                            && v.variant() == variant.as_str() =>
+                    {
+                        patterns
+                            .iter()
+                            .zip(v.values())
+                            .find(|(p, v)| p.match_value(v).is_none())
+                            .map_or_else(
+                                || mismatch(expected, format!("{v:?}")),
+                                |(p, v)| self.make_pattern_mismatch_err(p, v),
+                            )
+                    }
+                    Some(v) => mismatch(expected, format!("{}::{}", v.type_name(), v.variant())),
+                    None => mismatch(expected, actual_type()),
+                }
+            }
+
+            #[cfg(not(feature = "no_index"))]
+            Pattern::Array(x, ..) => {
+                let (patterns, rest) = &**x;
+
+                let Ok(array) = value.as_array_ref() else {
+                    return mismatch(
+                        self.map_type_name(type_name::<crate::Array>()).into(),
+                        actual_type(),
+                    );
+                };
+
+                let expected = count(patterns.len(), "item", "items");
+
+                match rest {
+                    Some(..) if array.len() < patterns.len() => {
+                        return mismatch(
+                            format!("array of at least {expected}"),
+                            format!("array of {}", count(array.len(), "item", "items")),
+                        )
+                    }
+                    None if array.len() != patterns.len() => {
+                        return mismatch(
+                            format!("array of {expected}"),
+                            format!("array of {}", count(array.len(), "item", "items")),
+                        )
+                    }
+                    _ => (),
+                }
+
+                patterns
+                    .iter()
+                    .zip(array.iter())
+                    .find(|(p, v)| p.match_value(v).is_none())
+                    .map_or_else(
+                        || mismatch(String::new(), actual_type()),
+                        |(p, v)| self.make_pattern_mismatch_err(p, v),
+                    )
+            }
+
+            #[cfg(not(feature = "no_object"))]
+            Pattern::Map(x, ..) => {
+                let (props, rest) = &**x;
+
+                let map = match value.as_map_ref() {
+                    Ok(map) => map.clone(),
+                    Err(_) => match value.downcast_ref::<crate::Record>() {
+                        Some(record) => record.fields().clone(),
+                        None => {
+                            return mismatch(
+                                self.map_type_name(type_name::<crate::Map>()).into(),
+                                actual_type(),
+                            )
+                        }
+                    },
+                };
+
+                for (id, p) in props {
+                    match map.get(id.name.as_str()) {
+                        Some(v) if p.match_value(v).is_none() => {
+                            return self.make_pattern_mismatch_err(p, v)
+                        }
+                        Some(..) => (),
+                        None => {
+                            return ERR::ErrorPropertyNotFound(id.name.to_string(), id.pos).into()
+                        }
+                    }
+                }
+
+                if rest.is_none() && map.len() != props.len() {
+                    return mismatch(
+                        format!(
+                            "object map with {}",
+                            count(props.len(), "property", "properties")
+                        ),
+                        format!(
+                            "object map with {}",
+                            count(map.len(), "property", "properties")
+                        ),
+                    );
+                }
+
+                mismatch(String::new(), actual_type())
+            }
+
+            Pattern::Wildcard(..) | Pattern::Bind(..) => mismatch(String::new(), actual_type()),
+        }
+    }
+
+    /// Check that a value matches a type annotation.
+    ///
+    /// Returns `Err(`[`EvalAltResult<ErrorMismatchDataType>`][ERR::ErrorMismatchDataType]`)` if not.
+    #[inline]
+    pub(crate) fn check_type_annotation(
+        &self,
+        typ: &str,
+        value: &Dynamic,
+        pos: Position,
+    ) -> RhaiResultOf<()> {
+        let typ = self.map_type_name(typ);
+        let actual = self.map_value_type_name(value);
+
+        if actual == typ {
+            Ok(())
+        } else {
+            Err(ERR::ErrorMismatchDataType(typ.into(), actual.into(), pos).into())
+        }
+    }
+
     /// Compact a script to eliminate insignificant whitespaces and comments.
     ///
     /// This is useful to prepare a script for further compressing.
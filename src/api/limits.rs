@@ -5,6 +5,8 @@ use crate::Engine;
 use std::num::{NonZeroU64, NonZeroUsize};
 #[cfg(feature = "no_std")]
 use std::prelude::v1::*;
+#[cfg(not(feature = "no_time"))]
+use std::time::Duration;
 
 #[cfg(debug_assertions)]
 pub mod default_limits {
@@ -88,6 +90,13 @@ pub struct Limits {
     /// Not available under `no_object`.
     #[cfg(not(feature = "no_object"))]
     pub map_size: Option<NonZeroUsize>,
+    /// Maximum approximate number of bytes held by the variables of a script at any instant.
+    pub memory: Option<NonZeroUsize>,
+    /// Maximum wall-clock time allowed for each evaluation run.
+    ///
+    /// Not available under `no_time`.
+    #[cfg(not(feature = "no_time"))]
+    pub execution_time: Option<Duration>,
 }
 
 impl Limits {
@@ -113,6 +122,9 @@ impl Limits {
             array_size: None,
             #[cfg(not(feature = "no_object"))]
             map_size: None,
+            memory: None,
+            #[cfg(not(feature = "no_time"))]
+            execution_time: None,
         }
     }
 }
@@ -344,4 +356,98 @@ impl Engine {
         #[cfg(feature = "no_object")]
         return 0;
     }
+    /// Set the maximum approximate number of bytes held by the variables of a script at any
+    /// instant (0 for unlimited).
+    ///
+    /// This includes all the variables in scope (including those of the functions being called)
+    /// and the values captured by closures. The size of each value is estimated from the sizes of
+    /// the strings, [arrays][crate::Array], [BLOB's][crate::Blob] and [object maps][crate::Map]
+    /// within it. Custom types are counted by their handle only.
+    ///
+    /// The limit is checked after each statement that may allocate data and before each function
+    /// call, taking time proportional to the amount of data held by the variables.
+    ///
+    /// Not available under `unchecked`.
+    #[inline(always)]
+    pub fn set_max_memory(&mut self, bytes: usize) -> &mut Self {
+        self.limits.memory = NonZeroUsize::new(bytes);
+        self
+    }
+    /// The maximum approximate number of bytes held by the variables of a script at any instant
+    /// (0 for unlimited).
+    ///
+    /// Not available under `unchecked`.
+    #[inline]
+    #[must_use]
+    pub const fn max_memory(&self) -> usize {
+        match self.limits.memory {
+            Some(n) => n.get(),
+            None => 0,
+        }
+    }
+    /// Set the maximum wall-clock time allowed for each evaluation run (zero for unlimited).
+    ///
+    /// When the time is up, the evaluation fails with
+    /// [`ErrorTimeout`][crate::EvalAltResult::ErrorTimeout]. The clock is only checked every few
+    /// hundred operations, so a long-running native function call is not interrupted.
+    ///
+    /// A time too long to be represented as a deadline (e.g. [`Duration::MAX`]) is also unlimited.
+    ///
+    /// A different deadline can be set for a single function call via
+    /// [`CallFnOptions::with_deadline`][crate::CallFnOptions::with_deadline].
+    ///
+    /// Not available under `unchecked` or `no_time`.
+    #[cfg(not(feature = "no_time"))]
+    #[inline(always)]
+    pub fn set_max_execution_time(&mut self, time: Duration) -> &mut Self {
+        self.limits.execution_time =
+            Some(time).filter(|&t| !t.is_zero() && crate::Instant::now().checked_add(t).is_some());
+        self
+    }
+    /// The maximum wall-clock time allowed for each evaluation run (zero for unlimited).
+    ///
+    /// Not available under `unchecked` or `no_time`.
+    #[cfg(not(feature = "no_time"))]
+    #[inline]
+    #[must_use]
+    pub fn max_execution_time(&self) -> Duration {
+        self.limits.execution_time.unwrap_or_default()
+    }
+    /// Get a [`CancellationHandle`][crate::CancellationHandle] that cancels all evaluations running
+    /// on this [`Engine`], possibly from another thread under the `sync` feature.
+    ///
+    /// The handle is created the first time it is requested, so it should be obtained before the
+    /// [`Engine`] is shared among threads.
+    ///
+    /// The handle stays cancelled, failing all evaluations afterwards, until it is
+    /// [reset][crate::CancellationHandle::reset].
+    ///
+    /// A single function call can also be cancelled via
+    /// [`CallFnOptions::with_cancellation`][crate::CallFnOptions::with_cancellation].
+    ///
+    /// Not available under `unchecked`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rhai::{Engine, EvalAltResult};
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// let handle = engine.cancellation_handle();
+    /// handle.cancel("stop!");
+    ///
+    /// let err = engine.run("loop {}").unwrap_err();
+    /// assert!(matches!(*err, EvalAltResult::ErrorTerminated(ref reason, ..) if reason.to_string() == "stop!"));
+    ///
+    /// handle.reset();
+    /// assert!(engine.run("40 + 2").is_ok());
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn cancellation_handle(&mut self) -> crate::CancellationHandle {
+        self.cancellation
+            .get_or_insert_with(crate::CancellationHandle::new)
+            .clone()
+    }
 }
@@ -0,0 +1,909 @@
+//! Module implementing a static linter for scripts.
+#![cfg(feature = "internals")]
+
+use crate::ast::{ASTFlags, ASTNode, Expr, FnCallExpr, Ident, RangeCase, Stmt};
+use crate::engine::{
+    KEYWORD_DEBUG, KEYWORD_EVAL, KEYWORD_FN_PTR, KEYWORD_FN_PTR_CALL, KEYWORD_FN_PTR_CURRY,
+    KEYWORD_IS_DEF_VAR, KEYWORD_PRINT, KEYWORD_TYPE_OF,
+};
+use crate::func::hashing::get_hasher;
+use crate::parser::ParseResult;
+use crate::{Dynamic, Engine, Identifier, Position, AST};
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+    ptr,
+};
+
+/// Name of the rule detecting variables that are never used.
+const UNUSED_VARIABLE: &str = "unused_variable";
+/// Name of the rule detecting function parameters that are never used.
+#[cfg(not(feature = "no_function"))]
+const UNUSED_PARAMETER: &str = "unused_parameter";
+/// Name of the rule detecting variables shadowing earlier declarations.
+const SHADOWED_VARIABLE: &str = "shadowed_variable";
+/// Name of the rule detecting code that can never be reached.
+const UNREACHABLE_CODE: &str = "unreachable_code";
+/// Name of the rule detecting calls to functions that are not defined.
+const UNKNOWN_FUNCTION: &str = "unknown_function";
+/// Name of the rule detecting assignments to constants.
+const ASSIGNMENT_TO_CONSTANT: &str = "assignment_to_constant";
+/// Name of the rule detecting `switch` cases that can never match.
+const UNREACHABLE_SWITCH_CASE: &str = "unreachable_switch_case";
+
+/// _(internals)_ A warning produced by the [`Linter`].
+/// Exported under the `internals` feature only.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub struct LintWarning {
+    /// Name of the [rule][LintRule] that produced this warning.
+    pub rule: Identifier,
+    /// Description of the problem.
+    pub message: String,
+    /// Position of the problem in the script.
+    pub position: Position,
+}
+
+impl fmt::Display for LintWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} [{}]", self.message, self.rule)?;
+
+        // Do not write any position if None
+        if !self.position.is_none() {
+            write!(f, " ({})", self.position)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// _(internals)_ Context of a [`LintRule`] check.
+/// Exported under the `internals` feature only.
+pub struct LintContext<'a> {
+    /// The [`Engine`] that the script is checked against.
+    engine: &'a Engine,
+    /// The [`AST`] being checked.
+    ast: &'a AST,
+    /// The function being checked, if any.
+    #[cfg(not(feature = "no_function"))]
+    function: Option<&'a crate::ast::ScriptFuncDef>,
+    /// Name of the rule being checked.
+    rule: Identifier,
+    /// Warnings produced.
+    warnings: Vec<LintWarning>,
+}
+
+impl<'a> LintContext<'a> {
+    /// The [`Engine`] that the script is checked against.
+    #[inline(always)]
+    #[must_use]
+    pub const fn engine(&self) -> &'a Engine {
+        self.engine
+    }
+    /// The [`AST`] being checked.
+    #[inline(always)]
+    #[must_use]
+    pub const fn ast(&self) -> &'a AST {
+        self.ast
+    }
+    /// The script-defined function whose body is being checked.
+    ///
+    /// Returns [`None`] for the main script body.
+    ///
+    /// Not available under `no_function`.
+    #[cfg(not(feature = "no_function"))]
+    #[inline(always)]
+    #[must_use]
+    pub const fn function(&self) -> Option<&'a crate::ast::ScriptFuncDef> {
+        self.function
+    }
+    /// Report a warning at a particular position.
+    #[inline]
+    pub fn warn(&mut self, message: impl Into<String>, position: Position) {
+        self.warnings.push(LintWarning {
+            rule: self.rule.clone(),
+            message: message.into(),
+            position,
+        });
+    }
+    /// Top-level statements of the function being checked, or of the main script.
+    #[inline]
+    #[must_use]
+    fn top_level(&self) -> &'a [Stmt] {
+        #[cfg(not(feature = "no_function"))]
+        if let Some(func) = self.function {
+            return func.body.statements();
+        }
+
+        self.ast.statements()
+    }
+    /// Find the statements block that directly contains the last node in `path`.
+    ///
+    /// Returns the statements together with the index of the node within them,
+    /// or [`None`] if the node is not a statement in a block.
+    #[must_use]
+    pub fn enclosing_block<'p>(&self, path: &[ASTNode<'p>]) -> Option<(&'p [Stmt], usize)>
+    where
+        'a: 'p,
+    {
+        let stmt = match path.last()? {
+            ASTNode::Stmt(stmt) => *stmt,
+            ASTNode::Expr(..) => return None,
+        };
+
+        let is_in = |stmts: &[Stmt]| stmts.iter().any(|s| ptr::eq(s, stmt));
+
+        let stmts: &'p [Stmt] = match path.len().checked_sub(2).map(|i| path[i]) {
+            None => self.top_level(),
+            Some(ASTNode::Stmt(Stmt::Block(block))) => block.statements(),
+            Some(ASTNode::Expr(Expr::Stmt(block))) => block.statements(),
+            Some(ASTNode::Stmt(Stmt::For(x, ..))) => x.2.body.statements(),
+            Some(ASTNode::Stmt(
+                Stmt::If(x, ..) | Stmt::While(x, ..) | Stmt::Do(x, ..) | Stmt::TryCatch(x, ..),
+            )) => {
+                if is_in(x.body.statements()) {
+                    x.body.statements()
+                } else {
+                    x.branch.statements()
+                }
+            }
+            Some(..) => return None,
+        };
+
+        stmts
+            .iter()
+            .position(|s| ptr::eq(s, stmt))
+            .map(|index| (stmts, index))
+    }
+    /// Find the nearest declaration of a variable that is visible from the last node in `path`.
+    #[must_use]
+    fn lookup<'p>(&self, path: &[ASTNode<'p>], name: &str) -> Option<Declaration<'p>>
+    where
+        'a: 'p,
+    {
+        for k in (0..path.len()).rev() {
+            // Variables declared by enclosing statements
+            if let (ASTNode::Stmt(stmt), Some(child)) = (path[k], path.get(k + 1)) {
+                let in_body = |stmts: &[Stmt]| match child {
+                    ASTNode::Stmt(child) => stmts.iter().any(|s| ptr::eq(s, *child)),
+                    ASTNode::Expr(..) => false,
+                };
+
+                match stmt {
+                    Stmt::For(x, ..) if in_body(x.2.body.statements()) => {
+                        if x.0.name == name {
+                            return Some(Declaration::LoopVariable(&x.0));
+                        }
+                        if let Some(counter) = x.1.as_ref().filter(|c| c.name == name) {
+                            return Some(Declaration::LoopVariable(counter));
+                        }
+                    }
+                    Stmt::TryCatch(x, ..) if in_body(x.branch.statements()) => {
+                        if let Expr::Variable(v, .., pos) = &x.expr {
+                            if v.1 == name {
+                                return Some(Declaration::CatchVariable(*pos));
+                            }
+                        }
+                    }
+                    _ => (),
+                }
+            }
+
+            // Variables declared earlier in the same block
+            if let Some((stmts, index)) = self.enclosing_block(&path[..=k]) {
+                let found = stmts[..index].iter().rev().find_map(|s| match s {
+                    Stmt::Var(x, flags, ..) if x.0.name == name => {
+                        Some(Declaration::Variable(&x.0, *flags))
+                    }
+                    Stmt::Destructure(x, flags, ..) => {
+                        x.0.variables()
+                            .into_iter()
+                            .find(|id| id.name == name)
+                            .map(|id| Declaration::Variable(id, *flags))
+                    }
+                    _ => None,
+                });
+
+                if found.is_some() {
+                    return found;
+                }
+            }
+        }
+
+        #[cfg(not(feature = "no_function"))]
+        if let Some(func) = self.function {
+            if func.params.iter().any(|p| p == name) {
+                return Some(Declaration::Parameter);
+            }
+        }
+
+        None
+    }
+}
+
+/// Declaration of a variable.
+enum Declaration<'a> {
+    /// `let` or `const` statement.
+    Variable(&'a Ident, ASTFlags),
+    /// `for` loop variable or counter.
+    LoopVariable(&'a Ident),
+    /// `catch` variable.
+    CatchVariable(Position),
+    /// Function parameter.
+    #[cfg(not(feature = "no_function"))]
+    Parameter,
+}
+
+/// _(internals)_ A rule checked by the [`Linter`].
+/// Exported under the `internals` feature only.
+///
+/// # Example
+///
+/// ```
+/// use rhai::lint::{LintContext, LintRule, Linter};
+/// use rhai::{ASTNode, Engine, Stmt};
+///
+/// /// Forbid `while` loops.
+/// struct NoWhile;
+///
+/// impl LintRule for NoWhile {
+///     fn name(&self) -> &str {
+///         "no_while"
+///     }
+///     fn check_node(&self, context: &mut LintContext, path: &[ASTNode]) {
+///         if let Some(ASTNode::Stmt(stmt @ Stmt::While(..))) = path.last() {
+///             context.warn("Use a `for` loop instead", stmt.position());
+///         }
+///     }
+/// }
+///
+/// let engine = Engine::new();
+/// let mut linter = Linter::new();
+/// linter.add_rule(NoWhile);
+///
+/// let warnings = linter.lint(&engine, "let x = 0; while x < 10 { x += 1; }")?;
+///
+/// assert_eq!(warnings.len(), 1);
+/// assert_eq!(warnings[0].rule, "no_while");
+/// assert_eq!(warnings[0].position.line(), Some(1));
+/// # Ok::<_, rhai::ParseError>(())
+/// ```
+pub trait LintRule {
+    /// Name of the rule.
+    ///
+    /// This name is recorded in every [`LintWarning`] produced by the rule.
+    #[must_use]
+    fn name(&self) -> &str;
+    /// Check a node in the [`AST`].
+    ///
+    /// `path` contains the chain of nodes from the top-level statement (in the main script or in
+    /// a function body) down to the node being checked, which is the last.
+    #[allow(unused_variables)]
+    fn check_node(&self, context: &mut LintContext, path: &[ASTNode]) {}
+    /// Check a script-defined function, before its body is checked.
+    ///
+    /// Not available under `no_function`.
+    #[cfg(not(feature = "no_function"))]
+    #[allow(unused_variables)]
+    fn check_fn(&self, context: &mut LintContext, func: &crate::ast::ScriptFuncDef) {}
+}
+
+/// _(internals)_ A static linter that checks scripts against a set of [rules][LintRule].
+/// Exported under the `internals` feature only.
+///
+/// # Built-in Rules
+///
+/// | Rule                      | Description                                                         |
+/// | ------------------------- | ------------------------------------------------------------------- |
+/// | `unused_variable`         | variable or loop variable that is never used                        |
+/// | `unused_parameter`        | function parameter that is never used                               |
+/// | `shadowed_variable`       | variable that shadows an earlier declaration                        |
+/// | `unreachable_code`        | statement after `return`, `throw`, `break` or `continue`            |
+/// | `unknown_function`        | call to a function that is neither registered nor defined in script |
+/// | `assignment_to_constant`  | assignment to a constant or to a property/index of a constant       |
+/// | `unreachable_switch_case` | `switch` case that can never match                                  |
+///
+/// Variables and parameters with names starting with an underscore (`_`) are never reported as unused.
+///
+/// Calls to functions in namespaces and method calls are not checked, because they may resolve
+/// to modules or function pointers only known at run-time.
+///
+/// # Example
+///
+/// ```
+/// use rhai::lint::Linter;
+/// use rhai::Engine;
+///
+/// let engine = Engine::new();
+/// let linter = Linter::new();
+///
+/// let warnings = linter.lint(&engine, "
+///     let x = 42;
+///     let y = x + 1;
+///     return y;
+///     print(y);
+/// ")?;
+///
+/// assert_eq!(warnings.len(), 1);
+/// assert_eq!(warnings[0].rule, "unreachable_code");
+/// assert_eq!(warnings[0].position.line(), Some(5));
+/// # Ok::<_, rhai::ParseError>(())
+/// ```
+pub struct Linter {
+    /// Rules to check.
+    rules: Vec<Box<dyn LintRule>>,
+}
+
+impl fmt::Debug for Linter {
+    #[cold]
+    #[inline(never)]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.rules.iter().map(|rule| rule.name()))
+            .finish()
+    }
+}
+
+impl Default for Linter {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Linter {
+    /// Create a new [`Linter`] with all the built-in rules.
+    #[must_use]
+    pub fn new() -> Self {
+        let mut linter = Self::empty();
+        linter
+            .add_rule(UnusedVariables)
+            .add_rule(ShadowedVariables)
+            .add_rule(UnreachableCode)
+            .add_rule(UnknownFunctions)
+            .add_rule(AssignmentToConstants)
+            .add_rule(UnreachableSwitchCases);
+        #[cfg(not(feature = "no_function"))]
+        linter.add_rule(UnusedParameters);
+        linter
+    }
+    /// Create a new [`Linter`] without any rules.
+    #[inline(always)]
+    #[must_use]
+    pub const fn empty() -> Self {
+        Self { rules: Vec::new() }
+    }
+    /// Add a [rule][LintRule].
+    #[inline]
+    pub fn add_rule(&mut self, rule: impl LintRule + 'static) -> &mut Self {
+        self.rules.push(Box::new(rule));
+        self
+    }
+    /// Remove all [rules][LintRule] with a particular name.
+    #[inline]
+    pub fn remove_rule(&mut self, name: &str) -> &mut Self {
+        self.rules.retain(|rule| rule.name() != name);
+        self
+    }
+    /// Get an iterator over the names of all the [rules][LintRule].
+    #[inline]
+    pub fn rules(&self) -> impl Iterator<Item = &str> {
+        self.rules.iter().map(|rule| rule.name())
+    }
+    /// Compile a script without optimization and check it.
+    ///
+    /// Warnings are sorted by position.
+    pub fn lint(&self, engine: &Engine, script: impl AsRef<str>) -> ParseResult<Vec<LintWarning>> {
+        let ast = engine.compile_scripts_with_scope_raw(
+            None,
+            [script],
+            #[cfg(not(feature = "no_optimize"))]
+            crate::OptimizationLevel::None,
+        )?;
+
+        Ok(self.lint_ast(engine, &ast))
+    }
+    /// Check an [`AST`].
+    ///
+    /// The [`AST`] should be compiled without optimization, as the optimizer removes unused and
+    /// unreachable code.
+    ///
+    /// Warnings are sorted by position.
+    #[must_use]
+    pub fn lint_ast(&self, engine: &Engine, ast: &AST) -> Vec<LintWarning> {
+        let mut context = LintContext {
+            engine,
+            ast,
+            #[cfg(not(feature = "no_function"))]
+            function: None,
+            rule: Identifier::new_const(),
+            warnings: Vec::new(),
+        };
+
+        self.walk(&mut context, ast.statements());
+
+        #[cfg(not(feature = "no_function"))]
+        for func in ast.iter_fn_def() {
+            context.function = Some(func);
+
+            for rule in &self.rules {
+                context.rule = rule.name().into();
+                rule.check_fn(&mut context, func);
+            }
+
+            self.walk(&mut context, func.body.statements());
+        }
+
+        let mut warnings = context.warnings;
+        warnings.sort_by_key(|warning| warning.position);
+        warnings
+    }
+    /// Check all nodes in a list of statements.
+    fn walk(&self, context: &mut LintContext, statements: &[Stmt]) {
+        let path = &mut Vec::new();
+
+        for stmt in statements {
+            stmt.walk(path, &mut |path| {
+                for rule in &self.rules {
+                    context.rule = rule.name().into();
+                    rule.check_node(context, path);
+                }
+                true
+            });
+        }
+    }
+}
+
+/// Is a call to `eval`, which may use any variable in scope?
+#[must_use]
+fn is_eval_call(x: &FnCallExpr) -> bool {
+    #[cfg(not(feature = "no_module"))]
+    if !x.namespace.is_empty() {
+        return false;
+    }
+
+    x.name == KEYWORD_EVAL
+}
+
+/// Is a variable used in a list of statements, before it is declared again?
+///
+/// A call to `eval` counts as using all variables in scope.
+fn is_used(statements: &[Stmt], name: &str) -> bool {
+    for stmt in statements {
+        let mut found = false;
+
+        stmt.walk(&mut Vec::new(), &mut |path| {
+            found = uses_variable(path.last().unwrap(), name);
+            !found
+        });
+
+        #[cfg(not(feature = "no_module"))]
+        if let Stmt::Export(x, ..) = stmt {
+            found = found || x.0.name == name;
+        }
+
+        if found {
+            return true;
+        }
+
+        match stmt {
+            Stmt::Var(x, ..) if x.0.name == name => return false,
+            Stmt::Destructure(x, ..) if x.0.variables().iter().any(|id| id.name == name) => {
+                return false
+            }
+            _ => (),
+        }
+    }
+
+    false
+}
+
+/// Does a node use a variable?
+#[must_use]
+fn uses_variable(node: &ASTNode, name: &str) -> bool {
+    match node {
+        ASTNode::Expr(Expr::Variable(x, ..)) => x.1 == name,
+        ASTNode::Expr(Expr::FnCall(x, ..)) | ASTNode::Stmt(Stmt::FnCall(x, ..)) => is_eval_call(x),
+        // Arguments to method calls are not walked
+        ASTNode::Expr(Expr::MethodCall(x, ..)) => x.args.iter().any(|e| {
+            let mut found = false;
+            e.walk(&mut Vec::new(), &mut |path| {
+                found = uses_variable(path.last().unwrap(), name);
+                !found
+            });
+            found
+        }),
+        _ => false,
+    }
+}
+
+/// Get the name of the variable at the root of an assignment target, if not qualified.
+#[must_use]
+fn root_variable(expr: &Expr) -> Option<&str> {
+    match expr {
+        #[cfg(not(feature = "no_index"))]
+        Expr::Index(x, ..) => root_variable(&x.lhs),
+        #[cfg(not(feature = "no_object"))]
+        Expr::Dot(x, ..) => root_variable(&x.lhs),
+        #[cfg(not(feature = "no_module"))]
+        Expr::Variable(x, ..) if !x.2.is_empty() => None,
+        Expr::Variable(x, ..) => Some(&x.1),
+        _ => None,
+    }
+}
+
+/// Is this statement a `return`, `throw`, `break` or `continue`?
+#[inline]
+#[must_use]
+const fn is_terminating(stmt: &Stmt) -> bool {
+    matches!(stmt, Stmt::Return(..) | Stmt::BreakLoop(..))
+}
+
+/// Rule detecting variables that are never used.
+struct UnusedVariables;
+
+impl LintRule for UnusedVariables {
+    fn name(&self) -> &str {
+        UNUSED_VARIABLE
+    }
+    fn check_node(&self, context: &mut LintContext, path: &[ASTNode]) {
+        match path.last() {
+            Some(ASTNode::Stmt(Stmt::Var(x, flags, ..))) => {
+                let ident = &x.0;
+
+                if ident.name.starts_with('_') || flags.intersects(ASTFlags::EXPORTED) {
+                    return;
+                }
+
+                let used = context
+                    .enclosing_block(path)
+                    .map_or(true, |(stmts, index)| {
+                        is_used(&stmts[index + 1..], &ident.name)
+                    });
+
+                // Global constants are visible in functions
+                #[cfg(not(feature = "no_function"))]
+                let used = used
+                    || path.len() == 1
+                        && context.function.is_none()
+                        && flags.intersects(ASTFlags::CONSTANT)
+                        && context
+                            .ast
+                            .iter_fn_def()
+                            .any(|f| is_used(f.body.statements(), &ident.name));
+
+                if !used {
+                    context.warn(format!("Unused variable: {}", ident.name), ident.pos);
+                }
+            }
+            Some(ASTNode::Stmt(Stmt::Destructure(x, flags, ..))) => {
+                if flags.intersects(ASTFlags::EXPORTED) {
+                    return;
+                }
+
+                let Some((stmts, index)) = context.enclosing_block(path) else {
+                    return;
+                };
+
+                for ident in x.0.variables() {
+                    if !ident.name.starts_with('_') && !is_used(&stmts[index + 1..], &ident.name) {
+                        context.warn(format!("Unused variable: {}", ident.name), ident.pos);
+                    }
+                }
+            }
+            Some(ASTNode::Stmt(Stmt::For(x, ..))) => {
+                let (var, counter, flow) = &**x;
+
+                for ident in Some(var).into_iter().chain(counter) {
+                    if !ident.name.starts_with('_') && !is_used(flow.body.statements(), &ident.name)
+                    {
+                        context.warn(format!("Unused variable: {}", ident.name), ident.pos);
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Rule detecting function parameters that are never used.
+///
+/// Uses are matched by name only, and any call to `eval` in the function body counts as using
+/// every parameter, so a parameter that is used only within an `eval`'ed script is not reported.
+#[cfg(not(feature = "no_function"))]
+struct UnusedParameters;
+
+#[cfg(not(feature = "no_function"))]
+impl LintRule for UnusedParameters {
+    fn name(&self) -> &str {
+        UNUSED_PARAMETER
+    }
+    fn check_fn(&self, context: &mut LintContext, func: &crate::ast::ScriptFuncDef) {
+        for (param, &pos) in func.params.iter().zip(&func.param_positions) {
+            if !param.starts_with('_') && !is_used(func.body.statements(), param) {
+                context.warn(
+                    format!("Unused parameter: {param} in function {}", func.name),
+                    pos,
+                );
+            }
+        }
+    }
+}
+
+/// Rule detecting variables shadowing earlier declarations.
+struct ShadowedVariables;
+
+impl LintRule for ShadowedVariables {
+    fn name(&self) -> &str {
+        SHADOWED_VARIABLE
+    }
+    fn check_node(&self, context: &mut LintContext, path: &[ASTNode]) {
+        let idents = match path.last() {
+            Some(ASTNode::Stmt(Stmt::Var(x, ..))) => std::iter::once(&x.0).collect(),
+            Some(ASTNode::Stmt(Stmt::Destructure(x, ..))) => x.0.variables(),
+            _ => return,
+        };
+
+        for ident in idents {
+            if ident.name.starts_with('_') {
+                continue;
+            }
+
+            let previous = match context.lookup(path, &ident.name) {
+                Some(Declaration::Variable(ident, ..) | Declaration::LoopVariable(ident)) => {
+                    ident.pos
+                }
+                Some(Declaration::CatchVariable(pos)) => pos,
+                #[cfg(not(feature = "no_function"))]
+                Some(Declaration::Parameter) => Position::NONE,
+                None => continue,
+            };
+
+            let message = if previous.is_none() {
+                format!("Variable {} shadows an earlier declaration", ident.name)
+            } else {
+                format!(
+                    "Variable {} shadows an earlier declaration at {previous}",
+                    ident.name
+                )
+            };
+
+            context.warn(message, ident.pos);
+        }
+    }
+}
+
+/// Rule detecting code that can never be reached.
+struct UnreachableCode;
+
+impl LintRule for UnreachableCode {
+    fn name(&self) -> &str {
+        UNREACHABLE_CODE
+    }
+    fn check_node(&self, context: &mut LintContext, path: &[ASTNode]) {
+        match path.last() {
+            Some(ASTNode::Stmt(stmt)) if is_terminating(stmt) => (),
+            _ => return,
+        }
+
+        if let Some((stmts, index)) = context.enclosing_block(path) {
+            // Only report the first unreachable statement
+            if stmts[..index].iter().any(is_terminating) {
+                return;
+            }
+            if let Some(next) = stmts.get(index + 1) {
+                context.warn("Unreachable code", next.position());
+            }
+        }
+    }
+}
+
+/// Rule detecting calls to functions that are not defined.
+struct UnknownFunctions;
+
+impl UnknownFunctions {
+    /// Is a function with a particular name and number of parameters available?
+    #[must_use]
+    fn is_defined(context: &LintContext, name: &str, num_params: usize) -> bool {
+        let is_keyword = matches!(
+            name,
+            KEYWORD_PRINT
+                | KEYWORD_DEBUG
+                | KEYWORD_TYPE_OF
+                | KEYWORD_EVAL
+                | KEYWORD_FN_PTR
+                | KEYWORD_FN_PTR_CALL
+                | KEYWORD_FN_PTR_CURRY
+                | KEYWORD_IS_DEF_VAR
+        );
+        #[cfg(not(feature = "no_closure"))]
+        let is_keyword = is_keyword || name == crate::engine::KEYWORD_IS_SHARED;
+        #[cfg(not(feature = "no_function"))]
+        let is_keyword = is_keyword || name == crate::engine::KEYWORD_IS_DEF_FN;
+        #[cfg(not(feature = "no_function"))]
+        #[cfg(not(feature = "no_object"))]
+        let is_keyword = is_keyword || name == crate::engine::FN_RECORD;
+        #[cfg(not(feature = "no_function"))]
+        let is_keyword = is_keyword || name == crate::engine::FN_ENUM;
+
+        if is_keyword {
+            return true;
+        }
+
+        #[cfg(not(feature = "no_function"))]
+        if context
+            .ast
+            .iter_fn_def()
+            .any(|f| f.name == name && f.params.len() == num_params)
+        {
+            return true;
+        }
+
+        let is_match =
+            |f: &crate::module::FuncMetadata| f.name == name && f.num_params == num_params;
+
+        if context
+            .engine
+            .global_modules
+            .iter()
+            .any(|m| m.iter_fn().any(|(.., f)| is_match(f)))
+        {
+            return true;
+        }
+
+        #[cfg(not(feature = "no_module"))]
+        if context.engine.global_sub_modules.values().any(|m| {
+            m.iter_fn()
+                .any(|(.., f)| f.namespace == crate::FnNamespace::Global && is_match(f))
+        }) {
+            return true;
+        }
+
+        false
+    }
+}
+
+impl LintRule for UnknownFunctions {
+    fn name(&self) -> &str {
+        UNKNOWN_FUNCTION
+    }
+    fn check_node(&self, context: &mut LintContext, path: &[ASTNode]) {
+        let (x, pos) = match path.last() {
+            Some(ASTNode::Stmt(Stmt::FnCall(x, pos))) => (x, *pos),
+            Some(ASTNode::Expr(Expr::FnCall(x, pos))) => (x, *pos),
+            _ => return,
+        };
+
+        if x.op_token.is_some() {
+            return;
+        }
+        #[cfg(not(feature = "no_module"))]
+        if !x.namespace.is_empty() {
+            return;
+        }
+
+        if !Self::is_defined(context, &x.name, x.args.len()) {
+            let message = match x.args.len() {
+                1 => format!("Function not found: {} (with 1 argument)", x.name),
+                n => format!("Function not found: {} (with {n} arguments)", x.name),
+            };
+            context.warn(message, pos);
+        }
+    }
+}
+
+/// Rule detecting assignments to constants.
+struct AssignmentToConstants;
+
+impl LintRule for AssignmentToConstants {
+    fn name(&self) -> &str {
+        ASSIGNMENT_TO_CONSTANT
+    }
+    fn check_node(&self, context: &mut LintContext, path: &[ASTNode]) {
+        let lhs = match path.last() {
+            Some(ASTNode::Stmt(Stmt::Assignment(x, ..))) => &x.1.lhs,
+            _ => return,
+        };
+
+        let name = match root_variable(lhs) {
+            Some(name) => name,
+            None => return,
+        };
+
+        if let Some(Declaration::Variable(.., flags)) = context.lookup(path, name) {
+            if flags.intersects(ASTFlags::CONSTANT) {
+                context.warn(
+                    format!("Cannot assign to constant {name}"),
+                    lhs.start_position(),
+                );
+            }
+        }
+    }
+}
+
+/// Rule detecting `switch` cases that can never match.
+struct UnreachableSwitchCases;
+
+impl LintRule for UnreachableSwitchCases {
+    fn name(&self) -> &str {
+        UNREACHABLE_SWITCH_CASE
+    }
+    fn check_node(&self, context: &mut LintContext, path: &[ASTNode]) {
+        let sw = match path.last() {
+            Some(ASTNode::Stmt(Stmt::Switch(x, ..))) => &x.1,
+            _ => return,
+        };
+
+        let is_false =
+            |index: usize| matches!(sw.expressions[index].lhs, Expr::BoolConstant(false, ..));
+        let is_true =
+            |index: usize| matches!(sw.expressions[index].lhs, Expr::BoolConstant(true, ..));
+
+        let mut reachable = vec![false; sw.expressions.len()];
+
+        // Range cases are also keyed by the range value itself, which is not considered
+        let range_hashes: Vec<_> = sw
+            .ranges
+            .iter()
+            .map(|range| {
+                let value = match range {
+                    RangeCase::ExclusiveInt(r, ..) => Dynamic::from(r.clone()),
+                    RangeCase::InclusiveInt(r, ..) => Dynamic::from(r.clone()),
+                };
+                let hasher = &mut get_hasher();
+                value.hash(hasher);
+                hasher.finish()
+            })
+            .collect();
+
+        // Cases after an unconditional case with the same value can never match
+        for (hash, blocks) in &sw.cases {
+            if range_hashes.contains(hash) {
+                continue;
+            }
+
+            for &index in blocks {
+                if !is_false(index) {
+                    reachable[index] = true;
+                }
+                if is_true(index) {
+                    break;
+                }
+            }
+        }
+
+        // Ranges within earlier unconditional ranges can never match
+        for (i, range) in sw.ranges.iter().enumerate() {
+            let (first, last) = match range {
+                RangeCase::ExclusiveInt(r, ..) => (r.start, r.end - 1),
+                RangeCase::InclusiveInt(r, ..) => (*r.start(), *r.end()),
+            };
+
+            let covered = sw.ranges[..i]
+                .iter()
+                .any(|r| is_true(r.index()) && r.contains_int(first) && r.contains_int(last));
+
+            if !covered && !is_false(range.index()) {
+                reachable[range.index()] = true;
+            }
+        }
+
+        for &(.., index) in &sw.patterns {
+            if !is_false(index) {
+                reachable[index] = true;
+            }
+        }
+
+        if let Some(index) = sw.def_case {
+            reachable[index] = true;
+        }
+
+        for (index, expr) in sw.expressions.iter().enumerate() {
+            if !reachable[index] {
+                context.warn("Switch case can never match", expr.rhs.start_position());
+            }
+        }
+    }
+}
@@ -2,10 +2,16 @@
 
 pub mod eval;
 
+pub mod eval_async;
+
+pub mod resume;
+
 pub mod run;
 
 pub mod compile;
 
+pub mod reparse;
+
 pub mod json;
 
 pub mod files;
@@ -24,6 +30,12 @@ pub mod events;
 
 pub mod formatting;
 
+pub mod formatter;
+
+pub mod lint;
+
+pub mod type_check;
+
 pub mod custom_syntax;
 
 pub mod build_type;
@@ -76,6 +88,38 @@ impl Engine {
         })
     }
 
+    /// Set the seed of the random number generator used by
+    /// [`RandomPackage`][crate::packages::RandomPackage].
+    ///
+    /// Scripts then generate the same sequence of random values on every run, which is useful for
+    /// simulations and tests that must be reproducible.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::Engine;
+    /// use rhai::packages::{Package, RandomPackage};
+    ///
+    /// let mut engine = Engine::new();
+    /// RandomPackage::new().register_into_engine(&mut engine);
+    ///
+    /// engine.set_random_seed(42);
+    /// let x = engine.eval::<i64>("rand(1..=100)")?;
+    ///
+    /// engine.set_random_seed(42);
+    /// let y = engine.eval::<i64>("rand(1..=100)")?;
+    ///
+    /// assert_eq!(x, y);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline(always)]
+    pub fn set_random_seed(&mut self, seed: u64) -> &mut Self {
+        self.random_state = Some(crate::packages::random::Rng::new(seed).into());
+        self
+    }
+
     /// The module resolution service used by the [`Engine`].
     ///
     /// Not available under `no_module`.
@@ -1,7 +1,7 @@
 //! Module that defines the script optimization API of [`Engine`].
 #![cfg(not(feature = "no_optimize"))]
 
-use crate::{Engine, OptimizationLevel, Scope, AST};
+use crate::{Engine, OptimizationLevel, OptimizationReport, OptimizerOptions, Scope, AST};
 #[cfg(feature = "no_std")]
 use std::prelude::v1::*;
 
@@ -25,6 +25,44 @@ impl Engine {
         self.optimization_level
     }
 
+    /// Control which passes the optimizer performs.
+    /// Default is all passes.
+    ///
+    /// Passes that are not supported by the [optimization level][Engine::optimization_level]
+    /// are never performed.
+    ///
+    /// Not available under `no_optimize`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rhai::{Engine, OptimizerOptions};
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// // Keep all `switch` cases but optimize everything else
+    /// engine.set_optimizer_options(OptimizerOptions::all() - OptimizerOptions::SWITCH_PRUNING);
+    ///
+    /// let ast = engine.compile("switch 42 { 1 => print(1), _ => print(2) }")?;
+    ///
+    /// assert!(format!("{ast:?}").contains("Switch"));
+    /// # Ok::<_, Box<rhai::EvalAltResult>>(())
+    /// ```
+    #[inline(always)]
+    pub fn set_optimizer_options(&mut self, options: OptimizerOptions) -> &mut Self {
+        self.optimizer_options = options;
+        self
+    }
+
+    /// The passes currently performed by the optimizer.
+    ///
+    /// Not available under `no_optimize`.
+    #[inline(always)]
+    #[must_use]
+    pub const fn optimizer_options(&self) -> OptimizerOptions {
+        self.optimizer_options
+    }
+
     /// Optimize the [`AST`] with constants defined in an external Scope.
     /// An optimized copy of the [`AST`] is returned while the original [`AST`] is consumed.
     ///
@@ -51,9 +89,58 @@ impl Engine {
         ast: AST,
         optimization_level: OptimizationLevel,
     ) -> AST {
+        self.optimize_ast_with_report(scope, ast, optimization_level)
+            .0
+    }
+
+    /// Optimize the [`AST`] with constants defined in an external Scope, returning also an
+    /// [`OptimizationReport`] listing the rewrites performed.
+    ///
+    /// Not available under `no_optimize`.
+    ///
+    /// See [`optimize_ast`][Engine::optimize_ast] for details.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// # #[cfg(not(feature = "no_function"))]
+    /// # {
+    /// use rhai::{Engine, OptimizationLevel, Scope};
+    ///
+    /// let mut engine = Engine::new();
+    /// engine.set_optimization_level(OptimizationLevel::None);
+    ///
+    /// let ast = engine.compile("
+    ///     private fn double(x) { x * 2 }
+    ///     let x = 20;
+    ///     double(x) + 2
+    /// ")?;
+    ///
+    /// let (ast, report) =
+    ///     engine.optimize_ast_with_report(&Scope::new(), ast, OptimizationLevel::Full);
+    ///
+    /// assert_eq!(report.inlined.len(), 1);
+    /// assert_eq!(report.inlined[0].0, "double");
+    /// assert_eq!(report.removed, ["double"]);
+    /// assert_eq!(ast.iter_functions().count(), 0);
+    ///
+    /// assert_eq!(engine.eval_ast::<i64>(&ast)?, 42);
+    /// # }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn optimize_ast_with_report(
+        &self,
+        scope: &Scope,
+        ast: AST,
+        optimization_level: OptimizationLevel,
+    ) -> (AST, OptimizationReport) {
         let mut ast = ast;
+        let mut report = OptimizationReport::default();
 
-        let mut _new_ast = self.optimize_into_ast(
+        let mut _new_ast = self.optimize_into_ast_with_report(
             Some(scope),
             std::mem::take(ast.statements_mut()).to_vec().into(),
             #[cfg(not(feature = "no_function"))]
@@ -63,6 +150,7 @@ impl Engine {
                 .cloned()
                 .collect::<Vec<_>>(),
             optimization_level,
+            &mut report,
         );
 
         #[cfg(feature = "metadata")]
@@ -70,6 +158,6 @@ impl Engine {
             _new_ast.doc = std::mem::take(&mut ast.doc);
         }
 
-        _new_ast
+        (_new_ast, report)
     }
 }
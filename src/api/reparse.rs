@@ -0,0 +1,592 @@
+//! Module that defines the incremental reparsing API of [`Engine`].
+#![cfg(not(feature = "no_position"))]
+
+use crate::ast::{ASTFlags, Stmt, StmtBlockContainer};
+use crate::parser::{ParseResult, ParseState};
+use crate::tokenizer::{Token, TokenizerControl};
+use crate::types::dynamic::AccessMode;
+use crate::{Engine, Position, AST};
+use std::convert::TryFrom;
+use std::iter::{once, repeat};
+use std::ops::Range;
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+
+#[cfg(not(feature = "no_custom_syntax"))]
+use crate::ast::Expr;
+#[cfg(not(feature = "no_function"))]
+use crate::{ast::ScriptFuncDef, parser::is_anonymous_fn};
+
+/// An edit to a script.
+#[derive(Debug, Clone, Copy)]
+struct Edit {
+    /// Start of the edit.
+    start: Position,
+    /// End (exclusive) of the replaced text, in the script before the edit.
+    old_end: Position,
+    /// End (exclusive) of the new text, in the script after the edit.
+    new_end: Position,
+}
+
+impl Edit {
+    /// Map a [position][Position] in the script before the edit to the script after the edit.
+    ///
+    /// Returns [`None`] if the position is within the replaced text.
+    #[must_use]
+    fn map(self, pos: Position) -> Option<Position> {
+        if pos.is_none() || pos < self.start {
+            return Some(pos);
+        }
+        if pos < self.old_end {
+            return None;
+        }
+
+        let (line, column) = (pos.line()?, pos.position().unwrap_or(0));
+        let (old_line, old_column) = (self.old_end.line()?, self.old_end.position()?);
+        let (new_line, new_column) = (self.new_end.line()?, self.new_end.position()?);
+
+        Some(if line == old_line {
+            new_position(new_line, column + new_column - old_column)
+        } else {
+            new_position(line + new_line - old_line, column)
+        })
+    }
+}
+
+/// Where a top-level statement or function definition is located relative to the part of the
+/// script being reparsed.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
+enum Place {
+    /// Before the reparsed part.
+    Before,
+    /// Within the reparsed part.
+    Within,
+    /// After the reparsed part.
+    After,
+}
+
+impl Engine {
+    /// Compile a script into an [`AST`] after an edit, reusing the [`AST`] compiled from the
+    /// script before the edit.
+    ///
+    /// `script` is the whole script after the edit, in which the text within `range` of the old
+    /// script (end exclusive) has been replaced by `text`.
+    ///
+    /// Only the top-level statements and function definitions around the edit are parsed again.
+    /// The rest are taken from the old [`AST`], with their [positions][Position] shifted to match
+    /// the new script. The result is equivalent to compiling the new script via
+    /// [`compile`][Engine::compile].
+    ///
+    /// If the edit changes the global variables, constants, imported modules or functions seen by
+    /// the statements following it, all those statements are parsed again. If the edit cannot be
+    /// handled incrementally (e.g. because it does not match the script, or the new script has
+    /// errors), the whole script is compiled.
+    ///
+    /// This method is intended for editors that recompile a script on every keystroke.
+    ///
+    /// Not available under `no_position`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::{Engine, Position};
+    ///
+    /// let engine = Engine::new();
+    ///
+    /// let ast = engine.compile("let x = 40;\nx + 1")?;
+    ///
+    /// // Replace the '1' on line 2 by '2'
+    /// let script = "let x = 40;\nx + 2";
+    /// let ast = engine.reparse(&ast, script, Position::new(2, 5)..Position::new(2, 6), "2")?;
+    ///
+    /// assert_eq!(engine.eval_ast::<i64>(&ast)?, 42);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn reparse(
+        &self,
+        ast: &AST,
+        script: impl AsRef<str>,
+        range: Range<Position>,
+        text: impl AsRef<str>,
+    ) -> ParseResult<AST> {
+        let script = script.as_ref();
+
+        self.reparse_raw(ast, script, range, text.as_ref())
+            .map_or_else(|| self.compile(script), Ok)
+    }
+    /// Compile a script into an [`AST`] after an edit, reusing the [`AST`] compiled from the
+    /// script before the edit.
+    ///
+    /// Returns [`None`] if the script must be compiled as a whole.
+    fn reparse_raw(
+        &self,
+        ast: &AST,
+        script: &str,
+        range: Range<Position>,
+        text: &str,
+    ) -> Option<AST> {
+        let start = char_position(range.start)?;
+        let old_end = char_position(range.end)?;
+
+        if old_end < start {
+            return None;
+        }
+
+        // Offset of the beginning of each line
+        let lines: Vec<_> = once(0)
+            .chain(script.match_indices('\n').map(|(i, ..)| i + 1))
+            .collect();
+
+        // Positions stop advancing beyond the maximum number of lines or characters per line
+        let max = usize::from(u16::MAX);
+        let line_ends = lines.iter().skip(1).copied().chain(once(script.len()));
+
+        if lines.len() >= max || lines.iter().zip(line_ends).any(|(&a, b)| b - a >= max) {
+            return None;
+        }
+
+        let new_end = match text.rfind('\n') {
+            Some(n) => new_position(
+                start.line()? + text.matches('\n').count(),
+                text[n + 1..].chars().count() + 1,
+            ),
+            None => new_position(start.line()?, start.position()? + text.chars().count()),
+        };
+
+        // The new text must be in the script
+        let new_text = offset_of(script, &lines, start)?..offset_of(script, &lines, new_end)?;
+
+        if script.get(new_text)? != text {
+            return None;
+        }
+
+        let edit = Edit {
+            start,
+            old_end,
+            new_end,
+        };
+
+        let (items, _control) = split_statements(self, script, &lines)?;
+
+        if items.is_empty() {
+            return None;
+        }
+
+        // Reparse the statements containing the edit together with one statement on either side,
+        // because the boundaries of those may be affected
+        let region_start = items
+            .partition_point(|&(pos, ..)| pos <= start)
+            .saturating_sub(2);
+        let mut region_end =
+            (items.partition_point(|&(pos, ..)| pos < new_end) + 1).min(items.len());
+
+        // Statements after the edit that contain no old statement or function definition may have
+        // been commented out before the edit, so reparse up to the last of them
+        let mut anchored = vec![false; items.len()];
+        let anchors = ast.statements().iter().map(Stmt::position);
+        #[cfg(not(feature = "no_function"))]
+        let anchors = anchors.chain(
+            ast.shared_lib()
+                .iter_script_fn()
+                .map(|(.., fn_def)| fn_def.body.position()),
+        );
+
+        for pos in anchors
+            .filter_map(|pos| edit.map(pos))
+            .filter(|pos| !pos.is_none())
+        {
+            if let Some(n) = items.partition_point(|&(p, ..)| p <= pos).checked_sub(1) {
+                anchored[n] = true;
+            }
+        }
+        if let Some(n) = anchored.iter().rposition(|&a| !a) {
+            region_end = region_end.max(n + 1);
+        }
+
+        loop {
+            // Locate a position in the new script
+            let place_of = |pos: Position| match items
+                .partition_point(|&(p, ..)| p <= pos)
+                .saturating_sub(1)
+            {
+                n if n < region_start => Place::Before,
+                n if n < region_end => Place::Within,
+                _ => Place::After,
+            };
+            // Locate a position in the old script
+            let locate = |pos: Position| edit.map(pos).map_or(Place::Within, place_of);
+
+            // Sort the old statements
+            let mut before = StmtBlockContainer::new_const();
+            let mut removed = Vec::new();
+            let mut after = StmtBlockContainer::new_const();
+            let mut last = Place::Before;
+
+            for stmt in ast.statements() {
+                let place = match stmt.position() {
+                    pos if pos.is_none() => last,
+                    pos => locate(pos),
+                };
+
+                if place < last {
+                    return None;
+                }
+                last = place;
+
+                match place {
+                    Place::Before => before.push(stmt.clone()),
+                    Place::Within => removed.push(stmt),
+                    Place::After => after.push(stmt.clone()),
+                }
+            }
+
+            // Sort the old functions
+            #[cfg(not(feature = "no_function"))]
+            let mut functions = Vec::new();
+            #[cfg(not(feature = "no_function"))]
+            let mut removed_functions = Vec::new();
+            #[cfg(not(feature = "no_function"))]
+            let mut after_functions = Vec::new();
+
+            #[cfg(not(feature = "no_function"))]
+            for (.., fn_def) in ast.shared_lib().iter_script_fn() {
+                let place = match fn_def.body.position() {
+                    pos if pos.is_none() => Place::Before,
+                    pos => locate(pos),
+                };
+
+                match place {
+                    Place::Before => functions.push(fn_def.clone()),
+                    Place::Within if is_anonymous_fn(&fn_def.name) => (),
+                    Place::Within => removed_functions.push(fn_hash(fn_def)),
+                    Place::After => after_functions.push(fn_def.clone()),
+                }
+            }
+
+            // Parse the statements in the region, keeping their positions in the script
+            let (offset, pos) = match region_start {
+                0 => (0, Position::START),
+                n => (items[n].1, items[n].0),
+            };
+            let end = items.get(region_end).map_or(script.len(), |&(.., n)| n);
+
+            let region: String = repeat('\n')
+                .take(pos.line()? - 1)
+                .chain(repeat(' ').take(pos.position().unwrap_or(1) - 1))
+                .chain(script[offset..end].chars())
+                .collect();
+
+            let scripts = [region];
+            let (stream, tc) = self.lex(&scripts);
+            let input = &mut stream.peekable();
+            let lib = &mut <_>::default();
+            let mut state = ParseState::new(None, input, tc, lib);
+
+            // Replay the global definitions before the region
+            for stmt in &before {
+                match stmt {
+                    Stmt::Var(x, options, ..) => {
+                        let access = if options.intersects(ASTFlags::CONSTANT) {
+                            AccessMode::ReadOnly
+                        } else {
+                            AccessMode::ReadWrite
+                        };
+                        let is_export = options.intersects(ASTFlags::EXPORTED);
+                        state.declare_var(x.0.name.clone(), access, is_export);
+                    }
+                    Stmt::Destructure(x, options, ..) => {
+                        let access = if options.intersects(ASTFlags::CONSTANT) {
+                            AccessMode::ReadOnly
+                        } else {
+                            AccessMode::ReadWrite
+                        };
+                        let is_export = options.intersects(ASTFlags::EXPORTED);
+                        state.declare_pattern_vars(&x.0, access, is_export);
+                    }
+                    #[cfg(not(feature = "no_module"))]
+                    Stmt::Import(x, ..) => state.imports.push(x.1.name.clone()),
+                    #[cfg(not(feature = "no_module"))]
+                    Stmt::Export(x, ..) => state.alias_var(&x.0.name, x.1.name.clone()),
+                    // Custom syntax may change the scope in ways that cannot be replayed
+                    _ if is_declaration(stmt) => return None,
+                    _ => (),
+                }
+            }
+
+            #[cfg(not(feature = "no_function"))]
+            for fn_def in &functions {
+                state.lib.insert(fn_hash(fn_def), fn_def.clone());
+            }
+
+            let (statements, ..) = self.parse_global_level(&mut state, |_| {}).ok()?;
+
+            // Check that the statements after the region are not affected
+            let old_declarations: Vec<_> = removed.iter().filter(|s| is_declaration(s)).collect();
+            let new_declarations: Vec<_> =
+                statements.iter().filter(|s| is_declaration(s)).collect();
+
+            let is_unaffected = !removed.iter().any(|stmt| stmt.is_control_flow_break())
+                && old_declarations.len() == new_declarations.len()
+                && old_declarations
+                    .iter()
+                    .zip(new_declarations)
+                    .all(|(&a, b)| is_same_declaration(a, b));
+
+            #[cfg(not(feature = "no_function"))]
+            let functions: Vec<_> = state.lib.values().cloned().collect();
+
+            #[cfg(not(feature = "no_function"))]
+            let is_unaffected = is_unaffected && {
+                let mut new_functions: Vec<_> = functions
+                    .iter()
+                    .filter(|f| place_of(f.body.position()) == Place::Within)
+                    .filter(|f| !is_anonymous_fn(&f.name))
+                    .map(|f| fn_hash(f))
+                    .collect();
+                new_functions.sort_unstable();
+                removed_functions.sort_unstable();
+                new_functions == removed_functions
+            };
+
+            if !is_unaffected && region_end < items.len() {
+                region_end = items.len();
+                continue;
+            }
+
+            // Shift the positions of the statements after the region
+            let shift = &mut |pos: Position| edit.map(pos).unwrap_or(pos);
+
+            let mut statements: StmtBlockContainer = before.into_iter().chain(statements).collect();
+
+            statements.extend(after.into_iter().map(|mut stmt| {
+                stmt.map_positions(shift);
+                stmt
+            }));
+
+            // Keep functions in source order, as in a fresh compile
+            #[cfg(not(feature = "no_function"))]
+            let functions = {
+                let mut functions: Vec<_> = functions
+                    .into_iter()
+                    .chain(after_functions.into_iter().map(|fn_def| {
+                        let mut fn_def = crate::func::shared_take_or_clone(fn_def);
+                        fn_def.body.map_positions(shift);
+                        fn_def
+                            .param_positions
+                            .iter_mut()
+                            .for_each(|pos| *pos = shift(*pos));
+                        fn_def.into()
+                    }))
+                    .collect();
+                functions.sort_by_key(|f| f.body.position());
+                functions
+            };
+            #[cfg(feature = "no_function")]
+            let functions = Vec::new();
+
+            let mut new_ast = self.build_ast(
+                &state,
+                statements,
+                functions,
+                #[cfg(not(feature = "no_optimize"))]
+                self.optimization_level,
+            );
+
+            if let Some(source) = ast.source_raw() {
+                new_ast.set_source(source.clone());
+            }
+            #[cfg(feature = "metadata")]
+            {
+                let global_comments = &_control.borrow().global_comments;
+                new_ast.doc = global_comments.into();
+            }
+
+            return Some(new_ast);
+        }
+    }
+}
+
+/// Make a [`Position`] from a line number and a character position, both 1-based.
+#[must_use]
+fn new_position(line: usize, position: usize) -> Position {
+    Position::new(
+        u16::try_from(line).unwrap_or(u16::MAX),
+        u16::try_from(position).unwrap_or(u16::MAX),
+    )
+}
+
+/// Turn a [`Position`] at the beginning of a line into the position of the first character.
+///
+/// Returns [`None`] if there is no position.
+#[must_use]
+fn char_position(pos: Position) -> Option<Position> {
+    Some(new_position(pos.line()?, pos.position().unwrap_or(1)))
+}
+
+/// Get the byte offset of a [`Position`] in a script, given the offsets of its lines.
+///
+/// A position just after the end of a line is allowed.
+#[must_use]
+fn offset_of(script: &str, lines: &[usize], pos: Position) -> Option<usize> {
+    let start = *lines.get(pos.line()? - 1)?;
+    let end = lines.get(pos.line()?).map_or(script.len(), |&n| n);
+    let column = pos.position().map_or(0, |n| n - 1);
+
+    script[start..end]
+        .char_indices()
+        .map(|(n, ..)| start + n)
+        .chain(once(end))
+        .nth(column)
+}
+
+/// Split a script into top-level statements, returning the position and byte offset of the
+/// first token of each statement.
+///
+/// Statements end at a semicolon or, when they start with a keyword that is followed by a
+/// statements block (e.g. `if`), at the closing brace of the last block. Statements that cannot
+/// be told apart this way are kept together.
+///
+/// Returns [`None`] if the script cannot be tokenized, its braces are unbalanced or it contains
+/// the end of a block comment outside a comment.
+fn split_statements(
+    engine: &Engine,
+    script: &str,
+    lines: &[usize],
+) -> Option<(Vec<(Position, usize)>, TokenizerControl)> {
+    let scripts = [script];
+    let (mut stream, control) = engine.lex(&scripts);
+
+    let mut items = Vec::new();
+    // Open braces, `true` for statements blocks
+    let mut braces = Vec::new();
+    // Brace levels of the interpolated strings being scanned
+    let mut strings = Vec::new();
+    let mut within_text = false;
+    // Does the current statement start with a keyword that is followed by a block?
+    let mut is_block_stmt = None;
+    let mut at_start = true;
+    let mut at_block_end = false;
+    let mut last_token = Token::EOF;
+
+    loop {
+        let (token, pos) = match stream.next() {
+            Some((Token::EOF, ..)) | None => break,
+            Some((Token::LexError(..), ..)) => return None,
+            Some(item) => item,
+        };
+
+        // The end of a block comment outside a comment is always an error
+        if matches!((&last_token, &token), (Token::Multiply, Token::Divide)) {
+            return None;
+        }
+
+        let continued = within_text;
+        within_text = false;
+
+        match token {
+            Token::InterpolatedString(..) if continued => continue,
+            Token::StringConstant(..) if continued => {
+                strings.pop();
+                continue;
+            }
+            _ => (),
+        }
+
+        if at_start || (at_block_end && !matches!(token, Token::Else | Token::Catch)) {
+            items.push((pos, offset_of(script, lines, pos)?));
+            is_block_stmt = None;
+        }
+        at_start = false;
+        at_block_end = false;
+
+        if is_block_stmt.is_none() && !matches!(token, Token::Comment(..)) {
+            is_block_stmt = Some(match token {
+                Token::If
+                | Token::Switch
+                | Token::While
+                | Token::Loop
+                | Token::For
+                | Token::Try
+                | Token::LeftBrace => true,
+                #[cfg(not(feature = "no_function"))]
+                Token::Fn | Token::Private => true,
+                _ => false,
+            });
+        }
+
+        match token {
+            Token::InterpolatedString(..) => strings.push(braces.len()),
+            Token::LeftBrace => braces.push(true),
+            Token::MapStart => braces.push(false),
+            Token::RightBrace => {
+                let is_block = braces.pop()?;
+
+                // Switch the tokenizer back to text mode at the end of an interpolation
+                if strings.last() == Some(&braces.len()) {
+                    control.borrow_mut().is_within_text = true;
+                    within_text = true;
+                } else if braces.is_empty() && is_block && is_block_stmt == Some(true) {
+                    at_block_end = true;
+                }
+            }
+            Token::SemiColon if braces.is_empty() && strings.is_empty() => at_start = true,
+            _ => (),
+        }
+
+        last_token = token;
+    }
+
+    if !braces.is_empty() {
+        return None;
+    }
+
+    Some((items, control))
+}
+
+/// Does a top-level statement define something that affects how later statements are parsed?
+#[must_use]
+fn is_declaration(stmt: &Stmt) -> bool {
+    match stmt {
+        Stmt::Var(..) | Stmt::Destructure(..) => true,
+        #[cfg(not(feature = "no_module"))]
+        Stmt::Import(..) | Stmt::Export(..) => true,
+        #[cfg(not(feature = "no_custom_syntax"))]
+        Stmt::Expr(e) => matches!(&**e, Expr::Custom(x, ..) if x.scope_may_be_changed),
+        _ => false,
+    }
+}
+
+/// Do two top-level declarations affect later statements in the same way?
+///
+/// Constants are never the same because their values may be propagated into later statements.
+#[must_use]
+fn is_same_declaration(a: &Stmt, b: &Stmt) -> bool {
+    match (a, b) {
+        (Stmt::Var(x, x_options, ..), Stmt::Var(y, y_options, ..)) => {
+            x.0.name == y.0.name
+                && x_options == y_options
+                && !x_options.intersects(ASTFlags::CONSTANT)
+        }
+        #[cfg(not(feature = "no_module"))]
+        (Stmt::Import(x, ..), Stmt::Import(y, ..)) => x.1.name == y.1.name,
+        #[cfg(not(feature = "no_module"))]
+        (Stmt::Export(x, ..), Stmt::Export(y, ..)) => x.0.name == y.0.name && x.1.name == y.1.name,
+        _ => false,
+    }
+}
+
+/// Calculate the hash of a script-defined function, as used by the parser.
+#[cfg(not(feature = "no_function"))]
+#[must_use]
+fn fn_hash(fn_def: &ScriptFuncDef) -> u64 {
+    let hash = crate::calc_fn_hash(None, &fn_def.name, fn_def.params.len());
+
+    #[cfg(not(feature = "no_object"))]
+    if let Some(ref typ) = fn_def.this_type {
+        return crate::calc_typed_method_hash(hash, typ);
+    }
+
+    hash
+}
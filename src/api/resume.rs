@@ -0,0 +1,119 @@
+//! Module that defines the API to resume suspended evaluations.
+
+use crate::eval::{Caches, GlobalRuntimeState};
+use crate::types::dynamic::Variant;
+use crate::{Continuation, Dynamic, Engine, Position, RhaiResult, RhaiResultOf, Scope, AST, ERR};
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+use std::{
+    any::{type_name, TypeId},
+    mem,
+};
+
+impl Engine {
+    /// Resume a suspended evaluation of an [`AST`] with a value, returning the result value or an
+    /// error.
+    ///
+    /// The value becomes the result of the function call that suspended the evaluation via
+    /// [`NativeCallContext::suspend`][crate::NativeCallContext::suspend], which is not called
+    /// again. The evaluation may be suspended again, in which case a new [`Continuation`] is
+    /// returned within [`ErrorTerminated`][crate::EvalAltResult::ErrorTerminated].
+    ///
+    /// The variables in the [`Scope`] are replaced by those recorded in the [`Continuation`], and
+    /// the evaluation resumes by completing the statement that was suspended, so the [`AST`] must
+    /// be the same as the one used in the original evaluation.
+    /// See [`Continuation`] for details.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::{Engine, NativeCallContext, Scope};
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// engine.register_fn("wait", |context: NativeCallContext| context.suspend(()));
+    ///
+    /// let ast = engine.compile("let x = wait(); let y = wait(); x + y")?;
+    ///
+    /// let continuation = engine.eval_ast::<i64>(&ast).unwrap_err().into_continuation().unwrap();
+    ///
+    /// let continuation = engine
+    ///     .resume::<i64>(&mut Scope::new(), &ast, continuation, 40_i64)
+    ///     .unwrap_err()
+    ///     .into_continuation()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(continuation.num_resumed(), 1);
+    ///
+    /// let result = engine.resume::<i64>(&mut Scope::new(), &ast, continuation, 2_i64)?;
+    ///
+    /// assert_eq!(result, 42);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn resume<T: Variant + Clone>(
+        &self,
+        scope: &mut Scope,
+        ast: &AST,
+        continuation: Continuation,
+        value: impl Variant + Clone,
+    ) -> RhaiResultOf<T> {
+        let global = &mut self.new_global_runtime_state();
+        let output = Ok(Dynamic::from(value));
+
+        let result = self.resume_raw(global, scope, ast, continuation, output)?;
+
+        // Bail out early if the return type needs no cast
+        if TypeId::of::<T>() == TypeId::of::<Dynamic>() {
+            return Ok(reify! { result => T });
+        }
+
+        result.try_cast_result::<T>().map_err(|v| {
+            let typename = match type_name::<T>() {
+                typ if typ.contains("::") => self.map_type_name(typ),
+                typ => typ,
+            };
+
+            ERR::ErrorMismatchOutputType(
+                typename.into(),
+                self.map_type_name(v.type_name()).into(),
+                Position::NONE,
+            )
+            .into()
+        })
+    }
+    /// Resume a suspended evaluation of an [`AST`] with the output of the function call that
+    /// suspended it, returning the result value or an error.
+    ///
+    /// If the output is an error, it is raised at the function call.
+    pub(crate) fn resume_raw(
+        &self,
+        global: &mut GlobalRuntimeState,
+        scope: &mut Scope,
+        ast: &AST,
+        continuation: Continuation,
+        output: RhaiResult,
+    ) -> RhaiResult {
+        let frames = &mut continuation.into_resume_state(global);
+
+        let Some(frame) = frames.first_mut() else {
+            return Err(ERR::ErrorSystem(
+                "Cannot resume evaluation".into(),
+                "no statement to resume".into(),
+            )
+            .into());
+        };
+
+        // Variables defined via `eval` or a variable definition filter are restored as well,
+        // so always search the scope
+        global.always_search_scope = true;
+
+        *scope = mem::take(&mut frame.scope);
+
+        let caches = &mut Caches::new();
+        let resume = Some((&mut frames[..], output));
+
+        self.resume_ast_with_scope_raw(global, caches, scope, ast, resume)
+    }
+}
@@ -0,0 +1,824 @@
+//! Module implementing static type checking of scripts.
+#![cfg(feature = "metadata")]
+
+use crate::ast::{BinaryExpr, Expr, FnCallExpr, OpAssignment, Stmt};
+use crate::engine::{
+    KEYWORD_DEBUG, KEYWORD_EVAL, KEYWORD_FN_PTR, KEYWORD_FN_PTR_CALL, KEYWORD_FN_PTR_CURRY,
+    KEYWORD_IS_DEF_VAR, KEYWORD_PRINT, KEYWORD_TYPE_OF,
+};
+use crate::func::builtin::{get_builtin_binary_op_fn, get_builtin_op_assignment_fn};
+use crate::module::FuncMetadata;
+use crate::tokenizer::Token;
+use crate::{Dynamic, Engine, ExclusiveRange, FnPtr, InclusiveRange, Position, Scope, AST};
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+use std::{any::type_name, fmt};
+
+/// Name shown for a type that cannot be inferred.
+const UNKNOWN_TYPE: &str = "?";
+
+/// _(metadata)_ A function call whose argument types match no registered overload of the function.
+/// Exported under the `metadata` feature only.
+///
+/// Produced by [`Engine::check_types`].
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub struct TypeMismatch {
+    /// Name of the function called.
+    pub fn_name: String,
+    /// Inferred types of the arguments, `?` if a type cannot be inferred.
+    ///
+    /// For method calls, the type of the object is the first.
+    pub arg_types: Vec<String>,
+    /// Position of the call.
+    pub position: Position,
+}
+
+impl fmt::Display for TypeMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "No function matches the argument types: {} ({})",
+            self.fn_name,
+            self.arg_types.join(", ")
+        )?;
+
+        // Do not write any position if None
+        if !self.position.is_none() {
+            write!(f, " ({})", self.position)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Engine {
+    /// _(metadata)_ Check the types of the arguments of all function calls in an [`AST`] against
+    /// the registered functions, without running it.
+    /// Exported under the `metadata` feature only.
+    ///
+    /// Types of variables are inferred from the values assigned to them, and types of function
+    /// call results from the return types recorded in the functions metadata. A call is reported
+    /// when no function registered with the same name takes arguments of the inferred types.
+    ///
+    /// Returns the mismatches found, sorted by position.
+    ///
+    /// # Limitations
+    ///
+    /// This check is conservative and only reports calls that are sure to fail:
+    ///
+    /// * A variable assigned values of different types anywhere in its scope has an unknown type.
+    /// * Parameters of script-defined functions without type annotations, `this`, properties,
+    ///   indexing results, loop variables over arrays and values returned by `Dynamic` functions
+    ///   have unknown types.
+    /// * Unknown types match any parameter.
+    /// * Calls to script-defined functions, calls via function pointers and calls with a
+    ///   namespace are not checked.
+    /// * Methods called on object maps are not checked, as they may be function pointers.
+    /// * Operators are only checked on standard types.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::Engine;
+    ///
+    /// let engine = Engine::new();
+    ///
+    /// let ast = engine.compile(r#"
+    ///     let x = "hello";
+    ///     let y = x.len();
+    ///     x.sub_string(y, "2")
+    /// "#)?;
+    ///
+    /// let mismatches = engine.check_types(&ast);
+    ///
+    /// assert_eq!(mismatches.len(), 1);
+    /// assert_eq!(mismatches[0].fn_name, "sub_string");
+    /// assert_eq!(mismatches[0].arg_types, ["string", "i64", "string"]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline(always)]
+    #[must_use]
+    pub fn check_types(&self, ast: &AST) -> Vec<TypeMismatch> {
+        self.check_types_with_scope(&Scope::new(), ast)
+    }
+    /// _(metadata)_ Check the types of the arguments of all function calls in an [`AST`] against
+    /// the registered functions, without running it.
+    /// Exported under the `metadata` feature only.
+    ///
+    /// Variables in the [`Scope`] have the types of their current values.
+    ///
+    /// See [`check_types`][Engine::check_types] for details.
+    #[must_use]
+    pub fn check_types_with_scope(&self, scope: &Scope, ast: &AST) -> Vec<TypeMismatch> {
+        let mut checker = TypeChecker::new(self, ast);
+
+        // Infer the types of variables until they no longer change
+        loop {
+            checker.changed = false;
+            checker.num_vars = 0;
+            checker.mismatches.clear();
+
+            for (name, _, value) in scope.iter_raw() {
+                let typ = checker.value_type(value);
+                checker.declare(name, typ);
+            }
+
+            checker.block(ast.statements());
+
+            #[cfg(not(feature = "no_function"))]
+            for func in ast.iter_fn_def() {
+                checker.stack.clear();
+                for (i, param) in func.params.iter().enumerate() {
+                    let typ = match func.param_types.get(i) {
+                        Some(Some(typ)) => checker.known_type(typ),
+                        _ => None,
+                    };
+                    checker.declare(param, typ);
+                }
+                checker.block(func.body.statements());
+            }
+
+            checker.stack.clear();
+
+            if !checker.changed {
+                break;
+            }
+        }
+
+        let mut mismatches = checker.mismatches;
+        mismatches.sort_by_key(|m| m.position);
+        mismatches
+    }
+}
+
+/// Inferred type of a value, as returned by `type_of`, or [`None`] if unknown.
+type Type<'a> = Option<&'a str>;
+
+/// Join two inferred types.
+#[inline]
+#[must_use]
+fn join<'a>(x: Type<'a>, y: Type<'a>) -> Type<'a> {
+    if x == y {
+        x
+    } else {
+        None
+    }
+}
+
+/// Get the type within a `Result` return type, or [`None`] for `RhaiResult`.
+#[must_use]
+fn strip_result(typ: &str) -> Option<&str> {
+    let typ = typ.trim();
+
+    let (base, inner) = match typ.find('<') {
+        Some(n) if typ.ends_with('>') => (&typ[..n], &typ[n + 1..typ.len() - 1]),
+        _ if typ.ends_with("RhaiResult") => return None,
+        _ => return Some(typ),
+    };
+
+    if base.ends_with("RhaiResultOf") {
+        return Some(inner.trim());
+    }
+    if !base.ends_with("Result") {
+        return Some(typ);
+    }
+
+    // Cut the error type
+    let mut level = 0;
+
+    for (n, ch) in inner.char_indices() {
+        match ch {
+            '<' => level += 1,
+            '>' => level -= 1,
+            ',' if level == 0 => return Some(inner[..n].trim()),
+            _ => (),
+        }
+    }
+
+    Some(inner.trim())
+}
+
+/// State of the type checker.
+struct TypeChecker<'a> {
+    /// The [`Engine`] that the script is checked against.
+    engine: &'a Engine,
+    /// The [`AST`] being checked.
+    #[cfg(not(feature = "no_function"))]
+    ast: &'a AST,
+    /// Names of all known types, together with sample values of standard types.
+    types: Vec<(&'a str, Option<Dynamic>)>,
+    /// Variables in scope, with their indices into `vars`.
+    stack: Vec<(&'a str, usize)>,
+    /// Inferred types of all variables, in order of declaration.
+    vars: Vec<Type<'a>>,
+    /// Number of variables declared in the current pass.
+    num_vars: usize,
+    /// Has the inferred type of any variable changed in the current pass?
+    changed: bool,
+    /// Mismatches found in the current pass.
+    mismatches: Vec<TypeMismatch>,
+}
+
+impl<'a> TypeChecker<'a> {
+    /// Create a new [`TypeChecker`].
+    #[must_use]
+    fn new(engine: &'a Engine, _ast: &'a AST) -> Self {
+        let samples = [
+            Dynamic::UNIT,
+            Dynamic::TRUE,
+            Dynamic::from_int(1),
+            #[cfg(not(feature = "no_float"))]
+            Dynamic::from_float(1.0),
+            #[cfg(feature = "decimal")]
+            Dynamic::from_decimal(rust_decimal::Decimal::ONE),
+            Dynamic::from_char('a'),
+            "a".into(),
+            #[cfg(not(feature = "no_index"))]
+            Dynamic::from_array(crate::Array::new()),
+            #[cfg(not(feature = "no_index"))]
+            Dynamic::from_blob(crate::Blob::new()),
+            #[cfg(not(feature = "no_object"))]
+            Dynamic::from_map(crate::Map::new()),
+            Dynamic::from::<ExclusiveRange>(0..1),
+            Dynamic::from::<InclusiveRange>(0..=1),
+        ];
+        let others = [
+            type_name::<FnPtr>(),
+            #[cfg(not(feature = "no_time"))]
+            type_name::<crate::Instant>(),
+            type_name::<i8>(),
+            type_name::<i16>(),
+            type_name::<i32>(),
+            type_name::<i64>(),
+            type_name::<i128>(),
+            type_name::<u8>(),
+            type_name::<u16>(),
+            type_name::<u32>(),
+            type_name::<u64>(),
+            type_name::<u128>(),
+            #[cfg(not(feature = "no_float"))]
+            type_name::<f32>(),
+            #[cfg(not(feature = "no_float"))]
+            type_name::<f64>(),
+        ];
+
+        let mut types = IntoIterator::into_iter(samples)
+            .map(|v| (engine.map_type_name(v.type_name()), Some(v)))
+            .chain(IntoIterator::into_iter(others).map(|t| (engine.map_type_name(t), None)))
+            .collect::<Vec<_>>();
+
+        let custom_types = engine.global_modules.iter();
+        #[cfg(not(feature = "no_module"))]
+        let custom_types = custom_types.chain(engine.global_sub_modules.values());
+
+        for m in custom_types {
+            types.extend(
+                m.iter_custom_types()
+                    .map(|(.., t)| (t.display_name.as_str(), None)),
+            );
+        }
+
+        Self {
+            engine,
+            #[cfg(not(feature = "no_function"))]
+            ast: _ast,
+            types,
+            stack: Vec::new(),
+            vars: Vec::new(),
+            num_vars: 0,
+            changed: false,
+            mismatches: Vec::new(),
+        }
+    }
+    /// Map the name of a Rust type in function metadata to a known type.
+    #[must_use]
+    fn known_type(&self, typ: &'a str) -> Type<'a> {
+        let typ = typ.trim();
+        let typ = typ.strip_prefix("&mut ").unwrap_or(typ).trim();
+        let typ = match typ {
+            "String" | "str" => "&str",
+            #[cfg(feature = "decimal")]
+            "Decimal" => type_name::<rust_decimal::Decimal>(),
+            _ => typ,
+        };
+        let typ = self.engine.map_type_name(typ);
+
+        self.types
+            .iter()
+            .find(|(t, ..)| *t == typ)
+            .map(|(t, ..)| *t)
+    }
+    /// Get the type of a value.
+    #[inline]
+    #[must_use]
+    fn value_type(&self, value: &Dynamic) -> Type<'a> {
+        #[cfg(not(feature = "no_closure"))]
+        if value.is_shared() {
+            return None;
+        }
+        self.known_type(value.type_name())
+    }
+    /// Get a sample value of a standard type.
+    #[inline]
+    #[must_use]
+    fn sample(&self, typ: &str) -> Option<Dynamic> {
+        self.types
+            .iter()
+            .find(|(t, ..)| *t == typ)
+            .and_then(|(.., v)| v.clone())
+    }
+    /// Declare a new variable.
+    fn declare(&mut self, name: &'a str, typ: Type<'a>) {
+        let index = self.num_vars;
+        self.num_vars += 1;
+
+        if index < self.vars.len() {
+            self.assign(index, typ);
+        } else {
+            self.vars.push(typ);
+        }
+
+        self.stack.push((name, index));
+    }
+    /// Assign a value of a particular type to a variable.
+    fn assign(&mut self, index: usize, typ: Type<'a>) {
+        let joined = join(self.vars[index], typ);
+
+        if joined != self.vars[index] {
+            self.vars[index] = joined;
+            self.changed = true;
+        }
+    }
+    /// Find a variable in scope.
+    #[inline]
+    #[must_use]
+    fn find_var(&self, name: &str) -> Option<usize> {
+        self.stack
+            .iter()
+            .rev()
+            .find(|(n, ..)| *n == name)
+            .map(|&(.., index)| index)
+    }
+    /// Is this the name of a script-defined function?
+    #[inline]
+    #[must_use]
+    fn is_script_fn(&self, _name: &str) -> bool {
+        #[cfg(not(feature = "no_function"))]
+        return self.ast.iter_fn_def().any(|f| f.name == _name);
+
+        #[cfg(feature = "no_function")]
+        return false;
+    }
+    /// Get an iterator over all registered functions that can be called without a namespace.
+    fn iter_fn(&self) -> impl Iterator<Item = (bool, &'a FuncMetadata)> {
+        let engine = self.engine;
+
+        let iter = engine
+            .global_modules
+            .iter()
+            .flat_map(|m| m.iter_fn().map(|(f, m)| (f.is_script(), m)));
+
+        #[cfg(not(feature = "no_module"))]
+        let iter = iter.chain(engine.global_sub_modules.values().flat_map(|m| {
+            m.iter_fn()
+                .filter(|(.., m)| m.namespace == crate::FnNamespace::Global)
+                .map(|(f, m)| (f.is_script(), m))
+        }));
+
+        iter
+    }
+    /// Find all registered functions that take arguments of particular types.
+    ///
+    /// Returns whether any function with that name is registered, together with the joined
+    /// return type of the matching functions, or [`None`] if no function matches.
+    #[must_use]
+    fn resolve(&self, name: &str, args: &[Type<'a>]) -> (bool, Option<Type<'a>>) {
+        let mut found = false;
+        let mut result = None;
+
+        for (is_script, f) in self.iter_fn().filter(|(.., f)| f.name == name) {
+            found = true;
+
+            if f.num_params != args.len() {
+                continue;
+            }
+
+            // Script-defined functions and functions without metadata take any arguments
+            let has_info = !is_script
+                && f.params_info.len() == f.num_params
+                && (f.num_params > 0 || !f.return_type.is_empty());
+
+            if has_info {
+                let is_match = args.iter().zip(f.params_info.iter()).all(|(arg, param)| {
+                    match (arg, param.split_once(':')) {
+                        (Some(arg), Some((.., param))) => {
+                            self.known_type(param).map_or(true, |param| param == *arg)
+                        }
+                        _ => true,
+                    }
+                });
+
+                if !is_match {
+                    continue;
+                }
+            }
+
+            let typ = match f.return_type.as_str() {
+                _ if !has_info => None,
+                "" => self.known_type("()"),
+                r => strip_result(r).and_then(|r| self.known_type(r)),
+            };
+
+            result = Some(result.map_or(typ, |t| join(t, typ)));
+        }
+
+        (found, result)
+    }
+    /// Record a mismatch.
+    fn report(&mut self, name: &str, args: &[Type<'a>], position: Position) {
+        self.mismatches.push(TypeMismatch {
+            fn_name: name.into(),
+            arg_types: args
+                .iter()
+                .map(|t| t.unwrap_or(UNKNOWN_TYPE).into())
+                .collect(),
+            position,
+        });
+    }
+    /// Check a call to a registered function.
+    fn call(&mut self, name: &str, args: &[Type<'a>], position: Position) -> Type<'a> {
+        match self.resolve(name, args) {
+            (.., Some(typ)) => typ,
+            (true, None) => {
+                self.report(name, args, position);
+                None
+            }
+            (false, None) => None,
+        }
+    }
+    /// Check a binary operator.
+    fn binary_op(
+        &mut self,
+        name: &str,
+        op: &Token,
+        args: [Type<'a>; 2],
+        position: Position,
+    ) -> Type<'a> {
+        if let (.., Some(typ)) = self.resolve(name, &args) {
+            return typ;
+        }
+
+        let (mut x, mut y) = match (
+            args[0].and_then(|t| self.sample(t)),
+            args[1].and_then(|t| self.sample(t)),
+        ) {
+            (Some(x), Some(y)) => (x, y),
+            _ => return None,
+        };
+
+        match get_builtin_binary_op_fn(op, &x, &y) {
+            Some((f, false)) => f(None, &mut [&mut x, &mut y])
+                .ok()
+                .and_then(|v| self.value_type(&v)),
+            Some((.., true)) => None,
+            None => {
+                self.report(name, &args, position);
+                None
+            }
+        }
+    }
+    /// Check a function call.
+    fn fn_call(&mut self, x: &'a FnCallExpr, position: Position) -> Type<'a> {
+        let args = x.args.iter().map(|a| self.expr(a)).collect::<Vec<_>>();
+
+        #[cfg(not(feature = "no_module"))]
+        if !x.namespace.is_empty() {
+            return None;
+        }
+
+        if x.name == KEYWORD_EVAL {
+            // `eval` may assign anything to any variable in scope
+            for index in self
+                .stack
+                .iter()
+                .map(|&(.., index)| index)
+                .collect::<Vec<_>>()
+            {
+                self.assign(index, None);
+            }
+            return None;
+        }
+
+        match x.op_token {
+            Some(ref op) if args.len() == 2 => {
+                self.binary_op(&x.name, op, [args[0], args[1]], position)
+            }
+            // Unary operators are not checked
+            Some(..) => self.resolve(&x.name, &args).1.flatten(),
+            None if is_keyword(&x.name)
+                || self.is_script_fn(&x.name)
+                || self.find_var(&x.name).is_some() =>
+            {
+                None
+            }
+            None => self.call(&x.name, &args, position),
+        }
+    }
+    /// Check a method call.
+    #[cfg(not(feature = "no_object"))]
+    fn method_call(&mut self, x: &'a FnCallExpr, obj: Type<'a>, position: Position) -> Type<'a> {
+        let args = Some(obj)
+            .into_iter()
+            .chain(x.args.iter().map(|a| self.expr(a)))
+            .collect::<Vec<_>>();
+
+        let is_checked = obj.map_or(false, |t| {
+            Some(t) != self.known_type(type_name::<FnPtr>())
+                && Some(t) != self.known_type(type_name::<crate::Map>())
+        });
+
+        if !is_checked || is_keyword(&x.name) || self.is_script_fn(&x.name) {
+            return None;
+        }
+
+        self.call(&x.name, &args, position)
+    }
+    /// Check the rest of a dot or index chain, with the type of the object.
+    ///
+    /// Each link of the chain holds a property, method call or index, followed by the rest of the
+    /// chain.
+    #[cfg(any(not(feature = "no_index"), not(feature = "no_object")))]
+    fn chain(&mut self, _obj: Type<'a>, _is_dot: bool, rhs: &'a Expr) -> Type<'a> {
+        let (item, rest) = match rhs {
+            #[cfg(not(feature = "no_object"))]
+            Expr::Dot(x, ..) => (&x.lhs, Some((true, &x.rhs))),
+            #[cfg(not(feature = "no_index"))]
+            Expr::Index(x, ..) => (&x.lhs, Some((false, &x.rhs))),
+            _ => (rhs, None),
+        };
+
+        let typ = match item {
+            #[cfg(not(feature = "no_object"))]
+            Expr::MethodCall(x, pos) if _is_dot => self.method_call(x, _obj, *pos),
+            // Properties and indexing results have unknown types
+            _ => {
+                self.expr(item);
+                None
+            }
+        };
+
+        match rest {
+            Some((is_dot, rest)) => self.chain(typ, is_dot, rest),
+            None => typ,
+        }
+    }
+    /// Check an expression, returning its type.
+    fn expr(&mut self, expr: &'a Expr) -> Type<'a> {
+        match expr {
+            Expr::DynamicConstant(v, ..) => self.value_type(v),
+            Expr::BoolConstant(..) => self.known_type(type_name::<bool>()),
+            Expr::IntegerConstant(..) => self.known_type(type_name::<crate::INT>()),
+            #[cfg(not(feature = "no_float"))]
+            Expr::FloatConstant(..) => self.known_type(type_name::<crate::FLOAT>()),
+            Expr::CharConstant(..) => self.known_type(type_name::<char>()),
+            Expr::StringConstant(..) => self.known_type("&str"),
+            Expr::InterpolatedString(x, ..) => {
+                x.iter().for_each(|e| {
+                    self.expr(e);
+                });
+                self.known_type("&str")
+            }
+            Expr::Array(x, ..) => {
+                x.iter().for_each(|e| {
+                    self.expr(e);
+                });
+                #[cfg(not(feature = "no_index"))]
+                return self.known_type(type_name::<crate::Array>());
+                #[cfg(feature = "no_index")]
+                return None;
+            }
+            Expr::Map(x, ..) => {
+                x.0.iter().for_each(|(.., e)| {
+                    self.expr(e);
+                });
+                #[cfg(not(feature = "no_object"))]
+                return self.known_type(type_name::<crate::Map>());
+                #[cfg(feature = "no_object")]
+                return None;
+            }
+            Expr::Unit(..) => self.known_type("()"),
+
+            #[cfg(not(feature = "no_module"))]
+            Expr::Variable(x, ..) if !x.2.is_empty() => None,
+            Expr::Variable(x, ..) => self.find_var(&x.1).and_then(|index| self.vars[index]),
+
+            Expr::Stmt(x) => {
+                self.block(x.statements());
+                None
+            }
+            Expr::FnCall(x, pos) => self.fn_call(x, *pos),
+
+            #[cfg(not(feature = "no_object"))]
+            Expr::Dot(x, ..) => {
+                let obj = self.expr(&x.lhs);
+                self.chain(obj, true, &x.rhs)
+            }
+            #[cfg(not(feature = "no_index"))]
+            Expr::Index(x, ..) => {
+                let obj = self.expr(&x.lhs);
+                self.chain(obj, false, &x.rhs)
+            }
+            Expr::And(x, ..) | Expr::Or(x, ..) => {
+                self.expr(&x.lhs);
+                self.expr(&x.rhs);
+                self.known_type(type_name::<bool>())
+            }
+            Expr::Coalesce(x, ..) => {
+                let lhs = self.expr(&x.lhs);
+                let rhs = self.expr(&x.rhs);
+                join(lhs, rhs)
+            }
+            #[cfg(not(feature = "no_custom_syntax"))]
+            Expr::Custom(x, ..) => {
+                x.inputs.iter().for_each(|e| {
+                    self.expr(e);
+                });
+                None
+            }
+
+            _ => None,
+        }
+    }
+    /// Check an assignment.
+    fn assignment(&mut self, x: &'a (OpAssignment, BinaryExpr)) {
+        let (op_info, BinaryExpr { lhs, rhs }) = x;
+
+        let var = match lhs {
+            #[cfg(not(feature = "no_module"))]
+            Expr::Variable(v, ..) if !v.2.is_empty() => None,
+            Expr::Variable(v, ..) => self.find_var(&v.1),
+            _ => None,
+        };
+
+        let num_mismatches = self.mismatches.len();
+        let obj = self.expr(lhs);
+        let value = self.expr(rhs);
+
+        let typ = match op_info.get_op_assignment_info() {
+            None => value,
+            Some((.., op_assign, op_assign_syntax, op, op_syntax)) => {
+                let args = [obj, value];
+
+                let is_op_assign = self.resolve(op_assign_syntax, &args).1.is_some()
+                    || match (
+                        obj.and_then(|t| self.sample(t)),
+                        value.and_then(|t| self.sample(t)),
+                    ) {
+                        (Some(x), Some(y)) => {
+                            get_builtin_op_assignment_fn(op_assign, &x, &y).is_some()
+                        }
+                        _ => false,
+                    };
+
+                if is_op_assign {
+                    obj
+                } else {
+                    self.binary_op(op_syntax, op, args, op_info.position())
+                }
+            }
+        };
+
+        // A mismatch fails the assignment
+        if self.mismatches.len() > num_mismatches {
+            return;
+        }
+
+        if let Some(index) = var {
+            self.assign(index, typ);
+        }
+    }
+    /// Check a statement.
+    fn stmt(&mut self, stmt: &'a Stmt) {
+        match stmt {
+            Stmt::If(x, ..) | Stmt::While(x, ..) | Stmt::Do(x, ..) => {
+                self.expr(&x.expr);
+                self.block(x.body.statements());
+                self.block(x.branch.statements());
+            }
+            Stmt::Switch(x, ..) => {
+                self.expr(&x.0);
+                for (index, case) in x.1.expressions.iter().enumerate() {
+                    // Variables bound by patterns can hold values of any type
+                    let len = self.stack.len();
+                    if let Some((pattern, ..)) = x.1.patterns.iter().find(|&&(.., n)| n == index) {
+                        for var in pattern.variables() {
+                            self.declare(&var.name, None);
+                        }
+                    }
+                    self.expr(&case.lhs);
+                    self.expr(&case.rhs);
+                    self.stack.truncate(len);
+                }
+            }
+            Stmt::For(x, ..) => {
+                let (var, counter, flow) = &**x;
+                let iter = self.expr(&flow.expr);
+
+                let typ = if iter == self.known_type(type_name::<ExclusiveRange>())
+                    || iter == self.known_type(type_name::<InclusiveRange>())
+                {
+                    self.known_type(type_name::<crate::INT>())
+                } else if iter == self.known_type("&str") {
+                    self.known_type(type_name::<char>())
+                } else {
+                    None
+                };
+
+                let len = self.stack.len();
+                self.declare(&var.name, typ);
+                if let Some(counter) = counter {
+                    self.declare(&counter.name, self.known_type(type_name::<crate::INT>()));
+                }
+                self.block(flow.body.statements());
+                self.stack.truncate(len);
+            }
+            Stmt::TryCatch(x, ..) => {
+                self.block(x.body.statements());
+
+                let len = self.stack.len();
+                if let Expr::Variable(v, ..) = &x.expr {
+                    self.declare(&v.1, None);
+                }
+                self.block(x.branch.statements());
+                self.stack.truncate(len);
+            }
+            Stmt::Var(x, ..) => {
+                let typ = self.expr(&x.1);
+                let typ = x.3.as_ref().map_or(typ, |t| self.known_type(t));
+                self.declare(&x.0.name, typ);
+            }
+            Stmt::Destructure(x, ..) => {
+                self.expr(&x.1);
+                x.0.variables()
+                    .into_iter()
+                    .for_each(|id| self.declare(&id.name, None));
+            }
+            Stmt::Assignment(x) => self.assignment(x),
+            Stmt::FnCall(x, pos) => {
+                self.fn_call(x, *pos);
+            }
+            Stmt::Block(x) => self.block(x.statements()),
+            Stmt::Expr(x) => {
+                self.expr(x);
+            }
+            Stmt::BreakLoop(Some(x), ..) | Stmt::Return(Some(x), ..) => {
+                self.expr(x);
+            }
+            #[cfg(not(feature = "no_module"))]
+            Stmt::Import(x, ..) => {
+                self.expr(&x.0);
+            }
+            // Captured variables may be assigned anything by closures
+            #[cfg(not(feature = "no_closure"))]
+            Stmt::Share(x) => {
+                for (var, ..) in x.iter() {
+                    if let Some(index) = self.find_var(&var.name) {
+                        self.assign(index, None);
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+    /// Check a block of statements in a new scope.
+    fn block(&mut self, statements: &'a [Stmt]) {
+        let len = self.stack.len();
+        statements.iter().for_each(|s| self.stmt(s));
+        self.stack.truncate(len);
+    }
+}
+
+/// Is this the name of a keyword function?
+#[must_use]
+fn is_keyword(name: &str) -> bool {
+    let is_keyword = matches!(
+        name,
+        KEYWORD_PRINT
+            | KEYWORD_DEBUG
+            | KEYWORD_TYPE_OF
+            | KEYWORD_EVAL
+            | KEYWORD_FN_PTR
+            | KEYWORD_FN_PTR_CALL
+            | KEYWORD_FN_PTR_CURRY
+            | KEYWORD_IS_DEF_VAR
+    );
+    #[cfg(not(feature = "no_closure"))]
+    let is_keyword = is_keyword || name == crate::engine::KEYWORD_IS_SHARED;
+    #[cfg(not(feature = "no_function"))]
+    let is_keyword = is_keyword || name == crate::engine::KEYWORD_IS_DEF_FN;
+
+    is_keyword
+}
@@ -23,6 +23,7 @@ use std::{
 /// _(internals)_ A binary expression.
 /// Exported under the `internals` feature only.
 #[derive(Debug, Clone, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BinaryExpr {
     /// LHS expression.
     pub lhs: Expr,
@@ -36,12 +37,14 @@ pub struct BinaryExpr {
 /// Not available under `no_custom_syntax`.
 #[cfg(not(feature = "no_custom_syntax"))]
 #[derive(Debug, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CustomExpr {
     /// List of keywords.
     pub inputs: FnArgsVec<Expr>,
     /// List of tokens actually parsed.
     pub tokens: FnArgsVec<ImmutableString>,
     /// State value.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde::ast::constant"))]
     pub state: Dynamic,
     /// Is the current [`Scope`][crate::Scope] possibly modified by this custom statement
     /// (e.g. introducing a new variable)?
@@ -90,6 +93,7 @@ impl CustomExpr {
 ///   name plus the types of the arguments.  This is due to possible function overloading for
 ///   different parameter types.
 #[derive(Clone, Copy, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FnCallHashes {
     /// Pre-calculated hash for a script-defined function ([`None`] if native functions only).
     #[cfg(not(feature = "no_function"))]
@@ -182,6 +186,7 @@ impl FnCallHashes {
 /// _(internals)_ A function call.
 /// Exported under the `internals` feature only.
 #[derive(Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FnCallExpr {
     /// Namespace of the function, if any.
     #[cfg(not(feature = "no_module"))]
@@ -195,6 +200,7 @@ pub struct FnCallExpr {
     /// Does this function call capture the parent scope?
     pub capture_parent_scope: bool,
     /// Is this function call a native operator?
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde::ast::op_token"))]
     pub op_token: Option<Token>,
 }
 
@@ -253,6 +259,7 @@ impl FnCallExpr {
 /// _(internals)_ An expression sub-tree.
 /// Exported under the `internals` feature only.
 #[derive(Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[non_exhaustive]
 #[allow(clippy::type_complexity)]
 pub enum Expr {
@@ -262,7 +269,11 @@ pub enum Expr {
     /// Primitive data types should use the appropriate variants to avoid an allocation.
     ///
     /// The [`Dynamic`] value is boxed in order to avoid bloating the size of [`Expr`].
-    DynamicConstant(Box<Dynamic>, Position),
+    DynamicConstant(
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde::ast::boxed_constant"))]
+        Box<Dynamic>,
+        Position,
+    ),
     /// Boolean constant.
     BoolConstant(bool, Position),
     /// Integer constant.
@@ -280,6 +291,7 @@ pub enum Expr {
     Array(ThinVec<Expr>, Position),
     /// #{ name:expr, ... }
     Map(
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde::ast::map_template"))]
         Box<(StaticVec<(Ident, Expr)>, BTreeMap<Identifier, Dynamic>)>,
         Position,
     ),
@@ -913,4 +925,44 @@ impl Expr {
 
         true
     }
+    /// Recursively map all the [positions][Position] within this expression.
+    #[cfg(not(feature = "no_position"))]
+    pub(crate) fn map_positions(&mut self, f: &mut impl FnMut(Position) -> Position) {
+        match self {
+            Self::Stmt(x) => return x.map_positions(f),
+            Self::InterpolatedString(x, ..) | Self::Array(x, ..) => {
+                x.iter_mut().for_each(|e| e.map_positions(f));
+            }
+            Self::Map(x, ..) => {
+                for (key, e) in &mut x.0 {
+                    key.pos = f(key.pos);
+                    e.map_positions(f);
+                }
+            }
+            #[cfg(not(feature = "no_module"))]
+            Self::Variable(x, ..) => x.2.path.iter_mut().for_each(|id| id.pos = f(id.pos)),
+            Self::FnCall(x, ..) | Self::MethodCall(x, ..) => {
+                #[cfg(not(feature = "no_module"))]
+                x.namespace
+                    .path
+                    .iter_mut()
+                    .for_each(|id| id.pos = f(id.pos));
+                x.args.iter_mut().for_each(|e| e.map_positions(f));
+            }
+            Self::Index(x, ..)
+            | Self::Dot(x, ..)
+            | Self::And(x, ..)
+            | Self::Or(x, ..)
+            | Self::Coalesce(x, ..) => {
+                x.lhs.map_positions(f);
+                x.rhs.map_positions(f);
+            }
+            #[cfg(not(feature = "no_custom_syntax"))]
+            Self::Custom(x, ..) => x.inputs.iter_mut().for_each(|e| e.map_positions(f)),
+            _ => (),
+        }
+
+        let pos = f(self.position());
+        self.set_position(pos);
+    }
 }
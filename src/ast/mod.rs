@@ -20,8 +20,8 @@ pub use namespace::Namespace;
 #[cfg(not(feature = "no_function"))]
 pub use script_fn::{ScriptFnMetadata, ScriptFuncDef};
 pub use stmt::{
-    CaseBlocksList, FlowControl, OpAssignment, RangeCase, Stmt, StmtBlock, StmtBlockContainer,
-    SwitchCasesCollection,
+    CaseBlocksList, FlowControl, OpAssignment, Pattern, RangeCase, Stmt, StmtBlock,
+    StmtBlockContainer, SwitchCasesCollection,
 };
 
 /// _(internals)_ Empty placeholder for a script-defined function.
@@ -18,6 +18,7 @@ use std::{fmt, num::NonZeroUsize};
 /// A [`StaticVec`] is used because the vast majority of namespace-qualified access contains only
 /// one level, and it is wasteful to always allocate a [`Vec`] with one element.
 #[derive(Clone, Eq, PartialEq, Default, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[non_exhaustive]
 pub struct Namespace {
     /// Path segments.
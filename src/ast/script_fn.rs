@@ -2,7 +2,7 @@
 #![cfg(not(feature = "no_function"))]
 
 use super::{FnAccess, StmtBlock};
-use crate::{FnArgsVec, ImmutableString};
+use crate::{FnArgsVec, ImmutableString, Position};
 #[cfg(feature = "no_std")]
 use std::prelude::v1::*;
 use std::{fmt, hash::Hash};
@@ -10,6 +10,7 @@ use std::{fmt, hash::Hash};
 /// _(internals)_ A type containing information on a script-defined function.
 /// Exported under the `internals` feature only.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ScriptFuncDef {
     /// Function body.
     pub body: StmtBlock,
@@ -23,6 +24,17 @@ pub struct ScriptFuncDef {
     pub this_type: Option<ImmutableString>,
     /// Names of function parameters.
     pub params: FnArgsVec<ImmutableString>,
+    /// Positions of function parameters.
+    ///
+    /// This has the same length as [`params`][ScriptFuncDef::params].
+    pub param_positions: FnArgsVec<Position>,
+    /// Type annotations of function parameters, if any.
+    ///
+    /// This is empty if no parameter is annotated. Otherwise, it has the same length as
+    /// [`params`][ScriptFuncDef::params], with [`None`] for each parameter without an annotation.
+    pub param_types: FnArgsVec<Option<ImmutableString>>,
+    /// Type annotation of the return value, if any.
+    pub return_type: Option<ImmutableString>,
     /// _(metadata)_ Function doc-comments (if any). Exported under the `metadata` feature only.
     ///
     /// Doc-comments are comment lines beginning with `///` or comment blocks beginning with `/**`,
@@ -53,6 +65,9 @@ impl ScriptFuncDef {
             #[cfg(not(feature = "no_object"))]
             this_type: self.this_type.clone(),
             params: self.params.clone(),
+            param_positions: self.param_positions.clone(),
+            param_types: self.param_types.clone(),
+            return_type: self.return_type.clone(),
             #[cfg(feature = "metadata")]
             comments: <_>::default(),
         }
@@ -81,10 +96,19 @@ impl fmt::Display for ScriptFuncDef {
             self.name,
             self.params
                 .iter()
-                .map(ImmutableString::as_str)
+                .enumerate()
+                .map(|(i, p)| match self.param_types.get(i) {
+                    Some(Some(t)) => format!("{p}: {t}"),
+                    _ => p.to_string(),
+                })
                 .collect::<FnArgsVec<_>>()
                 .join(", ")
-        )
+        )?;
+
+        match self.return_type {
+            Some(ref t) => write!(f, " -> {t}"),
+            None => Ok(()),
+        }
     }
 }
 
@@ -119,6 +143,21 @@ pub struct ScriptFnMetadata<'a> {
         serde(default, skip_serializing_if = "Option::is_none")
     )]
     pub this_type: Option<&'a str>,
+    /// Type annotations of function parameters (if any).
+    ///
+    /// This is empty if no parameter is annotated. Otherwise, it has the same length as
+    /// [`params`][ScriptFnMetadata::params], with [`None`] for each parameter without an annotation.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub param_types: Vec<Option<&'a str>>,
+    /// Type annotation of the return value, if any.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub return_type: Option<&'a str>,
     /// _(metadata)_ Function doc-comments (if any).
     /// Exported under the `metadata` feature only.
     ///
@@ -160,10 +199,19 @@ impl fmt::Display for ScriptFnMetadata<'_> {
             self.name,
             self.params
                 .iter()
-                .copied()
+                .enumerate()
+                .map(|(i, &p)| match self.param_types.get(i) {
+                    Some(Some(t)) => format!("{p}: {t}"),
+                    _ => p.to_string(),
+                })
                 .collect::<FnArgsVec<_>>()
                 .join(", ")
-        )
+        )?;
+
+        match self.return_type {
+            Some(t) => write!(f, " -> {t}"),
+            None => Ok(()),
+        }
     }
 }
 
@@ -176,6 +224,8 @@ impl<'a> From<&'a ScriptFuncDef> for ScriptFnMetadata<'a> {
             access: value.access,
             #[cfg(not(feature = "no_object"))]
             this_type: value.this_type.as_deref(),
+            param_types: value.param_types.iter().map(Option::as_deref).collect(),
+            return_type: value.return_type.as_deref(),
             #[cfg(feature = "metadata")]
             comments: value.comments.iter().map(<_>::as_ref).collect(),
         }
@@ -2,11 +2,11 @@
 
 use super::{ASTFlags, ASTNode, BinaryExpr, Expr, FnCallExpr, Ident};
 use crate::engine::{KEYWORD_EVAL, OP_EQUALS};
-use crate::func::StraightHashMap;
+use crate::func::{hashing::get_hasher, StraightHashMap};
 use crate::tokenizer::Token;
 use crate::types::dynamic::Union;
 use crate::types::Span;
-use crate::{calc_fn_hash, Dynamic, FnArgsVec, Position, StaticVec, INT};
+use crate::{calc_fn_hash, Dynamic, FnArgsVec, ImmutableString, Position, StaticVec, INT};
 #[cfg(feature = "no_std")]
 use std::prelude::v1::*;
 use std::{
@@ -182,6 +182,7 @@ impl fmt::Debug for OpAssignment {
 /// _(internals)_ A type containing a range case for a `switch` statement.
 /// Exported under the `internals` feature only.
 #[derive(Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum RangeCase {
     /// Exclusive range.
     ExclusiveInt(Range<INT>, usize),
@@ -325,18 +326,342 @@ impl RangeCase {
     }
 }
 
+/// _(internals)_ A pattern in a `switch` case or a destructuring variable definition, which
+/// matches a value and binds parts of it to variables.
+/// Exported under the `internals` feature only.
+#[derive(Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[allow(clippy::type_complexity)]
+pub enum Pattern {
+    /// `_`, matching any value.
+    Wildcard(Position),
+    /// A variable name, matching any value and binding it to the variable.
+    Bind(Ident),
+    /// A literal constant, matching values that are equal to it.
+    Constant(
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde::ast::boxed_constant"))]
+        Box<Dynamic>,
+        Position,
+    ),
+    /// `Type::Variant(pattern, ...)`, matching a variant of a script-defined enum type and its
+    /// payload.
+    Variant(
+        Box<(ImmutableString, ImmutableString, StaticVec<Pattern>)>,
+        Position,
+    ),
+    /// `[pattern, ...]`, matching an array item by item.
+    ///
+    /// If the pattern ends with `..` or `..rest` (the second value is [`Some`]), the array may
+    /// contain more items, which are matched (as an array) against the rest pattern.
+    #[cfg(not(feature = "no_index"))]
+    Array(Box<(StaticVec<Pattern>, Option<Pattern>)>, Position),
+    /// `#{prop: pattern, ...}`, matching an object map (or a script-defined record) property by
+    /// property.
+    ///
+    /// If the pattern ends with `..` or `..rest` (the second value is [`Some`]), the object map
+    /// may contain more properties, which are matched (as an object map) against the rest pattern.
+    #[cfg(not(feature = "no_object"))]
+    Map(
+        Box<(StaticVec<(Ident, Pattern)>, Option<Pattern>)>,
+        Position,
+    ),
+}
+
+impl fmt::Debug for Pattern {
+    #[cold]
+    #[inline(never)]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Wildcard(..) => f.write_str("_"),
+            Self::Bind(x) => f.write_str(&x.name),
+            Self::Constant(x, ..) => write!(f, "{x:?}"),
+            Self::Variant(x, ..) => {
+                write!(f, "{}::{}", x.0, x.1)?;
+                if !x.2.is_empty() {
+                    let mut t = f.debug_tuple("");
+                    x.2.iter().for_each(|p| {
+                        t.field(p);
+                    });
+                    t.finish()?;
+                }
+                Ok(())
+            }
+            #[cfg(not(feature = "no_index"))]
+            Self::Array(x, ..) => {
+                let mut list = f.debug_list();
+                list.entries(x.0.iter());
+                match x.1 {
+                    Some(Self::Bind(ref id)) => list.entry(&format_args!("..{}", id.name)),
+                    Some(..) => list.entry(&format_args!("..")),
+                    None => &mut list,
+                };
+                list.finish()
+            }
+            #[cfg(not(feature = "no_object"))]
+            Self::Map(x, ..) => {
+                f.write_str("#")?;
+                let mut map = f.debug_map();
+                x.0.iter().for_each(|(id, p)| {
+                    map.entry(&format_args!("{}", id.name), p);
+                });
+                match x.1 {
+                    Some(Self::Bind(ref id)) => {
+                        map.entry(&format_args!(".."), &format_args!("{}", id.name))
+                    }
+                    Some(..) => map.entry(&format_args!(".."), &format_args!("_")),
+                    None => &mut map,
+                };
+                map.finish()
+            }
+        }
+    }
+}
+
+impl Pattern {
+    /// Get the [position][Position] of this pattern.
+    #[must_use]
+    pub const fn position(&self) -> Position {
+        match self {
+            Self::Bind(x) => x.pos,
+
+            Self::Wildcard(pos) | Self::Constant(.., pos) | Self::Variant(.., pos) => *pos,
+
+            #[cfg(not(feature = "no_index"))]
+            Self::Array(.., pos) => *pos,
+            #[cfg(not(feature = "no_object"))]
+            Self::Map(.., pos) => *pos,
+        }
+    }
+    /// Is this pattern `_`?
+    #[inline(always)]
+    #[must_use]
+    pub const fn is_wildcard(&self) -> bool {
+        matches!(self, Self::Wildcard(..))
+    }
+    /// Does this pattern match any value?
+    #[inline(always)]
+    #[must_use]
+    pub const fn is_irrefutable(&self) -> bool {
+        matches!(self, Self::Wildcard(..) | Self::Bind(..))
+    }
+    /// Get the literal value of this pattern, if it contains only literal constants.
+    ///
+    /// Returns [`None`] if the pattern contains any wildcard, variable, enum variant or `..`.
+    #[must_use]
+    pub fn get_literal_value(&self) -> Option<Dynamic> {
+        match self {
+            Self::Constant(x, ..) => Some(x.as_ref().clone()),
+
+            #[cfg(not(feature = "no_index"))]
+            Self::Array(x, ..) if x.1.is_none() => {
+                let mut array = crate::Array::with_capacity(x.0.len());
+                for p in &x.0 {
+                    array.push(p.get_literal_value()?);
+                }
+                Some(array.into())
+            }
+            #[cfg(not(feature = "no_object"))]
+            Self::Map(x, ..) if x.1.is_none() => {
+                let mut map = crate::Map::new();
+                for (id, p) in &x.0 {
+                    map.insert(id.name.as_str().into(), p.get_literal_value()?);
+                }
+                Some(map.into())
+            }
+
+            _ => None,
+        }
+    }
+    /// Get all the variables bound by this pattern, sorted by name.
+    #[must_use]
+    pub fn variables(&self) -> StaticVec<&Ident> {
+        fn collect<'a>(pattern: &'a Pattern, list: &mut StaticVec<&'a Ident>) {
+            match pattern {
+                Pattern::Wildcard(..) | Pattern::Constant(..) => (),
+                Pattern::Bind(x) => list.push(x),
+                Pattern::Variant(x, ..) => x.2.iter().for_each(|p| collect(p, list)),
+                #[cfg(not(feature = "no_index"))]
+                Pattern::Array(x, ..) => x.0.iter().chain(&x.1).for_each(|p| collect(p, list)),
+                #[cfg(not(feature = "no_object"))]
+                Pattern::Map(x, ..) => {
+                    x.0.iter().for_each(|(.., p)| collect(p, list));
+                    x.1.iter().for_each(|p| collect(p, list));
+                }
+            }
+        }
+
+        let mut list = StaticVec::new_const();
+        collect(self, &mut list);
+        list.sort_by(|a, b| a.name.cmp(&b.name));
+        list
+    }
+    /// Recursively map all the [positions][Position] within this pattern.
+    #[cfg(not(feature = "no_position"))]
+    pub(crate) fn map_positions(&mut self, f: &mut impl FnMut(Position) -> Position) {
+        match self {
+            Self::Bind(x) => x.pos = f(x.pos),
+            Self::Wildcard(pos) | Self::Constant(.., pos) => *pos = f(*pos),
+            Self::Variant(x, pos) => {
+                *pos = f(*pos);
+                x.2.iter_mut().for_each(|p| p.map_positions(f));
+            }
+            #[cfg(not(feature = "no_index"))]
+            Self::Array(x, pos) => {
+                *pos = f(*pos);
+                x.0.iter_mut()
+                    .chain(&mut x.1)
+                    .for_each(|p| p.map_positions(f));
+            }
+            #[cfg(not(feature = "no_object"))]
+            Self::Map(x, pos) => {
+                *pos = f(*pos);
+                x.0.iter_mut().for_each(|(id, p)| {
+                    id.pos = f(id.pos);
+                    p.map_positions(f);
+                });
+                x.1.iter_mut().for_each(|p| p.map_positions(f));
+            }
+        }
+    }
+    /// Match a value against this pattern.
+    ///
+    /// Returns the values bound to the pattern's variables, in the same order as
+    /// [`variables`][Pattern::variables], or [`None`] if the value does not match.
+    #[must_use]
+    pub fn match_value(&self, value: &Dynamic) -> Option<StaticVec<(ImmutableString, Dynamic)>> {
+        let mut bindings = StaticVec::new_const();
+
+        if !self.match_value_with(value, &mut bindings) {
+            return None;
+        }
+
+        bindings.sort_by(|(a, ..), (b, ..)| a.cmp(b));
+        Some(bindings)
+    }
+    /// Match a value against this pattern, collecting bound values.
+    fn match_value_with(
+        &self,
+        value: &Dynamic,
+        bindings: &mut StaticVec<(ImmutableString, Dynamic)>,
+    ) -> bool {
+        #[cfg(not(feature = "no_closure"))]
+        if value.is_shared() {
+            return self.match_value_with(&value.flatten_clone(), bindings);
+        }
+
+        match self {
+            Self::Wildcard(..) => true,
+            Self::Bind(x) => {
+                bindings.push((x.name.clone(), value.clone()));
+                true
+            }
+            Self::Constant(x, ..) => {
+                if !value.is_hashable() {
+                    return false;
+                }
+                let hasher = &mut get_hasher();
+                value.hash(hasher);
+                let hash = hasher.finish();
+                let hasher = &mut get_hasher();
+                x.hash(hasher);
+                hash == hasher.finish()
+            }
+            Self::Variant(x, ..) => {
+                let (type_name, variant, patterns) = &**x;
+
+                value.downcast_ref::<crate::EnumValue>().map_or(false, |v| {
+                    v.type_name() == type_name.as_str()
+                        && v.variant() == variant.as_str()
+                        && v.values().len() == patterns.len()
+                        && patterns
+                            .iter()
+                            .zip(v.values())
+                            .all(|(p, v)| p.match_value_with(v, bindings))
+                })
+            }
+            #[cfg(not(feature = "no_index"))]
+            Self::Array(x, ..) => {
+                let (patterns, rest) = &**x;
+
+                value.as_array_ref().map_or(false, |array| {
+                    let len = patterns.len();
+
+                    match rest {
+                        Some(_) if array.len() < len => return false,
+                        Some(p) if !p.is_wildcard() => {
+                            let others = array[len..].to_vec();
+                            if !p.match_value_with(&others.into(), bindings) {
+                                return false;
+                            }
+                        }
+                        Some(_) => (),
+                        None if array.len() != len => return false,
+                        None => (),
+                    }
+
+                    patterns
+                        .iter()
+                        .zip(array.iter())
+                        .all(|(p, v)| p.match_value_with(v, bindings))
+                })
+            }
+            #[cfg(not(feature = "no_object"))]
+            Self::Map(x, ..) => {
+                let (props, rest) = &**x;
+
+                let mut match_map = |map: &crate::Map| {
+                    match rest {
+                        Some(p) if !p.is_wildcard() => {
+                            let others = map
+                                .iter()
+                                .filter(|(k, ..)| !props.iter().any(|(id, ..)| id.name == **k))
+                                .map(|(k, v)| (k.clone(), v.clone()))
+                                .collect::<crate::Map>();
+                            if !p.match_value_with(&others.into(), bindings) {
+                                return false;
+                            }
+                        }
+                        Some(_) => (),
+                        None if map.len() != props.len() => return false,
+                        None => (),
+                    }
+
+                    props.iter().all(|(id, p)| {
+                        map.get(id.name.as_str())
+                            .map_or(false, |v| p.match_value_with(v, bindings))
+                    })
+                };
+
+                if let Ok(map) = value.as_map_ref() {
+                    match_map(&map)
+                } else if let Some(record) = value.downcast_ref::<crate::Record>() {
+                    match_map(record.fields())
+                } else {
+                    false
+                }
+            }
+        }
+    }
+}
+
 pub type CaseBlocksList = smallvec::SmallVec<[usize; 2]>;
 
 /// _(internals)_ A type containing all cases for a `switch` statement.
 /// Exported under the `internals` feature only.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SwitchCasesCollection {
     /// List of conditional expressions: LHS = condition, RHS = expression.
     pub expressions: FnArgsVec<BinaryExpr>,
     /// Dictionary mapping value hashes to [`CaseBlocksList`]'s.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde::ast::case_blocks"))]
     pub cases: StraightHashMap<CaseBlocksList>,
     /// List of range cases.
     pub ranges: StaticVec<RangeCase>,
+    /// List of pattern cases, each with the index to the list of expressions.
+    ///
+    /// Pattern cases are tried in order when no value or range case matches.
+    pub patterns: StaticVec<(Pattern, usize)>,
     /// Statements block for the default case (there can be no condition for the default case).
     pub def_case: Option<usize>,
 }
@@ -350,6 +675,7 @@ impl Hash for SwitchCasesCollection {
         self.cases.iter().for_each(|kv| kv.hash(state));
 
         self.ranges.hash(state);
+        self.patterns.hash(state);
         self.def_case.hash(state);
     }
 }
@@ -375,6 +701,7 @@ pub type StmtBlockContainer = crate::StaticVec<Stmt>;
 /// _(internals)_ A scoped block of statements.
 /// Exported under the `internals` feature only.
 #[derive(Clone, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct StmtBlock {
     /// List of [statements][Stmt].
     block: StmtBlockContainer,
@@ -477,6 +804,12 @@ impl StmtBlock {
     pub fn set_position(&mut self, start_pos: Position, end_pos: Position) {
         self.span = Span::new(start_pos, end_pos);
     }
+    /// Recursively map all the [positions][Position] within this statements block.
+    #[cfg(not(feature = "no_position"))]
+    pub(crate) fn map_positions(&mut self, f: &mut impl FnMut(Position) -> Position) {
+        self.block.iter_mut().for_each(|stmt| stmt.map_positions(f));
+        self.span = Span::new(f(self.span.start()), f(self.span.end()));
+    }
 }
 
 impl Borrow<[Stmt]> for StmtBlock {
@@ -573,6 +906,7 @@ impl Extend<Stmt> for StmtBlock {
 ///
 /// Exported under the `internals` feature only.
 #[derive(Debug, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FlowControl {
     /// Flow control expression.
     pub expr: Expr,
@@ -585,6 +919,7 @@ pub struct FlowControl {
 /// _(internals)_ A statement.
 /// Exported under the `internals` feature only.
 #[derive(Debug, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[non_exhaustive]
 #[allow(clippy::type_complexity)]
 pub enum Stmt {
@@ -613,13 +948,33 @@ pub enum Stmt {
     Do(Box<FlowControl>, ASTFlags, Position),
     /// `for` `(` id `,` counter `)` `in` expr `{` stmt `}`
     For(Box<(Ident, Option<Ident>, FlowControl)>, Position),
-    /// \[`export`\] `let`|`const` id `=` expr
+    /// \[`export`\] `let`|`const` id \[`:` type\] `=` expr
+    ///
+    /// ### Data Structure
+    ///
+    /// 0) Variable name
+    /// 1) Initial value
+    /// 2) Offset of an existing variable of the same name to reuse, if any
+    /// 3) Type annotation, if any
     ///
     /// ### Flags
     ///
     /// * [`EXPORTED`][ASTFlags::EXPORTED] = `export`  
     /// * [`CONSTANT`][ASTFlags::CONSTANT] = `const`
-    Var(Box<(Ident, Expr, Option<NonZeroUsize>)>, ASTFlags, Position),
+    Var(
+        Box<(Ident, Expr, Option<NonZeroUsize>, Option<ImmutableString>)>,
+        ASTFlags,
+        Position,
+    ),
+    /// \[`export`\] `let`|`const` pattern `=` expr
+    ///
+    /// A destructuring variable definition, binding all the variables in the pattern.
+    ///
+    /// ### Flags
+    ///
+    /// * [`EXPORTED`][ASTFlags::EXPORTED] = `export`  
+    /// * [`CONSTANT`][ASTFlags::CONSTANT] = `const`
+    Destructure(Box<(Pattern, Expr)>, ASTFlags, Position),
     /// expr op`=` expr
     Assignment(Box<(OpAssignment, BinaryExpr)>),
     /// func `(` expr `,` ... `)`
@@ -691,6 +1046,7 @@ impl Stmt {
         match self {
             Self::Do(_, options, _)
             | Self::Var(_, options, _)
+            | Self::Destructure(_, options, _)
             | Self::BreakLoop(_, options, _)
             | Self::Return(_, options, _) => *options,
 
@@ -726,6 +1082,7 @@ impl Stmt {
             | Self::For(.., pos)
             | Self::Return(.., pos)
             | Self::Var(.., pos)
+            | Self::Destructure(.., pos)
             | Self::TryCatch(.., pos) => *pos,
 
             Self::Assignment(x) => x.0.pos,
@@ -756,6 +1113,7 @@ impl Stmt {
             | Self::For(.., pos)
             | Self::Return(.., pos)
             | Self::Var(.., pos)
+            | Self::Destructure(.., pos)
             | Self::TryCatch(.., pos) => *pos = new_pos,
 
             Self::Assignment(x) => x.0.pos = new_pos,
@@ -793,7 +1151,11 @@ impl Stmt {
             | Self::For(..)
             | Self::TryCatch(..) => false,
 
-            Self::Var(..) | Self::Assignment(..) | Self::BreakLoop(..) | Self::Return(..) => false,
+            Self::Var(..)
+            | Self::Destructure(..)
+            | Self::Assignment(..)
+            | Self::BreakLoop(..)
+            | Self::Return(..) => false,
 
             #[cfg(not(feature = "no_module"))]
             Self::Import(..) | Self::Export(..) => false,
@@ -823,6 +1185,7 @@ impl Stmt {
             },
 
             Self::Var(..)
+            | Self::Destructure(..)
             | Self::Assignment(..)
             | Self::FnCall(..)
             | Self::Do(..)
@@ -860,6 +1223,10 @@ impl Stmt {
                         let block = &sw.expressions[r.index()];
                         block.lhs.is_pure() && block.rhs.is_pure()
                     })
+                    && sw.patterns.iter().all(|&(.., index)| {
+                        let block = &sw.expressions[index];
+                        block.lhs.is_pure() && block.rhs.is_pure()
+                    })
                     && sw.def_case.is_some()
                     && sw.expressions[sw.def_case.unwrap()].rhs.is_pure()
             }
@@ -880,7 +1247,9 @@ impl Stmt {
             // so infinite loops can never occur.
             Self::For(x, ..) => x.2.expr.is_pure() && x.2.body.iter().all(Self::is_pure),
 
-            Self::Var(..) | Self::Assignment(..) | Self::FnCall(..) => false,
+            Self::Var(..) | Self::Destructure(..) | Self::Assignment(..) | Self::FnCall(..) => {
+                false
+            }
             Self::Block(block, ..) => block.iter().all(Self::is_pure),
             Self::BreakLoop(..) | Self::Return(..) => false,
             Self::TryCatch(x, ..) => {
@@ -909,7 +1278,7 @@ impl Stmt {
     #[must_use]
     pub fn is_block_dependent(&self) -> bool {
         match self {
-            Self::Var(..) => true,
+            Self::Var(..) | Self::Destructure(..) => true,
 
             Self::Expr(e) => match &**e {
                 Expr::Stmt(s) => s.iter().all(Self::is_block_dependent),
@@ -941,6 +1310,7 @@ impl Stmt {
     pub fn is_internally_pure(&self) -> bool {
         match self {
             Self::Var(x, ..) => x.1.is_pure(),
+            Self::Destructure(..) => false,
 
             Self::Expr(e) => match &**e {
                 Expr::Stmt(s) => s.iter().all(Self::is_internally_pure),
@@ -991,6 +1361,7 @@ impl Stmt {
                     return false;
                 }
             }
+            Self::Destructure(x, ..) if !x.1.walk(path, on_node) => return false,
             Self::If(x, ..) => {
                 if !x.expr.walk(path, on_node) {
                     return false;
@@ -1034,6 +1405,16 @@ impl Stmt {
                         return false;
                     }
                 }
+                for &(.., index) in &sw.patterns {
+                    let block = &sw.expressions[index];
+
+                    if !block.lhs.walk(path, on_node) {
+                        return false;
+                    }
+                    if !block.rhs.walk(path, on_node) {
+                        return false;
+                    }
+                }
                 if let Some(index) = sw.def_case {
                     if !sw.expressions[index].lhs.walk(path, on_node) {
                         return false;
@@ -1117,4 +1498,72 @@ impl Stmt {
 
         true
     }
+    /// Recursively map all the [positions][Position] within this statement.
+    #[cfg(not(feature = "no_position"))]
+    pub(crate) fn map_positions(&mut self, f: &mut impl FnMut(Position) -> Position) {
+        match self {
+            Self::If(x, ..) | Self::While(x, ..) | Self::Do(x, ..) | Self::TryCatch(x, ..) => {
+                x.expr.map_positions(f);
+                x.body.map_positions(f);
+                x.branch.map_positions(f);
+            }
+            Self::Switch(x, ..) => {
+                x.0.map_positions(f);
+                for block in &mut x.1.expressions {
+                    block.lhs.map_positions(f);
+                    block.rhs.map_positions(f);
+                }
+                for (pattern, ..) in &mut x.1.patterns {
+                    pattern.map_positions(f);
+                }
+            }
+            Self::For(x, ..) => {
+                x.0.pos = f(x.0.pos);
+                if let Some(ref mut counter) = x.1 {
+                    counter.pos = f(counter.pos);
+                }
+                x.2.expr.map_positions(f);
+                x.2.body.map_positions(f);
+            }
+            Self::Var(x, ..) => {
+                x.0.pos = f(x.0.pos);
+                x.1.map_positions(f);
+            }
+            Self::Destructure(x, ..) => {
+                x.0.map_positions(f);
+                x.1.map_positions(f);
+            }
+            Self::Assignment(x) => {
+                x.1.lhs.map_positions(f);
+                x.1.rhs.map_positions(f);
+            }
+            Self::FnCall(x, ..) => {
+                #[cfg(not(feature = "no_module"))]
+                x.namespace
+                    .path
+                    .iter_mut()
+                    .for_each(|id| id.pos = f(id.pos));
+                x.args.iter_mut().for_each(|e| e.map_positions(f));
+            }
+            Self::Block(x) => return x.map_positions(f),
+            Self::Expr(e) => return e.map_positions(f),
+            Self::BreakLoop(Some(e), ..) | Self::Return(Some(e), ..) => e.map_positions(f),
+            #[cfg(not(feature = "no_module"))]
+            Self::Import(x, ..) => {
+                x.0.map_positions(f);
+                x.1.pos = f(x.1.pos);
+            }
+            #[cfg(not(feature = "no_module"))]
+            Self::Export(x, ..) => {
+                x.0.pos = f(x.0.pos);
+                x.1.pos = f(x.1.pos);
+            }
+            #[cfg(not(feature = "no_closure"))]
+            Self::Share(x) => return x.iter_mut().for_each(|(id, ..)| id.pos = f(id.pos)),
+            _ => (),
+        }
+
+        let pos = f(self.position());
+        self.set_position(pos);
+    }
 }
@@ -287,6 +287,7 @@ fn debug_callback(
                 err
             )
         }
+        DebuggerEvent::Exception(err) => println!("\x1b[31m! Exception: {err}\x1b[39m"),
         _ => unreachable!(),
     }
 
@@ -591,7 +592,100 @@ fn debug_callback(
     }
 }
 
+/// Run as a Debug Adapter Protocol server over stdio.
+///
+/// The script to debug is the `program` argument of the `launch` request.
+#[cfg(feature = "metadata")]
+fn run_dap() {
+    let server = rhai::debugger::DapServer::stdio();
+
+    let filename = match server.wait_for_launch() {
+        Ok(Some(filename)) => filename,
+        Ok(None) => {
+            let _ = server.output("stderr", "No script file specified.\n");
+            let _ = server.finish(1);
+            exit(1);
+        }
+        Err(err) => {
+            eprintln!("{err}");
+            exit(1);
+        }
+    };
+
+    // Initialize scripting engine
+    let mut engine = Engine::new();
+
+    #[cfg(not(feature = "no_optimize"))]
+    engine.set_optimization_level(rhai::OptimizationLevel::None);
+
+    // Redirect output to the client
+    let output = server.clone();
+    engine.on_print(move |s| {
+        let _ = output.output("stdout", &format!("{s}\n"));
+    });
+    let output = server.clone();
+    engine.on_debug(move |s, src, pos| {
+        let msg = match src {
+            Some(src) => format!("{src} @ {pos:?} | {s}\n"),
+            None => format!("{pos:?} | {s}\n"),
+        };
+        let _ = output.output("console", &msg);
+    });
+
+    // Set a file module resolver without caching
+    #[cfg(not(feature = "no_module"))]
+    #[cfg(not(feature = "no_std"))]
+    {
+        let mut resolver = rhai::module_resolvers::FileModuleResolver::new();
+        resolver.enable_cache(false);
+        engine.set_module_resolver(resolver);
+    }
+
+    let ast = std::fs::read_to_string(&filename)
+        .map_err(|err| format!("Error reading script file: {filename}\n{err}"))
+        .and_then(|contents| {
+            let script = if contents.starts_with("#!") {
+                // Skip shebang
+                &contents[contents.find('\n').unwrap_or(0)..]
+            } else {
+                &contents[..]
+            };
+            engine.compile(script).map_err(|err| err.to_string())
+        });
+
+    let exit_code = match ast {
+        Ok(mut ast) => {
+            // Line break-points are matched against the source
+            ast.set_source(filename);
+
+            #[allow(deprecated)]
+            engine.register_dap_server(&server);
+
+            match engine.run_ast(&ast) {
+                Ok(..) => 0,
+                Err(err) if matches!(*err, EvalAltResult::ErrorTerminated(..)) => 0,
+                Err(err) => {
+                    let _ = server.output("stderr", &format!("{err}\n"));
+                    1
+                }
+            }
+        }
+        Err(err) => {
+            let _ = server.output("stderr", &format!("{err}\n"));
+            1
+        }
+    };
+
+    let _ = server.finish(exit_code);
+}
+
 fn main() {
+    #[cfg(feature = "metadata")]
+    if env::args().nth(1).as_deref() == Some("--dap") {
+        run_dap();
+        return;
+    }
+
     let title = format!("Rhai Debugger (version {})", env!("CARGO_PKG_VERSION"));
     println!("{title}");
     println!("{0:=<1$}", "", title.len());
@@ -0,0 +1,164 @@
+//! A formatter for Rhai scripts.
+//!
+//! Formats each file passed to it in place, or standard input to standard output if no file is given.
+//! With `--check`, files are not changed; instead the names of files that are not formatted are printed
+//! and the process exits with a non-zero status.
+
+#[cfg(not(feature = "no_position"))]
+use rhai::{Engine, FormatOptions};
+
+use std::{env, process::exit};
+
+#[cfg(not(feature = "no_position"))]
+use std::{
+    fs,
+    io::{self, Read},
+};
+
+fn print_help() {
+    println!("Usage: rhai-fmt [--check] [--width <columns>] [--indent <spaces>] [files...]");
+    println!();
+    println!("Options:");
+    println!("  --check            Do not write any file; exit with an error if any file is not formatted");
+    println!("  --width <columns>  Maximum line width (default: 100)");
+    println!("  --indent <spaces>  Number of spaces for each indentation level (default: 4)");
+    println!("  -h, --help         Print this help message");
+    println!();
+    println!("If no file is given, the script is read from standard input and written to standard output.");
+}
+
+/// Format a script, keeping any shebang line as-is.
+#[cfg(not(feature = "no_position"))]
+fn format(engine: &Engine, contents: &str, options: FormatOptions) -> Result<String, String> {
+    let (shebang, script) = if contents.starts_with("#!") {
+        contents.split_at(contents.find('\n').map_or(contents.len(), |n| n + 1))
+    } else {
+        ("", contents)
+    };
+
+    engine
+        .format_script_with_options(script, options)
+        .map(|formatted| format!("{shebang}{formatted}"))
+        .map_err(|err| err.to_string())
+}
+
+#[cfg(not(feature = "no_position"))]
+fn main() {
+    let mut check = false;
+    let mut options = FormatOptions::new();
+    let mut files = Vec::new();
+
+    let mut args = env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--check" => check = true,
+            "--width" | "--indent" => {
+                let value = match args.next().and_then(|v| v.parse::<usize>().ok()) {
+                    Some(value) if value > 0 => value,
+                    _ => {
+                        eprintln!("Option {arg} requires a positive number");
+                        exit(2);
+                    }
+                };
+                options = if arg == "--width" {
+                    options.max_width(value)
+                } else {
+                    options.indent(value)
+                };
+            }
+            "-h" | "--help" => {
+                print_help();
+                return;
+            }
+            _ if arg.starts_with('-') => {
+                eprintln!("Unknown option: {arg}");
+                print_help();
+                exit(2);
+            }
+            _ => files.push(arg),
+        }
+    }
+
+    let engine = Engine::new();
+
+    if files.is_empty() {
+        let mut contents = String::new();
+
+        if let Err(err) = io::stdin().read_to_string(&mut contents) {
+            eprintln!("Error reading standard input\n{err}");
+            exit(2);
+        }
+
+        match format(&engine, &contents, options) {
+            Ok(formatted) if check => {
+                if formatted != contents {
+                    println!("<stdin>");
+                    exit(1);
+                }
+            }
+            Ok(formatted) => print!("{formatted}"),
+            Err(err) => {
+                eprintln!("<stdin>: {err}");
+                exit(2);
+            }
+        }
+        return;
+    }
+
+    let mut unformatted = false;
+    let mut failed = false;
+
+    for filename in files {
+        let contents = match fs::read_to_string(&filename) {
+            Ok(contents) => contents,
+            Err(err) => {
+                eprintln!("Error reading script file: {filename}\n{err}");
+                failed = true;
+                continue;
+            }
+        };
+
+        let formatted = match format(&engine, &contents, options) {
+            Ok(formatted) => formatted,
+            Err(err) => {
+                eprintln!("{filename}: {err}");
+                failed = true;
+                continue;
+            }
+        };
+
+        if formatted == contents {
+            continue;
+        }
+
+        if check {
+            println!("{filename}");
+            unformatted = true;
+        } else if let Err(err) = fs::write(&filename, formatted) {
+            eprintln!("Error writing script file: {filename}\n{err}");
+            failed = true;
+        }
+    }
+
+    if failed {
+        exit(2);
+    }
+    if unformatted {
+        exit(1);
+    }
+}
+
+#[cfg(feature = "no_position")]
+fn main() {
+    if env::args()
+        .skip(1)
+        .any(|arg| arg == "-h" || arg == "--help")
+    {
+        print_help();
+        return;
+    }
+
+    eprintln!("rhai-fmt is not available under the `no_position` feature.");
+    exit(2);
+}
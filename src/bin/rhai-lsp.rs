@@ -0,0 +1,814 @@
+//! A simple language server for Rhai scripts, speaking the Language Server Protocol over stdio.
+
+use rhai::{Engine, Position, Token, AST};
+use serde_json::{json, Value};
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    convert::TryFrom,
+    io::{self, BufRead, Write},
+    process::exit,
+};
+
+#[cfg(not(feature = "no_module"))]
+use rhai::module_resolvers::FileModuleResolver;
+#[cfg(not(feature = "no_module"))]
+use std::path::{Path, PathBuf};
+
+/// Keywords offered as completions.
+const KEYWORDS: &[&str] = &[
+    "let",
+    "const",
+    "if",
+    "else",
+    "switch",
+    "do",
+    "while",
+    "until",
+    "loop",
+    "for",
+    "in",
+    "break",
+    "continue",
+    "return",
+    "throw",
+    "try",
+    "catch",
+    "fn",
+    "private",
+    "import",
+    "export",
+    "as",
+    "true",
+    "false",
+    "this",
+    "global",
+    "is_shared",
+    "Fn",
+    "call",
+    "curry",
+    "type_of",
+    "print",
+    "debug",
+    "eval",
+];
+
+// LSP constants.
+const ERROR_METHOD_NOT_FOUND: i64 = -32601;
+const ERROR_INVALID_PARAMS: i64 = -32602;
+const SEVERITY_ERROR: i64 = 1;
+const KIND_FUNCTION: i64 = 3;
+const KIND_VARIABLE: i64 = 6;
+#[cfg(not(feature = "no_module"))]
+const KIND_MODULE: i64 = 9;
+const KIND_PROPERTY: i64 = 10;
+const KIND_KEYWORD: i64 = 14;
+
+/// Result of a request, with the error code and message on failure.
+type RequestResult<T> = Result<T, (i64, String)>;
+
+/// An open text document.
+struct Document {
+    /// Full text of the document.
+    text: String,
+    /// The (partial) [`AST`] compiled from the document.
+    ast: AST,
+    /// All tokens in the document, together with their starting positions.
+    tokens: Vec<(Token, Position)>,
+}
+
+/// A completion item seeded from the [`Engine`]'s definitions.
+struct Completion {
+    label: String,
+    kind: i64,
+    detail: String,
+    doc: String,
+    overloads: usize,
+}
+
+/// State of the language server.
+struct Server {
+    engine: Engine,
+    #[cfg(not(feature = "no_module"))]
+    resolver: FileModuleResolver,
+    documents: HashMap<String, Document>,
+    completions: Vec<Completion>,
+    shutdown: bool,
+}
+
+/// Read one message from the client.
+///
+/// Returns `None` when the input stream is closed.
+fn read_message(input: &mut impl BufRead) -> io::Result<Option<Value>> {
+    let mut content_length = None;
+
+    loop {
+        let mut line = String::new();
+
+        if input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+
+        let line = line.trim_end();
+
+        if line.is_empty() {
+            break;
+        }
+
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("Content-Length") {
+                content_length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+
+    let len = content_length.ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidData, "missing Content-Length header")
+    })?;
+
+    let mut buf = vec![0; len];
+    input.read_exact(&mut buf)?;
+
+    serde_json::from_slice(&buf)
+        .map(Some)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+/// Write one message to the client.
+fn write_message(output: &mut impl Write, message: &Value) -> io::Result<()> {
+    let body = message.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{body}", body.len())?;
+    output.flush()
+}
+
+/// Get the zero-based line and character offset (in chars) of a [`Position`].
+fn line_col(pos: Position) -> (usize, usize) {
+    (
+        pos.line().map_or(0, |line| line - 1),
+        pos.position().map_or(0, |col| col.saturating_sub(1)),
+    )
+}
+
+/// Convert a zero-based line and character offset (in chars) into an LSP position
+/// (which counts in UTF-16 code units).
+fn to_lsp_position(text: &str, (line, col): (usize, usize)) -> Value {
+    let character: usize = text
+        .split('\n')
+        .nth(line)
+        .map_or(0, |s| s.chars().take(col).map(char::len_utf16).sum());
+
+    json!({ "line": line, "character": character })
+}
+
+/// Convert an LSP position into a zero-based line and character offset (in chars).
+fn from_lsp_position(text: &str, position: &Value) -> Option<(usize, usize)> {
+    let line = usize::try_from(position["line"].as_u64()?).ok()?;
+    let character = usize::try_from(position["character"].as_u64()?).ok()?;
+
+    let mut units = 0;
+    let col = text
+        .split('\n')
+        .nth(line)
+        .unwrap_or("")
+        .chars()
+        .take_while(|ch| {
+            units += ch.len_utf16();
+            units <= character
+        })
+        .count();
+
+    Some((line, col))
+}
+
+/// Make an LSP range starting at a position and spanning a number of chars on the same line.
+fn to_lsp_range(text: &str, start: (usize, usize), len: usize) -> Value {
+    json!({
+        "start": to_lsp_position(text, start),
+        "end": to_lsp_position(text, (start.0, start.1 + len)),
+    })
+}
+
+/// Convert a `file://` URI into a file path.
+#[cfg(not(feature = "no_module"))]
+fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    let path = uri.strip_prefix("file://")?;
+    let mut bytes = Vec::with_capacity(path.len());
+    let mut iter = path.bytes();
+
+    while let Some(b) = iter.next() {
+        if b == b'%' {
+            let hex = [iter.next()?, iter.next()?];
+            let hex = std::str::from_utf8(&hex).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+        } else {
+            bytes.push(b);
+        }
+    }
+
+    let path = String::from_utf8(bytes).ok()?;
+
+    // Windows paths look like `/C:/...`
+    match path.as_bytes() {
+        [b'/', _, b':', ..] => Some(PathBuf::from(&path[1..])),
+        _ => Some(PathBuf::from(path)),
+    }
+}
+
+/// Convert a file path into a `file://` URI.
+#[cfg(not(feature = "no_module"))]
+fn path_to_uri(path: &Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    let mut uri = String::from("file://");
+
+    if !path.starts_with('/') {
+        uri.push('/');
+    }
+
+    for ch in path.chars() {
+        match ch {
+            ' ' => uri.push_str("%20"),
+            '%' => uri.push_str("%25"),
+            '#' => uri.push_str("%23"),
+            '?' => uri.push_str("%3F"),
+            _ => uri.push(ch),
+        }
+    }
+
+    uri
+}
+
+/// Tokenize a script.
+fn tokenize(engine: &Engine, text: &str) -> Vec<(Token, Position)> {
+    engine
+        .lex(&[text])
+        .0
+        .take_while(|(token, ..)| *token != Token::EOF)
+        .filter(|(token, ..)| !matches!(token, Token::Comment(..)))
+        .collect()
+}
+
+/// Find the index of the token at a zero-based line and character offset (in chars).
+fn token_at(tokens: &[(Token, Position)], cursor: (usize, usize)) -> Option<usize> {
+    let index = tokens
+        .iter()
+        .rposition(|&(.., pos)| line_col(pos) <= cursor)?;
+
+    let (token, pos) = &tokens[index];
+    let (line, col) = line_col(*pos);
+
+    if line != cursor.0 {
+        return None;
+    }
+
+    match token {
+        // The cursor may be placed right after the identifier.
+        Token::Identifier(name) if cursor.1 > col + name.chars().count() => None,
+        _ => Some(index),
+    }
+}
+
+/// Get the identifier name of a token.
+fn identifier(token: &Token) -> Option<&str> {
+    match token {
+        Token::Identifier(name) => Some(name.as_str()),
+        _ => None,
+    }
+}
+
+/// Find the definition of a script function in a list of tokens.
+///
+/// Returns the position of the function name.
+#[cfg(not(feature = "no_function"))]
+fn find_fn_def(tokens: &[(Token, Position)], name: &str) -> Option<Position> {
+    tokens
+        .iter()
+        .enumerate()
+        .filter(|(.., (token, ..))| *token == Token::Fn)
+        .find_map(|(index, ..)| {
+            // Skip over any `this` type (e.g. `fn Foo.bar()`) and take the last name
+            tokens[index + 1..]
+                .iter()
+                .take_while(|(token, ..)| *token != Token::LeftParen)
+                .take(4)
+                .filter_map(|(token, pos)| identifier(token).map(|n| (n, *pos)))
+                .last()
+                .filter(|&(n, ..)| n == name)
+                .map(|(.., pos)| pos)
+        })
+}
+
+/// Find the path of an `import` statement with a particular alias in a list of tokens.
+#[cfg(not(feature = "no_module"))]
+#[cfg(not(feature = "no_function"))]
+fn find_import(tokens: &[(Token, Position)], alias: &str) -> Option<String> {
+    tokens.windows(4).find_map(|w| match w {
+        [(Token::Import, ..), (Token::StringConstant(path), ..), (Token::As, ..), (Token::Identifier(name), ..)]
+            if name.as_str() == alias =>
+        {
+            Some(path.to_string())
+        }
+        _ => None,
+    })
+}
+
+/// Get the list of completions from the [`Engine`]'s definitions.
+///
+/// Overloaded functions are merged into a single completion item.
+fn definitions_completions(engine: &Engine) -> Vec<Completion> {
+    let definitions = engine.definitions().single_file();
+    let mut completions = BTreeMap::<String, Completion>::new();
+    let mut doc = Vec::new();
+
+    for line in definitions.lines().map(str::trim) {
+        if let Some(comment) = line.strip_prefix("///") {
+            doc.push(comment.strip_prefix(' ').unwrap_or(comment));
+            continue;
+        }
+
+        let signature = match line.strip_prefix("fn ") {
+            Some(signature) => signature.trim_end_matches(';'),
+            None => {
+                doc.clear();
+                continue;
+            }
+        };
+
+        let (name, kind) = if let Some(s) = signature.strip_prefix("get ") {
+            (s, KIND_PROPERTY)
+        } else if signature.starts_with("set ") {
+            doc.clear();
+            continue;
+        } else {
+            (signature, KIND_FUNCTION)
+        };
+
+        let name = name.split('(').next().unwrap_or("").trim();
+
+        if name.is_empty() {
+            doc.clear();
+            continue;
+        }
+
+        completions
+            .entry(name.to_string())
+            .and_modify(|c| {
+                c.overloads += 1;
+                if c.doc.is_empty() {
+                    c.doc = doc.join("\n");
+                }
+            })
+            .or_insert_with(|| Completion {
+                label: name.to_string(),
+                kind,
+                detail: format!("fn {signature}"),
+                doc: doc.join("\n"),
+                overloads: 0,
+            });
+
+        doc.clear();
+    }
+
+    completions.into_values().collect()
+}
+
+/// Strip the doc-comment leaders from a list of doc-comments.
+fn strip_doc_comments<'a>(comments: impl IntoIterator<Item = &'a str>) -> String {
+    comments
+        .into_iter()
+        .flat_map(|comment| {
+            if let Some(block) = comment.strip_prefix("/**") {
+                block
+                    .trim_end_matches("*/")
+                    .lines()
+                    .map(|line| {
+                        let line = line.trim_start();
+                        let line = line.strip_prefix('*').unwrap_or(line);
+                        line.strip_prefix(' ').unwrap_or(line)
+                    })
+                    .collect::<Vec<_>>()
+            } else {
+                comment
+                    .lines()
+                    .map(|line| {
+                        let line = line.trim_start();
+                        let line = line.strip_prefix("///").unwrap_or(line);
+                        line.strip_prefix(' ').unwrap_or(line)
+                    })
+                    .collect()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+/// Collect all functions with a particular name from functions metadata.
+fn collect_fn_metadata<'a>(metadata: &'a Value, name: &str, list: &mut Vec<&'a Value>) {
+    if let Some(functions) = metadata["functions"].as_array() {
+        list.extend(functions.iter().filter(|f| {
+            f["name"].as_str() == Some(name) && !f["isAnonymous"].as_bool().unwrap_or(false)
+        }));
+    }
+    if let Some(modules) = metadata["modules"].as_object() {
+        for m in modules.values() {
+            collect_fn_metadata(m, name, list);
+        }
+    }
+}
+
+/// Format the signature of a function from its metadata, with parameter types for display.
+fn format_signature(f: &Value) -> String {
+    let params = f["params"]
+        .as_array()
+        .map(|params| {
+            params
+                .iter()
+                .map(|p| match (p["name"].as_str(), p["type"].as_str()) {
+                    (Some(name), Some(typ)) => format!("{name}: {typ}"),
+                    (Some(name), None) => name.to_string(),
+                    (None, Some(typ)) => format!("_: {typ}"),
+                    (None, None) => "_".to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join(", ")
+        })
+        .unwrap_or_default();
+
+    let name = f["name"].as_str().unwrap_or("");
+
+    // Errors are thrown in scripts, so `Result<T, Box<EvalAltResult>>` simply returns `T`
+    let ret = f["returnType"].as_str().map(|ret| {
+        ret.strip_prefix("Result<")
+            .and_then(|r| r.strip_suffix(", Box<EvalAltResult>>"))
+            .unwrap_or(ret)
+    });
+
+    match ret {
+        Some(ret) if !ret.is_empty() && ret != "()" => format!("fn {name}({params}) -> {ret}"),
+        _ => format!("fn {name}({params})"),
+    }
+}
+
+impl Server {
+    /// Create a new language server.
+    fn new() -> Self {
+        let engine = Engine::new();
+        let completions = definitions_completions(&engine);
+
+        Self {
+            engine,
+            #[cfg(not(feature = "no_module"))]
+            resolver: FileModuleResolver::new(),
+            documents: HashMap::new(),
+            completions,
+            shutdown: false,
+        }
+    }
+
+    /// Handle a message from the client, returning any messages to send back.
+    fn handle(&mut self, message: &Value) -> Vec<Value> {
+        let method = message["method"].as_str().unwrap_or("");
+        let params = &message["params"];
+
+        let id = match message.get("id") {
+            Some(id) => id,
+            // Notification
+            None => {
+                return match method {
+                    "textDocument/didOpen" => {
+                        let doc = &params["textDocument"];
+                        self.update(doc["uri"].as_str(), doc["text"].as_str())
+                    }
+                    "textDocument/didChange" => {
+                        let text = params["contentChanges"]
+                            .as_array()
+                            .and_then(|changes| changes.last())
+                            .and_then(|change| change["text"].as_str());
+                        self.update(params["textDocument"]["uri"].as_str(), text)
+                    }
+                    "textDocument/didClose" => {
+                        let uri = params["textDocument"]["uri"].as_str().unwrap_or("");
+                        self.documents.remove(uri);
+                        vec![Self::diagnostics(uri, Vec::new())]
+                    }
+                    "exit" => exit(if self.shutdown { 0 } else { 1 }),
+                    _ => Vec::new(),
+                }
+            }
+        };
+
+        let result = match method {
+            "initialize" => Ok(json!({
+                "capabilities": {
+                    "textDocumentSync": 1,
+                    "hoverProvider": true,
+                    "definitionProvider": true,
+                    "completionProvider": { "triggerCharacters": [".", ":"] },
+                },
+                "serverInfo": { "name": "rhai-lsp", "version": env!("CARGO_PKG_VERSION") },
+            })),
+            "shutdown" => {
+                self.shutdown = true;
+                Ok(Value::Null)
+            }
+            "textDocument/hover" => self.hover(params),
+            "textDocument/definition" => self.definition(params),
+            "textDocument/completion" => self.completion(params),
+            _ => Err((ERROR_METHOD_NOT_FOUND, format!("Unknown method: {method}"))),
+        };
+
+        vec![match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, message)) => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": { "code": code, "message": message },
+            }),
+        }]
+    }
+
+    /// Make a `textDocument/publishDiagnostics` notification.
+    fn diagnostics(uri: &str, diagnostics: Vec<Value>) -> Value {
+        json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/publishDiagnostics",
+            "params": { "uri": uri, "diagnostics": diagnostics },
+        })
+    }
+
+    /// Compile a new version of a document, returning its diagnostics.
+    fn update(&mut self, uri: Option<&str>, text: Option<&str>) -> Vec<Value> {
+        let (Some(uri), Some(text)) = (uri, text) else {
+            return Vec::new();
+        };
+
+        let (ast, errors) = self.engine.compile_with_diagnostics(text);
+        let tokens = tokenize(&self.engine, text);
+
+        let diagnostics = errors
+            .iter()
+            .map(|err| {
+                let start = line_col(err.position());
+
+                // Highlight the whole token at the error position, if any
+                let len = match token_at(&tokens, start) {
+                    Some(index) if line_col(tokens[index].1) == start => {
+                        tokens[index].0.literal_syntax().chars().count().max(1)
+                    }
+                    _ => 1,
+                };
+
+                json!({
+                    "range": to_lsp_range(text, start, len),
+                    "severity": SEVERITY_ERROR,
+                    "source": "rhai",
+                    "message": err.err_type().to_string(),
+                })
+            })
+            .collect();
+
+        self.documents.insert(
+            uri.to_string(),
+            Document {
+                text: text.to_string(),
+                ast,
+                tokens,
+            },
+        );
+
+        vec![Self::diagnostics(uri, diagnostics)]
+    }
+
+    /// Get the document and cursor position referred to by a request.
+    fn locate<'a>(
+        &'a self,
+        params: &'a Value,
+    ) -> RequestResult<(&'a str, &'a Document, (usize, usize))> {
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or("");
+
+        let doc = self
+            .documents
+            .get(uri)
+            .ok_or_else(|| (ERROR_INVALID_PARAMS, format!("Unknown document: {uri}")))?;
+
+        let cursor = from_lsp_position(&doc.text, &params["position"])
+            .ok_or_else(|| (ERROR_INVALID_PARAMS, "Invalid position".to_string()))?;
+
+        Ok((uri, doc, cursor))
+    }
+
+    /// Get the file path of a module imported by a document.
+    #[cfg(not(feature = "no_module"))]
+    fn module_path(&self, uri: &str, path: &str) -> PathBuf {
+        let dir = uri_to_path(uri);
+        let dir = dir.as_deref().and_then(Path::parent);
+        self.resolver.get_file_path(path, dir)
+    }
+
+    /// Handle a `textDocument/hover` request.
+    fn hover(&self, params: &Value) -> RequestResult<Value> {
+        let (_, doc, cursor) = self.locate(params)?;
+
+        let (name, pos) = match token_at(&doc.tokens, cursor) {
+            Some(index) => match &doc.tokens[index] {
+                (Token::Identifier(name), pos) => (name.as_str(), *pos),
+                _ => return Ok(Value::Null),
+            },
+            None => return Ok(Value::Null),
+        };
+
+        let metadata = self
+            .engine
+            .gen_fn_metadata_with_ast_to_json(&doc.ast, true)
+            .map_err(|err| (ERROR_INVALID_PARAMS, err.to_string()))?;
+        let metadata: Value = serde_json::from_str(&metadata).unwrap_or_default();
+
+        let mut functions = Vec::new();
+        collect_fn_metadata(&metadata, name, &mut functions);
+
+        if functions.is_empty() {
+            return Ok(Value::Null);
+        }
+
+        let mut functions = functions
+            .into_iter()
+            .map(|f| (format_signature(f), f))
+            .collect::<Vec<_>>();
+        functions.sort_by(|(a, ..), (b, ..)| a.cmp(b));
+        functions.dedup_by(|(a, ..), (b, ..)| a == b);
+
+        let signatures = functions
+            .iter()
+            .map(|(s, ..)| s.as_str())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let mut contents = format!("```rhai\n{signatures}\n```");
+
+        let comments = functions
+            .iter()
+            .filter_map(|(.., f)| f["docComments"].as_array())
+            .find(|comments| !comments.is_empty())
+            .map(|comments| strip_doc_comments(comments.iter().filter_map(Value::as_str)));
+
+        if let Some(comments) = comments {
+            contents.push_str("\n\n---\n\n");
+            contents.push_str(&comments);
+        }
+
+        Ok(json!({
+            "contents": { "kind": "markdown", "value": contents },
+            "range": to_lsp_range(&doc.text, line_col(pos), name.chars().count()),
+        }))
+    }
+
+    /// Find the definition of a function in a module imported by a document.
+    #[cfg(not(feature = "no_module"))]
+    #[cfg(not(feature = "no_function"))]
+    fn find_module_fn_def(
+        &self,
+        uri: &str,
+        doc: &Document,
+        alias: &str,
+        name: &str,
+    ) -> Option<Value> {
+        let path = find_import(&doc.tokens, alias)?;
+        let file = self.module_path(uri, &path);
+        let text = std::fs::read_to_string(&file).ok()?;
+        let pos = find_fn_def(&tokenize(&self.engine, &text), name)?;
+
+        Some(json!({
+            "uri": path_to_uri(&file),
+            "range": to_lsp_range(&text, line_col(pos), name.chars().count()),
+        }))
+    }
+
+    /// Handle a `textDocument/definition` request.
+    #[allow(unused_variables)]
+    fn definition(&self, params: &Value) -> RequestResult<Value> {
+        let (uri, doc, cursor) = self.locate(params)?;
+
+        let index = match token_at(&doc.tokens, cursor) {
+            Some(index) => index,
+            None => return Ok(Value::Null),
+        };
+        let prev = |n: usize| index.checked_sub(n).map(|i| &doc.tokens[i].0);
+
+        let location = match &doc.tokens[index].0 {
+            // import "path" - go to the module file
+            #[cfg(not(feature = "no_module"))]
+            Token::StringConstant(path) if prev(1) == Some(&Token::Import) => {
+                let file = self.module_path(uri, path);
+
+                file.is_file().then(|| {
+                    json!({
+                        "uri": path_to_uri(&file),
+                        "range": to_lsp_range("", (0, 0), 0),
+                    })
+                })
+            }
+            // module::func - go to the function in the module file
+            #[cfg(not(feature = "no_module"))]
+            #[cfg(not(feature = "no_function"))]
+            Token::Identifier(name) if prev(1) == Some(&Token::DoubleColon) => prev(2)
+                .and_then(identifier)
+                .and_then(|alias| self.find_module_fn_def(uri, doc, alias, name)),
+            // func - go to the function in the same document
+            #[cfg(not(feature = "no_function"))]
+            Token::Identifier(name) => find_fn_def(&doc.tokens, name).map(|pos| {
+                json!({
+                    "uri": uri,
+                    "range": to_lsp_range(&doc.text, line_col(pos), name.chars().count()),
+                })
+            }),
+            _ => None,
+        };
+
+        Ok(location.unwrap_or_default())
+    }
+
+    /// Handle a `textDocument/completion` request.
+    fn completion(&self, params: &Value) -> RequestResult<Value> {
+        let (.., doc, _) = self.locate(params)?;
+        let mut items = Vec::new();
+
+        // Variables, constants and modules in the document
+        for w in doc.tokens.windows(2) {
+            let kind = match w[0].0 {
+                Token::Let | Token::Const => KIND_VARIABLE,
+                #[cfg(not(feature = "no_module"))]
+                Token::As => KIND_MODULE,
+                _ => continue,
+            };
+            if let Some(name) = identifier(&w[1].0) {
+                items.push(json!({ "label": name, "kind": kind }));
+            }
+        }
+
+        // Script-defined functions in the document
+        #[cfg(not(feature = "no_function"))]
+        for f in doc.ast.iter_functions() {
+            let mut item = json!({
+                "label": f.name,
+                "kind": KIND_FUNCTION,
+                "detail": format!("fn {f}"),
+            });
+            if !f.comments.is_empty() {
+                item["documentation"] = strip_doc_comments(f.comments.iter().copied()).into();
+            }
+            items.push(item);
+        }
+
+        // Functions from the engine
+        for c in &self.completions {
+            let mut item = json!({ "label": c.label, "kind": c.kind, "detail": c.detail });
+            if c.overloads > 0 {
+                item["detail"] = format!("{} (+{} overloads)", c.detail, c.overloads).into();
+            }
+            if !c.doc.is_empty() {
+                item["documentation"] = json!({ "kind": "markdown", "value": c.doc });
+            }
+            items.push(item);
+        }
+
+        // Keywords
+        items.extend(
+            KEYWORDS
+                .iter()
+                .map(|&keyword| json!({ "label": keyword, "kind": KIND_KEYWORD })),
+        );
+
+        Ok(json!({ "isIncomplete": false, "items": items }))
+    }
+}
+
+fn main() {
+    let mut server = Server::new();
+
+    let stdin = io::stdin();
+    let mut input = stdin.lock();
+    let stdout = io::stdout();
+    let mut output = stdout.lock();
+
+    loop {
+        let message = match read_message(&mut input) {
+            Ok(Some(message)) => message,
+            Ok(None) => break,
+            Err(err) => {
+                eprintln!("Error reading message: {err}");
+                continue;
+            }
+        };
+
+        for reply in server.handle(&message) {
+            if let Err(err) = write_message(&mut output, &reply) {
+                eprintln!("Error writing message: {err}");
+                exit(1);
+            }
+        }
+    }
+
+    // Input closed without an `exit` notification
+    exit(if server.shutdown { 0 } else { 1 });
+}
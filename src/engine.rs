@@ -3,8 +3,8 @@
 use crate::api::default_limits::MAX_STRINGS_INTERNED;
 use crate::api::options::LangOptions;
 use crate::func::native::{
-    locked_write, OnDebugCallback, OnDefVarCallback, OnParseTokenCallback, OnPrintCallback,
-    OnVarCallback,
+    locked_write, OnDebugCallback, OnDefVarCallback, OnParseTokenCallback, OnParseWarningCallback,
+    OnPrintCallback, OnVarCallback,
 };
 use crate::packages::{Package, StandardPackage};
 use crate::tokenizer::Token;
@@ -43,6 +43,11 @@ pub const FN_IDX_GET: &str = "index$get$";
 pub const FN_IDX_SET: &str = "index$set$";
 #[cfg(not(feature = "no_function"))]
 pub const FN_ANONYMOUS: &str = "anon$";
+#[cfg(not(feature = "no_function"))]
+#[cfg(not(feature = "no_object"))]
+pub const FN_RECORD: &str = "record$";
+#[cfg(not(feature = "no_function"))]
+pub const FN_ENUM: &str = "enum$";
 
 /// Standard equality comparison operator.
 ///
@@ -98,6 +103,11 @@ pub struct Engine {
 
     /// Strings interner.
     pub(crate) interned_strings: Option<Locked<StringsInterner>>,
+    /// Cache of compiled regular expressions (set up by [`Engine::new`] or when [`RegexPackage`][crate::packages::RegexPackage] is registered).
+    #[cfg(feature = "regex")]
+    pub(crate) regex_cache: Option<Locked<crate::packages::string_regex::RegexCache>>,
+    /// Random number generator (set up by [`Engine::new`] or when [`RandomPackage`][crate::packages::RandomPackage] is registered).
+    pub(crate) random_state: Option<Locked<crate::packages::random::Rng>>,
 
     /// A set of symbols to disable.
     pub(crate) disabled_symbols: BTreeSet<Identifier>,
@@ -129,6 +139,8 @@ pub struct Engine {
     pub(crate) print: Option<Box<OnPrintCallback>>,
     /// Callback closure for implementing the `debug` command.
     pub(crate) debug: Option<Box<OnDebugCallback>>,
+    /// Callback closure for reporting parse warnings.
+    pub(crate) parse_warning: Option<Box<OnParseWarningCallback>>,
     /// Callback closure for progress reporting.
     #[cfg(not(feature = "unchecked"))]
     pub(crate) progress: Option<Box<crate::func::native::OnProgressCallback>>,
@@ -142,10 +154,16 @@ pub struct Engine {
     /// Script optimization level.
     #[cfg(not(feature = "no_optimize"))]
     pub(crate) optimization_level: crate::OptimizationLevel,
+    /// Passes performed by the optimizer.
+    #[cfg(not(feature = "no_optimize"))]
+    pub(crate) optimizer_options: crate::OptimizerOptions,
 
     /// Max limits.
     #[cfg(not(feature = "unchecked"))]
     pub(crate) limits: crate::api::limits::Limits,
+    /// Handle to cancel all evaluations running on this [`Engine`] (if any).
+    #[cfg(not(feature = "unchecked"))]
+    pub(crate) cancellation: Option<crate::CancellationHandle>,
 
     /// Callback closure for debugging.
     #[cfg(feature = "debugging")]
@@ -190,9 +208,12 @@ impl fmt::Debug for Engine {
 
         #[cfg(not(feature = "no_optimize"))]
         f.field("optimization_level", &self.optimization_level);
+        #[cfg(not(feature = "no_optimize"))]
+        f.field("optimizer_options", &self.optimizer_options);
 
         #[cfg(not(feature = "unchecked"))]
-        f.field("limits", &self.limits);
+        f.field("limits", &self.limits)
+            .field("cancellation", &self.cancellation);
 
         #[cfg(feature = "debugging")]
         f.field("debugger_interface", &self.debugger_interface.is_some());
@@ -243,6 +264,9 @@ impl Engine {
         module_resolver: None,
 
         interned_strings: None,
+        #[cfg(feature = "regex")]
+        regex_cache: None,
+        random_state: None,
         disabled_symbols: BTreeSet::new(),
         #[cfg(not(feature = "no_custom_syntax"))]
         custom_keywords: std::collections::BTreeMap::new(),
@@ -262,6 +286,7 @@ impl Engine {
 
         print: None,
         debug: None,
+        parse_warning: None,
 
         #[cfg(not(feature = "unchecked"))]
         progress: None,
@@ -272,9 +297,13 @@ impl Engine {
 
         #[cfg(not(feature = "no_optimize"))]
         optimization_level: crate::OptimizationLevel::Simple,
+        #[cfg(not(feature = "no_optimize"))]
+        optimizer_options: crate::OptimizerOptions::new(),
 
         #[cfg(not(feature = "unchecked"))]
         limits: crate::api::limits::Limits::new(),
+        #[cfg(not(feature = "unchecked"))]
+        cancellation: None,
 
         #[cfg(feature = "debugging")]
         debugger_interface: None,
@@ -298,6 +327,15 @@ impl Engine {
         // Turn on the strings interner
         engine.set_max_strings_interned(MAX_STRINGS_INTERNED);
 
+        // Turn on the regular expressions cache
+        #[cfg(feature = "regex")]
+        {
+            engine.regex_cache = Some(crate::packages::string_regex::RegexCache::new().into());
+        }
+
+        // Seed the random number generator
+        engine.random_state = Some(crate::packages::random::Rng::from_entropy().into());
+
         // default print/debug implementations
         #[cfg(not(feature = "no_std"))]
         #[cfg(any(not(target_family = "wasm"), not(target_os = "unknown")))]
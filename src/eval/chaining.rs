@@ -459,6 +459,22 @@ impl Engine {
                 }
             }
 
+            #[cfg(not(feature = "no_object"))]
+            _ if target.is::<crate::Record>() => {
+                // val_record[field]
+                let field = idx.read_lock::<crate::ImmutableString>().ok_or_else(|| {
+                    self.make_type_mismatch_err::<crate::ImmutableString>(idx.type_name(), idx_pos)
+                })?;
+
+                // Fields cannot be added to a record, and missing fields are always errors
+                let record = target.downcast_mut::<crate::Record>().unwrap();
+
+                match record.get_mut(field.as_str()) {
+                    Some(value) => value.try_into(),
+                    None => Err(ERR::ErrorPropertyNotFound(field.to_string(), idx_pos).into()),
+                }
+            }
+
             #[cfg(not(feature = "no_closure"))]
             Dynamic(Union::Shared(..)) => {
                 unreachable!("`get_indexed_mut` cannot handle shared values")
@@ -471,7 +487,7 @@ impl Engine {
             _ => Err(ERR::ErrorIndexingType(
                 format!(
                     "{} [{}]",
-                    self.map_type_name(target.type_name()),
+                    self.map_value_type_name(target),
                     self.map_type_name(idx.type_name())
                 ),
                 op_pos,
@@ -875,7 +891,10 @@ impl Engine {
                     return Ok((Dynamic::UNIT, false));
                 }
 
-                match (rhs, new_val, target.as_ref().is_map()) {
+                // Properties of object maps and records are accessed directly
+                let is_map = target.as_ref().is_map() || target.as_ref().is::<crate::Record>();
+
+                match (rhs, new_val, is_map) {
                     // xxx.fn_name(...) = ???
                     (Expr::MethodCall(..), Some(..), ..) => {
                         unreachable!("method call cannot be assigned to")
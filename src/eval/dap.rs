@@ -0,0 +1,796 @@
+//! Module implementing a Debug Adapter Protocol server on top of the debugging interface.
+#![cfg(feature = "debugging")]
+#![cfg(feature = "metadata")]
+#![cfg(not(feature = "no_std"))]
+
+use super::{BreakPoint, DebuggerCommand, DebuggerEvent, EvalContext};
+use crate::ast::ASTNode;
+use crate::func::{locked_write, SendSync};
+use crate::{Dynamic, Engine, EvalAltResult, Locked, Position, RhaiResultOf, Shared, ERR};
+use serde_json::{json, Value};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    convert::TryFrom,
+    io::{self, BufRead, Write},
+};
+
+/// Input stream of a [`DapServer`].
+#[cfg(not(feature = "sync"))]
+type DapInput = Box<dyn BufRead>;
+/// Input stream of a [`DapServer`].
+#[cfg(feature = "sync")]
+type DapInput = Box<dyn BufRead + Send + Sync>;
+
+/// Output stream of a [`DapServer`].
+#[cfg(not(feature = "sync"))]
+type DapOutput = Box<dyn Write>;
+/// Output stream of a [`DapServer`].
+#[cfg(feature = "sync")]
+type DapOutput = Box<dyn Write + Send + Sync>;
+
+/// The only thread reported to the client.
+const THREAD_ID: u64 = 1;
+
+/// Name of the exception break-point filter.
+const EXCEPTION_FILTER: &str = "all";
+
+/// A variables container handed out to the client while stopped.
+#[derive(Clone)]
+enum Handle {
+    /// Local variables (and `this`) of the current frame.
+    Locals,
+    /// Arguments of a function call in the call stack.
+    Arguments(usize),
+    /// Items in an array or object map.
+    #[cfg_attr(all(feature = "no_index", feature = "no_object"), allow(dead_code))]
+    Value(Dynamic),
+}
+
+/// What to do after handling a request.
+enum Action {
+    /// Keep waiting for requests.
+    Wait,
+    /// Resume evaluation.
+    Resume(DebuggerCommand),
+    /// The client has disconnected.
+    Disconnect,
+}
+
+/// State of a debugging session.
+struct DapSession {
+    /// Stream of messages from the client.
+    input: DapInput,
+    /// Stream of messages to the client.
+    output: DapOutput,
+    /// Sequence number of the next message to the client.
+    seq: u64,
+    /// Do line numbers used by the client start at 1?
+    lines_start_at_1: bool,
+    /// Do column numbers used by the client start at 1?
+    columns_start_at_1: bool,
+    /// Has the `launch` or `attach` request been received?
+    launched: bool,
+    /// Has the `configurationDone` request been received?
+    configured: bool,
+    /// Has the client disconnected?
+    disconnected: bool,
+    /// The `program` argument of the `launch` request, if any.
+    program: Option<String>,
+    /// Stop at the beginning of the script?
+    stop_on_entry: bool,
+    /// Line break-points, by source.
+    line_break_points: BTreeMap<String, Vec<usize>>,
+    /// Function break-points.
+    fn_break_points: Vec<String>,
+    /// Break when an exception is raised?
+    break_on_exceptions: bool,
+    /// Variables containers handed out while stopped.
+    handles: Vec<Handle>,
+}
+
+impl DapSession {
+    /// Read a message from the client.
+    ///
+    /// Returns `None` if the input stream is closed.
+    fn read(&mut self) -> io::Result<Option<Value>> {
+        let mut content_length = None;
+
+        loop {
+            let mut line = String::new();
+
+            if self.input.read_line(&mut line)? == 0 {
+                return Ok(None);
+            }
+
+            let line = line.trim_end();
+
+            if line.is_empty() {
+                break;
+            }
+
+            if let Some((name, value)) = line.split_once(':') {
+                if name.trim().eq_ignore_ascii_case("Content-Length") {
+                    content_length = value.trim().parse::<usize>().ok();
+                }
+            }
+        }
+
+        let len = content_length.ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidData, "missing Content-Length header")
+        })?;
+
+        let mut buf = vec![0; len];
+        self.input.read_exact(&mut buf)?;
+
+        serde_json::from_slice(&buf)
+            .map(Some)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+    /// Send a message to the client.
+    fn send(&mut self, mut message: Value) -> io::Result<()> {
+        message["seq"] = self.seq.into();
+        self.seq += 1;
+
+        let body = message.to_string();
+        write!(self.output, "Content-Length: {}\r\n\r\n{body}", body.len())?;
+        self.output.flush()
+    }
+    /// Send an event to the client.
+    fn event(&mut self, event: &str, body: Value) -> io::Result<()> {
+        self.send(json!({ "type": "event", "event": event, "body": body }))
+    }
+    /// Send a response to a request.
+    fn respond(&mut self, request: &Value, result: Result<Value, String>) -> io::Result<()> {
+        let mut response = json!({
+            "type": "response",
+            "request_seq": request["seq"],
+            "command": request["command"],
+            "success": result.is_ok(),
+        });
+
+        match result {
+            Ok(Value::Null) => (),
+            Ok(body) => response["body"] = body,
+            Err(message) => response["message"] = message.into(),
+        }
+
+        self.send(response)
+    }
+    /// Convert a line number from the client.
+    fn line_from_client(&self, line: u64) -> usize {
+        usize::try_from(line).unwrap_or(0) + usize::from(!self.lines_start_at_1)
+    }
+    /// Convert a [`Position`] into line and column numbers for the client.
+    fn position_to_client(&self, pos: Position) -> (usize, usize) {
+        let line = pos.line().unwrap_or(0);
+        let col = pos.position().unwrap_or(0);
+
+        (
+            if self.lines_start_at_1 {
+                line
+            } else {
+                line.saturating_sub(1)
+            },
+            if self.columns_start_at_1 {
+                col
+            } else {
+                col.saturating_sub(1)
+            },
+        )
+    }
+    /// Make a `Source` object for the client.
+    fn source_to_client(&self, source: Option<&str>) -> Value {
+        match source.or(self.program.as_deref()) {
+            Some(path) => {
+                let name = std::path::Path::new(path)
+                    .file_name()
+                    .map_or(path.into(), |s| s.to_string_lossy());
+                json!({ "name": name, "path": path })
+            }
+            None => json!({ "name": "main" }),
+        }
+    }
+    /// Build the list of [`BreakPoint`]'s for the [`Debugger`][super::Debugger].
+    ///
+    /// The ID of each break-point is its index plus one.
+    fn break_points(&self) -> Vec<BreakPoint> {
+        #[cfg(not(feature = "no_position"))]
+        let lines = self.line_break_points.iter().flat_map(|(source, lines)| {
+            lines.iter().map(move |&line| BreakPoint::AtPosition {
+                source: Some(source.into()),
+                pos: Position::new(u16::try_from(line).unwrap_or(u16::MAX), 0),
+                enabled: true,
+            })
+        });
+        #[cfg(feature = "no_position")]
+        let lines = std::iter::empty();
+
+        let functions = self
+            .fn_break_points
+            .iter()
+            .map(|name| BreakPoint::AtFunctionName {
+                name: name.into(),
+                enabled: true,
+            });
+
+        lines.chain(functions).collect()
+    }
+    /// Make a `Variable` object for the client.
+    fn variable(&mut self, engine: &Engine, name: &str, value: &Dynamic) -> Value {
+        let value = value.flatten_clone();
+
+        let has_children = match value.type_name() {
+            #[cfg(not(feature = "no_index"))]
+            _ if value.is_array() => value.as_array_ref().map_or(false, |a| !a.is_empty()),
+            #[cfg(not(feature = "no_object"))]
+            _ if value.is_map() => value.as_map_ref().map_or(false, |m| !m.is_empty()),
+            _ => false,
+        };
+
+        let text = format!("{value:?}");
+        let typ = engine.map_type_name(value.type_name()).to_string();
+
+        let reference = if has_children {
+            self.handles.push(Handle::Value(value));
+            self.handles.len()
+        } else {
+            0
+        };
+
+        json!({ "name": name, "value": text, "type": typ, "variablesReference": reference })
+    }
+    /// Handle a `setBreakpoints` request, which replaces all line break-points in a source.
+    fn set_line_break_points(&mut self, args: &Value) -> Value {
+        let source = args["source"]["path"]
+            .as_str()
+            .or_else(|| args["source"]["name"].as_str())
+            .unwrap_or("");
+
+        let lines: Vec<_> = args["breakpoints"]
+            .as_array()
+            .map(|bps| {
+                bps.iter()
+                    .filter_map(|bp| bp["line"].as_u64())
+                    .map(|line| self.line_from_client(line))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        // Line break-points come first, ordered by source
+        let offset: usize = self
+            .line_break_points
+            .iter()
+            .take_while(|(k, ..)| k.as_str() < source)
+            .map(|(.., v)| v.len())
+            .sum();
+
+        let break_points: Vec<_> = lines
+            .iter()
+            .enumerate()
+            .map(|(i, &line)| {
+                json!({
+                    "id": offset + i + 1,
+                    "verified": cfg!(not(feature = "no_position")),
+                    "line": line - usize::from(!self.lines_start_at_1),
+                })
+            })
+            .collect();
+
+        if lines.is_empty() {
+            self.line_break_points.remove(source);
+        } else {
+            self.line_break_points.insert(source.into(), lines);
+        }
+
+        json!({ "breakpoints": break_points })
+    }
+    /// Handle a `setFunctionBreakpoints` request, which replaces all function break-points.
+    fn set_fn_break_points(&mut self, args: &Value) -> Value {
+        self.fn_break_points = args["breakpoints"]
+            .as_array()
+            .map(|bps| {
+                bps.iter()
+                    .filter_map(|bp| bp["name"].as_str().map(Into::into))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        // Function break-points come after all line break-points
+        let offset: usize = self.line_break_points.values().map(Vec::len).sum();
+
+        let break_points: Vec<_> = (0..self.fn_break_points.len())
+            .map(|i| json!({ "id": offset + i + 1, "verified": true }))
+            .collect();
+
+        json!({ "breakpoints": break_points })
+    }
+    /// Handle a request.
+    ///
+    /// Requests that inspect the current state of evaluation require an [`EvalContext`], and fail
+    /// when evaluation is not stopped.
+    fn handle(
+        &mut self,
+        request: &Value,
+        mut context: Option<&mut EvalContext>,
+        current: (Option<&str>, Position),
+    ) -> io::Result<Action> {
+        let args = &request["arguments"];
+        let command = request["command"].as_str().unwrap_or("");
+        let mut action = Action::Wait;
+
+        let result = match command {
+            "initialize" => {
+                self.lines_start_at_1 = args["linesStartAt1"].as_bool().unwrap_or(true);
+                self.columns_start_at_1 = args["columnsStartAt1"].as_bool().unwrap_or(true);
+
+                let capabilities = json!({
+                    "supportsConfigurationDoneRequest": true,
+                    "supportsFunctionBreakpoints": true,
+                    "supportsTerminateRequest": true,
+                    "supportsSteppingGranularity": true,
+                    "exceptionBreakpointFilters": [{
+                        "filter": EXCEPTION_FILTER,
+                        "label": "Exceptions",
+                        "description": "Break when an exception is raised.",
+                        "default": false,
+                    }],
+                });
+
+                self.respond(request, Ok(capabilities))?;
+                self.event("initialized", Value::Null)?;
+                return Ok(action);
+            }
+            "launch" => {
+                self.launched = true;
+                self.program = args["program"].as_str().map(Into::into);
+                self.stop_on_entry = args["stopOnEntry"].as_bool().unwrap_or(false);
+                Ok(Value::Null)
+            }
+            "attach" => {
+                self.launched = true;
+                self.stop_on_entry = args["stopOnEntry"].as_bool().unwrap_or(false);
+                Ok(Value::Null)
+            }
+            "configurationDone" => {
+                self.configured = true;
+                Ok(Value::Null)
+            }
+            "setBreakpoints" => Ok(self.set_line_break_points(args)),
+            "setFunctionBreakpoints" => Ok(self.set_fn_break_points(args)),
+            "setExceptionBreakpoints" => {
+                self.break_on_exceptions = args["filters"]
+                    .as_array()
+                    .map_or(false, |f| f.iter().any(|f| f == EXCEPTION_FILTER));
+                Ok(Value::Null)
+            }
+            "threads" => Ok(json!({ "threads": [{ "id": THREAD_ID, "name": "main" }] })),
+            "pause" => Ok(Value::Null),
+            "disconnect" | "terminate" => {
+                self.disconnected = true;
+                action = Action::Disconnect;
+                Ok(Value::Null)
+            }
+            _ => match context {
+                Some(ref mut context) => match step_command(command, args) {
+                    Some(cmd) => {
+                        action = Action::Resume(cmd);
+                        Ok(json!({ "allThreadsContinued": true }))
+                    }
+                    None => self.handle_stopped(command, args, context, current),
+                },
+                None => Err("Script is not stopped.".into()),
+            },
+        };
+
+        // Update the debugger with the new break-points
+        if let Some(context) = context {
+            if command.starts_with("set") && command.ends_with("Breakpoints") {
+                let debugger = context.global_runtime_state_mut().debugger_mut();
+                *debugger.break_points_mut() = self.break_points();
+                debugger.set_break_on_exceptions(self.break_on_exceptions);
+            }
+        }
+
+        self.respond(request, result)?;
+        Ok(action)
+    }
+}
+
+impl DapSession {
+    /// Handle a request that inspects the current state of evaluation.
+    fn handle_stopped(
+        &mut self,
+        command: &str,
+        args: &Value,
+        context: &mut EvalContext,
+        (source, pos): (Option<&str>, Position),
+    ) -> Result<Value, String> {
+        let engine = context.engine();
+
+        match command {
+            "stackTrace" => {
+                let call_stack = context.global_runtime_state().debugger().call_stack();
+                let mut frames = Vec::with_capacity(call_stack.len() + 1);
+                let mut location = (source, pos);
+
+                let mut frame = |name: &str, (source, pos): (Option<&str>, Position)| {
+                    let (line, column) = self.position_to_client(pos);
+                    frames.push(json!({
+                        "id": frames.len() + 1,
+                        "name": name,
+                        "source": self.source_to_client(source),
+                        "line": line,
+                        "column": column,
+                    }));
+                };
+
+                for f in call_stack.iter().rev() {
+                    frame(&f.fn_name, location);
+                    location = (f.source.as_deref(), f.pos);
+                }
+                frame("main", location);
+
+                Ok(json!({ "stackFrames": frames, "totalFrames": frames.len() }))
+            }
+            "scopes" => {
+                let num_frames = context.global_runtime_state().debugger().call_stack().len();
+                let frame = args["frameId"]
+                    .as_u64()
+                    .and_then(|n| usize::try_from(n).ok())
+                    .unwrap_or(0);
+
+                let (name, handle) = match frame {
+                    1 => ("Locals", Handle::Locals),
+                    n if n > 1 && n <= num_frames => {
+                        ("Arguments", Handle::Arguments(num_frames - n))
+                    }
+                    _ => return Ok(json!({ "scopes": [] })),
+                };
+
+                self.handles.push(handle);
+
+                Ok(json!({ "scopes": [{
+                    "name": name,
+                    "variablesReference": self.handles.len(),
+                    "expensive": false,
+                }]}))
+            }
+            "variables" => {
+                let handle = args["variablesReference"]
+                    .as_u64()
+                    .and_then(|n| usize::try_from(n).ok())
+                    .and_then(|n| self.handles.get(n.wrapping_sub(1)).cloned())
+                    .ok_or("Invalid variables reference.")?;
+
+                let mut variables = Vec::new();
+
+                match handle {
+                    Handle::Locals => {
+                        if let Some(this_ptr) = context.this_ptr() {
+                            variables.push(self.variable(engine, "this", this_ptr));
+                        }
+
+                        // Only the last variable of each name is visible
+                        let mut names = BTreeSet::new();
+                        let mut locals: Vec<_> = context
+                            .scope()
+                            .iter_raw()
+                            .filter(|&(name, ..)| names.insert(name))
+                            .map(|(name, .., value)| (name, value))
+                            .collect();
+                        locals.reverse();
+
+                        for (name, value) in locals {
+                            variables.push(self.variable(engine, name, value));
+                        }
+                    }
+                    Handle::Arguments(index) => {
+                        let call_stack = context.global_runtime_state().debugger().call_stack();
+
+                        if let Some(frame) = call_stack.get(index) {
+                            for (i, arg) in frame.args.iter().enumerate() {
+                                variables.push(self.variable(engine, &format!("arg{i}"), arg));
+                            }
+                        }
+                    }
+                    #[cfg(not(feature = "no_index"))]
+                    Handle::Value(value) if value.is_array() => {
+                        for (i, item) in value.as_array_ref().unwrap().iter().enumerate() {
+                            variables.push(self.variable(engine, &format!("[{i}]"), item));
+                        }
+                    }
+                    #[cfg(not(feature = "no_object"))]
+                    Handle::Value(value) if value.is_map() => {
+                        for (name, item) in value.as_map_ref().unwrap().iter() {
+                            variables.push(self.variable(engine, name, item));
+                        }
+                    }
+                    Handle::Value(..) => (),
+                }
+
+                Ok(json!({ "variables": variables }))
+            }
+            "evaluate" => {
+                let expr = args["expression"].as_str().unwrap_or("").trim();
+
+                let value = match expr {
+                    "this" => context.this_ptr().cloned(),
+                    _ => context.scope().get(expr).cloned(),
+                };
+
+                match value {
+                    Some(value) => {
+                        let var = self.variable(engine, expr, &value);
+                        Ok(json!({
+                            "result": var["value"],
+                            "type": var["type"],
+                            "variablesReference": var["variablesReference"],
+                        }))
+                    }
+                    None => Err(format!("Variable not found: {expr}")),
+                }
+            }
+            _ => Err(format!("Unsupported request: {command}")),
+        }
+    }
+}
+
+/// Map a stepping request into a [`DebuggerCommand`].
+fn step_command(command: &str, args: &Value) -> Option<DebuggerCommand> {
+    match command {
+        "continue" => Some(DebuggerCommand::Continue),
+        "next" if args["granularity"] == "instruction" => Some(DebuggerCommand::StepOver),
+        "next" => Some(DebuggerCommand::Next),
+        "stepIn" => Some(DebuggerCommand::StepInto),
+        "stepOut" => Some(DebuggerCommand::FunctionExit),
+        _ => None,
+    }
+}
+
+/// Convert an I/O error into a system error.
+fn io_error(err: io::Error) -> Box<EvalAltResult> {
+    ERR::ErrorSystem("DAP server error".into(), err.into()).into()
+}
+
+/// _(debugging, metadata)_ A server for the
+/// [Debug Adapter Protocol](https://microsoft.github.io/debug-adapter-protocol/) (DAP), which
+/// allows debugging Rhai scripts from any DAP client (e.g. Visual Studio Code).
+/// Exported under the `debugging` and `metadata` features only.
+///
+/// Not available under `no_std`.
+///
+/// Register the server on an [`Engine`] via [`Engine::register_dap_server`].
+///
+/// The server maps break-points, stack frames (from the call stack), local variables (from the
+/// current [`Scope`][crate::Scope]), stepping and exceptions into DAP requests and events.
+///
+/// Line break-points are matched against the source of each script, so set the source of an
+/// [`AST`][crate::AST] to the path of its script file (e.g. via
+/// [`AST::set_source`][crate::AST::set_source]).
+///
+/// # WARNING - Unstable API
+///
+/// This API is volatile and may change in the future.
+#[derive(Clone)]
+pub struct DapServer(Shared<Locked<DapSession>>);
+
+impl std::fmt::Debug for DapServer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DapServer").finish_non_exhaustive()
+    }
+}
+
+impl DapServer {
+    /// Create a new [`DapServer`] that receives messages from `input` and sends messages to
+    /// `output`.
+    #[must_use]
+    pub fn new(
+        input: impl BufRead + SendSync + 'static,
+        output: impl Write + SendSync + 'static,
+    ) -> Self {
+        Self(
+            Locked::new(DapSession {
+                input: Box::new(input),
+                output: Box::new(output),
+                seq: 1,
+                lines_start_at_1: true,
+                columns_start_at_1: true,
+                launched: false,
+                configured: false,
+                disconnected: false,
+                program: None,
+                stop_on_entry: false,
+                line_break_points: BTreeMap::new(),
+                fn_break_points: Vec::new(),
+                break_on_exceptions: false,
+                handles: Vec::new(),
+            })
+            .into(),
+        )
+    }
+    /// Create a new [`DapServer`] communicating over stdin and stdout.
+    #[inline(always)]
+    #[must_use]
+    pub fn stdio() -> Self {
+        Self::new(io::BufReader::new(io::stdin()), io::stdout())
+    }
+    /// Handle requests from the client until it has sent both a `launch` (or `attach`) request and
+    /// the `configurationDone` request.
+    ///
+    /// Call this before running any script so that all break-points are set.
+    ///
+    /// Returns the `program` argument of the `launch` request, if any.
+    pub fn wait_for_launch(&self) -> io::Result<Option<String>> {
+        let session = &mut *locked_write(&self.0).unwrap();
+
+        while !session.launched || !session.configured {
+            let request = match session.read()? {
+                Some(request) if request["type"] == "request" => request,
+                Some(..) => continue,
+                None => {
+                    session.disconnected = true;
+                    break;
+                }
+            };
+
+            if let Action::Disconnect = session.handle(&request, None, (None, Position::NONE))? {
+                break;
+            }
+        }
+
+        if session.disconnected {
+            Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "client disconnected",
+            ))
+        } else {
+            Ok(session.program.clone())
+        }
+    }
+    /// Has the client disconnected?
+    #[inline]
+    #[must_use]
+    pub fn is_disconnected(&self) -> bool {
+        locked_write(&self.0).unwrap().disconnected
+    }
+    /// Send text to be displayed by the client.
+    ///
+    /// `category` is usually `"console"`, `"stdout"` or `"stderr"`.
+    pub fn output(&self, category: &str, text: &str) -> io::Result<()> {
+        locked_write(&self.0)
+            .unwrap()
+            .event("output", json!({ "category": category, "output": text }))
+    }
+    /// Notify the client that the debugging session has ended with an exit code, then wait for it
+    /// to disconnect.
+    pub fn finish(&self, exit_code: i32) -> io::Result<()> {
+        let session = &mut *locked_write(&self.0).unwrap();
+
+        if session.disconnected {
+            return Ok(());
+        }
+
+        session.event("exited", json!({ "exitCode": exit_code }))?;
+        session.event("terminated", Value::Null)?;
+
+        while let Some(request) = session.read()? {
+            if request["type"] != "request" {
+                continue;
+            }
+            if let Action::Disconnect = session.handle(&request, None, (None, Position::NONE))? {
+                break;
+            }
+        }
+
+        session.disconnected = true;
+        Ok(())
+    }
+    /// Initialize a new [`Debugger`][super::Debugger] with the break-points set by the client.
+    fn init(&self, mut debugger: super::Debugger) -> super::Debugger {
+        let session = locked_write(&self.0).unwrap();
+        *debugger.break_points_mut() = session.break_points();
+        debugger.set_break_on_exceptions(session.break_on_exceptions);
+        debugger
+    }
+    /// Stop evaluation and handle requests from the client until it resumes.
+    fn on_debugger(
+        &self,
+        mut context: EvalContext,
+        event: DebuggerEvent,
+        #[cfg_attr(feature = "no_position", allow(unused_variables))] node: ASTNode,
+        source: Option<&str>,
+        pos: Position,
+    ) -> RhaiResultOf<DebuggerCommand> {
+        let session = &mut *locked_write(&self.0).unwrap();
+
+        if session.disconnected {
+            return Err(ERR::ErrorTerminated(Dynamic::UNIT, pos).into());
+        }
+
+        let mut body = json!({ "threadId": THREAD_ID, "allThreadsStopped": true });
+
+        body["reason"] = match event {
+            DebuggerEvent::Start if session.stop_on_entry => "entry".into(),
+            DebuggerEvent::Step
+            | DebuggerEvent::FunctionExitWithValue(..)
+            | DebuggerEvent::FunctionExitWithError(..) => "step".into(),
+            // Line break-points match every node on the line, so only stop at statements
+            #[cfg(not(feature = "no_position"))]
+            DebuggerEvent::BreakPoint(n)
+                if node.is_expr()
+                    && matches!(
+                        context.global_runtime_state().debugger().break_points()[n],
+                        BreakPoint::AtPosition { pos, .. } if pos.is_beginning_of_line()
+                    ) =>
+            {
+                return Ok(DebuggerCommand::Continue)
+            }
+            DebuggerEvent::BreakPoint(n) => {
+                body["hitBreakpointIds"] = json!([n + 1]);
+                "breakpoint".into()
+            }
+            DebuggerEvent::Exception(err) => {
+                body["description"] = "Exception".into();
+                body["text"] = err.to_string().into();
+                "exception".into()
+            }
+            _ => return Ok(DebuggerCommand::Continue),
+        };
+
+        session.event("stopped", body).map_err(io_error)?;
+
+        let result = loop {
+            let request = match session.read() {
+                Ok(Some(request)) if request["type"] == "request" => request,
+                Ok(Some(..)) => continue,
+                Ok(None) => {
+                    session.disconnected = true;
+                    break Err(ERR::ErrorTerminated(Dynamic::UNIT, pos).into());
+                }
+                Err(err) => break Err(io_error(err)),
+            };
+
+            match session.handle(&request, Some(&mut context), (source, pos)) {
+                Ok(Action::Wait) => (),
+                Ok(Action::Resume(command)) => break Ok(command),
+                Ok(Action::Disconnect) => {
+                    break Err(ERR::ErrorTerminated(Dynamic::UNIT, pos).into())
+                }
+                Err(err) => break Err(io_error(err)),
+            }
+        };
+
+        // Variables references are only valid while stopped
+        session.handles.clear();
+
+        result
+    }
+}
+
+impl Engine {
+    /// _(debugging, metadata)_ Register a [`DapServer`] as the debugging interface.
+    /// Exported under the `debugging` and `metadata` features only.
+    ///
+    /// Not available under `no_std`.
+    ///
+    /// This replaces any debugging interface registered via
+    /// [`register_debugger`][Engine::register_debugger].
+    ///
+    /// # WARNING - Unstable API
+    ///
+    /// This API is volatile and may change in the future.
+    #[deprecated = "This API is NOT deprecated, but it is considered volatile and may change in the future."]
+    #[inline]
+    pub fn register_dap_server(&mut self, server: &DapServer) -> &mut Self {
+        let init = server.clone();
+        let server = server.clone();
+
+        #[allow(deprecated)]
+        self.register_debugger(
+            move |_, debugger| init.init(debugger),
+            move |context, event, node, source, pos| {
+                server.on_debugger(context, event, node, source, pos)
+            },
+        )
+    }
+}
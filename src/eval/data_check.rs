@@ -3,11 +3,16 @@
 
 use super::GlobalRuntimeState;
 use crate::types::dynamic::Union;
-use crate::{Dynamic, Engine, Position, RhaiResultOf, ERR};
+use crate::{Dynamic, Engine, Position, RhaiResultOf, Scope, ERR};
 use std::borrow::Borrow;
+use std::mem::size_of;
 #[cfg(feature = "no_std")]
 use std::prelude::v1::*;
 
+/// Number of operations between checks of the deadline of an evaluation.
+#[cfg(not(feature = "no_time"))]
+const DEADLINE_CHECK_INTERVAL: u64 = 256;
+
 /// Recursively calculate the sizes of an array.
 ///
 /// Sizes returned are `(` [`Array`][crate::Array], [`Map`][crate::Map] and [`String`] `)`.
@@ -119,6 +124,33 @@ pub fn calc_data_sizes(value: &Dynamic, _top: bool) -> (usize, usize, usize) {
     }
 }
 
+/// Recursively calculate the approximate number of bytes held by a value.
+///
+/// Each value is counted as the size of a [`Dynamic`] plus the bytes held by the strings,
+/// [arrays][crate::Array], [BLOB's][crate::Blob], [object maps][crate::Map] and curried arguments
+/// within it. Shared values that are currently locked are counted by their handle only.
+#[must_use]
+fn calc_memory_size(value: &Dynamic) -> usize {
+    size_of::<Dynamic>()
+        + match value.0 {
+            Union::Str(ref s, ..) => s.len(),
+            #[cfg(not(feature = "no_index"))]
+            Union::Array(ref a, ..) => a.iter().map(calc_memory_size).sum(),
+            #[cfg(not(feature = "no_index"))]
+            Union::Blob(ref a, ..) => a.len(),
+            #[cfg(not(feature = "no_object"))]
+            Union::Map(ref m, ..) => m.iter().map(|(k, v)| k.len() + calc_memory_size(v)).sum(),
+            Union::FnPtr(ref f, ..) => {
+                f.fn_name().len() + f.curry().iter().map(calc_memory_size).sum::<usize>()
+            }
+            #[cfg(not(feature = "no_closure"))]
+            Union::Shared(..) => value
+                .read_lock::<Dynamic>()
+                .map_or(0, |v| calc_memory_size(&v)),
+            _ => 0,
+        }
+}
+
 impl Engine {
     /// Raise an error if any data size exceeds limit.
     ///
@@ -149,6 +181,62 @@ impl Engine {
         Ok(())
     }
 
+    /// Raise an error if the approximate number of bytes held by the variables in a [`Scope`],
+    /// together with some extra values and the variables of the calling functions, exceeds the
+    /// memory limit (if any).
+    ///
+    /// Only the sizes of the `written` variables and of variables new to the [`Scope`] since the
+    /// last check are calculated. Other variables are assumed to keep their sizes, so a value
+    /// changed in place without being written (e.g. via a closure capturing it) is not noticed
+    /// until it is written again.
+    ///
+    /// The number of bytes held by the variables in the [`Scope`], together with the extra values,
+    /// is recorded into the [`GlobalRuntimeState`].
+    #[cfg(not(feature = "unchecked"))]
+    pub(crate) fn check_memory<'a>(
+        &self,
+        global: &mut GlobalRuntimeState,
+        scope: &'a Scope,
+        written: impl IntoIterator<Item = impl AsRef<str>>,
+        extra: impl IntoIterator<Item = &'a Dynamic>,
+        pos: Position,
+    ) -> RhaiResultOf<()> {
+        let max = match self.limits.memory {
+            Some(max) => max.get(),
+            None => return Ok(()),
+        };
+
+        let sizes = &mut global.memory_sizes;
+
+        // Variables removed from the scope
+        sizes.truncate(scope.len());
+
+        // Variables added to the scope, or replaced by others
+        for (index, (name, .., value)) in scope.iter_inner().enumerate() {
+            match sizes.get_mut(index) {
+                Some((n, ..)) if n == name => (),
+                Some(entry) => *entry = (name.clone(), calc_memory_size(value)),
+                None => sizes.push((name.clone(), calc_memory_size(value))),
+            }
+        }
+
+        // Variables written
+        for name in written {
+            if let Some(index) = scope.search(name.as_ref()) {
+                sizes[index].1 = calc_memory_size(scope.get_entry_by_index(index).1);
+            }
+        }
+
+        global.memory_in_scope = sizes.iter().map(|&(.., size)| size).sum::<usize>()
+            + extra.into_iter().map(calc_memory_size).sum::<usize>();
+
+        if global.memory_of_callers + global.memory_in_scope > max {
+            return Err(ERR::ErrorTooMuchMemory(pos).into());
+        }
+
+        Ok(())
+    }
+
     /// Check whether the size of a [`Dynamic`] is within limits.
     #[cfg(not(feature = "unchecked"))]
     #[inline]
@@ -194,6 +282,26 @@ impl Engine {
             return Err(ERR::ErrorTooManyOperations(pos).into());
         }
 
+        // Guard against running past the deadline, checking the clock only once in a while
+        #[cfg(not(feature = "no_time"))]
+        if let Some(deadline) = global.deadline {
+            if global.num_operations % DEADLINE_CHECK_INTERVAL == 0
+                && crate::Instant::now() >= deadline
+            {
+                return Err(ERR::ErrorTimeout(pos).into());
+            }
+        }
+
+        // Guard against cancellation, possibly from another thread
+        if let Some(reason) = self
+            .cancellation
+            .iter()
+            .chain(global.cancellation.iter())
+            .find_map(crate::CancellationHandle::reason)
+        {
+            return Err(ERR::ErrorTerminated(reason, pos).into());
+        }
+
         self.progress
             .as_ref()
             .and_then(|progress| {
@@ -84,6 +84,10 @@ pub enum DebuggerEvent<'a> {
     FunctionExitWithValue(&'a Dynamic),
     /// Return from a function with a value.
     FunctionExitWithError(&'a EvalAltResult),
+    /// An exception is raised.
+    ///
+    /// Only triggered when [`Debugger::break_on_exceptions`] is `true`.
+    Exception(&'a EvalAltResult),
     /// Script evaluation ends.
     End,
 }
@@ -257,6 +261,10 @@ pub struct Debugger {
     call_stack: Vec<CallStackFrame>,
     /// The current state.
     state: Dynamic,
+    /// Break when an exception is raised?
+    break_on_exceptions: bool,
+    /// Has the exception currently propagating been reported?
+    exception_reported: bool,
 }
 
 impl Debugger {
@@ -269,6 +277,8 @@ impl Debugger {
             break_points: Vec::new(),
             call_stack: Vec::new(),
             state: Dynamic::UNIT,
+            break_on_exceptions: false,
+            exception_reported: false,
         }
     }
     /// Get the current call stack.
@@ -393,6 +403,20 @@ impl Debugger {
     pub fn set_state(&mut self, state: impl Into<Dynamic>) {
         self.state = state.into();
     }
+    /// Does the debugger break when an exception is raised?
+    #[inline(always)]
+    #[must_use]
+    pub const fn break_on_exceptions(&self) -> bool {
+        self.break_on_exceptions
+    }
+    /// Set whether the debugger breaks when an exception is raised.
+    ///
+    /// If `true`, the debugger callback is called with [`DebuggerEvent::Exception`] at the
+    /// statement where an exception is first raised, before it propagates.
+    #[inline(always)]
+    pub fn set_break_on_exceptions(&mut self, enable: bool) {
+        self.break_on_exceptions = enable;
+    }
 }
 
 impl Engine {
@@ -414,6 +438,47 @@ impl Engine {
 
         Ok(())
     }
+    /// Run the debugger callback if a statement raises an exception and the debugger is set to
+    /// break on exceptions.
+    ///
+    /// Each exception is only reported once, at the first statement that raises it.
+    /// The debugger callback may replace the exception by returning a different error.
+    #[inline]
+    pub(crate) fn dbg_exception<'a>(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        scope: &mut Scope,
+        this_ptr: Option<&mut Dynamic>,
+        node: impl Into<ASTNode<'a>>,
+        result: RhaiResultOf<Dynamic>,
+    ) -> RhaiResultOf<Dynamic> {
+        let dbg = match global.debugger {
+            Some(ref mut dbg) if self.is_debugger_registered() => dbg,
+            _ => return result,
+        };
+
+        let err = match result {
+            Ok(..) => {
+                // The exception, if any, has been caught
+                dbg.exception_reported = false;
+                return result;
+            }
+            Err(err) if err.is_pseudo_error() || !err.is_catchable() => return Err(err),
+            Err(_) if !dbg.break_on_exceptions || dbg.exception_reported => return result,
+            Err(err) => err,
+        };
+
+        dbg.exception_reported = true;
+
+        let event = DebuggerEvent::Exception(&err);
+
+        if let Some(status) = self.dbg_raw(global, caches, scope, this_ptr, node.into(), event)? {
+            global.debugger_mut().status = status;
+        }
+
+        Err(err)
+    }
     /// Run the debugger callback if there is a debugging interface registered.
     ///
     /// Returns [`Some`] if the debugger needs to be reactivated at the end of the block, statement or
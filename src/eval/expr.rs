@@ -4,7 +4,7 @@ use super::{Caches, EvalContext, GlobalRuntimeState, Target};
 use crate::ast::Expr;
 use crate::packages::string_basic::{print_with_func, FUNC_TO_STRING};
 use crate::types::dynamic::AccessMode;
-use crate::{Dynamic, Engine, RhaiResult, RhaiResultOf, Scope, SmartString, ERR};
+use crate::{Continuation, Dynamic, Engine, RhaiResult, RhaiResultOf, Scope, SmartString, ERR};
 #[cfg(feature = "no_std")]
 use std::prelude::v1::*;
 use std::{convert::TryInto, fmt::Write, num::NonZeroUsize};
@@ -265,9 +265,9 @@ impl Engine {
             Expr::Unit(..) => Ok(Dynamic::UNIT),
             Expr::DynamicConstant(x, ..) => Ok(x.as_ref().clone()),
 
-            Expr::FnCall(x, pos) => {
-                self.eval_fn_call_expr(global, caches, scope, this_ptr, x, *pos)
-            }
+            Expr::FnCall(x, pos) => self
+                .eval_fn_call_expr(global, caches, scope, this_ptr, x, *pos)
+                .map_err(|err| Continuation::record_fn_call(err, x)),
 
             Expr::ThisPtr(var_pos) => this_ptr
                 .ok_or_else(|| ERR::ErrorUnboundThis(*var_pos).into())
@@ -42,6 +42,24 @@ pub struct GlobalRuntimeState {
     /// Number of modules loaded.
     #[cfg(not(feature = "no_module"))]
     pub num_modules_loaded: usize,
+    /// Approximate number of bytes held by the variables of the calling functions.
+    #[cfg(not(feature = "unchecked"))]
+    pub memory_of_callers: usize,
+    /// Approximate number of bytes held by the variables of the current function, as of the last
+    /// check.
+    #[cfg(not(feature = "unchecked"))]
+    pub memory_in_scope: usize,
+    /// Names and approximate number of bytes held by each variable of the current function, as of
+    /// the last check.
+    #[cfg(not(feature = "unchecked"))]
+    pub(crate) memory_sizes: Vec<(ImmutableString, usize)>,
+    /// Time at which the evaluation times out (if any).
+    #[cfg(not(feature = "unchecked"))]
+    #[cfg(not(feature = "no_time"))]
+    pub deadline: Option<crate::Instant>,
+    /// Handle to cancel the evaluation (if any), in addition to that of the [`Engine`].
+    #[cfg(not(feature = "unchecked"))]
+    pub cancellation: Option<crate::CancellationHandle>,
     /// The current nesting level of function calls.
     pub level: usize,
     /// Level of the current scope.
@@ -70,6 +88,10 @@ pub struct GlobalRuntimeState {
     pub constants: Option<SharedGlobalConstants>,
     /// Custom state that can be used by the external host.
     pub tag: Dynamic,
+    /// Number of times a suspended evaluation has been resumed.
+    pub(crate) num_resumed: usize,
+    /// Is this an asynchronous evaluation, in which asynchronous functions can be called?
+    pub(crate) is_async: bool,
     /// Debugging interface.
     #[cfg(feature = "debugging")]
     pub(crate) debugger: Option<Box<super::Debugger>>,
@@ -93,6 +115,20 @@ impl Engine {
             num_operations: 0,
             #[cfg(not(feature = "no_module"))]
             num_modules_loaded: 0,
+            #[cfg(not(feature = "unchecked"))]
+            memory_of_callers: 0,
+            #[cfg(not(feature = "unchecked"))]
+            memory_in_scope: 0,
+            #[cfg(not(feature = "unchecked"))]
+            memory_sizes: Vec::new(),
+            #[cfg(not(feature = "unchecked"))]
+            #[cfg(not(feature = "no_time"))]
+            deadline: self
+                .limits
+                .execution_time
+                .and_then(|time| crate::Instant::now().checked_add(time)),
+            #[cfg(not(feature = "unchecked"))]
+            cancellation: None,
             scope_level: 0,
             level: 0,
             always_search_scope: false,
@@ -103,6 +139,8 @@ impl Engine {
             constants: None,
 
             tag: self.default_tag().clone(),
+            num_resumed: 0,
+            is_async: false,
 
             #[cfg(feature = "debugging")]
             debugger: self.debugger_interface.as_ref().map(|x| {
@@ -334,6 +372,17 @@ impl fmt::Debug for GlobalRuntimeState {
             .field("scope_level", &self.scope_level)
             .field("always_search_scope", &self.always_search_scope);
 
+        #[cfg(not(feature = "unchecked"))]
+        f.field("memory_of_callers", &self.memory_of_callers)
+            .field("memory_in_scope", &self.memory_in_scope);
+
+        #[cfg(not(feature = "unchecked"))]
+        #[cfg(not(feature = "no_time"))]
+        f.field("deadline", &self.deadline);
+
+        #[cfg(not(feature = "unchecked"))]
+        f.field("cancellation", &self.cancellation);
+
         #[cfg(not(feature = "no_module"))]
         #[cfg(not(feature = "no_function"))]
         f.field("constants", &self.constants);
@@ -1,5 +1,6 @@
 mod cache;
 mod chaining;
+mod dap;
 mod data_check;
 mod debugger;
 mod eval_context;
@@ -11,6 +12,10 @@ mod target;
 #[allow(unused_imports)]
 pub use cache::FnResolutionCache;
 pub use cache::{Caches, FnResolutionCacheEntry};
+#[cfg(feature = "debugging")]
+#[cfg(feature = "metadata")]
+#[cfg(not(feature = "no_std"))]
+pub use dap::DapServer;
 #[cfg(not(feature = "unchecked"))]
 #[cfg(not(feature = "no_index"))]
 pub use data_check::calc_array_sizes;
@@ -2,13 +2,19 @@
 
 use super::{Caches, EvalContext, GlobalRuntimeState, Target};
 use crate::ast::{
-    ASTFlags, BinaryExpr, Expr, FlowControl, OpAssignment, Stmt, SwitchCasesCollection,
+    ASTFlags, BinaryExpr, Expr, FlowControl, Ident, OpAssignment, Stmt, SwitchCasesCollection,
 };
-use crate::func::{get_builtin_op_assignment_fn, get_hasher};
+use crate::func::{get_builtin_op_assignment_fn, get_hasher, FnIterator};
+use crate::packages::iter_basic::{LazyCursor, LazyIterator};
 use crate::tokenizer::Token;
+use crate::types::continuation::{resume_call, ResumeFrame, ResumePoint};
 use crate::types::dynamic::{AccessMode, Union};
-use crate::{Dynamic, Engine, RhaiResult, RhaiResultOf, Scope, VarDefInfo, ERR, INT};
+use crate::{
+    Continuation, Dynamic, Engine, Position, RhaiResult, RhaiResultOf, Scope, VarDefInfo, ERR, INT,
+};
+use std::any::TypeId;
 use std::hash::{Hash, Hasher};
+use std::num::NonZeroUsize;
 #[cfg(feature = "no_std")]
 use std::prelude::v1::*;
 
@@ -22,6 +28,73 @@ impl Engine {
         }
     }
 
+    /// Check a variable definition against the variable definition filter, if any.
+    #[allow(clippy::too_many_arguments)]
+    fn check_def_var_filter(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        scope: &mut Scope,
+        this_ptr: Option<&mut Dynamic>,
+        var_name: &str,
+        access: AccessMode,
+        pos: Position,
+    ) -> RhaiResultOf<()> {
+        let Some(ref filter) = self.def_var_filter else {
+            return Ok(());
+        };
+
+        let will_shadow = scope.contains(var_name);
+        let is_const = access == AccessMode::ReadOnly;
+        let info = VarDefInfo::new(var_name, is_const, global.scope_level, will_shadow);
+        let orig_scope_len = scope.len();
+        let context = EvalContext::new(self, global, caches, scope, this_ptr);
+        let filter_result = filter(true, info, context);
+
+        if orig_scope_len != scope.len() {
+            // The scope is changed, always search from now on
+            global.always_search_scope = true;
+        }
+
+        if filter_result? {
+            Ok(())
+        } else {
+            Err(ERR::ErrorForbiddenVariable(var_name.to_string(), pos).into())
+        }
+    }
+
+    /// Get the iterator function for a type.
+    ///
+    /// lib should only contain scripts, so technically they cannot have iterators.
+    ///
+    /// Search order:
+    /// 1) Global namespace - functions registered via Engine::register_XXX
+    /// 2) Global modules - packages
+    /// 3) Imported modules - functions marked with global namespace
+    /// 4) Global sub-modules - functions marked with global namespace
+    #[must_use]
+    pub(crate) fn get_iter_func<'a>(
+        &'a self,
+        _global: &'a GlobalRuntimeState,
+        iter_type: TypeId,
+    ) -> Option<&'a FnIterator> {
+        let iter_func = self
+            .global_modules
+            .iter()
+            .find_map(|m| m.get_iter(iter_type));
+
+        #[cfg(not(feature = "no_module"))]
+        let iter_func = iter_func
+            .or_else(|| _global.get_iter(iter_type))
+            .or_else(|| {
+                self.global_sub_modules
+                    .values()
+                    .find_map(|m| m.get_qualified_iter(iter_type))
+            });
+
+        iter_func
+    }
+
     /// Evaluate a statements block.
     pub(crate) fn eval_stmt_block(
         &self,
@@ -67,13 +140,41 @@ impl Engine {
 
         // Run the statements
         statements.iter().try_fold(Dynamic::UNIT, |_, stmt| {
-            let this_ptr = this_ptr.as_deref_mut();
-
             #[cfg(not(feature = "no_module"))]
             let orig_imports_len = global.num_imports();
 
+            let result = self.eval_stmt(
+                global,
+                caches,
+                scope,
+                this_ptr.as_deref_mut(),
+                stmt,
+                restore_orig_state,
+            );
+
+            #[cfg(feature = "debugging")]
             let result =
-                self.eval_stmt(global, caches, scope, this_ptr, stmt, restore_orig_state)?;
+                self.dbg_exception(global, caches, scope, this_ptr.as_deref_mut(), stmt, result);
+
+            let result = result.map_err(|err| {
+                Continuation::record_statement(err, scope, statements, stmt, orig_scope_len)
+            })?;
+
+            // Guard against too much memory
+            #[cfg(not(feature = "unchecked"))]
+            if self.limits.memory.is_some()
+                && matches!(
+                    stmt,
+                    Stmt::Var(..)
+                        | Stmt::Destructure(..)
+                        | Stmt::Assignment(..)
+                        | Stmt::FnCall(..)
+                        | Stmt::Expr(..)
+                )
+            {
+                let written = written_variables(stmt);
+                self.check_memory(global, scope, written, None, stmt.position())?;
+            }
 
             #[cfg(not(feature = "no_module"))]
             if matches!(stmt, Stmt::Import(..)) {
@@ -253,6 +354,325 @@ impl Engine {
         target.propagate_changed_value(pos)
     }
 
+    /// Evaluate a `for` loop, with the counter starting at `start`.
+    ///
+    /// If `iterable` is `None`, the value to iterate is evaluated from the expression. Otherwise
+    /// it is iterated after skipping a number of its items.
+    fn eval_for_loop(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        scope: &mut Scope,
+        mut this_ptr: Option<&mut Dynamic>,
+        x: &(Ident, Option<Ident>, FlowControl),
+        start: usize,
+        iterable: Option<(Dynamic, usize)>,
+    ) -> RhaiResult {
+        let (var_name, counter, FlowControl { expr, body, .. }) = x;
+
+        // Guard against too many variables
+        #[cfg(not(feature = "unchecked"))]
+        if scope.len() >= self.max_variables() - usize::from(counter.is_some()) {
+            return Err(ERR::ErrorTooManyVariables(var_name.pos).into());
+        }
+
+        let (iter_obj, skip) = match iterable {
+            Some(iterable) => iterable,
+            None => {
+                let value = self
+                    .eval_expr(global, caches, scope, this_ptr.as_deref_mut(), expr)?
+                    .flatten();
+                (value, 0)
+            }
+        };
+
+        // Keep the value to iterate again if the evaluation is suspended, unless it is expensive
+        // to clone, in which case the remaining items are recorded instead
+        #[cfg(not(feature = "no_index"))]
+        let iterable = (!iter_obj.is_array() && !iter_obj.is_blob()).then(|| iter_obj.clone());
+        #[cfg(feature = "no_index")]
+        let iterable = Some(iter_obj.clone());
+
+        // Lazy iterators are started, other values are iterated via their type iterators
+        let mut iter = LazyIterator::Values(iter_obj).start(self, global, expr.start_position())?;
+
+        // Skip the items already iterated
+        for _ in 0..skip {
+            match iter.next(self, global, expr.position()) {
+                Some(Ok(..)) => (),
+                Some(Err(err)) => return Err(err.fill_position(expr.position())),
+                None => return Ok(Dynamic::UNIT),
+            }
+        }
+
+        // Restore scope at end of statement
+        defer! { scope => rewind; let orig_scope_len = scope.len(); }
+
+        // Add the loop variables
+        let counter_index = counter.as_ref().map(|counter| {
+            scope.push(counter.name.clone(), 0 as INT);
+            scope.len() - 1
+        });
+
+        scope.push(var_name.name.clone(), ());
+        let index = scope.len() - 1;
+
+        let mut result = Dynamic::UNIT;
+
+        if body.is_empty() {
+            while let Some(iter_value) = iter.next(self, global, expr.position()) {
+                if let Err(err) = iter_value {
+                    return Err(err.fill_position(expr.position()));
+                }
+                self.track_operation(global, body.position())?;
+            }
+        } else {
+            let mut i = start;
+
+            while let Some(iter_value) = iter.next(self, global, expr.position()) {
+                // Increment counter
+                if let Some(counter_index) = counter_index {
+                    // As the variable increments from 0, this should always work
+                    // since any overflow will first be caught below.
+                    let index_value = i as INT;
+
+                    #[cfg(not(feature = "unchecked"))]
+                    #[allow(clippy::absurd_extreme_comparisons)]
+                    if index_value > crate::MAX_USIZE_INT {
+                        return Err(ERR::ErrorArithmetic(
+                            format!("for-loop counter overflow: {i}"),
+                            counter.as_ref().unwrap().pos,
+                        )
+                        .into());
+                    }
+
+                    *scope.get_mut_by_index(counter_index).write_lock().unwrap() =
+                        Dynamic::from_int(index_value);
+                }
+
+                // Set loop value
+                let value = iter_value
+                    .map_err(|err| err.fill_position(expr.position()))?
+                    .flatten();
+
+                *scope.get_mut_by_index(index).write_lock().unwrap() = value;
+
+                // Run block
+                let this_ptr = this_ptr.as_deref_mut();
+                let statements = body.statements();
+
+                match self.eval_stmt_block(global, caches, scope, this_ptr, statements, true) {
+                    Ok(_) => (),
+                    Err(err) => match *err {
+                        ERR::LoopBreak(false, ..) => (),
+                        ERR::LoopBreak(true, value, ..) => {
+                            result = value;
+                            break;
+                        }
+                        _ if Continuation::is_suspension(&err) => {
+                            let pos = expr.position();
+                            return Err(self.record_iteration(global, err, i, iterable, iter, pos));
+                        }
+                        _ => return Err(err),
+                    },
+                }
+
+                i += 1;
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Record the iteration of a `for` loop being run into a suspension.
+    ///
+    /// If the value iterated is not kept, the items remaining in the iterator are recorded.
+    #[cold]
+    #[inline(never)]
+    fn record_iteration(
+        &self,
+        _global: &mut GlobalRuntimeState,
+        err: crate::RhaiError,
+        i: usize,
+        iterable: Option<Dynamic>,
+        _iter: LazyCursor,
+        _pos: Position,
+    ) -> crate::RhaiError {
+        let iterable = match iterable {
+            Some(value) => (value, i + 1),
+            #[cfg(not(feature = "no_index"))]
+            None => {
+                let mut iter = _iter;
+                let mut items = crate::Array::new();
+
+                while let Some(Ok(item)) = iter.next(self, _global, _pos) {
+                    items.push(item);
+                }
+
+                (items.into(), 0)
+            }
+            #[cfg(feature = "no_index")]
+            None => unreachable!("`iterable` is always kept"),
+        };
+
+        Continuation::record_iteration(err, i, iterable)
+    }
+
+    /// Define a variable with its initial value, as part of a variable definition statement.
+    fn define_var(
+        &self,
+        _global: &mut GlobalRuntimeState,
+        scope: &mut Scope,
+        x: &(
+            Ident,
+            Expr,
+            Option<NonZeroUsize>,
+            Option<crate::ImmutableString>,
+        ),
+        options: ASTFlags,
+        pos: Position,
+        value: Dynamic,
+        rewind_scope: bool,
+    ) -> RhaiResult {
+        let (var_name, expr, index, typ) = x;
+
+        let access = if options.intersects(ASTFlags::CONSTANT) {
+            AccessMode::ReadOnly
+        } else {
+            AccessMode::ReadWrite
+        };
+        let export = options.intersects(ASTFlags::EXPORTED);
+
+        let mut value = self.intern_string(value);
+
+        // Check the type annotation, if any
+        if let Some(typ) = typ {
+            self.check_type_annotation(typ, &value, expr.start_position().or_else(pos))?;
+        }
+
+        let _alias = if !rewind_scope {
+            // Put global constants into global module
+            #[cfg(not(feature = "no_function"))]
+            #[cfg(not(feature = "no_module"))]
+            if _global.scope_level == 0
+                && access == AccessMode::ReadOnly
+                && _global.lib.iter().any(|m| !m.is_empty())
+            {
+                crate::func::locked_write(_global.constants.get_or_insert_with(|| {
+                    crate::Shared::new(crate::Locked::new(std::collections::BTreeMap::new()))
+                }))
+                .unwrap()
+                .insert(var_name.name.clone(), value.clone());
+            }
+
+            export.then_some(var_name)
+        } else if !export {
+            None
+        } else {
+            unreachable!("exported variable not on global level");
+        };
+
+        let index = match index {
+            Some(index) => {
+                let index = scope.len() - index.get();
+                value.set_access_mode(access);
+                *scope.get_mut_by_index(index) = value;
+                index
+            }
+            _ => {
+                scope.push_entry(var_name.name.clone(), access, value);
+                scope.len() - 1
+            }
+        };
+
+        // Keep the type annotation with the variable to check assignments
+        scope.set_type_by_index(index, typ.clone());
+
+        #[cfg(not(feature = "no_module"))]
+        if let Some(alias) = _alias {
+            scope.add_alias_by_index(scope.len() - 1, alias.as_str().into());
+        }
+
+        Ok(Dynamic::UNIT)
+    }
+
+    /// Evaluate the `catch` block of a `try` statement with the error caught.
+    fn eval_catch(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        scope: &mut Scope,
+        this_ptr: Option<&mut Dynamic>,
+        x: &FlowControl,
+        mut err: crate::RhaiError,
+    ) -> RhaiResult {
+        let FlowControl {
+            expr: catch_var,
+            branch,
+            ..
+        } = x;
+
+        let err_value = match err.unwrap_inner() {
+            // No error variable
+            _ if catch_var.is_unit() => Dynamic::UNIT,
+
+            ERR::ErrorRuntime(x, ..) => x.clone(),
+
+            #[cfg(feature = "no_object")]
+            _ => {
+                let _ = err.take_position();
+                err.to_string().into()
+            }
+            #[cfg(not(feature = "no_object"))]
+            _ => {
+                let mut err_map = crate::Map::new();
+                let err_pos = err.take_position();
+
+                err_map.insert("message".into(), err.to_string().into());
+
+                if let Some(ref source) = global.source {
+                    err_map.insert("source".into(), source.into());
+                }
+
+                if !err_pos.is_none() {
+                    err_map.insert("line".into(), (err_pos.line().unwrap() as INT).into());
+                    err_map.insert(
+                        "position".into(),
+                        (err_pos.position().unwrap_or(0) as INT).into(),
+                    );
+                }
+
+                err.dump_fields(&mut err_map);
+                err_map.into()
+            }
+        };
+
+        // Restore scope at end of block
+        defer! { scope if !catch_var.is_unit() => rewind; let orig_scope_len = scope.len(); }
+
+        if let Expr::Variable(x, ..) = catch_var {
+            // Guard against too many variables
+            #[cfg(not(feature = "unchecked"))]
+            if scope.len() >= self.max_variables() {
+                return Err(ERR::ErrorTooManyVariables(catch_var.position()).into());
+            }
+            scope.push(x.1.clone(), err_value);
+        }
+
+        let statements = branch.statements();
+
+        self.eval_stmt_block(global, caches, scope, this_ptr, statements, true)
+            .map(|_| Dynamic::UNIT)
+            .map_err(|result_err| match *result_err {
+                // Re-throw exception
+                ERR::ErrorRuntime(v, pos) if v.is_unit() => {
+                    err.set_position(pos);
+                    err
+                }
+                _ => result_err,
+            })
+    }
+
     /// Evaluate a statement.
     pub(crate) fn eval_stmt(
         &self,
@@ -285,13 +705,14 @@ impl Engine {
                     Ok(Dynamic::UNIT)
                 } else {
                     self.eval_stmt_block(global, caches, scope, this_ptr, stmts.statements(), true)
+                        .map_err(|err| Continuation::record_entry(err, 0))
                 }
             }
 
             // Function call
-            Stmt::FnCall(x, pos) => {
-                self.eval_fn_call_expr(global, caches, scope, this_ptr, x, *pos)
-            }
+            Stmt::FnCall(x, pos) => self
+                .eval_fn_call_expr(global, caches, scope, this_ptr, x, *pos)
+                .map_err(|err| Continuation::record_fn_call(err, x)),
 
             // Assignment
             Stmt::Assignment(x, ..) => {
@@ -325,6 +746,21 @@ impl Engine {
 
                     self.track_operation(global, lhs.position())?;
 
+                    // Type annotation of the variable, if any
+                    let typ = if scope.has_types() {
+                        let index = match lhs {
+                            _ if global.always_search_scope => None,
+                            Expr::Variable(_, Some(i), ..) => Some(i.get() as usize),
+                            Expr::Variable(v, None, ..) => v.0.map(NonZeroUsize::get),
+                            _ => None,
+                        };
+                        index
+                            .map_or_else(|| scope.search(&x.1), |i| Some(scope.len() - i))
+                            .and_then(|i| scope.get_type_by_index(i).cloned())
+                    } else {
+                        None
+                    };
+
                     let mut target = self.search_namespace(global, caches, scope, this_ptr, lhs)?;
 
                     let is_temp_result = !target.is_ref();
@@ -343,7 +779,50 @@ impl Engine {
                         .into());
                     }
 
-                    self.eval_op_assignment(global, caches, op_info, lhs, &mut target, rhs_val)?;
+                    match typ {
+                        Some(ref typ) if !target.as_ref().is_read_only() => {
+                            let pos = rhs.start_position();
+
+                            if op_info.is_op_assignment() {
+                                // Check the result, restoring the original value on mismatch
+                                let orig_val = target.as_ref().flatten_clone();
+                                self.eval_op_assignment(
+                                    global,
+                                    caches,
+                                    op_info,
+                                    lhs,
+                                    &mut target,
+                                    rhs_val,
+                                )?;
+                                let value = target.as_ref().flatten_clone();
+
+                                if let Err(err) = self.check_type_annotation(typ, &value, pos) {
+                                    *target.as_mut().write_lock::<Dynamic>().unwrap() = orig_val;
+                                    return Err(err);
+                                }
+                            } else {
+                                self.check_type_annotation(typ, &rhs_val, pos)?;
+                                self.eval_op_assignment(
+                                    global,
+                                    caches,
+                                    op_info,
+                                    lhs,
+                                    &mut target,
+                                    rhs_val,
+                                )?;
+                            }
+                        }
+                        _ => {
+                            self.eval_op_assignment(
+                                global,
+                                caches,
+                                op_info,
+                                lhs,
+                                &mut target,
+                                rhs_val,
+                            )?;
+                        }
+                    }
                 } else {
                     #[cfg(any(not(feature = "no_index"), not(feature = "no_object")))]
                     {
@@ -388,47 +867,28 @@ impl Engine {
                 }
 
                 // Let/const statement
-                let (var_name, expr, index) = &**x;
+                let (var_name, expr, _index, ..) = &**x;
 
                 let access = if options.intersects(ASTFlags::CONSTANT) {
                     AccessMode::ReadOnly
                 } else {
                     AccessMode::ReadWrite
                 };
-                let export = options.intersects(ASTFlags::EXPORTED);
 
                 // Check variable definition filter
-                if let Some(ref filter) = self.def_var_filter {
-                    let will_shadow = scope.contains(var_name.as_str());
-                    let is_const = access == AccessMode::ReadOnly;
-                    let info = VarDefInfo::new(
-                        var_name.as_str(),
-                        is_const,
-                        global.scope_level,
-                        will_shadow,
-                    );
-                    let orig_scope_len = scope.len();
-                    let context =
-                        EvalContext::new(self, global, caches, scope, this_ptr.as_deref_mut());
-                    let filter_result = filter(true, info, context);
-
-                    if orig_scope_len != scope.len() {
-                        // The scope is changed, always search from now on
-                        global.always_search_scope = true;
-                    }
-
-                    if !filter_result? {
-                        return Err(ERR::ErrorForbiddenVariable(
-                            var_name.as_str().to_string(),
-                            *pos,
-                        )
-                        .into());
-                    }
-                }
+                self.check_def_var_filter(
+                    global,
+                    caches,
+                    scope,
+                    this_ptr.as_deref_mut(),
+                    var_name.as_str(),
+                    access,
+                    *pos,
+                )?;
 
                 // Guard against too many variables
                 #[cfg(not(feature = "unchecked"))]
-                if index.is_none() && scope.len() >= self.max_variables() {
+                if _index.is_none() && scope.len() >= self.max_variables() {
                     return Err(ERR::ErrorTooManyVariables(*pos).into());
                 }
 
@@ -436,13 +896,58 @@ impl Engine {
                 let value = self
                     .eval_expr(global, caches, scope, this_ptr, expr)?
                     .flatten();
-                let mut value = self.intern_string(value);
 
-                let _alias = if !rewind_scope {
+                self.define_var(global, scope, x, *options, *pos, value, rewind_scope)
+            }
+
+            // Destructuring variable definition
+            Stmt::Destructure(x, options, pos) => {
+                let (pattern, expr) = &**x;
+
+                let access = if options.intersects(ASTFlags::CONSTANT) {
+                    AccessMode::ReadOnly
+                } else {
+                    AccessMode::ReadWrite
+                };
+
+                // Evaluate initial value
+                let value = self
+                    .eval_expr(global, caches, scope, this_ptr.as_deref_mut(), expr)?
+                    .flatten();
+
+                let bindings = pattern
+                    .match_value(&value)
+                    .ok_or_else(|| self.make_pattern_mismatch_err(pattern, &value))?;
+
+                for (var_name, value) in bindings {
+                    if !self.allow_shadowing() && scope.contains(&var_name) {
+                        return Err(ERR::ErrorVariableExists(var_name.to_string(), *pos).into());
+                    }
+
+                    // Check variable definition filter
+                    self.check_def_var_filter(
+                        global,
+                        caches,
+                        scope,
+                        this_ptr.as_deref_mut(),
+                        &var_name,
+                        access,
+                        *pos,
+                    )?;
+
+                    // Guard against too many variables
+                    #[cfg(not(feature = "unchecked"))]
+                    if scope.len() >= self.max_variables() {
+                        return Err(ERR::ErrorTooManyVariables(*pos).into());
+                    }
+
+                    let value = self.intern_string(value.flatten());
+
                     // Put global constants into global module
                     #[cfg(not(feature = "no_function"))]
                     #[cfg(not(feature = "no_module"))]
-                    if global.scope_level == 0
+                    if !rewind_scope
+                        && global.scope_level == 0
                         && access == AccessMode::ReadOnly
                         && global.lib.iter().any(|m| !m.is_empty())
                     {
@@ -452,29 +957,20 @@ impl Engine {
                             )
                         }))
                         .unwrap()
-                        .insert(var_name.name.clone(), value.clone());
+                        .insert(var_name.clone(), value.clone());
                     }
 
-                    export.then_some(var_name)
-                } else if !export {
-                    None
-                } else {
-                    unreachable!("exported variable not on global level");
-                };
+                    #[cfg(not(feature = "no_module"))]
+                    let alias = options
+                        .intersects(ASTFlags::EXPORTED)
+                        .then(|| var_name.clone());
 
-                match index {
-                    Some(index) => {
-                        value.set_access_mode(access);
-                        *scope.get_mut_by_index(scope.len() - index.get()) = value;
-                    }
-                    _ => {
-                        scope.push_entry(var_name.name.clone(), access, value);
-                    }
-                }
+                    scope.push_entry(var_name, access, value);
 
-                #[cfg(not(feature = "no_module"))]
-                if let Some(alias) = _alias {
-                    scope.add_alias_by_index(scope.len() - 1, alias.as_str().into());
+                    #[cfg(not(feature = "no_module"))]
+                    if let Some(alias) = alias {
+                        scope.add_alias_by_index(scope.len() - 1, alias);
+                    }
                 }
 
                 Ok(Dynamic::UNIT)
@@ -491,8 +987,10 @@ impl Engine {
 
                 if guard_val && !body.is_empty() {
                     self.eval_stmt_block(global, caches, scope, this_ptr, body.statements(), true)
+                        .map_err(|err| Continuation::record_entry(err, 0))
                 } else if !guard_val && !branch.is_empty() {
                     self.eval_stmt_block(global, caches, scope, this_ptr, branch.statements(), true)
+                        .map_err(|err| Continuation::record_entry(err, 1))
                 } else {
                     Ok(Dynamic::UNIT)
                 }
@@ -507,6 +1005,7 @@ impl Engine {
                         cases,
                         def_case,
                         ranges,
+                        patterns,
                     },
                 ) = &**x;
 
@@ -564,6 +1063,52 @@ impl Engine {
                     }
                 }
 
+                // Then check patterns
+                if result.is_none() {
+                    for (pattern, index) in patterns {
+                        let Some(bindings) = pattern.match_value(&value) else {
+                            continue;
+                        };
+
+                        let orig_scope_len = scope.len();
+
+                        for (name, v) in bindings {
+                            scope.push(name, v);
+                        }
+
+                        let BinaryExpr { lhs, rhs } = &expressions[*index];
+
+                        let cond_result = match lhs {
+                            Expr::BoolConstant(b, ..) => Ok(*b),
+                            c => self
+                                .eval_expr(global, caches, scope, this_ptr.as_deref_mut(), c)
+                                .and_then(|v| {
+                                    v.as_bool().map_err(|typ| {
+                                        self.make_type_mismatch_err::<bool>(typ, c.position())
+                                    })
+                                }),
+                        };
+
+                        let result = match cond_result {
+                            Ok(true) => Some(self.eval_expr(
+                                global,
+                                caches,
+                                scope,
+                                this_ptr.as_deref_mut(),
+                                rhs,
+                            )),
+                            Ok(false) => None,
+                            Err(err) => Some(Err(err)),
+                        };
+
+                        scope.rewind(orig_scope_len);
+
+                        if let Some(r) = result {
+                            return r;
+                        }
+                    }
+                }
+
                 result
                     .or_else(|| def_case.as_ref().map(|&index| &expressions[index].rhs))
                     .map_or(Ok(Dynamic::UNIT), |expr| {
@@ -592,7 +1137,7 @@ impl Engine {
                         Err(err) => match *err {
                             ERR::LoopBreak(false, ..) => (),
                             ERR::LoopBreak(true, value, ..) => break Ok(value),
-                            _ => break Err(err),
+                            _ => break Err(Continuation::record_entry(err, 0)),
                         },
                     }
                 }
@@ -624,7 +1169,7 @@ impl Engine {
                         Err(err) => match *err {
                             ERR::LoopBreak(false, ..) => (),
                             ERR::LoopBreak(true, value, ..) => break Ok(value),
-                            _ => break Err(err),
+                            _ => break Err(Continuation::record_entry(err, 0)),
                         },
                     }
                 }
@@ -647,7 +1192,7 @@ impl Engine {
                             Err(err) => match *err {
                                 ERR::LoopBreak(false, ..) => continue,
                                 ERR::LoopBreak(true, value, ..) => break Ok(value),
-                                _ => break Err(err),
+                                _ => break Err(Continuation::record_entry(err, 0)),
                             },
                         }
                     }
@@ -664,113 +1209,7 @@ impl Engine {
             }
 
             // For loop
-            Stmt::For(x, ..) => {
-                let (var_name, counter, FlowControl { expr, body, .. }) = &**x;
-
-                // Guard against too many variables
-                #[cfg(not(feature = "unchecked"))]
-                if scope.len() >= self.max_variables() - usize::from(counter.is_some()) {
-                    return Err(ERR::ErrorTooManyVariables(var_name.pos).into());
-                }
-
-                let iter_obj = self
-                    .eval_expr(global, caches, scope, this_ptr.as_deref_mut(), expr)?
-                    .flatten();
-
-                let iter_type = iter_obj.type_id();
-
-                // lib should only contain scripts, so technically they cannot have iterators
-
-                // Search order:
-                // 1) Global namespace - functions registered via Engine::register_XXX
-                // 2) Global modules - packages
-                // 3) Imported modules - functions marked with global namespace
-                // 4) Global sub-modules - functions marked with global namespace
-                let iter_func = self
-                    .global_modules
-                    .iter()
-                    .find_map(|m| m.get_iter(iter_type));
-
-                #[cfg(not(feature = "no_module"))]
-                let iter_func = iter_func
-                    .or_else(|| global.get_iter(iter_type))
-                    .or_else(|| {
-                        self.global_sub_modules
-                            .values()
-                            .find_map(|m| m.get_qualified_iter(iter_type))
-                    });
-
-                let iter_func = iter_func.ok_or_else(|| ERR::ErrorFor(expr.start_position()))?;
-
-                // Restore scope at end of statement
-                defer! { scope => rewind; let orig_scope_len = scope.len(); }
-
-                // Add the loop variables
-                let counter_index = counter.as_ref().map(|counter| {
-                    scope.push(counter.name.clone(), 0 as INT);
-                    scope.len() - 1
-                });
-
-                scope.push(var_name.name.clone(), ());
-                let index = scope.len() - 1;
-
-                let mut result = Dynamic::UNIT;
-
-                if body.is_empty() {
-                    for _ in iter_func(iter_obj) {
-                        self.track_operation(global, body.position())?;
-                    }
-                } else {
-                    for (i, iter_value) in iter_func(iter_obj).enumerate() {
-                        // Increment counter
-                        if let Some(counter_index) = counter_index {
-                            // As the variable increments from 0, this should always work
-                            // since any overflow will first be caught below.
-                            let index_value = i as INT;
-
-                            #[cfg(not(feature = "unchecked"))]
-                            #[allow(clippy::absurd_extreme_comparisons)]
-                            if index_value > crate::MAX_USIZE_INT {
-                                return Err(ERR::ErrorArithmetic(
-                                    format!("for-loop counter overflow: {i}"),
-                                    counter.as_ref().unwrap().pos,
-                                )
-                                .into());
-                            }
-
-                            *scope.get_mut_by_index(counter_index).write_lock().unwrap() =
-                                Dynamic::from_int(index_value);
-                        }
-
-                        // Set loop value
-                        let value = iter_value
-                            .map_err(|err| err.fill_position(expr.position()))?
-                            .flatten();
-
-                        *scope.get_mut_by_index(index).write_lock().unwrap() = value;
-
-                        // Run block
-                        let this_ptr = this_ptr.as_deref_mut();
-                        let statements = body.statements();
-
-                        match self
-                            .eval_stmt_block(global, caches, scope, this_ptr, statements, true)
-                        {
-                            Ok(_) => (),
-                            Err(err) => match *err {
-                                ERR::LoopBreak(false, ..) => (),
-                                ERR::LoopBreak(true, value, ..) => {
-                                    result = value;
-                                    break;
-                                }
-                                _ => return Err(err),
-                            },
-                        }
-                    }
-                }
-
-                Ok(result)
-            }
+            Stmt::For(x, ..) => self.eval_for_loop(global, caches, scope, this_ptr, x, 0, None),
 
             // Continue/Break statement
             Stmt::BreakLoop(expr, options, pos) => {
@@ -786,88 +1225,20 @@ impl Engine {
 
             // Try/Catch statement
             Stmt::TryCatch(x, ..) => {
-                let FlowControl {
-                    body,
-                    expr: catch_var,
-                    branch,
-                } = &**x;
+                let body = x.body.statements();
 
                 match self.eval_stmt_block(
                     global,
                     caches,
                     scope,
                     this_ptr.as_deref_mut(),
-                    body.statements(),
+                    body,
                     true,
                 ) {
                     r @ Ok(_) => r,
                     Err(err) if err.is_pseudo_error() => Err(err),
-                    Err(err) if !err.is_catchable() => Err(err),
-                    Err(mut err) => {
-                        let err_value = match err.unwrap_inner() {
-                            // No error variable
-                            _ if catch_var.is_unit() => Dynamic::UNIT,
-
-                            ERR::ErrorRuntime(x, ..) => x.clone(),
-
-                            #[cfg(feature = "no_object")]
-                            _ => {
-                                let _ = err.take_position();
-                                err.to_string().into()
-                            }
-                            #[cfg(not(feature = "no_object"))]
-                            _ => {
-                                let mut err_map = crate::Map::new();
-                                let err_pos = err.take_position();
-
-                                err_map.insert("message".into(), err.to_string().into());
-
-                                if let Some(ref source) = global.source {
-                                    err_map.insert("source".into(), source.into());
-                                }
-
-                                if !err_pos.is_none() {
-                                    err_map.insert(
-                                        "line".into(),
-                                        (err_pos.line().unwrap() as INT).into(),
-                                    );
-                                    err_map.insert(
-                                        "position".into(),
-                                        (err_pos.position().unwrap_or(0) as INT).into(),
-                                    );
-                                }
-
-                                err.dump_fields(&mut err_map);
-                                err_map.into()
-                            }
-                        };
-
-                        // Restore scope at end of block
-                        defer! { scope if !catch_var.is_unit() => rewind; let orig_scope_len = scope.len(); }
-
-                        if let Expr::Variable(x, ..) = catch_var {
-                            // Guard against too many variables
-                            #[cfg(not(feature = "unchecked"))]
-                            if scope.len() >= self.max_variables() {
-                                return Err(ERR::ErrorTooManyVariables(catch_var.position()).into());
-                            }
-                            scope.push(x.1.clone(), err_value);
-                        }
-
-                        let this_ptr = this_ptr.as_deref_mut();
-                        let statements = branch.statements();
-
-                        self.eval_stmt_block(global, caches, scope, this_ptr, statements, true)
-                            .map(|_| Dynamic::UNIT)
-                            .map_err(|result_err| match *result_err {
-                                // Re-throw exception
-                                ERR::ErrorRuntime(v, pos) if v.is_unit() => {
-                                    err.set_position(pos);
-                                    err
-                                }
-                                _ => result_err,
-                            })
-                    }
+                    Err(err) if !err.is_catchable() => Err(Continuation::record_entry(err, 0)),
+                    Err(err) => self.eval_catch(global, caches, scope, this_ptr, x, err),
                 }
             }
 
@@ -1043,4 +1414,420 @@ impl Engine {
                 _ => Err(err),
             })
     }
+
+    /// Resume a suspended evaluation of a series of statements at global level.
+    ///
+    /// `frames` are the frames being run at the suspension point (outermost first, starting with
+    /// global level), and the suspended statement is completed with `output`, the result of the
+    /// function call that suspended the evaluation.
+    pub(crate) fn resume_global_statements(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        scope: &mut Scope,
+        statements: &[Stmt],
+        frames: &mut [ResumeFrame],
+        output: RhaiResult,
+    ) -> RhaiResult {
+        let (frame, frames) = frames.split_first_mut().expect("`frames` is not empty");
+
+        let result = if !frame.path.is_empty() {
+            let path = &mut frame.path;
+            self.resume_stmt_block(
+                global, caches, scope, statements, frame.pos, path, frames, output, false,
+            )
+        } else if !frames.is_empty() {
+            // A function called directly by the host
+            self.resume_frames(global, caches, frame.pos, frames, output)
+        } else {
+            Err(resume_mismatch_err(0))
+        };
+
+        result.or_else(|err| match *err {
+            ERR::Return(out, ..) | ERR::Exit(out, ..) => Ok(out),
+            ERR::LoopBreak(..) => {
+                unreachable!("no outer loop scope to break out of")
+            }
+            _ => Err(err),
+        })
+    }
+
+    /// Resume a suspended evaluation of the script-defined functions being called by a suspended
+    /// statement, returning the value of the function call.
+    ///
+    /// If there are no `frames`, the suspended statement calls the suspending function, and
+    /// `output` is returned.
+    fn resume_frames(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        pos: Position,
+        frames: &mut [ResumeFrame],
+        output: RhaiResult,
+    ) -> RhaiResult {
+        match frames.split_first_mut() {
+            None => output.map_err(|err| err.fill_position(pos)),
+            #[cfg(not(feature = "no_function"))]
+            Some((frame, frames)) => {
+                self.resume_script_fn(global, caches, frame, frames, output, pos)
+            }
+            #[cfg(feature = "no_function")]
+            Some(..) => {
+                let _ = (global, caches, pos);
+                Err(resume_mismatch_err(0))
+            }
+        }
+    }
+
+    /// Resume a suspended evaluation of a statements block.
+    ///
+    /// The statement at the first resume point is entered (if there are more resume points) or
+    /// completed with the output of its function call at `pos`, then the rest of the block is run.
+    pub(crate) fn resume_stmt_block(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        scope: &mut Scope,
+        statements: &[Stmt],
+        pos: Position,
+        path: &mut [ResumePoint],
+        frames: &mut [ResumeFrame],
+        output: RhaiResult,
+        restore_orig_state: bool,
+    ) -> RhaiResult {
+        let (point, path) = path.split_first_mut().expect("`path` is not empty");
+        let (index, scope_len) = (point.index, point.scope_len);
+
+        let Some(stmt) = statements.get(index) else {
+            return Err(resume_mismatch_err(index));
+        };
+
+        // Restore scope at end of block if necessary
+        defer! { scope if restore_orig_state => rewind; let orig_scope_len = scope_len; }
+
+        // Restore global state at end of block if necessary
+        let orig_always_search_scope = global.always_search_scope;
+        #[cfg(not(feature = "no_module"))]
+        let orig_imports_len = global.num_imports();
+
+        if restore_orig_state {
+            global.scope_level += 1;
+        }
+
+        defer! { global if restore_orig_state => move |g| {
+            g.scope_level -= 1;
+
+            #[cfg(not(feature = "no_module"))]
+            g.truncate_imports(orig_imports_len);
+
+            g.always_search_scope = orig_always_search_scope;
+        }}
+
+        // Pop new function resolution caches at end of block
+        defer! {
+            caches => rewind_fn_resolution_caches;
+            let orig_fn_resolution_caches_len = caches.fn_resolution_caches_len();
+        }
+
+        // Run the import statements before the resume point again
+        #[cfg(not(feature = "no_module"))]
+        for stmt in &statements[..index] {
+            if matches!(stmt, Stmt::Import(..)) {
+                let _ = self.eval_stmt(global, caches, scope, None, stmt, restore_orig_state)?;
+
+                // Functions may be resolved differently with the imported module
+                if restore_orig_state
+                    && caches.fn_resolution_caches_len() == orig_fn_resolution_caches_len
+                {
+                    caches.push_fn_resolution_cache();
+                } else {
+                    caches.fn_resolution_cache_mut().clear();
+                }
+            }
+        }
+
+        let result = if path.is_empty() {
+            self.complete_stmt(
+                global,
+                caches,
+                scope,
+                stmt,
+                pos,
+                frames,
+                output,
+                restore_orig_state,
+            )
+        } else {
+            let (entry, iterable) = (point.entry, point.iterable.take());
+            self.resume_stmt(
+                global, caches, scope, stmt, entry, iterable, pos, path, frames, output,
+            )
+        };
+
+        let result = result.map_err(|err| {
+            Continuation::record_statement(err, scope, statements, stmt, scope_len)
+        })?;
+
+        // Run the rest of the block
+        let rest = &statements[index + 1..];
+
+        if rest.is_empty() {
+            return Ok(result);
+        }
+
+        self.eval_stmt_block(global, caches, scope, None, rest, false)
+            .map_err(|err| Continuation::record_offset(err, index + 1, scope_len))
+    }
+
+    /// Complete a suspended statement with the output of its function call at `pos`, resuming the
+    /// script-defined functions being called first.
+    ///
+    /// Nothing in the statement before the function call is run again.
+    fn complete_stmt(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        scope: &mut Scope,
+        stmt: &Stmt,
+        pos: Position,
+        frames: &mut [ResumeFrame],
+        output: RhaiResult,
+        rewind_scope: bool,
+    ) -> RhaiResult {
+        let Some((call, call_pos)) = resume_call(stmt).filter(|&(.., p)| p == pos) else {
+            return Err(ERR::ErrorSystem(
+                "Cannot resume evaluation".into(),
+                format!("no function call to resume at {pos}").into(),
+            )
+            .into());
+        };
+
+        let value = self
+            .resume_frames(global, caches, call_pos, frames, output)
+            .map_err(|err| Continuation::record_fn_call(err, call))?
+            .flatten();
+
+        match stmt {
+            Stmt::Var(x, options, pos) => {
+                self.define_var(global, scope, x, *options, *pos, value, rewind_scope)
+            }
+            Stmt::Assignment(x) => {
+                let (op_info, BinaryExpr { lhs, .. }) = &**x;
+                let rhs = Expr::DynamicConstant(value.into(), call_pos);
+                let lhs = lhs.clone();
+                let stmt = Stmt::Assignment((op_info.clone(), BinaryExpr { lhs, rhs }).into());
+
+                self.eval_stmt(global, caches, scope, None, &stmt, rewind_scope)
+            }
+            Stmt::Return(_, options, pos) => {
+                let expr = Expr::DynamicConstant(value.into(), call_pos);
+                let stmt = Stmt::Return(Some(expr.into()), *options, *pos);
+
+                self.eval_stmt(global, caches, scope, None, &stmt, rewind_scope)
+            }
+            _ => Ok(value),
+        }
+    }
+
+    /// Resume a suspended evaluation of a statement by entering its body.
+    ///
+    /// `entry` identifies the body to enter (e.g. the branch of an `if` statement or the
+    /// iteration of a `for` loop), and `iterable` is the value to iterate by a `for` loop together
+    /// with the number of its items to skip.
+    fn resume_stmt(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        scope: &mut Scope,
+        stmt: &Stmt,
+        entry: usize,
+        iterable: Option<(Dynamic, usize)>,
+        pos: Position,
+        path: &mut [ResumePoint],
+        frames: &mut [ResumeFrame],
+        output: RhaiResult,
+    ) -> RhaiResult {
+        match stmt {
+            // Block scope
+            Stmt::Block(stmts, ..) => self
+                .resume_stmt_block(
+                    global,
+                    caches,
+                    scope,
+                    stmts.statements(),
+                    pos,
+                    path,
+                    frames,
+                    output,
+                    true,
+                )
+                .map_err(|err| Continuation::record_entry(err, 0)),
+
+            // If statement
+            Stmt::If(x, ..) => {
+                let body = match entry {
+                    0 => &x.body,
+                    1 => &x.branch,
+                    _ => return Err(resume_mismatch_err(entry)),
+                };
+
+                self.resume_stmt_block(
+                    global,
+                    caches,
+                    scope,
+                    body.statements(),
+                    pos,
+                    path,
+                    frames,
+                    output,
+                    true,
+                )
+                .map_err(|err| Continuation::record_entry(err, entry))
+            }
+
+            // Loops
+            Stmt::While(x, ..) | Stmt::Do(x, ..) => {
+                let statements = x.body.statements();
+
+                let is_continue = match self.resume_stmt_block(
+                    global, caches, scope, statements, pos, path, frames, output, true,
+                ) {
+                    Ok(..) => false,
+                    Err(err) => match *err {
+                        ERR::LoopBreak(false, ..) => true,
+                        ERR::LoopBreak(true, value, ..) => return Ok(value),
+                        _ => return Err(Continuation::record_entry(err, 0)),
+                    },
+                };
+
+                // Check the condition of a do loop, unless continued
+                if let Stmt::Do(x, options, ..) = stmt {
+                    let is_while = !options.intersects(ASTFlags::NEGATED);
+
+                    if !is_continue {
+                        let condition = self
+                            .eval_expr(global, caches, scope, None, &x.expr)?
+                            .as_bool()
+                            .map_err(|typ| {
+                                self.make_type_mismatch_err::<bool>(typ, x.expr.position())
+                            })?;
+
+                        if condition ^ is_while {
+                            return Ok(Dynamic::UNIT);
+                        }
+                    }
+                }
+
+                // Continue the loop
+                self.eval_stmt(global, caches, scope, None, stmt, true)
+            }
+
+            // For loop
+            Stmt::For(x, ..) => {
+                let Some(iterable) = iterable else {
+                    return Err(resume_mismatch_err(entry));
+                };
+
+                let statements = x.2.body.statements();
+                let num_vars = 1 + usize::from(x.1.is_some());
+                let orig_scope_len = path[0].scope_len.saturating_sub(num_vars);
+
+                let result = self.resume_stmt_block(
+                    global, caches, scope, statements, pos, path, frames, output, true,
+                );
+
+                // Remove the loop variables
+                scope.rewind(orig_scope_len);
+
+                match result {
+                    Ok(..) => (),
+                    Err(err) => match *err {
+                        ERR::LoopBreak(false, ..) => (),
+                        ERR::LoopBreak(true, value, ..) => return Ok(value),
+                        _ if Continuation::is_suspension(&err) => {
+                            return Err(Continuation::record_iteration(err, entry, iterable))
+                        }
+                        _ => return Err(err),
+                    },
+                }
+
+                // Continue the loop with the next item
+                self.eval_for_loop(global, caches, scope, None, x, entry + 1, Some(iterable))
+            }
+
+            // Try/Catch statement
+            Stmt::TryCatch(x, ..) => {
+                let statements = x.body.statements();
+
+                match self.resume_stmt_block(
+                    global, caches, scope, statements, pos, path, frames, output, true,
+                ) {
+                    r @ Ok(_) => r,
+                    Err(err) if err.is_pseudo_error() => Err(err),
+                    Err(err) if !err.is_catchable() => Err(Continuation::record_entry(err, 0)),
+                    Err(err) => self.eval_catch(global, caches, scope, None, x, err),
+                }
+            }
+
+            _ => Err(resume_mismatch_err(entry)),
+        }
+    }
+}
+
+/// Get the names of the variables that a statement may write to.
+///
+/// These are the variables declared by the statement, the roots of assignment targets and
+/// indexing or method-call chains, and the first arguments of function calls (which may be passed by
+/// reference).
+#[cfg(not(feature = "unchecked"))]
+fn written_variables(stmt: &Stmt) -> crate::StaticVec<crate::ImmutableString> {
+    use crate::ast::ASTNode;
+
+    /// Get the name of the variable at the root of an indexing or property chain.
+    fn root_variable(mut expr: &Expr) -> Option<crate::ImmutableString> {
+        loop {
+            match expr {
+                Expr::Dot(x, ..) | Expr::Index(x, ..) => expr = &x.lhs,
+                Expr::Variable(x, ..) => return Some(x.1.clone()),
+                _ => return None,
+            }
+        }
+    }
+
+    let mut names = crate::StaticVec::new_const();
+
+    match stmt {
+        Stmt::Var(x, ..) => names.push(x.0.name.clone()),
+        Stmt::Destructure(x, ..) => {
+            names.extend(x.0.variables().into_iter().map(|v| v.name.clone()))
+        }
+        Stmt::Assignment(x) => names.extend(root_variable(&x.1.lhs)),
+        _ => (),
+    }
+
+    stmt.walk(&mut Vec::new(), &mut |path| {
+        match path.last() {
+            Some(ASTNode::Expr(Expr::Dot(x, ..) | Expr::Index(x, ..))) => {
+                names.extend(root_variable(&x.lhs))
+            }
+            Some(ASTNode::Expr(Expr::FnCall(x, ..)) | ASTNode::Stmt(Stmt::FnCall(x, ..))) => {
+                names.extend(x.args.first().and_then(root_variable))
+            }
+            _ => (),
+        }
+        true
+    });
+
+    names
+}
+
+/// Create an error for a resume point that does not match the script.
+#[cold]
+#[inline(never)]
+fn resume_mismatch_err(index: usize) -> crate::RhaiError {
+    ERR::ErrorSystem(
+        "Cannot resume evaluation".into(),
+        format!("no statement to resume at index {index}").into(),
+    )
+    .into()
 }
@@ -0,0 +1,175 @@
+//! Module which defines the mechanism for registering asynchronous functions.
+#![allow(non_snake_case)]
+#![allow(unused_imports)]
+#![allow(unused_mut)]
+#![allow(unused_variables)]
+
+use super::call::FnCallArgs;
+use super::function::RhaiFunc;
+use super::native::{locked_write, Locked, SendSync, Shared};
+use super::register::{by_value, RhaiNativeFunc};
+use crate::types::dynamic::Variant;
+use crate::{Dynamic, NativeCallContext, RhaiResult, RhaiResultOf, ERR};
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+use std::{
+    any::{type_name, TypeId},
+    future::Future,
+    pin::Pin,
+};
+
+/// A boxed [`Future`] returned by an asynchronous function.
+#[cfg(not(feature = "sync"))]
+pub type AsyncResult = Pin<Box<dyn Future<Output = RhaiResult>>>;
+/// A boxed [`Future`] returned by an asynchronous function.
+#[cfg(feature = "sync")]
+pub type AsyncResult = Pin<Box<dyn Future<Output = RhaiResult> + Send + Sync>>;
+
+/// Trait to register custom asynchronous Rust functions.
+///
+/// An asynchronous function takes all its parameters by value and returns a [`Future`].
+///
+/// # Type Parameters
+///
+/// * `A` - a tuple containing parameter types.
+/// * `N` - a constant generic containing the number of parameters, must be consistent with `ARGS`.
+/// * `R` - output type of the [`Future`]; if the [`Future`] outputs `Result`, it is the unwrapped inner value type.
+/// * `F` - a constant boolean generic indicating whether the [`Future`] is fallible (i.e. outputs `Result<T, Box<EvalAltResult>>`).
+pub trait RhaiNativeAsyncFunc<A: 'static, const N: usize, R: 'static, const F: bool> {
+    /// Call this function with the arguments, returning a boxed [`Future`].
+    #[must_use]
+    fn call_async(&self, args: &mut FnCallArgs) -> AsyncResult;
+    /// Get the type ID's of this function's parameters.
+    #[must_use]
+    fn param_types() -> [TypeId; N];
+    /// _(metadata)_ Get the type names of this function's parameters.
+    /// Exported under the `metadata` feature only.
+    #[cfg(feature = "metadata")]
+    #[must_use]
+    fn param_names() -> [&'static str; N];
+}
+
+macro_rules! def_register_async {
+    () => {
+        def_register_async!(imp 0 ;);
+    };
+    (imp $n:expr ; $($par:ident),*) => {
+        impl<
+            FN: Fn($($par),*) -> FUT + SendSync + 'static,
+            FUT: Future<Output = RET> + SendSync + 'static,
+            $($par: Variant + Clone,)*
+            RET: Variant + Clone,
+        > RhaiNativeAsyncFunc<($($par,)*), $n, RET, false> for FN {
+            #[inline(always)] fn param_types() -> [TypeId;$n] { [$(TypeId::of::<$par>()),*] }
+            #[cfg(feature = "metadata")] #[inline(always)] fn param_names() -> [&'static str;$n] { [$(type_name::<$par>()),*] }
+            #[inline] fn call_async(&self, args: &mut FnCallArgs) -> AsyncResult {
+                // The arguments are assumed to be of the correct number and types!
+                let mut drain = args.iter_mut();
+                $(let mut $par = by_value::<$par>(drain.next().unwrap()); )*
+
+                // Call the function with each argument value
+                let future = self($($par),*);
+
+                // Map the result
+                Box::pin(async move { Ok(Dynamic::from(future.await)) })
+            }
+        }
+
+        impl<
+            FN: Fn($($par),*) -> FUT + SendSync + 'static,
+            FUT: Future<Output = RhaiResultOf<RET>> + SendSync + 'static,
+            $($par: Variant + Clone,)*
+            RET: Variant + Clone,
+        > RhaiNativeAsyncFunc<($($par,)*), $n, RET, true> for FN {
+            #[inline(always)] fn param_types() -> [TypeId;$n] { [$(TypeId::of::<$par>()),*] }
+            #[cfg(feature = "metadata")] #[inline(always)] fn param_names() -> [&'static str;$n] { [$(type_name::<$par>()),*] }
+            #[inline] fn call_async(&self, args: &mut FnCallArgs) -> AsyncResult {
+                // The arguments are assumed to be of the correct number and types!
+                let mut drain = args.iter_mut();
+                $(let mut $par = by_value::<$par>(drain.next().unwrap()); )*
+
+                // Call the function with each argument value
+                let future = self($($par),*);
+
+                // Map the result
+                Box::pin(async move { future.await.map(Dynamic::from) })
+            }
+        }
+    };
+    ($p0:ident:$n0:expr $(, $p:ident: $n:expr)*) => {
+        def_register_async!(imp $n0 ; $p0 $(, $p)*);
+        def_register_async!($($p: $n),*);
+    };
+}
+
+def_register_async!(A:20, B:19, C:18, D:17, E:16, F:15, G:14, H:13, J:12, K:11, L:10, M:9, N:8, P:7, Q:6, R:5, S:4, T:3, U:2, V:1);
+
+/// Wrapper that turns a [`RhaiNativeAsyncFunc`] into a [`RhaiNativeFunc`] which suspends the
+/// evaluation with an [`AsyncCall`] holding its [`Future`].
+pub struct AsyncFn<FUNC, const F: bool>(pub FUNC);
+
+impl<A: 'static, const N: usize, R: Variant + Clone, const F: bool, FUNC>
+    RhaiNativeFunc<A, N, true, R, true> for AsyncFn<FUNC, F>
+where
+    FUNC: RhaiNativeAsyncFunc<A, N, R, F> + SendSync + 'static,
+{
+    #[inline]
+    fn into_rhai_function(self, is_pure: bool, is_volatile: bool) -> RhaiFunc {
+        let func = self.0;
+
+        RhaiFunc::Pure {
+            func: Shared::new(
+                move |ctx: Option<NativeCallContext>, args: &mut FnCallArgs| {
+                    let ctx = ctx.unwrap();
+
+                    if !ctx.global_runtime_state().is_async {
+                        return Err(ERR::ErrorSystem(
+                            "Cannot call asynchronous function".into(),
+                            "not within an asynchronous evaluation".into(),
+                        )
+                        .into());
+                    }
+
+                    ctx.suspend(AsyncCall::new(func.call_async(args)))
+                },
+            ),
+            has_context: true,
+            is_pure,
+            is_volatile,
+        }
+    }
+    #[inline(always)]
+    fn param_types() -> [TypeId; N] {
+        FUNC::param_types()
+    }
+    #[cfg(feature = "metadata")]
+    #[inline(always)]
+    fn param_names() -> [&'static str; N] {
+        FUNC::param_names()
+    }
+    #[cfg(feature = "metadata")]
+    #[inline(always)]
+    fn return_type() -> TypeId {
+        TypeId::of::<R>()
+    }
+}
+
+/// A pending call to an asynchronous function, passed to the host when the evaluation is
+/// suspended by the call.
+#[derive(Clone)]
+pub struct AsyncCall(Shared<Locked<Option<AsyncResult>>>);
+
+impl AsyncCall {
+    /// Create a new [`AsyncCall`] with the [`Future`] of the function call.
+    #[inline]
+    #[must_use]
+    pub fn new(future: AsyncResult) -> Self {
+        Self(Shared::new(Locked::new(Some(future))))
+    }
+    /// Take the [`Future`] of the function call, if not already taken.
+    #[inline]
+    #[must_use]
+    pub fn take(&self) -> Option<AsyncResult> {
+        locked_write(&self.0).and_then(|mut future| future.take())
+    }
+}
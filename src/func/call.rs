@@ -145,9 +145,9 @@ impl Engine {
             "{fn_name} ({})",
             args.iter()
                 .map(|a| if a.is_string() {
-                    "&str | ImmutableString | String"
+                    "&str | ImmutableString | String".into()
                 } else {
-                    self.map_type_name(a.type_name())
+                    self.map_value_type_name(a)
                 })
                 .collect::<FnArgsVec<_>>()
                 .join(", ")
@@ -576,10 +576,28 @@ impl Engine {
             && match fn_name {
                 // Handle type_of()
                 KEYWORD_TYPE_OF if args.len() == 1 => {
-                    let typ = self.get_interned_string(self.map_type_name(args[0].type_name()));
+                    let typ = self.get_interned_string(&*self.map_value_type_name(args[0]));
                     return Ok((typ.into(), false));
                 }
 
+                // Construct a value of a script-defined record type
+                #[cfg(not(feature = "no_function"))]
+                #[cfg(not(feature = "no_object"))]
+                crate::engine::FN_RECORD if args.len() == 2 => {
+                    let name = args[0].take().cast::<ImmutableString>();
+                    let fields = args[1].take().cast::<crate::Map>();
+                    return Ok((Dynamic::from(crate::Record::new(name, fields)), false));
+                }
+                // Construct a variant of a script-defined enum type
+                #[cfg(not(feature = "no_function"))]
+                crate::engine::FN_ENUM if args.len() >= 2 => {
+                    let type_name = args[0].take().cast::<ImmutableString>();
+                    let variant = args[1].take().cast::<ImmutableString>();
+                    let values = args[2..].iter_mut().map(|v| v.take());
+                    let value = crate::EnumValue::new(type_name, variant, values);
+                    return Ok((Dynamic::from(value), false));
+                }
+
                 #[cfg(not(feature = "no_closure"))]
                 crate::engine::KEYWORD_IS_SHARED if args.len() == 1 => {
                     return Ok((args[0].is_shared().into(), false))
@@ -616,7 +634,7 @@ impl Engine {
             #[cfg(not(feature = "no_object"))]
             if _is_method_call && !args.is_empty() {
                 let typed_hash =
-                    crate::calc_typed_method_hash(hash, self.map_type_name(args[0].type_name()));
+                    crate::calc_typed_method_hash(hash, &self.map_value_type_name(args[0]));
                 resolved =
                     self.resolve_fn(global, caches, local_entry, None, typed_hash, None, false);
             }
@@ -633,7 +651,10 @@ impl Engine {
                 let fn_def = &*fn_def;
                 let env = env.as_deref();
 
-                if fn_def.body.is_empty() {
+                if fn_def.body.is_empty()
+                    && fn_def.param_types.is_empty()
+                    && fn_def.return_type.is_none()
+                {
                     return Ok((Dynamic::UNIT, false));
                 }
 
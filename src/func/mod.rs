@@ -1,5 +1,6 @@
 //! Module defining mechanisms to handle function calls in Rhai.
 
+pub mod async_fn;
 pub mod builtin;
 pub mod call;
 pub mod func_args;
@@ -12,6 +13,7 @@ pub mod plugin;
 pub mod register;
 pub mod script;
 
+pub use async_fn::RhaiNativeAsyncFunc;
 pub use builtin::{get_builtin_binary_op_fn, get_builtin_op_assignment_fn};
 #[cfg(not(feature = "no_closure"))]
 pub use call::ensure_no_data_race;
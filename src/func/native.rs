@@ -250,6 +250,50 @@ impl<'a> NativeCallContext<'a> {
     pub const fn tag(&self) -> Option<&Dynamic> {
         Some(&self.global.tag)
     }
+    /// Suspend the evaluation, passing a value to the host.
+    ///
+    /// This method returns [`ErrorTerminated`][crate::EvalAltResult::ErrorTerminated] carrying a
+    /// [`Continuation`][crate::Continuation], which must be returned straight back by the native
+    /// function. The [`Continuation`][crate::Continuation] records the position of this function
+    /// call, the script-defined functions being called and the variables in scope.
+    ///
+    /// The function call must be the whole value of a statement (e.g. `let x = ask(...);`),
+    /// otherwise the evaluation fails with [`ErrorSystem`][crate::EvalAltResult::ErrorSystem].
+    /// When the evaluation is resumed via [`Engine::resume`], the statement completes with the
+    /// value passed to [`Engine::resume`] as the result of this function call, and this function
+    /// is not called again.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::{Engine, NativeCallContext, Scope};
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// engine.register_fn("ask", |context: NativeCallContext, question: &str| {
+    ///     context.suspend(question.to_string())
+    /// });
+    ///
+    /// let ast = engine.compile(r#"let x = ask("x?"); x * 2"#)?;
+    ///
+    /// let err = engine.eval_ast::<i64>(&ast).unwrap_err();
+    /// let continuation = err.into_continuation().expect("suspended");
+    ///
+    /// assert_eq!(continuation.value().to_string(), "x?");
+    ///
+    /// let result = engine.resume::<i64>(&mut Scope::new(), &ast, continuation, 21_i64)?;
+    ///
+    /// assert_eq!(result, 42);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn suspend(&self, value: impl Variant + Clone) -> RhaiResult {
+        let continuation =
+            crate::Continuation::new(self.global, self.fn_name, Dynamic::from(value), self.pos);
+
+        Err(ERR::ErrorTerminated(Dynamic::from(continuation), self.pos).into())
+    }
     /// Get an iterator over the current set of modules imported via `import` statements
     /// in reverse order.
     ///
@@ -661,6 +705,13 @@ pub type OnDebugCallback = dyn Fn(&str, Option<&str>, Position);
 #[cfg(feature = "sync")]
 pub type OnDebugCallback = dyn Fn(&str, Option<&str>, Position) + Send + Sync;
 
+/// Callback function for parse warnings.
+#[cfg(not(feature = "sync"))]
+pub type OnParseWarningCallback = dyn Fn(&str, Position);
+/// Callback function for parse warnings.
+#[cfg(feature = "sync")]
+pub type OnParseWarningCallback = dyn Fn(&str, Position) + Send + Sync;
+
 /// _(internals)_ Callback function when a property accessed is not found in a [`Map`][crate::Map].
 /// Exported under the `internals` feature only.
 #[cfg(not(feature = "sync"))]
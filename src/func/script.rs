@@ -4,7 +4,8 @@
 use super::call::FnCallArgs;
 use crate::ast::{EncapsulatedEnviron, ScriptFuncDef};
 use crate::eval::{Caches, GlobalRuntimeState};
-use crate::{Dynamic, Engine, Position, RhaiResult, Scope, ERR};
+use crate::types::continuation::ResumeFrame;
+use crate::{Continuation, Dynamic, Engine, Position, RhaiResult, Scope, ERR};
 #[cfg(feature = "no_std")]
 use std::prelude::v1::*;
 
@@ -43,12 +44,22 @@ impl Engine {
             return Err(ERR::ErrorStackOverflow(pos).into());
         }
 
+        // Check the types of arguments
+        for (typ, arg) in fn_def.param_types.iter().zip(args.iter()) {
+            if let Some(typ) = typ {
+                self.check_type_annotation(typ, arg, pos)?;
+            }
+        }
+
         #[cfg(feature = "debugging")]
-        if self.debugger_interface.is_none() && fn_def.body.is_empty() {
+        if self.debugger_interface.is_none()
+            && fn_def.body.is_empty()
+            && fn_def.return_type.is_none()
+        {
             return Ok(Dynamic::UNIT);
         }
         #[cfg(not(feature = "debugging"))]
-        if fn_def.body.is_empty() {
+        if fn_def.body.is_empty() && fn_def.return_type.is_none() {
             return Ok(Dynamic::UNIT);
         }
 
@@ -69,12 +80,45 @@ impl Engine {
             return Err(ERR::ErrorTooManyVariables(pos).into());
         }
 
+        // Guard against too much memory, counting the variables of the caller as of the last check
+        #[cfg(not(feature = "unchecked"))]
+        let orig_memory = self.limits.memory.map(|_| {
+            let orig_memory = (
+                global.memory_of_callers,
+                global.memory_in_scope,
+                std::mem::take(&mut global.memory_sizes),
+            );
+            global.memory_of_callers += global.memory_in_scope;
+            global.memory_in_scope = 0;
+            orig_memory
+        });
+        #[cfg(not(feature = "unchecked"))]
+        if let Some(ref orig_memory) = orig_memory {
+            let args = args.iter().map(|v| &**v);
+
+            if let Err(err) = self.check_memory(global, scope, None::<&str>, args, pos) {
+                (
+                    global.memory_of_callers,
+                    global.memory_in_scope,
+                    global.memory_sizes,
+                ) = orig_memory.clone();
+                return Err(err);
+            }
+        }
+
         // Put arguments into scope as variables
         scope.extend(fn_def.params.iter().cloned().zip(args.iter_mut().map(|v| {
             // Actually consume the arguments instead of cloning them
             v.take()
         })));
 
+        // Keep the type annotations of parameters with the variables to check assignments
+        for (i, typ) in fn_def.param_types.iter().enumerate() {
+            if typ.is_some() {
+                scope.set_type_by_index(orig_scope_len + i, typ.clone());
+            }
+        }
+
         // Push a new call stack frame
         #[cfg(feature = "debugging")]
         if self.is_debugger_registered() {
@@ -118,42 +162,19 @@ impl Engine {
         }
 
         // Evaluate the function
-        let mut _result: RhaiResult = self
-            .eval_stmt_block(
-                global,
-                caches,
-                scope,
-                this_ptr.as_deref_mut(),
-                fn_def.body.statements(),
-                rewind_scope,
-            )
-            .or_else(|err| match *err {
-                // Convert return statement to return value
-                ERR::Return(x, ..) => Ok(x),
-                // Exit value is passed straight-through
-                mut err @ ERR::Exit(..) => {
-                    err.set_position(pos);
-                    Err(err.into())
-                }
-                // System errors are passed straight-through
-                mut err if err.is_system_exception() => {
-                    err.set_position(pos);
-                    Err(err.into())
-                }
-                // Other errors are wrapped in `ErrorInFunctionCall`
-                _ => Err(ERR::ErrorInFunctionCall(
-                    fn_def.name.to_string(),
-                    #[cfg(not(feature = "no_module"))]
-                    _env.and_then(|env| env.lib.id())
-                        .unwrap_or_else(|| global.source().unwrap_or(""))
-                        .to_string(),
-                    #[cfg(feature = "no_module")]
-                    global.source().unwrap_or("").to_string(),
-                    err,
-                    pos,
-                )
-                .into()),
-            });
+        let result = self.eval_stmt_block(
+            global,
+            caches,
+            scope,
+            this_ptr.as_deref_mut(),
+            fn_def.body.statements(),
+            rewind_scope,
+        );
+
+        // Only functions called directly by name can be resumed when suspended
+        let resumable = this_ptr.is_none() && _env.is_none();
+
+        let mut _result = self.script_fn_result(global, _env, fn_def, result, resumable, pos);
 
         #[cfg(feature = "debugging")]
         if self.is_debugger_registered() {
@@ -203,10 +224,133 @@ impl Engine {
 
         // Restore state
         caches.rewind_fn_resolution_caches(orig_fn_resolution_caches_len);
+        #[cfg(not(feature = "unchecked"))]
+        if let Some(orig_memory) = orig_memory {
+            (
+                global.memory_of_callers,
+                global.memory_in_scope,
+                global.memory_sizes,
+            ) = orig_memory;
+        }
 
         _result
     }
 
+    /// Convert the result of evaluating the body of a script-defined function into the result of
+    /// the function call.
+    fn script_fn_result(
+        &self,
+        global: &GlobalRuntimeState,
+        _env: Option<&EncapsulatedEnviron>,
+        fn_def: &ScriptFuncDef,
+        result: RhaiResult,
+        resumable: bool,
+        pos: Position,
+    ) -> RhaiResult {
+        result
+            .or_else(|err| match *err {
+                // Convert return statement to return value
+                ERR::Return(x, ..) => Ok(x),
+                // Exit value is passed straight-through
+                mut err @ ERR::Exit(..) => {
+                    err.set_position(pos);
+                    Err(err.into())
+                }
+                // System errors are passed straight-through
+                mut err if err.is_system_exception() => {
+                    Continuation::record_call(&mut err, fn_def, resumable, pos);
+                    err.set_position(pos);
+                    Err(err.into())
+                }
+                // Other errors are wrapped in `ErrorInFunctionCall`
+                _ => Err(ERR::ErrorInFunctionCall(
+                    fn_def.name.to_string(),
+                    #[cfg(not(feature = "no_module"))]
+                    _env.and_then(|env| env.lib.id())
+                        .unwrap_or_else(|| global.source().unwrap_or(""))
+                        .to_string(),
+                    #[cfg(feature = "no_module")]
+                    global.source().unwrap_or("").to_string(),
+                    err,
+                    pos,
+                )
+                .into()),
+            })
+            // Check the type of the return value
+            .and_then(|r| match fn_def.return_type {
+                Some(ref typ) => self.check_type_annotation(typ, &r, pos).map(|()| r),
+                None => Ok(r),
+            })
+    }
+
+    /// Resume a suspended evaluation of a script-defined function called at `pos`, returning the
+    /// value of the function call.
+    ///
+    /// `frame` is the frame of the function, and `frames` are the frames of the functions it is
+    /// calling (outermost first).
+    pub(crate) fn resume_script_fn(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        frame: &mut ResumeFrame,
+        frames: &mut [ResumeFrame],
+        output: RhaiResult,
+        pos: Position,
+    ) -> RhaiResult {
+        let fn_def = frame.fn_name.as_ref().and_then(|(name, num_params)| {
+            global
+                .lib
+                .iter()
+                .find_map(|m| m.get_script_fn(name, *num_params))
+                .cloned()
+        });
+
+        let Some(fn_def) = fn_def else {
+            return Err(ERR::ErrorSystem(
+                "Cannot resume evaluation".into(),
+                format!("no function to resume at {pos}").into(),
+            )
+            .into());
+        };
+
+        defer! { let orig_level = global.level; global.level += 1 }
+
+        self.track_operation(global, pos)?;
+
+        // Guard against too much memory, counting the variables of the caller as of the last check
+        #[cfg(not(feature = "unchecked"))]
+        let orig_memory = self.limits.memory.map(|_| {
+            let orig_memory = (
+                global.memory_of_callers,
+                global.memory_in_scope,
+                std::mem::take(&mut global.memory_sizes),
+            );
+            global.memory_of_callers += global.memory_in_scope;
+            global.memory_in_scope = 0;
+            orig_memory
+        });
+
+        let scope = &mut std::mem::take(&mut frame.scope);
+        let statements = fn_def.body.statements();
+        let path = &mut frame.path;
+
+        let result = self.resume_stmt_block(
+            global, caches, scope, statements, frame.pos, path, frames, output, true,
+        );
+        let result = self.script_fn_result(global, None, &fn_def, result, true, pos);
+
+        #[cfg(not(feature = "unchecked"))]
+        if let Some(orig_memory) = orig_memory {
+            (
+                global.memory_of_callers,
+                global.memory_in_scope,
+                global.memory_sizes,
+            ) = orig_memory;
+        }
+
+        result
+    }
+
     // Does a script-defined function exist?
     ///
     /// # Note
@@ -236,6 +236,8 @@ pub use api::custom_syntax::Expression;
 #[cfg(not(feature = "no_std"))]
 #[cfg(any(not(target_family = "wasm"), not(target_os = "unknown")))]
 pub use api::files::{eval_file, run_file};
+#[cfg(feature = "metadata")]
+pub use api::type_check::TypeMismatch;
 pub use api::{eval::eval, run::run};
 pub use ast::{FnAccess, AST};
 use defer::Deferred;
@@ -244,16 +246,19 @@ pub use eval::EvalContext;
 #[cfg(not(feature = "no_function"))]
 #[cfg(not(feature = "no_object"))]
 use func::calc_typed_method_hash;
+pub use func::RhaiNativeAsyncFunc;
 use func::{calc_fn_hash, calc_fn_hash_full, calc_var_hash};
 pub use func::{plugin, FuncArgs, NativeCallContext, RhaiNativeFunc};
 pub use module::{FnNamespace, FuncRegistration, Module};
 pub use packages::string_basic::{FUNC_TO_DEBUG, FUNC_TO_STRING};
 pub use rhai_codegen::*;
+#[cfg(not(feature = "unchecked"))]
+pub use types::CancellationHandle;
 #[cfg(not(feature = "no_time"))]
 pub use types::Instant;
 pub use types::{
-    Dynamic, EvalAltResult, FnPtr, ImmutableString, LexError, ParseError, ParseErrorType, Position,
-    Scope, VarDefInfo,
+    Continuation, DateTime, Dynamic, EnumValue, EvalAltResult, FnPtr, ImmutableString, LexError,
+    ParseError, ParseErrorType, Position, Scope, VarDefInfo,
 };
 
 /// _(debugging)_ Module containing types for debugging.
@@ -262,9 +267,19 @@ pub use types::{
 pub mod debugger {
     #[cfg(not(feature = "no_function"))]
     pub use super::eval::CallStackFrame;
+    #[cfg(feature = "metadata")]
+    #[cfg(not(feature = "no_std"))]
+    pub use super::eval::DapServer;
     pub use super::eval::{BreakPoint, Debugger, DebuggerCommand, DebuggerEvent};
 }
 
+/// _(internals)_ Module containing types for static analysis of scripts.
+/// Exported under the `internals` feature only.
+#[cfg(feature = "internals")]
+pub mod lint {
+    pub use super::api::lint::{LintContext, LintRule, LintWarning, Linter};
+}
+
 /// _(internals)_ An identifier in Rhai.
 /// Exported under the `internals` feature only.
 ///
@@ -293,6 +308,9 @@ pub use ast::ScriptFnMetadata;
 #[cfg(not(feature = "no_function"))]
 pub use api::call_fn::CallFnOptions;
 
+#[cfg(not(feature = "no_position"))]
+pub use api::formatter::FormatOptions;
+
 /// Variable-sized array of [`Dynamic`] values.
 ///
 /// Not available under `no_index`.
@@ -314,6 +332,9 @@ pub type Blob = Vec<u8>;
 #[cfg(not(feature = "no_object"))]
 pub type Map = std::collections::BTreeMap<Identifier, Dynamic>;
 
+#[cfg(not(feature = "no_object"))]
+pub use types::Record;
+
 #[cfg(not(feature = "no_object"))]
 pub use api::json::format_map_as_json;
 
@@ -325,7 +346,7 @@ pub use module::ModuleResolver;
 pub use module::resolvers as module_resolvers;
 
 #[cfg(not(feature = "no_optimize"))]
-pub use optimizer::OptimizationLevel;
+pub use optimizer::{OptimizationLevel, OptimizationReport, OptimizerOptions};
 
 // Expose internal data structures.
 
@@ -358,7 +379,7 @@ pub use api::default_limits;
 #[cfg(feature = "internals")]
 pub use ast::{
     ASTFlags, ASTNode, BinaryExpr, EncapsulatedEnviron, Expr, FlowControl, FnCallExpr,
-    FnCallHashes, Ident, OpAssignment, RangeCase, ScriptFuncDef, Stmt, StmtBlock,
+    FnCallHashes, Ident, OpAssignment, Pattern, RangeCase, ScriptFuncDef, Stmt, StmtBlock,
     SwitchCasesCollection,
 };
 
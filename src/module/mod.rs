@@ -1312,9 +1312,20 @@ impl Module {
             num_params,
             param_types: FnArgsVec::new_const(),
             #[cfg(feature = "metadata")]
-            params_info: fn_def.params.iter().map(Into::into).collect(),
+            params_info: fn_def
+                .params
+                .iter()
+                .enumerate()
+                .map(|(i, p)| match fn_def.param_types.get(i) {
+                    Some(Some(t)) => format!("{p}: {t}").into(),
+                    _ => p.into(),
+                })
+                .collect(),
             #[cfg(feature = "metadata")]
-            return_type: <_>::default(),
+            return_type: fn_def
+                .return_type
+                .as_ref()
+                .map_or_else(<_>::default, Into::into),
             #[cfg(feature = "metadata")]
             comments: crate::StaticVec::new_const(),
         };
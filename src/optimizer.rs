@@ -5,18 +5,25 @@ use crate::ast::{
     ASTFlags, Expr, FlowControl, OpAssignment, Stmt, StmtBlock, StmtBlockContainer,
     SwitchCasesCollection,
 };
+#[cfg(not(feature = "no_function"))]
+use crate::ast::{ASTNode, Ident, ScriptFuncDef};
 use crate::engine::{
     KEYWORD_DEBUG, KEYWORD_EVAL, KEYWORD_FN_PTR, KEYWORD_FN_PTR_CURRY, KEYWORD_PRINT,
     KEYWORD_TYPE_OF, OP_NOT,
 };
+#[cfg(not(feature = "no_function"))]
+use crate::engine::{KEYWORD_IS_DEF_FN, KEYWORD_IS_DEF_VAR};
 use crate::eval::{Caches, GlobalRuntimeState};
 use crate::func::builtin::get_builtin_binary_op_fn;
 use crate::func::hashing::get_hasher;
+#[cfg(not(feature = "no_function"))]
+use crate::func::StraightHashMap;
 use crate::tokenizer::Token;
 use crate::{
     calc_fn_hash, calc_fn_hash_full, Dynamic, Engine, FnArgsVec, FnPtr, ImmutableString, Position,
     Scope, AST,
 };
+use bitflags::bitflags;
 #[cfg(feature = "no_std")]
 use std::prelude::v1::*;
 use std::{
@@ -26,6 +33,11 @@ use std::{
     hash::{Hash, Hasher},
     mem,
 };
+#[cfg(not(feature = "no_function"))]
+use std::{
+    collections::BTreeSet,
+    num::{NonZeroU8, NonZeroUsize},
+};
 
 /// Level of optimization performed.
 ///
@@ -41,9 +53,100 @@ pub enum OptimizationLevel {
     Simple,
     /// Full optimizations performed, including evaluating functions.
     /// Take care that this may cause side effects as it essentially assumes that all functions are pure.
+    ///
+    /// Small script-defined functions are also inlined at their call sites, and private
+    /// script-defined functions that are never referenced are removed. Take care that such
+    /// functions can no longer be called via [`Engine::call_fn`][crate::Engine::call_fn].
     Full,
 }
 
+bitflags! {
+    /// Bit-flags containing the passes performed by the optimizer.
+    ///
+    /// Passes are only performed at the [optimization levels][OptimizationLevel] that support
+    /// them. In particular, nothing is performed under [`OptimizationLevel::None`], while
+    /// [`EAGER_EVALUATION`][OptimizerOptions::EAGER_EVALUATION] and
+    /// [`INLINING`][OptimizerOptions::INLINING] require [`OptimizationLevel::Full`].
+    ///
+    /// Not available under `no_optimize`.
+    #[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy)]
+    pub struct OptimizerOptions: u8 {
+        /// Replace constants with their values.
+        const CONSTANT_PROPAGATION = 0b_0000_0001;
+        /// Evaluate operators, indexing, property access and string interpolation on literals.
+        const CONSTANT_FOLDING = 0b_0000_0010;
+        /// Remove statements that have no effect or can never be run.
+        const DEAD_CODE_REMOVAL = 0b_0000_0100;
+        /// Select the matching case of a `switch` on a constant and remove cases that can never match.
+        const SWITCH_PRUNING = 0b_0000_1000;
+        /// Call native functions with constant arguments during optimization.
+        const EAGER_EVALUATION = 0b_0001_0000;
+        /// Inline calls to small script-defined functions.
+        #[cfg(not(feature = "no_function"))]
+        const INLINING = 0b_0010_0000;
+        /// Simplify the structure of statements, e.g. flattening nested blocks.
+        const SIMPLIFICATION = 0b_0100_0000;
+    }
+}
+
+impl OptimizerOptions {
+    /// Create a new [`OptimizerOptions`] with all passes enabled.
+    #[inline(always)]
+    #[must_use]
+    pub const fn new() -> Self {
+        Self::all()
+    }
+    /// Get the passes that can be performed at an [optimization level][OptimizationLevel].
+    #[inline]
+    #[must_use]
+    const fn for_level(self, optimization_level: OptimizationLevel) -> Self {
+        match optimization_level {
+            OptimizationLevel::None => Self::empty(),
+            OptimizationLevel::Simple => self.difference(Self::EAGER_EVALUATION.union({
+                #[cfg(not(feature = "no_function"))]
+                {
+                    Self::INLINING
+                }
+                #[cfg(feature = "no_function")]
+                {
+                    Self::empty()
+                }
+            })),
+            OptimizationLevel::Full => self,
+        }
+    }
+}
+
+impl Default for OptimizerOptions {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Transformations performed during an optimization run.
+///
+/// Not available under `no_optimize`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct OptimizationReport {
+    /// Rewrites performed, in order, each with the (single) pass that performed it and the
+    /// [position][Position] of the code rewritten.
+    pub rewrites: Vec<(OptimizerOptions, Position)>,
+    /// Script-defined functions inlined, together with the [positions][Position] of the calls.
+    pub inlined: Vec<(ImmutableString, Position)>,
+    /// Private script-defined functions removed because they are never referenced.
+    pub removed: Vec<ImmutableString>,
+}
+
+/// Maximum number of nodes in the body of a script-defined function that is inlined.
+#[cfg(not(feature = "no_function"))]
+const MAX_INLINE_FN_SIZE: usize = 16;
+
+/// Script-defined functions that can be inlined, keyed by their script hashes.
+#[cfg(not(feature = "no_function"))]
+type InlineFns = StraightHashMap<crate::Shared<ScriptFuncDef>>;
+
 /// Mutable state throughout an optimization pass.
 #[derive(Debug, Clone)]
 struct OptimizerState<'a> {
@@ -61,8 +164,13 @@ struct OptimizerState<'a> {
     global: GlobalRuntimeState,
     /// Function resolution caches.
     caches: Caches,
-    /// Optimization level.
-    optimization_level: OptimizationLevel,
+    /// Passes to perform.
+    options: OptimizerOptions,
+    /// Script-defined functions that can be inlined.
+    #[cfg(not(feature = "no_function"))]
+    inline_fns: &'a InlineFns,
+    /// Report of the transformations performed.
+    report: OptimizationReport,
 }
 
 impl<'a> OptimizerState<'a> {
@@ -71,6 +179,7 @@ impl<'a> OptimizerState<'a> {
     pub fn new(
         engine: &'a Engine,
         lib: &'a [crate::SharedModule],
+        #[cfg(not(feature = "no_function"))] inline_fns: &'a InlineFns,
         scope: Option<&'a Scope<'a>>,
         optimization_level: OptimizationLevel,
     ) -> Self {
@@ -82,15 +191,20 @@ impl<'a> OptimizerState<'a> {
             _global.lib = _lib.into();
         }
 
+        let options = engine.optimizer_options.for_level(optimization_level);
+
         Self {
             is_dirty: false,
             variables: Vec::new(),
-            propagate_constants: true,
+            propagate_constants: options.contains(OptimizerOptions::CONSTANT_PROPAGATION),
             engine,
             scope,
             global: _global,
             caches: Caches::new(),
-            optimization_level,
+            options,
+            #[cfg(not(feature = "no_function"))]
+            inline_fns,
+            report: OptimizationReport::default(),
         }
     }
     /// Set the [`AST`] state to be dirty (i.e. changed).
@@ -108,6 +222,17 @@ impl<'a> OptimizerState<'a> {
     pub const fn is_dirty(&self) -> bool {
         self.is_dirty
     }
+    /// Is an optimizer pass to be performed?
+    #[inline(always)]
+    pub const fn is_enabled(&self, pass: OptimizerOptions) -> bool {
+        self.options.contains(pass)
+    }
+    /// Record a rewrite performed by an optimizer pass and set the [`AST`] state to be dirty.
+    #[inline]
+    pub fn rewrite(&mut self, pass: OptimizerOptions, pos: Position) {
+        self.set_dirty();
+        self.report.rewrites.push((pass, pos));
+    }
     /// Rewind the variables stack back to a specified size.
     #[inline(always)]
     pub fn rewind_var(&mut self, len: usize) {
@@ -175,11 +300,13 @@ fn optimize_stmt_block(
     };
 
     // Flatten blocks
-    while let Some(n) = statements.iter().position(
-        |s| matches!(s, Stmt::Block(block, ..) if !block.iter().any(Stmt::is_block_dependent)),
-    ) {
+    while let Some(n) = statements.iter().position(|s| {
+        state.is_enabled(OptimizerOptions::SIMPLIFICATION)
+            && matches!(s, Stmt::Block(block, ..) if !block.iter().any(Stmt::is_block_dependent))
+    }) {
         let (first, second) = statements.split_at_mut(n);
         let mut stmt = second[0].take();
+        state.rewrite(OptimizerOptions::SIMPLIFICATION, stmt.position());
         let stmts = match stmt {
             Stmt::Block(ref mut block, ..) => block.statements_mut(),
             _ => unreachable!("Stmt::Block expected but gets {:?}", stmt),
@@ -206,9 +333,11 @@ fn optimize_stmt_block(
 
         statements.retain(|stmt| {
             if dead_code {
-                state.set_dirty();
+                state.rewrite(OptimizerOptions::DEAD_CODE_REMOVAL, stmt.position());
                 false
-            } else if stmt.is_control_flow_break() {
+            } else if stmt.is_control_flow_break()
+                && state.is_enabled(OptimizerOptions::DEAD_CODE_REMOVAL)
+            {
                 dead_code = true;
                 true
             } else {
@@ -231,6 +360,27 @@ fn optimize_stmt_block(
                     };
                     state.push_var(x.0.name.clone(), value);
                 }
+                Stmt::Destructure(x, options, ..) => {
+                    optimize_expr(&mut x.1, state, false);
+
+                    let bindings = if options.intersects(ASTFlags::CONSTANT) && x.1.is_constant() {
+                        // constant literal - bind constants if the pattern matches
+                        x.0.match_value(&x.1.get_literal_value().unwrap())
+                    } else {
+                        None
+                    };
+                    match bindings {
+                        Some(bindings) => bindings.into_iter().for_each(|(name, value)| {
+                            state.push_var(name, Some(Cow::Owned(value)));
+                        }),
+                        // variables
+                        None => {
+                            x.0.variables()
+                                .into_iter()
+                                .for_each(|id| state.push_var(id.name.clone(), None))
+                        }
+                    }
+                }
                 // Optimize the statement
                 _ => optimize_stmt(stmt, state, preserve_result),
             }
@@ -254,17 +404,17 @@ fn optimize_stmt_block(
             })
             .map_or(0, |n| statements.len() - n - 1);
 
-        while index < statements.len() {
+        while state.is_enabled(OptimizerOptions::DEAD_CODE_REMOVAL) && index < statements.len() {
             if preserve_result && index >= statements.len() - 1 {
                 break;
             }
             match statements[index] {
                 ref stmt if is_pure(stmt) && index >= first_non_constant => {
-                    state.set_dirty();
+                    state.rewrite(OptimizerOptions::DEAD_CODE_REMOVAL, stmt.position());
                     statements.remove(index);
                 }
                 ref stmt if stmt.is_pure() => {
-                    state.set_dirty();
+                    state.rewrite(OptimizerOptions::DEAD_CODE_REMOVAL, stmt.position());
                     if index < first_non_constant {
                         first_non_constant -= 1;
                     }
@@ -280,30 +430,39 @@ fn optimize_stmt_block(
             loop {
                 match statements[..] {
                     // { return; } -> {}
-                    [Stmt::Return(None, options, ..)]
-                        if reduce_return && !options.intersects(ASTFlags::BREAK) =>
+                    [Stmt::Return(None, options, pos)]
+                        if reduce_return
+                            && !options.intersects(ASTFlags::BREAK)
+                            && state.is_enabled(OptimizerOptions::SIMPLIFICATION) =>
                     {
-                        state.set_dirty();
+                        state.rewrite(OptimizerOptions::SIMPLIFICATION, pos);
                         statements.clear();
                     }
-                    [ref stmt] if !stmt.returns_value() && is_pure(stmt) => {
-                        state.set_dirty();
+                    [ref stmt]
+                        if !stmt.returns_value()
+                            && is_pure(stmt)
+                            && state.is_enabled(OptimizerOptions::DEAD_CODE_REMOVAL) =>
+                    {
+                        state.rewrite(OptimizerOptions::DEAD_CODE_REMOVAL, stmt.position());
                         statements.clear();
                     }
                     // { ...; return; } -> { ... }
-                    [.., ref last_stmt, Stmt::Return(None, options, ..)]
+                    [.., ref last_stmt, Stmt::Return(None, options, pos)]
                         if reduce_return
                             && !options.intersects(ASTFlags::BREAK)
-                            && !last_stmt.returns_value() =>
+                            && !last_stmt.returns_value()
+                            && state.is_enabled(OptimizerOptions::SIMPLIFICATION) =>
                     {
-                        state.set_dirty();
+                        state.rewrite(OptimizerOptions::SIMPLIFICATION, pos);
                         statements.pop().unwrap();
                     }
                     // { ...; return val; } -> { ...; val }
                     [.., Stmt::Return(ref mut expr, options, pos)]
-                        if reduce_return && !options.intersects(ASTFlags::BREAK) =>
+                        if reduce_return
+                            && !options.intersects(ASTFlags::BREAK)
+                            && state.is_enabled(OptimizerOptions::SIMPLIFICATION) =>
                     {
-                        state.set_dirty();
+                        state.rewrite(OptimizerOptions::SIMPLIFICATION, pos);
                         *statements.last_mut().unwrap() = expr
                             .as_mut()
                             .map_or_else(|| Stmt::Noop(pos), |e| Stmt::Expr(mem::take(e)));
@@ -317,9 +476,11 @@ fn optimize_stmt_block(
                     // { ...; stmt_that_returns; pure_non_value_stmt } -> { ...; stmt_that_returns; noop }
                     // { ...; stmt; pure_non_value_stmt } -> { ...; stmt }
                     [.., ref second_last_stmt, ref last_stmt]
-                        if !last_stmt.returns_value() && is_pure(last_stmt) =>
+                        if !last_stmt.returns_value()
+                            && is_pure(last_stmt)
+                            && state.is_enabled(OptimizerOptions::DEAD_CODE_REMOVAL) =>
                     {
-                        state.set_dirty();
+                        state.rewrite(OptimizerOptions::DEAD_CODE_REMOVAL, last_stmt.position());
                         if second_last_stmt.returns_value() {
                             *statements.last_mut().unwrap() = Stmt::Noop(last_stmt.position());
                         } else {
@@ -332,28 +493,37 @@ fn optimize_stmt_block(
         } else {
             loop {
                 match statements[..] {
-                    [ref stmt] if is_pure(stmt) => {
-                        state.set_dirty();
+                    [ref stmt]
+                        if is_pure(stmt)
+                            && state.is_enabled(OptimizerOptions::DEAD_CODE_REMOVAL) =>
+                    {
+                        state.rewrite(OptimizerOptions::DEAD_CODE_REMOVAL, stmt.position());
                         statements.clear();
                     }
                     // { ...; return; } -> { ... }
-                    [.., Stmt::Return(None, options, ..)]
-                        if reduce_return && !options.intersects(ASTFlags::BREAK) =>
+                    [.., Stmt::Return(None, options, pos)]
+                        if reduce_return
+                            && !options.intersects(ASTFlags::BREAK)
+                            && state.is_enabled(OptimizerOptions::SIMPLIFICATION) =>
                     {
-                        state.set_dirty();
+                        state.rewrite(OptimizerOptions::SIMPLIFICATION, pos);
                         statements.pop().unwrap();
                     }
                     // { ...; return pure_val; } -> { ... }
-                    [.., Stmt::Return(Some(ref expr), options, ..)]
+                    [.., Stmt::Return(Some(ref expr), options, pos)]
                         if reduce_return
                             && !options.intersects(ASTFlags::BREAK)
-                            && expr.is_pure() =>
+                            && expr.is_pure()
+                            && state.is_enabled(OptimizerOptions::DEAD_CODE_REMOVAL) =>
                     {
-                        state.set_dirty();
+                        state.rewrite(OptimizerOptions::DEAD_CODE_REMOVAL, pos);
                         statements.pop().unwrap();
                     }
-                    [.., ref last_stmt] if is_pure(last_stmt) => {
-                        state.set_dirty();
+                    [.., ref last_stmt]
+                        if is_pure(last_stmt)
+                            && state.is_enabled(OptimizerOptions::DEAD_CODE_REMOVAL) =>
+                    {
+                        state.rewrite(OptimizerOptions::DEAD_CODE_REMOVAL, last_stmt.position());
                         statements.pop().unwrap();
                     }
                     _ => break,
@@ -394,7 +564,7 @@ fn is_hashable_constant(expr: &Expr) -> bool {
     match expr {
         _ if !expr.is_constant() => false,
         Expr::DynamicConstant(v, ..) => v.is_hashable(),
-        _ => false,
+        _ => expr.get_literal_value().map_or(false, |v| v.is_hashable()),
     }
 }
 
@@ -420,11 +590,12 @@ fn optimize_stmt(stmt: &mut Stmt, state: &mut OptimizerState, preserve_result: b
                         if Token::lookup_symbol_from_syntax(&x2.name).map_or(false, |t| t.has_op_assignment())
                         && x2.args.len() == 2
                         && x2.args[0].get_variable_name(true) == x.1.lhs.get_variable_name(true)
-                ) =>
+                )
+                && state.is_enabled(OptimizerOptions::SIMPLIFICATION) =>
         {
             match x.1.rhs {
                 Expr::FnCall(ref mut x2, pos) => {
-                    state.set_dirty();
+                    state.rewrite(OptimizerOptions::SIMPLIFICATION, pos);
                     x.0 = OpAssignment::new_op_assignment_from_base(&x2.name, pos);
                     x.1.rhs = x2.args[1].take();
                 }
@@ -441,11 +612,15 @@ fn optimize_stmt(stmt: &mut Stmt, state: &mut OptimizerState, preserve_result: b
         }
 
         // if expr {}
-        Stmt::If(x, ..) if x.body.is_empty() && x.branch.is_empty() => {
+        Stmt::If(x, ..)
+            if x.body.is_empty()
+                && x.branch.is_empty()
+                && state.is_enabled(OptimizerOptions::DEAD_CODE_REMOVAL) =>
+        {
             let condition = &mut x.expr;
-            state.set_dirty();
-
             let pos = condition.start_position();
+            state.rewrite(OptimizerOptions::DEAD_CODE_REMOVAL, pos);
+
             let mut expr = condition.take();
             optimize_expr(&mut expr, state, false);
 
@@ -466,19 +641,24 @@ fn optimize_stmt(stmt: &mut Stmt, state: &mut OptimizerState, preserve_result: b
         }
         // if false { if_block } -> Noop
         Stmt::If(x, ..)
-            if matches!(x.expr, Expr::BoolConstant(false, ..)) && x.branch.is_empty() =>
+            if matches!(x.expr, Expr::BoolConstant(false, ..))
+                && x.branch.is_empty()
+                && state.is_enabled(OptimizerOptions::DEAD_CODE_REMOVAL) =>
         {
             match x.expr {
                 Expr::BoolConstant(false, pos) => {
-                    state.set_dirty();
+                    state.rewrite(OptimizerOptions::DEAD_CODE_REMOVAL, pos);
                     *stmt = Stmt::Noop(pos);
                 }
                 _ => unreachable!("`Expr::BoolConstant`"),
             }
         }
         // if false { if_block } else { else_block } -> else_block
-        Stmt::If(x, ..) if matches!(x.expr, Expr::BoolConstant(false, ..)) => {
-            state.set_dirty();
+        Stmt::If(x, pos)
+            if matches!(x.expr, Expr::BoolConstant(false, ..))
+                && state.is_enabled(OptimizerOptions::DEAD_CODE_REMOVAL) =>
+        {
+            state.rewrite(OptimizerOptions::DEAD_CODE_REMOVAL, *pos);
             let body = x.branch.take_statements();
             *stmt = match optimize_stmt_block(body, state, preserve_result, true, false) {
                 statements if statements.is_empty() => Stmt::Noop(x.branch.position()),
@@ -488,8 +668,11 @@ fn optimize_stmt(stmt: &mut Stmt, state: &mut OptimizerState, preserve_result: b
             }
         }
         // if true { if_block } else { else_block } -> if_block
-        Stmt::If(x, ..) if matches!(x.expr, Expr::BoolConstant(true, ..)) => {
-            state.set_dirty();
+        Stmt::If(x, pos)
+            if matches!(x.expr, Expr::BoolConstant(true, ..))
+                && state.is_enabled(OptimizerOptions::DEAD_CODE_REMOVAL) =>
+        {
+            state.rewrite(OptimizerOptions::DEAD_CODE_REMOVAL, *pos);
             let body = x.body.take_statements();
             *stmt = match optimize_stmt_block(body, state, preserve_result, true, false) {
                 statements if statements.is_empty() => Stmt::Noop(x.body.position()),
@@ -514,7 +697,12 @@ fn optimize_stmt(stmt: &mut Stmt, state: &mut OptimizerState, preserve_result: b
         }
 
         // switch const { ... }
-        Stmt::Switch(x, pos) if is_hashable_constant(&x.0) => {
+        Stmt::Switch(x, pos)
+            if is_hashable_constant(&x.0)
+                && x.1.patterns.is_empty()
+                && state.is_enabled(OptimizerOptions::SWITCH_PRUNING) =>
+        {
+            let pos = *pos;
             let (
                 match_expr,
                 SwitchCasesCollection {
@@ -522,6 +710,7 @@ fn optimize_stmt(stmt: &mut Stmt, state: &mut OptimizerState, preserve_result: b
                     cases,
                     ranges,
                     def_case,
+                    ..
                 },
             ) = &mut **x;
 
@@ -561,7 +750,7 @@ fn optimize_stmt(stmt: &mut Stmt, state: &mut OptimizerState, preserve_result: b
                             );
                         }
 
-                        state.set_dirty();
+                        state.rewrite(OptimizerOptions::SWITCH_PRUNING, pos);
                         return;
                     }
                     _ => {
@@ -573,7 +762,7 @@ fn optimize_stmt(stmt: &mut Stmt, state: &mut OptimizerState, preserve_result: b
                                 let mut statements = Stmt::Expr(b.rhs.take().into());
                                 optimize_stmt(&mut statements, state, true);
                                 *stmt = statements;
-                                state.set_dirty();
+                                state.rewrite(OptimizerOptions::SWITCH_PRUNING, pos);
                                 return;
                             }
                         }
@@ -618,13 +807,13 @@ fn optimize_stmt(stmt: &mut Stmt, state: &mut OptimizerState, preserve_result: b
                             );
                         }
 
-                        state.set_dirty();
+                        state.rewrite(OptimizerOptions::SWITCH_PRUNING, pos);
                         return;
                     }
                 } else {
                     // Multiple ranges - clear the table and just keep the right ranges
                     if !cases.is_empty() {
-                        state.set_dirty();
+                        state.rewrite(OptimizerOptions::SWITCH_PRUNING, pos);
                         cases.clear();
                     }
 
@@ -633,7 +822,7 @@ fn optimize_stmt(stmt: &mut Stmt, state: &mut OptimizerState, preserve_result: b
                     ranges.retain(|r| r.contains(&value));
 
                     if ranges.len() != old_ranges_len {
-                        state.set_dirty();
+                        state.rewrite(OptimizerOptions::SWITCH_PRUNING, pos);
                     }
 
                     for r in ranges {
@@ -646,7 +835,7 @@ fn optimize_stmt(stmt: &mut Stmt, state: &mut OptimizerState, preserve_result: b
             }
 
             // Promote the default case
-            state.set_dirty();
+            state.rewrite(OptimizerOptions::SWITCH_PRUNING, pos);
 
             match def_case {
                 Some(index) => {
@@ -654,7 +843,7 @@ fn optimize_stmt(stmt: &mut Stmt, state: &mut OptimizerState, preserve_result: b
                     optimize_stmt(&mut def_stmt, state, true);
                     *stmt = def_stmt;
                 }
-                _ => *stmt = Stmt::Block(StmtBlock::empty(*pos).into()),
+                _ => *stmt = Stmt::Block(StmtBlock::empty(pos).into()),
             }
         }
         // switch
@@ -665,30 +854,55 @@ fn optimize_stmt(stmt: &mut Stmt, state: &mut OptimizerState, preserve_result: b
                     expressions,
                     cases,
                     ranges,
+                    patterns,
                     def_case,
-                    ..
                 },
             ) = &mut **x;
 
             optimize_expr(match_expr, state, false);
 
             // Optimize blocks
-            for b in &mut *expressions {
+            for (index, b) in expressions.iter_mut().enumerate() {
+                // Variables bound by patterns shadow any constants
+                let orig_vars_len = state.variables.len();
+
+                if let Some((pattern, ..)) = patterns.iter().find(|&&(.., n)| n == index) {
+                    pattern
+                        .variables()
+                        .into_iter()
+                        .for_each(|x| state.push_var(x.name.clone(), None));
+                }
+
                 optimize_expr(&mut b.lhs, state, false);
                 optimize_expr(&mut b.rhs, state, false);
 
-                if matches!(b.lhs, Expr::BoolConstant(false, ..)) && !b.rhs.is_unit() {
-                    b.rhs = Expr::Unit(b.rhs.position());
-                    state.set_dirty();
+                state.rewind_var(orig_vars_len);
+
+                if matches!(b.lhs, Expr::BoolConstant(false, ..))
+                    && !b.rhs.is_unit()
+                    && state.is_enabled(OptimizerOptions::SWITCH_PRUNING)
+                {
+                    let pos = b.rhs.position();
+                    b.rhs = Expr::Unit(pos);
+                    state.rewrite(OptimizerOptions::SWITCH_PRUNING, pos);
                 }
             }
 
+            if let Some(index) = def_case {
+                optimize_expr(&mut expressions[*index].rhs, state, false);
+            }
+
+            if !state.is_enabled(OptimizerOptions::SWITCH_PRUNING) {
+                return;
+            }
+
             // Remove false cases
             cases.retain(|_, list| {
                 // Remove all entries that have false conditions
                 list.retain(|index| {
-                    if matches!(expressions[*index].lhs, Expr::BoolConstant(false, ..)) {
-                        state.set_dirty();
+                    let b = &expressions[*index];
+                    if matches!(b.lhs, Expr::BoolConstant(false, ..)) {
+                        state.rewrite(OptimizerOptions::SWITCH_PRUNING, b.lhs.position());
                         false
                     } else {
                         true
@@ -699,13 +913,14 @@ fn optimize_stmt(stmt: &mut Stmt, state: &mut OptimizerState, preserve_result: b
                     matches!(expressions[index].lhs, Expr::BoolConstant(true, ..))
                 }) {
                     if n + 1 < list.len() {
-                        state.set_dirty();
+                        let pos = expressions[list[n + 1]].lhs.position();
+                        state.rewrite(OptimizerOptions::SWITCH_PRUNING, pos);
                         list.truncate(n + 1);
                     }
                 }
                 // Remove if no entry left
                 if list.is_empty() {
-                    state.set_dirty();
+                    state.rewrite(OptimizerOptions::SWITCH_PRUNING, match_expr.position());
                     false
                 } else {
                     true
@@ -714,39 +929,54 @@ fn optimize_stmt(stmt: &mut Stmt, state: &mut OptimizerState, preserve_result: b
 
             // Remove false ranges
             ranges.retain(|r| {
-                if matches!(expressions[r.index()].lhs, Expr::BoolConstant(false, ..)) {
-                    state.set_dirty();
+                let b = &expressions[r.index()];
+                if matches!(b.lhs, Expr::BoolConstant(false, ..)) {
+                    state.rewrite(OptimizerOptions::SWITCH_PRUNING, b.lhs.position());
                     false
                 } else {
                     true
                 }
             });
 
-            if let Some(index) = def_case {
-                optimize_expr(&mut expressions[*index].rhs, state, false);
-            }
+            // Remove false patterns
+            patterns.retain(|(.., index)| {
+                let b = &expressions[*index];
+                if matches!(b.lhs, Expr::BoolConstant(false, ..)) {
+                    state.rewrite(OptimizerOptions::SWITCH_PRUNING, b.lhs.position());
+                    false
+                } else {
+                    true
+                }
+            });
 
             // Remove unused block statements
             expressions.iter_mut().enumerate().for_each(|(index, b)| {
                 if *def_case != Some(index)
                     && cases.values().flat_map(|c| c.iter()).all(|&n| n != index)
                     && ranges.iter().all(|r| r.index() != index)
+                    && patterns.iter().all(|&(.., n)| n != index)
                     && !b.rhs.is_unit()
                 {
-                    b.rhs = Expr::Unit(b.rhs.position());
-                    state.set_dirty();
+                    let pos = b.rhs.position();
+                    b.rhs = Expr::Unit(pos);
+                    state.rewrite(OptimizerOptions::SWITCH_PRUNING, pos);
                 }
             });
         }
 
         // while false { block } -> Noop
-        Stmt::While(x, ..) if matches!(x.expr, Expr::BoolConstant(false, ..)) => match x.expr {
-            Expr::BoolConstant(false, pos) => {
-                state.set_dirty();
-                *stmt = Stmt::Noop(pos);
+        Stmt::While(x, ..)
+            if matches!(x.expr, Expr::BoolConstant(false, ..))
+                && state.is_enabled(OptimizerOptions::DEAD_CODE_REMOVAL) =>
+        {
+            match x.expr {
+                Expr::BoolConstant(false, pos) => {
+                    state.rewrite(OptimizerOptions::DEAD_CODE_REMOVAL, pos);
+                    *stmt = Stmt::Noop(pos);
+                }
+                _ => unreachable!("`Expr::BoolConstant"),
             }
-            _ => unreachable!("`Expr::BoolConstant"),
-        },
+        }
         // while expr { block }
         Stmt::While(x, ..) => {
             let FlowControl { expr, body, .. } = &mut **x;
@@ -773,6 +1003,8 @@ fn optimize_stmt(stmt: &mut Stmt, state: &mut OptimizerState, preserve_result: b
         Stmt::Var(x, options, ..) if !options.intersects(ASTFlags::CONSTANT) => {
             optimize_expr(&mut x.1, state, false);
         }
+        // let pattern = expr;
+        Stmt::Destructure(x, ..) => optimize_expr(&mut x.1, state, false),
         // import expr as var;
         #[cfg(not(feature = "no_module"))]
         Stmt::Import(x, ..) => optimize_expr(&mut x.0, state, false),
@@ -782,22 +1014,28 @@ fn optimize_stmt(stmt: &mut Stmt, state: &mut OptimizerState, preserve_result: b
                 optimize_stmt_block(block.take_statements(), state, preserve_result, true, false);
 
             match stmts.as_mut_slice() {
-                [] => {
-                    state.set_dirty();
-                    *stmt = Stmt::Noop(block.span().start());
+                [] if state.is_enabled(OptimizerOptions::SIMPLIFICATION) => {
+                    let pos = block.span().start();
+                    state.rewrite(OptimizerOptions::SIMPLIFICATION, pos);
+                    *stmt = Stmt::Noop(pos);
                 }
                 // Only one statement which is not block-dependent - promote
-                [s] if !s.is_block_dependent() => {
-                    state.set_dirty();
+                [s] if !s.is_block_dependent()
+                    && state.is_enabled(OptimizerOptions::SIMPLIFICATION) =>
+                {
+                    state.rewrite(OptimizerOptions::SIMPLIFICATION, block.position());
                     *stmt = s.take();
                 }
                 _ => *block.statements_mut() = stmts,
             }
         }
         // try { pure try_block } catch ( var ) { catch_block } -> try_block
-        Stmt::TryCatch(x, ..) if x.body.iter().all(Stmt::is_pure) => {
+        Stmt::TryCatch(x, pos)
+            if x.body.iter().all(Stmt::is_pure)
+                && state.is_enabled(OptimizerOptions::DEAD_CODE_REMOVAL) =>
+        {
             // If try block is pure, there will never be any exceptions
-            state.set_dirty();
+            state.rewrite(OptimizerOptions::DEAD_CODE_REMOVAL, *pos);
             let statements = x.body.take_statements();
             let block = StmtBlock::new_with_span(
                 optimize_stmt_block(statements, state, false, true, false),
@@ -814,8 +1052,11 @@ fn optimize_stmt(stmt: &mut Stmt, state: &mut OptimizerState, preserve_result: b
         }
 
         // expr(stmt)
-        Stmt::Expr(expr) if matches!(**expr, Expr::Stmt(..)) => {
-            state.set_dirty();
+        Stmt::Expr(expr)
+            if matches!(**expr, Expr::Stmt(..))
+                && state.is_enabled(OptimizerOptions::SIMPLIFICATION) =>
+        {
+            state.rewrite(OptimizerOptions::SIMPLIFICATION, expr.position());
             match expr.as_mut() {
                 Expr::Stmt(block) if !block.is_empty() => {
                     let mut stmts_blk = mem::take(block.as_mut());
@@ -829,8 +1070,11 @@ fn optimize_stmt(stmt: &mut Stmt, state: &mut OptimizerState, preserve_result: b
         }
 
         // expr(func())
-        Stmt::Expr(expr) if matches!(**expr, Expr::FnCall(..)) => {
-            state.set_dirty();
+        Stmt::Expr(expr)
+            if matches!(**expr, Expr::FnCall(..))
+                && state.is_enabled(OptimizerOptions::SIMPLIFICATION) =>
+        {
+            state.rewrite(OptimizerOptions::SIMPLIFICATION, expr.position());
             match expr.take() {
                 Expr::FnCall(x, pos) => *stmt = Stmt::FnCall(x, pos),
                 _ => unreachable!(),
@@ -860,22 +1104,21 @@ fn optimize_stmt(stmt: &mut Stmt, state: &mut OptimizerState, preserve_result: b
 
         // Share nothing
         #[cfg(not(feature = "no_closure"))]
-        Stmt::Share(x) if x.is_empty() => {
-            state.set_dirty();
+        Stmt::Share(x) if x.is_empty() && state.is_enabled(OptimizerOptions::DEAD_CODE_REMOVAL) => {
+            state.rewrite(OptimizerOptions::DEAD_CODE_REMOVAL, Position::NONE);
             *stmt = Stmt::Noop(Position::NONE);
         }
         // Share constants
         #[cfg(not(feature = "no_closure"))]
-        Stmt::Share(x) => {
-            let orig_len = x.len();
-
-            if state.propagate_constants {
-                x.retain(|(v, _)| state.find_literal_constant(v.as_str()).is_none());
-
-                if x.len() != orig_len {
-                    state.set_dirty();
+        Stmt::Share(x) if state.propagate_constants => {
+            x.retain(|(v, _)| {
+                if state.find_literal_constant(v.as_str()).is_some() {
+                    state.rewrite(OptimizerOptions::CONSTANT_PROPAGATION, v.pos);
+                    false
+                } else {
+                    true
                 }
-            }
+            });
         }
 
         // All other statements - skip
@@ -906,6 +1149,435 @@ fn move_constant_arg(arg_expr: &mut Expr) -> bool {
     }
 }
 
+/// Optimize an argument of a function call, converting it into [`Expr::DynamicConstant`] if constant.
+fn optimize_arg(arg_expr: &mut Expr, state: &mut OptimizerState) {
+    optimize_expr(arg_expr, state, false);
+
+    if state.is_enabled(OptimizerOptions::CONSTANT_FOLDING) && move_constant_arg(arg_expr) {
+        state.rewrite(OptimizerOptions::CONSTANT_FOLDING, arg_expr.position());
+    }
+}
+
+/// Visit all the nodes of an [expression][Expr] that can be moved into another scope.
+///
+/// Only literals, variables, function calls, operators, indexing and property access can be
+/// moved. Statement blocks, custom syntax, `this`, qualified names, calls capturing the parent
+/// scope, as well as `eval` and `is_def_var` which depend on the scope, cannot.
+///
+/// Returns `false` if the expression cannot be moved or the callback returns `false`.
+#[cfg(not(feature = "no_function"))]
+fn walk_movable_expr(expr: &Expr, on_node: &mut impl FnMut(&Expr) -> bool) -> bool {
+    if !on_node(expr) {
+        return false;
+    }
+
+    match expr {
+        Expr::DynamicConstant(..)
+        | Expr::BoolConstant(..)
+        | Expr::IntegerConstant(..)
+        | Expr::CharConstant(..)
+        | Expr::StringConstant(..)
+        | Expr::Unit(..)
+        | Expr::Property(..) => true,
+        #[cfg(not(feature = "no_float"))]
+        Expr::FloatConstant(..) => true,
+
+        #[cfg(not(feature = "no_module"))]
+        Expr::Variable(x, ..) => x.2.is_empty(),
+        #[cfg(feature = "no_module")]
+        Expr::Variable(..) => true,
+
+        Expr::InterpolatedString(x, ..) | Expr::Array(x, ..) => {
+            x.iter().all(|e| walk_movable_expr(e, on_node))
+        }
+        Expr::Map(x, ..) => x.0.iter().all(|(.., e)| walk_movable_expr(e, on_node)),
+        Expr::Index(x, ..)
+        | Expr::Dot(x, ..)
+        | Expr::And(x, ..)
+        | Expr::Or(x, ..)
+        | Expr::Coalesce(x, ..) => {
+            walk_movable_expr(&x.lhs, on_node) && walk_movable_expr(&x.rhs, on_node)
+        }
+
+        #[cfg(not(feature = "no_module"))]
+        Expr::FnCall(x, ..) | Expr::MethodCall(x, ..) if x.is_qualified() => false,
+        Expr::FnCall(x, ..) | Expr::MethodCall(x, ..) => {
+            !x.capture_parent_scope
+                && x.name != KEYWORD_EVAL
+                && x.name != KEYWORD_IS_DEF_VAR
+                && x.args.iter().all(|e| walk_movable_expr(e, on_node))
+        }
+
+        _ => false,
+    }
+}
+
+/// Map all the variables within an [expression][Expr] that can be moved into another scope.
+#[cfg(not(feature = "no_function"))]
+fn map_movable_vars(expr: &mut Expr, f: &mut impl FnMut(&mut Expr)) {
+    match expr {
+        Expr::Variable(..) => f(expr),
+        Expr::InterpolatedString(x, ..) | Expr::Array(x, ..) => {
+            x.iter_mut().for_each(|e| map_movable_vars(e, f));
+        }
+        Expr::Map(x, ..) => x.0.iter_mut().for_each(|(.., e)| map_movable_vars(e, f)),
+        Expr::Index(x, ..)
+        | Expr::Dot(x, ..)
+        | Expr::And(x, ..)
+        | Expr::Or(x, ..)
+        | Expr::Coalesce(x, ..) => {
+            map_movable_vars(&mut x.lhs, f);
+            map_movable_vars(&mut x.rhs, f);
+        }
+        Expr::FnCall(x, ..) | Expr::MethodCall(x, ..) => {
+            x.args.iter_mut().for_each(|e| map_movable_vars(e, f));
+        }
+        _ => (),
+    }
+}
+
+/// Is this [`Expr`] a variable with a particular name?
+#[cfg(not(feature = "no_function"))]
+#[inline]
+fn is_var(expr: &Expr, name: &str) -> bool {
+    expr.get_variable_name(true) == Some(name)
+}
+
+/// Set the offset of a variable from the end of the [`Scope`].
+#[cfg(not(feature = "no_function"))]
+#[inline]
+fn set_var_index(expr: &mut Expr, index: Option<NonZeroUsize>) {
+    if let Expr::Variable(x, short_index, ..) = expr {
+        x.0 = index;
+        *short_index = index
+            .and_then(|n| u8::try_from(n.get()).ok())
+            .and_then(NonZeroU8::new);
+    }
+}
+
+/// Can a variable be modified within an [expression][Expr] that can be moved into another scope?
+///
+/// A variable may be modified when it is passed as the first argument to a function, or when a
+/// property or method is called on it.
+#[cfg(not(feature = "no_function"))]
+fn may_modify_var(expr: &Expr, name: &str) -> bool {
+    fn root(expr: &Expr) -> &Expr {
+        match expr {
+            Expr::Index(x, ..) | Expr::Dot(x, ..) => root(&x.lhs),
+            _ => expr,
+        }
+    }
+
+    !walk_movable_expr(expr, &mut |e| match e {
+        Expr::FnCall(x, ..) if x.op_token.is_none() => {
+            !x.args.first().map_or(false, |arg| is_var(arg, name))
+        }
+        Expr::Dot(x, ..) => !is_var(root(&x.lhs), name),
+        _ => true,
+    })
+}
+
+/// Get the body of a script-defined function as a single [expression][Expr], if possible.
+#[cfg(not(feature = "no_function"))]
+fn fn_body_expr(fn_def: &ScriptFuncDef) -> Option<Expr> {
+    match fn_def.body.statements() {
+        [] => Some(Expr::Unit(fn_def.body.position())),
+        [Stmt::Expr(e)] => Some(e.as_ref().clone()),
+        [Stmt::FnCall(x, pos)] => Some(Expr::FnCall(x.clone(), *pos)),
+        [Stmt::Return(Some(e), options, ..)] if !options.intersects(ASTFlags::BREAK) => {
+            Some(e.as_ref().clone())
+        }
+        _ => None,
+    }
+}
+
+/// Find the script-defined functions that can be inlined.
+///
+/// A function can be inlined if its body is a single small expression that refers to no variables
+/// other than its parameters, and it does not call itself, directly or via other such functions.
+///
+/// Functions with type annotations are never inlined because the types are checked on each call.
+#[cfg(not(feature = "no_function"))]
+fn find_inline_fns(functions: &[crate::Shared<ScriptFuncDef>]) -> InlineFns {
+    let mut fns = functions
+        .iter()
+        .filter(|fn_def| {
+            #[cfg(not(feature = "no_object"))]
+            if fn_def.this_type.is_some() {
+                return false;
+            }
+            if !fn_def.param_types.is_empty() || fn_def.return_type.is_some() {
+                return false;
+            }
+
+            let mut size = 0;
+
+            fn_body_expr(fn_def).map_or(false, |body| {
+                walk_movable_expr(&body, &mut |e| {
+                    size += 1;
+                    size <= MAX_INLINE_FN_SIZE
+                        && match e {
+                            Expr::Variable(..) => fn_def.params.iter().any(|p| is_var(e, p)),
+                            _ => true,
+                        }
+                })
+            })
+        })
+        .map(|fn_def| {
+            let hash = calc_fn_hash(None, &fn_def.name, fn_def.params.len());
+            (hash, fn_def.clone())
+        })
+        .collect::<InlineFns>();
+
+    let calls = fns
+        .iter()
+        .map(|(&hash, fn_def)| {
+            let mut calls = Vec::new();
+
+            walk_movable_expr(&fn_body_expr(fn_def).unwrap(), &mut |e| {
+                match e {
+                    Expr::FnCall(x, ..) | Expr::MethodCall(x, ..) if !x.hashes.is_native_only() => {
+                        calls.push(x.hashes.script());
+                    }
+                    _ => (),
+                }
+                true
+            });
+
+            (hash, calls)
+        })
+        .collect::<StraightHashMap<_>>();
+
+    // Remove functions that call themselves
+    fns.retain(|hash, _| {
+        let mut visited = Vec::new();
+        let mut pending = calls[hash].clone();
+
+        while let Some(h) = pending.pop() {
+            if h == *hash {
+                return false;
+            }
+            if !visited.contains(&h) {
+                visited.push(h);
+                pending.extend(calls.get(&h).into_iter().flatten());
+            }
+        }
+
+        true
+    });
+
+    fns
+}
+
+/// Inline a call to a script-defined function, if possible.
+///
+/// Constant arguments are substituted into the function body when the parameter is used only
+/// once or never modified. Other arguments are bound to variables in a new statements block.
+#[cfg(not(feature = "no_function"))]
+fn inline_fn_call(
+    x: &mut crate::ast::FnCallExpr,
+    fn_def: &ScriptFuncDef,
+    pos: Position,
+    state: &OptimizerState,
+) -> Option<Expr> {
+    let mut body = fn_body_expr(fn_def)?;
+    let mut bound = FnArgsVec::<(&ImmutableString, &Expr)>::new();
+    let mut substituted = FnArgsVec::new();
+
+    for (param, arg) in fn_def.params.iter().zip(x.args.iter()) {
+        let mut uses = 0;
+        walk_movable_expr(&body, &mut |e| {
+            if is_var(e, param) {
+                uses += 1;
+            }
+            true
+        });
+
+        if arg.is_constant() && (uses <= 1 || !may_modify_var(&body, param)) {
+            substituted.push((param, arg.get_literal_value().unwrap()));
+        } else if walk_movable_expr(arg, &mut |e| {
+            // Variables must not be hidden by the arguments bound before
+            !bound.iter().any(|(p, ..)| is_var(e, p))
+        }) {
+            bound.push((param, arg));
+        } else {
+            return None;
+        }
+    }
+
+    // New variables must be allowed
+    if !bound.is_empty()
+        && (state.engine.def_var_filter.is_some() || !state.engine.allow_shadowing())
+    {
+        return None;
+    }
+
+    // Parameters are either replaced by constants or refer to variables bound in order
+    map_movable_vars(&mut body, &mut |e| {
+        let name = match e {
+            Expr::Variable(v, ..) => v.1.clone(),
+            _ => unreachable!("Expr::Variable expected but gets {:?}", e),
+        };
+
+        if let Some((.., value)) = substituted.iter().find(|(p, ..)| **p == name) {
+            *e = Expr::from_dynamic(value.clone(), e.position());
+        } else if let Some(n) = bound.iter().position(|(p, ..)| **p == name) {
+            set_var_index(e, NonZeroUsize::new(bound.len() - n));
+        }
+    });
+
+    if bound.is_empty() {
+        return Some(body);
+    }
+
+    // Arguments are evaluated after the variables for earlier arguments are bound
+    let statements = bound
+        .into_iter()
+        .enumerate()
+        .map(|(n, (param, arg))| {
+            let mut arg = arg.clone();
+            map_movable_vars(&mut arg, &mut |e| {
+                if let Expr::Variable(v, ..) = e {
+                    let index = v.0.and_then(|i| NonZeroUsize::new(i.get() + n));
+                    set_var_index(e, index);
+                }
+            });
+            let ident = Ident {
+                name: param.clone(),
+                pos,
+            };
+            Stmt::Var((ident, arg, None, None).into(), ASTFlags::empty(), pos)
+        })
+        .chain(std::iter::once(Stmt::Expr(body.into())))
+        .collect::<StmtBlockContainer>();
+
+    Some(Expr::Stmt(StmtBlock::new(statements, pos, pos).into()))
+}
+
+/// Collect the names of the functions that may be referenced by an [`ASTNode`].
+///
+/// Returns `false` if functions may be referenced by names that are not known (e.g. via `eval`).
+#[cfg(not(feature = "no_function"))]
+fn collect_fn_refs(node: &ASTNode, refs: &mut BTreeSet<ImmutableString>) -> bool {
+    fn collect_value(value: &Dynamic, refs: &mut BTreeSet<ImmutableString>) {
+        if let Some(s) = value.downcast_ref::<ImmutableString>() {
+            refs.insert(s.clone());
+        } else if let Some(fn_ptr) = value.downcast_ref::<FnPtr>() {
+            refs.insert(fn_ptr.fn_name_raw().clone());
+            fn_ptr.curry().iter().for_each(|v| collect_value(v, refs));
+        } else {
+            #[cfg(not(feature = "no_index"))]
+            if let Some(array) = value.downcast_ref::<crate::Array>() {
+                array.iter().for_each(|v| collect_value(v, refs));
+            }
+            #[cfg(not(feature = "no_object"))]
+            if let Some(map) = value.downcast_ref::<crate::Map>() {
+                map.values().for_each(|v| collect_value(v, refs));
+            }
+        }
+    }
+
+    let x = match node {
+        ASTNode::Stmt(Stmt::FnCall(x, ..))
+        | ASTNode::Expr(Expr::FnCall(x, ..) | Expr::MethodCall(x, ..)) => x,
+        ASTNode::Expr(Expr::Variable(x, ..)) => {
+            refs.insert(x.1.clone());
+            return true;
+        }
+        ASTNode::Expr(Expr::StringConstant(s, ..)) => {
+            refs.insert(s.clone());
+            return true;
+        }
+        ASTNode::Expr(Expr::DynamicConstant(v, ..)) => {
+            collect_value(v, refs);
+            return true;
+        }
+        #[cfg(not(feature = "no_custom_syntax"))]
+        ASTNode::Expr(Expr::Custom(..)) => return false,
+        _ => return true,
+    };
+
+    match x.name.as_str() {
+        KEYWORD_EVAL => return false,
+        KEYWORD_FN_PTR | KEYWORD_IS_DEF_FN
+            if matches!(node, ASTNode::Expr(Expr::MethodCall(..)))
+                || !x.args.first().map_or(false, Expr::is_constant) =>
+        {
+            return false
+        }
+        _ => (),
+    }
+
+    refs.insert(x.name.clone());
+
+    // Arguments to method calls are not walked
+    match node {
+        ASTNode::Expr(Expr::MethodCall(..)) => x.args.iter().all(|e| {
+            e.walk(&mut Vec::new(), &mut |path| {
+                collect_fn_refs(path.last().unwrap(), refs)
+            })
+        }),
+        _ => true,
+    }
+}
+
+/// Remove private script-defined functions that are never referenced, returning the names of the
+/// functions removed.
+///
+/// Nothing is removed if functions may be referenced by names that are not known.
+#[cfg(not(feature = "no_function"))]
+fn remove_unused_fns(
+    statements: &[Stmt],
+    functions: &mut Vec<crate::Shared<ScriptFuncDef>>,
+) -> Vec<ImmutableString> {
+    let refs = &mut BTreeSet::new();
+
+    let walk = |statements: &[Stmt], refs: &mut BTreeSet<ImmutableString>| {
+        statements.iter().all(|stmt| {
+            stmt.walk(&mut Vec::new(), &mut |path| {
+                collect_fn_refs(path.last().unwrap(), refs)
+            })
+        })
+    };
+
+    if !walk(statements, refs) {
+        return Vec::new();
+    }
+
+    let mut used = functions
+        .iter()
+        .map(|f| f.access != crate::FnAccess::Private)
+        .collect::<Vec<_>>();
+    let mut walked = vec![false; functions.len()];
+
+    loop {
+        functions
+            .iter()
+            .zip(used.iter_mut())
+            .filter(|(f, ..)| refs.contains(&f.name))
+            .for_each(|(.., u)| *u = true);
+
+        match (0..functions.len()).find(|&n| used[n] && !walked[n]) {
+            Some(n) if walk(functions[n].body.statements(), refs) => walked[n] = true,
+            Some(..) => return Vec::new(),
+            None => break,
+        }
+    }
+
+    let mut used = used.into_iter();
+    let mut removed = Vec::new();
+
+    functions.retain(|f| {
+        let keep = used.next().unwrap();
+        if !keep {
+            removed.push(f.name.clone());
+        }
+        keep
+    });
+
+    removed
+}
+
 /// Optimize an [expression][Expr].
 fn optimize_expr(expr: &mut Expr, state: &mut OptimizerState, _chaining: bool) {
     // These keywords are handled specially
@@ -917,9 +1589,9 @@ fn optimize_expr(expr: &mut Expr, state: &mut OptimizerState, _chaining: bool) {
 
     match expr {
         // {}
-        Expr::Stmt(x) if x.is_empty() => { state.set_dirty(); *expr = Expr::Unit(x.position()) }
-        Expr::Stmt(x) if x.len() == 1 && matches!(x.statements()[0], Stmt::Expr(..)) => {
-            state.set_dirty();
+        Expr::Stmt(x) if x.is_empty() && state.is_enabled(OptimizerOptions::SIMPLIFICATION) => { state.rewrite(OptimizerOptions::SIMPLIFICATION, x.position()); *expr = Expr::Unit(x.position()) }
+        Expr::Stmt(x) if x.len() == 1 && matches!(x.statements()[0], Stmt::Expr(..)) && state.is_enabled(OptimizerOptions::SIMPLIFICATION) => {
+            state.rewrite(OptimizerOptions::SIMPLIFICATION, x.position());
             match x.take_statements().remove(0) {
                 Stmt::Expr(mut e) => {
                     optimize_expr(&mut e, state, false);
@@ -933,40 +1605,42 @@ fn optimize_expr(expr: &mut Expr, state: &mut OptimizerState, _chaining: bool) {
             *x.statements_mut() = optimize_stmt_block(x.take_statements(), state, true, true, false);
 
             // { Stmt(Expr) } - promote
-            if let [ Stmt::Expr(e) ] = x.statements_mut().as_mut() { state.set_dirty(); *expr = e.take(); }
+            if let [ Stmt::Expr(e) ] = x.statements_mut().as_mut() {
+                if state.is_enabled(OptimizerOptions::SIMPLIFICATION) { state.rewrite(OptimizerOptions::SIMPLIFICATION, e.position()); *expr = e.take(); }
+            }
         }
         // ()?.rhs
         #[cfg(not(feature = "no_object"))]
-        Expr::Dot(x, options, ..) if options.intersects(ASTFlags::NEGATED) && matches!(x.lhs, Expr::Unit(..)) => {
-            state.set_dirty();
+        Expr::Dot(x, options, pos) if options.intersects(ASTFlags::NEGATED) && matches!(x.lhs, Expr::Unit(..)) && state.is_enabled(OptimizerOptions::CONSTANT_FOLDING) => {
+            state.rewrite(OptimizerOptions::CONSTANT_FOLDING, *pos);
             *expr = x.lhs.take();
         }
         // lhs.rhs
         #[cfg(not(feature = "no_object"))]
         Expr::Dot(x, ..) if !_chaining => match (&mut x.lhs, &mut x.rhs) {
             // map.string
-            (Expr::Map(m, pos), Expr::Property(p, ..)) if m.0.iter().all(|(.., x)| x.is_pure()) => {
+            (Expr::Map(m, pos), Expr::Property(p, ..)) if m.0.iter().all(|(.., x)| x.is_pure()) && state.is_enabled(OptimizerOptions::CONSTANT_FOLDING) => {
                 // Map literal where everything is pure - promote the indexed item.
                 // All other items can be thrown away.
-                state.set_dirty();
+                state.rewrite(OptimizerOptions::CONSTANT_FOLDING, *pos);
                 *expr = mem::take(&mut m.0).into_iter().find(|(x, ..)| x.name == p.2)
                             .map_or_else(|| Expr::Unit(*pos), |(.., mut expr)| { expr.set_position(*pos); expr });
             }
             // var.rhs or this.rhs
             (Expr::Variable(..) | Expr::ThisPtr(..), rhs) => optimize_expr(rhs, state, true),
             // const.type_of()
-            (lhs, Expr::MethodCall(x, pos)) if lhs.is_constant() && x.name == KEYWORD_TYPE_OF && x.args.is_empty() => {
+            (lhs, Expr::MethodCall(x, pos)) if lhs.is_constant() && x.name == KEYWORD_TYPE_OF && x.args.is_empty() && state.is_enabled(OptimizerOptions::CONSTANT_FOLDING) => {
                 if let Some(value) = lhs.get_literal_value() {
-                    state.set_dirty();
+                    state.rewrite(OptimizerOptions::CONSTANT_FOLDING, *pos);
                     let typ = state.engine.map_type_name(value.type_name()).into();
                     *expr = Expr::from_dynamic(typ, *pos);
                 }
             }
             // const.is_shared()
             #[cfg(not(feature = "no_closure"))]
-            (lhs, Expr::MethodCall(x, pos)) if lhs.is_constant() && x.name == crate::engine::KEYWORD_IS_SHARED && x.args.is_empty() => {
+            (lhs, Expr::MethodCall(x, pos)) if lhs.is_constant() && x.name == crate::engine::KEYWORD_IS_SHARED && x.args.is_empty() && state.is_enabled(OptimizerOptions::CONSTANT_FOLDING) => {
                 if lhs.get_literal_value().is_some() {
-                    state.set_dirty();
+                    state.rewrite(OptimizerOptions::CONSTANT_FOLDING, *pos);
                     *expr = Expr::from_dynamic(Dynamic::FALSE, *pos);
                 }
             }
@@ -979,8 +1653,8 @@ fn optimize_expr(expr: &mut Expr, state: &mut OptimizerState, _chaining: bool) {
 
         // ()?[rhs]
         #[cfg(not(feature = "no_index"))]
-        Expr::Index(x, options, ..) if options.intersects(ASTFlags::NEGATED) && matches!(x.lhs, Expr::Unit(..)) => {
-            state.set_dirty();
+        Expr::Index(x, options, pos) if options.intersects(ASTFlags::NEGATED) && matches!(x.lhs, Expr::Unit(..)) && state.is_enabled(OptimizerOptions::CONSTANT_FOLDING) => {
+            state.rewrite(OptimizerOptions::CONSTANT_FOLDING, *pos);
             *expr = x.lhs.take();
         }
         // lhs[rhs]
@@ -988,58 +1662,58 @@ fn optimize_expr(expr: &mut Expr, state: &mut OptimizerState, _chaining: bool) {
         #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
         Expr::Index(x, ..) if !_chaining => match (&mut x.lhs, &mut x.rhs) {
             // array[int]
-            (Expr::Array(a, pos), Expr::IntegerConstant(i, ..)) if *i >= 0 && *i <= crate::MAX_USIZE_INT && (*i as usize) < a.len() && a.iter().all(Expr::is_pure) => {
+            (Expr::Array(a, pos), Expr::IntegerConstant(i, ..)) if *i >= 0 && *i <= crate::MAX_USIZE_INT && (*i as usize) < a.len() && a.iter().all(Expr::is_pure) && state.is_enabled(OptimizerOptions::CONSTANT_FOLDING) => {
                 // Array literal where everything is pure - promote the indexed item.
                 // All other items can be thrown away.
-                state.set_dirty();
+                state.rewrite(OptimizerOptions::CONSTANT_FOLDING, *pos);
                 let mut result = a[*i as usize].take();
                 result.set_position(*pos);
                 *expr = result;
             }
             // array[-int]
             #[allow(clippy::unnecessary_cast)]
-            (Expr::Array(a, pos), Expr::IntegerConstant(i, ..)) if *i < 0 && i.unsigned_abs() as u64 <= a.len() as u64 && a.iter().all(Expr::is_pure) => {
+            (Expr::Array(a, pos), Expr::IntegerConstant(i, ..)) if *i < 0 && i.unsigned_abs() as u64 <= a.len() as u64 && a.iter().all(Expr::is_pure) && state.is_enabled(OptimizerOptions::CONSTANT_FOLDING) => {
                 // Array literal where everything is pure - promote the indexed item.
                 // All other items can be thrown away.
-                state.set_dirty();
+                state.rewrite(OptimizerOptions::CONSTANT_FOLDING, *pos);
                 let index = a.len() - i.unsigned_abs() as usize;
                 let mut result = a[index].take();
                 result.set_position(*pos);
                 *expr = result;
             }
             // map[string]
-            (Expr::Map(m, pos), Expr::StringConstant(s, ..)) if m.0.iter().all(|(.., x)| x.is_pure()) => {
+            (Expr::Map(m, pos), Expr::StringConstant(s, ..)) if m.0.iter().all(|(.., x)| x.is_pure()) && state.is_enabled(OptimizerOptions::CONSTANT_FOLDING) => {
                 // Map literal where everything is pure - promote the indexed item.
                 // All other items can be thrown away.
-                state.set_dirty();
+                state.rewrite(OptimizerOptions::CONSTANT_FOLDING, *pos);
                 *expr = mem::take(&mut m.0).into_iter().find(|(x, ..)| x.name == s)
                             .map_or_else(|| Expr::Unit(*pos), |(.., mut expr)| { expr.set_position(*pos); expr });
             }
             // int[int]
-            (Expr::IntegerConstant(n, pos), Expr::IntegerConstant(i, ..)) if *i >= 0 && *i <= crate::MAX_USIZE_INT && (*i as usize) < crate::INT_BITS => {
+            (Expr::IntegerConstant(n, pos), Expr::IntegerConstant(i, ..)) if *i >= 0 && *i <= crate::MAX_USIZE_INT && (*i as usize) < crate::INT_BITS && state.is_enabled(OptimizerOptions::CONSTANT_FOLDING) => {
                 // Bit-field literal indexing - get the bit
-                state.set_dirty();
+                state.rewrite(OptimizerOptions::CONSTANT_FOLDING, *pos);
                 *expr = Expr::BoolConstant((*n & (1 << (*i as usize))) != 0, *pos);
             }
             // int[-int]
             #[allow(clippy::unnecessary_cast)]
-            (Expr::IntegerConstant(n, pos), Expr::IntegerConstant(i, ..)) if *i < 0 && i.unsigned_abs() as u64 <= crate::INT_BITS as u64 => {
+            (Expr::IntegerConstant(n, pos), Expr::IntegerConstant(i, ..)) if *i < 0 && i.unsigned_abs() as u64 <= crate::INT_BITS as u64 && state.is_enabled(OptimizerOptions::CONSTANT_FOLDING) => {
                 // Bit-field literal indexing - get the bit
-                state.set_dirty();
+                state.rewrite(OptimizerOptions::CONSTANT_FOLDING, *pos);
                 *expr = Expr::BoolConstant((*n & (1 << (crate::INT_BITS - i.unsigned_abs() as usize))) != 0, *pos);
             }
             // string[int]
             #[allow(clippy::unnecessary_cast)]
-            (Expr::StringConstant(s, pos), Expr::IntegerConstant(i, ..)) if *i >= 0 && *i <= crate::MAX_USIZE_INT && (*i as usize) < s.chars().count() => {
+            (Expr::StringConstant(s, pos), Expr::IntegerConstant(i, ..)) if *i >= 0 && *i <= crate::MAX_USIZE_INT && (*i as usize) < s.chars().count() && state.is_enabled(OptimizerOptions::CONSTANT_FOLDING) => {
                 // String literal indexing - get the character
-                state.set_dirty();
+                state.rewrite(OptimizerOptions::CONSTANT_FOLDING, *pos);
                 *expr = Expr::CharConstant(s.chars().nth(*i as usize).unwrap(), *pos);
             }
             // string[-int]
             #[allow(clippy::unnecessary_cast)]
-            (Expr::StringConstant(s, pos), Expr::IntegerConstant(i, ..)) if *i < 0 && i.unsigned_abs() as u64 <= s.chars().count() as u64 => {
+            (Expr::StringConstant(s, pos), Expr::IntegerConstant(i, ..)) if *i < 0 && i.unsigned_abs() as u64 <= s.chars().count() as u64 && state.is_enabled(OptimizerOptions::CONSTANT_FOLDING) => {
                 // String literal indexing - get the character
-                state.set_dirty();
+                state.rewrite(OptimizerOptions::CONSTANT_FOLDING, *pos);
                 *expr = Expr::CharConstant(s.chars().rev().nth(i.unsigned_abs() as usize - 1).unwrap(), *pos);
             }
             // var[rhs] or this[rhs]
@@ -1051,13 +1725,13 @@ fn optimize_expr(expr: &mut Expr, state: &mut OptimizerState, _chaining: bool) {
         #[cfg(not(feature = "no_index"))]
         Expr::Index(x, ..) => { optimize_expr(&mut x.lhs, state, false); optimize_expr(&mut x.rhs, state, _chaining); }
         // ``
-        Expr::InterpolatedString(x, pos) if x.is_empty() => {
-            state.set_dirty();
+        Expr::InterpolatedString(x, pos) if x.is_empty() && state.is_enabled(OptimizerOptions::CONSTANT_FOLDING) => {
+            state.rewrite(OptimizerOptions::CONSTANT_FOLDING, *pos);
             *expr = Expr::StringConstant(state.engine.const_empty_string(), *pos);
         }
         // `... ${const} ...`
-        Expr::InterpolatedString(..) if expr.is_constant() => {
-            state.set_dirty();
+        Expr::InterpolatedString(..) if expr.is_constant() && state.is_enabled(OptimizerOptions::CONSTANT_FOLDING) => {
+            state.rewrite(OptimizerOptions::CONSTANT_FOLDING, expr.position());
             *expr = Expr::StringConstant(expr.get_literal_value().unwrap().cast::<ImmutableString>(), expr.position());
         }
         // `... ${ ... } ...`
@@ -1067,23 +1741,27 @@ fn optimize_expr(expr: &mut Expr, state: &mut OptimizerState, _chaining: bool) {
             let mut n = 0;
 
             // Merge consecutive strings
-            while n < x.len() - 1 {
+            while n < x.len() - 1 && state.is_enabled(OptimizerOptions::CONSTANT_FOLDING) {
+                let pos = x[n + 1].position();
+
                 match (x[n].take(),x[n+1].take()) {
-                    (Expr::StringConstant(mut s1, pos), Expr::StringConstant(s2, ..)) => { s1 += s2; x[n] = Expr::StringConstant(s1, pos); x.remove(n+1); state.set_dirty(); }
-                    (expr1, Expr::Unit(..)) => { x[n] = expr1; x.remove(n+1); state.set_dirty(); }
-                    (Expr::Unit(..), expr2) => { x[n+1] = expr2; x.remove(n); state.set_dirty(); }
-                    (expr1, Expr::StringConstant(s, ..)) if s.is_empty() => { x[n] = expr1; x.remove(n+1); state.set_dirty(); }
-                    (Expr::StringConstant(s, ..), expr2) if s.is_empty()=> { x[n+1] = expr2; x.remove(n); state.set_dirty(); }
-                    (expr1, expr2) => { x[n] = expr1; x[n+1] = expr2; n += 1; }
+                    (Expr::StringConstant(mut s1, pos1), Expr::StringConstant(s2, ..)) => { s1 += s2; x[n] = Expr::StringConstant(s1, pos1); x.remove(n+1); }
+                    (expr1, Expr::Unit(..)) => { x[n] = expr1; x.remove(n+1); }
+                    (Expr::Unit(..), expr2) => { x[n+1] = expr2; x.remove(n); }
+                    (expr1, Expr::StringConstant(s, ..)) if s.is_empty() => { x[n] = expr1; x.remove(n+1); }
+                    (Expr::StringConstant(s, ..), expr2) if s.is_empty()=> { x[n+1] = expr2; x.remove(n); }
+                    (expr1, expr2) => { x[n] = expr1; x[n+1] = expr2; n += 1; continue; }
                 }
+
+                state.rewrite(OptimizerOptions::CONSTANT_FOLDING, pos);
             }
 
             x.shrink_to_fit();
         }
         // [ constant .. ]
         #[cfg(not(feature = "no_index"))]
-        Expr::Array(..) if expr.is_constant() => {
-            state.set_dirty();
+        Expr::Array(..) if expr.is_constant() && state.is_enabled(OptimizerOptions::CONSTANT_FOLDING) => {
+            state.rewrite(OptimizerOptions::CONSTANT_FOLDING, expr.position());
             *expr = Expr::DynamicConstant(expr.get_literal_value().unwrap().into(), expr.position());
         }
         // [ items .. ]
@@ -1091,53 +1769,54 @@ fn optimize_expr(expr: &mut Expr, state: &mut OptimizerState, _chaining: bool) {
         Expr::Array(x, ..) => x.iter_mut().for_each(|expr| optimize_expr(expr, state, false)),
         // #{ key:constant, .. }
         #[cfg(not(feature = "no_object"))]
-        Expr::Map(..) if expr.is_constant() => {
-            state.set_dirty();
+        Expr::Map(..) if expr.is_constant() && state.is_enabled(OptimizerOptions::CONSTANT_FOLDING) => {
+            state.rewrite(OptimizerOptions::CONSTANT_FOLDING, expr.position());
             *expr = Expr::DynamicConstant(expr.get_literal_value().unwrap().into(), expr.position());
         }
         // #{ key:value, .. }
         #[cfg(not(feature = "no_object"))]
         Expr::Map(x, ..) => x.0.iter_mut().for_each(|(.., expr)| optimize_expr(expr, state, false)),
         // lhs && rhs
-        Expr::And(x, ..) => match (&mut x.lhs, &mut x.rhs) {
+        Expr::And(x, pos) => match (&mut x.lhs, &mut x.rhs) {
             // true && rhs -> rhs
-            (Expr::BoolConstant(true, ..), rhs) => { state.set_dirty(); optimize_expr(rhs, state, false); *expr = rhs.take(); }
+            (Expr::BoolConstant(true, ..), rhs) if state.is_enabled(OptimizerOptions::CONSTANT_FOLDING) => { state.rewrite(OptimizerOptions::CONSTANT_FOLDING, *pos); optimize_expr(rhs, state, false); *expr = rhs.take(); }
             // false && rhs -> false
-            (Expr::BoolConstant(false, pos), ..) => { state.set_dirty(); *expr = Expr::BoolConstant(false, *pos); }
+            (Expr::BoolConstant(false, pos), ..) if state.is_enabled(OptimizerOptions::CONSTANT_FOLDING) => { state.rewrite(OptimizerOptions::CONSTANT_FOLDING, *pos); *expr = Expr::BoolConstant(false, *pos); }
             // lhs && true -> lhs
-            (lhs, Expr::BoolConstant(true, ..)) => { state.set_dirty(); optimize_expr(lhs, state, false); *expr = lhs.take(); }
+            (lhs, Expr::BoolConstant(true, ..)) if state.is_enabled(OptimizerOptions::CONSTANT_FOLDING) => { state.rewrite(OptimizerOptions::CONSTANT_FOLDING, *pos); optimize_expr(lhs, state, false); *expr = lhs.take(); }
             // lhs && rhs
             (lhs, rhs) => { optimize_expr(lhs, state, false); optimize_expr(rhs, state, false); }
         },
         // lhs || rhs
-        Expr::Or(ref mut x, ..) => match (&mut x.lhs, &mut x.rhs) {
+        Expr::Or(ref mut x, pos) => match (&mut x.lhs, &mut x.rhs) {
             // false || rhs -> rhs
-            (Expr::BoolConstant(false, ..), rhs) => { state.set_dirty(); optimize_expr(rhs, state, false); *expr = rhs.take(); }
+            (Expr::BoolConstant(false, ..), rhs) if state.is_enabled(OptimizerOptions::CONSTANT_FOLDING) => { state.rewrite(OptimizerOptions::CONSTANT_FOLDING, *pos); optimize_expr(rhs, state, false); *expr = rhs.take(); }
             // true || rhs -> true
-            (Expr::BoolConstant(true, pos), ..) => { state.set_dirty(); *expr = Expr::BoolConstant(true, *pos); }
+            (Expr::BoolConstant(true, pos), ..) if state.is_enabled(OptimizerOptions::CONSTANT_FOLDING) => { state.rewrite(OptimizerOptions::CONSTANT_FOLDING, *pos); *expr = Expr::BoolConstant(true, *pos); }
             // lhs || false
-            (lhs, Expr::BoolConstant(false, ..)) => { state.set_dirty(); optimize_expr(lhs, state, false); *expr = lhs.take(); }
+            (lhs, Expr::BoolConstant(false, ..)) if state.is_enabled(OptimizerOptions::CONSTANT_FOLDING) => { state.rewrite(OptimizerOptions::CONSTANT_FOLDING, *pos); optimize_expr(lhs, state, false); *expr = lhs.take(); }
             // lhs || rhs
             (lhs, rhs) => { optimize_expr(lhs, state, false); optimize_expr(rhs, state, false); }
         },
         // () ?? rhs -> rhs
-        Expr::Coalesce(x, ..) if matches!(x.lhs, Expr::Unit(..)) => {
-            state.set_dirty();
+        Expr::Coalesce(x, pos) if matches!(x.lhs, Expr::Unit(..)) && state.is_enabled(OptimizerOptions::CONSTANT_FOLDING) => {
+            state.rewrite(OptimizerOptions::CONSTANT_FOLDING, *pos);
             *expr = x.rhs.take();
         },
         // lhs:constant ?? rhs -> lhs
-        Expr::Coalesce(x, ..) if x.lhs.is_constant() => {
-            state.set_dirty();
+        Expr::Coalesce(x, pos) if x.lhs.is_constant() && state.is_enabled(OptimizerOptions::CONSTANT_FOLDING) => {
+            state.rewrite(OptimizerOptions::CONSTANT_FOLDING, *pos);
             *expr = x.lhs.take();
         },
 
         // !true or !false
-        Expr::FnCall(x,..)
+        Expr::FnCall(x, pos)
             if x.name == OP_NOT
             && x.args.len() == 1
             && matches!(x.args[0], Expr::BoolConstant(..))
+            && state.is_enabled(OptimizerOptions::CONSTANT_FOLDING)
         => {
-            state.set_dirty();
+            state.rewrite(OptimizerOptions::CONSTANT_FOLDING, *pos);
             match x.args[0] {
                 Expr::BoolConstant(b, pos) => *expr = Expr::BoolConstant(!b, pos),
                 _ => unreachable!(),
@@ -1146,26 +1825,20 @@ fn optimize_expr(expr: &mut Expr, state: &mut OptimizerState, _chaining: bool) {
 
         // nnn::id(args ..) -> optimize function call arguments
         #[cfg(not(feature = "no_module"))]
-        Expr::FnCall(x, ..) if x.is_qualified() => x.args.iter_mut().for_each(|arg_expr| {
-            optimize_expr(arg_expr, state, false);
-
-            if move_constant_arg(arg_expr) {
-                state.set_dirty();
-            }
-        }),
+        Expr::FnCall(x, ..) if x.is_qualified() => x.args.iter_mut().for_each(|arg_expr| optimize_arg(arg_expr, state)),
         // eval!
         Expr::FnCall(x, ..) if x.name == KEYWORD_EVAL => {
             state.propagate_constants = false;
         }
         // Fn
-        Expr::FnCall(x, pos) if x.args.len() == 1 && x.name == KEYWORD_FN_PTR && x.constant_args() => {
+        Expr::FnCall(x, pos) if x.args.len() == 1 && x.name == KEYWORD_FN_PTR && x.constant_args() && state.is_enabled(OptimizerOptions::CONSTANT_FOLDING) => {
             let fn_name = match x.args[0] {
                 Expr::StringConstant(ref s, ..) => s.clone().into(),
                 _ => Dynamic::UNIT
             };
 
             if let Ok(fn_ptr) = fn_name.into_immutable_string().map_err(Into::into).and_then(FnPtr::try_from) {
-                state.set_dirty();
+                state.rewrite(OptimizerOptions::CONSTANT_FOLDING, *pos);
                 *expr = Expr::DynamicConstant(Box::new(fn_ptr.into()), *pos);
             } else {
                 optimize_expr(&mut x.args[0], state, false);
@@ -1176,10 +1849,11 @@ fn optimize_expr(expr: &mut Expr, state: &mut OptimizerState, _chaining: bool) {
                                 && x.name == KEYWORD_FN_PTR_CURRY
                                 && matches!(x.args[0], Expr::DynamicConstant(ref v, ..) if v.is_fnptr())
                                 && x.constant_args()
+                                && state.is_enabled(OptimizerOptions::CONSTANT_FOLDING)
         => {
             let mut fn_ptr = x.args[0].get_literal_value().unwrap().cast::<FnPtr>();
             fn_ptr.extend(x.args.iter().skip(1).map(|arg_expr| arg_expr.get_literal_value().unwrap()));
-            state.set_dirty();
+            state.rewrite(OptimizerOptions::CONSTANT_FOLDING, *pos);
             *expr = Expr::DynamicConstant(Box::new(fn_ptr.into()), *pos);
         }
 
@@ -1188,8 +1862,27 @@ fn optimize_expr(expr: &mut Expr, state: &mut OptimizerState, _chaining: bool) {
             x.args.iter_mut().for_each(|arg_expr| optimize_expr(arg_expr, state, false));
         }
 
+        // Inline script-defined functions
+        #[cfg(not(feature = "no_function"))]
+        Expr::FnCall(x, pos) if !x.hashes.is_native_only()
+                                && x.op_token.is_none()
+                                && !x.capture_parent_scope
+                                && state.inline_fns.contains_key(&x.hashes.script())
+        => {
+            x.args.iter_mut().for_each(|arg_expr| optimize_arg(arg_expr, state));
+
+            let fn_def = state.inline_fns[&x.hashes.script()].clone();
+
+            if let Some(inlined) = inline_fn_call(x, &fn_def, *pos, state) {
+                state.rewrite(OptimizerOptions::INLINING, *pos);
+                state.report.inlined.push((x.name.clone(), *pos));
+                *expr = inlined;
+            }
+        }
+
         // Call built-in operators
-        Expr::FnCall(x, pos) if state.optimization_level == OptimizationLevel::Simple // simple optimizations
+        Expr::FnCall(x, pos) if state.is_enabled(OptimizerOptions::CONSTANT_FOLDING)
+                                && !state.is_enabled(OptimizerOptions::EAGER_EVALUATION) // not eagerly calling functions
                                 && x.constant_args() // all arguments are constants
         => {
             let arg_values = &mut x.args.iter().map(|arg_expr| arg_expr.get_literal_value().unwrap()).collect::<FnArgsVec<_>>();
@@ -1197,14 +1890,14 @@ fn optimize_expr(expr: &mut Expr, state: &mut OptimizerState, _chaining: bool) {
 
             match x.name.as_str() {
                 KEYWORD_TYPE_OF if arg_values.len() == 1 => {
-                    state.set_dirty();
+                    state.rewrite(OptimizerOptions::CONSTANT_FOLDING, *pos);
                     let typ = state.engine.map_type_name(arg_values[0].type_name()).into();
                     *expr = Expr::from_dynamic(typ, *pos);
                     return;
                 }
                 #[cfg(not(feature = "no_closure"))]
                 crate::engine::KEYWORD_IS_SHARED if arg_values.len() == 1 => {
-                    state.set_dirty();
+                    state.rewrite(OptimizerOptions::CONSTANT_FOLDING, *pos);
                     *expr = Expr::from_dynamic(Dynamic::FALSE, *pos);
                     return;
                 }
@@ -1215,7 +1908,7 @@ fn optimize_expr(expr: &mut Expr, state: &mut OptimizerState, _chaining: bool) {
                         let (first, second) = arg_values.split_first_mut().unwrap();
 
                         if let Ok(result) = f(context, &mut [ first, &mut second[0] ]) {
-                            state.set_dirty();
+                            state.rewrite(OptimizerOptions::CONSTANT_FOLDING, *pos);
                             *expr = Expr::from_dynamic(result, *pos);
                             return;
                         }
@@ -1224,16 +1917,11 @@ fn optimize_expr(expr: &mut Expr, state: &mut OptimizerState, _chaining: bool) {
                 _ => ()
             }
 
-            x.args.iter_mut().for_each(|arg_expr| {
-                optimize_expr(arg_expr, state, false);
-                if move_constant_arg(arg_expr) {
-                    state.set_dirty();
-                }
-            });
+            x.args.iter_mut().for_each(|arg_expr| optimize_arg(arg_expr, state));
         }
 
         // Eagerly call functions
-        Expr::FnCall(x, pos) if state.optimization_level == OptimizationLevel::Full // full optimizations
+        Expr::FnCall(x, pos) if state.is_enabled(OptimizerOptions::EAGER_EVALUATION) // full optimizations
                                 && x.constant_args() // all arguments are constants
         => {
             // First search for script-defined functions (can override built-in)
@@ -1252,7 +1940,7 @@ fn optimize_expr(expr: &mut Expr, state: &mut OptimizerState, _chaining: bool) {
                 };
 
                 if let Some(r) = result {
-                    state.set_dirty();
+                    state.rewrite(OptimizerOptions::EAGER_EVALUATION, *pos);
                     *expr = Expr::from_dynamic(r, *pos);
                     return;
                 }
@@ -1262,20 +1950,15 @@ fn optimize_expr(expr: &mut Expr, state: &mut OptimizerState, _chaining: bool) {
         }
 
         // id(args ..) or xxx.id(args ..) -> optimize function call arguments
-        Expr::FnCall(x, ..) | Expr::MethodCall(x, ..) => x.args.iter_mut().for_each(|arg_expr| {
-            optimize_expr(arg_expr, state, false);
-            if move_constant_arg(arg_expr) {
-                state.set_dirty();
-            }
-        }),
+        Expr::FnCall(x, ..) | Expr::MethodCall(x, ..) => x.args.iter_mut().for_each(|arg_expr| optimize_arg(arg_expr, state)),
 
         // constant-name
         #[cfg(not(feature = "no_module"))]
         Expr::Variable(x, ..) if !x.2.is_empty() => (),
         Expr::Variable(x, .., pos) if state.propagate_constants && state.find_literal_constant(&x.1).is_some() => {
             // Replace constant with value
+            state.rewrite(OptimizerOptions::CONSTANT_PROPAGATION, *pos);
             *expr = Expr::from_dynamic(state.find_literal_constant(&x.1).unwrap().clone(), *pos);
-            state.set_dirty();
         }
 
         // Custom syntax
@@ -1329,7 +2012,9 @@ impl Engine {
         statements: StmtBlockContainer,
         scope: Option<&Scope>,
         lib: &[crate::SharedModule],
+        #[cfg(not(feature = "no_function"))] inline_fns: &InlineFns,
         optimization_level: OptimizationLevel,
+        report: &mut OptimizationReport,
     ) -> StmtBlockContainer {
         let mut statements = statements;
 
@@ -1340,7 +2025,14 @@ impl Engine {
         }
 
         // Set up the state
-        let mut state = OptimizerState::new(self, lib, scope, optimization_level);
+        let mut state = OptimizerState::new(
+            self,
+            lib,
+            #[cfg(not(feature = "no_function"))]
+            inline_fns,
+            scope,
+            optimization_level,
+        );
 
         // Add constants from global modules
         self.global_modules
@@ -1365,23 +2057,61 @@ impl Engine {
                 );
             });
 
-        optimize_stmt_block(statements, &mut state, true, false, true)
+        let statements = optimize_stmt_block(statements, &mut state, true, false, true);
+
+        report.rewrites.append(&mut state.report.rewrites);
+        report.inlined.append(&mut state.report.inlined);
+
+        statements
     }
 
     /// Optimize a collection of statements and functions into an [`AST`].
+    #[inline(always)]
     pub(crate) fn optimize_into_ast(
         &self,
         scope: Option<&Scope>,
         statements: StmtBlockContainer,
-        #[cfg(not(feature = "no_function"))] functions: impl IntoIterator<Item = crate::Shared<crate::ast::ScriptFuncDef>>
-            + AsRef<[crate::Shared<crate::ast::ScriptFuncDef>]>,
+        #[cfg(not(feature = "no_function"))] functions: impl IntoIterator<Item = crate::Shared<ScriptFuncDef>>
+            + AsRef<[crate::Shared<ScriptFuncDef>]>,
         optimization_level: OptimizationLevel,
+    ) -> AST {
+        self.optimize_into_ast_with_report(
+            scope,
+            statements,
+            #[cfg(not(feature = "no_function"))]
+            functions,
+            optimization_level,
+            &mut OptimizationReport::default(),
+        )
+    }
+
+    /// Optimize a collection of statements and functions into an [`AST`], recording the
+    /// transformations performed into an [`OptimizationReport`].
+    pub(crate) fn optimize_into_ast_with_report(
+        &self,
+        scope: Option<&Scope>,
+        statements: StmtBlockContainer,
+        #[cfg(not(feature = "no_function"))] functions: impl IntoIterator<Item = crate::Shared<ScriptFuncDef>>
+            + AsRef<[crate::Shared<ScriptFuncDef>]>,
+        optimization_level: OptimizationLevel,
+        report: &mut OptimizationReport,
     ) -> AST {
         let mut statements = statements;
 
         #[cfg(not(feature = "no_function"))]
-        let lib: crate::Shared<_> = if optimization_level == OptimizationLevel::None {
-            crate::Module::from(functions).into()
+        let options = self.optimizer_options.for_level(optimization_level);
+
+        // Only inline functions under full optimizations
+        #[cfg(not(feature = "no_function"))]
+        let inline_fns = &if options.contains(OptimizerOptions::INLINING) {
+            find_inline_fns(functions.as_ref())
+        } else {
+            InlineFns::default()
+        };
+
+        #[cfg(not(feature = "no_function"))]
+        let mut functions = if optimization_level == OptimizationLevel::None {
+            functions.into_iter().collect::<Vec<_>>()
         } else {
             // We only need the script library's signatures for optimization purposes
             let lib2 = crate::Module::from(
@@ -1393,30 +2123,64 @@ impl Engine {
 
             let lib2 = &[lib2.into()];
 
-            crate::Module::from(functions.into_iter().map(|fn_def| {
-                // Optimize the function body
-                let mut fn_def = crate::func::shared_take_or_clone(fn_def);
-                let statements = fn_def.body.take_statements();
-                *fn_def.body.statements_mut() =
-                    self.optimize_top_level(statements, scope, lib2, optimization_level);
-                fn_def.into()
-            }))
-            .into()
+            functions
+                .into_iter()
+                .map(|fn_def| {
+                    // Optimize the function body
+                    let mut fn_def = crate::func::shared_take_or_clone(fn_def);
+                    let statements = fn_def.body.take_statements();
+                    *fn_def.body.statements_mut() = self.optimize_top_level(
+                        statements,
+                        scope,
+                        lib2,
+                        inline_fns,
+                        optimization_level,
+                        report,
+                    );
+                    fn_def.into()
+                })
+                .collect()
         };
-        #[cfg(feature = "no_function")]
-        let lib: crate::Shared<_> = crate::Module::new().into();
 
         statements.shrink_to_fit();
 
-        AST::new(
-            match optimization_level {
-                OptimizationLevel::None => statements,
-                OptimizationLevel::Simple | OptimizationLevel::Full => {
-                    self.optimize_top_level(statements, scope, &[lib.clone()], optimization_level)
+        #[cfg(not(feature = "no_function"))]
+        let statements = match optimization_level {
+            OptimizationLevel::None => statements,
+            OptimizationLevel::Simple | OptimizationLevel::Full => {
+                let lib = crate::Module::from(functions.iter().cloned());
+
+                let statements = self.optimize_top_level(
+                    statements,
+                    scope,
+                    &[lib.into()],
+                    inline_fns,
+                    optimization_level,
+                    report,
+                );
+
+                // Remove private functions that are no longer called after inlining
+                if optimization_level == OptimizationLevel::Full
+                    && options.contains(OptimizerOptions::DEAD_CODE_REMOVAL)
+                {
+                    report.removed = remove_unused_fns(&statements, &mut functions);
                 }
-            },
+
+                statements
+            }
+        };
+        #[cfg(feature = "no_function")]
+        let statements = match optimization_level {
+            OptimizationLevel::None => statements,
+            OptimizationLevel::Simple | OptimizationLevel::Full => {
+                self.optimize_top_level(statements, scope, &[], optimization_level, report)
+            }
+        };
+
+        AST::new(
+            statements,
             #[cfg(not(feature = "no_function"))]
-            lib,
+            crate::Module::from(functions),
         )
     }
 }
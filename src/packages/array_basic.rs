@@ -3,15 +3,25 @@
 use crate::api::deprecated::deprecated_array_functions;
 use crate::engine::OP_EQUALS;
 use crate::eval::{calc_index, calc_offset_len};
+use crate::func::{hashing::get_hasher, StraightHashMap};
+use crate::packages::string_basic::{print_with_func, FUNC_TO_STRING};
 use crate::plugin::*;
 use crate::types::fn_ptr::FnPtrType;
 use crate::{
     def_package, Array, Dynamic, ExclusiveRange, FnPtr, InclusiveRange, NativeCallContext,
-    Position, RhaiResultOf, ERR, INT, MAX_USIZE_INT,
+    Position, RhaiResult, RhaiResultOf, ERR, INT, MAX_USIZE_INT,
 };
 #[cfg(feature = "no_std")]
 use std::prelude::v1::*;
-use std::{any::TypeId, cmp::Ordering, mem};
+use std::{
+    any::TypeId,
+    cmp::Ordering,
+    hash::{Hash, Hasher},
+    mem,
+};
+
+#[cfg(not(feature = "no_object"))]
+use crate::Map;
 
 def_package! {
     /// Package of basic array utilities.
@@ -1965,6 +1975,467 @@ pub mod array_functions {
 
         drained
     }
+    /// Split the array into chunks of `size` elements and return them as an array of arrays.
+    ///
+    /// The last chunk may contain fewer than `size` elements.
+    ///
+    /// An error is raised if `size` ≤ 0.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let x = [1, 2, 3, 4, 5];
+    ///
+    /// print(x.chunks(2));     // prints "[[1, 2], [3, 4], [5]]"
+    /// ```
+    #[rhai_fn(return_raw, pure)]
+    pub fn chunks(array: &mut Array, size: INT) -> RhaiResultOf<Array> {
+        let size = check_group_size("chunk", size)?;
+
+        Ok(array
+            .chunks(size)
+            .map(|chunk| chunk.to_vec().into())
+            .collect())
+    }
+    /// Return all contiguous windows of `size` elements in the array as an array of arrays.
+    ///
+    /// If `size` > length of array, an empty array is returned.
+    ///
+    /// An error is raised if `size` ≤ 0.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let x = [1, 2, 3, 4, 5];
+    ///
+    /// print(x.windows(3));    // prints "[[1, 2, 3], [2, 3, 4], [3, 4, 5]]"
+    /// ```
+    #[rhai_fn(return_raw, pure)]
+    pub fn windows(ctx: NativeCallContext, array: &mut Array, size: INT) -> RhaiResultOf<Array> {
+        let size = check_group_size("window", size)?;
+
+        if size > array.len() {
+            return Ok(Array::new());
+        }
+
+        let _ctx = ctx;
+
+        // Check if array will be over max size limit (each element is copied into at most `size` windows)
+        #[cfg(not(feature = "unchecked"))]
+        if _ctx.engine().max_array_size() > 0 {
+            let (a, m, s) = crate::eval::calc_array_sizes(array);
+            let windows = array.len() - size + 1;
+
+            _ctx.engine().throw_on_size((
+                a.saturating_mul(size).saturating_add(windows),
+                m.saturating_mul(size),
+                s.saturating_mul(size),
+            ))?;
+        }
+
+        Ok(array
+            .windows(size)
+            .map(|window| window.to_vec().into())
+            .collect())
+    }
+    /// Flatten an array of arrays by one level, returning a new array.
+    ///
+    /// Elements that are not arrays are kept as they are.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let x = [[1, 2], 3, [[4], 5]];
+    ///
+    /// print(x.flatten());     // prints "[1, 2, 3, [4], 5]"
+    /// ```
+    #[rhai_fn(pure)]
+    pub fn flatten(array: &mut Array) -> Array {
+        let mut result = Array::with_capacity(array.len());
+
+        for item in array.iter() {
+            match item.read_lock::<Array>() {
+                Some(items) => result.extend(items.iter().cloned()),
+                None => result.push(item.clone()),
+            }
+        }
+
+        result
+    }
+    /// Iterate through all the elements in the array, applying a `mapper` function to each element
+    /// in turn, and return the results, flattened by one level, as a new array.
+    ///
+    /// If the `mapper` function returns an array, its elements are added to the result;
+    /// otherwise the returned value is added.
+    ///
+    /// # No Function Parameter
+    ///
+    /// Array element (mutable) is bound to `this`.
+    ///
+    /// This method is marked _pure_; the `mapper` function should not mutate array elements.
+    ///
+    /// # Function Parameters
+    ///
+    /// * `element`: copy of array element
+    /// * `index` _(optional)_: current index in the array
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let x = [1, 2, 3];
+    ///
+    /// let y = x.flat_map(|v| [v, v * 10]);
+    ///
+    /// print(y);       // prints "[1, 10, 2, 20, 3, 30]"
+    /// ```
+    #[rhai_fn(return_raw, pure)]
+    pub fn flat_map(
+        ctx: NativeCallContext,
+        array: &mut Array,
+        mapper: FnPtr,
+    ) -> RhaiResultOf<Array> {
+        if array.is_empty() {
+            return Ok(Array::new());
+        }
+
+        let mut ar = Array::with_capacity(array.len());
+
+        for (i, item) in array.iter_mut().enumerate() {
+            let ex = [(i as INT).into()];
+            let value =
+                mapper.call_raw_with_extra_args("flat_map", &ctx, Some(item), [], ex, Some(0))?;
+
+            if value.is_array() {
+                ar.extend(value.into_array().unwrap());
+            } else {
+                ar.push(value);
+            }
+
+            // Check if array will be over max size limit
+            #[cfg(not(feature = "unchecked"))]
+            if ctx.engine().max_array_size() > 0 && ar.len() > ctx.engine().max_array_size() {
+                return Err(
+                    ERR::ErrorDataTooLarge("Size of array".to_string(), Position::NONE).into(),
+                );
+            }
+        }
+
+        Ok(ar)
+    }
+    /// Group the elements in the array by the keys returned by the `key` function, and return an
+    /// object map from each key to an array of the elements (in order) with that key.
+    ///
+    /// Keys that are not strings are converted into strings.
+    ///
+    /// # No Function Parameter
+    ///
+    /// Array element (mutable) is bound to `this`.
+    ///
+    /// This method is marked _pure_; the `key` function should not mutate array elements.
+    ///
+    /// # Function Parameters
+    ///
+    /// * `element`: copy of array element
+    /// * `index` _(optional)_: current index in the array
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let x = [1, 2, 3, 4, 5];
+    ///
+    /// let y = x.group_by(|v| if v % 2 == 0 { "even" } else { "odd" });
+    ///
+    /// print(y);       // prints "#{"even": [2, 4], "odd": [1, 3, 5]}"
+    /// ```
+    #[cfg(not(feature = "no_object"))]
+    #[rhai_fn(return_raw, pure)]
+    pub fn group_by(ctx: NativeCallContext, array: &mut Array, key: FnPtr) -> RhaiResultOf<Map> {
+        let mut map = Map::new();
+
+        for (i, item) in array.iter_mut().enumerate() {
+            let ex = [(i as INT).into()];
+            let mut k =
+                key.call_raw_with_extra_args("group_by", &ctx, Some(item), [], ex, Some(0))?;
+            let k = print_with_func(FUNC_TO_STRING, &ctx, &mut k);
+
+            map.entry(k.as_str().into())
+                .or_insert_with(|| Array::new().into())
+                .write_lock::<Array>()
+                .unwrap()
+                .push(item.clone());
+        }
+
+        Ok(map)
+    }
+    /// Split the array into two arrays, the first containing all elements (in order) that return
+    /// `true` when applied the `filter` function, and the second containing all other elements.
+    ///
+    /// The two arrays are returned as an array.
+    ///
+    /// # No Function Parameter
+    ///
+    /// Array element (mutable) is bound to `this`.
+    ///
+    /// This method is marked _pure_; the `filter` function should not mutate array elements.
+    ///
+    /// # Function Parameters
+    ///
+    /// * `element`: copy of array element
+    /// * `index` _(optional)_: current index in the array
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let x = [1, 2, 3, 4, 5];
+    ///
+    /// let y = x.partition(|v| v >= 3);
+    ///
+    /// print(y);       // prints "[[3, 4, 5], [1, 2]]"
+    /// ```
+    #[rhai_fn(return_raw, pure)]
+    pub fn partition(
+        ctx: NativeCallContext,
+        array: &mut Array,
+        filter: FnPtr,
+    ) -> RhaiResultOf<Array> {
+        let mut matched = Array::new();
+        let mut others = Array::new();
+
+        for (i, item) in array.iter_mut().enumerate() {
+            let ex = [(i as INT).into()];
+
+            if filter
+                .call_raw_with_extra_args("partition", &ctx, Some(item), [], ex, Some(0))?
+                .as_bool()
+                .unwrap_or(false)
+            {
+                matched.push(item.clone());
+            } else {
+                others.push(item.clone());
+            }
+        }
+
+        Ok(vec![matched.into(), others.into()])
+    }
+    /// Return the sum of all elements in the array.
+    ///
+    /// The operator `+` is used to add elements and must be defined.
+    ///
+    /// If the array is empty, zero is returned.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let x = [1, 2, 3, 4, 5];
+    ///
+    /// print(x.sum());     // prints 15
+    ///
+    /// let y = [1.5, 2, 3];
+    ///
+    /// print(y.sum());     // prints 6.5
+    /// ```
+    #[rhai_fn(return_raw, pure)]
+    pub fn sum(ctx: NativeCallContext, array: &mut Array) -> RhaiResult {
+        fold_with_operator(&ctx, array, "+", Dynamic::from_int(0))
+    }
+    /// Return the product of all elements in the array.
+    ///
+    /// The operator `*` is used to multiply elements and must be defined.
+    ///
+    /// If the array is empty, one is returned.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let x = [1, 2, 3, 4, 5];
+    ///
+    /// print(x.product());     // prints 120
+    /// ```
+    #[rhai_fn(return_raw, pure)]
+    pub fn product(ctx: NativeCallContext, array: &mut Array) -> RhaiResult {
+        fold_with_operator(&ctx, array, "*", Dynamic::from_int(1))
+    }
+    /// Return the smallest element in the array.
+    ///
+    /// The operator `<` is used to compare elements and must be defined.
+    ///
+    /// If the array is empty, `()` is returned.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let x = [3, 1, 4, 1, 5];
+    ///
+    /// print(x.min());     // prints 1
+    /// ```
+    #[rhai_fn(name = "min", return_raw, pure)]
+    pub fn min_item(ctx: NativeCallContext, array: &mut Array) -> RhaiResult {
+        let mut result: Option<&Dynamic> = None;
+
+        for item in array.iter() {
+            result = match result {
+                Some(r) if compare_items(&ctx, item, r)? != Ordering::Less => Some(r),
+                _ => Some(item),
+            };
+        }
+
+        Ok(result.cloned().unwrap_or(Dynamic::UNIT))
+    }
+    /// Return the largest element in the array.
+    ///
+    /// The operator `<` is used to compare elements and must be defined.
+    ///
+    /// If the array is empty, `()` is returned.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let x = [3, 1, 4, 1, 5];
+    ///
+    /// print(x.max());     // prints 5
+    /// ```
+    #[rhai_fn(name = "max", return_raw, pure)]
+    pub fn max_item(ctx: NativeCallContext, array: &mut Array) -> RhaiResult {
+        let mut result: Option<&Dynamic> = None;
+
+        for item in array.iter() {
+            result = match result {
+                Some(r) if compare_items(&ctx, item, r)? != Ordering::Greater => Some(r),
+                _ => Some(item),
+            };
+        }
+
+        Ok(result.cloned().unwrap_or(Dynamic::UNIT))
+    }
+    /// Sort the array based on the keys returned by applying the `key` function to each element.
+    ///
+    /// The `key` function is called once for each element. The sort is stable, i.e. elements with
+    /// equal keys keep their order.
+    ///
+    /// The operator `<` is used to compare keys and must be defined.
+    ///
+    /// # No Function Parameter
+    ///
+    /// Array element (mutable) is bound to `this`.
+    ///
+    /// # Function Parameters
+    ///
+    /// * `element`: copy of array element
+    /// * `index` _(optional)_: current index in the array
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let x = ["apple", "fig", "banana", "kiwi"];
+    ///
+    /// x.sort_by_key(|s| s.len);
+    ///
+    /// print(x);       // prints "["fig", "kiwi", "apple", "banana"]"
+    /// ```
+    #[rhai_fn(return_raw)]
+    pub fn sort_by_key(ctx: NativeCallContext, array: &mut Array, key: FnPtr) -> RhaiResultOf<()> {
+        if array.len() <= 1 {
+            return Ok(());
+        }
+
+        let mut keyed = Vec::with_capacity(array.len());
+
+        for (i, mut item) in mem::take(array).into_iter().enumerate() {
+            let ex = [(i as INT).into()];
+            let k = key.call_raw_with_extra_args(
+                "sort_by_key",
+                &ctx,
+                Some(&mut item),
+                [],
+                ex,
+                Some(0),
+            )?;
+            keyed.push((k, item));
+        }
+
+        let mut error = None;
+
+        keyed.sort_by(|(x, _), (y, _)| {
+            if error.is_some() {
+                return Ordering::Equal;
+            }
+            compare_items(&ctx, x, y).unwrap_or_else(|err| {
+                error = Some(err);
+                Ordering::Equal
+            })
+        });
+
+        if let Some(err) = error {
+            return Err(err);
+        }
+
+        array.extend(keyed.into_iter().map(|(_, item)| item));
+
+        Ok(())
+    }
+    /// Return a new array with only the first element (in order) for each distinct key returned by
+    /// applying the `key` function to the elements.
+    ///
+    /// The operator `==` is used to compare keys. Keys of different types are never equal.
+    ///
+    /// # No Function Parameter
+    ///
+    /// Array element (mutable) is bound to `this`.
+    ///
+    /// This method is marked _pure_; the `key` function should not mutate array elements.
+    ///
+    /// # Function Parameters
+    ///
+    /// * `element`: copy of array element
+    /// * `index` _(optional)_: current index in the array
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let x = [1, -1, 2, 3, -2, 1];
+    ///
+    /// let y = x.unique_by(|v| abs(v));
+    ///
+    /// print(y);       // prints "[1, 2, 3]"
+    /// ```
+    #[rhai_fn(return_raw, pure)]
+    pub fn unique_by(ctx: NativeCallContext, array: &mut Array, key: FnPtr) -> RhaiResultOf<Array> {
+        let mut result = Array::new();
+        let mut keys = Array::new();
+        // Indices into `keys` for each hash of hashable keys
+        let mut hashed = StraightHashMap::<Vec<usize>>::default();
+
+        for (i, item) in array.iter_mut().enumerate() {
+            let ex = [(i as INT).into()];
+            let k = key.call_raw_with_extra_args("unique_by", &ctx, Some(item), [], ex, Some(0))?;
+
+            let candidates = if k.is_hashable() {
+                let hasher = &mut get_hasher();
+                k.hash(hasher);
+                hashed.entry(hasher.finish()).or_default()
+            } else {
+                // Unhashable keys are compared with all other unhashable keys
+                hashed.entry(0).or_default()
+            };
+
+            let mut found = false;
+
+            for &index in candidates.iter() {
+                if keys[index].type_id() == k.type_id() && equals_item(&ctx, &keys[index], &k)? {
+                    found = true;
+                    break;
+                }
+            }
+
+            if !found {
+                candidates.push(keys.len());
+                keys.push(k);
+                result.push(item.clone());
+            }
+        }
+
+        Ok(result)
+    }
     /// Return `true` if two arrays are equal (i.e. all elements are equal and in the same order).
     ///
     /// The operator `==` is used to compare elements and must be defined,
@@ -2040,3 +2511,64 @@ pub mod array_functions {
         equals(ctx, array1, array2).map(|r| !r)
     }
 }
+
+/// Check the size of chunks or windows of an array.
+#[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
+fn check_group_size(kind: &str, size: INT) -> RhaiResultOf<usize> {
+    if size <= 0 || size > MAX_USIZE_INT {
+        return Err(
+            ERR::ErrorArithmetic(format!("Invalid {kind} size: {size}"), Position::NONE).into(),
+        );
+    }
+
+    Ok(size as usize)
+}
+
+/// Fold all elements in an array with a binary operator, starting from an `initial` value.
+///
+/// The `initial` value is returned if the array is empty, otherwise it is not used.
+fn fold_with_operator(
+    ctx: &NativeCallContext,
+    array: &mut Array,
+    op: &str,
+    initial: Dynamic,
+) -> RhaiResult {
+    let mut items = array.iter();
+
+    let mut result = match items.next() {
+        Some(item) => item.clone(),
+        None => return Ok(initial),
+    };
+
+    for item in items {
+        result = ctx.call_native_fn_raw(op, true, &mut [&mut result, &mut item.clone()])?;
+    }
+
+    Ok(result)
+}
+
+/// Compare two elements with the `<` operator.
+fn compare_items(ctx: &NativeCallContext, x: &Dynamic, y: &Dynamic) -> RhaiResultOf<Ordering> {
+    if let (Ok(x), Ok(y)) = (x.as_int(), y.as_int()) {
+        return Ok(x.cmp(&y));
+    }
+
+    let less_than = |x: &Dynamic, y: &Dynamic| {
+        ctx.call_native_fn_raw("<", false, &mut [&mut x.clone(), &mut y.clone()])
+            .map(|v| v.as_bool().unwrap_or(false))
+    };
+
+    Ok(if less_than(x, y)? {
+        Ordering::Less
+    } else if less_than(y, x)? {
+        Ordering::Greater
+    } else {
+        Ordering::Equal
+    })
+}
+
+/// Check if two elements are equal with the `==` operator.
+fn equals_item(ctx: &NativeCallContext, x: &Dynamic, y: &Dynamic) -> RhaiResultOf<bool> {
+    ctx.call_native_fn_raw(OP_EQUALS, false, &mut [&mut x.clone(), &mut y.clone()])
+        .map(|v| v.as_bool().unwrap_or(false))
+}
@@ -0,0 +1,499 @@
+use super::arithmetic::make_err as make_arithmetic_err;
+use crate::plugin::*;
+use crate::{def_package, DateTime, ImmutableString, RhaiResult, RhaiResultOf, INT};
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+
+#[cfg(not(feature = "no_float"))]
+use crate::FLOAT;
+
+#[cfg(feature = "no_std")]
+#[cfg(not(feature = "no_float"))]
+use num_traits::Float;
+
+def_package! {
+    /// Package of calendar date/time utilities based on [`DateTime`].
+    ///
+    /// This package is not part of the [`StandardPackage`][super::StandardPackage] and must be
+    /// registered separately. Unlike the [`BasicTimePackage`][super::BasicTimePackage], it is
+    /// available under `no_time`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rhai::Engine;
+    /// use rhai::packages::{Package, DateTimePackage};
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// DateTimePackage::new().register_into_engine(&mut engine);
+    ///
+    /// let result = engine.eval::<String>(r#"
+    ///     let date = parse_datetime("2024-01-31T09:30:00+08:00");
+    ///     date.add_months(1).format("%d %B %Y, %H:%M")
+    /// "#)?;
+    ///
+    /// assert_eq!(result, "29 February 2024, 09:30");
+    /// # Ok::<_, Box<rhai::EvalAltResult>>(())
+    /// ```
+    pub DateTimePackage(lib) {
+        lib.set_standard_lib(true);
+
+        combine_with_exported_module!(lib, "datetime", datetime_functions);
+    }
+}
+
+#[export_module]
+#[allow(clippy::unnecessary_cast)] // `INT` may be `i64` or `i32`
+mod datetime_functions {
+    /// Create a date/time containing the current system time, in UTC.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let now = now();
+    ///
+    /// print(now.year);
+    /// ```
+    #[cfg(not(feature = "no_std"))]
+    #[rhai_fn(volatile)]
+    pub fn now() -> DateTime {
+        DateTime::now()
+    }
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn make_datetime(
+        year: INT,
+        month: INT,
+        day: INT,
+        hour: INT,
+        minute: INT,
+        second: INT,
+    ) -> RhaiResultOf<DateTime> {
+        // Out-of-range values are clamped to values that are also out of range
+        let to_u32 = |value: INT| value.clamp(0, 60) as u32;
+
+        DateTime::new(
+            year.clamp(-1, 10_000) as i32,
+            to_u32(month),
+            to_u32(day),
+            to_u32(hour),
+            to_u32(minute),
+            to_u32(second),
+        )
+        .ok_or_else(|| {
+            make_arithmetic_err(format!(
+                "Invalid date/time: {year}-{month}-{day} {hour}:{minute}:{second}"
+            ))
+        })
+    }
+
+    /// Create a date/time in UTC at midnight of the specified date.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let date = datetime(2024, 1, 15);
+    ///
+    /// print(date);            // prints "2024-01-15T00:00:00Z"
+    /// ```
+    #[rhai_fn(name = "datetime", return_raw)]
+    pub fn datetime_from_date(year: INT, month: INT, day: INT) -> RhaiResultOf<DateTime> {
+        make_datetime(year, month, day, 0, 0, 0)
+    }
+    /// Create a date/time in UTC from the specified date and time.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let date = datetime(2024, 1, 15, 9, 30, 0);
+    ///
+    /// print(date);            // prints "2024-01-15T09:30:00Z"
+    /// ```
+    #[rhai_fn(return_raw)]
+    pub fn datetime(
+        year: INT,
+        month: INT,
+        day: INT,
+        hour: INT,
+        minute: INT,
+        second: INT,
+    ) -> RhaiResultOf<DateTime> {
+        make_datetime(year, month, day, hour, minute, second)
+    }
+    /// Create a date/time in UTC from the number of seconds since the Unix epoch
+    /// (1970-01-01T00:00:00Z).
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let date = datetime_from_unix(1705311000);
+    ///
+    /// print(date);            // prints "2024-01-15T09:30:00Z"
+    /// ```
+    #[rhai_fn(return_raw)]
+    pub fn datetime_from_unix(seconds: INT) -> RhaiResultOf<DateTime> {
+        DateTime::from_unix_timestamp(seconds as i64, 0)
+            .ok_or_else(|| make_arithmetic_err(format!("Unix timestamp out of range: {seconds}")))
+    }
+    /// Parse an RFC 3339 date/time string, keeping its offset from UTC.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let date = parse_datetime("2024-01-15T09:30:00+08:00");
+    ///
+    /// print(date.hour);       // prints 9
+    /// ```
+    #[rhai_fn(name = "parse_datetime", return_raw)]
+    pub fn parse_rfc3339(string: &str) -> RhaiResultOf<DateTime> {
+        DateTime::parse_rfc3339(string)
+    }
+    /// Parse a date/time string with a format string.
+    ///
+    /// The format string takes the same specifiers as `format`, except for `%j`, `%u` and `%w`.
+    /// The year is required, other components default to the start of their ranges, and the offset
+    /// defaults to UTC.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let date = parse_datetime("15/01/2024 9:30 PM", "%d/%m/%Y %I:%M %p");
+    ///
+    /// print(date);            // prints "2024-01-15T21:30:00Z"
+    /// ```
+    #[rhai_fn(name = "parse_datetime", return_raw)]
+    pub fn parse_with_format(string: &str, format: &str) -> RhaiResultOf<DateTime> {
+        DateTime::parse_from_str(string, format)
+    }
+
+    /// Format the date/time with a format string.
+    ///
+    /// | Specifier   | Component                                 |
+    /// |-------------|-------------------------------------------|
+    /// | `%Y` / `%y` | year / year within the century            |
+    /// | `%m`        | month                                     |
+    /// | `%b` / `%B` | abbreviated / full month name             |
+    /// | `%d` / `%e` | day of the month, zero / space padded     |
+    /// | `%j`        | day of the year                           |
+    /// | `%a` / `%A` | abbreviated / full weekday name           |
+    /// | `%u` / `%w` | weekday, from Monday = 1 / Sunday = 0     |
+    /// | `%H`        | hour                                      |
+    /// | `%I` / `%p` | hour in 12-hour clock / `AM` or `PM`      |
+    /// | `%M` / `%S` | minute / second                           |
+    /// | `%f`        | nanoseconds                               |
+    /// | `%3f` / `%6f` / `%9f` | milli- / micro- / nanoseconds   |
+    /// | `%z` / `%:z`| offset from UTC, as `+hhmm` / `+hh:mm`    |
+    /// | `%s`        | number of seconds since the Unix epoch    |
+    /// | `%F` / `%T` | same as `%Y-%m-%d` / `%H:%M:%S`           |
+    /// | `%%`        | a literal `%`                             |
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let date = datetime(2024, 1, 15, 9, 30, 0);
+    ///
+    /// print(date.format("%a %e %b %Y %I:%M %p"));     // prints "Mon 15 Jan 2024 09:30 AM"
+    /// ```
+    #[rhai_fn(pure, return_raw)]
+    pub fn format(dt: &mut DateTime, format: &str) -> RhaiResultOf<ImmutableString> {
+        dt.format(format).map(Into::into)
+    }
+    /// Convert the date/time to an RFC 3339 string.
+    #[rhai_fn(
+        name = "print",
+        name = "to_string",
+        name = "debug",
+        name = "to_debug",
+        pure
+    )]
+    pub fn to_string(dt: &mut DateTime) -> ImmutableString {
+        dt.to_string().into()
+    }
+
+    /// Return the year of the date/time.
+    #[rhai_fn(name = "year", get = "year", pure)]
+    pub fn year(dt: &mut DateTime) -> INT {
+        dt.year() as INT
+    }
+    /// Return the month of the date/time, from 1 to 12.
+    #[rhai_fn(name = "month", get = "month", pure)]
+    pub fn month(dt: &mut DateTime) -> INT {
+        dt.month() as INT
+    }
+    /// Return the day of the month of the date/time, from 1 to 31.
+    #[rhai_fn(name = "day", get = "day", pure)]
+    pub fn day(dt: &mut DateTime) -> INT {
+        dt.day() as INT
+    }
+    /// Return the hour of the date/time, from 0 to 23.
+    #[rhai_fn(name = "hour", get = "hour", pure)]
+    pub fn hour(dt: &mut DateTime) -> INT {
+        dt.hour() as INT
+    }
+    /// Return the minute of the date/time, from 0 to 59.
+    #[rhai_fn(name = "minute", get = "minute", pure)]
+    pub fn minute(dt: &mut DateTime) -> INT {
+        dt.minute() as INT
+    }
+    /// Return the second of the date/time, from 0 to 59.
+    #[rhai_fn(name = "second", get = "second", pure)]
+    pub fn second(dt: &mut DateTime) -> INT {
+        dt.second() as INT
+    }
+    /// Return the nanoseconds within the second of the date/time.
+    #[rhai_fn(name = "nanosecond", get = "nanosecond", pure)]
+    pub fn nanosecond(dt: &mut DateTime) -> INT {
+        dt.nanosecond() as INT
+    }
+    /// Return the day of the week of the date/time, from 1 (Monday) to 7 (Sunday).
+    #[rhai_fn(name = "weekday", get = "weekday", pure)]
+    pub fn weekday(dt: &mut DateTime) -> INT {
+        dt.weekday() as INT
+    }
+    /// Return the day of the year of the date/time, from 1 to 366.
+    #[rhai_fn(name = "day_of_year", get = "day_of_year", pure)]
+    pub fn day_of_year(dt: &mut DateTime) -> INT {
+        dt.ordinal() as INT
+    }
+    /// Return the offset from UTC of the date/time, in minutes.
+    #[rhai_fn(name = "offset", get = "offset", pure)]
+    pub fn offset(dt: &mut DateTime) -> INT {
+        dt.offset() as INT
+    }
+    /// Return the number of seconds since the Unix epoch (1970-01-01T00:00:00Z) of the date/time.
+    #[rhai_fn(name = "unix_timestamp", get = "unix_timestamp", pure, return_raw)]
+    pub fn unix_timestamp(dt: &mut DateTime) -> RhaiResultOf<INT> {
+        let seconds = dt.unix_timestamp();
+
+        #[cfg(feature = "only_i32")]
+        if seconds > (INT::MAX as i64) || seconds < (INT::MIN as i64) {
+            return Err(make_arithmetic_err(format!(
+                "Integer overflow for unix_timestamp: {seconds}"
+            )));
+        }
+
+        Ok(seconds as INT)
+    }
+
+    /// Return the same point in time as the date/time, in UTC.
+    #[rhai_fn(pure, return_raw)]
+    pub fn to_utc(dt: &mut DateTime) -> RhaiResultOf<DateTime> {
+        to_offset(dt, 0)
+    }
+    /// Return the same point in time as the date/time, under the specified offset from UTC in
+    /// minutes.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let date = parse_datetime("2024-01-15T09:30:00Z");
+    ///
+    /// print(date.to_offset(-300));    // prints "2024-01-15T04:30:00-05:00"
+    /// ```
+    #[rhai_fn(pure, return_raw)]
+    pub fn to_offset(dt: &mut DateTime, minutes: INT) -> RhaiResultOf<DateTime> {
+        let result = if (-1440..=1440).contains(&minutes) {
+            #[allow(clippy::cast_possible_truncation)]
+            dt.with_offset(minutes as i32)
+        } else {
+            None
+        };
+        result.ok_or_else(|| make_arithmetic_err(format!("Invalid offset from UTC: {minutes}")))
+    }
+
+    fn add_impl(result: Option<DateTime>) -> RhaiResultOf<DateTime> {
+        result.ok_or_else(|| make_arithmetic_err("Date/time out of range"))
+    }
+
+    /// Add the specified number of calendar years to the date/time and return it as a new
+    /// date/time.
+    ///
+    /// February 29 becomes February 28 in a year that is not a leap year.
+    #[rhai_fn(pure, return_raw)]
+    pub fn add_years(dt: &mut DateTime, years: INT) -> RhaiResultOf<DateTime> {
+        let result = (years as i64)
+            .checked_mul(12)
+            .and_then(|months| dt.checked_add_months(months));
+        add_impl(result)
+    }
+    /// Add the specified number of calendar months to the date/time and return it as a new
+    /// date/time.
+    ///
+    /// The day is kept, unless it does not exist in the resulting month, in which case the last
+    /// day of that month is used.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let date = datetime(2024, 1, 31);
+    ///
+    /// print(date.add_months(1));      // prints "2024-02-29T00:00:00Z"
+    /// ```
+    #[rhai_fn(pure, return_raw)]
+    pub fn add_months(dt: &mut DateTime, months: INT) -> RhaiResultOf<DateTime> {
+        add_impl(dt.checked_add_months(months as i64))
+    }
+    /// Add the specified number of days to the date/time and return it as a new date/time.
+    #[rhai_fn(pure, return_raw)]
+    pub fn add_days(dt: &mut DateTime, days: INT) -> RhaiResultOf<DateTime> {
+        add_impl(dt.checked_add_days(days as i64))
+    }
+    /// Add the specified number of hours to the date/time and return it as a new date/time.
+    #[rhai_fn(pure, return_raw)]
+    pub fn add_hours(dt: &mut DateTime, hours: INT) -> RhaiResultOf<DateTime> {
+        let result = (hours as i64)
+            .checked_mul(3600)
+            .and_then(|seconds| dt.checked_add_seconds(seconds));
+        add_impl(result)
+    }
+    /// Add the specified number of minutes to the date/time and return it as a new date/time.
+    #[rhai_fn(pure, return_raw)]
+    pub fn add_minutes(dt: &mut DateTime, minutes: INT) -> RhaiResultOf<DateTime> {
+        let result = (minutes as i64)
+            .checked_mul(60)
+            .and_then(|seconds| dt.checked_add_seconds(seconds));
+        add_impl(result)
+    }
+    /// Add the specified number of seconds to the date/time and return it as a new date/time.
+    #[rhai_fn(pure, return_raw)]
+    pub fn add_seconds(dt: &mut DateTime, seconds: INT) -> RhaiResultOf<DateTime> {
+        add_impl(dt.checked_add_seconds(seconds as i64))
+    }
+
+    /// Add the specified number of `seconds` to the date/time and return it as a new date/time.
+    #[rhai_fn(return_raw, name = "+")]
+    pub fn add(dt: DateTime, seconds: INT) -> RhaiResultOf<DateTime> {
+        add_impl(dt.checked_add_seconds(seconds as i64))
+    }
+    /// Add the specified number of `seconds` to the date/time.
+    #[rhai_fn(return_raw, name = "+=")]
+    pub fn add_assign(dt: &mut DateTime, seconds: INT) -> RhaiResultOf<()> {
+        *dt = add(*dt, seconds)?;
+        Ok(())
+    }
+    /// Subtract the specified number of `seconds` from the date/time and return it as a new
+    /// date/time.
+    #[rhai_fn(return_raw, name = "-")]
+    pub fn subtract(dt: DateTime, seconds: INT) -> RhaiResultOf<DateTime> {
+        let result = (seconds as i64)
+            .checked_neg()
+            .and_then(|seconds| dt.checked_add_seconds(seconds));
+        add_impl(result)
+    }
+    /// Subtract the specified number of `seconds` from the date/time.
+    #[rhai_fn(return_raw, name = "-=")]
+    pub fn subtract_assign(dt: &mut DateTime, seconds: INT) -> RhaiResultOf<()> {
+        *dt = subtract(*dt, seconds)?;
+        Ok(())
+    }
+
+    /// Return the number of seconds between two date/times.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let start = parse_datetime("2024-01-15T09:30:00Z");
+    /// let end = parse_datetime("2024-01-15T10:00:00+01:00");
+    ///
+    /// print(end - start);     // prints -1800.0
+    /// ```
+    #[rhai_fn(return_raw, name = "-")]
+    #[allow(clippy::unnecessary_wraps)]
+    pub fn time_diff(dt1: DateTime, dt2: DateTime) -> RhaiResult {
+        let nanos = dt1.nanoseconds_since(&dt2);
+
+        #[cfg(not(feature = "no_float"))]
+        return Ok(((nanos as f64 / 1e9) as FLOAT).into());
+
+        #[cfg(feature = "no_float")]
+        {
+            let seconds = nanos / 1_000_000_000;
+
+            if cfg!(not(feature = "unchecked"))
+                && (seconds > (INT::MAX as i128) || seconds < (INT::MIN as i128))
+            {
+                return Err(make_arithmetic_err(format!(
+                    "Integer overflow for date/time duration: {seconds}"
+                )));
+            }
+
+            Ok((seconds as INT).into())
+        }
+    }
+
+    #[cfg(not(feature = "no_float"))]
+    pub mod float_functions {
+        #[allow(clippy::cast_possible_truncation)]
+        fn add_impl(dt: DateTime, seconds: FLOAT) -> RhaiResultOf<DateTime> {
+            let nanos = (seconds * 1e9).round();
+
+            if !nanos.is_finite() {
+                return Err(make_arithmetic_err(format!(
+                    "Date/time overflow when adding {seconds} second(s)"
+                )));
+            }
+
+            dt.checked_add_nanoseconds(nanos as i128).ok_or_else(|| {
+                make_arithmetic_err(format!(
+                    "Date/time overflow when adding {seconds} second(s)"
+                ))
+            })
+        }
+
+        /// Add the specified number of `seconds` to the date/time and return it as a new date/time.
+        #[rhai_fn(return_raw, name = "+")]
+        pub fn add(dt: DateTime, seconds: FLOAT) -> RhaiResultOf<DateTime> {
+            add_impl(dt, seconds)
+        }
+        /// Add the specified number of `seconds` to the date/time.
+        #[rhai_fn(return_raw, name = "+=")]
+        pub fn add_assign(dt: &mut DateTime, seconds: FLOAT) -> RhaiResultOf<()> {
+            *dt = add_impl(*dt, seconds)?;
+            Ok(())
+        }
+        /// Subtract the specified number of `seconds` from the date/time and return it as a new
+        /// date/time.
+        #[rhai_fn(return_raw, name = "-")]
+        pub fn subtract(dt: DateTime, seconds: FLOAT) -> RhaiResultOf<DateTime> {
+            add_impl(dt, -seconds)
+        }
+        /// Subtract the specified number of `seconds` from the date/time.
+        #[rhai_fn(return_raw, name = "-=")]
+        pub fn subtract_assign(dt: &mut DateTime, seconds: FLOAT) -> RhaiResultOf<()> {
+            *dt = add_impl(*dt, -seconds)?;
+            Ok(())
+        }
+    }
+
+    /// Return `true` if two date/times are the same point in time.
+    #[rhai_fn(name = "==")]
+    pub fn eq(dt1: DateTime, dt2: DateTime) -> bool {
+        dt1 == dt2
+    }
+    /// Return `true` if two date/times are not the same point in time.
+    #[rhai_fn(name = "!=")]
+    pub fn ne(dt1: DateTime, dt2: DateTime) -> bool {
+        dt1 != dt2
+    }
+    /// Return `true` if the first date/time is earlier than the second.
+    #[rhai_fn(name = "<")]
+    pub fn lt(dt1: DateTime, dt2: DateTime) -> bool {
+        dt1 < dt2
+    }
+    /// Return `true` if the first date/time is earlier than or equals to the second.
+    #[rhai_fn(name = "<=")]
+    pub fn lte(dt1: DateTime, dt2: DateTime) -> bool {
+        dt1 <= dt2
+    }
+    /// Return `true` if the first date/time is later than the second.
+    #[rhai_fn(name = ">")]
+    pub fn gt(dt1: DateTime, dt2: DateTime) -> bool {
+        dt1 > dt2
+    }
+    /// Return `true` if the first date/time is later than or equals to the second.
+    #[rhai_fn(name = ">=")]
+    pub fn gte(dt1: DateTime, dt2: DateTime) -> bool {
+        dt1 >= dt2
+    }
+}
@@ -1,8 +1,9 @@
-use crate::eval::calc_index;
+use crate::eval::{calc_index, GlobalRuntimeState};
 use crate::plugin::*;
 use crate::FuncRegistration;
 use crate::{
-    def_package, ExclusiveRange, InclusiveRange, RhaiResultOf, ERR, INT, INT_BITS, MAX_USIZE_INT,
+    def_package, ExclusiveRange, FnPtr, InclusiveRange, RhaiResultOf, ERR, INT, INT_BITS,
+    MAX_USIZE_INT,
 };
 #[cfg(feature = "no_std")]
 use std::prelude::v1::*;
@@ -248,6 +249,228 @@ impl ExactSizeIterator for CharsStream {
     }
 }
 
+/// Lazy iterator built from script, consisting of a source of values and a chain of adapters.
+///
+/// No values are produced until it is iterated, e.g. in a `for` statement. Each iteration starts
+/// from the beginning, so the same [`LazyIterator`] can be iterated multiple times.
+#[derive(Debug, Clone)]
+pub enum LazyIterator {
+    /// Values of any iterable type.
+    Values(Dynamic),
+    /// Values returned by a function pointer until it returns `()`, with an optional state bound
+    /// to `this`.
+    Generator(FnPtr, Option<Dynamic>),
+    /// Values mapped by a function pointer.
+    Map(Box<Self>, FnPtr),
+    /// Values filtered by a function pointer.
+    Filter(Box<Self>, FnPtr),
+    /// Up to a number of values.
+    Take(Box<Self>, usize),
+    /// Values after skipping a number of values.
+    Skip(Box<Self>, usize),
+    /// Pairs of values from two iterators, as arrays.
+    #[cfg(not(feature = "no_index"))]
+    Zip(Box<Self>, Box<Self>),
+    /// Values paired with their indices, as arrays.
+    #[cfg(not(feature = "no_index"))]
+    Enumerate(Box<Self>),
+    /// Values from one iterator followed by values from another.
+    Chain(Box<Self>, Box<Self>),
+}
+
+impl LazyIterator {
+    /// Start iterating, returning a [`LazyCursor`] that produces the values.
+    ///
+    /// Returns [`ErrorFor`][ERR::ErrorFor] if a source of values is not iterable.
+    pub(crate) fn start(
+        self,
+        engine: &Engine,
+        global: &GlobalRuntimeState,
+        pos: Position,
+    ) -> RhaiResultOf<LazyCursor> {
+        let start = |iter: Box<Self>| iter.start(engine, global, pos).map(Box::new);
+
+        Ok(match self {
+            Self::Values(value) => {
+                let value = match value.flatten().try_cast_result::<Self>() {
+                    Ok(iter) => return iter.start(engine, global, pos),
+                    Err(value) => value,
+                };
+                let iter_func = engine
+                    .get_iter_func(global, value.type_id())
+                    .ok_or_else(|| ERR::ErrorFor(pos))?;
+                LazyCursor::Values(iter_func(value))
+            }
+            Self::Generator(func, state) => LazyCursor::Generator(func, state, 0),
+            Self::Map(iter, func) => LazyCursor::Map(start(iter)?, func, 0),
+            Self::Filter(iter, func) => LazyCursor::Filter(start(iter)?, func, 0),
+            Self::Take(iter, n) => LazyCursor::Take(start(iter)?, n),
+            Self::Skip(iter, n) => LazyCursor::Skip(start(iter)?, n),
+            #[cfg(not(feature = "no_index"))]
+            Self::Zip(iter1, iter2) => LazyCursor::Zip(start(iter1)?, start(iter2)?),
+            #[cfg(not(feature = "no_index"))]
+            Self::Enumerate(iter) => LazyCursor::Enumerate(start(iter)?, 0),
+            Self::Chain(iter1, iter2) => LazyCursor::Chain(start(iter1)?, start(iter2)?),
+        })
+    }
+    /// Does iterating call any function pointer?
+    #[must_use]
+    pub(crate) fn calls_functions(&self) -> bool {
+        match self {
+            Self::Values(value) => value
+                .read_lock::<Self>()
+                .map_or(false, |iter| iter.calls_functions()),
+            Self::Generator(..) | Self::Map(..) | Self::Filter(..) => true,
+            Self::Take(iter, ..) | Self::Skip(iter, ..) => iter.calls_functions(),
+            #[cfg(not(feature = "no_index"))]
+            Self::Zip(iter1, iter2) => iter1.calls_functions() || iter2.calls_functions(),
+            #[cfg(not(feature = "no_index"))]
+            Self::Enumerate(iter) => iter.calls_functions(),
+            Self::Chain(iter1, iter2) => iter1.calls_functions() || iter2.calls_functions(),
+        }
+    }
+}
+
+/// Running state of a [`LazyIterator`].
+pub(crate) enum LazyCursor {
+    /// Values from a type iterator.
+    Values(Box<dyn Iterator<Item = RhaiResultOf<Dynamic>>>),
+    /// Generator function pointer, its state and the current index.
+    Generator(FnPtr, Option<Dynamic>, INT),
+    /// Mapping function pointer and the current index.
+    Map(Box<Self>, FnPtr, INT),
+    /// Filter function pointer and the current index.
+    Filter(Box<Self>, FnPtr, INT),
+    /// Number of values remaining.
+    Take(Box<Self>, usize),
+    /// Number of values remaining to skip.
+    Skip(Box<Self>, usize),
+    /// Two cursors iterated in lockstep.
+    #[cfg(not(feature = "no_index"))]
+    Zip(Box<Self>, Box<Self>),
+    /// Current index.
+    #[cfg(not(feature = "no_index"))]
+    Enumerate(Box<Self>, INT),
+    /// Two cursors iterated one after another.
+    Chain(Box<Self>, Box<Self>),
+    /// No more values.
+    Done,
+}
+
+impl LazyCursor {
+    /// Get the next value, or [`None`] if there are no more values.
+    ///
+    /// Values skipped by `filter` and `skip` count towards the number of operations.
+    pub(crate) fn next(
+        &mut self,
+        engine: &Engine,
+        global: &mut GlobalRuntimeState,
+        pos: Position,
+    ) -> Option<RhaiResultOf<Dynamic>> {
+        match self {
+            Self::Values(iter) => iter.next(),
+            Self::Generator(func, state, index) => {
+                let ctx = (engine, "generator", global.source(), &*global, pos).into();
+                let ex = [(*index).into()];
+                *index += 1;
+
+                match func.call_raw_with_extra_args("generator", &ctx, state.as_mut(), [], ex, None)
+                {
+                    Ok(value) if value.is_unit() => {
+                        *self = Self::Done;
+                        None
+                    }
+                    result => Some(result),
+                }
+            }
+            Self::Map(iter, func, index) => {
+                let mut item = match iter.next(engine, global, pos)? {
+                    Ok(item) => item,
+                    err => return Some(err),
+                };
+                let ctx = (engine, "map", global.source(), &*global, pos).into();
+                let ex = [(*index).into()];
+                *index += 1;
+
+                Some(func.call_raw_with_extra_args("map", &ctx, Some(&mut item), [], ex, Some(0)))
+            }
+            Self::Filter(iter, func, index) => loop {
+                let mut item = match iter.next(engine, global, pos)? {
+                    Ok(item) => item,
+                    err => return Some(err),
+                };
+                let ctx = (engine, "filter", global.source(), &*global, pos).into();
+                let ex = [(*index).into()];
+                *index += 1;
+
+                match func.call_raw_with_extra_args(
+                    "filter",
+                    &ctx,
+                    Some(&mut item),
+                    [],
+                    ex,
+                    Some(0),
+                ) {
+                    Ok(r) if r.as_bool().unwrap_or(false) => return Some(Ok(item)),
+                    Ok(_) => (),
+                    Err(err) => return Some(Err(err)),
+                }
+
+                if let Err(err) = engine.track_operation(global, pos) {
+                    return Some(Err(err));
+                }
+            },
+            Self::Take(iter, n) => {
+                if *n == 0 {
+                    return None;
+                }
+                *n -= 1;
+                iter.next(engine, global, pos)
+            }
+            Self::Skip(iter, n) => {
+                while *n > 0 {
+                    *n -= 1;
+
+                    if let Err(err) = iter.next(engine, global, pos)? {
+                        return Some(Err(err));
+                    }
+                    if let Err(err) = engine.track_operation(global, pos) {
+                        return Some(Err(err));
+                    }
+                }
+                iter.next(engine, global, pos)
+            }
+            #[cfg(not(feature = "no_index"))]
+            Self::Zip(iter1, iter2) => {
+                let item1 = match iter1.next(engine, global, pos)? {
+                    Ok(item) => item,
+                    err => return Some(err),
+                };
+                let item2 = match iter2.next(engine, global, pos)? {
+                    Ok(item) => item,
+                    err => return Some(err),
+                };
+                Some(Ok(Dynamic::from_array(vec![item1, item2])))
+            }
+            #[cfg(not(feature = "no_index"))]
+            Self::Enumerate(iter, index) => {
+                let item = match iter.next(engine, global, pos)? {
+                    Ok(item) => item,
+                    err => return Some(err),
+                };
+                let i = *index;
+                *index += 1;
+                Some(Ok(Dynamic::from_array(vec![i.into(), item])))
+            }
+            Self::Chain(iter1, iter2) => match iter1.next(engine, global, pos) {
+                Some(result) => Some(result),
+                None => iter2.next(engine, global, pos),
+            },
+            Self::Done => None,
+        }
+    }
+}
+
 macro_rules! reg_range {
     ($lib:ident => $( $arg_type:ty ),*) => {
         $({
@@ -394,6 +617,7 @@ def_package! {
         // Register iterator functions
         combine_with_exported_module!(lib, "iterator", iterator_functions);
         combine_with_exported_module!(lib, "range", range_functions);
+        combine_with_exported_module!(lib, "lazy_iterator", lazy_iterator_functions);
     }
 }
 
@@ -672,3 +896,191 @@ mod range_functions {
         range.contains(&value)
     }
 }
+
+#[export_module]
+mod lazy_iterator_functions {
+    /// Return a lazy iterator over the values of an iterable value (e.g. an array, a range or
+    /// another lazy iterator).
+    ///
+    /// Lazy iterators produce no values until iterated in a `for` statement, and can be chained
+    /// with adapters such as `map`, `filter` and `take`.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let x = iter(0..1000000).map(|v| v * v).take(3);
+    ///
+    /// for v in x {
+    ///     print(v);           // prints 0, 1, 4
+    /// }
+    /// ```
+    pub fn iter(value: Dynamic) -> LazyIterator {
+        LazyIterator::Values(value)
+    }
+    /// Return a lazy iterator over the values returned by calling a `generator` function
+    /// repeatedly, until it returns `()`.
+    ///
+    /// # Function Parameters
+    ///
+    /// * `index` _(optional)_: number of values generated so far
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let x = generator(|i| if i < 3 { i * 10 });
+    ///
+    /// for v in x {
+    ///     print(v);           // prints 0, 10, 20
+    /// }
+    /// ```
+    #[rhai_fn(name = "generator")]
+    pub fn generator(generator: FnPtr) -> LazyIterator {
+        LazyIterator::Generator(generator, None)
+    }
+    /// Return a lazy iterator over the values returned by calling a `generator` function
+    /// repeatedly, until it returns `()`.
+    ///
+    /// A copy of `state` is bound to `this` within the `generator` function each time iteration
+    /// starts, and is kept between calls.
+    ///
+    /// # Function Parameters
+    ///
+    /// * `index` _(optional)_: number of values generated so far
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let fib = generator(#{ a: 0, b: 1 }, || {
+    ///     let v = this.a;
+    ///     this.a = this.b;
+    ///     this.b += v;
+    ///     v
+    /// });
+    ///
+    /// for v in fib.take(6) {
+    ///     print(v);           // prints 0, 1, 1, 2, 3, 5
+    /// }
+    /// ```
+    #[rhai_fn(name = "generator")]
+    pub fn generator_with_state(state: Dynamic, generator: FnPtr) -> LazyIterator {
+        LazyIterator::Generator(generator, Some(state))
+    }
+    /// Return a lazy iterator over the values of the iterator, each mapped by a `mapper` function.
+    ///
+    /// # No Function Parameter
+    ///
+    /// The value is bound to `this`.
+    ///
+    /// # Function Parameters
+    ///
+    /// * `value`: the value
+    /// * `index` _(optional)_: index of the value
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// for v in iter([1, 2, 3]).map(|v| v * 2) {
+    ///     print(v);           // prints 2, 4, 6
+    /// }
+    /// ```
+    pub fn map(iter: LazyIterator, mapper: FnPtr) -> LazyIterator {
+        LazyIterator::Map(Box::new(iter), mapper)
+    }
+    /// Return a lazy iterator over the values of the iterator for which a `filter` function
+    /// returns `true`.
+    ///
+    /// # No Function Parameter
+    ///
+    /// The value is bound to `this`.
+    ///
+    /// # Function Parameters
+    ///
+    /// * `value`: the value
+    /// * `index` _(optional)_: index of the value
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// for v in iter(1..=10).filter(|v| v % 3 == 0) {
+    ///     print(v);           // prints 3, 6, 9
+    /// }
+    /// ```
+    pub fn filter(iter: LazyIterator, filter: FnPtr) -> LazyIterator {
+        LazyIterator::Filter(Box::new(iter), filter)
+    }
+    /// Return a lazy iterator over at most the first `n` values of the iterator.
+    ///
+    /// If `n` ≤ 0, the iterator is empty.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// for v in iter(1..100).take(3) {
+    ///     print(v);           // prints 1, 2, 3
+    /// }
+    /// ```
+    pub fn take(iter: LazyIterator, n: INT) -> LazyIterator {
+        #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
+        let n = n.clamp(0, MAX_USIZE_INT) as usize;
+        LazyIterator::Take(Box::new(iter), n)
+    }
+    /// Return a lazy iterator over the values of the iterator after skipping the first `n` values.
+    ///
+    /// If `n` ≤ 0, no values are skipped.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// for v in iter(1..6).skip(3) {
+    ///     print(v);           // prints 4, 5
+    /// }
+    /// ```
+    pub fn skip(iter: LazyIterator, n: INT) -> LazyIterator {
+        #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
+        let n = n.clamp(0, MAX_USIZE_INT) as usize;
+        LazyIterator::Skip(Box::new(iter), n)
+    }
+    /// Return a lazy iterator over pairs of values from the iterator and another iterable value,
+    /// each as an array of two items.
+    ///
+    /// Iteration stops when either runs out of values.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// for v in iter([1, 2, 3]).zip("ab") {
+    ///     print(v);           // prints [1, 'a'], [2, 'b']
+    /// }
+    /// ```
+    #[cfg(not(feature = "no_index"))]
+    pub fn zip(iter: LazyIterator, other: Dynamic) -> LazyIterator {
+        LazyIterator::Zip(Box::new(iter), Box::new(LazyIterator::Values(other)))
+    }
+    /// Return a lazy iterator over the values of the iterator, each paired with its index as an
+    /// array of two items.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// for v in iter("ab").enumerate() {
+    ///     print(v);           // prints [0, 'a'], [1, 'b']
+    /// }
+    /// ```
+    #[cfg(not(feature = "no_index"))]
+    pub fn enumerate(iter: LazyIterator) -> LazyIterator {
+        LazyIterator::Enumerate(Box::new(iter))
+    }
+    /// Return a lazy iterator over the values of the iterator followed by the values of another
+    /// iterable value.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// for v in iter(1..3).chain([8, 9]) {
+    ///     print(v);           // prints 1, 2, 8, 9
+    /// }
+    /// ```
+    pub fn chain(iter: LazyIterator, other: Dynamic) -> LazyIterator {
+        LazyIterator::Chain(Box::new(iter), Box::new(LazyIterator::Values(other)))
+    }
+}
@@ -6,6 +6,7 @@ pub(crate) mod arithmetic;
 pub(crate) mod array_basic;
 pub(crate) mod bit_field;
 pub(crate) mod blob_basic;
+pub(crate) mod datetime;
 pub(crate) mod debugging;
 pub(crate) mod fn_basic;
 pub(crate) mod iter_basic;
@@ -15,8 +16,10 @@ pub(crate) mod map_basic;
 pub(crate) mod math_basic;
 pub(crate) mod pkg_core;
 pub(crate) mod pkg_std;
+pub(crate) mod random;
 pub(crate) mod string_basic;
 pub(crate) mod string_more;
+pub(crate) mod string_regex;
 pub(crate) mod time_basic;
 
 pub use arithmetic::ArithmeticPackage;
@@ -25,6 +28,7 @@ pub use array_basic::BasicArrayPackage;
 pub use bit_field::BitFieldPackage;
 #[cfg(not(feature = "no_index"))]
 pub use blob_basic::BasicBlobPackage;
+pub use datetime::DateTimePackage;
 #[cfg(feature = "debugging")]
 pub use debugging::DebuggingPackage;
 pub use fn_basic::BasicFnPackage;
@@ -36,8 +40,11 @@ pub use map_basic::BasicMapPackage;
 pub use math_basic::BasicMathPackage;
 pub use pkg_core::CorePackage;
 pub use pkg_std::StandardPackage;
+pub use random::RandomPackage;
 pub use string_basic::BasicStringPackage;
 pub use string_more::MoreStringPackage;
+#[cfg(feature = "regex")]
+pub use string_regex::RegexPackage;
 #[cfg(not(feature = "no_time"))]
 pub use time_basic::BasicTimePackage;
 
@@ -0,0 +1,309 @@
+use crate::func::locked_write;
+use crate::plugin::*;
+use crate::{
+    def_package, ExclusiveRange, InclusiveRange, NativeCallContext, RhaiError, RhaiResultOf, INT,
+};
+use std::fmt;
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+
+#[cfg(not(feature = "no_float"))]
+use crate::FLOAT;
+
+#[cfg(not(feature = "no_index"))]
+use crate::{Array, Blob, MAX_USIZE_INT};
+
+def_package! {
+    /// Package of random number utilities.
+    pub RandomPackage(lib) {
+        lib.set_standard_lib(true);
+
+        combine_with_exported_module!(lib, "random", random_functions);
+    }
+    |> |engine| {
+        engine.random_state.get_or_insert_with(|| Rng::from_entropy().into());
+    }
+}
+
+/// A fast, seedable pseudo-random number generator (_SplitMix64_).
+///
+/// It is not cryptographically secure.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct Rng(u64);
+
+impl Rng {
+    /// Create a new [`Rng`] from a seed.
+    #[inline(always)]
+    #[must_use]
+    pub const fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+    /// Create a new [`Rng`] with a seed that is different each time.
+    #[inline]
+    #[must_use]
+    pub fn from_entropy() -> Self {
+        Self(ahash::RandomState::new().hash_one(0_u64))
+    }
+    /// Get the next random [`u64`].
+    #[inline]
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+    /// Get a random [`u64`] in the range `0..bound`, without bias.
+    ///
+    /// `bound` must not be zero.
+    #[inline]
+    pub fn next_below(&mut self, bound: u64) -> u64 {
+        // Reject values in the incomplete last block of `bound`
+        let zone = u64::MAX - (u64::MAX - bound + 1) % bound;
+
+        loop {
+            let value = self.next_u64();
+
+            if value <= zone {
+                return value % bound;
+            }
+        }
+    }
+}
+
+/// Run a function with the random number generator of the [`Engine`][crate::Engine].
+///
+/// An error is returned if there is none (e.g. the [`Engine`][crate::Engine] is created via
+/// [`Engine::new_raw`][crate::Engine::new_raw] and [`RandomPackage`] is not registered via
+/// [`Package::register_into_engine`][super::Package::register_into_engine]) or it cannot be locked.
+fn with_rng<T>(ctx: &NativeCallContext, f: impl FnOnce(&mut Rng) -> T) -> RhaiResultOf<T> {
+    match ctx.engine().random_state.as_ref().and_then(locked_write) {
+        Some(mut rng) => Ok(f(&mut rng)),
+        None => Err(crate::ERR::ErrorSystem(
+            "Cannot generate random numbers".into(),
+            "random number generator is not available".into(),
+        )
+        .into()),
+    }
+}
+
+/// Create an error for an empty range.
+#[cold]
+#[inline(never)]
+fn empty_range_error(range: &impl fmt::Debug) -> RhaiError {
+    crate::ERR::ErrorArithmetic(
+        format!("Cannot pick a random number from an empty range {range:?}"),
+        crate::Position::NONE,
+    )
+    .into()
+}
+
+/// Get a random number in the range `start..=end`, which must not be empty.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn rand_between(ctx: &NativeCallContext, start: INT, end: INT) -> RhaiResultOf<INT> {
+    #[allow(clippy::unnecessary_cast)] // INT may be i64 or i32
+    let (start, end) = (start as i64, end as i64);
+    let span = end.wrapping_sub(start) as u64;
+
+    let offset = with_rng(ctx, |rng| match span.checked_add(1) {
+        Some(bound) => rng.next_below(bound),
+        None => rng.next_u64(),
+    })?;
+
+    Ok(start.wrapping_add(offset as i64) as INT)
+}
+
+#[export_module]
+mod random_functions {
+    /// Return a random integer.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let x = rand();
+    ///
+    /// print(x);           // prints a random integer, positive or negative
+    /// ```
+    #[rhai_fn(return_raw, volatile)]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+    pub fn rand(ctx: NativeCallContext) -> RhaiResultOf<INT> {
+        with_rng(&ctx, Rng::next_u64).map(|x| x as INT)
+    }
+    /// Return a random integer within an exclusive `range`.
+    ///
+    /// An error is raised if the range is empty.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let dice = rand(1..7);
+    ///
+    /// print(dice);        // prints a random number between 1 and 6
+    /// ```
+    #[rhai_fn(name = "rand", return_raw, volatile)]
+    pub fn rand_exclusive_range(
+        ctx: NativeCallContext,
+        range: ExclusiveRange,
+    ) -> RhaiResultOf<INT> {
+        if range.is_empty() {
+            return Err(empty_range_error(&range));
+        }
+        rand_between(&ctx, range.start, range.end - 1)
+    }
+    /// Return a random integer within an inclusive `range`.
+    ///
+    /// An error is raised if the range is empty.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let dice = rand(1..=6);
+    ///
+    /// print(dice);        // prints a random number between 1 and 6
+    /// ```
+    #[rhai_fn(name = "rand", return_raw, volatile)]
+    pub fn rand_inclusive_range(
+        ctx: NativeCallContext,
+        range: InclusiveRange,
+    ) -> RhaiResultOf<INT> {
+        if range.is_empty() {
+            return Err(empty_range_error(&range));
+        }
+        rand_between(&ctx, *range.start(), *range.end())
+    }
+    /// Return a random floating-point number between 0.0 (inclusive) and 1.0 (exclusive).
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let x = rand_float();
+    ///
+    /// print(x);           // prints a random number between 0.0 and 1.0
+    /// ```
+    #[cfg(not(feature = "no_float"))]
+    #[rhai_fn(return_raw, volatile)]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn rand_float(ctx: NativeCallContext) -> RhaiResultOf<FLOAT> {
+        // Take as many bits as the mantissa holds
+        let bits = FLOAT::MANTISSA_DIGITS;
+        let value = with_rng(&ctx, Rng::next_u64)? >> (64 - bits);
+        Ok(value as FLOAT / (1_u64 << bits) as FLOAT)
+    }
+
+    #[cfg(not(feature = "no_index"))]
+    pub mod arrays {
+        /// Shuffle the elements in the array randomly.
+        ///
+        /// # Example
+        ///
+        /// ```rhai
+        /// let x = [1, 2, 3, 4, 5];
+        ///
+        /// x.shuffle();
+        ///
+        /// print(x);       // prints the numbers 1 to 5 in a random order
+        /// ```
+        #[rhai_fn(return_raw, volatile)]
+        #[allow(clippy::cast_possible_truncation)]
+        pub fn shuffle(ctx: NativeCallContext, array: &mut Array) -> RhaiResultOf<()> {
+            if array.len() <= 1 {
+                return Ok(());
+            }
+
+            with_rng(&ctx, |rng| {
+                for i in (1..array.len()).rev() {
+                    let j = rng.next_below(i as u64 + 1) as usize;
+                    array.swap(i, j);
+                }
+            })
+        }
+        /// Return an array of `n` elements picked randomly from the array, without repetition.
+        ///
+        /// * If `n` ≤ 0, an empty array is returned.
+        /// * If `n` ≥ length of array, all elements are returned in a random order.
+        ///
+        /// # Example
+        ///
+        /// ```rhai
+        /// let x = [1, 2, 3, 4, 5];
+        ///
+        /// let y = x.sample(2);
+        ///
+        /// print(y);       // prints two different numbers between 1 and 5
+        ///
+        /// print(x);       // prints "[1, 2, 3, 4, 5]"
+        /// ```
+        #[rhai_fn(return_raw, volatile, pure)]
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        pub fn sample(ctx: NativeCallContext, array: &mut Array, n: INT) -> RhaiResultOf<Array> {
+            if n <= 0 || array.is_empty() {
+                return Ok(Array::new());
+            }
+
+            let n = if n > MAX_USIZE_INT {
+                array.len()
+            } else {
+                (n as usize).min(array.len())
+            };
+            let mut indices: Vec<_> = (0..array.len()).collect();
+
+            with_rng(&ctx, |rng| {
+                // Partial Fisher-Yates shuffle of the first `n` indices
+                for i in 0..n {
+                    let j = i + rng.next_below((indices.len() - i) as u64) as usize;
+                    indices.swap(i, j);
+                }
+            })?;
+
+            Ok(indices[..n].iter().map(|&i| array[i].clone()).collect())
+        }
+        /// Return a BLOB of `n` random bytes.
+        ///
+        /// # Example
+        ///
+        /// ```rhai
+        /// let b = rand_blob(16);
+        ///
+        /// print(b.len());     // prints 16
+        /// ```
+        #[rhai_fn(return_raw, volatile)]
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        pub fn rand_blob(ctx: NativeCallContext, n: INT) -> RhaiResultOf<Blob> {
+            if n <= 0 {
+                return Ok(Blob::new());
+            }
+
+            if n > MAX_USIZE_INT {
+                return Err(crate::ERR::ErrorDataTooLarge(
+                    "Size of BLOB".to_string(),
+                    crate::Position::NONE,
+                )
+                .into());
+            }
+
+            let n = n as usize;
+
+            // Check if blob will be over max size limit
+            #[cfg(not(feature = "unchecked"))]
+            if ctx.engine().max_array_size() > 0 && n > ctx.engine().max_array_size() {
+                return Err(crate::ERR::ErrorDataTooLarge(
+                    "Size of BLOB".to_string(),
+                    crate::Position::NONE,
+                )
+                .into());
+            }
+
+            let mut blob = Blob::with_capacity(n);
+
+            with_rng(&ctx, |rng| {
+                while blob.len() < n {
+                    let bytes = rng.next_u64().to_le_bytes();
+                    let len = bytes.len().min(n - blob.len());
+                    blob.extend_from_slice(&bytes[..len]);
+                }
+            })?;
+
+            Ok(blob)
+        }
+    }
+}
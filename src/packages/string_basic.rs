@@ -1,10 +1,11 @@
 use super::iter_basic::CharsStream;
 use crate::plugin::*;
-use crate::{def_package, FnPtr, ImmutableString, SmartString, INT, MAX_USIZE_INT};
+use crate::{def_package, FnPtr, ImmutableString, RhaiResultOf, SmartString, INT, MAX_USIZE_INT};
 use std::any::TypeId;
 use std::fmt::{Binary, LowerHex, Octal, Write};
 #[cfg(feature = "no_std")]
 use std::prelude::v1::*;
+use std::{iter::Peekable, str::Chars};
 
 #[cfg(not(feature = "no_index"))]
 use crate::Array;
@@ -29,6 +30,7 @@ def_package! {
 
         combine_with_exported_module!(lib, "print_debug", print_debug_functions);
         combine_with_exported_module!(lib, "number_formatting", number_formatting);
+        combine_with_exported_module!(lib, "string_formatting", string_formatting);
         combine_with_exported_module!(lib, "char", char_functions);
 
         // Register characters iterator
@@ -61,6 +63,327 @@ pub fn print_with_func(
     }
 }
 
+/// An argument referenced by a placeholder in a format string.
+#[derive(Debug, Clone, Copy)]
+enum FormatArg<'a> {
+    /// Positional argument.
+    Index(usize),
+    /// Named argument.
+    Name(&'a str),
+}
+
+/// The format specifier of a placeholder in a format string.
+#[derive(Debug, Clone, Copy, Default)]
+struct FormatSpec {
+    /// Character used for padding.
+    fill: char,
+    /// Alignment: `<`, `^` or `>`.
+    align: Option<char>,
+    /// Always show the sign of numbers?
+    plus: bool,
+    /// Prefix numbers in hex, octal or binary format with `0x`, `0o` or `0b`?
+    alternate: bool,
+    /// Pad numbers with zeros after the sign?
+    zero: bool,
+    /// Minimum width, in characters.
+    width: usize,
+    /// Number of decimal places for floating-point numbers, or maximum number of characters.
+    precision: Option<usize>,
+    /// Format type: `?`, `x`, `X`, `o`, `b`, `e` or `E`.
+    kind: Option<char>,
+}
+
+impl FormatSpec {
+    /// Parse a format specifier in the form `[[fill]align][+][#][0][width][.precision][type]`.
+    fn parse(spec: &str) -> Option<Self> {
+        fn parse_number(chars: &mut Peekable<Chars>) -> Option<usize> {
+            let mut number = None;
+
+            while let Some(digit) = chars.peek().and_then(|c| c.to_digit(10)) {
+                chars.next();
+                number = Some(
+                    number
+                        .unwrap_or(0_usize)
+                        .checked_mul(10)?
+                        .checked_add(digit as usize)?,
+                );
+            }
+
+            number
+        }
+
+        let mut result = Self {
+            fill: ' ',
+            ..Self::default()
+        };
+        let mut chars = spec.chars().peekable();
+
+        let mut lookahead = spec.chars();
+        match (lookahead.next(), lookahead.next()) {
+            (Some(fill), Some(align @ ('<' | '^' | '>'))) => {
+                result.fill = fill;
+                result.align = Some(align);
+                chars.nth(1);
+            }
+            (Some(align @ ('<' | '^' | '>')), _) => {
+                result.align = Some(align);
+                chars.next();
+            }
+            _ => (),
+        }
+
+        result.plus = chars.next_if_eq(&'+').is_some();
+        result.alternate = chars.next_if_eq(&'#').is_some();
+        result.zero = chars.next_if_eq(&'0').is_some();
+        result.width = parse_number(&mut chars).unwrap_or(0);
+
+        if chars.next_if_eq(&'.').is_some() {
+            result.precision = Some(parse_number(&mut chars)?);
+        }
+
+        result.kind = chars.next_if(|c| matches!(c, '?' | 'x' | 'X' | 'o' | 'b' | 'e' | 'E'));
+
+        chars.next().is_none().then_some(result)
+    }
+}
+
+/// Format a value according to a format specifier.
+///
+/// Returns the reason if the format specifier is not supported for the value.
+fn format_value(
+    ctx: &NativeCallContext,
+    value: &mut Dynamic,
+    spec: &FormatSpec,
+) -> Result<SmartString, String> {
+    let unsupported = |kind: char, value: &Dynamic| {
+        format!(
+            "format type '{kind}' is not supported for {}",
+            ctx.engine().map_type_name(value.type_name())
+        )
+    };
+
+    let mut buf = SmartString::new_const();
+    let mut numeric = true;
+
+    if let Ok(n) = value.as_int() {
+        match (spec.kind, spec.alternate) {
+            (None | Some('?'), _) => write!(buf, "{n}"),
+            (Some('x'), false) => write!(buf, "{n:x}"),
+            (Some('x'), true) => write!(buf, "{n:#x}"),
+            (Some('X'), false) => write!(buf, "{n:X}"),
+            (Some('X'), true) => write!(buf, "{n:#X}"),
+            (Some('o'), false) => write!(buf, "{n:o}"),
+            (Some('o'), true) => write!(buf, "{n:#o}"),
+            (Some('b'), false) => write!(buf, "{n:b}"),
+            (Some('b'), true) => write!(buf, "{n:#b}"),
+            (Some(kind), _) => return Err(unsupported(kind, value)),
+        }
+        .unwrap();
+    } else if let Some(result) = format_float(&mut buf, value, spec) {
+        result.map_err(|kind| unsupported(kind, value))?;
+    } else {
+        numeric = false;
+
+        buf = match spec.kind {
+            None => print_with_func(FUNC_TO_STRING, ctx, value).as_str().into(),
+            Some('?') => print_with_func(FUNC_TO_DEBUG, ctx, value).as_str().into(),
+            Some(kind) => return Err(unsupported(kind, value)),
+        };
+
+        if let Some(precision) = spec.precision {
+            if let Some((offset, _)) = buf.char_indices().nth(precision) {
+                buf.truncate(offset);
+            }
+        }
+    }
+
+    if numeric && spec.plus && !buf.starts_with('-') {
+        buf.insert(0, '+');
+    }
+
+    let len = buf.chars().count();
+
+    if spec.width <= len {
+        return Ok(buf);
+    }
+
+    let padding = spec.width - len;
+
+    if numeric && spec.zero && spec.align.is_none() {
+        // Pad with zeros after the sign and the prefix
+        let mut offset = usize::from(buf.starts_with(['+', '-']));
+        if spec.alternate && matches!(spec.kind, Some('x' | 'X' | 'o' | 'b')) {
+            offset += 2;
+        }
+        buf.insert_str(offset, &"0".repeat(padding));
+        return Ok(buf);
+    }
+
+    let (left, right) = match spec.align.unwrap_or(if numeric { '>' } else { '<' }) {
+        '<' => (0, padding),
+        '^' => (padding / 2, padding - padding / 2),
+        _ => (padding, 0),
+    };
+
+    let mut result = SmartString::new_const();
+    result.extend(std::iter::repeat(spec.fill).take(left));
+    result.push_str(&buf);
+    result.extend(std::iter::repeat(spec.fill).take(right));
+    Ok(result)
+}
+
+/// Format a floating-point number according to a format specifier.
+///
+/// Returns `None` if the value is not a floating-point number, or `Some(Err(kind))` if the format
+/// type is not supported.
+#[allow(unused_variables)]
+fn format_float(
+    buf: &mut SmartString,
+    value: &Dynamic,
+    spec: &FormatSpec,
+) -> Option<Result<(), char>> {
+    #[cfg(not(feature = "no_float"))]
+    if let Ok(f) = value.as_float() {
+        match (spec.kind, spec.precision) {
+            (None, None) => write!(buf, "{}", crate::types::FloatWrapper::new(f)),
+            (Some('?'), None) => write!(buf, "{:?}", crate::types::FloatWrapper::new(f)),
+            (None | Some('?'), Some(p)) => write!(buf, "{f:.p$}"),
+            (Some('e'), None) => write!(buf, "{f:e}"),
+            (Some('e'), Some(p)) => write!(buf, "{f:.p$e}"),
+            (Some('E'), None) => write!(buf, "{f:E}"),
+            (Some('E'), Some(p)) => write!(buf, "{f:.p$E}"),
+            (Some(kind), _) => return Some(Err(kind)),
+        }
+        .unwrap();
+
+        return Some(Ok(()));
+    }
+
+    #[cfg(feature = "decimal")]
+    if let Ok(d) = value.as_decimal() {
+        match (spec.kind, spec.precision) {
+            (None | Some('?'), None) => write!(buf, "{d}"),
+            (None | Some('?'), Some(p)) => write!(buf, "{d:.p$}"),
+            (Some(kind), _) => return Some(Err(kind)),
+        }
+        .unwrap();
+
+        return Some(Ok(()));
+    }
+
+    None
+}
+
+/// Format arguments according to a format string.
+fn format_with_args(
+    ctx: &NativeCallContext,
+    template: &str,
+    mut get_arg: impl FnMut(FormatArg) -> Option<Dynamic>,
+) -> RhaiResultOf<ImmutableString> {
+    let error = |reason: String| {
+        crate::ERR::ErrorArithmetic(
+            format!("Invalid format string '{template}': {reason}"),
+            crate::Position::NONE,
+        )
+    };
+
+    let mut result = SmartString::new_const();
+    let mut next_index = 0;
+    let mut rest = template;
+
+    while let Some(pos) = rest.find(['{', '}']) {
+        result.push_str(&rest[..pos]);
+
+        let brace = if rest[pos..].starts_with('{') {
+            '{'
+        } else {
+            '}'
+        };
+        rest = &rest[pos + 1..];
+
+        // `{{` and `}}` are escaped braces
+        if rest.starts_with(brace) {
+            result.push(brace);
+            rest = &rest[1..];
+            continue;
+        }
+        if brace == '}' {
+            return Err(error("unmatched '}'".into()).into());
+        }
+
+        let end = rest
+            .find('}')
+            .ok_or_else(|| error("unmatched '{'".into()))?;
+        let field = &rest[..end];
+        rest = &rest[end + 1..];
+
+        let (name, spec) = field.split_once(':').unwrap_or((field, ""));
+        let spec = FormatSpec::parse(spec)
+            .ok_or_else(|| error(format!("invalid format specifier '{spec}'")))?;
+
+        // Check if string will be over max size limit
+        #[cfg(not(feature = "unchecked"))]
+        if ctx.engine().max_string_size() > 0
+            && spec.width.max(spec.precision.unwrap_or(0)) > ctx.engine().max_string_size()
+        {
+            return Err(crate::ERR::ErrorDataTooLarge(
+                "Length of string".to_string(),
+                crate::Position::NONE,
+            )
+            .into());
+        }
+
+        let name = name.trim();
+        let arg = if name.is_empty() {
+            next_index += 1;
+            FormatArg::Index(next_index - 1)
+        } else if let Ok(index) = name.parse::<usize>() {
+            FormatArg::Index(index)
+        } else {
+            FormatArg::Name(name)
+        };
+
+        let mut value = get_arg(arg).ok_or_else(|| {
+            error(match arg {
+                FormatArg::Index(index) => format!("no argument at position {index}"),
+                FormatArg::Name(name) => format!("no argument named '{name}'"),
+            })
+        })?;
+
+        result.push_str(&format_value(ctx, &mut value, &spec).map_err(error)?);
+    }
+
+    result.push_str(rest);
+    Ok(result.into())
+}
+
+/// Look up positional arguments in a list.
+fn positional_args(args: &[Dynamic]) -> impl FnMut(FormatArg) -> Option<Dynamic> + '_ {
+    move |arg| match arg {
+        FormatArg::Index(index) => args.get(index).cloned(),
+        FormatArg::Name(_) => None,
+    }
+}
+
+/// Check the number of decimal places requested when printing a number.
+#[allow(dead_code, clippy::cast_sign_loss, clippy::cast_possible_truncation)]
+fn check_precision(ctx: &NativeCallContext, precision: INT) -> RhaiResultOf<usize> {
+    let precision = precision.clamp(0, MAX_USIZE_INT) as usize;
+    let _ctx = ctx;
+
+    // Check if string will be over max size limit
+    #[cfg(not(feature = "unchecked"))]
+    if _ctx.engine().max_string_size() > 0 && precision > _ctx.engine().max_string_size() {
+        return Err(crate::ERR::ErrorDataTooLarge(
+            "Length of string".to_string(),
+            crate::Position::NONE,
+        )
+        .into());
+    }
+
+    Ok(precision)
+}
+
 #[export_module]
 mod print_debug_functions {
     /// Convert the value of the `item` into a string.
@@ -174,6 +497,75 @@ mod print_debug_functions {
         write!(&mut buf, "{}", crate::types::FloatWrapper::new(number)).unwrap();
         buf.into()
     }
+    /// Convert the value of `number` into a string with `precision` decimal places.
+    ///
+    /// If `precision` < 0, no decimal places are shown.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let x = 3.14159;
+    ///
+    /// print(x.to_string(2));      // prints "3.14"
+    /// ```
+    #[cfg(not(feature = "no_float"))]
+    #[rhai_fn(name = "to_string", return_raw)]
+    pub fn print_f64_with_precision(
+        ctx: NativeCallContext,
+        number: f64,
+        precision: INT,
+    ) -> RhaiResultOf<ImmutableString> {
+        let precision = check_precision(&ctx, precision)?;
+        let mut buf = SmartString::new_const();
+        write!(&mut buf, "{number:.precision$}").unwrap();
+        Ok(buf.into())
+    }
+    /// Convert the value of `number` into a string with `precision` decimal places.
+    ///
+    /// If `precision` < 0, no decimal places are shown.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let x = 3.14159;
+    ///
+    /// print(x.to_string(2));      // prints "3.14"
+    /// ```
+    #[cfg(not(feature = "no_float"))]
+    #[rhai_fn(name = "to_string", return_raw)]
+    pub fn print_f32_with_precision(
+        ctx: NativeCallContext,
+        number: f32,
+        precision: INT,
+    ) -> RhaiResultOf<ImmutableString> {
+        let precision = check_precision(&ctx, precision)?;
+        let mut buf = SmartString::new_const();
+        write!(&mut buf, "{number:.precision$}").unwrap();
+        Ok(buf.into())
+    }
+    /// Convert the value of the decimal `number` into a string with `precision` decimal places.
+    ///
+    /// If `precision` < 0, no decimal places are shown.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let x = to_decimal(3.14159);
+    ///
+    /// print(x.to_string(2));      // prints "3.14"
+    /// ```
+    #[cfg(feature = "decimal")]
+    #[rhai_fn(name = "to_string", return_raw)]
+    pub fn print_decimal_with_precision(
+        ctx: NativeCallContext,
+        number: rust_decimal::Decimal,
+        precision: INT,
+    ) -> RhaiResultOf<ImmutableString> {
+        let precision = check_precision(&ctx, precision)?;
+        let mut buf = SmartString::new_const();
+        write!(&mut buf, "{number:.precision$}").unwrap();
+        Ok(buf.into())
+    }
     /// Convert the value of `number` into a string.
     #[cfg(not(feature = "no_float"))]
     #[rhai_fn(name = "debug", name = "to_debug")]
@@ -440,6 +832,152 @@ mod number_formatting {
     }
 }
 
+#[export_module]
+mod string_formatting {
+    /// Format the `format` string without any values, replacing `{{` and `}}` with literal braces.
+    ///
+    /// An error is raised if `format` contains any placeholder.
+    ///
+    /// See `format(format, value)` for the syntax of `format`.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// print(format("{{hello}}"));     // prints "{hello}"
+    /// ```
+    #[rhai_fn(name = "format", return_raw)]
+    pub fn format_0(ctx: NativeCallContext, format: &str) -> RhaiResultOf<ImmutableString> {
+        format_with_args(&ctx, format, positional_args(&[]))
+    }
+    /// Format the `value` according to the `format` string.
+    ///
+    /// Placeholders in `format` are enclosed in braces in the form `{[position][:specifier]}`,
+    /// where the specifier is `[[fill]align][+][#][0][width][.precision][type]`:
+    ///
+    /// * `align` is `<` (left), `^` (center) or `>` (right), padded with `fill` (default space)
+    /// * `+` always shows the sign of numbers
+    /// * `#` prefixes numbers in hex, octal or binary format with `0x`, `0o` or `0b`
+    /// * `0` pads numbers with zeros after the sign
+    /// * `precision` is the number of decimal places of floating-point numbers, or the maximum
+    ///   number of characters of other values
+    /// * `type` is `?` (debug format), `x`/`X` (hex), `o` (octal), `b` (binary) or `e`/`E`
+    ///   (scientific notation)
+    ///
+    /// Use `{{` and `}}` for literal braces.
+    ///
+    /// Values of custom types are converted via their `to_string` or `to_debug` functions.
+    ///
+    /// Up to three values can be passed directly to `format`. To format more values, pass them in
+    /// an array to `format_args` instead. Arrays and object maps passed to `format` are formatted
+    /// as single values.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// print(format("[{:>8.2}]", 3.14159));    // prints "[    3.14]"
+    ///
+    /// print(format("{:#06x}", 255));          // prints "0x00ff"
+    /// ```
+    #[rhai_fn(name = "format", return_raw)]
+    pub fn format_1(
+        ctx: NativeCallContext,
+        format: &str,
+        value: Dynamic,
+    ) -> RhaiResultOf<ImmutableString> {
+        format_with_args(&ctx, format, positional_args(&[value]))
+    }
+    /// Format the values according to the `format` string.
+    ///
+    /// Placeholders without positions take the values in order.
+    ///
+    /// See `format(format, value)` for the syntax of `format`.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// print(format("{1}, {0}!", "world", "hello"));   // prints "hello, world!"
+    /// ```
+    #[rhai_fn(name = "format", return_raw)]
+    pub fn format_2(
+        ctx: NativeCallContext,
+        format: &str,
+        value1: Dynamic,
+        value2: Dynamic,
+    ) -> RhaiResultOf<ImmutableString> {
+        format_with_args(&ctx, format, positional_args(&[value1, value2]))
+    }
+    /// Format the values according to the `format` string.
+    ///
+    /// Placeholders without positions take the values in order.
+    ///
+    /// See `format(format, value)` for the syntax of `format`.
+    ///
+    /// To format more than three values, pass them in an array, e.g. `format_args(format, [a, b, c, d])`.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// print(format("{2}-{1:02}-{0:02}", 5, 1, 2024));     // prints "2024-01-05"
+    /// ```
+    #[rhai_fn(name = "format", return_raw)]
+    pub fn format_3(
+        ctx: NativeCallContext,
+        format: &str,
+        value1: Dynamic,
+        value2: Dynamic,
+        value3: Dynamic,
+    ) -> RhaiResultOf<ImmutableString> {
+        format_with_args(&ctx, format, positional_args(&[value1, value2, value3]))
+    }
+    /// Format the items in the `values` array according to the `format` string.
+    ///
+    /// Placeholders without positions take the items in order.
+    ///
+    /// See `format(format, value)` for the syntax of `format`.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let values = [1, "hello", 3.5, true];
+    ///
+    /// print(format_args("{:<3}|{:^9}|{:+}|{}", values));     // prints "1  |  hello  |+3.5|true"
+    /// ```
+    #[cfg(not(feature = "no_index"))]
+    #[rhai_fn(name = "format_args", return_raw)]
+    pub fn format_array(
+        ctx: NativeCallContext,
+        format: &str,
+        values: Array,
+    ) -> RhaiResultOf<ImmutableString> {
+        format_with_args(&ctx, format, positional_args(&values))
+    }
+    /// Format the properties of the `values` object map according to the `format` string.
+    ///
+    /// Placeholders refer to properties by name, e.g. `{name:>10}`.
+    ///
+    /// See `format(format, value)` for the syntax of `format`.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let values = #{ name: "Bob", score: 97.56 };
+    ///
+    /// print(format_named("{name}: {score:.1}", values));     // prints "Bob: 97.6"
+    /// ```
+    #[cfg(not(feature = "no_object"))]
+    #[rhai_fn(name = "format_named", return_raw)]
+    pub fn format_map(
+        ctx: NativeCallContext,
+        format: &str,
+        values: Map,
+    ) -> RhaiResultOf<ImmutableString> {
+        format_with_args(&ctx, format, |arg| match arg {
+            FormatArg::Name(name) => values.get(name).cloned(),
+            FormatArg::Index(_) => None,
+        })
+    }
+}
+
 #[export_module]
 mod char_functions {
     /// Convert the Unicode character into a 32-bit integer value.
@@ -0,0 +1,340 @@
+#![cfg(feature = "regex")]
+
+use crate::func::locked_write;
+use crate::plugin::*;
+use crate::{def_package, Dynamic, ImmutableString, NativeCallContext, RhaiResultOf};
+use regex::{Regex, RegexBuilder};
+use std::collections::BTreeMap;
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+
+#[cfg(not(feature = "no_index"))]
+use crate::Array;
+
+#[cfg(not(feature = "no_object"))]
+use crate::Map;
+
+/// Maximum number of compiled regular expressions cached per [`Engine`][crate::Engine].
+pub const MAX_REGEX_CACHED: usize = 64;
+
+/// Maximum size (in bytes) of a compiled regular expression.
+///
+/// It is further limited by [`Engine::max_memory`][crate::Engine::max_memory], if set.
+#[cfg(not(feature = "unchecked"))]
+pub const MAX_REGEX_SIZE: usize = 10 * (1 << 20);
+
+/// Maximum size (in bytes) of the lazy DFA cache of a compiled regular expression.
+///
+/// It is further limited by [`Engine::max_memory`][crate::Engine::max_memory], if set.
+#[cfg(not(feature = "unchecked"))]
+pub const MAX_REGEX_DFA_SIZE: usize = 2 * (1 << 20);
+
+/// Cache of compiled regular expressions, keyed by pattern.
+///
+/// When the cache is full, the least recently used regular expression is evicted.
+#[derive(Debug, Clone, Default)]
+pub struct RegexCache {
+    /// Compiled regular expressions, with the time of last use.
+    regexes: BTreeMap<ImmutableString, (Regex, u64)>,
+    /// Counter incremented on each use of the cache.
+    clock: u64,
+}
+
+impl RegexCache {
+    /// Create a new [`RegexCache`].
+    #[inline(always)]
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            regexes: BTreeMap::new(),
+            clock: 0,
+        }
+    }
+    /// Get the compiled regular expression for a `pattern`, if cached, marking it as used.
+    #[inline]
+    pub fn get(&mut self, pattern: &str) -> Option<Regex> {
+        self.clock += 1;
+        let clock = self.clock;
+
+        self.regexes.get_mut(pattern).map(|(regex, used)| {
+            *used = clock;
+            regex.clone()
+        })
+    }
+    /// Add a compiled regular expression for a `pattern`, evicting the least recently used one
+    /// if the cache is full.
+    pub fn insert(&mut self, pattern: ImmutableString, regex: Regex) {
+        if self.regexes.len() >= MAX_REGEX_CACHED {
+            let oldest = self
+                .regexes
+                .iter()
+                .min_by_key(|(.., (.., used))| *used)
+                .map(|(p, ..)| p.clone());
+
+            if let Some(p) = oldest {
+                self.regexes.remove(&p);
+            }
+        }
+
+        self.clock += 1;
+        self.regexes.insert(pattern, (regex, self.clock));
+    }
+}
+
+def_package! {
+    /// Package of string utilities based on regular expressions.
+    ///
+    /// Compiled regular expressions are cached in the [`Engine`][crate::Engine].
+    /// The cache is created by [`Engine::new`][crate::Engine::new], or when this package is registered via
+    /// [`Package::register_into_engine`][super::Package::register_into_engine].
+    /// Without it (e.g. for an [`Engine::new_raw`][crate::Engine::new_raw] with this package registered via
+    /// [`Engine::register_global_module`][crate::Engine::register_global_module]), each function
+    /// call compiles its regular expression again.
+    pub RegexPackage(lib) {
+        lib.set_standard_lib(true);
+
+        combine_with_exported_module!(lib, "regex", regex_functions);
+    }
+    |> |engine| {
+        engine.regex_cache.get_or_insert_with(|| RegexCache::new().into());
+    }
+}
+
+/// Get the compiled regular expression for a `pattern`, compiling and caching it if necessary.
+fn get_regex(ctx: &NativeCallContext, pattern: &ImmutableString) -> RhaiResultOf<Regex> {
+    let engine = ctx.engine();
+
+    let cache = engine.regex_cache.as_ref();
+
+    if let Some(regex) = cache
+        .and_then(locked_write)
+        .and_then(|mut c| c.get(pattern))
+    {
+        return Ok(regex);
+    }
+
+    #[allow(unused_mut)]
+    let mut builder = RegexBuilder::new(pattern);
+
+    // Limit the size and nesting of the compiled regular expression
+    #[cfg(not(feature = "unchecked"))]
+    {
+        use std::convert::TryFrom;
+
+        if engine.max_memory() > 0 {
+            builder
+                .size_limit(engine.max_memory().min(MAX_REGEX_SIZE))
+                .dfa_size_limit(engine.max_memory().min(MAX_REGEX_DFA_SIZE));
+        }
+        if engine.max_expr_depth() > 0 {
+            builder.nest_limit(u32::try_from(engine.max_expr_depth()).unwrap_or(u32::MAX));
+        }
+    }
+
+    let regex = builder.build().map_err(|err| match err {
+        regex::Error::CompiledTooBig(..) => crate::ERR::ErrorDataTooLarge(
+            "Size of regular expression".to_string(),
+            crate::Position::NONE,
+        ),
+        _ => crate::ERR::ErrorArithmetic(
+            format!("Invalid regular expression '{pattern}': {err}"),
+            crate::Position::NONE,
+        ),
+    })?;
+
+    if let Some(mut cache) = cache.and_then(locked_write) {
+        cache.insert(pattern.clone(), regex.clone());
+    }
+
+    Ok(regex)
+}
+
+#[export_module]
+mod regex_functions {
+    /// Return `true` if any part of the string matches the regular expression `pattern`.
+    ///
+    /// Use `^` and `$` in `pattern` to match the entire string.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let text = "hello, world!";
+    ///
+    /// print(text.matches("w.r"));         // prints true
+    ///
+    /// print(text.matches("^w.r"));        // prints false
+    /// ```
+    #[rhai_fn(return_raw)]
+    pub fn matches(
+        ctx: NativeCallContext,
+        string: &str,
+        pattern: ImmutableString,
+    ) -> RhaiResultOf<bool> {
+        Ok(get_regex(&ctx, &pattern)?.is_match(string))
+    }
+    /// Find the first match of the regular expression `pattern` in the string and return the matched text.
+    ///
+    /// If there is no match, `()` is returned.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let text = "call 555-1234 or 555-9876";
+    ///
+    /// print(text.find("\\d{3}-\\d{4}"));   // prints "555-1234"
+    ///
+    /// text.find("x+");                    // returns ()
+    /// ```
+    #[rhai_fn(return_raw)]
+    pub fn find(ctx: NativeCallContext, string: &str, pattern: ImmutableString) -> RhaiResult {
+        Ok(get_regex(&ctx, &pattern)?
+            .find(string)
+            .map_or(Dynamic::UNIT, |m| m.as_str().into()))
+    }
+    /// Replace all matches of the regular expression `pattern` in the string with the `replacement` string,
+    /// returning the new string.
+    ///
+    /// Capture groups can be referenced in `replacement` by index (e.g. `$1` or `${1}`) or by name
+    /// (e.g. `${name}`). Use `$$` for a literal `$`.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let text = "2024-01-15";
+    ///
+    /// print(text.replace_regex("(\\d+)-(\\d+)-(\\d+)", "$3/$2/$1"));  // prints "15/01/2024"
+    ///
+    /// print(text.replace_regex("(?<year>\\d{4})", "[${year}]"));      // prints "[2024]-01-15"
+    /// ```
+    #[rhai_fn(return_raw)]
+    pub fn replace_regex(
+        ctx: NativeCallContext,
+        string: ImmutableString,
+        pattern: ImmutableString,
+        replacement: &str,
+    ) -> RhaiResultOf<ImmutableString> {
+        let regex = get_regex(&ctx, &pattern)?;
+
+        Ok(match regex.replace_all(&string, replacement) {
+            std::borrow::Cow::Borrowed(_) => string,
+            std::borrow::Cow::Owned(s) => s.into(),
+        })
+    }
+
+    #[cfg(not(feature = "no_index"))]
+    pub mod arrays {
+        /// Find all non-overlapping matches of the regular expression `pattern` in the string,
+        /// returning an array of the matched text.
+        ///
+        /// # Example
+        ///
+        /// ```rhai
+        /// let text = "call 555-1234 or 555-9876";
+        ///
+        /// print(text.find_all("\\d{3}-\\d{4}"));   // prints ["555-1234", "555-9876"]
+        /// ```
+        #[rhai_fn(return_raw)]
+        pub fn find_all(
+            ctx: NativeCallContext,
+            string: &str,
+            pattern: ImmutableString,
+        ) -> RhaiResultOf<Array> {
+            Ok(get_regex(&ctx, &pattern)?
+                .find_iter(string)
+                .map(|m| m.as_str().into())
+                .collect())
+        }
+        /// Find the first match of the regular expression `pattern` in the string and return an array
+        /// of the capture groups.
+        ///
+        /// The first element is the text of the entire match, followed by each capture group in order.
+        /// Capture groups that did not participate in the match are `()`.
+        ///
+        /// If there is no match, `()` is returned.
+        ///
+        /// # Example
+        ///
+        /// ```rhai
+        /// let text = "version 1.22 released";
+        ///
+        /// print(text.captures("(\\d+)\\.(\\d+)(-\\w+)?"));    // prints ["1.22", "1", "22", ()]
+        /// ```
+        #[rhai_fn(return_raw)]
+        pub fn captures(
+            ctx: NativeCallContext,
+            string: &str,
+            pattern: ImmutableString,
+        ) -> RhaiResult {
+            Ok(get_regex(&ctx, &pattern)?
+                .captures(string)
+                .map_or(Dynamic::UNIT, |caps| {
+                    caps.iter()
+                        .map(|m| m.map_or(Dynamic::UNIT, |m| m.as_str().into()))
+                        .collect::<Array>()
+                        .into()
+                }))
+        }
+        /// Split the string into segments separated by matches of the regular expression `pattern`,
+        /// returning an array of the segments.
+        ///
+        /// # Example
+        ///
+        /// ```rhai
+        /// let text = "one,  two;three";
+        ///
+        /// print(text.split_regex("[,;]\\s*"));    // prints ["one", "two", "three"]
+        /// ```
+        #[rhai_fn(return_raw)]
+        pub fn split_regex(
+            ctx: NativeCallContext,
+            string: &str,
+            pattern: ImmutableString,
+        ) -> RhaiResultOf<Array> {
+            Ok(get_regex(&ctx, &pattern)?
+                .split(string)
+                .map(Into::into)
+                .collect())
+        }
+    }
+
+    #[cfg(not(feature = "no_object"))]
+    pub mod maps {
+        /// Find the first match of the regular expression `pattern` in the string and return an
+        /// object map of the named capture groups.
+        ///
+        /// Named capture groups that did not participate in the match are `()`.
+        ///
+        /// If there is no match, `()` is returned.
+        ///
+        /// # Example
+        ///
+        /// ```rhai
+        /// let text = "from 09:30 to 17:00";
+        ///
+        /// let m = text.named_captures("(?<hour>\\d+):(?<minute>\\d+)");
+        ///
+        /// print(m.hour);      // prints "09"
+        /// print(m.minute);    // prints "30"
+        /// ```
+        #[rhai_fn(return_raw)]
+        pub fn named_captures(
+            ctx: NativeCallContext,
+            string: &str,
+            pattern: ImmutableString,
+        ) -> RhaiResult {
+            let regex = get_regex(&ctx, &pattern)?;
+
+            Ok(regex.captures(string).map_or(Dynamic::UNIT, |caps| {
+                regex
+                    .capture_names()
+                    .flatten()
+                    .map(|name| {
+                        let value = caps.name(name).map_or(Dynamic::UNIT, |m| m.as_str().into());
+                        (name.into(), value)
+                    })
+                    .collect::<Map>()
+                    .into()
+            }))
+        }
+    }
+}
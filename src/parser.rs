@@ -3,7 +3,7 @@
 use crate::api::options::LangOptions;
 use crate::ast::{
     ASTFlags, BinaryExpr, CaseBlocksList, Expr, FlowControl, FnCallExpr, FnCallHashes, Ident,
-    OpAssignment, RangeCase, ScriptFuncDef, Stmt, StmtBlock, StmtBlockContainer,
+    OpAssignment, Pattern, RangeCase, ScriptFuncDef, Stmt, StmtBlock, StmtBlockContainer,
     SwitchCasesCollection,
 };
 use crate::engine::{Precedence, OP_CONTAINS, OP_NOT};
@@ -83,6 +83,10 @@ pub struct ParseState<'a, 't, 'f> {
     /// List of globally-imported [module][crate::Module] names.
     #[cfg(not(feature = "no_module"))]
     pub global_imports: ThinVec<ImmutableString>,
+    /// Errors collected during parsing, if error recovery is turned on.
+    ///
+    /// When [`None`], parsing stops at the first error.
+    pub errors: Option<Vec<ParseError>>,
     /// Unused dummy field.
     #[cfg(feature = "no_function")]
     pub _dummy: &'f (),
@@ -108,6 +112,8 @@ impl fmt::Debug for ParseState<'_, '_, '_> {
         f.field("imports", &self.imports)
             .field("global_imports", &self.global_imports);
 
+        f.field("errors", &self.errors);
+
         f.finish()
     }
 }
@@ -142,6 +148,19 @@ impl<'a, 't, 'f> ParseState<'a, 't, 'f> {
             imports: ThinVec::new(),
             #[cfg(not(feature = "no_module"))]
             global_imports: ThinVec::new(),
+            errors: None,
+        }
+    }
+
+    /// Record a parse error if error recovery is turned on, otherwise return it.
+    #[inline]
+    pub fn recover(&mut self, err: ParseError) -> ParseResult<()> {
+        match self.errors {
+            Some(ref mut errors) => {
+                errors.push(err);
+                Ok(())
+            }
+            None => Err(err),
         }
     }
 
@@ -174,6 +193,82 @@ impl<'a, 't, 'f> ParseState<'a, 't, 'f> {
         (index, hit_barrier)
     }
 
+    /// Declare a variable in the [`ParseState`], optionally exporting it.
+    ///
+    /// If a variable of the same name is already defined in the current block scope, and it has
+    /// not been aliased, it is reused and its offset (see [`find_var`][ParseState::find_var]) is
+    /// returned.
+    pub fn declare_var(
+        &mut self,
+        name: ImmutableString,
+        access: AccessMode,
+        _is_export: bool,
+    ) -> Option<NonZeroUsize> {
+        let (existing, hit_barrier) = self.find_var(&name);
+
+        let existing = if !hit_barrier && existing > 0 {
+            match self.stack.len() - existing {
+                // Variable has been aliased
+                #[cfg(not(feature = "no_module"))]
+                offset if !self.stack.get_entry_by_index(offset).2.is_empty() => None,
+                // Defined in parent block
+                offset if offset < self.frame_pointer => None,
+                offset => Some(offset),
+            }
+        } else {
+            None
+        };
+
+        let idx = if let Some(n) = existing {
+            self.stack.get_mut_by_index(n).set_access_mode(access);
+            Some(NonZeroUsize::new(self.stack.len() - n).unwrap())
+        } else {
+            self.stack.push_entry(name.clone(), access, Dynamic::UNIT);
+            None
+        };
+
+        #[cfg(not(feature = "no_module"))]
+        if _is_export {
+            self.stack.add_alias_by_index(self.stack.len() - 1, name);
+        }
+
+        idx
+    }
+
+    /// Declare all the variables bound by a destructuring [`Pattern`] in the [`ParseState`], in
+    /// the order they are bound.
+    ///
+    /// Unlike [`declare_var`][ParseState::declare_var], an existing variable of the same name is
+    /// never reused.
+    pub fn declare_pattern_vars(
+        &mut self,
+        pattern: &Pattern,
+        access: AccessMode,
+        _is_export: bool,
+    ) {
+        for id in pattern.variables() {
+            self.stack
+                .push_entry(id.name.clone(), access, Dynamic::UNIT);
+
+            #[cfg(not(feature = "no_module"))]
+            if _is_export {
+                self.stack
+                    .add_alias_by_index(self.stack.len() - 1, id.name.clone());
+            }
+        }
+    }
+
+    /// Export a variable in the [`ParseState`] under an alias, if it is defined.
+    #[cfg(not(feature = "no_module"))]
+    pub fn alias_var(&mut self, name: &str, alias: ImmutableString) {
+        let (existing, hit_barrier) = self.find_var(name);
+
+        if !hit_barrier && existing > 0 {
+            self.stack
+                .add_alias_by_index(self.stack.len() - existing, alias);
+        }
+    }
+
     /// Find a module by name in the [`ParseState`], searching in reverse.
     ///
     /// Returns the offset to be deducted from `Stack::len`,
@@ -394,6 +489,98 @@ fn match_token(input: &mut TokenStream, token: &Token) -> (bool, Position) {
     }
 }
 
+/// Parse doc-comments, which must be followed immediately by a function or type definition.
+#[cfg(not(feature = "no_function"))]
+#[cfg(feature = "metadata")]
+fn parse_doc_comments(
+    input: &mut TokenStream,
+    is_global: bool,
+) -> ParseResult<StaticVec<SmartString>> {
+    let mut comments = StaticVec::<SmartString>::new_const();
+    let mut comments_pos = Position::NONE;
+    let mut buf = SmartString::new_const();
+
+    // Handle doc-comments.
+    while let (Token::Comment(ref comment), pos) = input.peek().unwrap() {
+        if comments_pos.is_none() {
+            comments_pos = *pos;
+        }
+
+        debug_assert!(
+            crate::tokenizer::is_doc_comment(comment),
+            "doc-comment expected but gets {:?}",
+            comment
+        );
+
+        if !is_global {
+            return Err(PERR::WrongDocComment.into_err(comments_pos));
+        }
+
+        match input.next().unwrap() {
+            (Token::Comment(comment), pos) => {
+                if comment.contains('\n') {
+                    // Assume block comment
+                    if !buf.is_empty() {
+                        comments.push(buf.clone());
+                        buf.clear();
+                    }
+                    let c = unindent_block_comment(*comment, pos.position().unwrap_or(1) - 1);
+                    comments.push(c.into());
+                } else {
+                    if !buf.is_empty() {
+                        buf.push_str("\n");
+                    }
+                    buf.push_str(&comment);
+                }
+
+                match input.peek().unwrap() {
+                    (Token::Fn | Token::Private, ..) => break,
+                    #[cfg(not(feature = "no_object"))]
+                    (Token::Reserved(s), ..) if &**s == "struct" => break,
+                    (Token::Reserved(s), ..) if &**s == "enum" => break,
+                    (Token::Comment(..), ..) => (),
+                    _ => return Err(PERR::WrongDocComment.into_err(comments_pos)),
+                }
+            }
+            (token, ..) => unreachable!("Token::Comment expected but gets {:?}", token),
+        }
+    }
+
+    if !buf.is_empty() {
+        comments.push(buf);
+    }
+
+    Ok(comments)
+}
+
+/// Skip tokens until the end of the current statement, in order to recover from a parse error.
+///
+/// Stops after a `;` or a `}` that closes a block started within the skipped tokens.
+/// A `}` that closes the enclosing block is not consumed, unless at global level.
+fn skip_to_statement_end(input: &mut TokenStream, is_global: bool) {
+    let mut level = 0_usize;
+
+    loop {
+        match input.peek().unwrap() {
+            (Token::EOF, ..) => return,
+            (Token::SemiColon, ..) if level == 0 => {
+                eat_token(input, &Token::SemiColon);
+                return;
+            }
+            (Token::RightBrace, ..) if level == 0 && !is_global => return,
+            (Token::RightBrace, ..) if level <= 1 => {
+                eat_token(input, &Token::RightBrace);
+                return;
+            }
+            (Token::RightBrace, ..) => level -= 1,
+            (Token::LeftBrace | Token::MapStart, ..) => level += 1,
+            _ => (),
+        }
+
+        input.next().unwrap();
+    }
+}
+
 /// Process a block comment such that it indents properly relative to the start token.
 #[cfg(not(feature = "no_function"))]
 #[cfg(feature = "metadata")]
@@ -442,6 +629,30 @@ fn parse_var_name(input: &mut TokenStream) -> ParseResult<(SmartString, Position
     }
 }
 
+/// Get the variants of a script-defined enum type, sorted by name, together with the number of
+/// values in the payload of each variant.
+///
+/// Returns an empty list if there is no enum type with the specified name.
+#[allow(unused_variables)]
+fn enum_variants(state: &ParseState, type_name: &str) -> StaticVec<(SmartString, usize)> {
+    #[cfg(feature = "no_function")]
+    return StaticVec::new_const();
+
+    #[cfg(not(feature = "no_function"))]
+    {
+        let mut variants: StaticVec<_> = state
+            .lib
+            .values()
+            .filter_map(|f| {
+                let variant = f.name.strip_prefix(type_name)?.strip_prefix("::")?;
+                Some((variant.into(), f.params.len()))
+            })
+            .collect();
+        variants.sort();
+        variants
+    }
+}
+
 /// Optimize the structure of a chained expression where the root expression is another chained expression.
 ///
 /// # Panics
@@ -1099,6 +1310,246 @@ impl Engine {
         Ok(Expr::Map((map, template).into(), settings.pos))
     }
 
+    /// Is the next token the start of a pattern in a `switch` case?
+    #[must_use]
+    fn is_pattern_start(state: &mut ParseState) -> bool {
+        let type_name = match state.input.peek().unwrap().0 {
+            #[cfg(not(feature = "no_index"))]
+            Token::LeftBracket => return true,
+            #[cfg(not(feature = "no_object"))]
+            Token::MapStart => return true,
+            Token::Identifier(ref s) => s.clone(),
+            _ => return false,
+        };
+
+        !enum_variants(state, &type_name).is_empty()
+    }
+
+    /// Parse a pattern in a `switch` case.
+    fn parse_pattern(
+        &self,
+        state: &mut ParseState,
+        settings: ParseSettings,
+    ) -> ParseResult<Pattern> {
+        let settings = settings.level_up()?;
+
+        let (token, pos) = state.input.peek().unwrap();
+        let pos = *pos;
+
+        match token {
+            // _
+            Token::Underscore => {
+                eat_token(state.input, &Token::Underscore);
+                Ok(Pattern::Wildcard(pos))
+            }
+
+            // Type::Variant(pattern, ...) or variable
+            Token::Identifier(..) => {
+                let (name, ..) = parse_var_name(state.input)?;
+                let variants = enum_variants(state, &name);
+
+                if variants.is_empty() {
+                    let name = self.get_interned_string(name);
+                    return Ok(Pattern::Bind(Ident { name, pos }));
+                }
+
+                match state.input.next().unwrap() {
+                    (Token::DoubleColon, ..) => (),
+                    (Token::LexError(err), pos) => return Err(err.into_err(pos)),
+                    (.., pos) => {
+                        return Err(PERR::MissingToken(
+                            Token::DoubleColon.into(),
+                            format!("to specify a variant of type '{name}'"),
+                        )
+                        .into_err(pos))
+                    }
+                }
+
+                let (variant, variant_pos) = parse_var_name(state.input)?;
+
+                let num_values = match variants.iter().find(|(v, ..)| *v == variant) {
+                    Some(&(.., n)) => n,
+                    None => {
+                        return Err(PERR::MalformedPattern(format!(
+                            "Type '{name}' has no variant '{variant}'"
+                        ))
+                        .into_err(variant_pos))
+                    }
+                };
+
+                let mut patterns = StaticVec::new_const();
+
+                if !match_token(state.input, &Token::Unit).0
+                    && match_token(state.input, &Token::LeftParen).0
+                {
+                    loop {
+                        if match_token(state.input, &Token::RightParen).0 {
+                            break;
+                        }
+
+                        patterns.push(self.parse_pattern(state, settings)?);
+
+                        match state.input.next().unwrap() {
+                            (Token::RightParen, ..) => break,
+                            (Token::Comma, ..) => (),
+                            (Token::LexError(err), pos) => return Err(err.into_err(pos)),
+                            (.., pos) => {
+                                return Err(PERR::MissingToken(
+                                    Token::RightParen.into(),
+                                    "to close the values of this variant".into(),
+                                )
+                                .into_err(pos))
+                            }
+                        }
+                    }
+                }
+
+                if patterns.len() != num_values {
+                    return Err(PERR::MalformedPattern(format!(
+                        "Variant {name}::{variant} holds {num_values} value(s), not {}",
+                        patterns.len()
+                    ))
+                    .into_err(variant_pos));
+                }
+
+                let name = self.get_interned_string(name);
+                let variant = self.get_interned_string(variant);
+
+                Ok(Pattern::Variant((name, variant, patterns).into(), pos))
+            }
+
+            // [pattern, ...]
+            #[cfg(not(feature = "no_index"))]
+            Token::LeftBracket => {
+                eat_token(state.input, &Token::LeftBracket);
+
+                let mut patterns = StaticVec::new_const();
+                let mut rest = None;
+
+                loop {
+                    match state.input.peek().unwrap() {
+                        (Token::RightBracket, ..) => {
+                            eat_token(state.input, &Token::RightBracket);
+                            break;
+                        }
+                        // .. ] or ..rest ]
+                        (Token::ExclusiveRange, ..) => {
+                            let pos = eat_token(state.input, &Token::ExclusiveRange);
+                            rest = Some(self.parse_rest_pattern(state, pos));
+                        }
+                        _ => patterns.push(self.parse_pattern(state, settings)?),
+                    }
+
+                    match state.input.next().unwrap() {
+                        (Token::RightBracket, ..) => break,
+                        (Token::Comma, ..) if rest.is_none() => (),
+                        (Token::LexError(err), pos) => return Err(err.into_err(pos)),
+                        (.., pos) => {
+                            return Err(PERR::MissingToken(
+                                Token::RightBracket.into(),
+                                "to end this array pattern".into(),
+                            )
+                            .into_err(pos))
+                        }
+                    }
+                }
+
+                Ok(Pattern::Array((patterns, rest).into(), pos))
+            }
+
+            // #{prop: pattern, ...}
+            #[cfg(not(feature = "no_object"))]
+            Token::MapStart => {
+                eat_token(state.input, &Token::MapStart);
+
+                let mut props = StaticVec::<(Ident, Pattern)>::new_const();
+                let mut rest = None;
+
+                loop {
+                    match state.input.next().unwrap() {
+                        (Token::RightBrace, ..) => break,
+                        // .. } or ..rest }
+                        (Token::ExclusiveRange, pos) => {
+                            rest = Some(self.parse_rest_pattern(state, pos))
+                        }
+                        (Token::Identifier(s) | Token::StringConstant(s), pos) => {
+                            if props.iter().any(|(p, ..)| p.name == *s) {
+                                return Err(PERR::DuplicatedProperty(s.to_string()).into_err(pos));
+                            }
+
+                            let name = self.get_interned_string(*s);
+                            let id = Ident { name, pos };
+
+                            // Shorthand binds the property to a variable of the same name
+                            let pattern = if match_token(state.input, &Token::Colon).0 {
+                                self.parse_pattern(state, settings)?
+                            } else if is_valid_identifier(&id.name) {
+                                Pattern::Bind(id.clone())
+                            } else {
+                                return Err(PERR::MissingToken(
+                                    Token::Colon.into(),
+                                    format!(
+                                        "to follow the property '{}' in this object map pattern",
+                                        id.name
+                                    ),
+                                )
+                                .into_err(pos));
+                            };
+
+                            props.push((id, pattern));
+                        }
+                        (Token::LexError(err), pos) => return Err(err.into_err(pos)),
+                        (.., pos) => return Err(PERR::PropertyExpected.into_err(pos)),
+                    }
+
+                    match state.input.next().unwrap() {
+                        (Token::RightBrace, ..) => break,
+                        (Token::Comma, ..) if rest.is_none() => (),
+                        (Token::LexError(err), pos) => return Err(err.into_err(pos)),
+                        (.., pos) => {
+                            return Err(PERR::MissingToken(
+                                Token::RightBrace.into(),
+                                "to end this object map pattern".into(),
+                            )
+                            .into_err(pos))
+                        }
+                    }
+                }
+
+                Ok(Pattern::Map((props, rest).into(), pos))
+            }
+
+            // literal
+            _ => {
+                let expr = self.parse_unary(state, settings)?;
+
+                match expr.get_literal_value() {
+                    Some(value) => Ok(Pattern::Constant(value.into(), pos)),
+                    None => Err(PERR::MalformedPattern(
+                        "Expecting a literal value, a variable name or '_' in this pattern".into(),
+                    )
+                    .into_err(expr.start_position())),
+                }
+            }
+        }
+    }
+
+    /// Parse the optional variable name following the `..` (at `pos`) at the end of an array or
+    /// object map pattern.
+    #[cfg(any(not(feature = "no_index"), not(feature = "no_object")))]
+    fn parse_rest_pattern(&self, state: &mut ParseState, pos: Position) -> Pattern {
+        match state.input.peek().unwrap() {
+            (Token::Identifier(..), ..) => match state.input.next().unwrap() {
+                (Token::Identifier(s), pos) => {
+                    let name = self.get_interned_string(*s);
+                    Pattern::Bind(Ident { name, pos })
+                }
+                _ => unreachable!("Token::Identifier expected"),
+            },
+            _ => Pattern::Wildcard(pos),
+        }
+    }
+
     /// Parse a switch expression.
     fn parse_switch(&self, state: &mut ParseState, settings: ParseSettings) -> ParseResult<Stmt> {
         // switch ...
@@ -1121,13 +1572,16 @@ impl Engine {
         let mut expressions = FnArgsVec::<BinaryExpr>::new();
         let mut cases = StraightHashMap::<CaseBlocksList>::default();
         let mut ranges = StaticVec::<RangeCase>::new();
+        let mut patterns = StaticVec::<(Pattern, usize)>::new();
         let mut def_case = None;
         let mut def_case_pos = Position::NONE;
 
         loop {
             const MISSING_RBRACE: &str = "to end this switch block";
 
-            let (case_expr_list, condition) = match state.input.peek().unwrap() {
+            let orig_stack_len = state.stack.len();
+
+            let (case_expr_list, case_patterns, condition) = match state.input.peek().unwrap() {
                 (Token::RightBrace, ..) => {
                     eat_token(state.input, &Token::RightBrace);
                     break;
@@ -1149,6 +1603,7 @@ impl Engine {
                     }
 
                     (
+                        StaticVec::new_const(),
                         StaticVec::new_const(),
                         Expr::BoolConstant(true, Position::NONE),
                     )
@@ -1159,17 +1614,29 @@ impl Engine {
 
                 _ => {
                     let mut case_expr_list = StaticVec::new_const();
+                    let mut case_patterns = StaticVec::<Pattern>::new_const();
 
                     loop {
-                        let filter = state.expr_filter;
-                        state.expr_filter = |t| t != &Token::Pipe;
-                        let expr = self.parse_expr(state, settings);
-                        state.expr_filter = filter;
-
-                        match expr {
-                            Ok(expr) => case_expr_list.push(expr),
-                            Err(err) => {
-                                return Err(PERR::ExprExpected("literal".into()).into_err(err.1))
+                        if Self::is_pattern_start(state) {
+                            let pattern = self.parse_pattern(state, settings)?;
+
+                            // Patterns without variables are matched as literal values
+                            match pattern.get_literal_value() {
+                                Some(value) => case_expr_list
+                                    .push(Expr::DynamicConstant(value.into(), pattern.position())),
+                                None => case_patterns.push(pattern),
+                            }
+                        } else {
+                            let filter = state.expr_filter;
+                            state.expr_filter = |t| t != &Token::Pipe;
+                            let expr = self.parse_expr(state, settings);
+                            state.expr_filter = filter;
+
+                            match expr {
+                                Ok(expr) => case_expr_list.push(expr),
+                                Err(err) => {
+                                    return Err(PERR::ExprExpected("literal".into()).into_err(err.1))
+                                }
                             }
                         }
 
@@ -1178,6 +1645,43 @@ impl Engine {
                         }
                     }
 
+                    // All alternatives must bind the same variables
+                    if let Some(pattern) = case_patterns.first() {
+                        let vars = pattern.variables();
+
+                        if let Some(w) = vars.windows(2).find(|w| w[0].name == w[1].name) {
+                            return Err(
+                                PERR::DuplicatedVariable(w[1].name.to_string()).into_err(w[1].pos)
+                            );
+                        }
+
+                        let mismatched = case_patterns.iter().skip(1).find(|p| {
+                            !p.variables()
+                                .iter()
+                                .map(|v| &v.name)
+                                .eq(vars.iter().map(|v| &v.name))
+                        });
+                        let mismatched = match mismatched {
+                            Some(p) => Some(p.position()),
+                            None if !vars.is_empty() => {
+                                case_expr_list.first().map(Expr::start_position)
+                            }
+                            None => None,
+                        };
+
+                        if let Some(pos) = mismatched {
+                            return Err(PERR::MalformedPattern(
+                                "All alternatives in a switch case must bind the same variables"
+                                    .into(),
+                            )
+                            .into_err(pos));
+                        }
+
+                        for var in vars {
+                            state.stack.push(var.name.clone(), ());
+                        }
+                    }
+
                     let condition = if match_token(state.input, &Token::If).0 {
                         ensure_not_statement_expr(state.input, "a boolean")?;
                         let guard = self.parse_expr(state, settings)?.ensure_bool_expr()?;
@@ -1186,7 +1690,7 @@ impl Engine {
                     } else {
                         Expr::BoolConstant(true, Position::NONE)
                     };
-                    (case_expr_list, condition)
+                    (case_expr_list, case_patterns, condition)
                 }
             };
 
@@ -1213,6 +1717,8 @@ impl Engine {
                     (Expr::Stmt(stmt_block.into()), need_comma)
                 };
 
+            state.stack.rewind(orig_stack_len);
+
             expressions.push(BinaryExpr {
                 lhs: condition,
                 rhs: action_expr,
@@ -1220,9 +1726,11 @@ impl Engine {
 
             let index = expressions.len() - 1;
 
-            if case_expr_list.is_empty() {
+            if case_expr_list.is_empty() && case_patterns.is_empty() {
                 def_case = Some(index);
             } else {
+                patterns.extend(case_patterns.into_iter().map(|p| (p, index)));
+
                 for expr in case_expr_list {
                     let value = expr.get_literal_value().ok_or_else(|| {
                         PERR::ExprExpected("a literal".into()).into_err(expr.start_position())
@@ -1293,14 +1801,57 @@ impl Engine {
             }
         }
 
+        // Warn about variants of enum types that are not matched
+        if def_case.is_none() {
+            if let Some(ref on_warning) = self.parse_warning {
+                let mut types = StaticVec::<&ImmutableString>::new_const();
+
+                for (pattern, ..) in &patterns {
+                    if let Pattern::Variant(x, ..) = pattern {
+                        if !types.contains(&&x.0) {
+                            types.push(&x.0);
+                        }
+                    }
+                }
+
+                for type_name in types {
+                    let missing: StaticVec<_> = enum_variants(state, type_name)
+                        .into_iter()
+                        .filter(|(variant, ..)| {
+                            !patterns.iter().any(|(pattern, index)| match pattern {
+                                Pattern::Variant(x, ..) => {
+                                    x.0 == *type_name
+                                        && x.1 == variant.as_str()
+                                        && x.2.iter().all(Pattern::is_irrefutable)
+                                        && matches!(
+                                            expressions[*index].lhs,
+                                            Expr::BoolConstant(true, ..)
+                                        )
+                                }
+                                _ => false,
+                            })
+                        })
+                        .map(|(variant, ..)| format!("{type_name}::{variant}"))
+                        .collect();
+
+                    if !missing.is_empty() {
+                        let msg = format!("Non-exhaustive switch: missing {}", missing.join(", "));
+                        on_warning(&msg, settings.pos);
+                    }
+                }
+            }
+        }
+
         expressions.shrink_to_fit();
         cases.shrink_to_fit();
         ranges.shrink_to_fit();
+        patterns.shrink_to_fit();
 
         let cases = SwitchCasesCollection {
             expressions,
             cases,
             ranges,
+            patterns,
             def_case,
         };
 
@@ -1520,6 +2071,10 @@ impl Engine {
                     token => unreachable!("Token::Identifier expected but gets {:?}", token),
                 };
 
+                let is_enum_variant = state.input.peek().unwrap().0 == Token::DoubleColon
+                    && !options.intersects(ChainingFlags::DISALLOW_NAMESPACES)
+                    && !enum_variants(state, &s).is_empty();
+
                 match state.input.peek().unwrap() {
                     // Function call
                     (Token::LeftParen | Token::Bang | Token::Unit, _) => {
@@ -1535,6 +2090,56 @@ impl Engine {
                             settings.pos,
                         )
                     }
+                    // Enum variant
+                    _ if is_enum_variant => {
+                        eat_token(state.input, &Token::DoubleColon);
+
+                        let (variant, variant_pos) = parse_var_name(state.input)?;
+
+                        if enum_variants(state, &s).iter().all(|(v, ..)| *v != variant) {
+                            return Err(PERR::MissingSymbol(format!(
+                                "Type '{s}' has no variant '{variant}'"
+                            ))
+                            .into_err(variant_pos));
+                        }
+
+                        // Once the identifier consumed we must enable next variables capturing
+                        state.allow_capture = true;
+
+                        let name = self.get_interned_string(format!("{s}::{variant}"));
+
+                        // Unit variants can be constructed without parentheses
+                        let no_args = match state.input.peek().unwrap().0 {
+                            Token::LeftParen => {
+                                eat_token(state.input, &Token::LeftParen);
+                                false
+                            }
+                            Token::Unit => {
+                                eat_token(state.input, &Token::Unit);
+                                true
+                            }
+                            _ => true,
+                        };
+
+                        let mut expr = self.parse_fn_call(
+                            state,
+                            settings,
+                            name,
+                            no_args,
+                            false,
+                            #[cfg(not(feature = "no_module"))]
+                            ns,
+                        )?;
+
+                        // Constructors are script-defined functions even though their names
+                        // are not valid function names
+                        if let Expr::FnCall(ref mut f, ..) = expr {
+                            let hash = calc_fn_hash(None, &f.name, f.args.len());
+                            f.hashes = FnCallHashes::from_hash(hash);
+                        }
+
+                        expr
+                    }
                     // Namespace qualification
                     #[cfg(not(feature = "no_module"))]
                     (token @ Token::DoubleColon, pos) => {
@@ -2731,10 +3336,10 @@ impl Engine {
         let mut settings = settings.level_up_with_position(eat_token(state.input, &Token::For))?;
 
         // for name ...
-        let (name, name_pos, counter_name, counter_pos) =
+        let (name, name_pos, pattern, counter_name, counter_pos) =
             if match_token(state.input, &Token::LeftParen).0 {
                 // ( name, counter )
-                let (name, name_pos) = parse_var_name(state.input)?;
+                let (name, name_pos, pattern) = self.parse_var_or_pattern(state, settings)?;
                 let (has_comma, pos) = match_token(state.input, &Token::Comma);
                 if !has_comma {
                     return Err(PERR::MissingToken(
@@ -2745,7 +3350,11 @@ impl Engine {
                 }
                 let (counter_name, counter_pos) = parse_var_name(state.input)?;
 
-                if counter_name == name {
+                if counter_name == name
+                    || pattern.as_ref().map_or(false, |p| {
+                        p.variables().iter().any(|id| id.name == counter_name)
+                    })
+                {
                     return Err(PERR::DuplicatedVariable(counter_name.into()).into_err(counter_pos));
                 }
 
@@ -2757,11 +3366,11 @@ impl Engine {
                     )
                     .into_err(pos));
                 }
-                (name, name_pos, Some(counter_name), counter_pos)
+                (name, name_pos, pattern, Some(counter_name), counter_pos)
             } else {
                 // name
-                let (name, name_pos) = parse_var_name(state.input)?;
-                (name, name_pos, None, Position::NONE)
+                let (name, name_pos, pattern) = self.parse_var_or_pattern(state, settings)?;
+                (name, name_pos, pattern, None, Position::NONE)
             };
 
         // for name in ...
@@ -2802,8 +3411,22 @@ impl Engine {
             prev_stack_len
         };
 
+        // for [pattern] in expr { let [pattern] = loop_var; body }
+        let destructure = pattern.map(|pattern| {
+            self.make_destructure_stmt(
+                state,
+                pattern,
+                loop_var.name.clone(),
+                NonZeroUsize::new(1).unwrap(),
+            )
+        });
+
         settings.flags |= ParseSettingFlags::BREAKABLE;
-        let body = self.parse_block(state, settings)?.into();
+        let mut body: StmtBlock = self.parse_block(state, settings)?.into();
+
+        if let Some(stmt) = destructure {
+            body.statements_mut().insert(0, stmt);
+        }
 
         state.stack.rewind(prev_stack_len);
 
@@ -2815,26 +3438,93 @@ impl Engine {
         ))
     }
 
-    /// Parse a variable definition statement.
-    fn parse_let(
+    /// Parse a loop variable or a function parameter, which may be a destructuring pattern.
+    ///
+    /// The value destructured by a pattern is held in a hidden variable named after the pattern.
+    fn parse_var_or_pattern(
         &self,
         state: &mut ParseState,
-        mut settings: ParseSettings,
-        access: AccessMode,
-        is_export: bool,
-    ) -> ParseResult<Stmt> {
-        // let/const... (specified in `var_type`)
-        settings.pos = state.input.next().unwrap().1;
+        settings: ParseSettings,
+    ) -> ParseResult<(SmartString, Position, Option<Pattern>)> {
+        if Self::is_destructuring_start(state) {
+            let pattern = self.parse_destructuring_pattern(state, settings)?;
+            Ok((
+                format!("{pattern:?}").into(),
+                pattern.position(),
+                Some(pattern),
+            ))
+        } else {
+            let (name, pos) = parse_var_name(state.input)?;
+            Ok((name, pos, None))
+        }
+    }
 
-        // let name ...
-        let (name, pos) = parse_var_name(state.input)?;
+    /// Make a statement that destructures the value of a hidden variable into the variables of a
+    /// [`Pattern`].
+    ///
+    /// The hidden variable is at the specified offset from the top of the [`ParseState`] stack.
+    fn make_destructure_stmt(
+        &self,
+        state: &mut ParseState,
+        pattern: Pattern,
+        name: ImmutableString,
+        index: NonZeroUsize,
+    ) -> Stmt {
+        let pos = pattern.position();
+        let short_index = u8::try_from(index.get()).ok().and_then(NonZeroU8::new);
+
+        let expr = Expr::Variable(
+            #[cfg(not(feature = "no_module"))]
+            (Some(index), name, crate::ast::Namespace::NONE, 0).into(),
+            #[cfg(feature = "no_module")]
+            (Some(index), name).into(),
+            short_index,
+            pos,
+        );
+
+        state.declare_pattern_vars(&pattern, AccessMode::ReadWrite, false);
+
+        Stmt::Destructure((pattern, expr).into(), ASTFlags::empty(), pos)
+    }
+
+    /// Get an interned type name, mapping `int` and `float` to the actual types.
+    fn get_type_name(&self, name: impl AsRef<str> + Into<ImmutableString>) -> ImmutableString {
+        match name.as_ref() {
+            "int" => self.get_interned_string(std::any::type_name::<crate::INT>()),
+            #[cfg(not(feature = "no_float"))]
+            "float" => self.get_interned_string(std::any::type_name::<crate::FLOAT>()),
+            _ => self.get_interned_string(name),
+        }
+    }
 
-        if !self.allow_shadowing() && state.stack.get(&name).is_some() {
+    /// Parse a type annotation.
+    fn parse_type_annotation(&self, state: &mut ParseState) -> ParseResult<ImmutableString> {
+        match state.input.next().unwrap() {
+            (Token::Identifier(s), ..) => Ok(self.get_type_name(*s)),
+            (Token::Reserved(s), ..) if is_valid_identifier(&s) => Ok(self.get_type_name(*s)),
+            (Token::StringConstant(s), ..) => Ok(self.get_type_name(*s)),
+            (Token::Unit, ..) => Ok(self.get_interned_string("()")),
+            (Token::LexError(err), pos) => Err(err.into_err(pos)),
+            (.., pos) => Err(PERR::MissingSymbol("Expecting a type name".into()).into_err(pos)),
+        }
+    }
+
+    /// Check a variable definition against the shadowing option and the variable definition
+    /// filter, if any.
+    fn check_var_def(
+        &self,
+        state: &mut ParseState,
+        name: &str,
+        pos: Position,
+        access: AccessMode,
+        settings: ParseSettings,
+    ) -> ParseResult<()> {
+        if !self.allow_shadowing() && state.stack.get(name).is_some() {
             return Err(PERR::VariableExists(name.into()).into_err(pos));
         }
 
         if let Some(ref filter) = self.def_var_filter {
-            let will_shadow = state.stack.get(&name).is_some();
+            let will_shadow = state.stack.get(name).is_some();
 
             let global = state
                 .global
@@ -2842,7 +3532,7 @@ impl Engine {
 
             global.level = settings.level;
             let is_const = access == AccessMode::ReadOnly;
-            let info = VarDefInfo::new(&name, is_const, settings.level, will_shadow);
+            let info = VarDefInfo::new(name, is_const, settings.level, will_shadow);
             let caches = &mut Caches::new();
             let context = EvalContext::new(self, global, caches, &mut state.stack, None);
 
@@ -2858,15 +3548,69 @@ impl Engine {
             }
         }
 
-        let name = self.get_interned_string(name);
+        Ok(())
+    }
 
-        // let name = ...
-        let expr = if match_token(state.input, &Token::Equals).0 {
-            // let name = expr
-            self.parse_expr(state, settings.level_up()?)?
-        } else {
-            Expr::Unit(Position::NONE)
-        };
+    /// Is the next token the start of a destructuring pattern?
+    #[must_use]
+    fn is_destructuring_start(state: &mut ParseState) -> bool {
+        match state.input.peek().unwrap().0 {
+            #[cfg(not(feature = "no_index"))]
+            Token::LeftBracket => true,
+            #[cfg(not(feature = "no_object"))]
+            Token::MapStart => true,
+            _ => false,
+        }
+    }
+
+    /// Parse a destructuring pattern, which must not bind the same variable twice.
+    fn parse_destructuring_pattern(
+        &self,
+        state: &mut ParseState,
+        settings: ParseSettings,
+    ) -> ParseResult<Pattern> {
+        let pattern = self.parse_pattern(state, settings)?;
+
+        if let Some(w) = pattern
+            .variables()
+            .windows(2)
+            .find(|w| w[0].name == w[1].name)
+        {
+            return Err(PERR::DuplicatedVariable(w[1].name.to_string()).into_err(w[1].pos));
+        }
+
+        Ok(pattern)
+    }
+
+    /// Parse a destructuring variable definition statement.
+    fn parse_let_pattern(
+        &self,
+        state: &mut ParseState,
+        settings: ParseSettings,
+        access: AccessMode,
+        is_export: bool,
+    ) -> ParseResult<Stmt> {
+        // let [pattern] ...
+        let pattern = self.parse_destructuring_pattern(state, settings)?;
+
+        for id in pattern.variables() {
+            self.check_var_def(state, &id.name, id.pos, access, settings)?;
+        }
+
+        // let [pattern] = ...
+        let (has_equals, pos) = match_token(state.input, &Token::Equals);
+        if !has_equals {
+            return Err(PERR::MissingToken(
+                Token::Equals.into(),
+                "to provide a value to destructure".into(),
+            )
+            .into_err(pos));
+        }
+
+        // let [pattern] = expr
+        let expr = self.parse_expr(state, settings.level_up()?)?;
+
+        state.declare_pattern_vars(&pattern, access, is_export);
 
         let export = if is_export {
             ASTFlags::EXPORTED
@@ -2874,37 +3618,67 @@ impl Engine {
             ASTFlags::empty()
         };
 
-        let (existing, hit_barrier) = state.find_var(&name);
-
-        let existing = if !hit_barrier && existing > 0 {
-            match state.stack.len() - existing {
-                // Variable has been aliased
-                #[cfg(not(feature = "no_module"))]
-                offset if !state.stack.get_entry_by_index(offset).2.is_empty() => None,
-                // Defined in parent block
-                offset if offset < state.frame_pointer => None,
-                offset => Some(offset),
+        Ok(match access {
+            // let [pattern] = expr
+            AccessMode::ReadWrite => {
+                Stmt::Destructure((pattern, expr).into(), export, settings.pos)
             }
+            // const [pattern] = expr
+            AccessMode::ReadOnly => Stmt::Destructure(
+                (pattern, expr).into(),
+                ASTFlags::CONSTANT | export,
+                settings.pos,
+            ),
+        })
+    }
+
+    /// Parse a variable definition statement.
+    fn parse_let(
+        &self,
+        state: &mut ParseState,
+        mut settings: ParseSettings,
+        access: AccessMode,
+        is_export: bool,
+    ) -> ParseResult<Stmt> {
+        // let/const... (specified in `var_type`)
+        settings.pos = state.input.next().unwrap().1;
+
+        // let [pattern] ... or let #{pattern} ...
+        if Self::is_destructuring_start(state) {
+            return self.parse_let_pattern(state, settings, access, is_export);
+        }
+
+        // let name ...
+        let (name, pos) = parse_var_name(state.input)?;
+
+        self.check_var_def(state, &name, pos, access, settings)?;
+
+        let name = self.get_interned_string(name);
+
+        // let name: type ...
+        let typ = if match_token(state.input, &Token::Colon).0 {
+            Some(self.parse_type_annotation(state)?)
         } else {
             None
         };
 
-        let idx = if let Some(n) = existing {
-            state.stack.get_mut_by_index(n).set_access_mode(access);
-            Some(NonZeroUsize::new(state.stack.len() - n).unwrap())
+        // let name = ...
+        let expr = if match_token(state.input, &Token::Equals).0 {
+            // let name = expr
+            self.parse_expr(state, settings.level_up()?)?
         } else {
-            state.stack.push_entry(name.clone(), access, Dynamic::UNIT);
-            None
+            Expr::Unit(Position::NONE)
         };
 
-        #[cfg(not(feature = "no_module"))]
-        if is_export {
-            state
-                .stack
-                .add_alias_by_index(state.stack.len() - 1, name.clone());
-        }
+        let export = if is_export {
+            ASTFlags::EXPORTED
+        } else {
+            ASTFlags::empty()
+        };
+
+        let idx = state.declare_var(name.clone(), access, is_export);
 
-        let var_def = (Ident { name, pos }, expr, idx).into();
+        let var_def = (Ident { name, pos }, expr, idx, typ).into();
 
         Ok(match access {
             // let name = expr
@@ -2978,13 +3752,7 @@ impl Engine {
             (self.get_interned_string(""), Position::NONE)
         };
 
-        let (existing, hit_barrier) = state.find_var(&id);
-
-        if !hit_barrier && existing > 0 {
-            state
-                .stack
-                .add_alias_by_index(state.stack.len() - existing, alias.clone());
-        }
+        state.alias_var(&id, alias.clone());
 
         let export = (
             Ident {
@@ -3047,11 +3815,15 @@ impl Engine {
             match state.input.peek().unwrap() {
                 (Token::RightBrace, ..) => break eat_token(state.input, &Token::RightBrace),
                 (Token::EOF, pos) => {
-                    return Err(PERR::MissingToken(
-                        Token::RightBrace.into(),
-                        "to terminate this block".into(),
-                    )
-                    .into_err(*pos));
+                    let pos = *pos;
+                    state.recover(
+                        PERR::MissingToken(
+                            Token::RightBrace.into(),
+                            "to terminate this block".into(),
+                        )
+                        .into_err(pos),
+                    )?;
+                    break pos;
                 }
                 _ => (),
             }
@@ -3059,7 +3831,14 @@ impl Engine {
             // Parse statements inside the block
             settings.flags.remove(ParseSettingFlags::GLOBAL_LEVEL);
 
-            let stmt = self.parse_stmt(state, settings)?;
+            let stmt = match self.parse_stmt(state, settings) {
+                Ok(stmt) => stmt,
+                Err(err) => {
+                    state.recover(err)?;
+                    skip_to_statement_end(state.input, false);
+                    continue;
+                }
+            };
 
             if stmt.is_noop() {
                 continue;
@@ -3084,15 +3863,20 @@ impl Engine {
                 // { ... { stmt } ???
                 _ if !need_semicolon => (),
                 // { ... stmt <error>
-                (Token::LexError(err), err_pos) => return Err(err.clone().into_err(*err_pos)),
+                (Token::LexError(err), err_pos) => {
+                    let err = err.clone().into_err(*err_pos);
+                    state.recover(err)?;
+                    skip_to_statement_end(state.input, false);
+                }
                 // { ... stmt ???
                 (.., pos) => {
                     // Semicolons are not optional between statements
-                    return Err(PERR::MissingToken(
+                    let err = PERR::MissingToken(
                         Token::SemiColon.into(),
                         "to terminate this statement".into(),
                     )
-                    .into_err(*pos));
+                    .into_err(*pos);
+                    state.recover(err)?;
                 }
             }
         };
@@ -3142,61 +3926,10 @@ impl Engine {
 
         #[cfg(not(feature = "no_function"))]
         #[cfg(feature = "metadata")]
-        let comments = {
-            let mut comments = StaticVec::<SmartString>::new_const();
-            let mut comments_pos = Position::NONE;
-            let mut buf = SmartString::new_const();
-
-            // Handle doc-comments.
-            while let (Token::Comment(ref comment), pos) = state.input.peek().unwrap() {
-                if comments_pos.is_none() {
-                    comments_pos = *pos;
-                }
-
-                debug_assert!(
-                    crate::tokenizer::is_doc_comment(comment),
-                    "doc-comment expected but gets {:?}",
-                    comment
-                );
-
-                if !settings.has_flag(ParseSettingFlags::GLOBAL_LEVEL) {
-                    return Err(PERR::WrongDocComment.into_err(comments_pos));
-                }
-
-                match state.input.next().unwrap() {
-                    (Token::Comment(comment), pos) => {
-                        if comment.contains('\n') {
-                            // Assume block comment
-                            if !buf.is_empty() {
-                                comments.push(buf.clone());
-                                buf.clear();
-                            }
-                            let c =
-                                unindent_block_comment(*comment, pos.position().unwrap_or(1) - 1);
-                            comments.push(c.into());
-                        } else {
-                            if !buf.is_empty() {
-                                buf.push_str("\n");
-                            }
-                            buf.push_str(&comment);
-                        }
-
-                        match state.input.peek().unwrap() {
-                            (Token::Fn | Token::Private, ..) => break,
-                            (Token::Comment(..), ..) => (),
-                            _ => return Err(PERR::WrongDocComment.into_err(comments_pos)),
-                        }
-                    }
-                    (token, ..) => unreachable!("Token::Comment expected but gets {:?}", token),
-                }
-            }
-
-            if !buf.is_empty() {
-                comments.push(buf);
-            }
-
-            comments
-        };
+        let comments = parse_doc_comments(
+            state.input,
+            settings.has_flag(ParseSettingFlags::GLOBAL_LEVEL),
+        )?;
 
         let (token, token_pos) = match state.input.peek().unwrap() {
             (Token::EOF, pos) => return Ok(Stmt::Noop(*pos)),
@@ -3223,92 +3956,50 @@ impl Engine {
 
             #[cfg(not(feature = "no_function"))]
             Token::Fn | Token::Private => {
-                let access = if matches!(token, Token::Private) {
-                    eat_token(state.input, &Token::Private);
-                    crate::FnAccess::Private
-                } else {
-                    crate::FnAccess::Public
-                };
-
-                match state.input.next().unwrap() {
-                    #[cfg(not(feature = "unchecked"))]
-                    (Token::Fn, pos) if state.lib.len() >= self.max_functions() => {
-                        Err(PERR::TooManyFunctions.into_err(pos))
-                    }
-                    (Token::Fn, pos) => {
-                        // Build new parse state
-                        let new_state = &mut ParseState::new(
-                            state.external_constants,
-                            state.input,
-                            state.tokenizer_control.clone(),
-                            state.lib,
-                        );
-
-                        #[cfg(not(feature = "no_module"))]
-                        {
-                            // Do not allow storing an index to a globally-imported module
-                            // just in case the function is separated from this `AST`.
-                            //
-                            // Keep them in `global_imports` instead so that strict variables
-                            // mode will not complain.
-                            new_state.global_imports.clone_from(&state.global_imports);
-                            new_state.global_imports.extend(state.imports.clone());
-                        }
-
-                        // Brand new options
-                        let options = self.options | (settings.options & LangOptions::STRICT_VAR);
-
-                        // Brand new flags, turn on function scope
-                        let flags = ParseSettingFlags::FN_SCOPE
-                            | (settings.flags
-                                & ParseSettingFlags::DISALLOW_UNQUOTED_MAP_PROPERTIES);
-
-                        let new_settings = ParseSettings {
-                            flags,
-                            level: 0,
-                            options,
-                            pos,
-                            #[cfg(not(feature = "unchecked"))]
-                            max_expr_depth: self.max_function_expr_depth(),
-                        };
-
-                        let f = self.parse_fn(
-                            new_state,
-                            new_settings,
-                            access,
-                            #[cfg(feature = "metadata")]
-                            comments,
-                        )?;
-
-                        let hash = calc_fn_hash(None, &f.name, f.params.len());
-
-                        #[cfg(not(feature = "no_object"))]
-                        let hash = f
-                            .this_type
-                            .as_ref()
-                            .map_or(hash, |typ| crate::calc_typed_method_hash(hash, typ));
-
-                        if state.lib.contains_key(&hash) {
-                            return Err(PERR::FnDuplicatedDefinition(
-                                f.name.to_string(),
-                                f.params.len(),
-                            )
-                            .into_err(pos));
-                        }
-
-                        state.lib.insert(hash, f.into());
+                let (f, pos) = self.parse_fn_def(
+                    state,
+                    settings,
+                    #[cfg(feature = "metadata")]
+                    comments,
+                )?;
+                Self::add_fn_def(state, f, pos)?;
+                Ok(Stmt::Noop(pos))
+            }
 
-                        Ok(Stmt::Noop(pos))
-                    }
+            // struct ...
+            #[cfg(not(feature = "no_function"))]
+            #[cfg(not(feature = "no_object"))]
+            Token::Reserved(s)
+                if &**s == "struct" && !settings.has_flag(ParseSettingFlags::GLOBAL_LEVEL) =>
+            {
+                Err(PERR::WrongTypeDefinition.into_err(token_pos))
+            }
 
-                    (.., pos) => Err(PERR::MissingToken(
-                        Token::Fn.into(),
-                        format!("following '{}'", Token::Private),
-                    )
-                    .into_err(pos)),
-                }
+            #[cfg(not(feature = "no_function"))]
+            #[cfg(not(feature = "no_object"))]
+            Token::Reserved(s) if &**s == "struct" => self.parse_struct(
+                state,
+                settings,
+                #[cfg(feature = "metadata")]
+                comments,
+            ),
+
+            // enum ...
+            #[cfg(not(feature = "no_function"))]
+            Token::Reserved(s)
+                if &**s == "enum" && !settings.has_flag(ParseSettingFlags::GLOBAL_LEVEL) =>
+            {
+                Err(PERR::WrongTypeDefinition.into_err(token_pos))
             }
 
+            #[cfg(not(feature = "no_function"))]
+            Token::Reserved(s) if &**s == "enum" => self.parse_enum(
+                state,
+                settings,
+                #[cfg(feature = "metadata")]
+                comments,
+            ),
+
             Token::If => self.parse_if(state, settings.level_up()?),
             Token::Switch => self.parse_switch(state, settings.level_up()?),
             Token::While | Token::Loop if self.allow_looping() => {
@@ -3376,94 +4067,654 @@ impl Engine {
                 }
             }
 
-            Token::Try => self.parse_try_catch(state, settings.level_up()?),
+            Token::Try => self.parse_try_catch(state, settings.level_up()?),
+
+            Token::Let => self.parse_let(state, settings.level_up()?, ReadWrite, false),
+            Token::Const => self.parse_let(state, settings.level_up()?, ReadOnly, false),
+
+            #[cfg(not(feature = "no_module"))]
+            Token::Import => self.parse_import(state, settings.level_up()?),
+
+            #[cfg(not(feature = "no_module"))]
+            Token::Export if !settings.has_flag(ParseSettingFlags::GLOBAL_LEVEL) => {
+                Err(PERR::WrongExport.into_err(token_pos))
+            }
+
+            #[cfg(not(feature = "no_module"))]
+            Token::Export => self.parse_export(state, settings.level_up()?),
+
+            _ => self.parse_expr_stmt(state, settings.level_up()?),
+        }
+    }
+
+    /// Parse a try/catch statement.
+    fn parse_try_catch(
+        &self,
+        state: &mut ParseState,
+        settings: ParseSettings,
+    ) -> ParseResult<Stmt> {
+        // try ...
+        let settings = settings.level_up_with_position(eat_token(state.input, &Token::Try))?;
+
+        // try { try_block }
+        let body = self.parse_block(state, settings)?.into();
+
+        // try { try_block } catch
+        let (matched, catch_pos) = match_token(state.input, &Token::Catch);
+
+        if !matched {
+            return Err(
+                PERR::MissingToken(Token::Catch.into(), "for the 'try' statement".into())
+                    .into_err(catch_pos),
+            );
+        }
+
+        // try { try_block } catch (
+        let catch_var = if match_token(state.input, &Token::LeftParen).0 {
+            let (name, pos) = parse_var_name(state.input)?;
+            let (matched, err_pos) = match_token(state.input, &Token::RightParen);
+
+            if !matched {
+                return Err(PERR::MissingToken(
+                    Token::RightParen.into(),
+                    "to enclose the catch variable".into(),
+                )
+                .into_err(err_pos));
+            }
+
+            let name = self.get_interned_string(name);
+            state.stack.push(name.clone(), ());
+            Ident { name, pos }
+        } else {
+            Ident {
+                name: self.get_interned_string(""),
+                pos: Position::NONE,
+            }
+        };
+
+        // try { try_block } catch ( var ) { catch_block }
+        let branch = self.parse_block(state, settings)?.into();
+
+        let expr = if catch_var.is_empty() {
+            Expr::Unit(catch_var.pos)
+        } else {
+            // Remove the error variable from the stack
+            state.stack.pop();
+
+            Expr::Variable(
+                #[cfg(not(feature = "no_module"))]
+                (None, catch_var.name, <_>::default(), 0).into(),
+                #[cfg(feature = "no_module")]
+                (None, catch_var.name).into(),
+                None,
+                catch_var.pos,
+            )
+        };
+
+        Ok(Stmt::TryCatch(
+            FlowControl { expr, body, branch }.into(),
+            settings.pos,
+        ))
+    }
+
+    /// Parse a function definition, optionally preceded by `private`, in a brand new [`ParseState`].
+    #[cfg(not(feature = "no_function"))]
+    fn parse_fn_def(
+        &self,
+        state: &mut ParseState,
+        settings: ParseSettings,
+        #[cfg(feature = "metadata")] comments: impl IntoIterator<Item = crate::Identifier>,
+    ) -> ParseResult<(ScriptFuncDef, Position)> {
+        let access = if match_token(state.input, &Token::Private).0 {
+            crate::FnAccess::Private
+        } else {
+            crate::FnAccess::Public
+        };
+
+        match state.input.next().unwrap() {
+            #[cfg(not(feature = "unchecked"))]
+            (Token::Fn, pos) if state.lib.len() >= self.max_functions() => {
+                Err(PERR::TooManyFunctions.into_err(pos))
+            }
+            (Token::Fn, pos) => {
+                // Build new parse state
+                let new_state = &mut ParseState::new(
+                    state.external_constants,
+                    state.input,
+                    state.tokenizer_control.clone(),
+                    state.lib,
+                );
+                new_state.errors = state.errors.take();
+
+                #[cfg(not(feature = "no_module"))]
+                {
+                    // Do not allow storing an index to a globally-imported module
+                    // just in case the function is separated from this `AST`.
+                    //
+                    // Keep them in `global_imports` instead so that strict variables
+                    // mode will not complain.
+                    new_state.global_imports.clone_from(&state.global_imports);
+                    new_state.global_imports.extend(state.imports.clone());
+                }
+
+                // Brand new options
+                let options = self.options | (settings.options & LangOptions::STRICT_VAR);
+
+                // Brand new flags, turn on function scope
+                let flags = ParseSettingFlags::FN_SCOPE
+                    | (settings.flags & ParseSettingFlags::DISALLOW_UNQUOTED_MAP_PROPERTIES);
+
+                let new_settings = ParseSettings {
+                    flags,
+                    level: 0,
+                    options,
+                    pos,
+                    #[cfg(not(feature = "unchecked"))]
+                    max_expr_depth: self.max_function_expr_depth(),
+                };
+
+                let f = self.parse_fn(
+                    new_state,
+                    new_settings,
+                    access,
+                    #[cfg(feature = "metadata")]
+                    comments,
+                );
+                state.errors = new_state.errors.take();
+
+                Ok((f?, pos))
+            }
+
+            (.., pos) => Err(PERR::MissingToken(
+                Token::Fn.into(),
+                format!("following '{}'", Token::Private),
+            )
+            .into_err(pos)),
+        }
+    }
+
+    /// Add a function definition to the functions library, checking for duplicates.
+    #[cfg(not(feature = "no_function"))]
+    fn add_fn_def(state: &mut ParseState, f: ScriptFuncDef, pos: Position) -> ParseResult<()> {
+        let hash = calc_fn_hash(None, &f.name, f.params.len());
+
+        #[cfg(not(feature = "no_object"))]
+        let hash = f
+            .this_type
+            .as_ref()
+            .map_or(hash, |typ| crate::calc_typed_method_hash(hash, typ));
+
+        if state.lib.contains_key(&hash) {
+            return Err(
+                PERR::FnDuplicatedDefinition(f.name.to_string(), f.params.len()).into_err(pos),
+            );
+        }
+
+        state.lib.insert(hash, f.into());
+
+        Ok(())
+    }
+
+    /// Parse a record type definition.
+    ///
+    /// The fields become the parameters of a constructor function named after the type, and the
+    /// methods become script-defined functions with the type as the type of `this`.
+    #[cfg(not(feature = "no_function"))]
+    #[cfg(not(feature = "no_object"))]
+    fn parse_struct(
+        &self,
+        state: &mut ParseState,
+        settings: ParseSettings,
+        #[cfg(feature = "metadata")] comments: impl IntoIterator<Item = crate::Identifier>,
+    ) -> ParseResult<Stmt> {
+        // struct ...
+        let (token, struct_pos) = state.input.next().unwrap();
+        debug_assert!(
+            matches!(token, Token::Reserved(ref s) if &**s == "struct"),
+            "Token::Reserved(struct) expected but gets {:?}",
+            token
+        );
+
+        let settings = settings.level_up()?;
+
+        // struct name ...
+        let (name, name_pos) = match state.input.next().unwrap() {
+            (Token::Identifier(s), pos) => (self.get_interned_string(*s), pos),
+            (Token::Reserved(s), pos) => return Err(PERR::Reserved(s.to_string()).into_err(pos)),
+            (Token::LexError(err), pos) => return Err(err.into_err(pos)),
+            (.., pos) => {
+                return Err(PERR::MissingSymbol("Expecting a type name".into()).into_err(pos))
+            }
+        };
+
+        // struct name { ...
+        match state.input.next().unwrap() {
+            (Token::LeftBrace, ..) => (),
+            (Token::LexError(err), pos) => return Err(err.into_err(pos)),
+            (.., pos) => {
+                return Err(PERR::MissingToken(
+                    Token::LeftBrace.into(),
+                    format!("to start the definition of type '{name}'"),
+                )
+                .into_err(pos))
+            }
+        }
+
+        let mut fields = StaticVec::<Ident>::new_const();
+        let mut field_types = FnArgsVec::new_const();
+
+        loop {
+            let (token, pos) = state.input.peek().unwrap();
+
+            match token {
+                // ... }
+                Token::RightBrace => {
+                    eat_token(state.input, &Token::RightBrace);
+                    break;
+                }
+                // ... fn method() { ... }
+                Token::Fn | Token::Private | Token::Comment(..) => {
+                    #[cfg(feature = "metadata")]
+                    let comments = parse_doc_comments(
+                        state.input,
+                        settings.has_flag(ParseSettingFlags::GLOBAL_LEVEL),
+                    )?;
+
+                    let (mut f, pos) = self.parse_fn_def(
+                        state,
+                        settings,
+                        #[cfg(feature = "metadata")]
+                        comments,
+                    )?;
+
+                    if f.this_type.is_some() {
+                        return Err(PERR::MissingSymbol(format!(
+                            "Methods of type '{name}' cannot specify the type of 'this'"
+                        ))
+                        .into_err(pos));
+                    }
+                    f.this_type = Some(name.clone());
+
+                    Self::add_fn_def(state, f, pos)?;
+                    continue;
+                }
+                Token::EOF => {
+                    return Err(PERR::MissingToken(
+                        Token::RightBrace.into(),
+                        format!("to end the definition of type '{name}'"),
+                    )
+                    .into_err(*pos))
+                }
+                _ => (),
+            }
+
+            // ... field: type ...
+            match state.input.next().unwrap() {
+                (Token::Identifier(s), pos) => {
+                    if fields.iter().any(|f| f.name == *s) {
+                        return Err(PERR::DuplicatedProperty(s.to_string()).into_err(pos));
+                    }
+
+                    let name = self.get_interned_string(*s);
+                    fields.push(Ident { name, pos });
+
+                    field_types.push(if match_token(state.input, &Token::Colon).0 {
+                        Some(self.parse_type_annotation(state)?)
+                    } else {
+                        None
+                    });
+                }
+                (Token::Reserved(s), pos) if is_valid_identifier(&s) => {
+                    return Err(PERR::Reserved(s.to_string()).into_err(pos));
+                }
+                (Token::LexError(err), pos) => return Err(err.into_err(pos)),
+                (.., pos) => return Err(PERR::PropertyExpected.into_err(pos)),
+            }
+
+            match state.input.peek().unwrap() {
+                (Token::Comma, ..) => {
+                    eat_token(state.input, &Token::Comma);
+                }
+                (
+                    Token::RightBrace
+                    | Token::Fn
+                    | Token::Private
+                    | Token::Comment(..)
+                    | Token::LexError(..),
+                    ..,
+                ) => (),
+                (.., pos) => {
+                    return Err(PERR::MissingToken(
+                        Token::Comma.into(),
+                        format!("to separate the fields of type '{name}'"),
+                    )
+                    .into_err(*pos))
+                }
+            }
+        }
+
+        // Constructor function: name(field, ...) -> record
+        let values = fields.iter().map(|Ident { name, pos }| {
+            #[cfg(not(feature = "no_module"))]
+            let var = (None, name.clone(), crate::ast::Namespace::NONE, 0);
+            #[cfg(feature = "no_module")]
+            let var = (None, name.clone());
+
+            let expr = Expr::Variable(var.into(), None, *pos);
+            (
+                Ident {
+                    name: name.clone(),
+                    pos: *pos,
+                },
+                expr,
+            )
+        });
+        let template = fields
+            .iter()
+            .map(|f| (f.name.as_str().into(), Dynamic::UNIT))
+            .collect();
 
-            Token::Let => self.parse_let(state, settings.level_up()?, ReadWrite, false),
-            Token::Const => self.parse_let(state, settings.level_up()?, ReadOnly, false),
+        let mut args = FnArgsVec::with_capacity(2);
+        args.push(Expr::StringConstant(name.clone(), name_pos));
+        args.push(Expr::Map((values.collect(), template).into(), name_pos));
 
+        let body = FnCallExpr {
             #[cfg(not(feature = "no_module"))]
-            Token::Import => self.parse_import(state, settings.level_up()?),
+            namespace: crate::ast::Namespace::NONE,
+            name: self.get_interned_string(crate::engine::FN_RECORD),
+            hashes: FnCallHashes::from_native_only(calc_fn_hash(None, crate::engine::FN_RECORD, 2)),
+            args,
+            op_token: None,
+            capture_parent_scope: false,
+        };
 
-            #[cfg(not(feature = "no_module"))]
-            Token::Export if !settings.has_flag(ParseSettingFlags::GLOBAL_LEVEL) => {
-                Err(PERR::WrongExport.into_err(token_pos))
-            }
+        field_types.shrink_to_fit();
 
-            #[cfg(not(feature = "no_module"))]
-            Token::Export => self.parse_export(state, settings.level_up()?),
+        // Field types are kept only if any field is annotated
+        if field_types.iter().all(Option::is_none) {
+            field_types.clear();
+        }
 
-            _ => self.parse_expr_stmt(state, settings.level_up()?),
+        let constructor = ScriptFuncDef {
+            name: name.clone(),
+            access: crate::FnAccess::Public,
+            this_type: None,
+            param_positions: fields.iter().map(|f| f.pos).collect(),
+            params: fields.into_iter().map(|f| f.name).collect(),
+            param_types: field_types,
+            return_type: None,
+            body: Stmt::FnCall(body.into(), name_pos).into(),
+            #[cfg(feature = "metadata")]
+            comments: comments.into_iter().collect(),
+        };
+
+        #[cfg(not(feature = "unchecked"))]
+        if state.lib.len() >= self.max_functions() {
+            return Err(PERR::TooManyFunctions.into_err(struct_pos));
         }
+
+        Self::add_fn_def(state, constructor, name_pos)?;
+
+        Ok(Stmt::Noop(struct_pos))
     }
 
-    /// Parse a try/catch statement.
-    fn parse_try_catch(
+    /// Parse an enum type definition.
+    ///
+    /// Each variant becomes a constructor function named `Type::Variant` which takes the values of
+    /// the variant's payload as parameters, and the methods become script-defined functions with
+    /// the type as the type of `this`.
+    #[cfg(not(feature = "no_function"))]
+    fn parse_enum(
         &self,
         state: &mut ParseState,
         settings: ParseSettings,
+        #[cfg(feature = "metadata")] comments: impl IntoIterator<Item = crate::Identifier>,
     ) -> ParseResult<Stmt> {
-        // try ...
-        let settings = settings.level_up_with_position(eat_token(state.input, &Token::Try))?;
-
-        // try { try_block }
-        let body = self.parse_block(state, settings)?.into();
+        // enum ...
+        let (token, enum_pos) = state.input.next().unwrap();
+        debug_assert!(
+            matches!(token, Token::Reserved(ref s) if &**s == "enum"),
+            "Token::Reserved(enum) expected but gets {:?}",
+            token
+        );
 
-        // try { try_block } catch
-        let (matched, catch_pos) = match_token(state.input, &Token::Catch);
+        #[allow(unused_variables)]
+        let settings = settings.level_up()?;
 
-        if !matched {
-            return Err(
-                PERR::MissingToken(Token::Catch.into(), "for the 'try' statement".into())
-                    .into_err(catch_pos),
-            );
-        }
+        #[cfg(feature = "metadata")]
+        let comments: StaticVec<_> = comments.into_iter().collect();
 
-        // try { try_block } catch (
-        let catch_var = if match_token(state.input, &Token::LeftParen).0 {
-            let (name, pos) = parse_var_name(state.input)?;
-            let (matched, err_pos) = match_token(state.input, &Token::RightParen);
+        // enum name ...
+        let name = match state.input.next().unwrap() {
+            (Token::Identifier(s), ..) => self.get_interned_string(*s),
+            (Token::Reserved(s), pos) => return Err(PERR::Reserved(s.to_string()).into_err(pos)),
+            (Token::LexError(err), pos) => return Err(err.into_err(pos)),
+            (.., pos) => {
+                return Err(PERR::MissingSymbol("Expecting a type name".into()).into_err(pos))
+            }
+        };
 
-            if !matched {
+        // enum name { ...
+        match state.input.next().unwrap() {
+            (Token::LeftBrace, ..) => (),
+            (Token::LexError(err), pos) => return Err(err.into_err(pos)),
+            (.., pos) => {
                 return Err(PERR::MissingToken(
-                    Token::RightParen.into(),
-                    "to enclose the catch variable".into(),
+                    Token::LeftBrace.into(),
+                    format!("to start the definition of type '{name}'"),
                 )
-                .into_err(err_pos));
+                .into_err(pos))
             }
+        }
 
-            let name = self.get_interned_string(name);
-            state.stack.push(name.clone(), ());
-            Ident { name, pos }
-        } else {
-            Ident {
-                name: self.get_interned_string(""),
-                pos: Position::NONE,
+        let mut variants = StaticVec::<SmartString>::new_const();
+
+        loop {
+            let (token, pos) = state.input.peek().unwrap();
+
+            match token {
+                // ... }
+                Token::RightBrace => {
+                    eat_token(state.input, &Token::RightBrace);
+                    break;
+                }
+                // ... fn method() { ... }
+                #[cfg(not(feature = "no_object"))]
+                Token::Fn | Token::Private | Token::Comment(..) => {
+                    #[cfg(feature = "metadata")]
+                    let comments = parse_doc_comments(
+                        state.input,
+                        settings.has_flag(ParseSettingFlags::GLOBAL_LEVEL),
+                    )?;
+
+                    let (mut f, pos) = self.parse_fn_def(
+                        state,
+                        settings,
+                        #[cfg(feature = "metadata")]
+                        comments,
+                    )?;
+
+                    if f.this_type.is_some() {
+                        return Err(PERR::MissingSymbol(format!(
+                            "Methods of type '{name}' cannot specify the type of 'this'"
+                        ))
+                        .into_err(pos));
+                    }
+                    f.this_type = Some(name.clone());
+
+                    Self::add_fn_def(state, f, pos)?;
+                    continue;
+                }
+                Token::EOF => {
+                    return Err(PERR::MissingToken(
+                        Token::RightBrace.into(),
+                        format!("to end the definition of type '{name}'"),
+                    )
+                    .into_err(*pos))
+                }
+                _ => (),
             }
-        };
 
-        // try { try_block } catch ( var ) { catch_block }
-        let branch = self.parse_block(state, settings)?.into();
+            // ... variant ...
+            let (variant, variant_pos) = match state.input.next().unwrap() {
+                (Token::Identifier(s), pos) => {
+                    if variants.contains(&*s) {
+                        return Err(PERR::MissingSymbol(format!(
+                            "Variant '{s}' of type '{name}' is already defined"
+                        ))
+                        .into_err(pos));
+                    }
+                    variants.push((*s).clone());
+                    (self.get_interned_string(*s), pos)
+                }
+                (Token::Reserved(s), pos) if is_valid_identifier(&s) => {
+                    return Err(PERR::Reserved(s.to_string()).into_err(pos));
+                }
+                (Token::LexError(err), pos) => return Err(err.into_err(pos)),
+                (.., pos) => {
+                    return Err(
+                        PERR::MissingSymbol("Expecting the name of a variant".into()).into_err(pos),
+                    )
+                }
+            };
 
-        let expr = if catch_var.is_empty() {
-            Expr::Unit(catch_var.pos)
-        } else {
-            // Remove the error variable from the stack
-            state.stack.pop();
+            let fn_name = self.get_interned_string(format!("{name}::{variant}"));
 
-            Expr::Variable(
+            let mut params = StaticVec::<Ident>::new_const();
+            let mut param_types = FnArgsVec::new_const();
+
+            // ... variant(value: type, ...) ...
+            if !match_token(state.input, &Token::Unit).0
+                && match_token(state.input, &Token::LeftParen).0
+            {
+                loop {
+                    match state.input.next().unwrap() {
+                        (Token::RightParen, ..) => break,
+                        (Token::Identifier(s), pos) => {
+                            if params.iter().any(|p| p.name == *s) {
+                                return Err(PERR::FnDuplicatedParam(
+                                    fn_name.to_string(),
+                                    s.to_string(),
+                                )
+                                .into_err(pos));
+                            }
+
+                            let name = self.get_interned_string(*s);
+                            params.push(Ident { name, pos });
+
+                            param_types.push(if match_token(state.input, &Token::Colon).0 {
+                                Some(self.parse_type_annotation(state)?)
+                            } else {
+                                None
+                            });
+                        }
+                        (Token::Reserved(s), pos) if is_valid_identifier(&s) => {
+                            return Err(PERR::Reserved(s.to_string()).into_err(pos));
+                        }
+                        (Token::LexError(err), pos) => return Err(err.into_err(pos)),
+                        (.., pos) => return Err(PERR::VariableExpected.into_err(pos)),
+                    }
+
+                    match state.input.next().unwrap() {
+                        (Token::RightParen, ..) => break,
+                        (Token::Comma, ..) => (),
+                        (Token::LexError(err), pos) => return Err(err.into_err(pos)),
+                        (.., pos) => {
+                            return Err(PERR::MissingToken(
+                                Token::RightParen.into(),
+                                format!("to close the payload of variant '{fn_name}'"),
+                            )
+                            .into_err(pos))
+                        }
+                    }
+                }
+            }
+
+            match state.input.peek().unwrap() {
+                (Token::Comma, ..) => {
+                    eat_token(state.input, &Token::Comma);
+                }
+                (
+                    Token::RightBrace
+                    | Token::Fn
+                    | Token::Private
+                    | Token::Comment(..)
+                    | Token::LexError(..),
+                    ..,
+                ) => (),
+                (.., pos) => {
+                    return Err(PERR::MissingToken(
+                        Token::Comma.into(),
+                        format!("to separate the variants of type '{name}'"),
+                    )
+                    .into_err(*pos))
+                }
+            }
+
+            // Constructor function: Type::Variant(value, ...) -> enum value
+            let mut args = FnArgsVec::with_capacity(params.len() + 2);
+            args.push(Expr::StringConstant(name.clone(), variant_pos));
+            args.push(Expr::StringConstant(variant, variant_pos));
+            args.extend(params.iter().map(|Ident { name, pos }| {
                 #[cfg(not(feature = "no_module"))]
-                (None, catch_var.name, <_>::default(), 0).into(),
+                let var = (None, name.clone(), crate::ast::Namespace::NONE, 0);
                 #[cfg(feature = "no_module")]
-                (None, catch_var.name).into(),
-                None,
-                catch_var.pos,
-            )
-        };
+                let var = (None, name.clone());
 
-        Ok(Stmt::TryCatch(
-            FlowControl { expr, body, branch }.into(),
-            settings.pos,
-        ))
+                Expr::Variable(var.into(), None, *pos)
+            }));
+
+            let body = FnCallExpr {
+                #[cfg(not(feature = "no_module"))]
+                namespace: crate::ast::Namespace::NONE,
+                name: self.get_interned_string(crate::engine::FN_ENUM),
+                hashes: FnCallHashes::from_native_only(calc_fn_hash(
+                    None,
+                    crate::engine::FN_ENUM,
+                    args.len(),
+                )),
+                args,
+                op_token: None,
+                capture_parent_scope: false,
+            };
+
+            param_types.shrink_to_fit();
+
+            // Payload types are kept only if any value is annotated
+            if param_types.iter().all(Option::is_none) {
+                param_types.clear();
+            }
+
+            let constructor = ScriptFuncDef {
+                name: fn_name,
+                access: crate::FnAccess::Public,
+                #[cfg(not(feature = "no_object"))]
+                this_type: None,
+                param_positions: params.iter().map(|p| p.pos).collect(),
+                params: params.into_iter().map(|p| p.name).collect(),
+                param_types,
+                return_type: None,
+                body: Stmt::FnCall(body.into(), variant_pos).into(),
+                #[cfg(feature = "metadata")]
+                comments: comments.clone(),
+            };
+
+            #[cfg(not(feature = "unchecked"))]
+            if state.lib.len() >= self.max_functions() {
+                return Err(PERR::TooManyFunctions.into_err(variant_pos));
+            }
+
+            Self::add_fn_def(state, constructor, variant_pos)?;
+        }
+
+        if variants.is_empty() {
+            return Err(PERR::MissingSymbol(format!(
+                "Type '{name}' must have at least one variant"
+            ))
+            .into_err(enum_pos));
+        }
+
+        Ok(Stmt::Noop(enum_pos))
     }
 
     /// Parse a function definition.
@@ -3487,12 +4738,7 @@ impl Engine {
             match token {
                 Token::StringConstant(s) if next_token == &Token::Period => {
                     eat_token(state.input, &Token::Period);
-                    let s = match s.as_str() {
-                        "int" => self.get_interned_string(std::any::type_name::<crate::INT>()),
-                        #[cfg(not(feature = "no_float"))]
-                        "float" => self.get_interned_string(std::any::type_name::<crate::FLOAT>()),
-                        _ => self.get_interned_string(*s),
-                    };
+                    let s = self.get_type_name(*s);
                     (state.input.next().unwrap(), Some(s))
                 }
                 Token::StringConstant(..) => {
@@ -3504,12 +4750,7 @@ impl Engine {
                 }
                 Token::Identifier(s) if next_token == &Token::Period => {
                     eat_token(state.input, &Token::Period);
-                    let s = match s.as_str() {
-                        "int" => self.get_interned_string(std::any::type_name::<crate::INT>()),
-                        #[cfg(not(feature = "no_float"))]
-                        "float" => self.get_interned_string(std::any::type_name::<crate::FLOAT>()),
-                        _ => self.get_interned_string(*s),
-                    };
+                    let s = self.get_type_name(*s);
                     (state.input.next().unwrap(), Some(s))
                 }
                 _ => ((token, pos), None),
@@ -3537,11 +4778,40 @@ impl Engine {
         };
 
         let mut params = StaticVec::<(ImmutableString, _)>::new_const();
+        let mut param_types = FnArgsVec::new_const();
+        let mut param_patterns = StaticVec::<(usize, Pattern)>::new_const();
+        let params_offset = state.stack.len();
 
         if !no_params {
             let sep_err = format!("to separate the parameters of function '{name}'");
 
             loop {
+                // [pattern] or #{pattern}
+                if Self::is_destructuring_start(state) {
+                    let (s, pos, pattern) = self.parse_var_or_pattern(state, settings)?;
+
+                    if params.iter().any(|(p, _)| p == &s) {
+                        return Err(PERR::FnDuplicatedParam(name.into(), s.into()).into_err(pos));
+                    }
+
+                    let s = self.get_interned_string(s);
+                    state.stack.push(s.clone(), ());
+                    param_patterns.push((params.len(), pattern.unwrap()));
+                    params.push((s, pos));
+                    param_types.push(None);
+
+                    match state.input.next().unwrap() {
+                        (Token::RightParen, ..) => break,
+                        (Token::Comma, ..) => continue,
+                        (Token::LexError(err), pos) => return Err(err.into_err(pos)),
+                        (.., pos) => {
+                            return Err(
+                                PERR::MissingToken(Token::Comma.into(), sep_err).into_err(pos)
+                            )
+                        }
+                    }
+                }
+
                 match state.input.next().unwrap() {
                     (Token::RightParen, ..) => break,
                     (Token::Identifier(s), pos) => {
@@ -3554,6 +4824,13 @@ impl Engine {
                         let s = self.get_interned_string(*s);
                         state.stack.push(s.clone(), ());
                         params.push((s, pos));
+
+                        // param: type
+                        param_types.push(if match_token(state.input, &Token::Colon).0 {
+                            Some(self.parse_type_annotation(state)?)
+                        } else {
+                            None
+                        });
                     }
                     (Token::LexError(err), pos) => return Err(err.into_err(pos)),
                     (.., pos) => {
@@ -3576,22 +4853,82 @@ impl Engine {
             }
         }
 
+        // Parse return type
+        let return_type = match state.input.peek().unwrap() {
+            (Token::Reserved(s), ..) if &**s == "->" => {
+                state.input.next().unwrap();
+                Some(self.parse_type_annotation(state)?)
+            }
+            #[cfg(not(feature = "no_custom_syntax"))]
+            (Token::Custom(s), ..) if &**s == "->" => {
+                state.input.next().unwrap();
+                Some(self.parse_type_annotation(state)?)
+            }
+            _ => None,
+        };
+
+        // Variables bound by patterns must not clash with other parameters
+        let duplicated = {
+            let mut vars = param_patterns
+                .iter()
+                .flat_map(|(.., p)| p.variables())
+                .map(|id| (&id.name, id.pos))
+                .chain(
+                    params
+                        .iter()
+                        .enumerate()
+                        .filter(|(i, ..)| param_patterns.iter().all(|(n, ..)| n != i))
+                        .map(|(.., (p, pos))| (p, *pos)),
+                )
+                .collect::<StaticVec<_>>();
+            vars.sort_by_key(|&(name, ..)| name);
+            vars.windows(2)
+                .find(|w| w[0].0 == w[1].0)
+                .map(|w| (w[1].0.to_string(), w[1].1))
+        };
+
+        if let Some((param, pos)) = duplicated {
+            return Err(PERR::FnDuplicatedParam(name.into(), param).into_err(pos));
+        }
+
+        // Destructure parameters into the variables of their patterns
+        let destructures = param_patterns
+            .into_iter()
+            .map(|(n, pattern)| {
+                let index = NonZeroUsize::new(state.stack.len() - params_offset - n).unwrap();
+                self.make_destructure_stmt(state, pattern, params[n].0.clone(), index)
+            })
+            .collect::<StaticVec<_>>();
+
         // Parse function body
-        let body = match state.input.peek().unwrap() {
+        let mut body: StmtBlock = match state.input.peek().unwrap() {
             (Token::LeftBrace, ..) => self.parse_block(state, settings)?,
             (.., pos) => return Err(PERR::FnMissingBody(name.into()).into_err(*pos)),
         }
         .into();
 
+        body.statements_mut().insert_many(0, destructures);
+
+        let mut param_positions: FnArgsVec<_> = params.iter().map(|&(.., pos)| pos).collect();
+        param_positions.shrink_to_fit();
         let mut params: FnArgsVec<_> = params.into_iter().map(|(p, ..)| p).collect();
         params.shrink_to_fit();
 
+        // Parameter types are kept only if any parameter is annotated
+        if param_types.iter().all(Option::is_none) {
+            param_types.clear();
+        }
+        param_types.shrink_to_fit();
+
         Ok(ScriptFuncDef {
             name: self.get_interned_string(name),
             access,
             #[cfg(not(feature = "no_object"))]
             this_type,
             params,
+            param_positions,
+            param_types,
+            return_type,
             body,
             #[cfg(feature = "metadata")]
             comments: comments.into_iter().collect(),
@@ -3697,6 +5034,7 @@ impl Engine {
         }
 
         let mut params_list = StaticVec::<ImmutableString>::new_const();
+        let mut param_positions_list = StaticVec::<Position>::new_const();
 
         // Parse parameters
         if !skip_parameters
@@ -3716,6 +5054,7 @@ impl Engine {
                         let s = self.get_interned_string(*s);
                         new_state.stack.push(s.clone(), ());
                         params_list.push(s);
+                        param_positions_list.push(pos);
                     }
                     (Token::LexError(err), pos) => return Err(err.into_err(pos)),
                     (.., pos) => {
@@ -3762,23 +5101,30 @@ impl Engine {
         };
 
         // Parse function body
-        let body = self.parse_stmt(new_state, new_settings.level_up()?)?;
+        let body_settings = new_settings.level_up()?;
+        new_state.errors = state.errors.take();
+        let body = self.parse_stmt(new_state, body_settings);
+        state.errors = new_state.errors.take();
+        let body = body?;
 
         let _ = new_settings; // Make sure it doesn't leak into code below
 
         // External variables may need to be processed in a consistent order,
         // so extract them into a list.
         #[cfg(not(feature = "no_closure"))]
-        let (mut params, _externals) = {
+        let (mut params, mut param_positions, _externals) = {
             let externals = std::mem::take(&mut new_state.external_vars);
 
             let mut params = FnArgsVec::with_capacity(params_list.len() + externals.len());
             params.extend(externals.iter().map(|Ident { name, .. }| name.clone()));
+            let mut param_positions = FnArgsVec::with_capacity(params.capacity());
+            param_positions.extend(externals.iter().map(|Ident { pos, .. }| *pos));
 
-            (params, externals)
+            (params, param_positions, externals)
         };
         #[cfg(feature = "no_closure")]
-        let (mut params, _externals) = (
+        let (mut params, mut param_positions, _externals) = (
+            FnArgsVec::with_capacity(params_list.len()),
             FnArgsVec::with_capacity(params_list.len()),
             ThinVec::<Ident>::new(),
         );
@@ -3786,6 +5132,7 @@ impl Engine {
         let _ = new_state; // Make sure it doesn't leak into code below
 
         params.append(&mut params_list);
+        param_positions.append(&mut param_positions_list);
 
         // Create unique function name by hashing the script body plus the parameters.
         let hasher = &mut get_hasher();
@@ -3801,6 +5148,9 @@ impl Engine {
             #[cfg(not(feature = "no_object"))]
             this_type: None,
             params,
+            param_positions,
+            param_types: FnArgsVec::new_const(),
+            return_type: None,
             body: body.into(),
             #[cfg(not(feature = "no_function"))]
             #[cfg(feature = "metadata")]
@@ -3897,7 +5247,7 @@ impl Engine {
     }
 
     /// Parse the global level statements.
-    fn parse_global_level(
+    pub(crate) fn parse_global_level(
         &self,
         state: &mut ParseState,
         process_settings: impl FnOnce(&mut ParseSettings),
@@ -3915,7 +5265,14 @@ impl Engine {
         process_settings(&mut settings);
 
         while state.input.peek().unwrap().0 != Token::EOF {
-            let stmt = self.parse_stmt(state, settings)?;
+            let stmt = match self.parse_stmt(state, settings) {
+                Ok(stmt) => stmt,
+                Err(err) => {
+                    state.recover(err)?;
+                    skip_to_statement_end(state.input, true);
+                    continue;
+                }
+            };
 
             if stmt.is_noop() {
                 continue;
@@ -3937,21 +5294,31 @@ impl Engine {
                 // { stmt } ???
                 _ if !need_semicolon => (),
                 // stmt <error>
-                (Token::LexError(err), pos) => return Err(err.clone().into_err(*pos)),
+                (Token::LexError(err), pos) => {
+                    let err = err.clone().into_err(*pos);
+                    state.recover(err)?;
+                    skip_to_statement_end(state.input, true);
+                }
                 // stmt ???
                 (.., pos) => {
                     // Semicolons are not optional between statements
-                    return Err(PERR::MissingToken(
+                    let err = PERR::MissingToken(
                         Token::SemiColon.into(),
                         "to terminate this statement".into(),
                     )
-                    .into_err(*pos));
+                    .into_err(*pos);
+                    state.recover(err)?;
                 }
             }
         }
 
+        // Keep functions in source order
         #[cfg(not(feature = "no_function"))]
-        let lib = state.lib.values().cloned().collect();
+        let lib = {
+            let mut lib: Vec<_> = state.lib.values().cloned().collect();
+            lib.sort_by_key(|f| f.body.position());
+            lib
+        };
         #[cfg(feature = "no_function")]
         let lib = Vec::new();
 
@@ -3965,19 +5332,68 @@ impl Engine {
         mut state: ParseState,
         #[cfg(not(feature = "no_optimize"))] optimization_level: crate::OptimizationLevel,
     ) -> ParseResult<AST> {
-        let (statements, _lib) = self.parse_global_level(&mut state, |_| {})?;
+        let (statements, lib) = self.parse_global_level(&mut state, |_| {})?;
+
+        Ok(self.build_ast(
+            &state,
+            statements,
+            lib,
+            #[cfg(not(feature = "no_optimize"))]
+            optimization_level,
+        ))
+    }
+
+    /// Run the parser on an input stream with error recovery, returning a partial AST
+    /// together with all the errors found.
+    ///
+    /// After an error, parsing resumes at the next statement boundary (i.e. after a `;` or a
+    /// `}`), skipping the erroneous statement.
+    pub(crate) fn parse_with_recovery(
+        &self,
+        mut state: ParseState,
+        #[cfg(not(feature = "no_optimize"))] optimization_level: crate::OptimizationLevel,
+    ) -> (AST, Vec<ParseError>) {
+        state.errors = Some(Vec::new());
+
+        let (statements, lib) = match self.parse_global_level(&mut state, |_| {}) {
+            Ok(result) => result,
+            Err(err) => {
+                state.errors.get_or_insert_with(Vec::new).push(err);
+                (StmtBlockContainer::new_const(), Vec::new())
+            }
+        };
+
+        let ast = self.build_ast(
+            &state,
+            statements,
+            lib,
+            #[cfg(not(feature = "no_optimize"))]
+            optimization_level,
+        );
 
+        (ast, state.errors.take().unwrap_or_default())
+    }
+
+    /// Build an [`AST`] from parsed statements and functions.
+    #[inline]
+    pub(crate) fn build_ast(
+        &self,
+        _state: &ParseState,
+        statements: StmtBlockContainer,
+        _lib: Vec<Shared<ScriptFuncDef>>,
+        #[cfg(not(feature = "no_optimize"))] optimization_level: crate::OptimizationLevel,
+    ) -> AST {
         #[cfg(not(feature = "no_optimize"))]
-        return Ok(self.optimize_into_ast(
-            state.external_constants,
+        return self.optimize_into_ast(
+            _state.external_constants,
             statements,
             #[cfg(not(feature = "no_function"))]
             _lib,
             optimization_level,
-        ));
+        );
 
         #[cfg(feature = "no_optimize")]
-        return Ok(AST::new(
+        return AST::new(
             statements,
             #[cfg(not(feature = "no_function"))]
             {
@@ -3985,6 +5401,6 @@ impl Engine {
                 new_lib.extend(_lib);
                 new_lib
             },
-        ));
+        );
     }
 }
@@ -0,0 +1,548 @@
+//! Serialization of compiled [`AST`]'s into bytes.
+
+use super::binary;
+use crate::ast::{ASTFlags, OpAssignment, Stmt};
+use crate::func::StraightHashMap;
+use crate::tokenizer::Token;
+use crate::types::dynamic::{AccessMode, Tag, Union};
+use crate::{calc_fn_hash, Dynamic, Engine, Identifier, ImmutableString, Position, RhaiResultOf};
+use crate::{SmartString, ThinVec, AST, ERR};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::convert::{TryFrom, TryInto};
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+
+/// Magic bytes at the beginning of a serialized [`AST`].
+const MAGIC: &[u8; 4] = b"RHAI";
+
+/// Version of the serialized [`AST`] format.
+///
+/// Bump this whenever the layout of any AST node changes.
+const FORMAT_VERSION: u32 = 5;
+
+/// Bit-flags of the features that affect the layout of a serialized [`AST`].
+const fn features_fingerprint() -> u32 {
+    let mut flags = 0;
+
+    if cfg!(feature = "only_i32") {
+        flags |= 1 << 0;
+    }
+    if cfg!(feature = "no_float") {
+        flags |= 1 << 1;
+    }
+    if cfg!(feature = "f32_float") {
+        flags |= 1 << 2;
+    }
+    if cfg!(feature = "decimal") {
+        flags |= 1 << 3;
+    }
+    if cfg!(feature = "no_index") {
+        flags |= 1 << 4;
+    }
+    if cfg!(feature = "no_object") {
+        flags |= 1 << 5;
+    }
+    if cfg!(feature = "no_function") {
+        flags |= 1 << 6;
+    }
+    if cfg!(feature = "no_closure") {
+        flags |= 1 << 7;
+    }
+    if cfg!(feature = "no_module") {
+        flags |= 1 << 8;
+    }
+    if cfg!(feature = "no_custom_syntax") {
+        flags |= 1 << 9;
+    }
+    if cfg!(feature = "no_position") {
+        flags |= 1 << 10;
+    }
+    if cfg!(feature = "metadata") {
+        flags |= 1 << 11;
+    }
+
+    flags
+}
+
+/// Hash value used to detect a different hashing seed.
+///
+/// Function call and variable hashes are pre-calculated in an [`AST`], so it cannot be used
+/// with a different hashing seed.
+#[inline(always)]
+fn hashes_fingerprint() -> u64 {
+    calc_fn_hash(None, "$ast$", 0)
+}
+
+/// Header of a serialized [`AST`].
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+struct Header {
+    /// Version of the serialized format.
+    version: u32,
+    /// Version of Rhai that created the serialized [`AST`].
+    rhai: SmartString,
+    /// Features that affect the layout.
+    features: u32,
+    /// Hashing seed fingerprint.
+    hashes: u64,
+}
+
+impl Header {
+    /// Create a [`Header`] for the current build.
+    fn current() -> Self {
+        Self {
+            version: FORMAT_VERSION,
+            rhai: env!("CARGO_PKG_VERSION").into(),
+            features: features_fingerprint(),
+            hashes: hashes_fingerprint(),
+        }
+    }
+}
+
+/// Contents of a serialized [`AST`].
+#[derive(Serialize, Deserialize)]
+struct Contents {
+    /// Source of the [`AST`].
+    source: Option<ImmutableString>,
+    /// [`AST`] documentation.
+    #[cfg(feature = "metadata")]
+    doc: SmartString,
+    /// Global statements.
+    body: ThinVec<Stmt>,
+    /// Script-defined functions.
+    #[cfg(not(feature = "no_function"))]
+    functions: Vec<crate::ast::ScriptFuncDef>,
+}
+
+impl AST {
+    /// Serialize the [`AST`] into bytes, which can later be loaded via [`Engine::load_ast`]
+    /// without parsing the script again.
+    /// Exported under the `serde` feature only.
+    ///
+    /// The bytes can only be loaded by a build of Rhai with the same version, the same features
+    /// and the same hashing seed (see [`set_hashing_seed`][crate::config::hashing::set_hashing_seed]).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the [`AST`] contains constant values that cannot be serialized
+    /// (e.g. custom types or function pointers to native Rust functions), or if it embeds a
+    /// [module resolver][crate::ModuleResolver].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::Engine;
+    ///
+    /// let engine = Engine::new();
+    ///
+    /// let ast = engine.compile("fn add(x, y) { x + y } add(40, 2)")?;
+    ///
+    /// let bytes = ast.to_bytes()?;
+    ///
+    /// let ast = engine.load_ast(&bytes)?;
+    ///
+    /// assert_eq!(engine.eval_ast::<i64>(&ast)?, 42);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn to_bytes(&self) -> RhaiResultOf<Vec<u8>> {
+        #[cfg(not(feature = "no_module"))]
+        if self.resolver.as_deref().map_or(false, |r| !r.is_empty()) {
+            return Err(ERR::ErrorSystem(
+                "Cannot serialize AST".into(),
+                "embedded module resolver is not supported".into(),
+            )
+            .into());
+        }
+
+        let contents = Contents {
+            source: self.source_raw().cloned(),
+            #[cfg(feature = "metadata")]
+            doc: self.doc.clone(),
+            body: self.statements().iter().cloned().collect(),
+            #[cfg(not(feature = "no_function"))]
+            functions: self
+                .shared_lib()
+                .iter_script_fn()
+                .map(|(.., fn_def)| fn_def.as_ref().clone())
+                .collect(),
+        };
+
+        let mut buf = MAGIC.to_vec();
+        binary::to_bytes(&Header::current(), &mut buf)?;
+        binary::to_bytes(&contents, &mut buf)?;
+        Ok(buf)
+    }
+}
+
+impl Engine {
+    /// Load an [`AST`] previously serialized via [`AST::to_bytes`].
+    /// Exported under the `serde` feature only.
+    ///
+    /// The [`AST`] is not optimized again.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the bytes are corrupted, or if they were created by a build of Rhai
+    /// with a different version, different features or a different hashing seed.
+    pub fn load_ast(&self, bytes: &[u8]) -> RhaiResultOf<AST> {
+        let err = |msg: String| ERR::ErrorSystem("Cannot load AST".into(), msg.into());
+
+        let bytes = bytes
+            .strip_prefix(MAGIC)
+            .ok_or_else(|| err("not a serialized AST".into()))?;
+
+        let (header, bytes) =
+            binary::from_bytes::<Header>(bytes).map_err(|_| err("invalid header".into()))?;
+
+        let current = Header::current();
+
+        if header.version != current.version {
+            return Err(err(format!(
+                "unsupported format version {} (expecting {})",
+                header.version, current.version
+            ))
+            .into());
+        }
+        if header.rhai != current.rhai {
+            return Err(err(format!(
+                "created by Rhai version {} (expecting {})",
+                header.rhai, current.rhai
+            ))
+            .into());
+        }
+        if header.features != current.features {
+            return Err(err("created with a different set of features".into()).into());
+        }
+        if header.hashes != current.hashes {
+            return Err(err("created with a different hashing seed".into()).into());
+        }
+
+        let (contents, rest) = binary::from_bytes::<Contents>(bytes)
+            .map_err(|e| err(format!("corrupted data: {e}")))?;
+
+        if !rest.is_empty() {
+            return Err(err("corrupted data: trailing bytes".into()).into());
+        }
+
+        #[cfg(not(feature = "no_function"))]
+        let lib = crate::Module::from(contents.functions.into_iter().map(crate::Shared::new));
+
+        let mut ast = AST::new(
+            contents.body,
+            #[cfg(not(feature = "no_function"))]
+            lib,
+        );
+
+        if let Some(source) = contents.source {
+            ast.set_source(source);
+        }
+        #[cfg(feature = "metadata")]
+        {
+            ast.doc = contents.doc;
+        }
+
+        Ok(ast)
+    }
+}
+
+/// A lossless representation of a constant [`Dynamic`] value.
+#[derive(Serialize, Deserialize)]
+enum ConstantValue {
+    Unit,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Decimal(String),
+    Char(char),
+    Str(ImmutableString),
+    Array(Vec<Constant>),
+    Blob(Vec<u8>),
+    Map(Vec<(Identifier, Constant)>),
+    FnPtr(ImmutableString, Vec<Constant>),
+}
+
+/// A constant [`Dynamic`] value together with its tag and access mode.
+#[derive(Serialize, Deserialize)]
+struct Constant(i32, bool, ConstantValue);
+
+impl Constant {
+    fn from_dynamic(value: &Dynamic) -> RhaiResultOf<Self> {
+        let constant = match value.0 {
+            Union::Unit(..) => ConstantValue::Unit,
+            Union::Bool(b, ..) => ConstantValue::Bool(b),
+            Union::Str(ref s, ..) => ConstantValue::Str(s.clone()),
+            Union::Char(c, ..) => ConstantValue::Char(c),
+            #[allow(clippy::useless_conversion)]
+            Union::Int(x, ..) => ConstantValue::Int(x.into()),
+            #[cfg(not(feature = "no_float"))]
+            #[allow(clippy::useless_conversion)]
+            Union::Float(x, ..) => ConstantValue::Float((*x).into()),
+            #[cfg(feature = "decimal")]
+            Union::Decimal(ref x, ..) => ConstantValue::Decimal(x.to_string()),
+            #[cfg(not(feature = "no_index"))]
+            Union::Array(ref a, ..) => ConstantValue::Array(
+                a.iter()
+                    .map(Self::from_dynamic)
+                    .collect::<RhaiResultOf<_>>()?,
+            ),
+            #[cfg(not(feature = "no_index"))]
+            Union::Blob(ref b, ..) => ConstantValue::Blob(b.to_vec()),
+            #[cfg(not(feature = "no_object"))]
+            Union::Map(ref m, ..) => ConstantValue::Map(
+                m.iter()
+                    .map(|(k, v)| Self::from_dynamic(v).map(|v| (k.clone(), v)))
+                    .collect::<RhaiResultOf<_>>()?,
+            ),
+            Union::FnPtr(ref f, ..) => {
+                #[cfg(not(feature = "no_function"))]
+                let has_env = f.env.is_some();
+                #[cfg(feature = "no_function")]
+                let has_env = false;
+
+                if has_env || !matches!(f.typ, crate::types::fn_ptr::FnPtrType::Normal) {
+                    return Err(Self::unsupported(value));
+                }
+
+                ConstantValue::FnPtr(
+                    f.name.clone(),
+                    f.curry
+                        .iter()
+                        .map(Self::from_dynamic)
+                        .collect::<RhaiResultOf<_>>()?,
+                )
+            }
+            #[cfg(not(feature = "no_closure"))]
+            Union::Shared(..) => return Self::from_dynamic(&value.flatten_clone()),
+            _ => return Err(Self::unsupported(value)),
+        };
+
+        #[allow(clippy::useless_conversion)]
+        Ok(Self(value.tag().into(), value.is_read_only(), constant))
+    }
+
+    fn into_dynamic(self) -> RhaiResultOf<Dynamic> {
+        let Self(tag, read_only, constant) = self;
+
+        #[allow(clippy::useless_conversion, clippy::unnecessary_fallible_conversions)]
+        let mut value = match constant {
+            ConstantValue::Unit => Dynamic::UNIT,
+            ConstantValue::Bool(b) => Dynamic::from_bool(b),
+            ConstantValue::Str(s) => s.into(),
+            ConstantValue::Char(c) => Dynamic::from_char(c),
+            ConstantValue::Int(x) => Dynamic::from_int(
+                x.try_into()
+                    .map_err(|_| Self::invalid("integer out of range"))?,
+            ),
+            #[cfg(not(feature = "no_float"))]
+            #[allow(clippy::cast_possible_truncation, clippy::unnecessary_cast)]
+            ConstantValue::Float(x) => Dynamic::from_float(x as crate::FLOAT),
+            #[cfg(feature = "decimal")]
+            ConstantValue::Decimal(x) => Dynamic::from_decimal(
+                x.parse()
+                    .map_err(|_| Self::invalid("invalid decimal number"))?,
+            ),
+            #[cfg(not(feature = "no_index"))]
+            ConstantValue::Array(a) => Dynamic::from_array(
+                a.into_iter()
+                    .map(Self::into_dynamic)
+                    .collect::<RhaiResultOf<_>>()?,
+            ),
+            #[cfg(not(feature = "no_index"))]
+            ConstantValue::Blob(b) => Dynamic::from_blob(b),
+            #[cfg(not(feature = "no_object"))]
+            ConstantValue::Map(m) => Dynamic::from_map(
+                m.into_iter()
+                    .map(|(k, v)| v.into_dynamic().map(|v| (k, v)))
+                    .collect::<RhaiResultOf<_>>()?,
+            ),
+            ConstantValue::FnPtr(name, curry) => crate::FnPtr {
+                name,
+                curry: curry
+                    .into_iter()
+                    .map(Self::into_dynamic)
+                    .collect::<RhaiResultOf<_>>()?,
+                #[cfg(not(feature = "no_function"))]
+                env: None,
+                typ: crate::types::fn_ptr::FnPtrType::Normal,
+            }
+            .into(),
+            #[allow(unreachable_patterns)]
+            _ => return Err(Self::invalid("constant type not supported")),
+        };
+
+        #[allow(clippy::unnecessary_fallible_conversions)]
+        value.set_tag(Tag::try_from(tag).map_err(|_| Self::invalid("tag value out of range"))?);
+        if read_only {
+            value.set_access_mode(AccessMode::ReadOnly);
+        }
+
+        Ok(value)
+    }
+
+    #[cold]
+    #[inline(never)]
+    fn unsupported(value: &Dynamic) -> crate::RhaiError {
+        ERR::ErrorMismatchDataType("constant".into(), value.type_name().into(), Position::NONE)
+            .into()
+    }
+
+    #[cold]
+    #[inline(never)]
+    fn invalid(msg: &str) -> crate::RhaiError {
+        serde::de::Error::custom(msg)
+    }
+}
+
+/// (De)serialize a constant [`Dynamic`] value.
+pub mod constant {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(value: &Dynamic, ser: S) -> Result<S::Ok, S::Error> {
+        Constant::from_dynamic(value)
+            .map_err(serde::ser::Error::custom)?
+            .serialize(ser)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(de: D) -> Result<Dynamic, D::Error> {
+        Constant::deserialize(de)?
+            .into_dynamic()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// (De)serialize a boxed constant [`Dynamic`] value.
+pub mod boxed_constant {
+    use super::*;
+
+    #[allow(clippy::borrowed_box)]
+    #[inline(always)]
+    pub fn serialize<S: Serializer>(value: &Box<Dynamic>, ser: S) -> Result<S::Ok, S::Error> {
+        constant::serialize(value, ser)
+    }
+
+    #[inline(always)]
+    pub fn deserialize<'de, D: Deserializer<'de>>(de: D) -> Result<Box<Dynamic>, D::Error> {
+        constant::deserialize(de).map(Box::new)
+    }
+}
+
+/// (De)serialize the contents of an object map literal.
+///
+/// The template map only holds the property names with `()` values, so it is rebuilt from the
+/// list of properties instead of being serialized.
+pub mod map_template {
+    use super::*;
+    use crate::ast::{Expr, Ident};
+    use crate::StaticVec;
+    use std::collections::BTreeMap;
+
+    type MapLiteral = (StaticVec<(Ident, Expr)>, BTreeMap<Identifier, Dynamic>);
+
+    #[allow(clippy::borrowed_box)]
+    #[inline(always)]
+    pub fn serialize<S: Serializer>(value: &Box<MapLiteral>, ser: S) -> Result<S::Ok, S::Error> {
+        value.0.serialize(ser)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(de: D) -> Result<Box<MapLiteral>, D::Error> {
+        let props = StaticVec::<(Ident, Expr)>::deserialize(de)?;
+        let template = props
+            .iter()
+            .map(|(Ident { name, .. }, ..)| (name.as_str().into(), Dynamic::UNIT))
+            .collect();
+        Ok((props, template).into())
+    }
+}
+
+/// (De)serialize the native operator [`Token`] of a function call.
+pub mod op_token {
+    use super::*;
+
+    #[allow(clippy::ref_option)]
+    pub fn serialize<S: Serializer>(token: &Option<Token>, ser: S) -> Result<S::Ok, S::Error> {
+        token.as_ref().map(Token::to_string).serialize(ser)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(de: D) -> Result<Option<Token>, D::Error> {
+        Option::<SmartString>::deserialize(de)?
+            .map(|syntax| match Token::lookup_symbol_from_syntax(&syntax) {
+                Some(token) => Ok(token),
+                // Custom operator
+                #[cfg(not(feature = "no_custom_syntax"))]
+                None => Ok(Token::Custom(syntax.into())),
+                #[cfg(feature = "no_custom_syntax")]
+                None => Err(serde::de::Error::custom(format!(
+                    "invalid operator: {syntax}"
+                ))),
+            })
+            .transpose()
+    }
+}
+
+/// (De)serialize the case blocks of a `switch` statement, keyed by value hashes.
+pub mod case_blocks {
+    use super::*;
+    use crate::ast::CaseBlocksList;
+
+    pub fn serialize<S: Serializer>(
+        cases: &StraightHashMap<CaseBlocksList>,
+        ser: S,
+    ) -> Result<S::Ok, S::Error> {
+        // Sort by hash for a deterministic output
+        let mut cases = cases.iter().collect::<Vec<_>>();
+        cases.sort_unstable_by_key(|&(&hash, ..)| hash);
+        ser.collect_seq(cases)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        de: D,
+    ) -> Result<StraightHashMap<CaseBlocksList>, D::Error> {
+        Ok(Vec::<(u64, CaseBlocksList)>::deserialize(de)?
+            .into_iter()
+            .collect())
+    }
+}
+
+impl Serialize for OpAssignment {
+    fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        let op = self
+            .get_op_assignment_info()
+            .map(|(.., op_assign, _, _, _)| op_assign.literal_syntax());
+        (op, self.position()).serialize(ser)
+    }
+}
+
+impl<'de> Deserialize<'de> for OpAssignment {
+    fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+        let (op, pos) = <(Option<SmartString>, Position)>::deserialize(de)?;
+
+        match op {
+            None => Ok(Self::new_assignment(pos)),
+            Some(op) => match Token::lookup_symbol_from_syntax(&op) {
+                Some(token) if token.get_base_op_from_assignment().is_some() => {
+                    Ok(Self::new_op_assignment_from_token(token, pos))
+                }
+                _ => Err(serde::de::Error::custom(format!(
+                    "invalid op-assignment operator: {op}"
+                ))),
+            },
+        }
+    }
+}
+
+impl Serialize for ASTFlags {
+    #[inline(always)]
+    fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        self.bits().serialize(ser)
+    }
+}
+
+impl<'de> Deserialize<'de> for ASTFlags {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+        let bits = u8::deserialize(de)?;
+        Self::from_bits(bits)
+            .ok_or_else(|| serde::de::Error::custom(format!("invalid AST flags: {bits:#b}")))
+    }
+}
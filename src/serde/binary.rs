@@ -0,0 +1,706 @@
+//! Compact binary data format for [`serde`], used to cache compiled [`AST`][crate::AST]'s.
+//!
+//! The format is _not_ self-describing: every value is encoded without type information, so it can
+//! only be read back by deserializing into exactly the same Rust type that was serialized.
+//!
+//! * Booleans and `u8` are single bytes.
+//! * Other integers are LEB128 variable-length, with zig-zag encoding for signed integers.
+//! * Floating-point numbers are little-endian IEEE-754 bytes.
+//! * Strings and byte arrays are a length followed by the raw bytes.
+//! * Sequences and maps are a length followed by the items.
+//! * Structs and tuples are their fields in order.
+//! * Enums are a variant index followed by the variant's content.
+
+use crate::{RhaiError, RhaiResultOf};
+use serde::de::{
+    self, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, SeqAccess, VariantAccess,
+    Visitor,
+};
+use serde::ser::{
+    self, SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant, SerializeTuple,
+    SerializeTupleStruct, SerializeTupleVariant,
+};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::convert::{TryFrom, TryInto};
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+
+/// Serialize a value into the compact binary format, appending to a buffer.
+pub fn to_bytes<T: Serialize + ?Sized>(value: &T, buf: &mut Vec<u8>) -> RhaiResultOf<()> {
+    value.serialize(&mut BinarySerializer { buf })
+}
+
+/// Deserialize a value from the compact binary format.
+///
+/// Returns the value together with the remaining unread bytes.
+pub fn from_bytes<'de, T: Deserialize<'de>>(bytes: &'de [u8]) -> RhaiResultOf<(T, &'de [u8])> {
+    let mut de = BinaryDeserializer { input: bytes };
+    let value = T::deserialize(&mut de)?;
+    Ok((value, de.input))
+}
+
+/// Serializer for the compact binary format.
+struct BinarySerializer<'a> {
+    buf: &'a mut Vec<u8>,
+}
+
+impl BinarySerializer<'_> {
+    #[inline]
+    fn write_varint(&mut self, mut value: u128) {
+        loop {
+            let byte = (value & 0x7f) as u8;
+            value >>= 7;
+            if value == 0 {
+                self.buf.push(byte);
+                return;
+            }
+            self.buf.push(byte | 0x80);
+        }
+    }
+    #[inline(always)]
+    fn write_signed(&mut self, value: i128) {
+        self.write_varint(((value << 1) ^ (value >> 127)) as u128);
+    }
+    #[inline]
+    fn write_len(&mut self, len: Option<usize>) -> RhaiResultOf<()> {
+        let len =
+            len.ok_or_else(|| <RhaiError as ser::Error>::custom("sequence length must be known"))?;
+        self.write_varint(len as u128);
+        Ok(())
+    }
+}
+
+impl<'a, 'b> Serializer for &'a mut BinarySerializer<'b> {
+    type Ok = ();
+    type Error = RhaiError;
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+    type SerializeMap = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+
+    #[inline(always)]
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+    #[inline(always)]
+    fn serialize_bool(self, v: bool) -> RhaiResultOf<()> {
+        self.buf.push(u8::from(v));
+        Ok(())
+    }
+    #[inline(always)]
+    fn serialize_i8(self, v: i8) -> RhaiResultOf<()> {
+        self.write_signed(v.into());
+        Ok(())
+    }
+    #[inline(always)]
+    fn serialize_i16(self, v: i16) -> RhaiResultOf<()> {
+        self.write_signed(v.into());
+        Ok(())
+    }
+    #[inline(always)]
+    fn serialize_i32(self, v: i32) -> RhaiResultOf<()> {
+        self.write_signed(v.into());
+        Ok(())
+    }
+    #[inline(always)]
+    fn serialize_i64(self, v: i64) -> RhaiResultOf<()> {
+        self.write_signed(v.into());
+        Ok(())
+    }
+    #[inline(always)]
+    fn serialize_i128(self, v: i128) -> RhaiResultOf<()> {
+        self.write_signed(v);
+        Ok(())
+    }
+    #[inline(always)]
+    fn serialize_u8(self, v: u8) -> RhaiResultOf<()> {
+        self.buf.push(v);
+        Ok(())
+    }
+    #[inline(always)]
+    fn serialize_u16(self, v: u16) -> RhaiResultOf<()> {
+        self.write_varint(v.into());
+        Ok(())
+    }
+    #[inline(always)]
+    fn serialize_u32(self, v: u32) -> RhaiResultOf<()> {
+        self.write_varint(v.into());
+        Ok(())
+    }
+    #[inline(always)]
+    fn serialize_u64(self, v: u64) -> RhaiResultOf<()> {
+        self.write_varint(v.into());
+        Ok(())
+    }
+    #[inline(always)]
+    fn serialize_u128(self, v: u128) -> RhaiResultOf<()> {
+        self.write_varint(v);
+        Ok(())
+    }
+    #[inline(always)]
+    fn serialize_f32(self, v: f32) -> RhaiResultOf<()> {
+        self.buf.extend_from_slice(&v.to_le_bytes());
+        Ok(())
+    }
+    #[inline(always)]
+    fn serialize_f64(self, v: f64) -> RhaiResultOf<()> {
+        self.buf.extend_from_slice(&v.to_le_bytes());
+        Ok(())
+    }
+    #[inline(always)]
+    fn serialize_char(self, v: char) -> RhaiResultOf<()> {
+        self.write_varint(u32::from(v).into());
+        Ok(())
+    }
+    #[inline(always)]
+    fn serialize_str(self, v: &str) -> RhaiResultOf<()> {
+        self.serialize_bytes(v.as_bytes())
+    }
+    #[inline]
+    fn serialize_bytes(self, v: &[u8]) -> RhaiResultOf<()> {
+        self.write_varint(v.len() as u128);
+        self.buf.extend_from_slice(v);
+        Ok(())
+    }
+    #[inline(always)]
+    fn serialize_none(self) -> RhaiResultOf<()> {
+        self.buf.push(0);
+        Ok(())
+    }
+    #[inline(always)]
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> RhaiResultOf<()> {
+        self.buf.push(1);
+        value.serialize(self)
+    }
+    #[inline(always)]
+    fn serialize_unit(self) -> RhaiResultOf<()> {
+        Ok(())
+    }
+    #[inline(always)]
+    fn serialize_unit_struct(self, _name: &'static str) -> RhaiResultOf<()> {
+        Ok(())
+    }
+    #[inline(always)]
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+    ) -> RhaiResultOf<()> {
+        self.write_varint(variant_index.into());
+        Ok(())
+    }
+    #[inline(always)]
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> RhaiResultOf<()> {
+        value.serialize(self)
+    }
+    #[inline(always)]
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        value: &T,
+    ) -> RhaiResultOf<()> {
+        self.write_varint(variant_index.into());
+        value.serialize(self)
+    }
+    #[inline(always)]
+    fn serialize_seq(self, len: Option<usize>) -> RhaiResultOf<Self::SerializeSeq> {
+        self.write_len(len)?;
+        Ok(self)
+    }
+    #[inline(always)]
+    fn serialize_tuple(self, _len: usize) -> RhaiResultOf<Self::SerializeTuple> {
+        Ok(self)
+    }
+    #[inline(always)]
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> RhaiResultOf<Self::SerializeTupleStruct> {
+        Ok(self)
+    }
+    #[inline(always)]
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> RhaiResultOf<Self::SerializeTupleVariant> {
+        self.write_varint(variant_index.into());
+        Ok(self)
+    }
+    #[inline(always)]
+    fn serialize_map(self, len: Option<usize>) -> RhaiResultOf<Self::SerializeMap> {
+        self.write_len(len)?;
+        Ok(self)
+    }
+    #[inline(always)]
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> RhaiResultOf<Self::SerializeStruct> {
+        Ok(self)
+    }
+    #[inline(always)]
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> RhaiResultOf<Self::SerializeStructVariant> {
+        self.write_varint(variant_index.into());
+        Ok(self)
+    }
+}
+
+impl SerializeSeq for &mut BinarySerializer<'_> {
+    type Ok = ();
+    type Error = RhaiError;
+
+    #[inline(always)]
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> RhaiResultOf<()> {
+        value.serialize(&mut **self)
+    }
+    #[inline(always)]
+    fn end(self) -> RhaiResultOf<()> {
+        Ok(())
+    }
+}
+
+impl SerializeTuple for &mut BinarySerializer<'_> {
+    type Ok = ();
+    type Error = RhaiError;
+
+    #[inline(always)]
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> RhaiResultOf<()> {
+        value.serialize(&mut **self)
+    }
+    #[inline(always)]
+    fn end(self) -> RhaiResultOf<()> {
+        Ok(())
+    }
+}
+
+impl SerializeTupleStruct for &mut BinarySerializer<'_> {
+    type Ok = ();
+    type Error = RhaiError;
+
+    #[inline(always)]
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> RhaiResultOf<()> {
+        value.serialize(&mut **self)
+    }
+    #[inline(always)]
+    fn end(self) -> RhaiResultOf<()> {
+        Ok(())
+    }
+}
+
+impl SerializeTupleVariant for &mut BinarySerializer<'_> {
+    type Ok = ();
+    type Error = RhaiError;
+
+    #[inline(always)]
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> RhaiResultOf<()> {
+        value.serialize(&mut **self)
+    }
+    #[inline(always)]
+    fn end(self) -> RhaiResultOf<()> {
+        Ok(())
+    }
+}
+
+impl SerializeMap for &mut BinarySerializer<'_> {
+    type Ok = ();
+    type Error = RhaiError;
+
+    #[inline(always)]
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> RhaiResultOf<()> {
+        key.serialize(&mut **self)
+    }
+    #[inline(always)]
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> RhaiResultOf<()> {
+        value.serialize(&mut **self)
+    }
+    #[inline(always)]
+    fn end(self) -> RhaiResultOf<()> {
+        Ok(())
+    }
+}
+
+impl SerializeStruct for &mut BinarySerializer<'_> {
+    type Ok = ();
+    type Error = RhaiError;
+
+    #[inline(always)]
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> RhaiResultOf<()> {
+        value.serialize(&mut **self)
+    }
+    #[cold]
+    #[inline(never)]
+    fn skip_field(&mut self, key: &'static str) -> RhaiResultOf<()> {
+        Err(<RhaiError as ser::Error>::custom(format!(
+            "cannot skip field `{key}`"
+        )))
+    }
+    #[inline(always)]
+    fn end(self) -> RhaiResultOf<()> {
+        Ok(())
+    }
+}
+
+impl SerializeStructVariant for &mut BinarySerializer<'_> {
+    type Ok = ();
+    type Error = RhaiError;
+
+    #[inline(always)]
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> RhaiResultOf<()> {
+        value.serialize(&mut **self)
+    }
+    #[cold]
+    #[inline(never)]
+    fn skip_field(&mut self, key: &'static str) -> RhaiResultOf<()> {
+        Err(<RhaiError as ser::Error>::custom(format!(
+            "cannot skip field `{key}`"
+        )))
+    }
+    #[inline(always)]
+    fn end(self) -> RhaiResultOf<()> {
+        Ok(())
+    }
+}
+
+/// Deserializer for the compact binary format.
+struct BinaryDeserializer<'de> {
+    input: &'de [u8],
+}
+
+impl<'de> BinaryDeserializer<'de> {
+    #[cold]
+    #[inline(never)]
+    fn eof<T>() -> RhaiResultOf<T> {
+        Err(<RhaiError as de::Error>::custom("unexpected end of data"))
+    }
+    #[inline]
+    fn read_u8(&mut self) -> RhaiResultOf<u8> {
+        match self.input.split_first() {
+            Some((&b, rest)) => {
+                self.input = rest;
+                Ok(b)
+            }
+            None => Self::eof(),
+        }
+    }
+    #[inline]
+    fn read_slice(&mut self, len: usize) -> RhaiResultOf<&'de [u8]> {
+        if len > self.input.len() {
+            return Self::eof();
+        }
+        let (bytes, rest) = self.input.split_at(len);
+        self.input = rest;
+        Ok(bytes)
+    }
+    fn read_varint(&mut self) -> RhaiResultOf<u128> {
+        let mut value = 0_u128;
+        let mut shift = 0;
+
+        loop {
+            let byte = self.read_u8()?;
+
+            if shift >= 128 {
+                return Err(<RhaiError as de::Error>::custom("integer too large"));
+            }
+
+            value |= u128::from(byte & 0x7f) << shift;
+
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+
+            shift += 7;
+        }
+    }
+    #[inline]
+    fn read_signed(&mut self) -> RhaiResultOf<i128> {
+        let value = self.read_varint()?;
+        Ok((value >> 1) as i128 ^ -((value & 1) as i128))
+    }
+    #[inline]
+    fn read_int<T: TryFrom<i128>>(&mut self) -> RhaiResultOf<T> {
+        T::try_from(self.read_signed()?)
+            .map_err(|_| <RhaiError as de::Error>::custom("integer out of range"))
+    }
+    #[inline]
+    fn read_uint<T: TryFrom<u128>>(&mut self) -> RhaiResultOf<T> {
+        T::try_from(self.read_varint()?)
+            .map_err(|_| <RhaiError as de::Error>::custom("integer out of range"))
+    }
+    #[inline]
+    fn read_bytes(&mut self) -> RhaiResultOf<&'de [u8]> {
+        let len = self.read_uint()?;
+        self.read_slice(len)
+    }
+}
+
+impl<'de> Deserializer<'de> for &mut BinaryDeserializer<'de> {
+    type Error = RhaiError;
+
+    #[inline(always)]
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+    #[cold]
+    #[inline(never)]
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> RhaiResultOf<V::Value> {
+        Err(<RhaiError as de::Error>::custom(
+            "data format is not self-describing",
+        ))
+    }
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> RhaiResultOf<V::Value> {
+        match self.read_u8()? {
+            0 => visitor.visit_bool(false),
+            1 => visitor.visit_bool(true),
+            _ => Err(<RhaiError as de::Error>::custom("invalid boolean")),
+        }
+    }
+    fn deserialize_i8<V: Visitor<'de>>(self, visitor: V) -> RhaiResultOf<V::Value> {
+        visitor.visit_i8(self.read_int()?)
+    }
+    fn deserialize_i16<V: Visitor<'de>>(self, visitor: V) -> RhaiResultOf<V::Value> {
+        visitor.visit_i16(self.read_int()?)
+    }
+    fn deserialize_i32<V: Visitor<'de>>(self, visitor: V) -> RhaiResultOf<V::Value> {
+        visitor.visit_i32(self.read_int()?)
+    }
+    fn deserialize_i64<V: Visitor<'de>>(self, visitor: V) -> RhaiResultOf<V::Value> {
+        visitor.visit_i64(self.read_int()?)
+    }
+    fn deserialize_i128<V: Visitor<'de>>(self, visitor: V) -> RhaiResultOf<V::Value> {
+        visitor.visit_i128(self.read_signed()?)
+    }
+    fn deserialize_u8<V: Visitor<'de>>(self, visitor: V) -> RhaiResultOf<V::Value> {
+        visitor.visit_u8(self.read_u8()?)
+    }
+    fn deserialize_u16<V: Visitor<'de>>(self, visitor: V) -> RhaiResultOf<V::Value> {
+        visitor.visit_u16(self.read_uint()?)
+    }
+    fn deserialize_u32<V: Visitor<'de>>(self, visitor: V) -> RhaiResultOf<V::Value> {
+        visitor.visit_u32(self.read_uint()?)
+    }
+    fn deserialize_u64<V: Visitor<'de>>(self, visitor: V) -> RhaiResultOf<V::Value> {
+        visitor.visit_u64(self.read_uint()?)
+    }
+    fn deserialize_u128<V: Visitor<'de>>(self, visitor: V) -> RhaiResultOf<V::Value> {
+        visitor.visit_u128(self.read_varint()?)
+    }
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> RhaiResultOf<V::Value> {
+        let bytes = self.read_slice(4)?;
+        visitor.visit_f32(f32::from_le_bytes(bytes.try_into().unwrap()))
+    }
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> RhaiResultOf<V::Value> {
+        let bytes = self.read_slice(8)?;
+        visitor.visit_f64(f64::from_le_bytes(bytes.try_into().unwrap()))
+    }
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> RhaiResultOf<V::Value> {
+        char::from_u32(self.read_uint()?)
+            .ok_or_else(|| <RhaiError as de::Error>::custom("invalid character"))
+            .and_then(|ch| visitor.visit_char(ch))
+    }
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> RhaiResultOf<V::Value> {
+        std::str::from_utf8(self.read_bytes()?)
+            .map_err(<RhaiError as de::Error>::custom)
+            .and_then(|s| visitor.visit_borrowed_str(s))
+    }
+    #[inline(always)]
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> RhaiResultOf<V::Value> {
+        self.deserialize_str(visitor)
+    }
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> RhaiResultOf<V::Value> {
+        visitor.visit_borrowed_bytes(self.read_bytes()?)
+    }
+    #[inline(always)]
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> RhaiResultOf<V::Value> {
+        self.deserialize_bytes(visitor)
+    }
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> RhaiResultOf<V::Value> {
+        match self.read_u8()? {
+            0 => visitor.visit_none(),
+            1 => visitor.visit_some(self),
+            _ => Err(<RhaiError as de::Error>::custom("invalid option")),
+        }
+    }
+    #[inline(always)]
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> RhaiResultOf<V::Value> {
+        visitor.visit_unit()
+    }
+    #[inline(always)]
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> RhaiResultOf<V::Value> {
+        visitor.visit_unit()
+    }
+    #[inline(always)]
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> RhaiResultOf<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> RhaiResultOf<V::Value> {
+        let len = self.read_uint()?;
+        visitor.visit_seq(Access { de: self, len })
+    }
+    #[inline(always)]
+    fn deserialize_tuple<V: Visitor<'de>>(self, len: usize, visitor: V) -> RhaiResultOf<V::Value> {
+        visitor.visit_seq(Access { de: self, len })
+    }
+    #[inline(always)]
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> RhaiResultOf<V::Value> {
+        self.deserialize_tuple(len, visitor)
+    }
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> RhaiResultOf<V::Value> {
+        let len = self.read_uint()?;
+        visitor.visit_map(Access { de: self, len })
+    }
+    #[inline(always)]
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> RhaiResultOf<V::Value> {
+        self.deserialize_tuple(fields.len(), visitor)
+    }
+    #[inline(always)]
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> RhaiResultOf<V::Value> {
+        visitor.visit_enum(self)
+    }
+    fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> RhaiResultOf<V::Value> {
+        visitor.visit_u32(self.read_uint()?)
+    }
+    #[cold]
+    #[inline(never)]
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, _visitor: V) -> RhaiResultOf<V::Value> {
+        Err(<RhaiError as de::Error>::custom(
+            "data format is not self-describing",
+        ))
+    }
+}
+
+impl<'de> EnumAccess<'de> for &mut BinaryDeserializer<'de> {
+    type Error = RhaiError;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> RhaiResultOf<(V::Value, Self)> {
+        let index: u32 = self.read_uint()?;
+        let value = seed.deserialize(IntoDeserializer::<RhaiError>::into_deserializer(index))?;
+        Ok((value, self))
+    }
+}
+
+impl<'de> VariantAccess<'de> for &mut BinaryDeserializer<'de> {
+    type Error = RhaiError;
+
+    #[inline(always)]
+    fn unit_variant(self) -> RhaiResultOf<()> {
+        Ok(())
+    }
+    #[inline(always)]
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> RhaiResultOf<T::Value> {
+        seed.deserialize(self)
+    }
+    #[inline(always)]
+    fn tuple_variant<V: Visitor<'de>>(self, len: usize, visitor: V) -> RhaiResultOf<V::Value> {
+        self.deserialize_tuple(len, visitor)
+    }
+    #[inline(always)]
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> RhaiResultOf<V::Value> {
+        self.deserialize_tuple(fields.len(), visitor)
+    }
+}
+
+/// Access to a fixed number of sequence items or map entries.
+struct Access<'a, 'de> {
+    de: &'a mut BinaryDeserializer<'de>,
+    len: usize,
+}
+
+impl<'de> SeqAccess<'de> for Access<'_, 'de> {
+    type Error = RhaiError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> RhaiResultOf<Option<T::Value>> {
+        if self.len == 0 {
+            return Ok(None);
+        }
+        self.len -= 1;
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+    #[inline(always)]
+    fn size_hint(&self) -> Option<usize> {
+        // Never trust the length to pre-allocate more than the remaining input
+        Some(self.len.min(self.de.input.len()))
+    }
+}
+
+impl<'de> MapAccess<'de> for Access<'_, 'de> {
+    type Error = RhaiError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> RhaiResultOf<Option<K::Value>> {
+        if self.len == 0 {
+            return Ok(None);
+        }
+        self.len -= 1;
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+    #[inline(always)]
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> RhaiResultOf<V::Value> {
+        seed.deserialize(&mut *self.de)
+    }
+    #[inline(always)]
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.len.min(self.de.input.len()))
+    }
+}
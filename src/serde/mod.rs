@@ -1,6 +1,8 @@
 //! _(serde)_ Serialization and deserialization support for [`serde`](https://crates.io/crates/serde).
 //! Exported under the `serde` feature only.
 
+pub(crate) mod ast;
+mod binary;
 mod de;
 mod deserialize;
 mod metadata;
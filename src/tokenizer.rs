@@ -522,7 +522,7 @@ static RESERVED_ASSOC_VALUES: [u8; 256] = [
     150, 45, 35, 30, 30, 150, 20, 15, 150, 150, 150, 150, 150, 150, 150, 150, 150, 150, 150, 35,
     30, 15, 5, 25, 0, 25, 150, 150, 150, 150, 150, 65, 150, 150, 150, 150, 150, 150, 150, 150, 150,
     150, 150, 150, 150, 150, 150, 150, 150, 150, 150, 150, 40, 150, 150, 150, 150, 150, 0, 150, 0,
-    0, 0, 15, 45, 10, 15, 150, 150, 35, 25, 10, 50, 0, 150, 5, 0, 15, 0, 5, 25, 45, 15, 150, 150,
+    0, 0, 15, 45, 10, 15, 150, 150, 35, 54, 10, 50, 0, 150, 5, 0, 15, 0, 5, 25, 45, 15, 150, 150,
     25, 150, 20, 150, 150, 150, 150, 150, 150, 150, 150, 150, 150, 150, 150, 150, 150, 150, 150,
     150, 150, 150, 150, 150, 150, 150, 150, 150, 150, 150, 150, 150, 150, 150, 150, 150, 150, 150,
     150, 150, 150, 150, 150, 150, 150, 150, 150, 150, 150, 150, 150, 150, 150, 150, 150, 150, 150,
@@ -568,11 +568,11 @@ static RESERVED_LIST: [(&str, bool, bool, bool); 150] = [
     ("", false, false, false),
     ("is_def_fn", cfg!(not(feature = "no_function")), true, false),
     ("yield", true, false, false),
-    ("", false, false, false),
+    ("struct", true, false, false),
     ("fn", cfg!(feature = "no_function"), false, false),
     ("new", true, false, false),
     ("call", true, true, true),
-    ("match", true, false, false),
+    ("", false, false, false),
     ("~", true, false, false),
     ("!.", true, false, false),
     ("", false, false, false),
@@ -593,15 +593,15 @@ static RESERVED_LIST: [(&str, bool, bool, bool); 150] = [
     ("", false, false, false),
     ("void", true, false, false),
     ("", false, false, false),
-    ("import", cfg!(feature = "no_module"), false, false),
+    ("", false, false, false),
     ("--", true, false, false),
     ("nil", true, false, false),
     ("exit", false, false, false),
     ("", false, false, false),
     ("export", cfg!(feature = "no_module"), false, false),
     ("<|", true, false, false),
-    ("", false, false, false),
-    ("", false, false, false),
+    ("enum", true, false, false),
+    ("match", true, false, false),
     ("", false, false, false),
     ("$", true, false, false),
     ("->", true, false, false),
@@ -613,7 +613,7 @@ static RESERVED_LIST: [(&str, bool, bool, bool); 150] = [
     ("", false, false, false),
     ("", false, false, false),
     ("", false, false, false),
-    ("module", true, false, false),
+    ("", false, false, false),
     ("?[", cfg!(feature = "no_index"), false, false),
     ("", false, false, false),
     ("", false, false, false),
@@ -622,7 +622,7 @@ static RESERVED_LIST: [(&str, bool, bool, bool); 150] = [
     ("Fn", true, true, false),
     ("::<", true, false, false),
     ("", false, false, false),
-    ("", false, false, false),
+    ("import", cfg!(feature = "no_module"), false, false),
     ("", false, false, false),
     ("++", true, false, false),
     ("", false, false, false),
@@ -642,7 +642,7 @@ static RESERVED_LIST: [(&str, bool, bool, bool); 150] = [
     ("(*", true, false, false),
     ("", false, false, false),
     ("", false, false, false),
-    ("", false, false, false),
+    ("module", true, false, false),
     ("", false, false, false),
     ("", false, false, false),
     ("", false, false, false),
@@ -2127,6 +2127,10 @@ fn get_next_token_inner(
                             *g += "\n";
                         }
                         *g += &comment;
+
+                        if state.include_comments {
+                            return (Token::Comment(comment.into()), start_pos);
+                        }
                     }
                     Some(comment) => return (Token::Comment(comment.into()), start_pos),
                     None => (),
@@ -2204,7 +2208,6 @@ fn get_next_token_inner(
             }
             ('=', ..) => return (Token::Equals, start_pos),
 
-            #[cfg(not(feature = "no_module"))]
             (':', ':') => {
                 stream.eat_next_and_advance(pos);
 
@@ -2678,8 +2681,6 @@ impl<'a> Iterator for TokenIterator<'a> {
                 ("!==", false) => Token::LexError(LERR::ImproperSymbol(s.to_string(),
                     "'!==' is not a valid operator. This is not JavaScript! Should it be '!='?".to_string(),
                 ).into()),
-                ("->", false) => Token::LexError(LERR::ImproperSymbol(s.to_string(),
-                    "'->' is not a valid symbol. This is not C or C++!".to_string()).into()),
                 ("<-", false) => Token::LexError(LERR::ImproperSymbol(s.to_string(),
                     "'<-' is not a valid symbol. This is not Go! Should it be '<='?".to_string(),
                 ).into()),
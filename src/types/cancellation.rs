@@ -0,0 +1,94 @@
+//! Module defining the cancellation of running evaluations.
+#![cfg(not(feature = "unchecked"))]
+
+use crate::func::{locked_read, locked_write, Locked, Shared};
+use crate::types::dynamic::Variant;
+use crate::Dynamic;
+use std::hash::{Hash, Hasher};
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// A cloneable handle to cancel running evaluations.
+///
+/// All clones of a [`CancellationHandle`] share the same state, so one clone can be kept by the
+/// evaluation while another one is triggered, possibly from another thread under the `sync`
+/// feature.
+///
+/// A cancelled evaluation fails with [`ErrorTerminated`][crate::EvalAltResult::ErrorTerminated]
+/// carrying the reason passed to [`cancel`][CancellationHandle::cancel]. The cancellation is
+/// checked at the same points as the [maximum number of operations][crate::Engine::set_max_operations],
+/// so a long-running native function call is not interrupted.
+///
+/// A [`CancellationHandle`] stays cancelled, failing all evaluations that check it, until it is
+/// [reset][CancellationHandle::reset].
+///
+/// Two [`CancellationHandle`] values are equal only if one is a clone of the other.
+///
+/// Not available under `unchecked`.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationHandle(Shared<CancellationState>);
+
+impl PartialEq for CancellationHandle {
+    #[inline(always)]
+    fn eq(&self, other: &Self) -> bool {
+        Shared::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for CancellationHandle {}
+
+impl Hash for CancellationHandle {
+    #[inline(always)]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Shared::as_ptr(&self.0).hash(state);
+    }
+}
+
+/// Shared state of a [`CancellationHandle`].
+#[derive(Debug, Default)]
+struct CancellationState {
+    /// Has the handle been cancelled?
+    cancelled: AtomicBool,
+    /// Reason for the cancellation.
+    reason: Locked<Dynamic>,
+}
+
+impl CancellationHandle {
+    /// Create a new [`CancellationHandle`].
+    #[inline(always)]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Cancel all evaluations checking this [`CancellationHandle`], with a reason.
+    ///
+    /// If already cancelled, the reason is replaced.
+    #[inline]
+    pub fn cancel(&self, reason: impl Variant + Clone) {
+        *locked_write(&self.0.reason).unwrap() = Dynamic::from(reason);
+        self.0.cancelled.store(true, Ordering::Release);
+    }
+    /// Has this [`CancellationHandle`] been cancelled?
+    #[inline(always)]
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.0.cancelled.load(Ordering::Acquire)
+    }
+    /// Get the reason for the cancellation, if cancelled.
+    #[inline]
+    #[must_use]
+    pub fn reason(&self) -> Option<Dynamic> {
+        if self.is_cancelled() {
+            Some(locked_read(&self.0.reason).unwrap().clone())
+        } else {
+            None
+        }
+    }
+    /// Reset this [`CancellationHandle`] so that evaluations can run again.
+    #[inline]
+    pub fn reset(&self) {
+        self.0.cancelled.store(false, Ordering::Release);
+        *locked_write(&self.0.reason).unwrap() = Dynamic::UNIT;
+    }
+}
@@ -0,0 +1,475 @@
+//! Module defining the continuation of a suspended evaluation.
+
+use crate::ast::{FnCallExpr, Stmt};
+use crate::eval::GlobalRuntimeState;
+use crate::{Dynamic, ImmutableString, Position, RhaiError, Scope, ERR};
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+
+/// A suspended evaluation, which can be resumed via [`Engine::resume`][crate::Engine::resume].
+///
+/// An evaluation is suspended when a native function calls
+/// [`NativeCallContext::suspend`][crate::NativeCallContext::suspend]. The evaluation then fails with
+/// [`ErrorTerminated`][crate::EvalAltResult::ErrorTerminated] carrying the [`Continuation`] as its
+/// token, which can be taken out via
+/// [`EvalAltResult::into_continuation`][crate::EvalAltResult::into_continuation].
+///
+/// # Statement Boundaries
+///
+/// An evaluation can only be suspended at a statement boundary, i.e. the suspending function call
+/// must be the whole value of a statement: `foo(...);`, `let x = foo(...);`, `x = foo(...);` (also
+/// compound assignments such as `x += foo(...);`) or `return foo(...);`. Such a statement may be
+/// nested in blocks, `if` branches, loops and `try` blocks, or in the body of a script-defined
+/// function that is itself called in the same way (directly by name, not as a method).
+///
+/// Suspending anywhere else fails with [`ErrorSystem`][crate::EvalAltResult::ErrorSystem].
+///
+/// # Resuming
+///
+/// A [`Continuation`] records the statements being run, and the variables in scope, at global
+/// level and in each script-defined function being called, together with the constants defined at
+/// global level. Resuming an evaluation restores them and completes the suspended statement with
+/// the value it is resumed with, so nothing that was run before the suspension is run again.
+/// The number of operations performed is kept as well, so limits such as
+/// [`max_operations`][crate::Engine::max_operations] apply to the evaluation as a whole.
+///
+/// For an enclosing `for` loop over an array or a BLOB, the items not yet iterated are recorded.
+/// Any other value iterated is recorded and iterated again, skipping the items already iterated;
+/// suspending within a `for` loop over a lazy iterator that calls functions (e.g. via `map`) fails
+/// because the functions would be called again. `import` statements in
+/// enclosing blocks are run again, so the module resolver should return the same modules.
+///
+/// A [`Continuation`] holds no reference to the [`Engine`][crate::Engine] or the
+/// [`AST`][crate::AST]. Under the `serde` feature, it can be serialized in order to resume the
+/// evaluation in another process, provided that all the values involved can be serialized.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Continuation {
+    /// Number of times the evaluation has been resumed before.
+    num_resumed: usize,
+    /// Number of operations performed before the suspension.
+    num_operations: u64,
+    /// Deadline of an asynchronous evaluation, which applies to the evaluation as a whole.
+    #[cfg(not(feature = "unchecked"))]
+    #[cfg(not(feature = "no_time"))]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    deadline: Option<crate::Instant>,
+    /// Value passed to the host by the suspension.
+    value: Dynamic,
+    /// Functions being run at the suspension point (innermost first), ending with global level.
+    frames: Vec<ResumeFrame>,
+    /// Constants defined at global level that are visible to functions.
+    #[cfg(not(feature = "no_module"))]
+    #[cfg(not(feature = "no_function"))]
+    constants: Vec<(ImmutableString, Dynamic)>,
+    /// Name of the function call being returned from, if it may still be at a statement boundary.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    callee: Option<ImmutableString>,
+    /// Number of function call expressions returned from within the current frame.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    num_calls: usize,
+    /// Index into the body of the statement to be entered when resuming, if any, together with
+    /// the value to iterate by a `for` loop and the number of its items to skip.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    entry: Option<(usize, Option<(Dynamic, usize)>)>,
+}
+
+impl Continuation {
+    /// Create a new [`Continuation`] for a suspension by a native function call.
+    #[inline]
+    #[must_use]
+    pub(crate) fn new(
+        global: &GlobalRuntimeState,
+        fn_name: &str,
+        value: Dynamic,
+        pos: Position,
+    ) -> Self {
+        Self {
+            num_resumed: global.num_resumed,
+            num_operations: global.num_operations,
+            #[cfg(not(feature = "unchecked"))]
+            #[cfg(not(feature = "no_time"))]
+            deadline: global.deadline.filter(|_| global.is_async),
+            value,
+            frames: vec![ResumeFrame {
+                pos,
+                ..ResumeFrame::default()
+            }],
+            #[cfg(not(feature = "no_module"))]
+            #[cfg(not(feature = "no_function"))]
+            constants: global
+                .constants
+                .as_ref()
+                .map_or_else(Vec::new, |constants| {
+                    crate::func::locked_read(constants)
+                        .unwrap()
+                        .iter()
+                        .map(|(name, value)| (name.clone(), value.clone()))
+                        .collect()
+                }),
+            callee: Some(fn_name.into()),
+            num_calls: 0,
+            entry: None,
+        }
+    }
+    /// Get the value passed to the host by the suspension.
+    #[inline(always)]
+    pub const fn value(&self) -> &Dynamic {
+        &self.value
+    }
+    /// Get the [position][Position] of the function call that suspended the evaluation.
+    #[inline(always)]
+    #[must_use]
+    pub fn position(&self) -> Position {
+        self.frames[0].pos
+    }
+    /// Get the number of times the evaluation has been resumed before.
+    #[inline(always)]
+    #[must_use]
+    pub const fn num_resumed(&self) -> usize {
+        self.num_resumed
+    }
+    /// Get an iterator over the script-defined functions being called at the suspension point,
+    /// together with the [positions][Position] of the calls.
+    ///
+    /// The outermost function call is iterated first.
+    #[inline]
+    pub fn call_stack(&self) -> impl Iterator<Item = (&str, Position)> {
+        self.frames
+            .iter()
+            .zip(self.frames.iter().skip(1))
+            .rev()
+            .filter_map(|(frame, caller)| {
+                frame
+                    .fn_name
+                    .as_ref()
+                    .map(|(name, ..)| (name.as_str(), caller.pos))
+            })
+    }
+    /// Get the variables in scope at the suspension point.
+    ///
+    /// If the suspension point is inside a function, only the variables of that function are
+    /// included.
+    #[inline(always)]
+    #[must_use]
+    pub fn scope(&self) -> &Scope<'static> {
+        &self.frames[0].scope
+    }
+    /// Restore the state needed to resume the evaluation into a [`GlobalRuntimeState`].
+    ///
+    /// Returns the frames being run (outermost first, starting with global level), each with the
+    /// statements being run (outermost first).
+    #[must_use]
+    pub(crate) fn into_resume_state(self, global: &mut GlobalRuntimeState) -> Vec<ResumeFrame> {
+        global.num_resumed = self.num_resumed + 1;
+        global.num_operations = self.num_operations;
+        #[cfg(not(feature = "unchecked"))]
+        #[cfg(not(feature = "no_time"))]
+        if self.deadline.is_some() {
+            global.deadline = self.deadline;
+        }
+
+        #[cfg(not(feature = "no_module"))]
+        #[cfg(not(feature = "no_function"))]
+        if !self.constants.is_empty() {
+            global.constants = Some(crate::Shared::new(crate::Locked::new(
+                self.constants.into_iter().collect(),
+            )));
+        }
+
+        let mut frames = self.frames;
+        frames.reverse();
+        frames.iter_mut().for_each(|frame| frame.path.reverse());
+        frames
+    }
+    /// Is an error a suspension carrying a [`Continuation`]?
+    #[inline(always)]
+    #[must_use]
+    pub(crate) fn is_suspension(err: &ERR) -> bool {
+        matches!(err, ERR::ErrorTerminated(token, ..) if token.is::<Self>())
+    }
+    /// Is the innermost statement of the current frame at a statement boundary?
+    #[must_use]
+    fn is_at_boundary(&self, stmt: &Stmt) -> bool {
+        self.frames
+            .last()
+            .map_or(false, |frame| frame.path.is_empty())
+            && self.num_calls == 1
+            && self.callee.is_some()
+            && resume_call(stmt).is_some()
+    }
+    /// Record the statement being run within a block, and the variables in scope, into the
+    /// [`Continuation`] carried by an error, if any.
+    ///
+    /// The innermost statement of each frame must be at a statement boundary, otherwise the error
+    /// is replaced by [`ErrorSystem`][ERR::ErrorSystem].
+    #[inline(always)]
+    pub(crate) fn record_statement(
+        err: RhaiError,
+        scope: &Scope,
+        statements: &[Stmt],
+        stmt: &Stmt,
+        scope_len: usize,
+    ) -> RhaiError {
+        if Self::is_suspension(&err) {
+            Self::record_statement_raw(err, scope, statements, stmt, scope_len)
+        } else {
+            err
+        }
+    }
+    #[cold]
+    #[inline(never)]
+    fn record_statement_raw(
+        mut err: RhaiError,
+        scope: &Scope,
+        statements: &[Stmt],
+        stmt: &Stmt,
+        scope_len: usize,
+    ) -> RhaiError {
+        let index = statements
+            .iter()
+            .position(|s| std::ptr::eq(s, stmt))
+            .expect("`stmt` is in `statements`");
+
+        if let ERR::ErrorTerminated(ref mut token, ..) = *err {
+            if let Some(mut continuation) = token.write_lock::<Self>() {
+                let (entry, iterable) = match continuation.entry.take() {
+                    Some(entry) => entry,
+                    None if continuation.is_at_boundary(stmt) => {
+                        continuation.frames.last_mut().unwrap().scope = scope.clone_static();
+                        (0, None)
+                    }
+                    None => return cannot_suspend_err(continuation.position()),
+                };
+
+                continuation
+                    .frames
+                    .last_mut()
+                    .unwrap()
+                    .path
+                    .push(ResumePoint {
+                        index,
+                        scope_len,
+                        entry,
+                        iterable,
+                    });
+            }
+        }
+        err
+    }
+    /// Record that the statement being run can be entered when resuming, into the
+    /// [`Continuation`] carried by an error, if any.
+    ///
+    /// The error must come from a block directly in the body of the statement, which is
+    /// identified by `entry` (e.g. the branch of an `if` statement).
+    #[inline(always)]
+    pub(crate) fn record_entry(err: RhaiError, entry: usize) -> RhaiError {
+        if Self::is_suspension(&err) {
+            Self::record_entry_raw(err, entry)
+        } else {
+            err
+        }
+    }
+    #[cold]
+    #[inline(never)]
+    fn record_entry_raw(mut err: RhaiError, entry: usize) -> RhaiError {
+        if let ERR::ErrorTerminated(ref mut token, ..) = *err {
+            if let Some(mut continuation) = token.write_lock::<Self>() {
+                continuation.entry = Some((entry, None));
+            }
+        }
+        err
+    }
+    /// Record that the body of a `for` loop can be entered when resuming, into the
+    /// [`Continuation`] carried by an error, if any.
+    ///
+    /// `entry` is the index of the iteration, and `iterable` is the value to iterate when resuming,
+    /// together with the number of its items to skip.
+    ///
+    /// The error must be a suspension.
+    #[cold]
+    #[inline(never)]
+    pub(crate) fn record_iteration(
+        mut err: RhaiError,
+        entry: usize,
+        iterable: (Dynamic, usize),
+    ) -> RhaiError {
+        if let ERR::ErrorTerminated(ref mut token, ..) = *err {
+            if let Some(mut continuation) = token.write_lock::<Self>() {
+                // Functions of lazy iterators would be called again when skipping items
+                let calls_functions = iterable
+                    .0
+                    .read_lock::<crate::packages::iter_basic::LazyIterator>()
+                    .map_or(false, |iter| iter.calls_functions());
+
+                if calls_functions {
+                    return cannot_suspend_err(continuation.position());
+                }
+
+                continuation.entry = Some((entry, Some(iterable)));
+            }
+        }
+        err
+    }
+    /// Shift the index of the statement last recorded, into the [`Continuation`] carried by an
+    /// error, if any.
+    ///
+    /// This is used when the remaining statements of a block are run separately after resuming.
+    #[inline(always)]
+    pub(crate) fn record_offset(err: RhaiError, offset: usize, scope_len: usize) -> RhaiError {
+        if Self::is_suspension(&err) {
+            Self::record_offset_raw(err, offset, scope_len)
+        } else {
+            err
+        }
+    }
+    #[cold]
+    #[inline(never)]
+    fn record_offset_raw(mut err: RhaiError, offset: usize, scope_len: usize) -> RhaiError {
+        if let ERR::ErrorTerminated(ref mut token, ..) = *err {
+            if let Some(mut continuation) = token.write_lock::<Self>() {
+                if let Some(point) = continuation.frames.last_mut().unwrap().path.last_mut() {
+                    point.index += offset;
+                    point.scope_len = scope_len;
+                }
+            }
+        }
+        err
+    }
+    /// Record a function call expression being returned from, into the [`Continuation`] carried
+    /// by an error, if any.
+    #[inline(always)]
+    pub(crate) fn record_fn_call(err: RhaiError, x: &FnCallExpr) -> RhaiError {
+        if Self::is_suspension(&err) {
+            Self::record_fn_call_raw(err, x)
+        } else {
+            err
+        }
+    }
+    #[cold]
+    #[inline(never)]
+    fn record_fn_call_raw(mut err: RhaiError, x: &FnCallExpr) -> RhaiError {
+        if let ERR::ErrorTerminated(ref mut token, ..) = *err {
+            if let Some(mut continuation) = token.write_lock::<Self>() {
+                let is_callee = continuation
+                    .callee
+                    .as_ref()
+                    .map_or(false, |name| *name == x.name);
+
+                if !is_callee || x.capture_parent_scope {
+                    continuation.callee = None;
+                }
+
+                continuation.num_calls += 1;
+            }
+        }
+        err
+    }
+    /// Record a script-defined function call into the [`Continuation`] carried by an error, if any.
+    ///
+    /// If the function is not `resumable` (e.g. a method call), the error is replaced by
+    /// [`ErrorSystem`][ERR::ErrorSystem].
+    #[cfg(not(feature = "no_function"))]
+    #[cold]
+    #[inline(never)]
+    pub(crate) fn record_call(
+        err: &mut ERR,
+        fn_def: &crate::ast::ScriptFuncDef,
+        resumable: bool,
+        pos: Position,
+    ) {
+        let ERR::ErrorTerminated(ref mut token, ..) = err else {
+            return;
+        };
+
+        let rejected = match token.write_lock::<Self>() {
+            Some(mut continuation) => {
+                let frame = continuation.frames.last_mut().unwrap();
+
+                if resumable && !frame.path.is_empty() {
+                    frame.fn_name = Some((fn_def.name.clone(), fn_def.params.len()));
+                    continuation.frames.push(ResumeFrame {
+                        pos,
+                        ..ResumeFrame::default()
+                    });
+                    continuation.callee = Some(fn_def.name.clone());
+                    continuation.num_calls = 0;
+                    None
+                } else {
+                    Some(continuation.position())
+                }
+            }
+            None => None,
+        };
+
+        if let Some(pos) = rejected {
+            *err = *cannot_suspend_err(pos);
+        }
+    }
+}
+
+/// A script-defined function, or global level, being run at the suspension point.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct ResumeFrame {
+    /// Name and number of parameters of the function, or `None` at global level.
+    pub fn_name: Option<(ImmutableString, usize)>,
+    /// [Position] of the function call being run by the innermost statement.
+    pub pos: Position,
+    /// Statements being run.
+    pub path: Vec<ResumePoint>,
+    /// Variables in scope at the innermost statement.
+    pub scope: Scope<'static>,
+}
+
+/// A statement being run at the suspension point, within a block.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct ResumePoint {
+    /// Index of the statement within the block.
+    pub index: usize,
+    /// Number of variables in scope when the block was entered.
+    pub scope_len: usize,
+    /// Index into the body of the statement to be entered when resuming (e.g. the branch of an
+    /// `if` statement or the iteration of a `for` loop).
+    pub entry: usize,
+    /// Value to iterate by a `for` loop, if any, and the number of its items to skip.
+    pub iterable: Option<(Dynamic, usize)>,
+}
+
+/// Get the function call that provides the whole value of a statement, if any, together with its
+/// [position][Position].
+///
+/// An evaluation can only be suspended and resumed at such a function call.
+#[must_use]
+pub(crate) fn resume_call(stmt: &Stmt) -> Option<(&FnCallExpr, Position)> {
+    let (x, pos) = match stmt {
+        Stmt::FnCall(x, pos) => (&**x, *pos),
+        Stmt::Expr(expr) | Stmt::Return(Some(expr), ..) => match **expr {
+            crate::ast::Expr::FnCall(ref x, pos) => (&**x, pos),
+            _ => return None,
+        },
+        Stmt::Var(x, ..) => match x.1 {
+            crate::ast::Expr::FnCall(ref x, pos) => (&**x, pos),
+            _ => return None,
+        },
+        Stmt::Assignment(x) => match x.1.rhs {
+            crate::ast::Expr::FnCall(ref x, pos) => (&**x, pos),
+            _ => return None,
+        },
+        _ => return None,
+    };
+
+    (!x.capture_parent_scope).then_some((x, pos))
+}
+
+/// Create an error for a suspension that is not at a statement boundary.
+#[cold]
+#[inline(never)]
+fn cannot_suspend_err(pos: Position) -> RhaiError {
+    ERR::ErrorSystem(
+        "Cannot suspend evaluation".into(),
+        format!("function call at {pos} is not the whole value of a statement").into(),
+    )
+    .into()
+}
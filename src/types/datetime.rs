@@ -0,0 +1,877 @@
+//! Module defining the calendar date/time type.
+
+use crate::{Position, RhaiResultOf, ERR};
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt::{self, Write};
+use std::hash::{Hash, Hasher};
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+
+#[cfg(not(feature = "no_std"))]
+#[cfg(any(not(target_family = "wasm"), not(target_os = "unknown")))]
+use std::time::SystemTime;
+
+#[cfg(not(feature = "no_std"))]
+#[cfg(all(target_family = "wasm", target_os = "unknown"))]
+use instant::SystemTime;
+
+/// Number of seconds in a day.
+const SECS_PER_DAY: i64 = 86_400;
+/// Number of nanoseconds in a second.
+const NANOS_PER_SEC: u32 = 1_000_000_000;
+/// Limit (exclusive) of the offset from UTC, in minutes.
+const MAX_OFFSET: i32 = 24 * 60;
+/// Earliest supported local date/time (0000-01-01T00:00:00), in seconds since the Unix epoch.
+const MIN_LOCAL_SECS: i64 = -62_167_219_200;
+/// Latest supported local date/time (9999-12-31T23:59:59), in seconds since the Unix epoch.
+const MAX_LOCAL_SECS: i64 = 253_402_300_799;
+
+/// Names of the days of the week, starting from Monday.
+const WEEKDAY_NAMES: [&str; 7] = [
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+    "Sunday",
+];
+/// Names of the months.
+const MONTH_NAMES: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+/// Is a year a leap year?
+#[inline]
+#[must_use]
+const fn is_leap_year(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+/// Get the number of days in a month.
+#[inline]
+#[must_use]
+const fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Get the number of days since the Unix epoch of a date in the proleptic Gregorian calendar.
+#[must_use]
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    // Years start from March so that the leap day is the last day of the year.
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * i64::from((month + 9) % 12) + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Get the date, in the proleptic Gregorian calendar, of a number of days since the Unix epoch.
+#[must_use]
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_from_march + 2) / 5 + 1) as u32;
+    let month = if month_from_march < 10 {
+        month_from_march + 3
+    } else {
+        month_from_march - 9
+    } as u32;
+    let year = year_of_era + era * 400;
+
+    (if month <= 2 { year + 1 } else { year }, month, day)
+}
+
+/// Write an offset from UTC, in minutes, in the form `+hhmm` or `+hh:mm`.
+fn write_offset(f: &mut impl Write, offset: i32, colon: bool) -> fmt::Result {
+    let sign = if offset < 0 { '-' } else { '+' };
+    let offset = offset.unsigned_abs();
+    let separator = if colon { ":" } else { "" };
+    write!(f, "{sign}{:02}{separator}{:02}", offset / 60, offset % 60)
+}
+
+/// A calendar date and time with a fixed offset from UTC.
+///
+/// A [`DateTime`] is a point in time, with nanosecond precision, together with the offset from UTC
+/// under which its calendar components (e.g. year, month, hour) are shown. There is no time zone
+/// database, so offsets never change due to daylight saving time.
+///
+/// Dates are in the proleptic Gregorian calendar, with local dates from year 0 to year 9999.
+///
+/// Two [`DateTime`] values are compared by the point in time they represent, regardless of their
+/// offsets.
+///
+/// A [`DateTime`] is displayed in [RFC 3339](https://www.rfc-editor.org/rfc/rfc3339) format, e.g.
+/// `2024-01-15T09:30:00+08:00`.
+#[derive(Clone, Copy)]
+pub struct DateTime {
+    /// Number of seconds since the Unix epoch (1970-01-01T00:00:00Z).
+    secs: i64,
+    /// Nanoseconds within the second.
+    nanos: u32,
+    /// Offset from UTC, in minutes.
+    offset: i32,
+}
+
+impl PartialEq for DateTime {
+    #[inline(always)]
+    fn eq(&self, other: &Self) -> bool {
+        self.secs == other.secs && self.nanos == other.nanos
+    }
+}
+
+impl Eq for DateTime {}
+
+impl PartialOrd for DateTime {
+    #[inline(always)]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for DateTime {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.secs
+            .cmp(&other.secs)
+            .then(self.nanos.cmp(&other.nanos))
+    }
+}
+
+impl Hash for DateTime {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.secs.hash(state);
+        self.nanos.hash(state);
+    }
+}
+
+impl fmt::Debug for DateTime {
+    #[cold]
+    #[inline(never)]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl fmt::Display for DateTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (year, month, day) = self.date();
+        let (hour, minute, second) = self.time();
+
+        write!(
+            f,
+            "{year:04}-{month:02}-{day:02}T{hour:02}:{minute:02}:{second:02}"
+        )?;
+
+        match self.nanos {
+            0 => (),
+            n if n % 1_000_000 == 0 => write!(f, ".{:03}", n / 1_000_000)?,
+            n if n % 1_000 == 0 => write!(f, ".{:06}", n / 1_000)?,
+            n => write!(f, ".{n:09}")?,
+        }
+
+        if self.offset == 0 {
+            f.write_char('Z')
+        } else {
+            write_offset(f, self.offset, true)
+        }
+    }
+}
+
+impl DateTime {
+    /// The Unix epoch, 1970-01-01T00:00:00Z.
+    pub const UNIX_EPOCH: Self = Self {
+        secs: 0,
+        nanos: 0,
+        offset: 0,
+    };
+
+    /// Create a new [`DateTime`] in UTC from calendar components.
+    ///
+    /// Returns [`None`] if any component is out of range.
+    #[inline]
+    #[must_use]
+    pub fn new(
+        year: i32,
+        month: u32,
+        day: u32,
+        hour: u32,
+        minute: u32,
+        second: u32,
+    ) -> Option<Self> {
+        Self::from_local(year.into(), month, day, hour, minute, second, 0, 0)
+    }
+    /// Create a new [`DateTime`] in UTC from the number of seconds (plus nanoseconds) since the
+    /// Unix epoch.
+    ///
+    /// Returns [`None`] if the date/time is out of range.
+    #[inline]
+    #[must_use]
+    pub fn from_unix_timestamp(secs: i64, nanos: u32) -> Option<Self> {
+        Self::checked_new(secs, nanos, 0)
+    }
+    /// Create a new [`DateTime`] in UTC containing the current system time.
+    ///
+    /// Not available under `no_std`.
+    #[cfg(not(feature = "no_std"))]
+    #[inline]
+    #[must_use]
+    #[allow(clippy::cast_possible_wrap)]
+    pub fn now() -> Self {
+        let duration = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default();
+
+        Self {
+            secs: duration.as_secs() as i64,
+            nanos: duration.subsec_nanos(),
+            offset: 0,
+        }
+    }
+    /// Create a new [`DateTime`], checking that it is within range.
+    #[must_use]
+    fn checked_new(secs: i64, nanos: u32, offset: i32) -> Option<Self> {
+        if nanos >= NANOS_PER_SEC || offset <= -MAX_OFFSET || offset >= MAX_OFFSET {
+            return None;
+        }
+
+        let local = secs.checked_add(i64::from(offset) * 60)?;
+
+        if (MIN_LOCAL_SECS..=MAX_LOCAL_SECS).contains(&local) {
+            Some(Self {
+                secs,
+                nanos,
+                offset,
+            })
+        } else {
+            None
+        }
+    }
+    /// Create a new [`DateTime`] from local calendar components and an offset from UTC.
+    #[must_use]
+    fn from_local(
+        year: i64,
+        month: u32,
+        day: u32,
+        hour: u32,
+        minute: u32,
+        second: u32,
+        nanos: u32,
+        offset: i32,
+    ) -> Option<Self> {
+        if !(0..=9999).contains(&year)
+            || !(1..=12).contains(&month)
+            || day == 0
+            || day > days_in_month(year, month)
+            || hour > 23
+            || minute > 59
+            || second > 59
+        {
+            return None;
+        }
+
+        let local = days_from_civil(year, month, day) * SECS_PER_DAY
+            + i64::from(hour * 3600 + minute * 60 + second);
+
+        Self::checked_new(local - i64::from(offset) * 60, nanos, offset)
+    }
+
+    /// Get the number of seconds since the Unix epoch, in local time.
+    #[inline(always)]
+    #[must_use]
+    const fn local_secs(&self) -> i64 {
+        self.secs + self.offset as i64 * 60
+    }
+    /// Get the number of days since the Unix epoch, in local time.
+    #[inline(always)]
+    #[must_use]
+    const fn local_days(&self) -> i64 {
+        self.local_secs().div_euclid(SECS_PER_DAY)
+    }
+    /// Get the local year, month and day.
+    #[inline(always)]
+    #[must_use]
+    fn date(&self) -> (i64, u32, u32) {
+        civil_from_days(self.local_days())
+    }
+    /// Get the local hour, minute and second.
+    #[inline]
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    const fn time(&self) -> (u32, u32, u32) {
+        let secs = self.local_secs().rem_euclid(SECS_PER_DAY) as u32;
+        (secs / 3600, secs / 60 % 60, secs % 60)
+    }
+
+    /// Get the year.
+    #[inline]
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn year(&self) -> i32 {
+        self.date().0 as i32
+    }
+    /// Get the month, from 1 to 12.
+    #[inline]
+    #[must_use]
+    pub fn month(&self) -> u32 {
+        self.date().1
+    }
+    /// Get the day of the month, from 1 to 31.
+    #[inline]
+    #[must_use]
+    pub fn day(&self) -> u32 {
+        self.date().2
+    }
+    /// Get the hour, from 0 to 23.
+    #[inline(always)]
+    #[must_use]
+    pub const fn hour(&self) -> u32 {
+        self.time().0
+    }
+    /// Get the minute, from 0 to 59.
+    #[inline(always)]
+    #[must_use]
+    pub const fn minute(&self) -> u32 {
+        self.time().1
+    }
+    /// Get the second, from 0 to 59.
+    #[inline(always)]
+    #[must_use]
+    pub const fn second(&self) -> u32 {
+        self.time().2
+    }
+    /// Get the nanoseconds within the second.
+    #[inline(always)]
+    #[must_use]
+    pub const fn nanosecond(&self) -> u32 {
+        self.nanos
+    }
+    /// Get the day of the week, from 1 (Monday) to 7 (Sunday).
+    #[inline]
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub const fn weekday(&self) -> u32 {
+        // The Unix epoch is a Thursday
+        (self.local_days() + 3).rem_euclid(7) as u32 + 1
+    }
+    /// Get the day of the year, from 1 to 366.
+    #[inline]
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn ordinal(&self) -> u32 {
+        let days = self.local_days();
+        (days - days_from_civil(civil_from_days(days).0, 1, 1)) as u32 + 1
+    }
+    /// Get the offset from UTC, in minutes.
+    #[inline(always)]
+    #[must_use]
+    pub const fn offset(&self) -> i32 {
+        self.offset
+    }
+    /// Get the number of seconds since the Unix epoch.
+    #[inline(always)]
+    #[must_use]
+    pub const fn unix_timestamp(&self) -> i64 {
+        self.secs
+    }
+    /// Get the number of nanoseconds from `earlier` to this [`DateTime`].
+    ///
+    /// The result is negative if `earlier` is later than this [`DateTime`].
+    #[inline]
+    #[must_use]
+    pub fn nanoseconds_since(&self, earlier: &Self) -> i128 {
+        (i128::from(self.secs) - i128::from(earlier.secs)) * i128::from(NANOS_PER_SEC)
+            + i128::from(self.nanos)
+            - i128::from(earlier.nanos)
+    }
+
+    /// Return the same point in time under a different offset from UTC, in minutes.
+    ///
+    /// Returns [`None`] if the offset is not within 24 hours, or if the local date/time is out of range.
+    #[inline]
+    #[must_use]
+    pub fn with_offset(&self, offset: i32) -> Option<Self> {
+        Self::checked_new(self.secs, self.nanos, offset)
+    }
+    /// Add a number of nanoseconds, which may be negative.
+    ///
+    /// Returns [`None`] if the result is out of range.
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn checked_add_nanoseconds(&self, nanos: i128) -> Option<Self> {
+        let total = (i128::from(self.secs) * i128::from(NANOS_PER_SEC) + i128::from(self.nanos))
+            .checked_add(nanos)?;
+        let secs = i64::try_from(total.div_euclid(i128::from(NANOS_PER_SEC))).ok()?;
+        let nanos = total.rem_euclid(i128::from(NANOS_PER_SEC)) as u32;
+
+        Self::checked_new(secs, nanos, self.offset)
+    }
+    /// Add a number of seconds, which may be negative.
+    ///
+    /// Returns [`None`] if the result is out of range.
+    #[inline]
+    #[must_use]
+    pub fn checked_add_seconds(&self, secs: i64) -> Option<Self> {
+        Self::checked_new(self.secs.checked_add(secs)?, self.nanos, self.offset)
+    }
+    /// Add a number of days, which may be negative.
+    ///
+    /// Returns [`None`] if the result is out of range.
+    #[inline]
+    #[must_use]
+    pub fn checked_add_days(&self, days: i64) -> Option<Self> {
+        self.checked_add_seconds(days.checked_mul(SECS_PER_DAY)?)
+    }
+    /// Add a number of calendar months, which may be negative.
+    ///
+    /// The day of the month is kept, unless it does not exist in the resulting month, in which
+    /// case the last day of that month is used (e.g. one month after January 31 is February 28 or 29).
+    ///
+    /// Returns [`None`] if the result is out of range.
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn checked_add_months(&self, months: i64) -> Option<Self> {
+        let (year, month, day) = self.date();
+        let (hour, minute, second) = self.time();
+
+        let months = (year * 12 + i64::from(month) - 1).checked_add(months)?;
+        let (year, month) = (months.div_euclid(12), months.rem_euclid(12) as u32 + 1);
+        let day = day.min(days_in_month(year, month));
+
+        Self::from_local(
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+            self.nanos,
+            self.offset,
+        )
+    }
+
+    /// Format this [`DateTime`] with a format string.
+    ///
+    /// The following specifiers are replaced by the corresponding components:
+    ///
+    /// | Specifier   | Component                                 | Example     |
+    /// |-------------|-------------------------------------------|-------------|
+    /// | `%Y`        | year                                      | `2024`      |
+    /// | `%y`        | year within the century                   | `24`        |
+    /// | `%m`        | month                                     | `01`        |
+    /// | `%b` / `%B` | abbreviated / full month name             | `Jan`       |
+    /// | `%d` / `%e` | day of the month, zero / space padded     | `05`        |
+    /// | `%j`        | day of the year                           | `005`       |
+    /// | `%a` / `%A` | abbreviated / full weekday name           | `Fri`       |
+    /// | `%u` / `%w` | weekday, from Monday = 1 / Sunday = 0     | `5`         |
+    /// | `%H`        | hour                                      | `09`        |
+    /// | `%I` / `%p` | hour in 12-hour clock / `AM` or `PM`      | `09`        |
+    /// | `%M`        | minute                                    | `30`        |
+    /// | `%S`        | second                                    | `00`        |
+    /// | `%f`        | nanoseconds                               | `500000000` |
+    /// | `%3f` / `%6f` / `%9f` | milli- / micro- / nanoseconds   | `500`       |
+    /// | `%z` / `%:z`| offset from UTC                           | `+0800`     |
+    /// | `%s`        | number of seconds since the Unix epoch    | `1704418200`|
+    /// | `%F`        | same as `%Y-%m-%d`                        |             |
+    /// | `%T`        | same as `%H:%M:%S`                        |             |
+    /// | `%%`        | a literal `%`                             |             |
+    pub fn format(&self, format: &str) -> RhaiResultOf<String> {
+        let (year, month, day) = self.date();
+        let (hour, minute, second) = self.time();
+        let month_name = MONTH_NAMES[month as usize - 1];
+        let weekday_name = WEEKDAY_NAMES[self.weekday() as usize - 1];
+
+        let mut buf = String::with_capacity(format.len() + 16);
+        let mut chars = format.chars();
+
+        while let Some(ch) = chars.next() {
+            if ch != '%' {
+                buf.push(ch);
+                continue;
+            }
+
+            match chars.next() {
+                Some('Y') => write!(buf, "{year:04}"),
+                Some('y') => write!(buf, "{:02}", year % 100),
+                Some('m') => write!(buf, "{month:02}"),
+                Some('b') => buf.write_str(&month_name[..3]),
+                Some('B') => buf.write_str(month_name),
+                Some('d') => write!(buf, "{day:02}"),
+                Some('e') => write!(buf, "{day:>2}"),
+                Some('j') => write!(buf, "{:03}", self.ordinal()),
+                Some('a') => buf.write_str(&weekday_name[..3]),
+                Some('A') => buf.write_str(weekday_name),
+                Some('u') => write!(buf, "{}", self.weekday()),
+                Some('w') => write!(buf, "{}", self.weekday() % 7),
+                Some('H') => write!(buf, "{hour:02}"),
+                Some('I') => write!(buf, "{:02}", (hour + 11) % 12 + 1),
+                Some('p') => buf.write_str(if hour < 12 { "AM" } else { "PM" }),
+                Some('M') => write!(buf, "{minute:02}"),
+                Some('S') => write!(buf, "{second:02}"),
+                Some('f') => write!(buf, "{:09}", self.nanos),
+                Some(digits @ ('3' | '6' | '9')) if chars.next() == Some('f') => {
+                    let digits = digits.to_digit(10).unwrap();
+                    let value = self.nanos / 10_u32.pow(9 - digits);
+                    write!(buf, "{value:0width$}", width = digits as usize)
+                }
+                Some(':') if chars.next() == Some('z') => write_offset(&mut buf, self.offset, true),
+                Some('z') => write_offset(&mut buf, self.offset, false),
+                Some('s') => write!(buf, "{}", self.secs),
+                Some('F') => write!(buf, "{year:04}-{month:02}-{day:02}"),
+                Some('T') => write!(buf, "{hour:02}:{minute:02}:{second:02}"),
+                Some('%') => buf.write_char('%'),
+                _ => {
+                    return Err(ERR::ErrorArithmetic(
+                        format!("Invalid date/time format string: '{format}'"),
+                        Position::NONE,
+                    )
+                    .into())
+                }
+            }
+            .unwrap();
+        }
+
+        Ok(buf)
+    }
+
+    /// Parse an [RFC 3339](https://www.rfc-editor.org/rfc/rfc3339) date/time string, e.g.
+    /// `2024-01-15T09:30:00Z` or `2024-01-15T09:30:00.5+08:00`.
+    ///
+    /// The offset from UTC in the string is kept.
+    pub fn parse_rfc3339(string: &str) -> RhaiResultOf<Self> {
+        let mut input = Scanner(string);
+
+        let result = (|| {
+            let year = input.number(4, 4)?;
+            input.expect('-')?;
+            let month = input.number(2, 2)?;
+            input.expect('-')?;
+            let day = input.number(2, 2)?;
+            if !input.eat('T') && !input.eat('t') && !input.eat(' ') {
+                return Err("expected 'T'".into());
+            }
+            let hour = input.number(2, 2)?;
+            input.expect(':')?;
+            let minute = input.number(2, 2)?;
+            input.expect(':')?;
+            let second = input.number(2, 2)?;
+            let nanos = if input.eat('.') { input.fraction()? } else { 0 };
+            let offset = input.offset()?;
+            input.end()?;
+
+            Self::from_local(year.into(), month, day, hour, minute, second, nanos, offset)
+                .ok_or_else(|| String::from("invalid date/time"))
+        })();
+
+        result.map_err(|reason| make_parse_err(string, &reason))
+    }
+    /// Parse a date/time string with a format string.
+    ///
+    /// The format string takes the same specifiers as [`format`][DateTime::format], except for
+    /// `%j`, `%u` and `%w`. Weekday names are skipped. Names of months and weekdays, as well as
+    /// `AM` and `PM`, are matched case-insensitively.
+    ///
+    /// The year (via `%Y`, `%y` or `%s`) is required. Other components default to the start of
+    /// their ranges, and the offset defaults to UTC.
+    pub fn parse_from_str(string: &str, format: &str) -> RhaiResultOf<Self> {
+        let mut input = Scanner(string);
+        let mut fields = Fields::default();
+
+        fields
+            .parse(&mut input, format)
+            .and_then(|()| input.end())
+            .and_then(|()| fields.resolve())
+            .map_err(|reason| make_parse_err(string, &reason))
+    }
+}
+
+/// Create an error for a date/time string that cannot be parsed.
+#[cold]
+#[inline(never)]
+fn make_parse_err(string: &str, reason: &str) -> crate::RhaiError {
+    ERR::ErrorArithmetic(
+        format!("Error parsing date/time '{string}': {reason}"),
+        Position::NONE,
+    )
+    .into()
+}
+
+/// The remaining input of a date/time string being parsed.
+struct Scanner<'a>(&'a str);
+
+impl Scanner<'_> {
+    /// Consume a character if it is next in the input.
+    #[inline]
+    fn eat(&mut self, ch: char) -> bool {
+        match self.0.strip_prefix(ch) {
+            Some(rest) => {
+                self.0 = rest;
+                true
+            }
+            None => false,
+        }
+    }
+    /// Consume a character which must be next in the input.
+    #[inline]
+    fn expect(&mut self, ch: char) -> Result<(), String> {
+        if self.eat(ch) {
+            Ok(())
+        } else {
+            Err(format!("expected '{ch}'"))
+        }
+    }
+    /// Make sure that the entire input has been consumed.
+    #[inline]
+    fn end(&self) -> Result<(), String> {
+        if self.0.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("unexpected '{}'", self.0))
+        }
+    }
+    /// Consume a number of between `min` and `max` digits.
+    fn number(&mut self, min: usize, max: usize) -> Result<u32, String> {
+        let len = self
+            .0
+            .bytes()
+            .take(max)
+            .take_while(u8::is_ascii_digit)
+            .count();
+
+        if len < min {
+            return Err("expected a number".into());
+        }
+
+        let value = self.0[..len].parse().unwrap();
+        self.0 = &self.0[len..];
+        Ok(value)
+    }
+    /// Consume a signed integer.
+    fn integer(&mut self) -> Result<i64, String> {
+        let sign_len = usize::from(self.0.starts_with(['+', '-']));
+        let len = sign_len
+            + self.0[sign_len..]
+                .bytes()
+                .take_while(u8::is_ascii_digit)
+                .count();
+
+        let value = self.0[..len]
+            .parse()
+            .map_err(|_| String::from("expected a number"))?;
+        self.0 = &self.0[len..];
+        Ok(value)
+    }
+    /// Consume the digits of a fraction of a second, returning it in nanoseconds.
+    ///
+    /// Digits beyond nanoseconds are ignored.
+    fn fraction(&mut self) -> Result<u32, String> {
+        let len = self.0.bytes().take_while(u8::is_ascii_digit).count();
+
+        if len == 0 {
+            return Err("expected a fraction of a second".into());
+        }
+
+        let nanos = self.0[..len]
+            .bytes()
+            .take(9)
+            .enumerate()
+            .fold(0, |nanos, (i, b)| {
+                nanos + u32::from(b - b'0') * 10_u32.pow(8 - i as u32)
+            });
+        self.0 = &self.0[len..];
+        Ok(nanos)
+    }
+    /// Consume an offset from UTC in the form `Z`, `+hhmm` or `+hh:mm`, returning it in minutes.
+    fn offset(&mut self) -> Result<i32, String> {
+        if self.eat('Z') || self.eat('z') {
+            return Ok(0);
+        }
+
+        let negative = if self.eat('-') {
+            true
+        } else if self.eat('+') {
+            false
+        } else {
+            return Err("expected an offset".into());
+        };
+        let hours = self.number(2, 2)?;
+        self.eat(':');
+        let minutes = self.number(2, 2)?;
+
+        if hours > 23 || minutes > 59 {
+            return Err("invalid offset".into());
+        }
+
+        #[allow(clippy::cast_possible_wrap)]
+        let offset = (hours * 60 + minutes) as i32;
+        Ok(if negative { -offset } else { offset })
+    }
+    /// Consume a name, in full or abbreviated to three letters, returning its index.
+    fn name(&mut self, names: &[&str]) -> Result<usize, String> {
+        for len in [usize::MAX, 3] {
+            for (index, name) in names.iter().enumerate() {
+                let name = &name[..len.min(name.len())];
+
+                if self
+                    .0
+                    .get(..name.len())
+                    .map_or(false, |s| s.eq_ignore_ascii_case(name))
+                {
+                    self.0 = &self.0[name.len()..];
+                    return Ok(index);
+                }
+            }
+        }
+
+        Err(format!("expected one of: {}", names.join(", ")))
+    }
+}
+
+/// Components of a date/time string being parsed.
+struct Fields {
+    year: Option<i64>,
+    month: u32,
+    day: u32,
+    hour: u32,
+    /// Is the hour in 12-hour clock?
+    hour12: bool,
+    is_pm: bool,
+    minute: u32,
+    second: u32,
+    nanos: u32,
+    offset: i32,
+    timestamp: Option<i64>,
+}
+
+impl Default for Fields {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            year: None,
+            month: 1,
+            day: 1,
+            hour: 0,
+            hour12: false,
+            is_pm: false,
+            minute: 0,
+            second: 0,
+            nanos: 0,
+            offset: 0,
+            timestamp: None,
+        }
+    }
+}
+
+impl Fields {
+    /// Parse the input according to a format string.
+    fn parse(&mut self, input: &mut Scanner, format: &str) -> Result<(), String> {
+        let mut chars = format.chars();
+
+        while let Some(ch) = chars.next() {
+            if ch != '%' {
+                input.expect(ch)?;
+                continue;
+            }
+
+            match chars.next() {
+                Some('Y') => self.year = Some(input.number(1, 4)?.into()),
+                Some('y') => {
+                    let year = i64::from(input.number(2, 2)?);
+                    self.year = Some(if year < 69 { 2000 + year } else { 1900 + year });
+                }
+                Some('m') => self.month = input.number(1, 2)?,
+                Some('b' | 'B') => {
+                    #[allow(clippy::cast_possible_truncation)]
+                    let month = input.name(&MONTH_NAMES)? as u32;
+                    self.month = month + 1;
+                }
+                Some('d') => self.day = input.number(1, 2)?,
+                Some('e') => {
+                    input.eat(' ');
+                    self.day = input.number(1, 2)?;
+                }
+                Some('a' | 'A') => {
+                    input.name(&WEEKDAY_NAMES)?;
+                }
+                Some('H') => {
+                    self.hour = input.number(1, 2)?;
+                    self.hour12 = false;
+                }
+                Some('I') => {
+                    self.hour = input.number(1, 2)?;
+                    self.hour12 = true;
+                }
+                Some('p') => self.is_pm = input.name(&["AM", "PM"])? == 1,
+                Some('M') => self.minute = input.number(1, 2)?,
+                Some('S') => self.second = input.number(1, 2)?,
+                Some('f') => self.nanos = input.fraction()?,
+                Some('3' | '6' | '9') if chars.next() == Some('f') => {
+                    self.nanos = input.fraction()?;
+                }
+                Some(':') if chars.next() == Some('z') => self.offset = input.offset()?,
+                Some('z') => self.offset = input.offset()?,
+                Some('s') => self.timestamp = Some(input.integer()?),
+                Some('F') => self.parse(input, "%Y-%m-%d")?,
+                Some('T') => self.parse(input, "%H:%M:%S")?,
+                Some('%') => input.expect('%')?,
+                _ => return Err(format!("invalid format string '{format}'")),
+            }
+        }
+
+        Ok(())
+    }
+    /// Create a [`DateTime`] from the parsed components.
+    fn resolve(&self) -> Result<DateTime, String> {
+        if let Some(timestamp) = self.timestamp {
+            return DateTime::from_unix_timestamp(timestamp, self.nanos)
+                .and_then(|dt| dt.with_offset(self.offset))
+                .ok_or_else(|| "date/time out of range".into());
+        }
+
+        let year = self.year.ok_or_else(|| String::from("missing year"))?;
+
+        let hour = match self.hour {
+            1..=12 if self.hour12 => self.hour % 12 + if self.is_pm { 12 } else { 0 },
+            _ if self.hour12 => return Err("invalid hour".into()),
+            hour => hour,
+        };
+
+        DateTime::from_local(
+            year,
+            self.month,
+            self.day,
+            hour,
+            self.minute,
+            self.second,
+            self.nanos,
+            self.offset,
+        )
+        .ok_or_else(|| "invalid date/time".into())
+    }
+}
@@ -556,6 +556,17 @@ impl fmt::Display for Dynamic {
                     };
                 }
 
+                #[cfg(not(feature = "no_object"))]
+                if let Some(record) = _value_any.downcast_ref::<crate::Record>() {
+                    return fmt::Display::fmt(record, f);
+                }
+                if let Some(value) = _value_any.downcast_ref::<crate::EnumValue>() {
+                    return fmt::Display::fmt(value, f);
+                }
+                if let Some(value) = _value_any.downcast_ref::<crate::DateTime>() {
+                    return fmt::Display::fmt(value, f);
+                }
+
                 f.write_str((***v).type_name())
             }
 
@@ -724,6 +735,17 @@ impl fmt::Debug for Dynamic {
                     };
                 }
 
+                #[cfg(not(feature = "no_object"))]
+                if let Some(record) = _value_any.downcast_ref::<crate::Record>() {
+                    return fmt::Debug::fmt(record, f);
+                }
+                if let Some(value) = _value_any.downcast_ref::<crate::EnumValue>() {
+                    return fmt::Debug::fmt(value, f);
+                }
+                if let Some(value) = _value_any.downcast_ref::<crate::DateTime>() {
+                    return fmt::Debug::fmt(value, f);
+                }
+
                 f.write_str((***v).type_name())
             }
 
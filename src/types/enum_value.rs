@@ -0,0 +1,92 @@
+//! Module defining values of script-defined enum types.
+
+use crate::{Dynamic, ImmutableString};
+use std::fmt;
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+
+/// A value of an enum type defined in script via `enum`.
+///
+/// An enum value holds the name of its type, which is returned by `type_of()`, the name of its
+/// variant, and the values of the variant's payload (if any).
+///
+/// Enum values are matched in `switch` cases via patterns such as `Shape::Circle(r)`.
+#[derive(Clone)]
+pub struct EnumValue {
+    /// Name of the enum type.
+    type_name: ImmutableString,
+    /// Name of the variant.
+    variant: ImmutableString,
+    /// Payload of the variant.
+    values: Vec<Dynamic>,
+}
+
+impl fmt::Debug for EnumValue {
+    #[cold]
+    #[inline(never)]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_with(f, fmt::Debug::fmt)
+    }
+}
+
+impl fmt::Display for EnumValue {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_with(f, fmt::Display::fmt)
+    }
+}
+
+impl EnumValue {
+    /// Create a new [`EnumValue`] of a variant of an enum type with the values of its payload.
+    #[inline(always)]
+    #[must_use]
+    pub fn new(
+        type_name: impl Into<ImmutableString>,
+        variant: impl Into<ImmutableString>,
+        values: impl IntoIterator<Item = Dynamic>,
+    ) -> Self {
+        Self {
+            type_name: type_name.into(),
+            variant: variant.into(),
+            values: values.into_iter().collect(),
+        }
+    }
+    /// Get the name of the enum type of this [`EnumValue`].
+    #[inline(always)]
+    #[must_use]
+    pub fn type_name(&self) -> &str {
+        &self.type_name
+    }
+    /// Get the name of the variant of this [`EnumValue`].
+    #[inline(always)]
+    #[must_use]
+    pub fn variant(&self) -> &str {
+        &self.variant
+    }
+    /// Get the values of the payload of this [`EnumValue`].
+    #[inline(always)]
+    pub fn values(&self) -> &[Dynamic] {
+        &self.values
+    }
+    /// Write this [`EnumValue`] in the form `Type::Variant(value, ...)`.
+    fn write_with(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        write_value: impl Fn(&Dynamic, &mut fmt::Formatter<'_>) -> fmt::Result,
+    ) -> fmt::Result {
+        write!(f, "{}::{}", self.type_name, self.variant)?;
+
+        if self.values.is_empty() {
+            return Ok(());
+        }
+
+        f.write_str("(")?;
+        for (i, value) in self.values.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write_value(value, f)?;
+        }
+        f.write_str(")")
+    }
+}
@@ -103,6 +103,10 @@ pub enum EvalAltResult {
     ErrorStackOverflow(Position),
     /// Data value over maximum size limit. Wrapped value is the type name.
     ErrorDataTooLarge(String, Position),
+    /// Memory held by the script over maximum limit.
+    ErrorTooMuchMemory(Position),
+    /// Execution time over maximum limit.
+    ErrorTimeout(Position),
     /// The script is prematurely terminated. Wrapped value is the termination token.
     ErrorTerminated(Dynamic, Position),
 
@@ -175,6 +179,8 @@ impl fmt::Display for EvalAltResult {
             Self::ErrorTooManyVariables(..) => f.write_str("Too many variables defined")?,
             Self::ErrorTooManyModules(..) => f.write_str("Too many modules imported")?,
             Self::ErrorStackOverflow(..) => f.write_str("Stack overflow")?,
+            Self::ErrorTooMuchMemory(..) => f.write_str("Memory limit exceeded")?,
+            Self::ErrorTimeout(..) => f.write_str("Execution time limit exceeded")?,
             Self::ErrorTerminated(..) => f.write_str("Script terminated")?,
 
             Self::ErrorRuntime(d, ..) if d.is_unit() => f.write_str("Runtime error")?,
@@ -344,6 +350,8 @@ impl EvalAltResult {
             | Self::ErrorTooManyVariables(..)
             | Self::ErrorTooManyModules(..)
             | Self::ErrorStackOverflow(..)
+            | Self::ErrorTooMuchMemory(..)
+            | Self::ErrorTimeout(..)
             | Self::ErrorDataTooLarge(..)
             | Self::ErrorTerminated(..) => false,
 
@@ -364,6 +372,8 @@ impl EvalAltResult {
                 | Self::ErrorTooManyVariables(..)
                 | Self::ErrorTooManyModules(..)
                 | Self::ErrorStackOverflow(..)
+                | Self::ErrorTooMuchMemory(..)
+                | Self::ErrorTimeout(..)
                 | Self::ErrorDataTooLarge(..)
                 | Self::ErrorTerminated(..)
         )
@@ -394,6 +404,8 @@ impl EvalAltResult {
             | Self::ErrorTooManyVariables(..)
             | Self::ErrorTooManyModules(..)
             | Self::ErrorStackOverflow(..)
+            | Self::ErrorTooMuchMemory(..)
+            | Self::ErrorTimeout(..)
             | Self::ErrorRuntime(..) => (),
 
             Self::ErrorFunctionNotFound(f, ..) | Self::ErrorNonPureMethodCallOnConstant(f, ..) => {
@@ -453,6 +465,30 @@ impl EvalAltResult {
             }
         };
     }
+    /// Is this error a suspended evaluation?
+    ///
+    /// An evaluation is suspended via [`NativeCallContext::suspend`][crate::NativeCallContext::suspend].
+    #[cold]
+    #[inline(never)]
+    #[must_use]
+    pub fn is_suspension(&self) -> bool {
+        matches!(self, Self::ErrorTerminated(token, ..) if token.is::<crate::Continuation>())
+    }
+    /// Convert this error into the [`Continuation`][crate::Continuation] of a suspended evaluation.
+    ///
+    /// # Errors
+    ///
+    /// Returns this error unchanged if it is not a suspended evaluation.
+    #[cold]
+    #[inline(never)]
+    pub fn into_continuation(self) -> Result<crate::Continuation, Self> {
+        match self {
+            Self::ErrorTerminated(token, ..) if token.is::<crate::Continuation>() => {
+                Ok(token.cast::<crate::Continuation>())
+            }
+            err => Err(err),
+        }
+    }
     /// Unwrap this error and get the very base error.
     #[cold]
     #[inline(never)]
@@ -499,6 +535,8 @@ impl EvalAltResult {
             | Self::ErrorTooManyVariables(pos)
             | Self::ErrorTooManyModules(pos)
             | Self::ErrorStackOverflow(pos)
+            | Self::ErrorTooMuchMemory(pos)
+            | Self::ErrorTimeout(pos)
             | Self::ErrorDataTooLarge(.., pos)
             | Self::ErrorTerminated(.., pos)
             | Self::ErrorCustomSyntax(.., pos)
@@ -561,6 +599,8 @@ impl EvalAltResult {
             | Self::ErrorTooManyVariables(pos)
             | Self::ErrorTooManyModules(pos)
             | Self::ErrorStackOverflow(pos)
+            | Self::ErrorTooMuchMemory(pos)
+            | Self::ErrorTimeout(pos)
             | Self::ErrorDataTooLarge(.., pos)
             | Self::ErrorTerminated(.., pos)
             | Self::ErrorCustomSyntax(.., pos)
@@ -16,6 +16,7 @@ use num_traits::float::FloatCore as Float;
 ///
 /// Not available under `no_float`.
 #[derive(Clone, Copy, Eq, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[must_use]
 pub struct FloatWrapper<F>(F);
 
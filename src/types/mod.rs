@@ -1,8 +1,12 @@
 //! Module defining Rhai data types.
 
 pub mod bloom_filter;
+pub mod cancellation;
+pub mod continuation;
 pub mod custom_types;
+pub mod datetime;
 pub mod dynamic;
+pub mod enum_value;
 pub mod error;
 pub mod float;
 pub mod fn_ptr;
@@ -11,15 +15,21 @@ pub mod interner;
 pub mod parse_error;
 pub mod position;
 pub mod position_none;
+pub mod record;
 pub mod scope;
 pub mod var_def;
 pub mod variant;
 
 pub use bloom_filter::BloomFilterU64;
+#[cfg(not(feature = "unchecked"))]
+pub use cancellation::CancellationHandle;
+pub use continuation::Continuation;
 pub use custom_types::{CustomTypeInfo, CustomTypesCollection};
+pub use datetime::DateTime;
 pub use dynamic::Dynamic;
 #[cfg(not(feature = "no_time"))]
 pub use dynamic::Instant;
+pub use enum_value::EnumValue;
 pub use error::EvalAltResult;
 #[cfg(not(feature = "no_float"))]
 pub use float::FloatWrapper;
@@ -27,6 +37,8 @@ pub use fn_ptr::FnPtr;
 pub use immutable_string::ImmutableString;
 pub use interner::StringsInterner;
 pub use parse_error::{LexError, ParseError, ParseErrorType};
+#[cfg(not(feature = "no_object"))]
+pub use record::Record;
 pub use var_def::VarDefInfo;
 
 #[cfg(not(feature = "no_position"))]
@@ -109,6 +109,8 @@ pub enum ParseErrorType {
     MalformedInExpr(String),
     /// A capturing  has syntax error. Wrapped value is the error description (if any).
     MalformedCapture(String),
+    /// A `switch` case pattern has syntax error. Wrapped value is the error description (if any).
+    MalformedPattern(String),
     /// A map definition has duplicated property names. Wrapped value is the property name.
     DuplicatedProperty(String),
     /// A `switch` case is duplicated.
@@ -146,6 +148,8 @@ pub enum ParseErrorType {
     WrongDocComment,
     /// Defining a function `fn` in an appropriate place (e.g. inside another function).
     WrongFnDefinition,
+    /// Defining a type (e.g. `struct`) in an appropriate place (e.g. inside a block).
+    WrongTypeDefinition,
     /// Defining a function with a name that conflicts with an existing function.
     /// Wrapped values are the function name and number of parameters.
     FnDuplicatedDefinition(String, usize),
@@ -199,6 +203,8 @@ impl fmt::Display for ParseErrorType {
 
             Self::MalformedCapture(s) if s.is_empty()  => f.write_str("Invalid capturing"),
             Self::MalformedCapture(s) => f.write_str(s),
+            Self::MalformedPattern(s) if s.is_empty()  => f.write_str("Invalid pattern"),
+            Self::MalformedPattern(s) => f.write_str(s),
 
             Self::FnDuplicatedDefinition(s, n) => {
                 write!(f, "Function {s} with ")?;
@@ -246,6 +252,7 @@ impl fmt::Display for ParseErrorType {
             Self::VariableExpected => f.write_str("Expecting name of a variable"),
             Self::ForbiddenVariable(s) => write!(f, "Forbidden variable name: {s}"),
             Self::WrongFnDefinition => f.write_str("Function definitions must be at global level and cannot be inside a block or another function"),
+            Self::WrongTypeDefinition => f.write_str("Type definitions must be at global level and cannot be inside a block or a function"),
             Self::FnMissingName => f.write_str("Expecting function name in function declaration"),
             Self::WrongDocComment => f.write_str("Doc-comment must be followed immediately by a function definition"),
             Self::WrongExport => f.write_str("Export statement can only appear at global level"),
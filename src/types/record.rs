@@ -0,0 +1,83 @@
+//! Module defining values of script-defined record types.
+#![cfg(not(feature = "no_object"))]
+
+use crate::{Dynamic, ImmutableString, Map};
+use std::fmt;
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+
+/// A value of a record type defined in script via `struct`.
+///
+/// A record holds the name of its type, which is returned by `type_of()`, and a fixed set of
+/// fields. Accessing a field that does not exist is always an error, regardless of
+/// [`fail_on_invalid_map_property`][crate::Engine::fail_on_invalid_map_property].
+///
+/// Not available under `no_object`.
+#[derive(Clone)]
+pub struct Record {
+    /// Name of the record type.
+    name: ImmutableString,
+    /// Fields of the record.
+    fields: Map,
+}
+
+impl fmt::Debug for Record {
+    #[cold]
+    #[inline(never)]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} #{:?}", self.name, self.fields)
+    }
+}
+
+impl fmt::Display for Record {
+    #[inline(always)]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+impl Record {
+    /// Create a new [`Record`] of a type with the values of its fields.
+    #[inline(always)]
+    #[must_use]
+    pub fn new(name: impl Into<ImmutableString>, fields: Map) -> Self {
+        Self {
+            name: name.into(),
+            fields,
+        }
+    }
+    /// Get the name of the type of this [`Record`].
+    #[inline(always)]
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    /// Get the fields of this [`Record`].
+    #[inline(always)]
+    #[must_use]
+    pub const fn fields(&self) -> &Map {
+        &self.fields
+    }
+    /// Get the value of a field.
+    ///
+    /// Returns [`None`] if the field does not exist.
+    #[inline(always)]
+    #[must_use]
+    pub fn get(&self, field: &str) -> Option<&Dynamic> {
+        self.fields.get(field)
+    }
+    /// Get a mutable reference to the value of a field.
+    ///
+    /// Returns [`None`] if the field does not exist. Fields cannot be added to a [`Record`].
+    #[inline(always)]
+    #[must_use]
+    pub fn get_mut(&mut self, field: &str) -> Option<&mut Dynamic> {
+        self.fields.get_mut(field)
+    }
+    /// Consume this [`Record`], returning the fields as an object map.
+    #[inline(always)]
+    #[must_use]
+    pub fn into_map(self) -> Map {
+        self.fields
+    }
+}
@@ -69,6 +69,10 @@ pub struct Scope<'a> {
     /// This `Vec` is not filled until needed because aliases are used rarely
     /// (only for `export` statements).
     aliases: ThinVec<StaticVec<ImmutableString>>,
+    /// Type annotation of the entry, if any.
+    ///
+    /// This `Vec` is not filled until needed because type annotations are used rarely.
+    types: ThinVec<Option<ImmutableString>>,
     /// Phantom to keep the lifetime parameter in order not to break existing code.
     dummy: PhantomData<&'a ()>,
 }
@@ -91,23 +95,9 @@ impl fmt::Display for Scope<'_> {
 }
 
 impl Clone for Scope<'_> {
-    #[inline]
+    #[inline(always)]
     fn clone(&self) -> Self {
-        Self {
-            values: self
-                .values
-                .iter()
-                .map(|v| {
-                    // Also copy the value's access mode (otherwise will turn to read-write)
-                    let mut v2 = v.clone();
-                    v2.set_access_mode(v.access_mode());
-                    v2
-                })
-                .collect(),
-            names: self.names.clone(),
-            aliases: self.aliases.clone(),
-            dummy: self.dummy,
-        }
+        self.clone_static()
     }
 }
 
@@ -175,6 +165,7 @@ impl Scope<'_> {
             values: ThinVec::new(),
             names: ThinVec::new(),
             aliases: ThinVec::new(),
+            types: ThinVec::new(),
             dummy: PhantomData,
         }
     }
@@ -197,6 +188,7 @@ impl Scope<'_> {
             values: ThinVec::with_capacity(capacity),
             names: ThinVec::with_capacity(capacity),
             aliases: ThinVec::new(),
+            types: ThinVec::new(),
             dummy: PhantomData,
         }
     }
@@ -224,6 +216,7 @@ impl Scope<'_> {
         self.names.clear();
         self.values.clear();
         self.aliases.clear();
+        self.types.clear();
         self
     }
     /// Get the number of entries inside the [`Scope`].
@@ -402,6 +395,7 @@ impl Scope<'_> {
             .unwrap_or_else(|| panic!("`Scope` is empty"));
         self.values.truncate(self.names.len());
         self.aliases.truncate(self.names.len());
+        self.types.truncate(self.names.len());
         self
     }
     /// Remove the last entry from the [`Scope`] and return it.
@@ -409,6 +403,7 @@ impl Scope<'_> {
     #[allow(dead_code)]
     pub(crate) fn pop_entry(&mut self) -> Option<(ImmutableString, Dynamic, Vec<ImmutableString>)> {
         self.values.pop().map(|value| {
+            self.types.truncate(self.values.len());
             (
                 self.names.pop().unwrap(),
                 value,
@@ -451,6 +446,7 @@ impl Scope<'_> {
         self.names.truncate(size);
         self.values.truncate(size);
         self.aliases.truncate(size);
+        self.types.truncate(size);
         self
     }
     /// Does the [`Scope`] contain the entry?
@@ -762,6 +758,9 @@ impl Scope<'_> {
             if self.aliases.len() > index {
                 self.aliases.remove(index);
             }
+            if self.types.len() > index {
+                self.types.remove(index);
+            }
             self.values.remove(index).try_cast()
         })
     }
@@ -806,6 +805,45 @@ impl Scope<'_> {
     pub(crate) fn get_mut_by_index(&mut self, index: usize) -> &mut Dynamic {
         &mut self.values[index]
     }
+    /// Get the type annotation of an entry in the [`Scope`], if any.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds.
+    #[inline]
+    #[must_use]
+    pub(crate) fn get_type_by_index(&self, index: usize) -> Option<&ImmutableString> {
+        assert!(index < self.len(), "index out of bounds");
+        self.types.get(index).and_then(Option::as_ref)
+    }
+    /// Set (or remove) the type annotation of an entry in the [`Scope`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds.
+    #[inline]
+    pub(crate) fn set_type_by_index(
+        &mut self,
+        index: usize,
+        typ: Option<ImmutableString>,
+    ) -> &mut Self {
+        assert!(index < self.len(), "index out of bounds");
+
+        if self.types.len() <= index {
+            if typ.is_none() {
+                return self;
+            }
+            self.types.resize(index + 1, None);
+        }
+        self.types[index] = typ;
+        self
+    }
+    /// Does any entry in the [`Scope`] have a type annotation?
+    #[inline(always)]
+    #[must_use]
+    pub(crate) fn has_types(&self) -> bool {
+        !self.types.is_empty()
+    }
     /// Add an alias to an entry in the [`Scope`].
     ///
     /// # Panics
@@ -849,6 +887,27 @@ impl Scope<'_> {
             self.add_alias_by_index(index, alias);
         }
     }
+    /// Clone the [`Scope`] into one that does not borrow anything.
+    #[inline]
+    #[must_use]
+    pub(crate) fn clone_static(&self) -> Scope<'static> {
+        Scope {
+            values: self
+                .values
+                .iter()
+                .map(|v| {
+                    // Also copy the value's access mode (otherwise will turn to read-write)
+                    let mut v2 = v.clone();
+                    v2.set_access_mode(v.access_mode());
+                    v2
+                })
+                .collect(),
+            names: self.names.clone(),
+            aliases: self.aliases.clone(),
+            types: self.types.clone(),
+            dummy: PhantomData,
+        }
+    }
     /// Clone the [`Scope`], keeping only the last instances of each variable name.
     /// Shadowed variables are omitted in the copy.
     #[inline]
@@ -871,6 +930,10 @@ impl Scope<'_> {
                 scope.aliases.resize(scope.len() - 1, <_>::default());
                 scope.aliases.push(self.aliases[index].clone());
             }
+
+            if let Some(typ) = self.types.get(index).cloned().flatten() {
+                scope.set_type_by_index(scope.len() - 1, Some(typ));
+            }
         });
 
         scope
@@ -951,6 +1014,14 @@ impl Scope<'_> {
                 self.aliases.drain(start..start + len).for_each(|_| {});
             }
         }
+
+        if self.types.len() > start {
+            if self.types.len() <= start + len {
+                self.types.truncate(start);
+            } else {
+                self.types.drain(start..start + len).for_each(|_| {});
+            }
+        }
     }
 }
 
@@ -492,6 +492,82 @@ fn test_arrays_map_reduce() {
         .unwrap();
 }
 
+#[test]
+fn test_arrays_aggregate() {
+    let engine = Engine::new();
+
+    assert_eq!(engine.eval::<String>("to_string(chunks([1, 2, 3, 4, 5], 2))").unwrap(), "[[1, 2], [3, 4], [5]]");
+    assert_eq!(engine.eval::<String>("to_string(windows([1, 2, 3, 4], 3))").unwrap(), "[[1, 2, 3], [2, 3, 4]]");
+    assert_eq!(engine.eval::<INT>("len(windows([1, 2], 3))").unwrap(), 0);
+    assert!(matches!(*engine.run("chunks([1, 2, 3], 0)").unwrap_err(), EvalAltResult::ErrorArithmetic(..)));
+    assert!(matches!(*engine.run("windows([1, 2, 3], -1)").unwrap_err(), EvalAltResult::ErrorArithmetic(..)));
+    assert_eq!(engine.eval::<INT>(&format!("len(chunks([1, 2, 3], {}))", INT::MAX)).unwrap(), 1);
+    assert_eq!(engine.eval::<INT>(&format!("len(windows([1, 2, 3], {}))", INT::MAX)).unwrap(), 0);
+    assert_eq!(engine.eval::<String>("to_string(flatten([[1, 2], 3, [[4], 5]]))").unwrap(), "[1, 2, 3, [4], 5]");
+
+    assert_eq!(engine.eval::<INT>("sum([1, 2, 3, 4, 5])").unwrap(), 15);
+    assert_eq!(engine.eval::<INT>("sum([])").unwrap(), 0);
+    assert_eq!(engine.eval::<INT>("product([1, 2, 3, 4, 5])").unwrap(), 120);
+    assert_eq!(engine.eval::<INT>("product([])").unwrap(), 1);
+    assert_eq!(engine.eval::<String>(r#"sum(["a", "b", "c"])"#).unwrap(), "abc");
+    #[cfg(not(feature = "no_float"))]
+    assert_eq!(engine.eval::<rhai::FLOAT>("sum([1.5, 2, 3])").unwrap(), 6.5);
+    assert!(matches!(*engine.run("sum([1, true])").unwrap_err(), EvalAltResult::ErrorFunctionNotFound(..)));
+
+    assert_eq!(engine.eval::<INT>("min([3, 1, 4, 1, 5])").unwrap(), 1);
+    assert_eq!(engine.eval::<INT>("max([3, 1, 4, 1, 5])").unwrap(), 5);
+    assert_eq!(engine.eval::<String>(r#"max(["apple", "kiwi", "fig"])"#).unwrap(), "kiwi");
+    assert!(engine.eval::<bool>("min([]) == ()").unwrap());
+}
+
+#[cfg(not(feature = "no_function"))]
+#[test]
+fn test_arrays_aggregate_callbacks() {
+    let engine = Engine::new();
+
+    assert_eq!(engine.eval::<String>("to_string(flat_map([1, 2, 3], |v| [v, v * 10]))").unwrap(), "[1, 10, 2, 20, 3, 30]");
+    assert_eq!(engine.eval::<String>("to_string(flat_map([1, 2, 3], |v, i| if i == 1 { v } else { [] }))").unwrap(), "[2]");
+    assert_eq!(engine.eval::<String>("to_string(partition([1, 2, 3, 4, 5], |v| v >= 3))").unwrap(), "[[3, 4, 5], [1, 2]]");
+    assert_eq!(
+        engine
+            .eval::<String>(r#"let x = ["apple", "fig", "banana", "kiwi"]; sort_by_key(x, |s| len(s)); to_string(x)"#)
+            .unwrap(),
+        r#"["fig", "kiwi", "apple", "banana"]"#
+    );
+    assert_eq!(engine.eval::<String>("let x = [5, 3, 4, 1]; sort_by_key(x, |v| -v); to_string(x)").unwrap(), "[5, 4, 3, 1]");
+    assert_eq!(engine.eval::<String>("to_string(unique_by([1, -1, 2, 3, -2, 1], |v| abs(v)))").unwrap(), "[1, 2, 3]");
+    assert_eq!(engine.eval::<String>(r#"to_string(unique_by([1, "1", 2, [1], [1]], |v| v))"#).unwrap(), r#"[1, "1", 2, [1]]"#);
+
+    #[cfg(not(feature = "no_object"))]
+    {
+        let result = engine.eval::<rhai::Map>(r#"[1, 2, 3, 4, 5].group_by(|v| if v % 2 == 0 { "even" } else { "odd" })"#).unwrap();
+        assert_eq!(result["even"].to_string(), "[2, 4]");
+        assert_eq!(result["odd"].to_string(), "[1, 3, 5]");
+        assert_eq!(engine.eval::<INT>("let m = [1, 2, 3, 4].group_by(|v| v % 2); m[`1`].len()").unwrap(), 2);
+    }
+}
+
+#[cfg(not(feature = "unchecked"))]
+#[test]
+fn test_arrays_aggregate_limits() {
+    let mut engine = Engine::new();
+
+    engine.set_max_array_size(10);
+    assert_eq!(engine.eval::<INT>("len(windows([1, 2, 3], 2))").unwrap(), 2);
+    assert!(matches!(*engine.run("windows([1, 2, 3, 4, 5, 6, 7, 8], 4)").unwrap_err(), EvalAltResult::ErrorDataTooLarge(..)));
+
+    #[cfg(not(feature = "no_function"))]
+    {
+        assert!(matches!(*engine.run("flat_map([1, 2, 3, 4], |v| [v, v, v])").unwrap_err(), EvalAltResult::ErrorDataTooLarge(..)));
+
+        engine.set_max_operations(500);
+        assert!(matches!(
+            *engine.run("sort_by_key([1, 2, 3, 4, 5], |v| { let x = 0; for i in 0..1000 { x += i; } x })").unwrap_err(),
+            EvalAltResult::ErrorInFunctionCall(.., err, _) if matches!(*err, EvalAltResult::ErrorTooManyOperations(..))
+        ));
+    }
+}
+
 #[test]
 fn test_arrays_elvis() {
     let engine = Engine::new();
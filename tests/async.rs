@@ -0,0 +1,207 @@
+use rhai::{Engine, EvalAltResult, Scope, INT};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread;
+use std::time::Duration;
+
+struct Unparker(thread::Thread);
+
+impl Wake for Unparker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = Box::pin(future);
+    let waker = Arc::new(Unparker(thread::current())).into();
+    let mut cx = Context::from_waker(&waker);
+
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+/// A future that is completed by another thread after a delay.
+struct Delayed {
+    state: Arc<Mutex<(Option<INT>, Option<Waker>)>>,
+}
+
+impl Delayed {
+    fn new(value: INT) -> Self {
+        let state = Arc::new(Mutex::new((None, None::<Waker>)));
+        let shared = state.clone();
+
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(10));
+            let mut state = shared.lock().unwrap();
+            state.0 = Some(value);
+            if let Some(waker) = state.1.take() {
+                waker.wake();
+            }
+        });
+
+        Self { state }
+    }
+}
+
+impl Future for Delayed {
+    type Output = INT;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<INT> {
+        let mut state = self.state.lock().unwrap();
+
+        match state.0.take() {
+            Some(value) => Poll::Ready(value),
+            None => {
+                state.1 = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+#[test]
+fn test_async_eval() {
+    let mut engine = Engine::new();
+
+    engine.register_async_fn("double", |x: INT| async move { x * 2 });
+    engine.register_async_fn("fetch", |x: INT| Delayed::new(x + 1));
+    engine.register_async_fn("check", |x: INT| async move {
+        if x >= 0 {
+            Ok::<_, Box<EvalAltResult>>(x)
+        } else {
+            Err("negative".into())
+        }
+    });
+
+    assert_eq!(block_on(engine.eval_async::<INT>("let x = double(20); x + 2")).unwrap(), 42);
+    assert_eq!(block_on(engine.eval_async::<INT>("let x = 0; for i in 0..5 { x += fetch(i); } x")).unwrap(), 15);
+    assert_eq!(block_on(engine.eval_async::<INT>("check(42)")).unwrap(), 42);
+    assert!(matches!(*block_on(engine.eval_async::<INT>("check(-1)")).unwrap_err(), EvalAltResult::ErrorRuntime(..)));
+    assert!(matches!(*block_on(engine.eval_async::<INT>("try { check(-1) } catch { 0 }")).unwrap_err(), EvalAltResult::ErrorMismatchOutputType(..)));
+    assert_eq!(block_on(engine.eval_async::<INT>("let r = 0; try { check(-1); r = 1; } catch (err) { r = if err == \"negative\" { 42 } else { 0 }; } r")).unwrap(), 42);
+    assert!(matches!(*block_on(engine.eval_async::<INT>("let x = ;")).unwrap_err(), EvalAltResult::ErrorParsing(..)));
+
+    let mut scope = Scope::new();
+    scope.push("x", 40 as INT);
+    assert_eq!(block_on(engine.eval_with_scope_async::<INT>(&mut scope, "x += double(1); x")).unwrap(), 42);
+    assert_eq!(scope.get_value::<INT>("x").unwrap(), 42);
+
+    // Async functions suspend the evaluation, so they can only be called at statement boundaries
+    assert!(matches!(*block_on(engine.eval_async::<INT>("double(20) + 2")).unwrap_err(), EvalAltResult::ErrorSystem(..)));
+
+    // Async functions cannot be called in a normal evaluation
+    assert!(matches!(*engine.eval::<INT>("double(21)").unwrap_err(), EvalAltResult::ErrorSystem(..)));
+
+    // The evaluation can be sent to another thread
+    #[cfg(feature = "sync")]
+    {
+        fn assert_send<T: Send>(_: &T) {}
+        assert_send(&engine.eval_async::<INT>("let x = fetch(1); x"));
+    }
+}
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+fn test_async_call_fn() {
+    let mut engine = Engine::new();
+
+    engine.register_async_fn("fetch", |x: INT| Delayed::new(x));
+
+    let ast = engine.compile("fn foo(x, y) { let a = fetch(x); let b = fetch(y); a + b + z }").unwrap();
+
+    let mut scope = Scope::new();
+    scope.push_constant("z", 2 as INT);
+
+    assert_eq!(block_on(engine.call_fn_async::<INT>(&mut scope, &ast, "foo", (10 as INT, 30 as INT))).unwrap(), 42);
+}
+
+#[test]
+#[cfg(not(feature = "unchecked"))]
+fn test_async_limits() {
+    let mut engine = Engine::new();
+
+    engine.register_async_fn("fetch", |x: INT| Delayed::new(x));
+    engine.set_max_operations(500);
+
+    assert!(matches!(*block_on(engine.eval_async::<INT>("let x = fetch(1); loop { x += 1; }")).unwrap_err(), EvalAltResult::ErrorTooManyOperations(..)));
+
+    // The operations are counted across asynchronous calls
+    assert!(matches!(*block_on(engine.eval_async::<INT>("let x = 0; loop { x += fetch(1); }")).unwrap_err(), EvalAltResult::ErrorTooManyOperations(..)));
+
+    let mut engine = Engine::new();
+
+    engine.register_async_fn("fetch", |x: INT| Delayed::new(x));
+    engine.on_progress(|count| if count > 100 { Some((42 as INT).into()) } else { None });
+
+    match *block_on(engine.eval_async::<INT>("let x = fetch(1); loop { x += fetch(1); }")).unwrap_err() {
+        EvalAltResult::ErrorTerminated(value, ..) => assert_eq!(value.as_int().unwrap(), 42),
+        err => panic!("{}", err),
+    }
+
+    // The deadline applies across asynchronous calls
+    #[cfg(not(feature = "no_time"))]
+    {
+        let mut engine = Engine::new();
+
+        engine.register_async_fn("fetch", |x: INT| Delayed::new(x));
+        engine.set_max_execution_time(Duration::from_millis(50));
+
+        let script = "let x = 0; for i in 0..20 { x += fetch(1); let y = 0; while y < 100 { y += 1; } } x";
+        assert!(matches!(*block_on(engine.eval_async::<INT>(script)).unwrap_err(), EvalAltResult::ErrorTimeout(..)));
+    }
+}
+
+/// A future that never completes, holding a value until dropped.
+struct Pending {
+    _held: Arc<()>,
+}
+
+impl Future for Pending {
+    type Output = INT;
+
+    fn poll(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<INT> {
+        Poll::Pending
+    }
+}
+
+#[test]
+fn test_async_cancel() {
+    let mut engine = Engine::new();
+
+    let held = Arc::new(());
+    let shared = held.clone();
+
+    engine.register_async_fn("wait", move || Pending { _held: shared.clone() });
+
+    let mut future = Box::pin(engine.eval_async::<INT>("wait()"));
+    let waker = Arc::new(Unparker(thread::current())).into();
+    let mut cx = Context::from_waker(&waker);
+
+    assert!(future.as_mut().poll(&mut cx).is_pending());
+    assert!(future.as_mut().poll(&mut cx).is_pending());
+    assert_eq!(Arc::strong_count(&held), 3);
+
+    // Dropping the evaluation drops the pending call
+    drop(future);
+
+    assert_eq!(Arc::strong_count(&held), 2);
+}
+
+#[test]
+fn test_async_no_calls() {
+    let engine = Engine::new();
+
+    // The script runs within the first poll
+    let mut future = Box::pin(engine.eval_async::<INT>("let x = 0; for i in 0..10 { x += i; } x"));
+    let waker = Arc::new(Unparker(thread::current())).into();
+    let mut cx = Context::from_waker(&waker);
+
+    assert!(matches!(future.as_mut().poll(&mut cx), Poll::Ready(Ok(45))));
+}
@@ -336,3 +336,139 @@ fn test_max_map_size() {
         4
     );
 }
+
+#[test]
+fn test_max_memory() {
+    let mut engine = Engine::new();
+    engine.set_max_string_size(100);
+    engine.set_max_memory(1000);
+
+    assert_eq!(engine.max_memory(), 1000);
+
+    assert_eq!(engine.eval::<INT>(r#"let x = "hello"; let y = x + ", world!"; len(y)"#).unwrap(), 13);
+
+    // Each string is within the size limit, but not all of them together
+    assert!(matches!(
+        *engine
+            .run(
+                r#"
+                    let a = ""; pad(a, 90, 'a');
+                    let b = ""; pad(b, 90, 'b');
+                    let c = ""; pad(c, 90, 'c');
+                    let d = ""; pad(d, 90, 'd');
+                    let e = ""; pad(e, 90, 'e');
+                    let f = ""; pad(f, 90, 'f');
+                    let g = ""; pad(g, 90, 'g');
+                    let h = ""; pad(h, 90, 'h');
+                    let i = ""; pad(i, 90, 'i');
+                    let j = ""; pad(j, 90, 'j');
+                    let k = ""; pad(k, 90, 'k');
+                "#
+            )
+            .unwrap_err(),
+        EvalAltResult::ErrorTooMuchMemory(..)
+    ));
+
+    #[cfg(not(feature = "no_index"))]
+    assert!(matches!(
+        *engine
+            .run(
+                "
+                    let x = [];
+                    for i in 0..100 { push(x, [1, 2, 3]); }
+                "
+            )
+            .unwrap_err(),
+        EvalAltResult::ErrorTooMuchMemory(..)
+    ));
+
+    // Values changed via method calls are counted
+    #[cfg(not(feature = "no_index"))]
+    #[cfg(not(feature = "no_object"))]
+    assert!(matches!(
+        *engine
+            .run(
+                "
+                    let x = [];
+                    for i in 0..100 { x.push([1, 2, 3]); }
+                "
+            )
+            .unwrap_err(),
+        EvalAltResult::ErrorTooMuchMemory(..)
+    ));
+
+    // Values changed via method calls on indexed elements are counted
+    #[cfg(not(feature = "no_index"))]
+    #[cfg(not(feature = "no_object"))]
+    assert!(matches!(
+        *engine
+            .run(
+                "
+                    let a = [[]];
+                    for i in 0..100 { a[0].push([1, 2, 3]); }
+                "
+            )
+            .unwrap_err(),
+        EvalAltResult::ErrorTooMuchMemory(..)
+    ));
+
+    // Variables going out of scope are no longer counted
+    engine.run(r#"for i in 0..20 { let s = ""; pad(s, 90, 'x'); }"#).unwrap();
+    engine.run(r#"for i in 0..20 { let s = ""; pad(s, 90, 'x'); } let t = ""; pad(t, 90, 'y');"#).unwrap();
+
+    // The limit cannot be caught
+    #[cfg(not(feature = "no_index"))]
+    assert!(matches!(
+        *engine
+            .run(
+                "
+                    try {
+                        let x = [];
+                        for i in 0..100 { push(x, i); }
+                    } catch {}
+                "
+            )
+            .unwrap_err(),
+        EvalAltResult::ErrorTooMuchMemory(..)
+    ));
+
+    // Arguments of functions being called are counted
+    #[cfg(not(feature = "no_function"))]
+    engine.set_max_call_levels(20);
+    #[cfg(not(feature = "no_function"))]
+    assert!(matches!(
+        *engine
+            .run(
+                r#"
+                    fn foo(x, n) { if n > 0 { foo(x + "", n - 1) } }
+                    let x = "";
+                    pad(x, 90, '0');
+                    foo(x, 10)
+                "#
+            )
+            .unwrap_err(),
+        EvalAltResult::ErrorTooMuchMemory(..)
+    ));
+
+    // Values captured by closures are counted
+    #[cfg(not(feature = "no_function"))]
+    #[cfg(not(feature = "no_closure"))]
+    #[cfg(not(feature = "no_index"))]
+    assert!(matches!(
+        *engine
+            .run(
+                "
+                    let x = [];
+                    let f = || { x += [1, 2, 3]; };
+                    for i in 0..100 { call(f); }
+                "
+            )
+            .unwrap_err(),
+        EvalAltResult::ErrorTooMuchMemory(..)
+    ));
+
+    engine.set_max_memory(0);
+
+    #[cfg(not(feature = "no_index"))]
+    assert_eq!(engine.eval::<INT>("let x = []; for i in 0..100 { push(x, [1, 2, 3]); } len(x)").unwrap(), 100);
+}
@@ -0,0 +1,124 @@
+#![cfg(not(feature = "no_object"))]
+use rhai::packages::{DateTimePackage, Package};
+use rhai::{DateTime, Engine, EvalAltResult, INT};
+
+fn make_engine() -> Engine {
+    let mut engine = Engine::new();
+    DateTimePackage::new().register_into_engine(&mut engine);
+    engine
+}
+
+#[test]
+fn test_datetime() {
+    let engine = make_engine();
+
+    assert_eq!(engine.eval::<String>("type_of(datetime(2024, 1, 15))").unwrap(), "datetime");
+    assert_eq!(engine.eval::<String>("datetime(2024, 1, 15, 9, 30, 0).to_string()").unwrap(), "2024-01-15T09:30:00Z");
+    assert_eq!(engine.eval::<String>("datetime_from_unix(1705311000).to_string()").unwrap(), "2024-01-15T09:30:00Z");
+    assert_eq!(engine.eval::<INT>("datetime(1969, 12, 31, 23, 59, 59).unix_timestamp").unwrap(), -1);
+
+    let date = engine.eval::<DateTime>(r#"parse_datetime("2024-02-29T21:05:30.25+08:00")"#).unwrap();
+    assert_eq!((date.year(), date.month(), date.day()), (2024, 2, 29));
+    assert_eq!((date.hour(), date.minute(), date.second(), date.nanosecond()), (21, 5, 30, 250_000_000));
+    assert_eq!((date.weekday(), date.ordinal(), date.offset()), (4, 60, 480));
+    assert_eq!(date.to_string(), "2024-02-29T21:05:30.250+08:00");
+
+    assert_eq!(
+        engine
+            .eval::<INT>(r#"let d = parse_datetime("2024-02-29T21:05:30+08:00"); d.year + d.month + d.day + d.weekday + d.day_of_year"#)
+            .unwrap(),
+        2024 + 2 + 29 + 4 + 60
+    );
+
+    #[cfg(not(feature = "no_std"))]
+    assert!(engine.eval::<INT>("now().year").unwrap() >= 2024);
+
+    assert!(matches!(*engine.run("datetime(2023, 2, 29)").unwrap_err(), EvalAltResult::ErrorArithmetic(..)));
+    assert!(matches!(*engine.run("datetime(2024, 13, 1)").unwrap_err(), EvalAltResult::ErrorArithmetic(..)));
+}
+
+#[test]
+fn test_datetime_format() {
+    let engine = make_engine();
+
+    assert_eq!(engine.eval::<String>(r#"datetime(2024, 1, 5, 21, 30, 0).format("%a %A %e %b %B %y %j %u %w %I:%M %p")"#).unwrap(), "Fri Friday  5 Jan January 24 005 5 5 09:30 PM");
+    assert_eq!(engine.eval::<String>(r#"parse_datetime("2024-01-15T09:30:00.123456+05:30").format("%F %T.%3f %z %:z")"#).unwrap(), "2024-01-15 09:30:00.123 +0530 +05:30");
+    assert_eq!(engine.eval::<String>(r#"datetime_from_unix(1705311000).format("%s %%")"#).unwrap(), "1705311000 %");
+
+    assert!(engine.run(r#"datetime(2024, 1, 1).format("%Q")"#).is_err());
+}
+
+#[test]
+fn test_datetime_parse() {
+    let engine = make_engine();
+
+    assert_eq!(engine.eval::<String>(r#"parse_datetime("2024-01-15 09:30:00z").to_string()"#).unwrap(), "2024-01-15T09:30:00Z");
+    assert_eq!(engine.eval::<String>(r#"parse_datetime("15/01/2024 9:30 pm", "%d/%m/%Y %I:%M %p").to_string()"#).unwrap(), "2024-01-15T21:30:00Z");
+    assert_eq!(
+        engine
+            .eval::<String>(r#"parse_datetime("Monday, March 4 2024 17:05:03 -0130", "%A, %B %e %Y %T %z").to_string()"#)
+            .unwrap(),
+        "2024-03-04T17:05:03-01:30"
+    );
+    assert_eq!(engine.eval::<String>(r#"parse_datetime("1705311000", "%s").to_string()"#).unwrap(), "2024-01-15T09:30:00Z");
+
+    assert_eq!(DateTime::parse_from_str("24-1-5", "%y-%m-%d").unwrap(), DateTime::new(2024, 1, 5, 0, 0, 0).unwrap());
+    assert_eq!(DateTime::parse_rfc3339("1970-01-01T00:00:00Z").unwrap(), DateTime::UNIX_EPOCH);
+
+    for (string, format) in [("2024-01-15T09:30:00", ""), ("2024-01-15T25:00:00Z", ""), ("2024-01-15", "%F %T"), ("09:30", "%H:%M"), ("2024-01-15x", "%F")] {
+        let script = if format.is_empty() { format!("parse_datetime({string:?})") } else { format!("parse_datetime({string:?}, {format:?})") };
+
+        assert!(matches!(
+            *engine.run(&script).unwrap_err(),
+            EvalAltResult::ErrorArithmetic(msg, ..) if msg.starts_with(&format!("Error parsing date/time '{string}'"))
+        ));
+    }
+}
+
+#[test]
+fn test_datetime_arithmetic() {
+    let engine = make_engine();
+
+    assert_eq!(engine.eval::<String>("datetime(2024, 1, 31).add_months(1).to_string()").unwrap(), "2024-02-29T00:00:00Z");
+    assert_eq!(engine.eval::<String>("datetime(2024, 3, 31).add_months(-13).to_string()").unwrap(), "2023-02-28T00:00:00Z");
+    assert_eq!(engine.eval::<String>("datetime(2024, 2, 29).add_years(1).to_string()").unwrap(), "2025-02-28T00:00:00Z");
+    assert_eq!(engine.eval::<String>("datetime(2024, 3, 1).add_days(-1).to_string()").unwrap(), "2024-02-29T00:00:00Z");
+    assert_eq!(
+        engine
+            .eval::<String>("datetime(2024, 12, 31, 23, 0, 0).add_hours(1).add_minutes(30).add_seconds(15).to_string()")
+            .unwrap(),
+        "2025-01-01T00:30:15Z"
+    );
+    assert_eq!(engine.eval::<String>("let d = datetime(2024, 1, 1); d += 90; d -= 30; d.to_string()").unwrap(), "2024-01-01T00:01:00Z");
+
+    // Offsets are kept
+    assert_eq!(engine.eval::<String>(r#"parse_datetime("2024-01-31T23:30:00-05:00").add_months(1).to_string()"#).unwrap(), "2024-02-29T23:30:00-05:00");
+    assert_eq!(engine.eval::<String>(r#"parse_datetime("2024-01-15T09:30:00Z").to_offset(-300).to_string()"#).unwrap(), "2024-01-15T04:30:00-05:00");
+    assert_eq!(engine.eval::<String>(r#"parse_datetime("2024-01-15T09:30:00+02:00").to_utc().to_string()"#).unwrap(), "2024-01-15T07:30:00Z");
+
+    assert!(matches!(*engine.run("datetime(9999, 12, 31).add_days(1)").unwrap_err(), EvalAltResult::ErrorArithmetic(..)));
+    assert!(matches!(*engine.run("datetime(2024, 1, 1).to_offset(1440)").unwrap_err(), EvalAltResult::ErrorArithmetic(..)));
+
+    #[cfg(not(feature = "no_float"))]
+    {
+        assert_eq!(
+            engine
+                .eval::<rhai::FLOAT>(r#"parse_datetime("2024-01-15T10:00:00+01:00") - parse_datetime("2024-01-15T09:30:00.5Z")"#)
+                .unwrap(),
+            -1800.5
+        );
+        assert_eq!(engine.eval::<String>("(datetime(2024, 1, 1) + 1.25).to_string()").unwrap(), "2024-01-01T00:00:01.250Z");
+    }
+    #[cfg(feature = "no_float")]
+    assert_eq!(engine.eval::<INT>(r#"parse_datetime("2024-01-15T10:00:00+01:00") - parse_datetime("2024-01-15T09:30:00Z")"#).unwrap(), -1800);
+}
+
+#[test]
+fn test_datetime_compare() {
+    let engine = make_engine();
+
+    assert!(engine.eval::<bool>(r#"parse_datetime("2024-01-15T10:00:00+01:00") == parse_datetime("2024-01-15T09:00:00Z")"#).unwrap());
+    assert!(engine.eval::<bool>("datetime(2024, 1, 1) < datetime(2024, 1, 1, 0, 0, 1)").unwrap());
+    assert!(engine.eval::<bool>("datetime(2024, 1, 1) >= datetime(2023, 12, 31)").unwrap());
+    assert!(engine.eval::<bool>("datetime(2024, 1, 1) != datetime(2023, 12, 31)").unwrap());
+}
@@ -71,3 +71,148 @@ fn test_debugger_state() {
 
     engine.run("let x = 42;").unwrap();
 }
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+fn test_debugger_exceptions() {
+    use rhai::debugger::{DebuggerCommand, DebuggerEvent};
+    use std::sync::{Arc, Mutex};
+
+    let mut engine = Engine::new();
+    let exceptions = Arc::new(Mutex::new(Vec::new()));
+    let list = exceptions.clone();
+
+    #[allow(deprecated)]
+    engine.register_debugger(
+        |_, mut debugger| {
+            debugger.set_break_on_exceptions(true);
+            debugger
+        },
+        move |_, event, _, _, _| {
+            if let DebuggerEvent::Exception(err) = event {
+                list.lock().unwrap().push(err.to_string());
+            }
+            Ok(DebuggerCommand::Continue)
+        },
+    );
+
+    let err = engine
+        .run(
+            r#"
+                fn foo(x) {
+                    if x > 2 { throw `boom${x}`; }
+                    x
+                }
+                try { foo(5); } catch { }
+                foo(1);
+                foo(3);
+            "#,
+        )
+        .unwrap_err();
+
+    assert!(err.to_string().contains("boom3"));
+
+    // Each exception is only reported once, where it is raised
+    let exceptions = exceptions.lock().unwrap();
+    assert_eq!(exceptions.len(), 2);
+    assert!(exceptions[0].contains("boom5"));
+    assert!(exceptions[1].contains("boom3"));
+}
+
+#[test]
+#[cfg(feature = "metadata")]
+#[cfg(not(feature = "no_position"))]
+#[cfg(not(feature = "no_index"))]
+fn test_debugger_dap() {
+    use rhai::debugger::DapServer;
+    use serde_json::{json, Value};
+    use std::io::{Cursor, Write};
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct Output(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Output {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let requests = [
+        ("initialize", json!({ "adapterID": "rhai" })),
+        ("launch", json!({ "program": "test.rhai" })),
+        ("setBreakpoints", json!({ "source": { "path": "test.rhai" }, "breakpoints": [{ "line": 3 }] })),
+        ("configurationDone", Value::Null),
+        ("stackTrace", json!({ "threadId": 1 })),
+        ("scopes", json!({ "frameId": 1 })),
+        ("variables", json!({ "variablesReference": 1 })),
+        ("variables", json!({ "variablesReference": 2 })),
+        ("evaluate", json!({ "expression": "x" })),
+        ("next", json!({ "threadId": 1 })),
+        ("stackTrace", json!({ "threadId": 1 })),
+        ("continue", json!({ "threadId": 1 })),
+        ("disconnect", Value::Null),
+    ];
+
+    let mut input = Vec::new();
+
+    for (seq, (command, arguments)) in requests.iter().enumerate() {
+        let body = json!({ "seq": seq + 1, "type": "request", "command": command, "arguments": arguments }).to_string();
+        write!(input, "Content-Length: {}\r\n\r\n{}", body.len(), body).unwrap();
+    }
+
+    let output = Output::default();
+    let server = DapServer::new(Cursor::new(input), output.clone());
+
+    assert_eq!(server.wait_for_launch().unwrap().as_deref(), Some("test.rhai"));
+
+    let mut engine = Engine::new();
+    #[allow(deprecated)]
+    engine.register_dap_server(&server);
+
+    let mut ast = engine.compile("let x = 40;\nlet y = [1, 2];\nx += 2;\nlet z = x;\nz").unwrap();
+    ast.set_source("test.rhai");
+
+    assert_eq!(engine.eval_ast::<INT>(&ast).unwrap(), 42);
+    server.finish(0).unwrap();
+    assert!(server.is_disconnected());
+
+    let output = output.0.lock().unwrap();
+    let mut output = std::str::from_utf8(&output).unwrap();
+    let mut messages = Vec::<Value>::new();
+
+    while let Some((header, rest)) = output.split_once("\r\n\r\n") {
+        let len = header.strip_prefix("Content-Length: ").unwrap().parse::<usize>().unwrap();
+        messages.push(serde_json::from_str(&rest[..len]).unwrap());
+        output = &rest[len..];
+    }
+
+    let response = |command: &str, n: usize| messages.iter().filter(|m| m["type"] == "response" && m["command"] == command).nth(n).unwrap();
+    let events: Vec<_> = messages.iter().filter(|m| m["type"] == "event").map(|m| m["event"].as_str().unwrap()).collect();
+
+    assert_eq!(events, ["initialized", "stopped", "stopped", "exited", "terminated"]);
+
+    let stopped: Vec<_> = messages.iter().filter(|m| m["event"] == "stopped").map(|m| m["body"]["reason"].clone()).collect();
+    assert_eq!(stopped, ["breakpoint", "step"]);
+
+    assert_eq!(response("setBreakpoints", 0)["body"]["breakpoints"][0]["verified"], true);
+    assert_eq!(response("stackTrace", 0)["body"]["stackFrames"][0]["line"], 3);
+    assert_eq!(response("stackTrace", 0)["body"]["stackFrames"][0]["source"]["path"], "test.rhai");
+    assert_eq!(response("stackTrace", 1)["body"]["stackFrames"][0]["line"], 4);
+
+    let variables = &response("variables", 0)["body"]["variables"];
+    assert_eq!(variables[0]["name"], "x");
+    assert_eq!(variables[0]["value"], "40");
+    assert_eq!(variables[1]["name"], "y");
+    assert_eq!(variables[1]["variablesReference"], 2);
+
+    let items = &response("variables", 1)["body"]["variables"];
+    assert_eq!(items[1]["name"], "[1]");
+    assert_eq!(items[1]["value"], "2");
+
+    assert_eq!(response("evaluate", 0)["body"]["result"], "40");
+    assert_eq!(response("disconnect", 0)["success"], true);
+}
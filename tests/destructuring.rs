@@ -0,0 +1,114 @@
+#![cfg(not(feature = "no_index"))]
+use rhai::{Engine, EvalAltResult, ParseErrorType, INT};
+
+#[test]
+fn test_destructuring_let() {
+    let engine = Engine::new();
+
+    assert_eq!(engine.eval::<INT>("let [a, b] = [40, 2]; a + b").unwrap(), 42);
+    assert_eq!(engine.eval::<INT>("let [a, _, c] = [1, 2, 3]; a + c").unwrap(), 4);
+    assert_eq!(engine.eval::<INT>("let [a, b, ..rest] = [1, 2, 3, 4, 5]; a + b + len(rest)").unwrap(), 6);
+    assert_eq!(engine.eval::<INT>("let [a, ..] = [42, 0, 0]; a").unwrap(), 42);
+    assert_eq!(engine.eval::<INT>("let [a, [b, c]] = [1, [2, 39]]; a + b + c").unwrap(), 42);
+    assert_eq!(engine.eval::<INT>("let x = 1; let [x, y] = [x + 1, x + 40]; x + y").unwrap(), 43);
+    assert_eq!(engine.eval::<INT>("let a = 42; { let [a] = [1]; } a").unwrap(), 42);
+    assert_eq!(engine.eval::<INT>("let [a, b] = [1, 2]; a = 40; a + b").unwrap(), 42);
+
+    assert!(matches!(
+        engine.compile("const [a, b] = [1, 2]; a = 40;").unwrap_err().err_type(),
+        ParseErrorType::AssignmentToConstant(v) if v == "a"
+    ));
+
+    #[cfg(not(feature = "no_object"))]
+    {
+        assert_eq!(engine.eval::<INT>(r#"let #{ name, age } = #{ name: "Bob", age: 42 }; age"#).unwrap(), 42);
+        assert_eq!(engine.eval::<String>(r#"let #{ name: n, .. } = #{ name: "Bob", age: 42 }; n"#).unwrap(), "Bob");
+        assert_eq!(engine.eval::<INT>(r#"let #{ name, ..others } = #{ name: "Bob", age: 42, id: 1 }; others.age + others.len()"#).unwrap(), 44);
+        assert_eq!(engine.eval::<INT>("let #{ p: [x, y] } = #{ p: [40, 2] }; x + y").unwrap(), 42);
+    }
+
+    assert!(matches!(engine.compile("let [a, a] = [1, 2];").unwrap_err().err_type(), ParseErrorType::DuplicatedVariable(v) if v == "a"));
+    assert!(matches!(engine.compile("let [a, b];").unwrap_err().err_type(), ParseErrorType::MissingToken(..)));
+}
+
+#[test]
+fn test_destructuring_errors() {
+    let engine = Engine::new();
+
+    assert!(matches!(
+        *engine.run("let [a, b] = [1, 2, 3];").unwrap_err(),
+        EvalAltResult::ErrorMismatchDataType(e, a, ..) if e == "array of 2 items" && a == "array of 3 items"
+    ));
+    assert!(matches!(
+        *engine.run("let [a, b, ..] = [1];").unwrap_err(),
+        EvalAltResult::ErrorMismatchDataType(e, a, ..) if e == "array of at least 2 items" && a == "array of 1 item"
+    ));
+    assert!(matches!(
+        *engine.run("let [a, [b, c]] = [1, 2];").unwrap_err(),
+        EvalAltResult::ErrorMismatchDataType(e, a, ..) if e == "array" && (a == "i64" || a == "i32")
+    ));
+
+    #[cfg(not(feature = "no_object"))]
+    {
+        assert!(matches!(
+            *engine.run("let #{ name, age } = #{ name: 1 };").unwrap_err(),
+            EvalAltResult::ErrorPropertyNotFound(p, ..) if p == "age"
+        ));
+        assert!(matches!(
+            *engine.run("let #{ name } = #{ name: 1, age: 2 };").unwrap_err(),
+            EvalAltResult::ErrorMismatchDataType(e, a, ..) if e == "object map with 1 property" && a == "object map with 2 properties"
+        ));
+        assert!(matches!(
+            *engine.run("let #{ name } = [1];").unwrap_err(),
+            EvalAltResult::ErrorMismatchDataType(e, a, ..) if e == "map" && a == "array"
+        ));
+    }
+}
+
+#[test]
+fn test_destructuring_for() {
+    let engine = Engine::new();
+
+    assert_eq!(engine.eval::<INT>("let s = 0; for [k, v] in [[1, 2], [3, 4]] { s += k * v; } s").unwrap(), 14);
+    assert_eq!(engine.eval::<INT>("let s = 0; for ([k, v], i) in [[1, 2], [3, 4]] { s += k * v + i; } s").unwrap(), 15);
+
+    #[cfg(not(feature = "no_object"))]
+    assert_eq!(engine.eval::<INT>("let s = 0; for #{ x, y } in [#{ x: 1, y: 2 }, #{ x: 30, y: 9 }] { s += x + y; } s").unwrap(), 42);
+
+    assert!(engine.run("for [k, v] in [[1, 2], [3]] {}").is_err());
+    assert!(matches!(engine.compile("for ([i, x], i) in [] {}").unwrap_err().err_type(), ParseErrorType::DuplicatedVariable(v) if v == "i"));
+}
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+fn test_destructuring_fn_params() {
+    let engine = Engine::new();
+
+    assert_eq!(engine.eval::<INT>("fn add([a, b]) { a + b } add([40, 2])").unwrap(), 42);
+    assert_eq!(engine.eval::<INT>("fn f(z, [a, ..rest]) { z + a + len(rest) } f(40, [1, 0])").unwrap(), 42);
+
+    #[cfg(not(feature = "no_object"))]
+    assert_eq!(engine.eval::<INT>("fn f([a, b], #{ x, y }) { a + b + x + y } f([1, 2], #{ x: 30, y: 9 })").unwrap(), 42);
+
+    assert!(matches!(
+        *engine.run("fn add([a, b]) { a + b } add(42)").unwrap_err(),
+        EvalAltResult::ErrorInFunctionCall(f, ..) if f == "add"
+    ));
+    assert!(matches!(engine.compile("fn f([a, b], a) { a }").unwrap_err().err_type(), ParseErrorType::FnDuplicatedParam(_, p) if p == "a"));
+}
+
+#[cfg(not(feature = "no_optimize"))]
+#[cfg(not(feature = "no_position"))]
+#[test]
+fn test_destructuring_optimizer() {
+    let mut engine = Engine::new();
+
+    engine.set_optimization_level(rhai::OptimizationLevel::Simple);
+
+    let ast = engine.compile("const [a, b] = [40, 2]; a + b").unwrap();
+    assert!(format!("{ast:?}").ends_with("Expr(42 @ 1:27)] }"));
+
+    // Non-matching constants are left for a runtime error
+    let ast = engine.compile("const [a, b] = [40]; a + b").unwrap();
+    assert!(engine.run_ast(&ast).is_err());
+}
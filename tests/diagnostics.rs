@@ -0,0 +1,72 @@
+use rhai::{Engine, ParseErrorType, INT};
+
+#[test]
+fn test_diagnostics_multiple_errors() {
+    let engine = Engine::new();
+
+    let (ast, errors) = engine.compile_with_diagnostics(
+        "
+            let x = 40 +;
+            let y = 2;
+            x +* y;
+            if y > 0 {
+                let z = ;
+                y += 1;
+            }
+            y
+        ",
+    );
+
+    assert_eq!(errors.len(), 3);
+    #[cfg(not(feature = "no_position"))]
+    assert_eq!(errors.iter().map(|err| err.position().line().unwrap()).collect::<Vec<_>>(), [2, 4, 6]);
+    assert_eq!(engine.eval_ast::<INT>(&ast).unwrap(), 3);
+
+    let (ast, errors) = engine.compile_with_diagnostics("let x = 40; x + 2");
+
+    assert!(errors.is_empty());
+    assert_eq!(engine.eval_ast::<INT>(&ast).unwrap(), 42);
+}
+
+#[test]
+fn test_diagnostics_missing_tokens() {
+    let engine = Engine::new();
+
+    let (ast, errors) = engine.compile_with_diagnostics("let x = 40 let y = 2; x + y");
+
+    assert_eq!(errors.len(), 1);
+    assert!(matches!(errors[0].err_type(), ParseErrorType::MissingToken(token, ..) if token == ";"));
+    assert_eq!(engine.eval_ast::<INT>(&ast).unwrap(), 42);
+
+    let (_, errors) = engine.compile_with_diagnostics("let x = 40; } let y = ; { x");
+
+    assert_eq!(errors.len(), 4);
+    assert!(matches!(errors[0].err_type(), ParseErrorType::BadInput(..)));
+    assert!(matches!(errors[1].err_type(), ParseErrorType::BadInput(..)));
+    assert!(matches!(errors[2].err_type(), ParseErrorType::MissingToken(token, ..) if token == ";"));
+    assert!(matches!(errors[3].err_type(), ParseErrorType::MissingToken(token, ..) if token == "}"));
+}
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+fn test_diagnostics_functions() {
+    let engine = Engine::new();
+
+    let (ast, errors) = engine.compile_with_diagnostics(
+        "
+            fn foo(x) {
+                let y = x +;
+                x * 2
+            }
+            fn bar(x y) { x }
+            let f = |x| { x + ; x };
+            foo(21)
+        ",
+    );
+
+    assert_eq!(errors.len(), 3);
+    #[cfg(not(feature = "no_position"))]
+    assert_eq!(errors.iter().map(|err| err.position().line().unwrap()).collect::<Vec<_>>(), [3, 6, 7]);
+    assert_eq!(ast.iter_functions().filter(|f| f.name == "foo").count(), 1);
+    assert_eq!(engine.eval_ast::<INT>(&ast).unwrap(), 42);
+}
@@ -0,0 +1,216 @@
+#![cfg(not(feature = "no_function"))]
+use rhai::{Engine, EnumValue, EvalAltResult, ParseErrorType, INT};
+use std::sync::{Arc, RwLock};
+
+const SHAPE: &str = "
+    enum Shape {
+        Circle(r),
+        Rect(w, h),
+        Empty,
+    }
+";
+
+#[test]
+fn test_enums() {
+    let engine = Engine::new();
+
+    assert_eq!(engine.eval::<String>(&format!("{SHAPE} type_of(Shape::Circle(1))")).unwrap(), "Shape");
+    assert_eq!(engine.eval::<String>(&format!("{SHAPE} type_of(Shape::Empty)")).unwrap(), "Shape");
+    assert_eq!(engine.eval::<String>(&format!("{SHAPE} let s = Shape::Rect(1, 2); `${{s}}`")).unwrap(), "Shape::Rect(1, 2)");
+
+    let value = engine.eval::<EnumValue>(&format!("{SHAPE} Shape::Rect(40, 2)")).unwrap();
+    assert_eq!(value.type_name(), "Shape");
+    assert_eq!(value.variant(), "Rect");
+    assert_eq!(value.values().len(), 2);
+    assert_eq!(value.values()[0].as_int().unwrap(), 40);
+    assert_eq!(format!("{value:?}"), "Shape::Rect(40, 2)");
+
+    assert!(matches!(
+        *engine.run(&format!("{SHAPE} Shape::Circle(1, 2)")).unwrap_err(),
+        EvalAltResult::ErrorFunctionNotFound(f, ..) if f.starts_with("Shape::Circle")
+    ));
+
+    assert!(matches!(engine.compile(format!("{SHAPE} Shape::Square(1)")).unwrap_err().err_type(), ParseErrorType::MissingSymbol(..)));
+    assert!(matches!(engine.compile("enum Shape { Circle(r), Circle }").unwrap_err().err_type(), ParseErrorType::MissingSymbol(..)));
+    assert!(matches!(engine.compile("enum Shape {}").unwrap_err().err_type(), ParseErrorType::MissingSymbol(..)));
+    assert!(matches!(engine.compile("{ enum Shape { Circle } }").unwrap_err().err_type(), ParseErrorType::WrongTypeDefinition));
+    assert!(matches!(engine.compile("fn foo() { enum Shape { Circle } }").unwrap_err().err_type(), ParseErrorType::WrongTypeDefinition));
+}
+
+#[test]
+fn test_enums_switch() {
+    let engine = Engine::new();
+
+    let script = format!(
+        "
+            {SHAPE}
+
+            fn area(shape) {{
+                switch shape {{
+                    Shape::Circle(r) if r < 0 => throw \"negative radius\",
+                    Shape::Circle(r) => 3 * r * r,
+                    Shape::Rect(w, 0) | Shape::Rect(0, w) => 0,
+                    Shape::Rect(w, h) => w * h,
+                    Shape::Empty => 0,
+                }}
+            }}
+        "
+    );
+
+    assert_eq!(engine.eval::<INT>(&format!("{script} area(Shape::Circle(2))")).unwrap(), 12);
+    assert_eq!(engine.eval::<INT>(&format!("{script} area(Shape::Rect(6, 7))")).unwrap(), 42);
+    assert_eq!(engine.eval::<INT>(&format!("{script} area(Shape::Rect(6, 0))")).unwrap(), 0);
+    assert_eq!(engine.eval::<INT>(&format!("{script} area(Shape::Empty)")).unwrap(), 0);
+    assert!(engine.run(&format!("{script} area(Shape::Circle(-1))")).is_err());
+
+    // Pattern variables are scoped to their case and shadow outer variables
+    assert_eq!(
+        engine
+            .eval::<INT>(&format!(
+                "
+                    {SHAPE}
+                    const r = 1;
+                    let x = switch Shape::Circle(42) {{ Shape::Circle(r) => r, _ => 0 }};
+                    x + r
+                "
+            ))
+            .unwrap(),
+        43
+    );
+
+    // Nested patterns and wildcards
+    assert_eq!(
+        engine
+            .eval::<INT>(
+                "
+                    enum Tree { Leaf(v), Node(l, r) }
+
+                    fn sum(t) {
+                        switch t {
+                            Tree::Leaf(v) => v,
+                            Tree::Node(Tree::Leaf(_), r) => 100 + sum(r),
+                            Tree::Node(l, r) => sum(l) + sum(r),
+                        }
+                    }
+
+                    sum(Tree::Node(Tree::Node(Tree::Leaf(1), Tree::Leaf(2)), Tree::Leaf(3)))
+                "
+            )
+            .unwrap(),
+        105
+    );
+
+    assert!(matches!(engine.compile(format!("{SHAPE} switch 1 {{ Shape::Circle(a, b) => 0 }}")).unwrap_err().err_type(), ParseErrorType::MalformedPattern(..)));
+    assert!(matches!(engine.compile(format!("{SHAPE} switch 1 {{ Shape::Square => 0 }}")).unwrap_err().err_type(), ParseErrorType::MalformedPattern(..)));
+    assert!(matches!(
+        engine.compile(format!("{SHAPE} switch 1 {{ Shape::Rect(a, a) => 0 }}")).unwrap_err().err_type(),
+        ParseErrorType::DuplicatedVariable(v) if v == "a"
+    ));
+    assert!(matches!(engine.compile(format!("{SHAPE} switch 1 {{ Shape::Rect(a, _) | Shape::Circle(b) => 0 }}")).unwrap_err().err_type(), ParseErrorType::MalformedPattern(..)));
+}
+
+#[test]
+fn test_enums_methods() {
+    let engine = Engine::new();
+
+    #[cfg(not(feature = "no_object"))]
+    assert_eq!(
+        engine
+            .eval::<INT>(
+                "
+                    enum Shape {
+                        Square(s),
+                        Rect(w, h),
+
+                        fn area() {
+                            switch this {
+                                Shape::Square(s) => s * s,
+                                Shape::Rect(w, h) => w * h,
+                            }
+                        }
+                    }
+
+                    Shape::Square(4).area() + Shape::Rect(2, 3).area()
+                "
+            )
+            .unwrap(),
+        22
+    );
+
+    assert_eq!(engine.eval::<INT>("enum Value { Int(x: int) } switch Value::Int(42) { Value::Int(x) => x }").unwrap(), 42);
+    assert!(engine.run(r#"enum Value { Int(x: int) } Value::Int("hello")"#).is_err());
+}
+
+#[test]
+fn test_enums_exhaustiveness() {
+    let warnings = Arc::new(RwLock::new(Vec::<String>::new()));
+
+    let mut engine = Engine::new();
+    let logger = warnings.clone();
+    engine.on_parse_warning(move |msg, _| logger.write().unwrap().push(msg.to_string()));
+
+    engine.compile(format!("{SHAPE} fn f(s) {{ switch s {{ Shape::Circle(r) => r }} }}")).unwrap();
+    assert_eq!(*warnings.read().unwrap(), ["Non-exhaustive switch: missing Shape::Empty, Shape::Rect"]);
+    warnings.write().unwrap().clear();
+
+    // Conditional cases and refutable payloads do not count
+    engine
+        .compile(format!("{SHAPE} fn f(s) {{ switch s {{ Shape::Circle(r) => r, Shape::Rect(1, h) => h, Shape::Empty if s != () => 0 }} }}"))
+        .unwrap();
+    assert_eq!(*warnings.read().unwrap(), ["Non-exhaustive switch: missing Shape::Empty, Shape::Rect"]);
+    warnings.write().unwrap().clear();
+
+    engine
+        .compile(format!("{SHAPE} fn f(s) {{ switch s {{ Shape::Circle(r) => r, Shape::Rect(_, _) | Shape::Empty => 0 }} }}"))
+        .unwrap();
+    engine.compile(format!("{SHAPE} fn f(s) {{ switch s {{ Shape::Circle(r) => r, _ => 0 }} }}")).unwrap();
+    assert!(warnings.read().unwrap().is_empty());
+}
+
+#[test]
+fn test_switch_patterns() {
+    let engine = Engine::new();
+
+    #[cfg(not(feature = "no_index"))]
+    {
+        let script = "
+            fn f(x) {
+                switch x {
+                    [] => 0,
+                    [1, 2] => 12,
+                    [a] => a,
+                    [a, b] if a == b => -1,
+                    [a, b] => a + b,
+                    [a, _, ..] => 100 + a,
+                    _ => 999,
+                }
+            }
+        ";
+
+        assert_eq!(engine.eval::<INT>(&format!("{script} f([])")).unwrap(), 0);
+        assert_eq!(engine.eval::<INT>(&format!("{script} f([1, 2])")).unwrap(), 12);
+        assert_eq!(engine.eval::<INT>(&format!("{script} f([42])")).unwrap(), 42);
+        assert_eq!(engine.eval::<INT>(&format!("{script} f([3, 3])")).unwrap(), -1);
+        assert_eq!(engine.eval::<INT>(&format!("{script} f([40, 2])")).unwrap(), 42);
+        assert_eq!(engine.eval::<INT>(&format!("{script} f([1, 2, 3, 4])")).unwrap(), 101);
+        assert_eq!(engine.eval::<INT>(&format!("{script} f(42)")).unwrap(), 999);
+    }
+
+    #[cfg(not(feature = "no_object"))]
+    {
+        let script = r#"
+            fn f(x) {
+                switch x {
+                    #{ kind: "point", x, y } => x + y,
+                    #{ kind: "circle", r, .. } => r,
+                    _ => 0,
+                }
+            }
+        "#;
+
+        assert_eq!(engine.eval::<INT>(&format!(r#"{script} f(#{{ kind: "point", x: 40, y: 2 }})"#)).unwrap(), 42);
+        assert_eq!(engine.eval::<INT>(&format!(r#"{script} f(#{{ kind: "point", x: 40, y: 2, z: 1 }})"#)).unwrap(), 0);
+        assert_eq!(engine.eval::<INT>(&format!(r#"{script} f(#{{ kind: "circle", r: 42, color: "red" }})"#)).unwrap(), 42);
+        assert_eq!(engine.eval::<INT>(&format!(r#"{script} struct Point {{ kind, x, y }} f(Point("point", 1, 2))"#)).unwrap(), 3);
+    }
+}
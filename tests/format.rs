@@ -0,0 +1,147 @@
+#![cfg(not(feature = "no_position"))]
+use rhai::{Engine, FormatOptions};
+
+#[test]
+fn test_format() {
+    let engine = Engine::new();
+
+    assert_eq!(engine.format_script("").unwrap(), "");
+    assert_eq!(engine.format_script("let x=1+2*3;x").unwrap(), "let x = 1 + 2 * 3;\nx\n");
+    assert_eq!(engine.format_script("let x = -1; let y = x - -1;").unwrap(), "let x = -1;\nlet y = x - -1;\n");
+    assert_eq!(engine.format_script("let x=0;\n\n\n\nx+=1;").unwrap(), "let x = 0;\n\nx += 1;\n");
+    assert_eq!(engine.format_script("if x>1{print(x)}else{print(-x)}").unwrap(), "if x > 1 { print(x) } else { print(-x) }\n");
+    assert_eq!(engine.format_script("if x > 1 { print(x) } else {\nprint(-x);\nx\n}").unwrap(), "if x > 1 {\n    print(x)\n} else {\n    print(-x);\n    x\n}\n");
+    assert_eq!(engine.format_script("while x<10{\nx+=1;\n}").unwrap(), "while x < 10 {\n    x += 1;\n}\n");
+    assert_eq!(engine.format_script("let s = `hello ${ x+1 }!`;").unwrap(), "let s = `hello ${ x+1 }!`;\n");
+
+    // A block with a single statement is broken if a nested block is broken
+    assert_eq!(engine.format_script("while x { if y { a(); b(); } }").unwrap(), "while x {\n    if y {\n        a();\n        b();\n    }\n}\n");
+    assert_eq!(engine.format_script("while x { if y { a() } }").unwrap(), "while x { if y { a() } }\n");
+    assert_eq!(engine.format_script("loop { if y { a() } else { b(); c(); } }").unwrap(), "loop {\n    if y {\n        a()\n    } else {\n        b();\n        c();\n    }\n}\n");
+
+    assert!(engine.format_script("let x = ;").is_err());
+}
+
+#[test]
+#[cfg(not(feature = "no_index"))]
+#[cfg(not(feature = "no_object"))]
+fn test_format_collections() {
+    let engine = Engine::new();
+
+    assert_eq!(engine.format_script("let a=[1,2,3,];").unwrap(), "let a = [1, 2, 3];\n");
+    assert_eq!(engine.format_script("let m=#{a:1,\"b\":[2,3]};m.a").unwrap(), "let m = #{a: 1, \"b\": [2, 3]};\nm.a\n");
+    assert_eq!(engine.format_script("x[0]=a?.b??c?[1];").unwrap(), "x[0] = a?.b ?? c?[1];\n");
+
+    let options = FormatOptions::new().max_width(20).indent(2);
+
+    assert_eq!(engine.format_script_with_options("let a = [11111, 22222, 33333];", options).unwrap(), "let a = [\n  11111,\n  22222,\n  33333,\n];\n");
+    assert_eq!(engine.format_script_with_options("let m = #{ aaaaa: 1, bbbbb: 2 };", options).unwrap(), "let m = #{\n  aaaaa: 1,\n  bbbbb: 2,\n};\n");
+    assert_eq!(engine.format_script_with_options("foo(aaaaaaaa, bbbbbbbbbb);", options).unwrap(), "foo(\n  aaaaaaaa,\n  bbbbbbbbbb\n);\n");
+}
+
+#[test]
+fn test_format_comments() {
+    let engine = Engine::new();
+
+    let script = "
+//! Module documentation
+
+// Leading comment
+let x = 1;    // trailing comment
+/* block */ let y = x /* inline */ + 1;
+
+/*   multi-line
+     comment */
+const Z = 42;
+";
+
+    assert_eq!(
+        engine.format_script(script).unwrap(),
+        "//! Module documentation
+
+// Leading comment
+let x = 1; // trailing comment
+/* block */ let y = x /* inline */ + 1;
+
+/*   multi-line
+     comment */
+const Z = 42;
+"
+    );
+
+    assert_eq!(engine.format_script("let x = 1 + // one\n2;").unwrap(), "let x = 1 + // one\n    2;\n");
+}
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+fn test_format_functions() {
+    let engine = Engine::new();
+
+    assert_eq!(engine.format_script("fn add(a,b){a+b}\nadd(1,2)").unwrap(), "fn add(a, b) { a + b }\nadd(1, 2)\n");
+    assert_eq!(engine.format_script("/// Add two numbers.\n/**   Really.\n   */\nfn add(a,b){a+b}").unwrap(), "/// Add two numbers.\n/**   Really.\n   */\nfn add(a, b) { a + b }\n");
+    assert_eq!(engine.format_script("let f=|a,b|a+b;let g=||{42};").unwrap(), "let f = |a, b| a + b;\nlet g = || { 42 };\n");
+    assert_eq!(engine.format_script("fn foo(x){\nif x>0{\nreturn x;\n}\n-x\n}").unwrap(), "fn foo(x) {\n    if x > 0 {\n        return x;\n    }\n    -x\n}\n");
+}
+
+#[test]
+fn test_format_switch() {
+    let engine = Engine::new();
+
+    assert_eq!(
+        engine.format_script("switch x{1|2=>print(\"a\"),3..10=>{print(\"b\");}\n_=>()}").unwrap(),
+        "switch x {\n    1 | 2 => print(\"a\"),\n    3..10 => {\n        print(\"b\");\n    },\n    _ => (),\n}\n"
+    );
+}
+
+#[test]
+fn test_format_idempotent() {
+    let engine = Engine::new();
+
+    let script = "
+// Compute something
+let total = 0;
+for (v, i) in 1..4 { total += v * i; }
+
+do { total -= 1; } while total > 10;
+
+loop {
+    if total < 0 { break; }
+    total -= 2;
+}
+try { throw \"error\"; } catch (err) { print(err) }
+total
+";
+
+    let formatted = engine.format_script(script).unwrap();
+    assert_eq!(engine.format_script(&formatted).unwrap(), formatted);
+
+    let options = FormatOptions::new().max_width(10);
+    let formatted = engine.format_script_with_options(script, options).unwrap();
+    assert_eq!(engine.format_script_with_options(&formatted, options).unwrap(), formatted);
+}
+
+#[test]
+fn test_format_check() {
+    use std::process::Command;
+
+    let dir = std::env::temp_dir().join("rhai-fmt-test");
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let good = dir.join("good.rhai");
+    let bad = dir.join("bad.rhai");
+    std::fs::write(&good, "#!/usr/bin/env rhai-run\nlet x = 1;\n").unwrap();
+    std::fs::write(&bad, "#!/usr/bin/env rhai-run\nlet x=1;").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_rhai-fmt")).arg("--check").arg(&good).arg(&bad).output().unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(!stdout.contains("good.rhai"));
+    assert!(stdout.contains("bad.rhai"));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_rhai-fmt")).arg(&bad).output().unwrap();
+    assert!(output.status.success());
+    assert_eq!(std::fs::read_to_string(&bad).unwrap(), "#!/usr/bin/env rhai-run\nlet x = 1;\n");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_rhai-fmt")).arg("--check").arg(&good).arg(&bad).output().unwrap();
+    assert!(output.status.success());
+}
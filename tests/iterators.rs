@@ -0,0 +1,102 @@
+#![cfg(not(feature = "no_function"))]
+#![cfg(not(feature = "no_object"))]
+use rhai::{Engine, EvalAltResult, INT};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+#[test]
+fn test_iterators_adapters() {
+    let engine = Engine::new();
+
+    assert_eq!(engine.eval::<INT>("let s = 0; for x in iter(1..=10).filter(|v| v % 2 == 0).map(|v| v * v) { s += x; } s").unwrap(), 220);
+    assert_eq!(engine.eval::<INT>("let s = 0; for x in iter(1..100).skip(2).take(3) { s += x; } s").unwrap(), 12);
+    assert_eq!(engine.eval::<INT>("let s = 0; for x in iter(1..3).chain(10..12).take(-1) { s += x; } s").unwrap(), 0);
+    assert_eq!(engine.eval::<INT>("let s = 0; for x in iter(1..3).chain(10..12) { s += x; } s").unwrap(), 24);
+    assert_eq!(engine.eval::<INT>("let s = 0; for x in iter(iter(5..8)).map(|v, i| v * i) { s += x; } s").unwrap(), 20);
+    assert_eq!(engine.eval::<INT>("let s = 0; for (x, i) in iter(5..8).filter(|v| v != 6) { s += x * i; } s").unwrap(), 7);
+    assert_eq!(engine.eval::<INT>("for x in generator(|i| i * 3) { if x > 10 { break x; } }").unwrap(), 12);
+    assert_eq!(engine.eval::<String>("let s = \"\"; for c in iter(\"hello\").map(|c| to_upper(c)).skip(1) { s += c; } s").unwrap(), "ELLO");
+
+    // Iteration starts from the beginning every time
+    assert_eq!(
+        engine
+            .eval::<INT>("let g = generator(|i| if i < 4 { i }); let s = 0; for x in g { s += x; } for x in g { s += x; } s")
+            .unwrap(),
+        12
+    );
+
+    assert_eq!(
+        engine
+            .eval::<INT>(
+                "
+                    let fib = generator(#{ a: 0, b: 1 }, || {
+                        let v = this.a;
+                        this.a = this.b;
+                        this.b += v;
+                        v
+                    });
+                    let s = 0;
+                    for x in fib.take(10) { s += x; }
+                    for x in fib.take(10) { s += x; }
+                    s
+                "
+            )
+            .unwrap(),
+        176
+    );
+
+    #[cfg(not(feature = "no_index"))]
+    {
+        assert_eq!(
+            engine
+                .eval::<String>("let s = \"\"; for x in iter(\"abc\").zip(generator(|i| i)).skip(1) { s += `${x[0]}${x[1]}`; } s")
+                .unwrap(),
+            "b1c2"
+        );
+        assert_eq!(engine.eval::<String>("let s = \"\"; for x in iter([7, 8]).enumerate() { s += `${x[0]}:${x[1]} `; } s").unwrap(), "0:7 1:8 ");
+        assert_eq!(engine.eval::<INT>("let s = 0; for x in iter([1, 2, 3]).zip([4, 5]) { s += x[0] * x[1]; } s").unwrap(), 14);
+    }
+
+    assert!(matches!(*engine.run("for x in iter(42) {}").unwrap_err(), EvalAltResult::ErrorFor(..)));
+    assert!(matches!(*engine.run("for x in iter(1..3).chain(42) {}").unwrap_err(), EvalAltResult::ErrorFor(..)));
+    assert!(matches!(engine.run("for x in iter(1..3).map(|v| throw v) {}").unwrap_err().unwrap_inner(), EvalAltResult::ErrorRuntime(..)));
+    assert!(matches!(engine.run("for x in generator(|| throw 42) {}").unwrap_err().unwrap_inner(), EvalAltResult::ErrorRuntime(..)));
+}
+
+#[test]
+fn test_iterators_lazy() {
+    let mut engine = Engine::new();
+
+    let count = Arc::new(AtomicUsize::new(0));
+    let counter = count.clone();
+
+    engine.register_fn("next", move |i: INT| {
+        counter.fetch_add(1, Ordering::SeqCst);
+        i * 2
+    });
+
+    assert_eq!(engine.eval::<INT>("let s = 0; for x in generator(|i| next(i)).take(5) { s += x; } s").unwrap(), 20);
+    assert_eq!(count.load(Ordering::SeqCst), 5);
+
+    count.store(0, Ordering::SeqCst);
+
+    assert_eq!(engine.eval::<INT>("let s = 0; for x in iter(0..1000000).map(|v| next(v)) { if x >= 6 { break; } s += x; } s").unwrap(), 6);
+    assert_eq!(count.load(Ordering::SeqCst), 4);
+
+    count.store(0, Ordering::SeqCst);
+
+    engine.run("let x = iter(0..1000000).map(|v| next(v)).filter(|v| v > 0);").unwrap();
+    assert_eq!(count.load(Ordering::SeqCst), 0);
+}
+
+#[test]
+#[cfg(not(feature = "unchecked"))]
+fn test_iterators_limits() {
+    let mut engine = Engine::new();
+
+    engine.set_max_operations(500);
+
+    assert!(matches!(engine.run("for x in generator(|i| i).filter(|v| false) {}").unwrap_err().unwrap_inner(), EvalAltResult::ErrorTooManyOperations(..)));
+    assert!(matches!(engine.run("for x in generator(|i| i).skip(1000000) {}").unwrap_err().unwrap_inner(), EvalAltResult::ErrorTooManyOperations(..)));
+    assert!(matches!(engine.run("for x in generator(|i| i) {}").unwrap_err().unwrap_inner(), EvalAltResult::ErrorTooManyOperations(..)));
+}
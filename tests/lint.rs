@@ -0,0 +1,178 @@
+#![cfg(feature = "internals")]
+use rhai::lint::{LintContext, LintRule, LintWarning, Linter};
+use rhai::{ASTNode, Engine, Expr, Position};
+
+fn lint(script: &str) -> Vec<LintWarning> {
+    Linter::new().lint(&Engine::new(), script).unwrap()
+}
+
+fn rules(warnings: &[LintWarning]) -> Vec<(&str, Option<usize>)> {
+    warnings.iter().map(|w| (w.rule.as_str(), w.position.line())).collect()
+}
+
+#[test]
+fn test_lint_unused() {
+    assert!(lint("let x = 42; x + 1").is_empty());
+    assert!(lint("let _x = 42;").is_empty());
+
+    let warnings = lint("let x = 42;\nlet y = 1;\ny");
+    assert_eq!(rules(&warnings), [("unused_variable", Some(1))]);
+    assert_eq!(warnings[0].message, "Unused variable: x");
+    assert_eq!(warnings[0].position, Position::new(1, 5));
+
+    // Use before re-declaration
+    assert_eq!(rules(&lint("let x = 1;\nlet x = x + 1;\n")), [("unused_variable", Some(2)), ("shadowed_variable", Some(2))]);
+
+    // Used within a nested block
+    assert!(lint("let x = 1; if true { print(x); }").is_empty());
+
+    // `eval` may use any variable in scope
+    assert!(lint(r#"let x = 1; eval("x");"#).is_empty());
+    assert!(lint(r#"let x = 1; if true { print(eval("x")); }"#).is_empty());
+    assert_eq!(rules(&lint("eval(\"1\");\nlet x = 1;")), [("unused_variable", Some(2))]);
+
+    // Used in arguments to method calls
+    #[cfg(not(feature = "no_object"))]
+    {
+        assert!(lint("let a = []; let v = 1; a.push(v);").is_empty());
+        assert!(lint("let a = []; let v = 1; a.push(v.abs());").is_empty());
+        #[cfg(not(feature = "no_function"))]
+        assert!(lint("let f = || 1; let x = 1; f.call(x);").is_empty());
+        #[cfg(not(feature = "no_function"))]
+        assert!(lint("let f = || 1; let x = 1; f.call(x + 1);").is_empty());
+        assert_eq!(rules(&lint("let a = [];\nlet v = 1;\na.push(1);")), [("unused_variable", Some(2))]);
+    }
+
+    #[cfg(not(feature = "no_index"))]
+    assert_eq!(rules(&lint("let total = 0;\nfor (x, i) in [1, 2, 3] { total += x; }\ntotal")), [("unused_variable", Some(2))]);
+
+    // Destructured variables
+    #[cfg(not(feature = "no_index"))]
+    {
+        assert!(lint("let [a, _b] = [1, 2]; a").is_empty());
+        let warnings = lint("let [a, b] = [1, 2];\na");
+        assert_eq!(rules(&warnings), [("unused_variable", Some(1))]);
+        assert_eq!(warnings[0].message, "Unused variable: b");
+    }
+}
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+fn test_lint_unused_params() {
+    assert!(lint("fn foo(x, _y) { x }").is_empty());
+
+    let warnings = lint("fn foo(x, y) {\n  x\n}");
+    assert_eq!(rules(&warnings), [("unused_parameter", Some(1))]);
+    assert_eq!(warnings[0].message, "Unused parameter: y in function foo");
+    assert_eq!(warnings[0].position, Position::new(1, 11));
+    assert!(lint(r#"fn foo(x) { eval("x") }"#).is_empty());
+
+    // Used in arguments to method calls
+    #[cfg(not(feature = "no_object"))]
+    {
+        assert!(lint("fn f(a, b) { a.push(b); a }").is_empty());
+        assert!(lint("fn f(g, x) { g.call(x + 1) }").is_empty());
+    }
+
+    // Global constants are visible in functions
+    #[cfg(not(feature = "no_module"))]
+    assert!(lint("const X = 42; fn foo() { global::X }").is_empty());
+
+    // Captured variables
+    #[cfg(not(feature = "no_closure"))]
+    assert!(lint("let x = 42; let f = |y| x + y; call(f, 1)").is_empty());
+}
+
+#[test]
+fn test_lint_shadowing() {
+    assert_eq!(rules(&lint("let x = 1;\nif x > 0 {\n  let x = 2;\n  print(x);\n}")), [("shadowed_variable", Some(3))]);
+    assert_eq!(rules(&lint("for i in 0..10 {\n  let i = 1;\n  print(i);\n}")), [("unused_variable", Some(1)), ("shadowed_variable", Some(2))]);
+    assert_eq!(rules(&lint("try { throw 1; } catch (err) {\n  let err = 0;\n  print(err);\n}")), [("shadowed_variable", Some(2))]);
+
+    // Separate blocks do not shadow each other
+    assert!(lint("{ let x = 1; print(x); } { let x = 2; print(x); }").is_empty());
+
+    #[cfg(not(feature = "no_function"))]
+    assert_eq!(rules(&lint("fn foo(x) {\n  let x = x + 1;\n  x\n}")), [("shadowed_variable", Some(2))]);
+}
+
+#[test]
+fn test_lint_unreachable() {
+    let warnings = lint("let x = 1;\nreturn x;\nprint(x);\nprint(x);");
+    assert_eq!(rules(&warnings), [("unreachable_code", Some(3))]);
+
+    assert_eq!(rules(&lint("while true {\n  break;\n  print(1);\n}")), [("unreachable_code", Some(3))]);
+    assert_eq!(rules(&lint("loop {\n  if true { continue; }\n  throw 42;\n  print(1);\n}")), [("unreachable_code", Some(4))]);
+}
+
+#[test]
+fn test_lint_unknown_function() {
+    let mut engine = Engine::new();
+    engine.register_fn("foo", |x: i64| x * 2);
+
+    let linter = Linter::new();
+
+    assert!(linter.lint(&engine, "print(foo(21) + abs(-1));").unwrap().is_empty());
+
+    let warnings = linter.lint(&engine, "foo(1, 2);\nlet y = bar(42);\ny").unwrap();
+    assert_eq!(rules(&warnings), [("unknown_function", Some(1)), ("unknown_function", Some(2))]);
+    assert_eq!(warnings[0].message, "Function not found: foo (with 2 arguments)");
+    assert_eq!(warnings[1].message, "Function not found: bar (with 1 argument)");
+
+    #[cfg(not(feature = "no_function"))]
+    assert!(linter.lint(&engine, "fn bar(x) { x } bar(42)").unwrap().is_empty());
+}
+
+#[test]
+fn test_lint_constants() {
+    #[cfg(not(feature = "no_index"))]
+    assert_eq!(rules(&lint("const X = [1, 2, 3];\nX[0] = 42;")), [("assignment_to_constant", Some(2))]);
+    #[cfg(not(feature = "no_object"))]
+    assert_eq!(rules(&lint("const X = #{a: 1};\nif true {\n  X.a += 1;\n}")), [("assignment_to_constant", Some(3))]);
+
+    // Re-declared as a variable
+    #[cfg(not(feature = "no_index"))]
+    assert_eq!(rules(&lint("const X = [1];\nlet X = X;\nX[0] = 42;")), [("shadowed_variable", Some(2))]);
+}
+
+#[test]
+fn test_lint_switch() {
+    let warnings = lint("let x = 1;\nswitch x {\n  1 => print(1),\n  1 => print(2),\n  2 if false => print(3),\n  0..10 => print(4),\n  2..5 => print(5),\n  _ => print(6)\n}");
+
+    assert_eq!(rules(&warnings), [("unreachable_switch_case", Some(4)), ("unreachable_switch_case", Some(5)), ("unreachable_switch_case", Some(7))]);
+
+    assert!(lint("let x = 1;\nswitch x {\n  1 if x > 0 => print(1),\n  1 => print(2),\n  0..10 if x > 0 => print(3),\n  2..5 => print(4)\n}").is_empty());
+}
+
+#[test]
+fn test_lint_custom_rule() {
+    struct NoPrint;
+
+    impl LintRule for NoPrint {
+        fn name(&self) -> &str {
+            "no_print"
+        }
+        fn check_node(&self, context: &mut LintContext, path: &[ASTNode]) {
+            if let Some(ASTNode::Expr(Expr::FnCall(x, pos))) = path.last() {
+                if x.name == "print" {
+                    context.warn("Do not print", *pos);
+                }
+            }
+        }
+    }
+
+    let engine = Engine::new();
+    let mut linter = Linter::new();
+    linter.add_rule(NoPrint).remove_rule("unused_variable");
+
+    assert!(linter.rules().any(|name| name == "no_print"));
+    assert!(!linter.rules().any(|name| name == "unused_variable"));
+
+    let warnings = linter.lint(&engine, "let x = 42;\nprint(x);\nlet y = 0;").unwrap();
+    assert_eq!(rules(&warnings), [("no_print", Some(2))]);
+    assert_eq!(warnings[0].to_string(), "Do not print [no_print] (line 2, position 1)");
+
+    let mut linter = Linter::empty();
+    linter.add_rule(NoPrint);
+    assert_eq!(linter.lint(&engine, "return 1; print(2);").unwrap().len(), 1);
+}
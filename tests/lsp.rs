@@ -0,0 +1,132 @@
+#![cfg(all(feature = "metadata", feature = "internals", not(feature = "no_position")))]
+use serde_json::{json, Value};
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Run `rhai-lsp` with a list of messages and collect all the messages it sends back.
+fn run_lsp(messages: &[Value]) -> Vec<Value> {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rhai-lsp")).stdin(Stdio::piped()).stdout(Stdio::piped()).spawn().unwrap();
+
+    let mut stdin = child.stdin.take().unwrap();
+
+    for message in messages {
+        let body = message.to_string();
+        write!(stdin, "Content-Length: {}\r\n\r\n{}", body.len(), body).unwrap();
+    }
+    drop(stdin);
+
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+
+    let mut output = std::str::from_utf8(&output.stdout).unwrap();
+    let mut replies = Vec::new();
+
+    while let Some((header, rest)) = output.split_once("\r\n\r\n") {
+        let len = header.strip_prefix("Content-Length: ").unwrap().parse::<usize>().unwrap();
+        replies.push(serde_json::from_str(&rest[..len]).unwrap());
+        output = &rest[len..];
+    }
+
+    replies
+}
+
+fn request(id: i64, method: &str, params: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params })
+}
+
+fn notification(method: &str, params: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "method": method, "params": params })
+}
+
+fn open(uri: &str, text: &str) -> Value {
+    notification("textDocument/didOpen", json!({ "textDocument": { "uri": uri, "languageId": "rhai", "version": 1, "text": text } }))
+}
+
+fn at(uri: &str, line: u32, character: u32) -> Value {
+    json!({ "textDocument": { "uri": uri }, "position": { "line": line, "character": character } })
+}
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+fn test_lsp() {
+    const URI: &str = "file:///test.rhai";
+
+    let script = "
+/// Double a number.
+fn double(x) { x * 2 }
+let y = double(21);
+let z = y +;
+abs(y)
+";
+
+    let replies = run_lsp(&[
+        request(1, "initialize", json!({ "capabilities": {} })),
+        notification("initialized", json!({})),
+        open(URI, script),
+        request(2, "textDocument/hover", at(URI, 3, 10)),
+        request(3, "textDocument/definition", at(URI, 3, 10)),
+        request(4, "textDocument/hover", at(URI, 5, 1)),
+        request(5, "textDocument/completion", at(URI, 5, 0)),
+        request(6, "shutdown", Value::Null),
+        notification("exit", Value::Null),
+    ]);
+
+    assert_eq!(replies.len(), 7);
+    assert_eq!(replies[0]["id"], 1);
+    assert_eq!(replies[0]["result"]["capabilities"]["hoverProvider"], true);
+
+    assert_eq!(replies[1]["method"], "textDocument/publishDiagnostics");
+    let diagnostics = replies[1]["params"]["diagnostics"].as_array().unwrap();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0]["range"]["start"], json!({ "line": 4, "character": 11 }));
+
+    let hover = replies[2]["result"]["contents"]["value"].as_str().unwrap();
+    assert!(hover.contains("fn double(x)"));
+    assert!(hover.contains("Double a number."));
+
+    assert_eq!(replies[3]["result"]["uri"], URI);
+    assert_eq!(replies[3]["result"]["range"]["start"], json!({ "line": 2, "character": 3 }));
+
+    let hover = replies[4]["result"]["contents"]["value"].as_str().unwrap();
+    assert!(hover.contains("fn abs(x: "));
+    assert!(hover.contains("Return the absolute value"));
+
+    let items = replies[5]["result"]["items"].as_array().unwrap();
+    assert!(items.iter().any(|item| item["label"] == "double"));
+    assert!(items.iter().any(|item| item["label"] == "y"));
+    assert!(items.iter().any(|item| item["label"] == "abs"));
+    assert!(items.iter().any(|item| item["label"] == "while"));
+
+    assert_eq!(replies[6]["id"], 6);
+    assert_eq!(replies[6]["result"], Value::Null);
+}
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+#[cfg(not(feature = "no_module"))]
+fn test_lsp_import() {
+    let dir = std::env::temp_dir().join("rhai-lsp-test");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("greeting.rhai"), "\nfn hello(name) { `hello, ${name}` }\n").unwrap();
+
+    let uri = format!("file://{}", dir.join("main.rhai").to_string_lossy().replace('\\', "/"));
+    let module_uri = format!("file://{}", dir.join("greeting.rhai").to_string_lossy().replace('\\', "/"));
+
+    let replies = run_lsp(&[
+        request(1, "initialize", json!({ "capabilities": {} })),
+        open(&uri, "import \"greeting\" as g;\ng::hello(\"world\")"),
+        request(2, "textDocument/definition", at(&uri, 0, 10)),
+        request(3, "textDocument/definition", at(&uri, 1, 4)),
+        request(4, "shutdown", Value::Null),
+        notification("exit", Value::Null),
+    ]);
+
+    assert_eq!(replies.len(), 5);
+    assert!(replies[1]["params"]["diagnostics"].as_array().unwrap().is_empty());
+
+    assert_eq!(replies[2]["result"]["uri"].as_str().unwrap().trim_start_matches("file:///"), module_uri.trim_start_matches("file:///"));
+    assert_eq!(replies[2]["result"]["range"]["start"], json!({ "line": 0, "character": 0 }));
+
+    assert_eq!(replies[3]["result"]["uri"].as_str().unwrap().trim_start_matches("file:///"), module_uri.trim_start_matches("file:///"));
+    assert_eq!(replies[3]["result"]["range"]["start"], json!({ "line": 1, "character": 3 }));
+}
@@ -140,3 +140,135 @@ fn test_max_operations_progress() {
         *engine.run("for x in 0..500 {}").unwrap_err(),
         EvalAltResult::ErrorTerminated(x, ..) if x.as_int().unwrap() == 42));
 }
+
+#[test]
+#[cfg(not(feature = "no_time"))]
+fn test_max_execution_time() {
+    use std::time::{Duration, Instant};
+
+    let mut engine = Engine::new();
+    engine.set_max_execution_time(Duration::from_millis(50));
+
+    assert_eq!(engine.max_execution_time(), Duration::from_millis(50));
+
+    engine.run("let x = 0; while x < 20 { x += 1; }").unwrap();
+
+    let start = Instant::now();
+    assert!(matches!(*engine.run("loop {}").unwrap_err(), EvalAltResult::ErrorTimeout(..)));
+    assert!(start.elapsed() >= Duration::from_millis(50));
+
+    // Time-outs cannot be caught
+    assert!(matches!(*engine.run("try { loop {} } catch { 42 }").unwrap_err(), EvalAltResult::ErrorTimeout(..)));
+
+    // Each evaluation run gets its own deadline
+    engine.run("let x = 0; while x < 20 { x += 1; }").unwrap();
+
+    engine.set_max_execution_time(Duration::ZERO);
+
+    assert_eq!(engine.max_execution_time(), Duration::ZERO);
+
+    // A time too long to be represented as a deadline is unlimited
+    engine.set_max_execution_time(Duration::MAX);
+
+    assert_eq!(engine.max_execution_time(), Duration::ZERO);
+    assert_eq!(engine.eval::<INT>("1 + 1").unwrap(), 2);
+
+    #[cfg(not(feature = "no_function"))]
+    {
+        use rhai::{CallFnOptions, Scope};
+
+        let ast = engine.compile("fn foo(x) { while x > 0 { x += 1; } x }").unwrap();
+
+        let options = CallFnOptions::new().with_deadline(Instant::now() + Duration::from_millis(10));
+        assert!(matches!(*engine.call_fn_with_options::<INT>(options, &mut Scope::new(), &ast, "foo", (1 as INT,)).unwrap_err(), EvalAltResult::ErrorTimeout(..)));
+
+        let options = CallFnOptions::new().with_deadline(Instant::now() + Duration::from_secs(10));
+        assert_eq!(engine.call_fn_with_options::<INT>(options, &mut Scope::new(), &ast, "foo", (0 as INT,)).unwrap(), 0);
+    }
+}
+
+#[test]
+fn test_cancellation() {
+    use rhai::Scope;
+
+    let mut engine = Engine::new();
+    let handle = engine.cancellation_handle();
+
+    assert!(!handle.is_cancelled());
+    engine.run("let x = 0; while x < 20 { x += 1; }").unwrap();
+
+    handle.cancel(42 as INT);
+
+    assert!(handle.is_cancelled());
+    assert!(matches!(*engine.run("loop {}").unwrap_err(), EvalAltResult::ErrorTerminated(ref reason, ..) if reason.as_int().unwrap() == 42));
+
+    // Cancellations cannot be caught
+    assert!(matches!(*engine.run("try { loop {} } catch { 42 }").unwrap_err(), EvalAltResult::ErrorTerminated(..)));
+
+    // The handle stays cancelled until it is reset
+    assert!(engine.run("40 + 2").is_err());
+    handle.reset();
+    assert!(handle.reason().is_none());
+    engine.run("40 + 2").unwrap();
+
+    // Cancel in the middle of a block
+    let stop = engine.cancellation_handle();
+    engine.register_fn("stop", move || stop.cancel("stopped"));
+
+    let mut scope = Scope::new();
+    scope.push("x", 0 as INT);
+
+    let err = engine.run_with_scope(&mut scope, "x = 1; let y = 2; { let z = 3; stop(); x = 99; }").unwrap_err();
+    assert!(matches!(*err, EvalAltResult::ErrorTerminated(ref reason, ..) if reason.to_string() == "stopped"));
+
+    assert_eq!(scope.len(), 2);
+    assert_eq!(scope.get_value::<INT>("x").unwrap(), 1);
+    assert_eq!(scope.get_value::<INT>("y").unwrap(), 2);
+
+    handle.reset();
+}
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+fn test_cancellation_call_fn() {
+    use rhai::{CallFnOptions, CancellationHandle, Scope};
+
+    let engine = Engine::new();
+    let ast = engine.compile("fn foo(x) { x + 1 }").unwrap();
+
+    let handle = CancellationHandle::new();
+    handle.cancel(());
+
+    let options = CallFnOptions::new().with_cancellation(handle.clone());
+    assert!(matches!(*engine.call_fn_with_options::<INT>(options, &mut Scope::new(), &ast, "foo", (41 as INT,)).unwrap_err(), EvalAltResult::ErrorTerminated(..)));
+
+    // Other evaluation runs are not affected
+    assert_eq!(engine.call_fn::<INT>(&mut Scope::new(), &ast, "foo", (41 as INT,)).unwrap(), 42);
+
+    handle.reset();
+
+    let options = CallFnOptions::new().with_cancellation(handle);
+    assert_eq!(engine.call_fn_with_options::<INT>(options, &mut Scope::new(), &ast, "foo", (41 as INT,)).unwrap(), 42);
+}
+
+#[test]
+#[cfg(feature = "sync")]
+fn test_cancellation_thread() {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    let mut engine = Engine::new();
+    let handle = engine.cancellation_handle();
+    let engine = Arc::new(engine);
+
+    let worker = {
+        let engine = engine.clone();
+        std::thread::spawn(move || engine.run("loop {}"))
+    };
+
+    std::thread::sleep(Duration::from_millis(20));
+    handle.cancel("shutting down");
+
+    let err = worker.join().unwrap().unwrap_err();
+    assert!(matches!(*err, EvalAltResult::ErrorTerminated(ref reason, ..) if reason.to_string() == "shutting down"));
+}
@@ -1,5 +1,8 @@
 #![cfg(not(feature = "no_optimize"))]
-use rhai::{Engine, FuncRegistration, Module, OptimizationLevel, Scope, INT};
+use rhai::{Engine, FuncRegistration, Module, OptimizationLevel, OptimizerOptions, Scope, INT};
+
+#[cfg(not(feature = "no_function"))]
+use rhai::OptimizationReport;
 
 #[test]
 fn test_optimizer() {
@@ -71,7 +74,7 @@ fn test_optimizer_parse() {
 
     let ast = engine.compile("const DECISION = false; if DECISION { 42 } else { 123 }").unwrap();
 
-    assert_eq!(format!("{ast:?}"), r#"AST { source: None, doc: "", resolver: None, body: [Var(("DECISION" @ 1:7, false @ 1:18, None), CONSTANT, 1:1), Expr(123 @ 1:51)] }"#);
+    assert_eq!(format!("{ast:?}"), r#"AST { source: None, doc: "", resolver: None, body: [Var(("DECISION" @ 1:7, false @ 1:18, None, None), CONSTANT, 1:1), Expr(123 @ 1:51)] }"#);
 
     let ast = engine.compile("if 1 == 2 { 42 }").unwrap();
 
@@ -223,3 +226,103 @@ fn test_optimizer_volatile() {
     // Make sure the call is optimized away
     assert!(!text_ast.contains(r#"name: "foo""#));
 }
+
+#[cfg(not(feature = "no_function"))]
+#[test]
+fn test_optimizer_inline() {
+    let engine = Engine::new();
+
+    let ast = engine
+        .compile(
+            "
+                private fn double(x) { x * 2 }
+                private fn unused() { 0 }
+                private fn fact(n) { if n <= 1 { 1 } else { n * fact(n - 1) } }
+                fn add(a, b) { a + b }
+                let x = 20;
+                double(x) + add(x, fact(1))
+            ",
+        )
+        .unwrap();
+
+    let (ast, mut report) = engine.optimize_ast_with_report(&Scope::new(), ast, OptimizationLevel::Full);
+
+    assert_eq!(report.inlined.iter().map(|(name, ..)| name.as_str()).collect::<Vec<_>>(), ["double", "add"]);
+    #[cfg(not(feature = "no_position"))]
+    assert!(report.inlined.iter().all(|(.., pos)| pos.line() == Some(7)));
+    report.removed.sort_unstable();
+    assert_eq!(report.removed, ["double", "unused"]);
+
+    // Recursive and public functions are kept
+    let mut names = ast.iter_functions().map(|f| f.name).collect::<Vec<_>>();
+    names.sort_unstable();
+    assert_eq!(names, ["add", "fact"]);
+
+    assert_eq!(engine.eval_ast::<INT>(&ast).unwrap(), 61);
+    assert_eq!(engine.call_fn::<INT>(&mut Scope::new(), &ast, "add", (40 as INT, 2 as INT)).unwrap(), 42);
+
+    // Functions may be called by name
+    let ast = engine.compile(r#"private fn foo() { 42 } eval("foo()")"#).unwrap();
+    let (ast, report) = engine.optimize_ast_with_report(&Scope::new(), ast, OptimizationLevel::Full);
+    assert_eq!(report, OptimizationReport::default());
+    assert_eq!(engine.eval_ast::<INT>(&ast).unwrap(), 42);
+
+    let ast = engine.compile(r#"private fn foo() { 42 } call(Fn("foo"))"#).unwrap();
+    let (ast, report) = engine.optimize_ast_with_report(&Scope::new(), ast, OptimizationLevel::Full);
+    assert!(report.removed.is_empty());
+    assert_eq!(engine.eval_ast::<INT>(&ast).unwrap(), 42);
+
+    // Nothing is changed below full optimization
+    let ast = engine.compile("private fn foo() { 42 } foo()").unwrap();
+    let (ast, report) = engine.optimize_ast_with_report(&Scope::new(), ast, OptimizationLevel::Simple);
+    assert_eq!(report, OptimizationReport::default());
+    assert_eq!(ast.iter_functions().count(), 1);
+}
+
+#[test]
+fn test_optimizer_options() {
+    const SCRIPT: &str = "
+        const X = 40;
+        let y = X + 2;
+        if false { y = 0; }
+        switch \"a\" { \"a\" => y, _ => 0 }
+    ";
+
+    let mut engine = Engine::new();
+    let passes = |engine: &Engine, level| {
+        let ast = engine.compile_with_scope(&Scope::new(), SCRIPT).unwrap();
+        let (ast, report) = engine.optimize_ast_with_report(&Scope::new(), ast, level);
+        assert_eq!(engine.eval_ast::<INT>(&ast).unwrap(), 42);
+        report.rewrites.into_iter().fold(OptimizerOptions::empty(), |passes, (pass, ..)| passes | pass)
+    };
+
+    engine.set_optimization_level(OptimizationLevel::None);
+
+    let passes_simple = passes(&engine, OptimizationLevel::Simple);
+    assert!(passes_simple.contains(OptimizerOptions::CONSTANT_PROPAGATION | OptimizerOptions::CONSTANT_FOLDING | OptimizerOptions::DEAD_CODE_REMOVAL | OptimizerOptions::SWITCH_PRUNING));
+    assert!(!passes_simple.contains(OptimizerOptions::EAGER_EVALUATION));
+    assert!(passes(&engine, OptimizationLevel::Full).contains(OptimizerOptions::EAGER_EVALUATION));
+    assert!(passes(&engine, OptimizationLevel::None).is_empty());
+
+    // Disable passes one at a time
+    for pass in OptimizerOptions::all().iter() {
+        engine.set_optimizer_options(OptimizerOptions::all() - pass);
+        assert!(!passes(&engine, OptimizationLevel::Simple).intersects(pass));
+        assert!(!passes(&engine, OptimizationLevel::Full).intersects(pass));
+    }
+
+    // Disabling all passes leaves the script intact
+    engine.set_optimizer_options(OptimizerOptions::empty());
+    let ast = engine.compile(SCRIPT).unwrap();
+    let (ast2, report) = engine.optimize_ast_with_report(&Scope::new(), ast.clone(), OptimizationLevel::Full);
+    assert!(report.rewrites.is_empty());
+    assert_eq!(format!("{ast:?}"), format!("{ast2:?}"));
+
+    // Rewrites are recorded with their positions
+    #[cfg(not(feature = "no_position"))]
+    {
+        engine.set_optimizer_options(OptimizerOptions::CONSTANT_PROPAGATION);
+        let (.., report) = engine.optimize_ast_with_report(&Scope::new(), ast, OptimizationLevel::Simple);
+        assert_eq!(report.rewrites, [(OptimizerOptions::CONSTANT_PROPAGATION, rhai::Position::new(3, 17))]);
+    }
+}
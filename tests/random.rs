@@ -0,0 +1,118 @@
+use rhai::packages::{Package, RandomPackage};
+use rhai::{Engine, EvalAltResult, INT};
+
+#[cfg(not(feature = "no_index"))]
+use rhai::Array;
+
+fn make_engine() -> Engine {
+    let mut engine = Engine::new();
+    RandomPackage::new().register_into_engine(&mut engine);
+    engine
+}
+
+#[test]
+fn test_random() {
+    let engine = make_engine();
+
+    for _ in 0..100 {
+        let x = engine.eval::<INT>("rand(1..7)").unwrap();
+        assert!((1..7).contains(&x));
+        let x = engine.eval::<INT>("rand(-3..=3)").unwrap();
+        assert!((-3..=3).contains(&x));
+    }
+
+    assert_eq!(engine.eval::<INT>("rand(42..=42)").unwrap(), 42);
+
+    #[cfg(not(feature = "no_float"))]
+    for _ in 0..100 {
+        let x = engine.eval::<rhai::FLOAT>("rand_float()").unwrap();
+        assert!((0.0..1.0).contains(&x));
+    }
+
+    assert!(matches!(
+        *engine.run("rand(5..5)").unwrap_err(),
+        EvalAltResult::ErrorArithmetic(msg, ..) if msg == "Cannot pick a random number from an empty range 5..5"
+    ));
+    assert!(matches!(*engine.run("rand(5..=4)").unwrap_err(), EvalAltResult::ErrorArithmetic(..)));
+}
+
+#[test]
+fn test_random_seed() {
+    let mut engine = make_engine();
+
+    let script = "let x = 0; for i in 0..3 { x = x * 1000 + rand(0..1000); } x";
+
+    engine.set_random_seed(42);
+    let first = engine.eval::<INT>(script).unwrap();
+    engine.set_random_seed(42);
+    let second = engine.eval::<INT>(script).unwrap();
+    engine.set_random_seed(43);
+    let third = engine.eval::<INT>(script).unwrap();
+
+    assert_eq!(first, second);
+    assert_ne!(first, third);
+
+    // Seeding before registering the package is kept
+    let mut engine = Engine::new();
+    engine.set_random_seed(42);
+    RandomPackage::new().register_into_engine(&mut engine);
+
+    assert_eq!(engine.eval::<INT>(script).unwrap(), first);
+
+    // Seeding also applies when the package is registered as a global module
+    let mut engine = Engine::new();
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+    engine.set_random_seed(42);
+
+    assert_eq!(engine.eval::<INT>(script).unwrap(), first);
+
+    // A raw engine without a random number generator raises an error
+    let mut engine = Engine::new_raw();
+    engine.register_global_module(RandomPackage::new().as_shared_module());
+
+    assert!(matches!(*engine.run("rand()").unwrap_err(), EvalAltResult::ErrorSystem(..)));
+}
+
+#[cfg(not(feature = "no_index"))]
+#[test]
+fn test_random_arrays() {
+    let engine = make_engine();
+
+    let mut result = engine
+        .eval::<Array>("let x = [1, 2, 3, 4, 5, 6, 7, 8]; shuffle(x); x")
+        .unwrap()
+        .into_iter()
+        .map(|v| v.as_int().unwrap())
+        .collect::<Vec<_>>();
+    result.sort_unstable();
+    assert_eq!(result, [1, 2, 3, 4, 5, 6, 7, 8]);
+
+    let mut result = engine
+        .eval::<Array>("sample([1, 2, 3, 4, 5, 6, 7, 8], 5)")
+        .unwrap()
+        .into_iter()
+        .map(|v| v.as_int().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(result.len(), 5);
+    result.sort_unstable();
+    result.dedup();
+    assert_eq!(result.len(), 5);
+    assert!(result.iter().all(|x| (1..=8).contains(x)));
+
+    assert_eq!(engine.eval::<INT>("len(sample([1, 2, 3], 10))").unwrap(), 3);
+    assert_eq!(engine.eval::<INT>("len(sample([1, 2, 3], 0))").unwrap(), 0);
+    assert_eq!(engine.eval::<String>("let x = [1, 2, 3]; sample(x, 2); to_string(x)").unwrap(), "[1, 2, 3]");
+
+    assert_eq!(engine.eval::<INT>("len(rand_blob(13))").unwrap(), 13);
+    assert_eq!(engine.eval::<INT>("len(rand_blob(-1))").unwrap(), 0);
+}
+
+#[cfg(not(feature = "no_index"))]
+#[cfg(not(feature = "unchecked"))]
+#[test]
+fn test_random_limits() {
+    let mut engine = make_engine();
+
+    engine.set_max_array_size(10);
+    assert!(matches!(*engine.run("rand_blob(11)").unwrap_err(), EvalAltResult::ErrorDataTooLarge(..)));
+}
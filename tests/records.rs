@@ -0,0 +1,121 @@
+#![cfg(not(feature = "no_object"))]
+#![cfg(not(feature = "no_function"))]
+use rhai::{Engine, EvalAltResult, ParseErrorType, Record, INT};
+
+#[test]
+fn test_records() {
+    let mut engine = Engine::new();
+
+    assert_eq!(engine.eval::<INT>("struct Point { x, y } let p = Point(40, 2); p.x + p.y").unwrap(), 42);
+    assert_eq!(engine.eval::<String>("struct Point { x, y } let p = Point(40, 2); type_of(p)").unwrap(), "Point");
+    assert_eq!(engine.eval::<String>("struct Empty {} type_of(Empty())").unwrap(), "Empty");
+    assert_eq!(engine.eval::<INT>("struct Point { x, y } let p = Point(1, 2); p.x = 40; p.y += 0; p.x + p.y").unwrap(), 42);
+    #[cfg(not(feature = "no_index"))]
+    assert_eq!(engine.eval::<INT>("struct Stack { items } let s = Stack([]); s.items.push(42); s[\"items\"][0]").unwrap(), 42);
+
+    let record = engine.eval::<Record>("struct Point { x, y } Point(40, 2)").unwrap();
+    assert_eq!(record.name(), "Point");
+    assert_eq!(record.get("x").unwrap().as_int().unwrap(), 40);
+    assert!(record.get("z").is_none());
+    assert_eq!(format!("{record:?}"), r#"Point #{"x": 40, "y": 2}"#);
+
+    // Fields cannot be added, and missing fields are always errors
+    engine.set_fail_on_invalid_map_property(false);
+
+    assert!(matches!(
+        *engine.run("struct Point { x, y } let p = Point(1, 2); p.z").unwrap_err(),
+        EvalAltResult::ErrorPropertyNotFound(p, ..) if p == "z"
+    ));
+    assert!(matches!(
+        *engine.run("struct Point { x, y } let p = Point(1, 2); p.z = 42;").unwrap_err(),
+        EvalAltResult::ErrorPropertyNotFound(p, ..) if p == "z"
+    ));
+
+    assert!(matches!(
+        engine.compile("struct Point { x, x }").unwrap_err().err_type(),
+        ParseErrorType::DuplicatedProperty(p) if p == "x"
+    ));
+    assert!(matches!(engine.compile("{ struct Point { x } }").unwrap_err().err_type(), ParseErrorType::WrongTypeDefinition));
+    assert!(matches!(engine.compile("fn foo() { struct Point { x } }").unwrap_err().err_type(), ParseErrorType::WrongTypeDefinition));
+    assert!(matches!(
+        engine.compile("struct Point { x } fn Point(x) { x }").unwrap_err().err_type(),
+        ParseErrorType::FnDuplicatedDefinition(f, 1) if f == "Point"
+    ));
+}
+
+#[test]
+fn test_records_methods() {
+    let engine = Engine::new();
+
+    assert_eq!(
+        engine
+            .eval::<INT>(
+                "
+                    struct Point {
+                        x, y,
+
+                        fn len2() { this.x * this.x + this.y * this.y }
+                        fn shift(dx, dy) { this.x += dx; this.y += dy; }
+                    }
+
+                    let p = Point(2, 3);
+                    p.shift(1, 1);
+                    p.len2()
+                "
+            )
+            .unwrap(),
+        25
+    );
+
+    // Methods are only available to values of the record type
+    assert!(matches!(
+        *engine.run("struct Point { x, fn foo() { 42 } } #{ x: 1 }.foo()").unwrap_err(),
+        EvalAltResult::ErrorFunctionNotFound(f, ..) if f.starts_with("foo")
+    ));
+
+    assert!(engine.compile(r#"struct Point { x, fn "int".foo() { 42 } }"#).is_err());
+}
+
+#[test]
+fn test_records_type_annotations() {
+    let engine = Engine::new();
+
+    assert_eq!(engine.eval::<INT>("struct Point { x: int, y: int } Point(40, 2).x").unwrap(), 40);
+    assert_eq!(engine.eval::<INT>("struct Point { x, y } fn sum(p: Point) -> int { p.x + p.y } sum(Point(40, 2))").unwrap(), 42);
+
+    assert!(matches!(
+        *engine.run(r#"struct Point { x: int, y: int } Point(40, "2")"#).unwrap_err(),
+        EvalAltResult::ErrorMismatchDataType(typ, actual, ..) if typ == std::any::type_name::<INT>() && actual == "string"
+    ));
+    assert!(matches!(
+        *engine.run("struct Point { x, y } struct Size { x, y } fn area(s: Size) { s.x * s.y } area(Point(1, 2))").unwrap_err(),
+        EvalAltResult::ErrorMismatchDataType(typ, actual, ..) if typ == "Size" && actual == "Point"
+    ));
+}
+
+#[test]
+#[cfg(not(feature = "no_module"))]
+fn test_records_modules() {
+    let mut engine = Engine::new();
+
+    let ast = engine.compile("struct Point { x, y, fn len2() { this.x * this.x + this.y * this.y } }").unwrap();
+    let module = rhai::Module::eval_ast_as_new(rhai::Scope::new(), &ast, &engine).unwrap();
+
+    let mut resolver = rhai::module_resolvers::StaticModuleResolver::new();
+    resolver.insert("geometry", module);
+    engine.set_module_resolver(resolver);
+
+    assert_eq!(
+        engine
+            .eval::<String>(
+                r#"
+                    import "geometry" as geo;
+
+                    let p = geo::Point(3, 4);
+                    `${type_of(p)} ${p.len2()}`
+                "#
+            )
+            .unwrap(),
+        "Point 25"
+    );
+}
@@ -0,0 +1,85 @@
+#![cfg(feature = "regex")]
+#![cfg(not(feature = "no_object"))]
+use rhai::packages::{Package, RegexPackage};
+use rhai::{Engine, EvalAltResult};
+
+#[cfg(not(feature = "no_index"))]
+use rhai::Array;
+
+fn make_engine() -> Engine {
+    let mut engine = Engine::new();
+    RegexPackage::new().register_into_engine(&mut engine);
+    engine
+}
+
+#[test]
+fn test_regex() {
+    let engine = make_engine();
+
+    assert!(engine.eval::<bool>(r#""hello, world!".matches("w.r")"#).unwrap());
+    assert!(!engine.eval::<bool>(r#""hello, world!".matches("^w.r")"#).unwrap());
+    assert_eq!(engine.eval::<String>(r#""call 555-1234 or 555-9876".find("\\d{3}-\\d{4}")"#).unwrap(), "555-1234");
+    assert!(engine.eval::<bool>(r#""hello".find("x+") == ()"#).unwrap());
+
+    assert_eq!(engine.eval::<String>(r#""2024-01-15".replace_regex("(\\d+)-(\\d+)-(\\d+)", "$3/$2/$1")"#).unwrap(), "15/01/2024");
+    assert_eq!(engine.eval::<String>(r#""2024-01-15".replace_regex("(?<year>\\d{4})", "[${year}]")"#).unwrap(), "[2024]-01-15");
+    assert_eq!(engine.eval::<String>(r#""a1b22c333".replace_regex("\\d+", "$$")"#).unwrap(), "a$b$c$");
+    assert_eq!(engine.eval::<String>(r#"let x = "hello"; x.replace_regex("z", "!")"#).unwrap(), "hello");
+
+    assert!(matches!(
+        *engine.run(r#""hello".matches("(abc")"#).unwrap_err(),
+        EvalAltResult::ErrorArithmetic(msg, ..) if msg.starts_with("Invalid regular expression '(abc'")
+    ));
+}
+
+#[cfg(not(feature = "no_index"))]
+#[test]
+fn test_regex_arrays() {
+    let engine = make_engine();
+
+    let result = engine.eval::<Array>(r#""call 555-1234 or 555-9876".find_all("\\d{3}-\\d{4}")"#).unwrap();
+    assert_eq!(result.into_iter().map(|v| v.into_string().unwrap()).collect::<Vec<_>>(), ["555-1234", "555-9876"]);
+
+    let result = engine.eval::<Array>(r#""version 1.22 released".captures("(\\d+)\\.(\\d+)(-\\w+)?")"#).unwrap();
+    assert_eq!(result.len(), 4);
+    assert_eq!(result[0].clone().into_string().unwrap(), "1.22");
+    assert_eq!(result[1].clone().into_string().unwrap(), "1");
+    assert_eq!(result[2].clone().into_string().unwrap(), "22");
+    assert!(result[3].is_unit());
+    assert!(engine.eval::<bool>(r#""hello".captures("(\\d+)") == ()"#).unwrap());
+
+    let result = engine.eval::<Array>(r#""one,  two;three".split_regex("[,;]\\s*")"#).unwrap();
+    assert_eq!(result.into_iter().map(|v| v.into_string().unwrap()).collect::<Vec<_>>(), ["one", "two", "three"]);
+}
+
+#[test]
+fn test_regex_maps() {
+    let engine = make_engine();
+
+    assert_eq!(
+        engine
+            .eval::<String>(r#"let m = "from 09:30 to 17:00".named_captures("(?<hour>\\d+):(?<minute>\\d+)(?<second>:\\d+)?"); `${m.hour}-${m.minute}-${type_of(m.second)}`"#)
+            .unwrap(),
+        "09-30-()"
+    );
+    assert!(engine.eval::<bool>(r#""hello".named_captures("(?<n>\\d+)") == ()"#).unwrap());
+}
+
+#[cfg(not(feature = "unchecked"))]
+#[test]
+fn test_regex_limits() {
+    let mut engine = make_engine();
+
+    assert!(engine.eval::<bool>(r#""hello".matches("\\w{100}")"#).is_ok());
+
+    engine.set_max_memory(10000);
+
+    assert!(engine.eval::<bool>(r#""hello".matches("l+o$")"#).unwrap());
+    assert!(matches!(*engine.run(r#""hello".matches("\\w{1000}")"#).unwrap_err(), EvalAltResult::ErrorDataTooLarge(..)));
+
+    engine.set_max_memory(0);
+    engine.set_max_expr_depths(10, 10);
+
+    assert!(engine.eval::<bool>(r#""hello".matches("((l))")"#).unwrap());
+    assert!(matches!(*engine.run(r#""hello".matches("((((((((((((l))))))))))))")"#).unwrap_err(), EvalAltResult::ErrorArithmetic(..)));
+}
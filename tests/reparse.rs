@@ -0,0 +1,93 @@
+#![cfg(not(feature = "no_position"))]
+use rhai::{Engine, ParseErrorType, Position, AST, INT};
+
+/// Replace the text within a range of a script, returning the new script.
+fn edit(script: &str, range: std::ops::Range<Position>, text: &str) -> String {
+    let offset = |pos: Position| {
+        let line = script.split_inclusive('\n').take(pos.line().unwrap() - 1).map(str::len).sum::<usize>();
+        line + script[line..].chars().take(pos.position().unwrap() - 1).map(char::len_utf8).sum::<usize>()
+    };
+    format!("{}{}{}", &script[..offset(range.start)], text, &script[offset(range.end)..])
+}
+
+fn reparse(engine: &Engine, ast: &AST, script: &str, range: std::ops::Range<Position>, text: &str) -> (String, AST) {
+    let script = edit(script, range.clone(), text);
+    let ast = engine.reparse(ast, &script, range, text).unwrap();
+    let expected = engine.compile(&script).unwrap();
+
+    assert_eq!(format!("{:?}", ast), format!("{:?}", expected));
+
+    (script, ast)
+}
+
+#[test]
+fn test_reparse() {
+    let engine = Engine::new();
+
+    let script = "let x = 40;\nlet y = 1;\nx + y";
+    let ast = engine.compile(script).unwrap();
+
+    // Replace a number
+    let (script, ast) = reparse(&engine, &ast, script, Position::new(2, 9)..Position::new(2, 10), "2");
+    assert_eq!(engine.eval_ast::<INT>(&ast).unwrap(), 42);
+
+    // Insert lines, shifting the positions of the statements after the edit
+    let (script, ast) = reparse(&engine, &ast, &script, Position::new(1, 12)..Position::new(1, 12), "\nlet z = 0;\nz += 1;");
+    assert_eq!(engine.eval_ast::<INT>(&ast).unwrap(), 42);
+
+    // Rename a variable used by later statements
+    let (script, ast) = reparse(&engine, &ast, &script, Position::new(1, 5)..Position::new(1, 6), "w");
+    assert!(engine.eval_ast::<INT>(&ast).is_err());
+
+    // Turn a variable into a constant that is propagated into later statements
+    let (script, ast) = reparse(&engine, &ast, &script, Position::new(1, 1)..Position::new(1, 6), "const x");
+    assert_eq!(engine.eval_ast::<INT>(&ast).unwrap(), 42);
+
+    // Comment out statements
+    let (script, ast) = reparse(&engine, &ast, &script, Position::new(2, 1)..Position::new(2, 1), "/*");
+    let (.., ast) = reparse(&engine, &ast, &script, Position::new(3, 8)..Position::new(3, 8), "*/");
+    assert_eq!(engine.eval_ast::<INT>(&ast).unwrap(), 42);
+}
+
+#[test]
+fn test_reparse_errors() {
+    let engine = Engine::new();
+
+    let script = "let x = 40;\nx + 2";
+    let ast = engine.compile(script).unwrap();
+
+    let err = engine.reparse(&ast, "let x = 40;\nx + ", Position::new(2, 5)..Position::new(2, 6), "").unwrap_err();
+    assert_eq!(*err.0, ParseErrorType::UnexpectedEOF);
+
+    // The edit does not match the script
+    let ast = engine.reparse(&ast, "let x = 40;\nx + 2", Position::new(2, 5)..Position::new(2, 6), "3").unwrap();
+    assert_eq!(engine.eval_ast::<INT>(&ast).unwrap(), 42);
+
+    // Uncomment statements
+    let ast = engine.compile("let x = 40;\n/* x = 1; */\nx + 2").unwrap();
+    let script = "let x = 40;\n  x = 1; */\nx + 2";
+    let err = engine.reparse(&ast, script, Position::new(2, 1)..Position::new(2, 3), " ").unwrap_err();
+    assert_eq!(err, engine.compile(script).unwrap_err());
+}
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+fn test_reparse_functions() {
+    let engine = Engine::new();
+
+    let script = "fn foo(x) { x + 1 }\nfn bar(x) { x * 2 }\nlet x = 20;\nfoo(bar(x))";
+    let ast = engine.compile(script).unwrap();
+    assert_eq!(engine.eval_ast::<INT>(&ast).unwrap(), 41);
+
+    // Change a function body
+    let (script, ast) = reparse(&engine, &ast, script, Position::new(1, 17)..Position::new(1, 18), "2");
+    assert_eq!(engine.eval_ast::<INT>(&ast).unwrap(), 42);
+
+    // Add a parameter to a function called by later statements
+    let (script, ast) = reparse(&engine, &ast, &script, Position::new(2, 9)..Position::new(2, 9), ", y");
+    assert!(engine.eval_ast::<INT>(&ast).is_err());
+
+    // Shift function definitions after the edit
+    let (.., ast) = reparse(&engine, &ast, &script, Position::new(1, 1)..Position::new(1, 1), "\n\n");
+    assert_eq!(ast.iter_functions().find(|f| f.name == "bar").unwrap().params, ["x", "y"]);
+}
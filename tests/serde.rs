@@ -756,3 +756,69 @@ fn test_serde_scope() {
     assert!(scope.get_value::<bool>("y").unwrap());
     assert_eq!(scope.get_value::<String>("z").unwrap(), "serde::test_serde_scope::TestStruct");
 }
+
+#[test]
+fn test_serde_ast_bytes() {
+    let engine = Engine::new();
+
+    let ast = engine
+        .compile(
+            r#"
+                let x = 40;
+                let s = `x = ${x}`;
+                x += 1;
+                if s != "" { x + 1 } else { 0 }
+            "#,
+        )
+        .unwrap();
+
+    let bytes = ast.to_bytes().unwrap();
+    let ast2 = engine.load_ast(&bytes).unwrap();
+
+    assert_eq!(format!("{ast2:?}"), format!("{ast:?}"));
+    assert_eq!(engine.eval_ast::<INT>(&ast2).unwrap(), 42);
+
+    // Corrupted data
+    assert!(engine.load_ast(&bytes[..bytes.len() - 1]).is_err());
+    assert!(engine.load_ast(b"hello").is_err());
+
+    let mut bad_version = bytes.clone();
+    bad_version[4] += 1;
+    assert!(engine.load_ast(&bad_version).is_err());
+}
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+#[cfg(not(feature = "no_index"))]
+#[cfg(not(feature = "no_object"))]
+fn test_serde_ast_bytes_functions() {
+    let engine = Engine::new();
+
+    let mut ast = engine
+        .compile(
+            r#"
+                fn add(x, y) { x + y }
+                private fn double(x) { x * 2 }
+                fn calc(x) {
+                    switch x {
+                        0 => 1,
+                        1..10 => 2,
+                        _ => double(x)
+                    }
+                }
+                let f = |x| add(x, 1);
+                let m = #{ a: [1, 2, 3], b: 'x' };
+                f.call(calc(21)) + m.a.len
+            "#,
+        )
+        .unwrap();
+
+    ast.set_source("test");
+
+    let ast2 = engine.load_ast(&ast.to_bytes().unwrap()).unwrap();
+
+    assert_eq!(ast2.source(), Some("test"));
+    assert_eq!(ast2.iter_functions().count(), ast.iter_functions().count());
+    assert_eq!(engine.eval_ast::<INT>(&ast2).unwrap(), 46);
+    assert_eq!(engine.call_fn::<INT>(&mut Scope::new(), &ast2, "add", (40 as INT, 2 as INT)).unwrap(), 42);
+}
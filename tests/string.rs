@@ -259,6 +259,84 @@ fn test_string_format() {
     assert_eq!(engine.eval::<String>(r#"let x = [new_ts()]; "foo" + x"#).unwrap(), "foo[!!!TS=42!!!]");
 }
 
+#[test]
+fn test_string_format_fn() {
+    #[derive(Debug, Clone)]
+    struct TestStruct {
+        field: INT,
+    }
+
+    let mut engine = Engine::new();
+
+    engine
+        .register_type_with_name::<TestStruct>("TestStruct")
+        .register_fn("new_ts", || TestStruct { field: 42 })
+        .register_fn("to_string", |ts: &mut TestStruct| format!("TS={}", ts.field))
+        .register_fn("to_debug", |ts: &mut TestStruct| format!("!!!TS={}!!!", ts.field));
+
+    assert_eq!(engine.eval::<String>(r#"format("[{:>8}]", 42)"#).unwrap(), "[      42]");
+    assert_eq!(engine.eval::<String>(r#"format("[{:<6}|{:^7}|{:*>5}]", "ab", "cd", 'x')"#).unwrap(), "[ab    |  cd   |****x]");
+    assert_eq!(engine.eval::<String>(r#"format("{:+} {:05} {:+06}", 1, -42, 7)"#).unwrap(), "+1 -0042 +00007");
+    assert_eq!(engine.eval::<String>(r#"format("{0:x} {0:#X} {0:#012b} {0:o}", 255)"#).unwrap(), "ff 0xFF 0b0011111111 377");
+    assert_eq!(engine.eval::<String>(r#"format("{2}-{1:02}-{0:02}", 5, 1, 2024)"#).unwrap(), "2024-01-05");
+    assert_eq!(engine.eval::<String>(r#"format("{{{}}} }}{{", "x")"#).unwrap(), "{x} }{");
+    assert_eq!(engine.eval::<String>(r#"format("hello")"#).unwrap(), "hello");
+    assert_eq!(engine.eval::<String>(r#"format("{{hello}}")"#).unwrap(), "{hello}");
+    assert!(matches!(*engine.run(r#"format("{}")"#).unwrap_err(), EvalAltResult::ErrorArithmetic(..)));
+    #[cfg(not(feature = "no_index"))]
+    assert_eq!(engine.eval::<String>(r#"format_args("{} {} {} {}", [1, 2, 3, 4])"#).unwrap(), "1 2 3 4");
+    assert_eq!(engine.eval::<String>(r#"format("{:.3}|{:?}", "hello", "hi")"#).unwrap(), r#"hel|"hi""#);
+    assert_eq!(engine.eval::<String>(r#"format("{} {:?} {:>10}", new_ts(), new_ts(), new_ts())"#).unwrap(), "TS=42 !!!TS=42!!!      TS=42");
+
+    #[cfg(not(feature = "no_float"))]
+    {
+        assert_eq!(engine.eval::<String>(r#"format("[{:>8.2}]", 3.14159)"#).unwrap(), "[    3.14]");
+        assert_eq!(engine.eval::<String>(r#"format("{} {:.0} {:+.1}", 1.0, 2.7, 0.25)"#).unwrap(), "1.0 3 +0.2");
+        assert_eq!(engine.eval::<String>(r#"format("{:e} {:.2E}", 1234.5, 0.000123)"#).unwrap(), "1.2345e3 1.23E-4");
+        assert_eq!(engine.eval::<String>("to_string(3.14159, 2)").unwrap(), "3.14");
+        assert_eq!(engine.eval::<String>("to_string(2.5, 0)").unwrap(), "2");
+        assert_eq!(engine.eval::<String>("to_string(-1.0, 3)").unwrap(), "-1.000");
+        assert_eq!(engine.eval::<String>("to_string(1.5, -1)").unwrap(), "2");
+    }
+
+    #[cfg(feature = "decimal")]
+    assert_eq!(engine.eval::<String>("to_string(to_decimal(1) / to_decimal(3), 4)").unwrap(), "0.3333");
+
+    #[cfg(not(feature = "no_index"))]
+    {
+        assert_eq!(engine.eval::<String>(r#"format_args("{:<3}|{:^9}|{:+}|{}", [1, "hello", -3, true])"#).unwrap(), "1  |  hello  |-3|true");
+        assert_eq!(engine.eval::<String>(r#"format("{}", [1, 2, 3])"#).unwrap(), "[1, 2, 3]");
+        assert_eq!(engine.eval::<String>(r#"format("{:?} {0:>11}", [1, "a"])"#).unwrap(), r#"[1, "a"]    [1, "a"]"#);
+    }
+
+    #[cfg(not(feature = "no_object"))]
+    {
+        assert_eq!(engine.eval::<String>(r#"format_named("{name}: {score:>4}", #{ name: "Bob", score: 97 })"#).unwrap(), "Bob:   97");
+        assert_eq!(engine.eval::<String>(r#"format("{:?}", #{ a: 1 })"#).unwrap(), r#"#{"a": 1}"#);
+        assert_eq!(engine.eval::<String>(r#"format("{}", #{ a: 1 })"#).unwrap(), r#"#{"a": 1}"#);
+    }
+
+    for (script, reason) in [
+        (r#"format("{", 1)"#, "unmatched '{'"),
+        (r#"format("}", 1)"#, "unmatched '}'"),
+        (r#"format("{:>>>}", 1)"#, "invalid format specifier '>>>'"),
+        (r#"format("{} {}", 1)"#, "no argument at position 1"),
+        (r#"format("{x}", 1)"#, "no argument named 'x'"),
+        (r#"format("{:x}", "hello")"#, "format type 'x' is not supported for string"),
+    ] {
+        assert!(matches!(
+            *engine.eval::<String>(script).unwrap_err(),
+            EvalAltResult::ErrorArithmetic(ref msg, ..) if msg.ends_with(reason)
+        ));
+    }
+
+    #[cfg(not(feature = "unchecked"))]
+    {
+        engine.set_max_string_size(100);
+        assert!(matches!(*engine.eval::<String>(r#"format("{:1000}", 1)"#).unwrap_err(), EvalAltResult::ErrorDataTooLarge(..)));
+    }
+}
+
 #[test]
 fn test_string_fn() {
     let mut engine = Engine::new();
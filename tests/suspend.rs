@@ -0,0 +1,359 @@
+use rhai::{Continuation, Engine, EvalAltResult, NativeCallContext, Scope, INT};
+use std::sync::{Arc, Mutex};
+
+fn suspended(result: Result<INT, Box<EvalAltResult>>) -> Continuation {
+    let err = result.unwrap_err();
+    assert!(err.is_suspension(), "{}", err);
+    err.into_continuation().unwrap()
+}
+
+#[test]
+fn test_suspend() {
+    let mut engine = Engine::new();
+
+    engine.register_fn("wait", |context: NativeCallContext, x: INT| context.suspend(x));
+
+    let ast = engine.compile("let total = 0;\nfor i in 0..3 {\n    total += wait(i);\n}\ntotal").unwrap();
+
+    let mut continuation = suspended(engine.eval_ast::<INT>(&ast));
+
+    for i in 0..3 {
+        assert_eq!(continuation.num_resumed(), i);
+        assert_eq!(continuation.value().as_int().unwrap(), i as INT);
+        #[cfg(not(feature = "no_position"))]
+        assert_eq!(continuation.position().line(), Some(3));
+        assert_eq!(continuation.scope().get_value::<INT>("i").unwrap(), i as INT);
+
+        match engine.resume::<INT>(&mut Scope::new(), &ast, continuation, 10 as INT) {
+            Ok(result) => {
+                assert_eq!(i, 2);
+                assert_eq!(result, 30);
+                return;
+            }
+            Err(err) => continuation = suspended(Err(err)),
+        }
+    }
+
+    unreachable!();
+}
+
+#[test]
+#[cfg(not(feature = "no_index"))]
+fn test_suspend_for_array() {
+    let mut engine = Engine::new();
+
+    engine.register_fn("wait", |context: NativeCallContext, x: INT| context.suspend(x));
+
+    let ast = engine.compile("let total = 0; for (x, i) in [10, 20, 30] { let v = wait(x); total += v * i; } total").unwrap();
+
+    let mut continuation = suspended(engine.eval_ast::<INT>(&ast));
+
+    for x in [10, 20, 30] {
+        assert_eq!(continuation.value().as_int().unwrap(), x);
+        assert_eq!(continuation.scope().get_value::<INT>("x").unwrap(), x);
+        assert_eq!(continuation.scope().get_value::<INT>("i").unwrap(), x / 10 - 1);
+
+        match engine.resume::<INT>(&mut Scope::new(), &ast, continuation, 1 as INT) {
+            Ok(result) => {
+                assert_eq!(x, 30);
+                assert_eq!(result, 3);
+                return;
+            }
+            Err(err) => continuation = suspended(Err(err)),
+        }
+    }
+
+    unreachable!();
+}
+
+#[test]
+fn test_suspend_side_effects() {
+    let mut engine = Engine::new();
+
+    let log = Arc::new(Mutex::new(Vec::<String>::new()));
+    let log2 = log.clone();
+
+    engine.on_print(move |s| log2.lock().unwrap().push(s.to_string()));
+    engine.register_fn("wait", |context: NativeCallContext, x: INT| context.suspend(x));
+
+    let ast = engine
+        .compile(
+            r#"
+                print("start");
+                let x = 0;
+                while x < 3 {
+                    print(`while ${x}`);
+                    x += wait(x);
+                }
+                if x > 0 {
+                    do {
+                        x -= wait(x);
+                        print(`do ${x}`);
+                    } until x <= 0;
+                }
+                let y = 0;
+                loop {
+                    let v = wait(-1);
+                    if v > 0 { y = v * 2; break; }
+                }
+                print("end");
+                y
+            "#,
+        )
+        .unwrap();
+
+    let mut continuation = suspended(engine.eval_ast::<INT>(&ast));
+    let mut values = vec![1, 1, 1, 1, 2, 0, 21].into_iter();
+
+    let result = loop {
+        let value = values.next().unwrap() as INT;
+
+        match engine.resume::<INT>(&mut Scope::new(), &ast, continuation, value) {
+            Ok(result) => break result,
+            Err(err) => continuation = suspended(Err(err)),
+        }
+    };
+
+    assert_eq!(result, 42);
+    assert_eq!(values.next(), None);
+    assert_eq!(*log.lock().unwrap(), ["start", "while 0", "while 1", "while 2", "do 2", "do 0", "end"]);
+}
+
+#[test]
+fn test_suspend_scope() {
+    let mut engine = Engine::new();
+
+    engine.register_fn("wait", |context: NativeCallContext| context.suspend(()));
+
+    let ast = engine.compile("let x = 40; { let y = wait(); x += y; } x += wait(); x").unwrap();
+
+    let mut scope = Scope::new();
+    scope.push("z", 0 as INT);
+
+    let continuation = suspended(engine.eval_ast_with_scope::<INT>(&mut scope, &ast));
+    assert_eq!(continuation.scope().get_value::<INT>("x").unwrap(), 40);
+    assert_eq!(continuation.scope().get_value::<INT>("z").unwrap(), 0);
+
+    // Variables are restored from the continuation
+    let mut scope = Scope::new();
+    let continuation = suspended(engine.resume::<INT>(&mut scope, &ast, continuation, 1 as INT));
+    assert_eq!(continuation.scope().get_value::<INT>("x").unwrap(), 41);
+    assert!(!continuation.scope().contains("y"));
+
+    assert_eq!(engine.resume::<INT>(&mut scope, &ast, continuation, 1 as INT).unwrap(), 42);
+    assert_eq!(scope.get_value::<INT>("x").unwrap(), 42);
+    assert_eq!(scope.get_value::<INT>("z").unwrap(), 0);
+}
+
+#[test]
+fn test_suspend_uncatchable() {
+    let mut engine = Engine::new();
+
+    engine.register_fn("wait", |context: NativeCallContext| context.suspend(()));
+
+    let ast = engine.compile("let x = 0; try { x = wait(); } catch { x = -1; } x + 1").unwrap();
+
+    let continuation = suspended(engine.eval_ast::<INT>(&ast));
+
+    assert_eq!(engine.resume::<INT>(&mut Scope::new(), &ast, continuation, 41 as INT).unwrap(), 42);
+
+    // Other terminations are not suspensions
+    #[cfg(not(feature = "unchecked"))]
+    {
+        engine.on_progress(|_| Some(().into()));
+        let err = engine.eval_ast::<INT>(&ast).unwrap_err();
+        assert!(!err.is_suspension());
+        assert!(matches!(err.into_continuation(), Err(EvalAltResult::ErrorTerminated(..))));
+    }
+}
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+fn test_suspend_call_stack() {
+    let mut engine = Engine::new();
+
+    engine.register_fn("wait", |context: NativeCallContext, x: INT| context.suspend(x));
+
+    let ast = engine
+        .compile(
+            "
+                fn inner(x) { let y = x + 1; let z = wait(y); z * 2 }
+                fn outer(x) { let r = inner(x); r + 1 }
+                let x = 20;
+                outer(x)
+            ",
+        )
+        .unwrap();
+
+    let continuation = suspended(engine.eval_ast::<INT>(&ast));
+
+    assert_eq!(continuation.value().as_int().unwrap(), 21);
+    assert_eq!(continuation.call_stack().map(|(name, ..)| name).collect::<Vec<_>>(), ["outer", "inner"]);
+    #[cfg(not(feature = "no_position"))]
+    assert_eq!(continuation.call_stack().map(|(.., pos)| pos.line().unwrap()).collect::<Vec<_>>(), [5, 3]);
+
+    let scope = continuation.scope();
+    assert_eq!(scope.len(), 2);
+    assert_eq!(scope.get_value::<INT>("x").unwrap(), 20);
+    assert_eq!(scope.get_value::<INT>("y").unwrap(), 21);
+
+    assert_eq!(engine.resume::<INT>(&mut Scope::new(), &ast, continuation, 20 as INT).unwrap(), 41);
+
+    #[cfg(not(feature = "no_module"))]
+    {
+        let ast = engine
+            .compile("const K = 1; fn foo(x) { x + global::K } let x = 0; for i in 0..2 { let v = wait(i); x += foo(v); } x")
+            .unwrap();
+
+        let continuation = suspended(engine.eval_ast::<INT>(&ast));
+        let continuation = suspended(engine.resume::<INT>(&mut Scope::new(), &ast, continuation, 20 as INT));
+        assert_eq!(engine.resume::<INT>(&mut Scope::new(), &ast, continuation, 20 as INT).unwrap(), 42);
+    }
+}
+
+#[test]
+fn test_suspend_boundary() {
+    let mut engine = Engine::new();
+
+    engine.register_fn("wait", |context: NativeCallContext| context.suspend(()));
+    engine.register_fn("add", |x: INT, y: INT| x + y);
+
+    // Suspending calls must be the whole value of a statement
+    for script in ["let x = wait() + 1;", "let x = add(wait(), 1);", "let x = if true { wait() } else { 0 };", "print(wait());"] {
+        let err = engine.run(script).unwrap_err();
+        assert!(matches!(*err, EvalAltResult::ErrorSystem(..)), "{}", script);
+    }
+
+    #[cfg(not(feature = "no_function"))]
+    for script in ["fn f() { wait() } let x = 1 + f();", "fn f() { let x = wait() * 2; x } f()"] {
+        let err = engine.run(script).unwrap_err();
+        assert!(matches!(*err, EvalAltResult::ErrorSystem(..)), "{}", script);
+    }
+
+    // Script-defined functions called as methods cannot be resumed
+    #[cfg(not(feature = "no_function"))]
+    #[cfg(not(feature = "no_object"))]
+    assert!(matches!(*engine.run("fn f() { wait() } let x = 1; x.f();").unwrap_err(), EvalAltResult::ErrorSystem(..)));
+
+    // Functions of lazy iterators would be called again
+    #[cfg(not(feature = "no_function"))]
+    #[cfg(not(feature = "no_index"))]
+    {
+        let err = engine.run("for x in iter([1, 2, 3]).map(|v| v * 2) { wait(); }").unwrap_err();
+        assert!(matches!(*err, EvalAltResult::ErrorSystem(..)));
+
+        let ast = engine.compile("let t = 0; for x in iter([1, 2, 3]).take(2) { wait(); t += x; } t").unwrap();
+        let continuation = suspended(engine.eval_ast::<INT>(&ast));
+        let continuation = suspended(engine.resume::<INT>(&mut Scope::new(), &ast, continuation, ()));
+        assert_eq!(engine.resume::<INT>(&mut Scope::new(), &ast, continuation, ()).unwrap(), 3);
+    }
+}
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+#[cfg(not(feature = "no_index"))]
+fn test_suspend_no_repeat() {
+    let mut engine = Engine::new();
+
+    let log = Arc::new(Mutex::new(Vec::<String>::new()));
+    let log2 = log.clone();
+
+    engine.on_print(move |s| log2.lock().unwrap().push(s.to_string()));
+    engine.register_fn("wait", |context: NativeCallContext, x: INT| context.suspend(x));
+
+    let ast = engine
+        .compile(
+            r#"
+                fn work(x) {
+                    print(`before ${x}`);
+                    let y = wait(x);
+                    print(`after ${y}`);
+                    return y + 1;
+                }
+                let items = [1, 2];
+                let total = 0;
+                for item in items {
+                    items.push(item);
+                    total += work(item);
+                }
+                total
+            "#,
+        )
+        .unwrap();
+
+    let continuation = suspended(engine.eval_ast::<INT>(&ast));
+    assert_eq!(continuation.value().as_int().unwrap(), 1);
+    let continuation = suspended(engine.resume::<INT>(&mut Scope::new(), &ast, continuation, 10 as INT));
+    assert_eq!(continuation.value().as_int().unwrap(), 2);
+    assert_eq!(engine.resume::<INT>(&mut Scope::new(), &ast, continuation, 30 as INT).unwrap(), 42);
+
+    assert_eq!(*log.lock().unwrap(), ["before 1", "after 10", "before 2", "after 30"]);
+}
+
+#[test]
+fn test_suspend_mismatch() {
+    let mut engine = Engine::new();
+
+    engine.register_fn("wait", |context: NativeCallContext| context.suspend(()));
+
+    let ast = engine.compile("wait(); wait(); 42").unwrap();
+
+    let continuation = suspended(engine.eval_ast::<INT>(&ast));
+    let continuation = suspended(engine.resume::<INT>(&mut Scope::new(), &ast, continuation, ()));
+
+    // Resume with a different script
+    let ast2 = engine.compile("42").unwrap();
+    assert!(matches!(*engine.resume::<INT>(&mut Scope::new(), &ast2, continuation.clone(), ()).unwrap_err(), EvalAltResult::ErrorSystem(..)));
+
+    #[cfg(not(feature = "no_position"))]
+    {
+        let ast2 = engine.compile("wait();\nwait();\n42").unwrap();
+        assert!(matches!(*engine.resume::<INT>(&mut Scope::new(), &ast2, continuation.clone(), ()).unwrap_err(), EvalAltResult::ErrorSystem(..)));
+    }
+
+    assert_eq!(engine.resume::<INT>(&mut Scope::new(), &ast, continuation, ()).unwrap(), 42);
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn test_suspend_serde() {
+    let mut engine = Engine::new();
+
+    engine.register_fn("wait", |context: NativeCallContext, x: INT| context.suspend(x));
+
+    let ast = engine.compile("let x = wait(1); let y = wait(x + 1); x + y").unwrap();
+
+    let continuation = suspended(engine.eval_ast::<INT>(&ast));
+    let continuation = suspended(engine.resume::<INT>(&mut Scope::new(), &ast, continuation, 20 as INT));
+
+    let json = serde_json::to_string(&continuation).unwrap();
+    let continuation: Continuation = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(continuation.value().as_int().unwrap(), 21);
+    assert_eq!(continuation.scope().get_value::<INT>("x").unwrap(), 20);
+    assert_eq!(engine.resume::<INT>(&mut Scope::new(), &ast, continuation, 22 as INT).unwrap(), 42);
+}
+
+#[test]
+#[cfg(not(feature = "no_module"))]
+fn test_suspend_import() {
+    let mut engine = Engine::new();
+
+    let mut module = rhai::Module::new();
+    module.set_var("answer", 41 as INT);
+
+    let mut resolver = rhai::module_resolvers::StaticModuleResolver::new();
+    resolver.insert("hello", module);
+    engine.set_module_resolver(resolver);
+
+    engine.register_fn("wait", |context: NativeCallContext| context.suspend(()));
+
+    let ast = engine
+        .compile(r#"import "hello" as h; let x = h::answer; { import "hello" as h2; x += wait(); } let y = wait(); x + h::answer - y"#)
+        .unwrap();
+
+    let continuation = suspended(engine.eval_ast::<INT>(&ast));
+    let continuation = suspended(engine.resume::<INT>(&mut Scope::new(), &ast, continuation, 1 as INT));
+
+    assert_eq!(engine.resume::<INT>(&mut Scope::new(), &ast, continuation, 41 as INT).unwrap(), 42);
+}
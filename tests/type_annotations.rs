@@ -0,0 +1,89 @@
+use rhai::{Engine, EvalAltResult, ParseErrorType, INT};
+
+#[test]
+fn test_type_annotations_let() {
+    let engine = Engine::new();
+
+    assert_eq!(engine.eval::<INT>("let x: int = 42; x").unwrap(), 42);
+    assert_eq!(engine.eval::<String>(r#"let x: string = "hello"; x"#).unwrap(), "hello");
+    assert!(engine.eval::<bool>("const x: bool = true; x").unwrap());
+    assert_eq!(engine.eval::<char>("let x: char = 'a'; x").unwrap(), 'a');
+    #[cfg(not(feature = "no_index"))]
+    assert_eq!(engine.eval::<INT>("let x: array = [1, 2, 3]; len(x)").unwrap(), 3);
+    #[cfg(not(feature = "no_object"))]
+    assert_eq!(engine.eval::<INT>("let x: map = #{a: 42}; x.a").unwrap(), 42);
+    #[cfg(not(feature = "no_float"))]
+    assert_eq!(engine.eval::<rhai::FLOAT>("let x: float = 1.5; x").unwrap(), 1.5);
+    engine.run("let x: () = ();").unwrap();
+
+    // Assignments are checked
+    assert_eq!(engine.eval::<INT>("let x: int = 42; x = 1; x += 2; x").unwrap(), 3);
+    assert_eq!(engine.eval::<INT>("let x: int = 42; { let x = true; x = false; } x").unwrap(), 42);
+    assert!(engine.eval::<bool>("let x: int = 42; let x = 1; x = true; x").unwrap());
+    assert!(matches!(
+        *engine.run("let x: int = 42; x = true;").unwrap_err(),
+        EvalAltResult::ErrorMismatchDataType(typ, actual, ..) if typ == std::any::type_name::<INT>() && actual == "bool"
+    ));
+    assert!(matches!(
+        *engine.run(r#"let x: int = 42; x += "!";"#).unwrap_err(),
+        EvalAltResult::ErrorMismatchDataType(typ, actual, ..) if typ == std::any::type_name::<INT>() && actual == "string"
+    ));
+    assert!(matches!(*engine.run("let x: int = 42; for i in 0..3 { let x: int = i; x = (); }").unwrap_err(), EvalAltResult::ErrorMismatchDataType(..)));
+
+    assert!(matches!(
+        *engine.run("let x: string = 42;").unwrap_err(),
+        EvalAltResult::ErrorMismatchDataType(typ, ..) if typ == "string"
+    ));
+    assert!(matches!(
+        *engine.run("let x: bool;").unwrap_err(),
+        EvalAltResult::ErrorMismatchDataType(typ, actual, ..) if typ == "bool" && actual == "()"
+    ));
+
+    assert!(matches!(engine.compile("let x: = 42;").unwrap_err().err_type(), ParseErrorType::MissingSymbol(..)));
+}
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+fn test_type_annotations_functions() {
+    let engine = Engine::new();
+
+    assert_eq!(engine.eval::<INT>("fn add(x: int, y: int) -> int { x + y } add(40, 2)").unwrap(), 42);
+    assert_eq!(engine.eval::<String>(r#"fn add(x, y: string) -> string { x + y } add(42, "!")"#).unwrap(), "42!");
+    assert_eq!(engine.eval::<INT>(r#"fn size(x: "string") { len(x) } size("hello")"#).unwrap(), 5);
+    #[cfg(not(feature = "no_object"))]
+    assert_eq!(engine.eval::<INT>("fn \"int\".add(x: int) -> int { this + x } 40.add(2)").unwrap(), 42);
+
+    assert!(matches!(
+        *engine.run(r#"fn add(x: int, y: int) { x + y } add(40, "2")"#).unwrap_err(),
+        EvalAltResult::ErrorMismatchDataType(typ, actual, ..) if typ == std::any::type_name::<INT>() && actual == "string"
+    ));
+    assert!(matches!(
+        *engine.run("fn foo(x) -> string { x } foo(true)").unwrap_err(),
+        EvalAltResult::ErrorMismatchDataType(typ, actual, ..) if typ == "string" && actual == "bool"
+    ));
+    assert!(matches!(
+        *engine.run(r#"fn foo(x: int) { x = "hello"; } foo(42)"#).unwrap_err(),
+        EvalAltResult::ErrorInFunctionCall(.., err, _) if matches!(*err, EvalAltResult::ErrorMismatchDataType(..))
+    ));
+    assert_eq!(engine.eval::<INT>("fn foo(x: int) { x *= 2; x } foo(21)").unwrap(), 42);
+    assert!(matches!(
+        *engine.run("fn foo(x: bool) {} foo(42)").unwrap_err(),
+        EvalAltResult::ErrorMismatchDataType(typ, ..) if typ == "bool"
+    ));
+    assert!(matches!(
+        *engine.run("fn foo() -> bool {} foo()").unwrap_err(),
+        EvalAltResult::ErrorMismatchDataType(typ, actual, ..) if typ == "bool" && actual == "()"
+    ));
+
+    let ast = engine.compile("fn add(x: int, y) -> string { x + y }").unwrap();
+    let f = ast.iter_functions().next().unwrap();
+    assert_eq!(f.params, ["x", "y"]);
+    assert_eq!(f.param_types, [Some(std::any::type_name::<INT>()), None]);
+    assert_eq!(f.return_type, Some("string"));
+    assert_eq!(f.to_string(), format!("add(x: {}, y) -> string", std::any::type_name::<INT>()));
+
+    let ast = engine.compile("fn add(x, y) { x + y }").unwrap();
+    let f = ast.iter_functions().next().unwrap();
+    assert!(f.param_types.is_empty());
+    assert_eq!(f.return_type, None);
+}
@@ -0,0 +1,122 @@
+#![cfg(feature = "metadata")]
+#![cfg(not(feature = "no_object"))]
+#![cfg(not(feature = "only_i32"))]
+use rhai::{Engine, Scope, TypeMismatch, INT};
+
+fn check(engine: &Engine, script: &str) -> Vec<TypeMismatch> {
+    engine.check_types(&engine.compile(script).unwrap())
+}
+
+fn calls(mismatches: &[TypeMismatch]) -> Vec<String> {
+    mismatches.iter().map(|m| format!("{} ({})", m.fn_name, m.arg_types.join(", "))).collect::<Vec<_>>()
+}
+
+#[test]
+fn test_type_check() {
+    let engine = Engine::new();
+
+    assert!(check(&engine, r#"let x = "hello"; x.sub_string(1, 2) + x.len()"#).is_empty());
+
+    // Types of function results
+    let mismatches = check(&engine, "let x = \"hello\";\nlet y = x.len();\ny.sub_string(1)");
+    assert_eq!(calls(&mismatches), ["sub_string (i64, i64)"]);
+    #[cfg(not(feature = "no_position"))]
+    assert_eq!(mismatches[0].position, rhai::Position::new(3, 3));
+
+    // Types of operator results
+    assert_eq!(calls(&check(&engine, "let x = 40 + 2; x.len()")), ["len (i64)"]);
+    assert_eq!(calls(&check(&engine, r#"let x = "a" + 1; x - 1"#)), ["- (string, i64)"]);
+    assert!(check(&engine, r#"let x = 40 + 2; x == "a""#).is_empty());
+
+    // Op-assignments
+    assert_eq!(calls(&check(&engine, r#"let x = "a"; x -= 1;"#)), ["- (string, i64)"]);
+    assert!(check(&engine, r#"let x = "a"; x += 1; x.len()"#).is_empty());
+
+    // Wrong number of arguments
+    assert_eq!(calls(&check(&engine, r#""hello".sub_string()"#)), ["sub_string (string)"]);
+
+    // Loop variables
+    assert!(check(&engine, "for x in 0..10 { x.abs(); }").is_empty());
+    assert_eq!(calls(&check(&engine, "for x in 0..10 { x.len(); }")), ["len (i64)"]);
+}
+
+#[test]
+fn test_type_check_inference() {
+    let engine = Engine::new();
+
+    // Variables assigned values of different types
+    assert!(check(&engine, r#"let x = 42; x = "hello"; x.len()"#).is_empty());
+    assert!(check(&engine, r#"let x = 42; loop { x.len(); x = "hello"; }"#).is_empty());
+    assert!(check(&engine, r#"let x = 42; let y = x; y.len(); x = "hello";"#).is_empty());
+
+    // Shadowed variables
+    assert_eq!(calls(&check(&engine, r#"let x = "hello"; { let x = 42; x.len(); } x.len()"#)), ["len (i64)"]);
+
+    // Variables with type annotations
+    assert_eq!(calls(&check(&engine, "let x: int = foo(); x.len()")), ["len (i64)"]);
+
+    // Unknown types
+    #[cfg(not(feature = "no_index"))]
+    assert!(check(&engine, "let x = [1, 2, 3]; x[0].len()").is_empty());
+    assert!(check(&engine, r#"let x = 42; eval("x = ()"); x.len()"#).is_empty());
+    #[cfg(not(feature = "no_function"))]
+    assert!(check(&engine, "let x = #{ len: || 42 }; x.len()").is_empty());
+    assert!(check(&engine, "let f = Fn(\"abs\"); f.call(42)").is_empty());
+
+    // Variables in scope
+    let mut scope = Scope::new();
+    scope.push("x", 42 as INT);
+    let ast = engine.compile("x.len()").unwrap();
+    assert_eq!(calls(&engine.check_types_with_scope(&scope, &ast)), ["len (i64)"]);
+    assert!(engine.check_types(&ast).is_empty());
+}
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+fn test_type_check_functions() {
+    let engine = Engine::new();
+
+    // Script-defined functions are not checked, and their parameters have unknown types
+    assert!(check(&engine, "fn len(x, y) { x + y } len(1, 2)").is_empty());
+    assert!(check(&engine, "fn foo(x) { x.len() } foo(42)").is_empty());
+
+    let mismatches = check(&engine, "fn foo(x) { let y = 42; y.len() + x }");
+    assert_eq!(calls(&mismatches), ["len (i64)"]);
+
+    // Parameters with type annotations
+    assert_eq!(calls(&check(&engine, "fn foo(x: string) { x.abs() }")), ["abs (string)"]);
+}
+
+#[test]
+fn test_type_check_custom_types() {
+    #[derive(Debug, Clone)]
+    struct TestStruct {
+        x: INT,
+    }
+
+    let mut engine = Engine::new();
+
+    engine
+        .register_type_with_name::<TestStruct>("TestStruct")
+        .register_fn("new_ts", || TestStruct { x: 1 })
+        .register_fn("update", |obj: &mut TestStruct, x: INT| obj.x += x)
+        .register_fn("get_x", |obj: &mut TestStruct| obj.x);
+
+    assert!(check(&engine, "let x = new_ts(); x.update(41); x.get_x() + 1").is_empty());
+
+    let mismatches = check(&engine, "let x = new_ts();\nx.update(\"a\");\nx.get_x().update(1);");
+    assert_eq!(calls(&mismatches), ["update (TestStruct, string)", "update (i64, i64)"]);
+    assert_eq!(
+        mismatches[0].to_string(),
+        if cfg!(feature = "no_position") {
+            "No function matches the argument types: update (TestStruct, string)"
+        } else {
+            "No function matches the argument types: update (TestStruct, string) (line 2, position 3)"
+        }
+    );
+
+    let mut scope = Scope::new();
+    scope.push("x", TestStruct { x: 42 });
+    let ast = engine.compile("x.update(true)").unwrap();
+    assert_eq!(calls(&engine.check_types_with_scope(&scope, &ast)), ["update (TestStruct, bool)"]);
+}